@@ -129,6 +129,7 @@ fn bench_request_processing(c: &mut Criterion) {
             ClientRequest::CallTool(CallToolParams {
                 name: "simple_tool".to_string(),
                 arguments: json!({"input": "test"}),
+                meta: None,
             }),
         ),
         (
@@ -139,6 +140,7 @@ fn bench_request_processing(c: &mut Criterion) {
                     "data": [1, 2, 3, 4, 5, 6, 7, 8, 9, 10],
                     "operation": "average"
                 }),
+                meta: None,
             }),
         ),
         (
@@ -208,11 +210,17 @@ fn bench_response_generation(c: &mut Criterion) {
                                 name: "tool1".to_string(),
                                 description: Some("First tool".to_string()),
                                 input_schema: json!({"type": "object"}),
+                                category: None,
+                                tags: Vec::new(),
+                                output_schema: None,
                             },
                             ToolInfo {
                                 name: "tool2".to_string(),
                                 description: Some("Second tool".to_string()),
                                 input_schema: json!({"type": "object"}),
+                                category: None,
+                                tags: Vec::new(),
+                                output_schema: None,
                             },
                         ],
                         next_cursor: None,
@@ -233,6 +241,7 @@ fn bench_response_generation(c: &mut Criterion) {
                                 .to_string(),
                         }],
                         is_error: false,
+                        meta: None,
                     })
                     .unwrap(),
                 ),
@@ -391,6 +400,7 @@ fn bench_batch_operations(c: &mut Criterion) {
                     "data": format!("Batch item {}", i),
                     "index": i
                 }),
+                meta: None,
             })
         })
         .collect();
@@ -413,6 +423,7 @@ fn bench_batch_operations(c: &mut Criterion) {
                 text: format!("Result for batch item {}: processed successfully", i),
             }],
             is_error: false,
+            meta: None,
         })
         .collect();
 
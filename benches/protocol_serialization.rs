@@ -49,6 +49,7 @@ fn bench_request_serialization(c: &mut Criterion) {
                 "format": "markdown"
             }
         }),
+        meta: None,
     });
 
     group.bench_function("call_tool_request", |b| {
@@ -149,6 +150,9 @@ fn bench_response_serialization(c: &mut Criterion) {
                         "limit": {"type": "number"}
                     }
                 }),
+                category: None,
+                tags: Vec::new(),
+                output_schema: None,
             },
             ToolInfo {
                 name: "analyze".to_string(),
@@ -160,6 +164,9 @@ fn bench_response_serialization(c: &mut Criterion) {
                         "method": {"type": "string"}
                     }
                 }),
+                category: None,
+                tags: Vec::new(),
+                output_schema: None,
             },
             ToolInfo {
                 name: "generate".to_string(),
@@ -171,6 +178,9 @@ fn bench_response_serialization(c: &mut Criterion) {
                         "variables": {"type": "object"}
                     }
                 }),
+                category: None,
+                tags: Vec::new(),
+                output_schema: None,
             },
         ],
         next_cursor: None,
@@ -191,6 +201,7 @@ fn bench_response_serialization(c: &mut Criterion) {
             },
         ],
         is_error: false,
+        meta: None,
     };
 
     group.bench_function("call_tool_response", |b| {
@@ -274,6 +285,7 @@ fn bench_large_messages(c: &mut Criterion) {
     let large_response = CallToolResult {
         content: large_content,
         is_error: false,
+        meta: None,
     };
 
     group.bench_function("large_tool_response_serialize", |b| {
@@ -299,6 +311,9 @@ fn bench_large_messages(c: &mut Criterion) {
                     }
                 }
             }),
+            category: None,
+            tags: Vec::new(),
+            output_schema: None,
         })
         .collect();
 
@@ -65,6 +65,8 @@ impl ResourceHandler for FileSystemResources {
                 contents: vec![Content::Text {
                     text: content.clone(),
                 }],
+                content_hash: None,
+                not_modified: false,
             }),
             None => Err(pmcp::Error::protocol(
                 pmcp::ErrorCode::METHOD_NOT_FOUND,
@@ -126,6 +128,8 @@ impl ResourceHandler for TemplateResources {
                 contents: vec![Content::Text {
                     text: format!("Hello, {}! Welcome to MCP resources.", name),
                 }],
+                content_hash: None,
+                not_modified: false,
             })
         } else if uri.starts_with("template://time/") {
             let timezone = uri.strip_prefix("template://time/").unwrap_or("UTC");
@@ -138,6 +142,8 @@ impl ResourceHandler for TemplateResources {
                         chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
                     ),
                 }],
+                content_hash: None,
+                not_modified: false,
             })
         } else {
             Err(pmcp::Error::protocol(
@@ -118,6 +118,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                             data.len()
                         );
                     },
+                    pmcp::types::Content::Blob { data, mime_type } => {
+                        println!(
+                            "   Binary content: {} (data length: {})",
+                            mime_type,
+                            data.len()
+                        );
+                    },
                 }
             }
         },
@@ -40,24 +40,24 @@ impl PromptHandler for CodeReviewPrompt {
         // System message
         messages.push(PromptMessage {
             role: Role::System,
-            content: pmcp::types::MessageContent::Text {
+            content: vec![pmcp::types::MessageContent::Text {
                 text: format!(
                     "You are an expert {} code reviewer. Focus on {} aspects of the code. \
                      Provide constructive feedback with specific suggestions for improvement.",
                     language, focus
                 ),
-            },
+            }],
         });
 
         // User message with the code
         messages.push(PromptMessage {
             role: Role::User,
-            content: pmcp::types::MessageContent::Text {
+            content: vec![pmcp::types::MessageContent::Text {
                 text: format!(
                     "Please review this {} code:\n\n```{}\n{}\n```",
                     language, language, code
                 ),
-            },
+            }],
         });
 
         Ok(GetPromptResult {
@@ -95,13 +95,13 @@ impl PromptHandler for DataAnalysisPrompt {
         // System message
         messages.push(PromptMessage {
             role: Role::System,
-            content: pmcp::types::MessageContent::Text {
+            content: vec![pmcp::types::MessageContent::Text {
                 text: format!(
                     "You are a data analyst expert. Analyze the provided {} data and \
                      present your findings in {} format. Be precise and data-driven.",
                     data_type, output_format
                 ),
-            },
+            }],
         });
 
         // User message with the data
@@ -113,7 +113,7 @@ impl PromptHandler for DataAnalysisPrompt {
 
         messages.push(PromptMessage {
             role: Role::User,
-            content: pmcp::types::MessageContent::Text { text: user_text },
+            content: vec![pmcp::types::MessageContent::Text { text: user_text }],
         });
 
         Ok(GetPromptResult {
@@ -151,21 +151,21 @@ impl PromptHandler for WritingAssistantPrompt {
         // System message
         messages.push(PromptMessage {
             role: Role::System,
-            content: pmcp::types::MessageContent::Text {
+            content: vec![pmcp::types::MessageContent::Text {
                 text: format!(
                     "You are a skilled writing assistant. Write in a {} style for a {} audience. \
                      The content should be {} in length. Ensure clarity, engagement, and appropriate tone.",
                     style, audience, length
                 ),
-            },
+            }],
         });
 
         // User message
         messages.push(PromptMessage {
             role: Role::User,
-            content: pmcp::types::MessageContent::Text {
+            content: vec![pmcp::types::MessageContent::Text {
                 text: format!("Write about: {}", topic),
-            },
+            }],
         });
 
         Ok(GetPromptResult {
@@ -86,16 +86,19 @@ fn fibonacci(n: u32) -> u32 {
             }
             println!("   Messages:");
             for (i, msg) in result.messages.iter().enumerate() {
-                println!(
-                    "   {}. [{}] {}",
-                    i + 1,
-                    msg.role,
-                    match &msg.content {
-                        pmcp::types::Content::Text { text } => text,
-                        pmcp::types::Content::Image { .. } => "[Image content]",
-                        pmcp::types::Content::Resource { .. } => "[Resource content]",
-                    }
-                );
+                for content in &msg.content {
+                    println!(
+                        "   {}. [{}] {}",
+                        i + 1,
+                        msg.role,
+                        match content {
+                            pmcp::types::Content::Text { text } => text,
+                            pmcp::types::Content::Image { .. } => "[Image content]",
+                            pmcp::types::Content::Blob { .. } => "[Binary content]",
+                            pmcp::types::Content::Resource { .. } => "[Resource content]",
+                        }
+                    );
+                }
             }
         },
         Err(e) => {
@@ -15,8 +15,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         reconnect_delay: std::time::Duration::from_secs(1),
         max_reconnect_delay: std::time::Duration::from_secs(30),
         max_reconnect_attempts: Some(5),
-        ping_interval: Some(std::time::Duration::from_secs(30)),
+        ws_ping_interval: Some(std::time::Duration::from_secs(30)),
         request_timeout: std::time::Duration::from_secs(30),
+        connect_timeout: std::time::Duration::from_secs(10),
+        ..Default::default()
     };
 
     info!("Creating WebSocket transport");
@@ -35,6 +35,7 @@ impl SamplingHandler for MockLLM {
                 .map(|m| match &m.content {
                     Content::Text { text } => text.as_str(),
                     Content::Image { .. } => "[image]",
+                    Content::Blob { .. } => "[binary]",
                     Content::Resource { .. } => "[resource]",
                 })
                 .unwrap_or("empty")
@@ -43,15 +43,15 @@ impl PromptHandler for DatabaseQueryPrompt {
             messages: vec![
                 PromptMessage {
                     role: Role::System,
-                    content: Content::Text {
+                    content: vec![Content::Text {
                         text: format!("You are a database assistant. Execute the following query on database '{}' table '{}':", database, table)
-                    },
+                    }],
                 },
                 PromptMessage {
                     role: Role::User,
-                    content: Content::Text {
+                    content: vec![Content::Text {
                         text: query
-                    },
+                    }],
                 },
             ],
         })
@@ -103,16 +103,16 @@ impl PromptHandler for DeploymentPrompt {
             messages: vec![
                 PromptMessage {
                     role: Role::System,
-                    content: Content::Text {
+                    content: vec![Content::Text {
                         text: "You are a deployment assistant. Generate a deployment configuration based on the following parameters:".to_string()
-                    },
+                    }],
                 },
                 PromptMessage {
                     role: Role::User,
-                    content: Content::Text {
+                    content: vec![Content::Text {
                         text: format!("Deploy service '{}' version '{}' to '{}' environment with configuration: {}",
                             service, version, environment, serde_json::to_string_pretty(&config).unwrap())
-                    },
+                    }],
                 },
             ],
         })
@@ -146,6 +146,8 @@ impl ResourceHandler for FileSystemResourceHandler {
 
         Ok(ReadResourceResult {
             contents: vec![Content::Text { text: content }],
+            content_hash: None,
+            not_modified: false,
         })
     }
 
@@ -21,7 +21,6 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::net::{Ipv4Addr, SocketAddr};
 use std::sync::Arc;
-use tokio::sync::Mutex;
 use tracing::info;
 
 // === Tool Implementations (same as stateful example) ===
@@ -167,8 +166,8 @@ async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
         .build()
         .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?;
 
-    // Wrap server in Arc<Mutex<>> for sharing
-    let server = Arc::new(Mutex::new(server));
+    // Wrap server in Arc for sharing
+    let server = Arc::new(server);
 
     // Configure the HTTP server address (different port from stateful example)
     let addr = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 8081);
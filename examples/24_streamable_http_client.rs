@@ -55,6 +55,8 @@ async fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
         auth_provider: None,
         session_id: None,           // Will be set by stateful server if applicable
         enable_json_response: true, // Use simple JSON responses
+        accept: None,
+        sse_accept: None,
         on_resumption_token: None,
     };
 
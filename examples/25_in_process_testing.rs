@@ -0,0 +1,68 @@
+//! Example: Testing a server in-process
+//!
+//! This example demonstrates:
+//! - Connecting a real client to a real server through memory
+//! - Calling a tool without spawning a server process
+//! - Checking tool results the way an integration test would
+
+use async_trait::async_trait;
+use pmcp::testing::duplex;
+use pmcp::{
+    Client, ClientCapabilities, RequestHandlerExtra, Server, ServerCapabilities, ToolHandler,
+};
+use serde_json::{json, Value};
+
+// Tool under test
+struct WordCountTool;
+
+#[async_trait]
+impl ToolHandler for WordCountTool {
+    async fn handle(&self, args: Value, _extra: RequestHandlerExtra) -> pmcp::Result<Value> {
+        let text = args["text"]
+            .as_str()
+            .ok_or_else(|| pmcp::Error::validation("text field required"))?;
+        Ok(json!({ "words": text.split_whitespace().count() }))
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let server = Server::builder()
+        .name("word-count-server")
+        .version("1.0.0")
+        .capabilities(ServerCapabilities::tools_only())
+        .tool("word_count", WordCountTool)
+        .build()?;
+
+    // Wire the client and server together; no process or socket involved
+    let (client_transport, server_transport) = duplex();
+    let server_task = tokio::spawn(server.run(server_transport));
+
+    let mut client = Client::new(client_transport);
+    let init = client.initialize(ClientCapabilities::default()).await?;
+    println!("Connected to {}", init.server_info.name);
+
+    let tools = client.list_tools(None).await?;
+    for tool in &tools.tools {
+        println!("Found tool: {}", tool.name);
+    }
+
+    let result = client
+        .call_tool(
+            "word_count".to_string(),
+            json!({ "text": "the quick brown fox" }),
+        )
+        .await?;
+    assert!(!result.is_error);
+    println!("Result: {:?}", result.content);
+
+    // Bad arguments come back as an error, just as they would over stdio
+    let error = client
+        .call_tool("word_count".to_string(), json!({}))
+        .await
+        .unwrap_err();
+    println!("Expected error: {}", error);
+
+    server_task.abort();
+    Ok(())
+}
@@ -4,10 +4,12 @@
 //! in web browsers to connect to MCP servers via WebSocket.
 
 use pmcp::client::Client;
+use pmcp::shared::protocol_helpers::try_create_notification;
 use pmcp::shared::wasm_websocket::WasmWebSocketTransport;
-use pmcp::types::{ClientCapabilities, ListToolsResult, CallToolRequest};
+use pmcp::types::{ClientCapabilities, ListToolsResult, CallToolRequest, Notification};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use tokio::sync::mpsc;
 use wasm_bindgen::prelude::*;
 use web_sys::console;
 
@@ -157,6 +159,87 @@ impl WasmClient {
         
         Ok(serde_wasm_bindgen::to_value(&result)?)
     }
+
+    /// Subscribe to updates of a resource
+    ///
+    /// Updates arrive as `notifications/resources/updated` notifications, see
+    /// `on_notification`.
+    #[wasm_bindgen]
+    pub async fn subscribe_resource(&mut self, uri: String) -> Result<(), JsValue> {
+        let client = self.client.as_mut()
+            .ok_or_else(|| JsValue::from_str("Not connected"))?;
+
+        client.subscribe_resource(uri)
+            .await
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Unsubscribe from updates of a resource
+    #[wasm_bindgen]
+    pub async fn unsubscribe_resource(&mut self, uri: String) -> Result<(), JsValue> {
+        let client = self.client.as_mut()
+            .ok_or_else(|| JsValue::from_str("Not connected"))?;
+
+        client.unsubscribe_resource(uri)
+            .await
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Call `callback` with every notification the server sends
+    ///
+    /// The callback receives the notification as a JSON-RPC message object, e.g.
+    /// for a resource update:
+    ///
+    /// ```json
+    /// {
+    ///   "jsonrpc": "2.0",
+    ///   "method": "notifications/resources/updated",
+    ///   "params": { "uri": "file:///config.json" }
+    /// }
+    /// ```
+    ///
+    /// `params` is left out for notifications without parameters, such as
+    /// `notifications/tools/list_changed`. Registering another callback replaces
+    /// this one.
+    #[wasm_bindgen]
+    pub fn on_notification(&mut self, callback: js_sys::Function) -> Result<(), JsValue> {
+        let client = self.client.as_ref()
+            .ok_or_else(|| JsValue::from_str("Not connected"))?;
+
+        // The client reads from the WebSocket while the receiver is alive
+        let notifications = client.notifications();
+        wasm_bindgen_futures::spawn_local(forward_notifications(notifications, callback));
+        Ok(())
+    }
+}
+
+/// Pass each notification received to `callback` until the client replaces or
+/// drops the receiver
+async fn forward_notifications(
+    mut notifications: mpsc::Receiver<Notification>,
+    callback: js_sys::Function,
+) {
+    while let Some(notification) = notifications.recv().await {
+        let message = match notification_to_js(notification) {
+            Ok(message) => message,
+            Err(e) => {
+                console::error_2(&"Invalid notification:".into(), &e);
+                continue;
+            }
+        };
+        if let Err(e) = callback.call1(&JsValue::NULL, &message) {
+            console::error_2(&"Notification callback failed:".into(), &e);
+        }
+    }
+}
+
+/// Convert a notification to its JSON-RPC message object
+fn notification_to_js(notification: Notification) -> Result<JsValue, JsValue> {
+    let message = try_create_notification(notification)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    // Plain objects rather than `Map`s, so callbacks can use `message.params.uri`
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    Ok(message.serialize(&serializer)?)
 }
 
 /// Initialize logging for WASM
@@ -190,4 +273,38 @@ pub fn stringify_json(value: JsValue) -> Result<String, JsValue> {
     let json_value: Value = serde_wasm_bindgen::from_value(value)?;
     serde_json::to_string_pretty(&json_value)
         .map_err(|e| JsValue::from_str(&e.to_string()))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pmcp::types::{ResourceUpdatedParams, ServerNotification};
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn test_resource_update_reaches_callback() {
+        let (tx, rx) = mpsc::channel(1);
+        let received = js_sys::Array::new();
+        let callback = js_sys::Function::new_with_args("message", "this.push(message)")
+            .bind(&received);
+
+        // A simulated update for a subscribed resource
+        tx.send(Notification::Server(ServerNotification::ResourceUpdated(
+            ResourceUpdatedParams::new("file:///config.json"),
+        )))
+        .await
+        .unwrap();
+        drop(tx);
+        forward_notifications(rx, callback).await;
+
+        assert_eq!(received.length(), 1);
+        let message = received.get(0);
+        let method = js_sys::Reflect::get(&message, &"method".into()).unwrap();
+        assert_eq!(method.as_string().unwrap(), "notifications/resources/updated");
+        let params = js_sys::Reflect::get(&message, &"params".into()).unwrap();
+        let uri = js_sys::Reflect::get(&params, &"uri".into()).unwrap();
+        assert_eq!(uri.as_string().unwrap(), "file:///config.json");
+    }
+}
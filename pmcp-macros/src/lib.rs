@@ -55,6 +55,7 @@
 use proc_macro::TokenStream;
 use syn::{parse_macro_input, ItemFn, ItemImpl};
 
+mod resource;
 mod tool;
 mod tool_router;
 mod utils;
@@ -65,7 +66,8 @@ mod utils;
 ///
 /// - `name` - Optional tool name (defaults to function name)
 /// - `description` - Tool description (required)
-/// - `annotations` - Additional metadata for the tool
+/// - `annotations` - Additional metadata for the tool; `category` and comma-separated
+///   `tags` are surfaced in `tools/list`
 ///
 /// # Examples
 ///
@@ -82,7 +84,7 @@ mod utils;
 /// #[tool(
 ///     name = "math_add",
 ///     description = "Add two numbers",
-///     annotations(category = "math", complexity = "simple")
+///     annotations(category = "math", tags = "arithmetic, basic")
 /// )]
 /// async fn add(a: i32, b: i32) -> Result<i32, String> {
 ///     Ok(a + b)
@@ -153,6 +155,25 @@ pub fn prompt(_args: TokenStream, input: TokenStream) -> TokenStream {
 
 /// Defines a resource handler with URI pattern matching.
 ///
+/// Generates a `<Name>ResourceHandler` serving every URI that matches the template.
+/// Template variables are bound to the parameters of the same name, parsed with
+/// `FromStr`; `Option` parameters may be absent from the URI. URIs that do not match
+/// fail with `Error::resource_not_found`. The template is listed by
+/// `resources/templates/list`.
+///
+/// The function may return a `ReadResourceResult`, or any `Display` value used as the
+/// resource text, optionally wrapped in a `Result`.
+///
+/// On a method taking `&self`, a `<method>_resource_handler` method is added instead,
+/// building the handler from an `Arc` of the receiver.
+///
+/// # Attributes
+///
+/// - `uri_template` - RFC 6570 URI template (required)
+/// - `name` - Optional template name (defaults to function name)
+/// - `description` - Optional template description
+/// - `mime_type` - Optional MIME type of the resources
+///
 /// # Examples
 ///
 /// ```rust,ignore
@@ -160,12 +181,34 @@ pub fn prompt(_args: TokenStream, input: TokenStream) -> TokenStream {
 ///     uri_template = "file:///{path}",
 ///     mime_type = "text/plain"
 /// )]
-/// async fn read_file(&self, path: String) -> Result<String, Error> {
-///     std::fs::read_to_string(path).map_err(|e| e.into())
+/// async fn read_file(path: String) -> Result<String, std::io::Error> {
+///     std::fs::read_to_string(path)
 /// }
+///
+/// let server = Server::builder()
+///     .resources(ReadFileResourceHandler::new())
+///     .build()?;
+/// ```
+///
+/// On a method:
+///
+/// ```rust,ignore
+/// impl Library {
+///     #[resource(uri_template = "docs://{name}")]
+///     async fn read_doc(&self, name: String) -> pmcp::Result<String> {
+///         self.load(&name).await
+///     }
+/// }
+///
+/// let server = Server::builder()
+///     .resources(Arc::new(library).read_doc_resource_handler())
+///     .build()?;
 /// ```
 #[proc_macro_attribute]
-pub fn resource(_args: TokenStream, input: TokenStream) -> TokenStream {
-    // Resource macro implementation deferred to future release
-    input
+pub fn resource(args: TokenStream, input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as ItemFn);
+
+    resource::expand_resource(args.into(), input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
 }
@@ -0,0 +1,537 @@
+//! Resource macro implementation
+//!
+//! This module implements the `#[resource]` attribute macro, which turns a function
+//! reading one resource into a `ResourceHandler` serving every URI matching a URI
+//! template.
+
+use crate::utils::{extract_option_inner, to_pascal_case};
+use darling::FromMeta;
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::parse::Parser;
+use syn::{FnArg, GenericArgument, ItemFn, Pat, PatType, PathArguments, ReturnType, Type};
+
+/// Resource macro arguments
+#[derive(Debug, FromMeta)]
+struct ResourceArgs {
+    /// RFC 6570 URI template of the resources, e.g. `file:///{path}`
+    uri_template: String,
+
+    /// Template name (defaults to function name)
+    #[darling(default)]
+    name: Option<String>,
+
+    /// Template description
+    #[darling(default)]
+    description: Option<String>,
+
+    /// MIME type of the resources
+    #[darling(default)]
+    mime_type: Option<String>,
+}
+
+/// Expands the #[resource] attribute macro
+pub fn expand_resource(args: TokenStream, input: ItemFn) -> syn::Result<TokenStream> {
+    let parser =
+        syn::punctuated::Punctuated::<darling::ast::NestedMeta, syn::Token![,]>::parse_terminated;
+    let nested_metas: Vec<_> = parser.parse2(args)?.into_iter().collect();
+    let args = ResourceArgs::from_list(&nested_metas)
+        .map_err(|e| syn::Error::new_spanned(&input.sig.ident, e.to_string()))?;
+
+    let fn_name = &input.sig.ident;
+    let receiver = match input.sig.receiver() {
+        Some(receiver) if receiver.reference.is_none() || receiver.mutability.is_some() => {
+            return Err(syn::Error::new_spanned(
+                receiver,
+                "#[resource] methods must take `&self`",
+            ));
+        },
+        receiver => receiver.is_some(),
+    };
+
+    let variables = template_variables(&args.uri_template);
+    if let Some(invalid) = variables.iter().find(|var| !is_variable_name(var)) {
+        return Err(syn::Error::new_spanned(
+            &input.sig.ident,
+            format!(
+                "invalid variable `{}` in URI template \"{}\"",
+                invalid, args.uri_template
+            ),
+        ));
+    }
+    let params = extract_parameters(&input)?;
+    for param in &params {
+        if !variables.contains(&param.name.to_string()) {
+            return Err(syn::Error::new_spanned(
+                &param.name,
+                format!(
+                    "parameter `{}` is not a variable of the URI template \"{}\"",
+                    param.name, args.uri_template
+                ),
+            ));
+        }
+    }
+
+    let uri_template = &args.uri_template;
+    let template_name = args.name.unwrap_or_else(|| fn_name.to_string());
+    let description = optional_string(args.description.as_deref());
+    let mime_type = optional_string(args.mime_type.as_deref());
+
+    let await_token = if input.sig.asyncness.is_some() {
+        quote!(.await)
+    } else {
+        quote!()
+    };
+    let param_extraction = params.iter().map(generate_param_extraction);
+    let param_names = params.iter().map(|p| &p.name);
+    let result_conversion = generate_result_conversion(&input.sig.output, &mime_type);
+    // Without parameters the variables are not needed
+    let vars = if params.is_empty() {
+        quote!(_)
+    } else {
+        quote!(mut vars)
+    };
+    let template = quote! {
+        pmcp::types::ResourceTemplate {
+            uri_template: #uri_template.to_string(),
+            name: #template_name.to_string(),
+            description: #description,
+            mime_type: #mime_type,
+        }
+    };
+    let parse_template = quote! {
+        pmcp::UriTemplate::new(#uri_template).expect("URI template checked by #[resource]")
+    };
+    let list = |template_expr: TokenStream| {
+        quote! {
+            async fn list(
+                &self,
+                _cursor: Option<String>,
+                _extra: pmcp::RequestHandlerExtra,
+            ) -> pmcp::Result<pmcp::types::ListResourcesResult> {
+                Ok(pmcp::types::ListResourcesResult {
+                    resources: Vec::new(),
+                    next_cursor: None,
+                })
+            }
+
+            async fn list_templates(
+                &self,
+                _cursor: Option<String>,
+                _extra: pmcp::RequestHandlerExtra,
+            ) -> pmcp::Result<pmcp::types::ListResourceTemplatesResult> {
+                Ok(pmcp::types::ListResourceTemplatesResult {
+                    resource_templates: vec![#template_expr],
+                    next_cursor: None,
+                })
+            }
+        }
+    };
+
+    if receiver {
+        // An attribute on a method can only expand to items of its impl block, so the
+        // handler is a local type built by a method taking the shared receiver
+        let list_methods = list(quote!(template()));
+        let handler_fn = Ident::new(&format!("{}_resource_handler", fn_name), fn_name.span());
+        return Ok(quote! {
+            #input
+
+            #[doc = concat!("Resource handler reading `", #uri_template, "` with [`Self::", stringify!(#fn_name), "`]")]
+            pub fn #handler_fn(self: ::std::sync::Arc<Self>) -> impl pmcp::ResourceHandler {
+                type ReadFuture = ::std::pin::Pin<Box<
+                    dyn ::std::future::Future<Output = pmcp::Result<pmcp::types::ReadResourceResult>>
+                        + Send,
+                >>;
+
+                fn template() -> pmcp::types::ResourceTemplate {
+                    #template
+                }
+
+                struct Handler {
+                    template: pmcp::UriTemplate,
+                    read: Box<
+                        dyn Fn(String, ::std::collections::HashMap<String, String>) -> ReadFuture
+                            + Send
+                            + Sync,
+                    >,
+                }
+
+                #[pmcp::async_trait]
+                impl pmcp::ResourceHandler for Handler {
+                    async fn read(
+                        &self,
+                        uri: &str,
+                        _extra: pmcp::RequestHandlerExtra,
+                    ) -> pmcp::Result<pmcp::types::ReadResourceResult> {
+                        let vars = self
+                            .template
+                            .match_uri(uri)
+                            .ok_or_else(|| pmcp::Error::resource_not_found(uri))?;
+                        (self.read)(uri.to_string(), vars).await
+                    }
+
+                    #list_methods
+                }
+
+                Handler {
+                    template: #parse_template,
+                    read: Box::new(move |uri, #vars| {
+                        let receiver = ::std::sync::Arc::clone(&self);
+                        Box::pin(async move {
+                            // Bind template variables to parameters
+                            #(#param_extraction)*
+
+                            // Call the original method
+                            let result = receiver.#fn_name(#(#param_names),*)#await_token;
+
+                            // Convert result to resource contents
+                            #result_conversion
+                        })
+                    }),
+                }
+            }
+        });
+    }
+
+    let wrapper_name = Ident::new(
+        &format!("{}ResourceHandler", to_pascal_case(&fn_name.to_string())),
+        fn_name.span(),
+    );
+    let list_methods = list(quote!(Self::template()));
+    Ok(quote! {
+        #input
+
+        #[doc = concat!("Auto-generated resource handler for `", #uri_template, "`")]
+        #[derive(Debug, Clone)]
+        pub struct #wrapper_name {
+            template: pmcp::UriTemplate,
+        }
+
+        impl #wrapper_name {
+            /// URI template of the resources this handler reads
+            pub const URI_TEMPLATE: &'static str = #uri_template;
+
+            /// Create the handler, parsing its URI template
+            pub fn new() -> Self {
+                Self {
+                    template: #parse_template,
+                }
+            }
+
+            /// Get resource template definition
+            pub fn template() -> pmcp::types::ResourceTemplate {
+                #template
+            }
+        }
+
+        impl Default for #wrapper_name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        #[pmcp::async_trait]
+        impl pmcp::ResourceHandler for #wrapper_name {
+            async fn read(
+                &self,
+                uri: &str,
+                _extra: pmcp::RequestHandlerExtra,
+            ) -> pmcp::Result<pmcp::types::ReadResourceResult> {
+                let #vars = self
+                    .template
+                    .match_uri(uri)
+                    .ok_or_else(|| pmcp::Error::resource_not_found(uri))?;
+
+                // Bind template variables to parameters
+                #(#param_extraction)*
+
+                // Call the original function
+                let result = #fn_name(#(#param_names),*)#await_token;
+
+                // Convert result to resource contents
+                #result_conversion
+            }
+
+            #list_methods
+        }
+    })
+}
+
+/// Parameter information
+struct ParamInfo {
+    name: Ident,
+    ty: Type,
+}
+
+/// Extract parameters from function signature
+fn extract_parameters(func: &ItemFn) -> syn::Result<Vec<ParamInfo>> {
+    func.sig
+        .inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            FnArg::Typed(pat_type) => Some(pat_type),
+            FnArg::Receiver(_) => None,
+        })
+        .map(|PatType { pat, ty, .. }| match pat.as_ref() {
+            Pat::Ident(pat_ident) => Ok(ParamInfo {
+                name: pat_ident.ident.clone(),
+                ty: ty.as_ref().clone(),
+            }),
+            other => Err(syn::Error::new_spanned(
+                other,
+                "#[resource] parameters must be plain identifiers",
+            )),
+        })
+        .collect()
+}
+
+/// Names of the variables of an RFC 6570 URI template
+fn template_variables(template: &str) -> Vec<String> {
+    template
+        .split('{')
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}'))
+        .flat_map(|(expression, _)| {
+            expression
+                .trim_start_matches(['+', '#', '.', '/', ';', '?', '&'])
+                .split(',')
+                .map(|var| {
+                    let var = var.trim_end_matches('*');
+                    var.split_once(':')
+                        .map_or(var, |(name, _)| name)
+                        .to_string()
+                })
+        })
+        .collect()
+}
+
+/// Check a template variable name the way `UriTemplate` does
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Generate code binding a template variable to a parameter
+///
+/// Values are parsed with `FromStr`; `Option` parameters are `None` when the variable
+/// is absent from the URI.
+fn generate_param_extraction(param: &ParamInfo) -> TokenStream {
+    let name = &param.name;
+    let name_str = name.to_string();
+    let parse = |ty: &Type| {
+        quote! {
+            <#ty as ::std::str::FromStr>::from_str(&value).map_err(|e| {
+                pmcp::Error::invalid_params(format!(
+                    "Invalid value for URI variable `{}`: {}",
+                    #name_str, e
+                ))
+            })?
+        }
+    };
+    match extract_option_inner(&param.ty) {
+        Some(inner) => {
+            let parse = parse(inner);
+            quote! {
+                let #name: Option<#inner> = match vars.remove(#name_str) {
+                    Some(value) => Some(#parse),
+                    None => None,
+                };
+            }
+        },
+        None => {
+            let ty = &param.ty;
+            let parse = parse(ty);
+            quote! {
+                let #name: #ty = match vars.remove(#name_str) {
+                    Some(value) => #parse,
+                    None => {
+                        return Err(pmcp::Error::invalid_params(format!(
+                            "Missing URI variable `{}`",
+                            #name_str
+                        )))
+                    },
+                };
+            }
+        },
+    }
+}
+
+/// Generate result conversion code
+///
+/// A `ReadResourceResult` is returned as is; any other value becomes the text of the
+/// resource. A `pmcp::Result` keeps its error, other errors become internal errors.
+fn generate_result_conversion(output: &ReturnType, mime_type: &TokenStream) -> TokenStream {
+    let to_contents = |ty: Option<&Type>| {
+        if ty.is_some_and(|ty| last_ident_is(ty, "ReadResourceResult")) {
+            quote!(value)
+        } else {
+            quote! {
+                pmcp::types::ReadResourceResult::new(vec![pmcp::types::Content::Resource {
+                    uri: uri.to_string(),
+                    text: Some(value.to_string()),
+                    mime_type: #mime_type,
+                }])
+            }
+        }
+    };
+    let ty = match output {
+        ReturnType::Default => None,
+        ReturnType::Type(_, ty) => Some(ty.as_ref()),
+    };
+    match ty.and_then(result_types) {
+        Some((ok, pmcp_error)) => {
+            let contents = to_contents(ok);
+            let error = if pmcp_error {
+                quote!(e)
+            } else {
+                quote!(pmcp::Error::internal(e.to_string()))
+            };
+            quote! {
+                match result {
+                    Ok(value) => Ok(#contents),
+                    Err(e) => Err(#error),
+                }
+            }
+        },
+        None => {
+            let contents = to_contents(ty);
+            quote! {
+                let value = result;
+                Ok(#contents)
+            }
+        },
+    }
+}
+
+/// For a `Result` type, its success type and whether its error is `pmcp::Error`
+///
+/// A single-argument `Result` is taken to be `pmcp::Result`.
+fn result_types(ty: &Type) -> Option<(Option<&Type>, bool)> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Result" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    let mut types = args.args.iter().filter_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    });
+    let ok = types.next();
+    let pmcp_error = match types.next() {
+        None => true,
+        Some(Type::Path(error)) => {
+            error.path.segments.len() >= 2
+                && error.path.segments.iter().any(|s| s.ident == "pmcp")
+                && error
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|s| s.ident == "Error")
+        },
+        Some(_) => false,
+    };
+    Some((ok, pmcp_error))
+}
+
+/// Check if the last path segment of a type is `ident`
+fn last_ident_is(ty: &Type, ident: &str) -> bool {
+    matches!(ty, Type::Path(type_path) if type_path.path.segments.last().is_some_and(|s| s.ident == ident))
+}
+
+fn optional_string(value: Option<&str>) -> TokenStream {
+    match value {
+        Some(value) => quote!(Some(#value.to_string())),
+        None => quote!(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_template_variables() {
+        assert_eq!(template_variables("file:///{path}"), ["path"]);
+        assert_eq!(
+            template_variables("db://{+table}/{id}{?fields*,limit:3}"),
+            ["table", "id", "fields", "limit"]
+        );
+        assert!(template_variables("config://settings").is_empty());
+    }
+
+    #[test]
+    fn test_parameter_missing_from_template_is_rejected() {
+        let input: ItemFn = parse_quote! {
+            async fn read_file(path: String, encoding: String) -> String {
+                path
+            }
+        };
+        let err = expand_resource(quote!(uri_template = "file:///{path}"), input).unwrap_err();
+        assert!(
+            err.to_string().contains("`encoding` is not a variable"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_methods_must_borrow_self() {
+        let input: ItemFn = parse_quote! {
+            async fn read_file(&mut self, path: String) -> String {
+                path
+            }
+        };
+        let err = expand_resource(quote!(uri_template = "file:///{path}"), input).unwrap_err();
+        assert!(err.to_string().contains("`&self`"), "{}", err);
+
+        let input: ItemFn = parse_quote! {
+            async fn read_file(&self, path: String) -> String {
+                path
+            }
+        };
+        let tokens = expand_resource(quote!(uri_template = "file:///{path}"), input)
+            .unwrap()
+            .to_string();
+        assert!(tokens.contains("read_file_resource_handler"), "{}", tokens);
+    }
+
+    #[test]
+    fn test_invalid_template_is_rejected() {
+        let input: ItemFn = parse_quote! {
+            fn read_file(path: String) -> String {
+                path
+            }
+        };
+        let err =
+            expand_resource(quote!(uri_template = "file:///{path}/{1st}"), input).unwrap_err();
+        assert!(
+            err.to_string().contains("invalid variable `1st`"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_result_types() {
+        let ty: Type = parse_quote!(pmcp::Result<String>);
+        assert!(matches!(result_types(&ty), Some((Some(_), true))));
+
+        let ty: Type = parse_quote!(Result<String, pmcp::Error>);
+        assert!(matches!(result_types(&ty), Some((Some(_), true))));
+
+        let ty: Type = parse_quote!(Result<String, std::io::Error>);
+        assert!(matches!(result_types(&ty), Some((Some(_), false))));
+
+        let ty: Type = parse_quote!(String);
+        assert!(result_types(&ty).is_none());
+    }
+}
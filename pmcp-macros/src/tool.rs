@@ -3,6 +3,7 @@
 //! This module implements the `#[tool]` attribute macro for defining MCP tools
 //! with automatic schema generation and handler implementation.
 
+use crate::utils::to_pascal_case;
 use darling::FromMeta;
 use proc_macro2::{Ident, TokenStream};
 use quote::quote;
@@ -27,14 +28,13 @@ struct ToolArgs {
 /// Tool annotations for metadata
 #[derive(Debug, Default, FromMeta)]
 struct ToolAnnotations {
+    /// Category used to group the tool in `tools/list`
     #[darling(default)]
     category: Option<String>,
 
+    /// Comma-separated tags, e.g. `tags = "files, search"`
     #[darling(default)]
-    complexity: Option<String>,
-
-    #[darling(default)]
-    read_only: Option<bool>,
+    tags: Option<String>,
 }
 
 /// Expands the #[tool] attribute macro  
@@ -57,6 +57,20 @@ pub fn expand_tool(args: TokenStream, input: ItemFn) -> syn::Result<TokenStream>
     let fn_name = &input.sig.ident;
     let tool_name = args.name.unwrap_or_else(|| fn_name.to_string());
     let description = args.description;
+    let annotations = args.annotations.unwrap_or_default();
+    let category = match annotations.category {
+        Some(category) => quote!(Some(#category.to_string())),
+        None => quote!(None),
+    };
+    let tags: Vec<String> = annotations
+        .tags
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(String::from)
+        .collect();
 
     // Extract function parameters
     let params = extract_parameters(&input)?;
@@ -105,15 +119,22 @@ pub fn expand_tool(args: TokenStream, input: ItemFn) -> syn::Result<TokenStream>
                 // Convert result to JSON
                 #result_conversion
             }
+
+            fn metadata(&self) -> Option<pmcp::types::ToolInfo> {
+                Some(Self::definition())
+            }
         }
 
         impl #wrapper_name {
             /// Get tool definition
-            pub fn definition() -> pmcp::types::Tool {
-                pmcp::types::Tool {
+            pub fn definition() -> pmcp::types::ToolInfo {
+                pmcp::types::ToolInfo {
                     name: #tool_name.to_string(),
                     description: Some(#description.to_string()),
-                    input_schema: Some(Self::input_schema()),
+                    input_schema: Self::input_schema(),
+                    category: #category,
+                    tags: vec![#(#tags.to_string()),*],
+                    output_schema: None,
                 }
             }
 
@@ -247,30 +268,10 @@ fn is_result_type(ty: &Type) -> bool {
     false
 }
 
-/// Convert snake_case to PascalCase
-fn to_pascal_case(s: &str) -> String {
-    s.split('_')
-        .map(|word| {
-            let mut chars = word.chars();
-            match chars.next() {
-                None => String::new(),
-                Some(first) => first.to_uppercase().chain(chars).collect(),
-            }
-        })
-        .collect()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_to_pascal_case() {
-        assert_eq!(to_pascal_case("hello_world"), "HelloWorld");
-        assert_eq!(to_pascal_case("add_numbers"), "AddNumbers");
-        assert_eq!(to_pascal_case("simple"), "Simple");
-    }
-
     #[test]
     fn test_is_option_type() {
         let opt_type: Type = parse_quote!(Option<String>);
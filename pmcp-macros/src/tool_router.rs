@@ -2,13 +2,17 @@
 //!
 //! This module implements the `#[tool_router]` attribute macro that collects
 //! all tool methods from an impl block and generates routing code.
+//!
+//! A tool method may take one reference parameter besides `&self`, such as
+//! `state: &AppState`. It receives the server's shared state, set with
+//! `ServerBuilder::state` and read from `RequestHandlerExtra::state`.
 
 use darling::ast::NestedMeta;
 use darling::FromMeta;
 use proc_macro2::{Ident, TokenStream};
 use quote::quote;
 use syn::parse::Parser;
-use syn::{parse_quote, Attribute, ImplItem, ImplItemFn, ItemImpl, Visibility};
+use syn::{parse_quote, Attribute, FnArg, ImplItem, ImplItemFn, ItemImpl, Type, Visibility};
 
 /// Tool router macro arguments
 #[derive(Debug, Default, FromMeta)]
@@ -32,6 +36,16 @@ struct ToolMethod {
     tool_name: String,
     description: String,
     is_async: bool,
+    /// What each parameter after `&self` receives
+    params: Vec<ToolParam>,
+}
+
+/// Value passed for a tool method parameter
+enum ToolParam {
+    /// The tool call arguments
+    Args,
+    /// The server's shared state of the given type
+    State(Box<Type>),
 }
 
 /// Expands the #[tool_router] attribute macro
@@ -83,15 +97,7 @@ pub fn expand_tool_router(args: TokenStream, mut input: ItemImpl) -> syn::Result
     // Add router field to the struct (this would need to be done separately)
     // For now, we'll document that the user needs to add it manually
 
-    let expanded = quote! {
-        #input
-
-        impl ToolRouterInfo for Self {
-            const ROUTER_FIELD: &'static str = stringify!(#router_field);
-        }
-    };
-
-    Ok(expanded)
+    Ok(quote! { #input })
 }
 
 /// Collect all methods marked with #[tool] from the impl block
@@ -110,6 +116,7 @@ fn collect_tool_methods(impl_block: &ItemImpl) -> syn::Result<Vec<ToolMethod>> {
                     tool_name,
                     description: tool_info.description,
                     is_async: method.sig.asyncness.is_some(),
+                    params: collect_tool_params(method)?,
                 });
             }
         }
@@ -118,6 +125,33 @@ fn collect_tool_methods(impl_block: &ItemImpl) -> syn::Result<Vec<ToolMethod>> {
     Ok(methods)
 }
 
+/// Classify the parameters of a tool method, allowing at most one state reference
+fn collect_tool_params(method: &ImplItemFn) -> syn::Result<Vec<ToolParam>> {
+    let mut params = Vec::new();
+    let mut has_state = false;
+
+    for input in &method.sig.inputs {
+        let FnArg::Typed(pat_type) = input else {
+            continue;
+        };
+        match pat_type.ty.as_ref() {
+            Type::Reference(reference) => {
+                if has_state {
+                    return Err(syn::Error::new_spanned(
+                        pat_type,
+                        "A tool can take only one state reference",
+                    ));
+                }
+                has_state = true;
+                params.push(ToolParam::State(reference.elem.clone()));
+            },
+            _ => params.push(ToolParam::Args),
+        }
+    }
+
+    Ok(params)
+}
+
 /// Tool attribute information
 struct ToolInfo {
     name: Option<String>,
@@ -207,10 +241,27 @@ fn generate_handle_tool_method(methods: &[ToolMethod], vis: &Visibility) -> Impl
             } else {
                 quote!()
             };
+            let state_binding = method.params.iter().find_map(|param| match param {
+                ToolParam::State(ty) => Some(quote! {
+                    let state = extra.state::<#ty>().ok_or_else(|| {
+                        pmcp::Error::internal(format!(
+                            "Tool '{}' needs server state of type {}",
+                            #tool_name,
+                            stringify!(#ty)
+                        ))
+                    })?;
+                }),
+                ToolParam::Args => None,
+            });
+            let call_args = method.params.iter().map(|param| match param {
+                ToolParam::Args => quote!(args.clone()),
+                ToolParam::State(_) => quote!(&state),
+            });
 
             quote! {
                 #tool_name => {
-                    let result = self.#method_name(args.clone())#await_token;
+                    #state_binding
+                    let result = self.#method_name(#(#call_args),*)#await_token;
                     match result {
                         Ok(value) => Ok(serde_json::to_value(value)?),
                         Err(e) => Err(pmcp::Error::ToolError(e.to_string())),
@@ -261,11 +312,6 @@ fn parse_visibility(vis_str: &Option<String>) -> syn::Result<Visibility> {
     }
 }
 
-/// Trait to mark types that have a tool router
-trait ToolRouterInfo {
-    const ROUTER_FIELD: &'static str;
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -277,4 +323,51 @@ mod tests {
         assert!(parse_visibility(&Some("pub(crate)".to_string())).is_ok());
         assert!(parse_visibility(&Some("invalid".to_string())).is_err());
     }
+
+    #[test]
+    fn test_state_parameter_is_read_from_extra() {
+        let input: ItemImpl = parse_quote! {
+            impl Inventory {
+                #[tool(description = "Count items")]
+                async fn count(&self, state: &AppState, filter: String) -> Result<usize, String> {
+                    Ok(state.items.len())
+                }
+
+                #[tool(description = "Ping")]
+                fn ping(&self) -> Result<String, String> {
+                    Ok("pong".to_string())
+                }
+            }
+        };
+
+        let expanded = expand_tool_router(TokenStream::new(), input)
+            .unwrap()
+            .to_string();
+        assert!(
+            expanded.contains("extra . state :: < AppState > ()"),
+            "{}",
+            expanded
+        );
+        assert!(
+            expanded.contains("self . count (& state , args . clone ()) . await"),
+            "{}",
+            expanded
+        );
+        assert!(expanded.contains("self . ping ()"), "{}", expanded);
+    }
+
+    #[test]
+    fn test_second_state_parameter_is_rejected() {
+        let input: ItemImpl = parse_quote! {
+            impl Inventory {
+                #[tool(description = "Count items")]
+                fn count(&self, state: &AppState, config: &Config) -> usize {
+                    0
+                }
+            }
+        };
+
+        let err = expand_tool_router(TokenStream::new(), input).unwrap_err();
+        assert!(err.to_string().contains("only one state reference"));
+    }
 }
@@ -0,0 +1,138 @@
+//! Integration tests for the resource macro
+//!
+//! These tests verify that the #[resource] macro generates resource handlers that
+//! bind URI template variables to function parameters.
+
+use pmcp::types::Content;
+use pmcp::{RequestHandlerExtra, ResourceHandler};
+use pmcp_macros::resource;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+#[resource(uri_template = "file:///{path}", mime_type = "text/plain")]
+async fn read_file(path: String) -> Result<String, std::io::Error> {
+    Ok(format!("contents of {}", path))
+}
+
+#[resource(
+    uri_template = "users://{id}/{section}",
+    name = "user_section",
+    description = "A section of a user's profile"
+)]
+fn read_user(id: u32, section: Option<String>) -> String {
+    format!("user {} {}", id, section.unwrap_or_default())
+}
+
+/// Documents kept in memory
+struct Library {
+    prefix: String,
+}
+
+impl Library {
+    #[resource(uri_template = "docs://{name}", mime_type = "text/markdown")]
+    async fn read_doc(&self, name: String) -> pmcp::Result<String> {
+        Ok(format!("{} {}", self.prefix, name))
+    }
+}
+
+fn extra() -> RequestHandlerExtra {
+    RequestHandlerExtra::new("1".to_string(), CancellationToken::new())
+}
+
+#[tokio::test]
+async fn test_matching_uri_binds_variables() {
+    let result = ReadFileResourceHandler::new()
+        .read("file:///notes.txt", extra())
+        .await
+        .unwrap();
+    assert_eq!(result.contents.len(), 1);
+    match &result.contents[0] {
+        Content::Resource {
+            uri,
+            text,
+            mime_type,
+        } => {
+            assert_eq!(uri, "file:///notes.txt");
+            assert_eq!(text.as_deref(), Some("contents of notes.txt"));
+            assert_eq!(mime_type.as_deref(), Some("text/plain"));
+        },
+        other => panic!("unexpected content: {:?}", other),
+    }
+
+    let result = ReadUserResourceHandler::new()
+        .read("users://42/settings", extra())
+        .await
+        .unwrap();
+    assert!(matches!(
+        &result.contents[0],
+        Content::Resource { text: Some(text), mime_type: None, .. } if text == "user 42 settings"
+    ));
+}
+
+#[tokio::test]
+async fn test_invalid_variable_value_is_rejected() {
+    let err = ReadUserResourceHandler::new()
+        .read("users://alice/settings", extra())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("`id`"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_non_matching_uri_is_not_found() {
+    let err = ReadFileResourceHandler::new()
+        .read("http://example.com/notes.txt", extra())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, pmcp::Error::NotFound(_)), "{:?}", err);
+}
+
+#[tokio::test]
+async fn test_templates_are_listed() {
+    let resources = ReadFileResourceHandler::new()
+        .list(None, extra())
+        .await
+        .unwrap();
+    assert!(resources.resources.is_empty());
+
+    let templates = ReadUserResourceHandler::new()
+        .list_templates(None, extra())
+        .await
+        .unwrap();
+    assert_eq!(templates.resource_templates.len(), 1);
+    let template = &templates.resource_templates[0];
+    assert_eq!(template.uri_template, "users://{id}/{section}");
+    assert_eq!(template.name, "user_section");
+    assert_eq!(
+        template.description.as_deref(),
+        Some("A section of a user's profile")
+    );
+    assert_eq!(
+        ReadFileResourceHandler::template().mime_type.as_deref(),
+        Some("text/plain")
+    );
+}
+
+#[tokio::test]
+async fn test_methods_read_through_their_receiver() {
+    let library = Arc::new(Library {
+        prefix: "#".to_string(),
+    });
+    let handler = library.read_doc_resource_handler();
+
+    let result = handler.read("docs://intro", extra()).await.unwrap();
+    assert!(matches!(
+        &result.contents[0],
+        Content::Resource { text: Some(text), mime_type: Some(mime_type), .. }
+            if text == "# intro" && mime_type == "text/markdown"
+    ));
+
+    let err = handler.read("file:///intro", extra()).await.unwrap_err();
+    assert!(matches!(err, pmcp::Error::NotFound(_)), "{:?}", err);
+
+    let templates = handler.list_templates(None, extra()).await.unwrap();
+    assert_eq!(
+        templates.resource_templates[0].uri_template,
+        "docs://{name}"
+    );
+}
@@ -4,9 +4,14 @@
 //! in MCP clients, including discovery and token management.
 
 use crate::error::{Error, ErrorCode, Result};
-use crate::server::auth::oauth2::OidcDiscoveryMetadata;
+use crate::server::auth::oauth2::{
+    AccessToken, GrantType, OAuthClient, OidcDiscoveryMetadata, TokenRequest,
+};
+use crate::types::jsonrpc::ResponsePayload;
+use crate::types::JSONRPCResponse;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// OIDC discovery client for fetching server configuration.
 ///
@@ -427,6 +432,224 @@ impl TokenExchangeClient {
     }
 }
 
+/// Time before expiry at which a [`TokenStore`] refreshes the access token, unless
+/// set with [`TokenStore::with_refresh_window`].
+pub const DEFAULT_REFRESH_WINDOW: Duration = Duration::from_secs(60);
+
+/// OAuth 2.0 tokens of a client, refreshed with the refresh token before they expire.
+///
+/// Given to [`ClientBuilder::with_oauth`](crate::ClientBuilder::with_oauth), the
+/// client sends the access token with every request. It is refreshed once it is
+/// within the refresh window of expiring, or when the server rejects it with
+/// `AUTHENTICATION_REQUIRED` or `PERMISSION_DENIED`, after which the rejected
+/// request is retried once. Keep a clone of the store to persist refreshed tokens.
+///
+/// # Examples
+///
+/// ```rust
+/// use pmcp::client::auth::TokenStore;
+/// use pmcp::server::auth::oauth2::{AccessToken, TokenType};
+/// use std::time::Duration;
+///
+/// let token = AccessToken {
+///     access_token: "token123".to_string(),
+///     token_type: TokenType::Bearer,
+///     expires_in: Some(3600),
+///     refresh_token: Some("refresh123".to_string()),
+///     scope: None,
+///     extra: Default::default(),
+/// };
+/// let tokens = TokenStore::new("https://auth.example.com/token", token)
+///     .with_refresh_window(Duration::from_secs(120));
+/// ```
+#[derive(Debug)]
+pub struct TokenStore {
+    /// Token endpoint refresh requests are sent to.
+    token_endpoint: String,
+    /// Time before expiry at which the access token is refreshed.
+    refresh_window: Duration,
+    /// HTTP client for making requests.
+    client: reqwest::Client,
+    /// Current tokens, locked while refreshing so only one refresh runs at a time.
+    current: tokio::sync::Mutex<StoredToken>,
+}
+
+#[derive(Debug)]
+struct StoredToken {
+    token: AccessToken,
+    /// When the access token expires, if it does
+    expires_at: Option<Instant>,
+}
+
+impl StoredToken {
+    fn new(token: AccessToken) -> Self {
+        let expires_at = token
+            .expires_in
+            .map(|secs| Instant::now() + Duration::from_secs(secs));
+        Self { token, expires_at }
+    }
+
+    fn expires_within(&self, window: Duration) -> bool {
+        self.expires_at
+            .is_some_and(|at| at.saturating_duration_since(Instant::now()) <= window)
+    }
+}
+
+impl TokenStore {
+    /// Create a token store refreshing `token` at `token_endpoint`.
+    pub fn new(token_endpoint: impl Into<String>, token: AccessToken) -> Self {
+        Self {
+            token_endpoint: token_endpoint.into(),
+            refresh_window: DEFAULT_REFRESH_WINDOW,
+            client: reqwest::Client::new(),
+            current: tokio::sync::Mutex::new(StoredToken::new(token)),
+        }
+    }
+
+    /// Set how long before expiry the access token is refreshed.
+    pub fn with_refresh_window(mut self, window: Duration) -> Self {
+        self.refresh_window = window;
+        self
+    }
+
+    /// The current tokens, with `expires_in` counting down from when they were
+    /// obtained.
+    pub async fn tokens(&self) -> AccessToken {
+        let current = self.current.lock().await;
+        AccessToken {
+            expires_in: current
+                .expires_at
+                .map(|at| at.saturating_duration_since(Instant::now()).as_secs()),
+            ..current.token.clone()
+        }
+    }
+
+    /// Refresh the access token now.
+    pub async fn refresh(&self, client: &OAuthClient) -> Result<AccessToken> {
+        let mut current = self.current.lock().await;
+        *current = self.request_refresh(client, &current.token).await?;
+        Ok(current.token.clone())
+    }
+
+    /// The access token to send, refreshed first if it is about to expire or if it
+    /// is the `rejected` one.
+    ///
+    /// A failed refresh of a token that has not been rejected is only logged, since
+    /// the server may still accept the token until it expires.
+    pub(crate) async fn access_token(
+        &self,
+        client: &OAuthClient,
+        rejected: Option<&str>,
+    ) -> Result<String> {
+        let mut current = self.current.lock().await;
+        match rejected {
+            // Another request already refreshed it
+            Some(rejected) if current.token.access_token != rejected => {},
+            Some(_) => *current = self.request_refresh(client, &current.token).await?,
+            None if current.token.refresh_token.is_some()
+                && current.expires_within(self.refresh_window) =>
+            {
+                match self.request_refresh(client, &current.token).await {
+                    Ok(refreshed) => *current = refreshed,
+                    Err(e) => tracing::warn!("Failed to refresh access token: {}", e),
+                }
+            },
+            None => {},
+        }
+        Ok(current.token.access_token.clone())
+    }
+
+    async fn request_refresh(
+        &self,
+        client: &OAuthClient,
+        token: &AccessToken,
+    ) -> Result<StoredToken> {
+        let refresh_token = token.refresh_token.clone().ok_or_else(|| {
+            Error::protocol(
+                ErrorCode::AUTHENTICATION_REQUIRED,
+                "No refresh token to refresh the access token with",
+            )
+        })?;
+        let params = TokenRequest {
+            grant_type: GrantType::RefreshToken,
+            code: None,
+            redirect_uri: None,
+            client_id: Some(client.client_id.clone()),
+            client_secret: None,
+            refresh_token: Some(refresh_token.clone()),
+            username: None,
+            password: None,
+            scope: None,
+            code_verifier: None,
+        };
+
+        let mut request = self
+            .client
+            .post(&self.token_endpoint)
+            .header("Accept", "application/json")
+            .form(&params);
+
+        // Add client authentication if secret is provided
+        if let Some(secret) = &client.client_secret {
+            request = request.basic_auth(&client.client_id, Some(secret));
+        }
+
+        let response = request.send().await.map_err(|e| {
+            Error::protocol(
+                ErrorCode::INTERNAL_ERROR,
+                format!("Failed to refresh token: {}", e),
+            )
+        })?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(Error::protocol(
+                ErrorCode::AUTHENTICATION_REQUIRED,
+                format!("Token refresh failed: {}", error_text),
+            ));
+        }
+
+        let mut refreshed = response.json::<AccessToken>().await.map_err(|e| {
+            Error::protocol(
+                ErrorCode::PARSE_ERROR,
+                format!("Failed to parse token response: {}", e),
+            )
+        })?;
+        // The refresh token stays valid unless a new one is issued
+        refreshed.refresh_token.get_or_insert(refresh_token);
+        Ok(StoredToken::new(refreshed))
+    }
+}
+
+/// OAuth client and tokens of a [`Client`](crate::Client).
+#[derive(Debug)]
+pub(crate) struct OAuthSession {
+    pub(crate) client: OAuthClient,
+    pub(crate) tokens: Arc<TokenStore>,
+}
+
+impl OAuthSession {
+    /// The access token to send, see [`TokenStore::access_token`].
+    pub(crate) async fn access_token(&self, rejected: Option<&str>) -> Result<String> {
+        self.tokens.access_token(&self.client, rejected).await
+    }
+}
+
+/// Whether the server rejected a request for its access token, either at the
+/// transport level (HTTP 401) or with a JSON-RPC error.
+pub(crate) fn rejects_token(response: &Result<JSONRPCResponse>) -> bool {
+    match response {
+        Err(Error::Authentication(_)) => true,
+        Err(_) => false,
+        Ok(response) => matches!(
+            &response.payload,
+            ResponsePayload::Error(error)
+                if error.code == ErrorCode::AUTHENTICATION_REQUIRED.as_i32()
+                    || error.code == ErrorCode::PERMISSION_DENIED.as_i32()
+        ),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
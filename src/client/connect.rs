@@ -0,0 +1,206 @@
+//! Connect to a server by URL, picking the transport from the scheme.
+
+use crate::error::{Error, Result};
+use crate::shared::{BoxedTransport, StdioTransport};
+use crate::types::ClientCapabilities;
+use url::Url;
+
+use super::BoxedClient;
+
+/// Where and how to connect, parsed from a server URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransportTarget {
+    /// `stdio:` talks over this process's stdin/stdout; `stdio:<command> [args...]`
+    /// launches the server as a subprocess
+    Stdio {
+        /// Command to launch, if any
+        command: Option<String>,
+        /// Arguments for the command
+        args: Vec<String>,
+    },
+    /// `ws://` or `wss://`
+    WebSocket(Url),
+    /// `http://` or `https://`
+    Http(Url),
+}
+
+impl TransportTarget {
+    /// Parse a server URL.
+    ///
+    /// Arguments in `stdio:` URLs are separated by whitespace and may be
+    /// percent-encoded, e.g. `stdio:node my%20server.js`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::client::TransportTarget;
+    ///
+    /// let target = TransportTarget::parse("stdio:node server.js")?;
+    /// assert_eq!(target, TransportTarget::Stdio {
+    ///     command: Some("node".to_string()),
+    ///     args: vec!["server.js".to_string()],
+    /// });
+    ///
+    /// assert!(TransportTarget::parse("ftp://example.com").is_err());
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn parse(url: &str) -> Result<Self> {
+        if let Some(rest) = url.strip_prefix("stdio:") {
+            let mut parts = rest
+                .trim_start_matches("//")
+                .split_whitespace()
+                .map(|part| {
+                    urlencoding::decode(part)
+                        .map(std::borrow::Cow::into_owned)
+                        .map_err(|e| {
+                            Error::validation(format!("Invalid stdio URL '{}': {}", url, e))
+                        })
+                })
+                .collect::<Result<Vec<_>>>()?
+                .into_iter();
+            return Ok(Self::Stdio {
+                command: parts.next(),
+                args: parts.collect(),
+            });
+        }
+
+        let parsed = Url::parse(url)
+            .map_err(|e| Error::validation(format!("Invalid URL '{}': {}", url, e)))?;
+        match parsed.scheme() {
+            "ws" | "wss" => Ok(Self::WebSocket(parsed)),
+            "http" | "https" => Ok(Self::Http(parsed)),
+            scheme => Err(Error::validation(format!(
+                "Unsupported transport scheme '{}' (expected stdio:, ws://, wss://, http:// or https://)",
+                scheme
+            ))),
+        }
+    }
+
+    /// Build the transport for this target.
+    ///
+    /// Stdio commands are launched with [`StdioTransport::spawn`], and WebSocket
+    /// transports are connected before they are returned.
+    pub async fn into_transport(self) -> Result<BoxedTransport> {
+        match self {
+            Self::Stdio { command: None, .. } => Ok(Box::new(StdioTransport::new())),
+            Self::Stdio {
+                command: Some(command),
+                args,
+            } => {
+                let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                Ok(Box::new(StdioTransport::spawn(&command, &args, &[])?))
+            },
+            Self::WebSocket(url) => websocket_transport(url).await,
+            Self::Http(url) => http_transport(url),
+        }
+    }
+}
+
+#[cfg(all(feature = "websocket", not(target_arch = "wasm32")))]
+async fn websocket_transport(url: Url) -> Result<BoxedTransport> {
+    let transport = crate::shared::WebSocketTransport::with_url(url)?;
+    transport.connect().await?;
+    Ok(Box::new(transport))
+}
+
+#[cfg(not(all(feature = "websocket", not(target_arch = "wasm32"))))]
+#[allow(clippy::unused_async)]
+async fn websocket_transport(url: Url) -> Result<BoxedTransport> {
+    Err(Error::validation(format!(
+        "Connecting to '{}' requires the `websocket` feature",
+        url
+    )))
+}
+
+#[cfg(feature = "http")]
+fn http_transport(url: Url) -> Result<BoxedTransport> {
+    Ok(Box::new(crate::shared::HttpTransport::with_url(url)?))
+}
+
+#[cfg(not(feature = "http"))]
+fn http_transport(url: Url) -> Result<BoxedTransport> {
+    Err(Error::validation(format!(
+        "Connecting to '{}' requires the `http` feature",
+        url
+    )))
+}
+
+/// Connect to a server and initialize the client.
+///
+/// The transport is picked from the URL scheme: `stdio:`, `ws://`, `wss://`,
+/// `http://` or `https://`. See [`TransportTarget::parse`] for the URL format.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # async fn example() -> pmcp::Result<()> {
+/// let client = pmcp::connect("ws://localhost:8080").await?;
+/// let tools = client.list_tools(None).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn connect(url: &str) -> Result<BoxedClient> {
+    let transport = TransportTarget::parse(url)?.into_transport().await?;
+    let mut client = BoxedClient::new_boxed(transport);
+    client.initialize(ClientCapabilities::default()).await?;
+    Ok(client)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scheme_dispatch() {
+        assert_eq!(
+            TransportTarget::parse("stdio:").unwrap(),
+            TransportTarget::Stdio {
+                command: None,
+                args: vec![],
+            }
+        );
+        assert_eq!(
+            TransportTarget::parse("stdio://python my%20server.py --verbose").unwrap(),
+            TransportTarget::Stdio {
+                command: Some("python".to_string()),
+                args: vec!["my server.py".to_string(), "--verbose".to_string()],
+            }
+        );
+        for url in ["ws://localhost:8080", "wss://example.com/mcp"] {
+            assert!(matches!(
+                TransportTarget::parse(url).unwrap(),
+                TransportTarget::WebSocket(parsed) if parsed.as_str().starts_with(url)
+            ));
+        }
+        for url in ["http://localhost:8080", "https://example.com/mcp"] {
+            assert!(matches!(
+                TransportTarget::parse(url).unwrap(),
+                TransportTarget::Http(parsed) if parsed.as_str().starts_with(url)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_unknown_scheme_is_rejected() {
+        let err = TransportTarget::parse("ftp://example.com").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Unsupported transport scheme 'ftp'"));
+        assert!(TransportTarget::parse("not a url").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stdio_target_builds_stdio_transport() {
+        let transport = TransportTarget::parse("stdio:")
+            .unwrap()
+            .into_transport()
+            .await
+            .unwrap();
+        assert_eq!(transport.transport_type(), "stdio");
+    }
+
+    #[tokio::test]
+    async fn test_connect_rejects_unknown_scheme() {
+        assert!(connect("gopher://example.com").await.is_err());
+    }
+}
@@ -0,0 +1,182 @@
+//! Elicitation requests from the server waiting for the user's answer.
+//!
+//! The receive loop (see [`router`](super::router)) hands `elicitation/input` requests
+//! to the subscriber of [`Client::elicitation_requests`](super::Client::elicitation_requests)
+//! and remembers them until [`Client::respond_elicitation`](super::Client::respond_elicitation)
+//! answers them. An elicitation is forgotten when the server cancels its request with
+//! `notifications/cancelled`, and expires after the elicitation timeout, by which time
+//! the server has stopped waiting; answering it afterwards fails instead of sending a
+//! response nobody reads.
+
+use crate::error::{Error, Result};
+use crate::types::elicitation::ElicitInputRequest;
+use crate::types::RequestId;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// How long an elicitation can be answered, matching the server's default wait.
+pub(crate) const DEFAULT_ELICITATION_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Number of elicitations kept for a subscriber that is not keeping up; later
+/// elicitations are dropped until it catches up.
+const ELICITATION_CAPACITY: usize = 64;
+
+/// An elicitation handed to the subscriber and not answered yet.
+#[derive(Debug)]
+struct PendingElicitation {
+    /// ID of the server's `elicitation/input` request
+    request_id: RequestId,
+    expires_at: Instant,
+}
+
+/// Elicitations from the server, shared by a client and its clones.
+#[derive(Debug)]
+pub(crate) struct Elicitations {
+    subscriber: parking_lot::RwLock<Option<mpsc::Sender<ElicitInputRequest>>>,
+    /// Unanswered elicitations by elicitation ID
+    pending: parking_lot::Mutex<HashMap<String, PendingElicitation>>,
+    timeout: Duration,
+}
+
+impl Default for Elicitations {
+    fn default() -> Self {
+        Self::new(DEFAULT_ELICITATION_TIMEOUT)
+    }
+}
+
+impl Elicitations {
+    pub(crate) fn new(timeout: Duration) -> Self {
+        Self {
+            subscriber: parking_lot::RwLock::default(),
+            pending: parking_lot::Mutex::default(),
+            timeout,
+        }
+    }
+
+    /// Replace the subscriber with a new one.
+    pub(crate) fn subscribe(&self) -> mpsc::Receiver<ElicitInputRequest> {
+        let (tx, rx) = mpsc::channel(ELICITATION_CAPACITY);
+        *self.subscriber.write() = Some(tx);
+        rx
+    }
+
+    /// The subscriber, unless there is none or it went away.
+    pub(crate) fn subscriber(&self) -> Option<mpsc::Sender<ElicitInputRequest>> {
+        self.subscriber
+            .read()
+            .as_ref()
+            .filter(|tx| !tx.is_closed())
+            .cloned()
+    }
+
+    /// Hand the elicitation the server sent as request `request_id` to the subscriber.
+    ///
+    /// Without a subscriber the elicitation is dropped and the server's wait for an
+    /// answer times out.
+    pub(crate) fn deliver(&self, request_id: RequestId, request: ElicitInputRequest) {
+        let Some(tx) = self.subscriber() else {
+            tracing::warn!(
+                "Ignoring elicitation {}, nobody receives elicitation requests",
+                request.elicitation_id
+            );
+            return;
+        };
+        let now = Instant::now();
+        let mut pending = self.pending.lock();
+        pending.retain(|_, elicitation| elicitation.expires_at > now);
+        let elicitation_id = request.elicitation_id.clone();
+        pending.insert(
+            elicitation_id.clone(),
+            PendingElicitation {
+                request_id,
+                expires_at: now + self.timeout,
+            },
+        );
+        if let Err(e) = tx.try_send(request) {
+            tracing::warn!("Dropping elicitation, subscriber is behind: {:?}", e);
+            pending.remove(&elicitation_id);
+        }
+    }
+
+    /// Forget the elicitation sent as request `request_id`, which the server cancelled.
+    pub(crate) fn cancel(&self, request_id: &RequestId) {
+        self.pending
+            .lock()
+            .retain(|_, elicitation| &elicitation.request_id != request_id);
+    }
+
+    /// Take the elicitation `elicitation_id` to answer it.
+    ///
+    /// Fails with [`Error::Timeout`] if it expired, and with an invalid params error
+    /// if it is unknown, was already answered or was cancelled by the server.
+    pub(crate) fn take(&self, elicitation_id: &str) -> Result<()> {
+        match self.pending.lock().remove(elicitation_id) {
+            Some(elicitation) if elicitation.expires_at > Instant::now() => Ok(()),
+            Some(_) => Err(Error::Timeout(
+                u64::try_from(self.timeout.as_millis()).unwrap_or(u64::MAX),
+            )),
+            None => Err(Error::invalid_params(format!(
+                "No pending elicitation '{}'",
+                elicitation_id
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::elicitation::elicit_text;
+
+    #[tokio::test]
+    async fn test_delivered_elicitation_is_taken_once() {
+        let elicitations = Elicitations::default();
+        let mut rx = elicitations.subscribe();
+
+        let request = elicit_text("Name?").id("name").build();
+        elicitations.deliver(RequestId::from(1i64), request);
+        assert_eq!(rx.recv().await.unwrap().elicitation_id, "name");
+
+        elicitations.take("name").unwrap();
+        assert!(matches!(
+            elicitations.take("name"),
+            Err(Error::Protocol { .. })
+        ));
+    }
+
+    #[test]
+    fn test_elicitation_without_subscriber_is_dropped() {
+        let elicitations = Elicitations::default();
+        elicitations.deliver(
+            RequestId::from(1i64),
+            elicit_text("Name?").id("name").build(),
+        );
+        assert!(elicitations.take("name").is_err());
+    }
+
+    #[test]
+    fn test_cancelled_elicitation_cannot_be_taken() {
+        let elicitations = Elicitations::default();
+        let _rx = elicitations.subscribe();
+        elicitations.deliver(
+            RequestId::from(7i64),
+            elicit_text("Name?").id("name").build(),
+        );
+
+        elicitations.cancel(&RequestId::from(7i64));
+        assert!(elicitations.take("name").is_err());
+    }
+
+    #[test]
+    fn test_expired_elicitation_times_out() {
+        let elicitations = Elicitations::new(Duration::ZERO);
+        let _rx = elicitations.subscribe();
+        elicitations.deliver(
+            RequestId::from(1i64),
+            elicit_text("Name?").id("name").build(),
+        );
+
+        assert!(matches!(elicitations.take("name"), Err(Error::Timeout(0))));
+    }
+}
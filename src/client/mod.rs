@@ -1,25 +1,47 @@
 //! MCP client implementation.
 
-use crate::error::{Error, Result};
-use crate::shared::{Protocol, ProtocolOptions, Transport};
+use crate::error::{Error, Result, TransportError};
+use crate::server::auth::oauth2::OAuthClient;
+use crate::shared::{
+    BoxedTransport, FieldCase, Protocol, ProtocolOptions, ReconnectManager, RequestOptions,
+    Transport, Utf8Mode,
+};
 use crate::types::{
-    CallToolRequest, CallToolResult, CancelledNotification, ClientCapabilities, ClientNotification,
-    ClientRequest, CompleteRequest, CompleteResult, CreateMessageRequest, CreateMessageResult,
-    GetPromptRequest, GetPromptResult, Implementation, InitializeRequest, InitializeResult,
-    ListPromptsRequest, ListPromptsResult, ListResourceTemplatesRequest,
-    ListResourceTemplatesResult, ListResourcesRequest, ListResourcesResult, ListToolsRequest,
-    ListToolsResult, LoggingLevel, Notification, ProgressNotification, ReadResourceRequest,
-    ReadResourceResult, Request, RequestId, ServerCapabilities, SubscribeRequest,
-    UnsubscribeRequest,
+    ArgumentChunk, ArgumentStream, CallToolRequest, CallToolResult, CancelledNotification,
+    ClientCapabilities, ClientNotification, ClientRequest, CompleteRequest, CompleteResult,
+    Content, CreateMessageRequest, CreateMessageResult, GetPromptRequest, GetPromptResult,
+    Implementation, InitializeRequest, InitializeResult, ListPromptsRequest, ListPromptsResult,
+    ListResourceTemplatesRequest, ListResourceTemplatesResult, ListResourcesRequest,
+    ListResourcesResult, ListToolsRequest, ListToolsResult, LoggingLevel, Notification,
+    ProgressNotification, ProtocolVersion, ReadResourceRequest, ReadResourceResult, Request,
+    RequestId, RequestMeta, ServerCapabilities, SubscribeRequest, UnsubscribeRequest,
 };
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::{mpsc, oneshot, RwLock};
+use tokio::sync::{mpsc, RwLock};
 use uuid::Uuid;
 
 pub mod auth;
+pub mod connect;
+mod elicitation;
+mod pending;
+mod ready_queue;
+mod router;
 pub mod transport;
 
+pub use connect::{connect, TransportTarget};
+pub use pending::ClientStats;
+
+/// Maximum number of pages the `*_stream` methods follow before giving up.
+///
+/// Guards against servers that keep returning a `next_cursor` forever.
+pub const MAX_AUTO_PAGES: usize = 100;
+
 /// MCP client for connecting to servers.
 ///
 /// The client provides a high-level interface for interacting with MCP servers,
@@ -86,15 +108,32 @@ pub struct Client<T: Transport> {
     transport: Arc<RwLock<T>>,
     protocol: Arc<RwLock<Protocol>>,
     capabilities: Option<ClientCapabilities>,
-    server_capabilities: Option<ServerCapabilities>,
+    /// Server capabilities and tool schemas, shared with clones and updated by the
+    /// receive loop as the server announces changes
+    server: router::ServerState,
     server_version: Option<Implementation>,
+    protocol_version: Option<ProtocolVersion>,
     instructions: Option<String>,
     initialized: bool,
     info: Implementation,
-    /// Channel for handling incoming notifications
-    notification_tx: Option<mpsc::Sender<Notification>>,
-    /// Active request tracking for cancellation
-    active_requests: Arc<RwLock<HashMap<RequestId, oneshot::Sender<()>>>>,
+    /// Subscriber of the notifications read by the receive loop
+    notifications: Arc<router::Notifications>,
+    /// Elicitations from the server waiting for an answer
+    elicitations: Arc<elicitation::Elicitations>,
+    /// Requests waiting for a response, tracked for cancellation and timeouts
+    active_requests: Arc<pending::PendingRequests>,
+    /// Receive loop delivering responses to the requests waiting for them
+    router: Arc<router::Router>,
+    /// Resource URIs subscribed to, replayed after a reconnect
+    subscriptions: Arc<RwLock<Vec<String>>>,
+    /// Reconnection manager notified when the session is resumed
+    reconnect_manager: Option<Arc<ReconnectManager>>,
+    /// Requests from clones waiting for `initialize`, when queueing is enabled
+    ready_queue: Option<ready_queue::ReadyHandle>,
+    /// Set by [`Client::close`], shared with clones
+    closed: Arc<AtomicBool>,
+    /// OAuth tokens sent with every request, see [`ClientBuilder::with_oauth`]
+    oauth: Option<Arc<auth::OAuthSession>>,
 }
 
 impl<T: Transport> std::fmt::Debug for Client<T> {
@@ -103,14 +142,36 @@ impl<T: Transport> std::fmt::Debug for Client<T> {
             .field("transport", &"<Arc<RwLock<Transport>>>")
             .field("protocol", &"<Arc<RwLock<Protocol>>>")
             .field("capabilities", &self.capabilities)
-            .field("server_capabilities", &self.server_capabilities)
+            .field("server_capabilities", &*self.server.capabilities.read())
             .field("initialized", &self.initialized)
             .field("info", &self.info)
             .finish()
     }
 }
 
-impl<T: Transport> Client<T> {
+/// A client over a transport chosen at runtime.
+///
+/// Useful when the transport depends on configuration (e.g. the URL scheme) and
+/// clients over different transports need to share one type.
+pub type BoxedClient = Client<BoxedTransport>;
+
+impl BoxedClient {
+    /// Create a new client over a boxed transport.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::{BoxedClient, StdioTransport, Transport};
+    ///
+    /// let transport: Box<dyn Transport> = Box::new(StdioTransport::new());
+    /// let client = BoxedClient::new_boxed(transport);
+    /// ```
+    pub fn new_boxed(transport: BoxedTransport) -> Self {
+        Self::new(transport)
+    }
+}
+
+impl<T: Transport + 'static> Client<T> {
     /// Create a new client with the given transport.
     ///
     /// Uses default client information with the name "pmcp-client" and the
@@ -156,13 +217,24 @@ impl<T: Transport> Client<T> {
             transport: Arc::new(RwLock::new(transport)),
             protocol: Arc::new(RwLock::new(Protocol::new(ProtocolOptions::default()))),
             capabilities: None,
-            server_capabilities: None,
+            server: router::ServerState::default(),
             server_version: None,
+            protocol_version: None,
             instructions: None,
             initialized: false,
             info: client_info,
-            notification_tx: None,
-            active_requests: Arc::new(RwLock::new(HashMap::new())),
+            notifications: Arc::default(),
+            elicitations: Arc::default(),
+            active_requests: Arc::new(pending::PendingRequests::new(
+                crate::shared::DEFAULT_MAX_PENDING,
+                None,
+            )),
+            router: Arc::default(),
+            subscriptions: Arc::new(RwLock::new(Vec::new())),
+            reconnect_manager: None,
+            ready_queue: None,
+            closed: Arc::new(AtomicBool::new(false)),
+            oauth: None,
         }
     }
 
@@ -172,7 +244,9 @@ impl<T: Transport> Client<T> {
     ///
     /// ```rust
     /// use pmcp::{Client, StdioTransport, Implementation};
-    /// use pmcp::shared::ProtocolOptions;
+    /// use pmcp::shared::protocol::{
+    ///     FieldCase, ProtocolOptions, Utf8Mode, DEFAULT_MAX_JSON_DEPTH, DEFAULT_MAX_PENDING,
+    /// };
     ///
     /// // Custom options for high-throughput scenarios
     /// let options = ProtocolOptions {
@@ -181,6 +255,12 @@ impl<T: Transport> Client<T> {
     ///         "notifications/progress".to_string(),
     ///         "notifications/message".to_string(),
     ///     ],
+    ///     arbitrary_precision: false,
+    ///     field_case: FieldCase::Strict,
+    ///     utf8: Utf8Mode::Strict,
+    ///     max_pending: DEFAULT_MAX_PENDING,
+    ///     max_json_depth: DEFAULT_MAX_JSON_DEPTH,
+    ///     pending_timeout: None,
     /// };
     ///
     /// let transport = StdioTransport::new();
@@ -192,21 +272,35 @@ impl<T: Transport> Client<T> {
     /// let client = Client::with_options(transport, client_info, options);
     /// ```
     pub fn with_options(
-        transport: T,
+        mut transport: T,
         client_info: Implementation,
         options: ProtocolOptions,
     ) -> Self {
+        transport.set_utf8_mode(options.utf8);
+        transport.set_max_json_depth(options.max_json_depth);
+        let active_requests = Arc::new(pending::PendingRequests::new(
+            options.max_pending,
+            options.pending_timeout,
+        ));
         Self {
             transport: Arc::new(RwLock::new(transport)),
             protocol: Arc::new(RwLock::new(Protocol::new(options))),
             capabilities: None,
-            server_capabilities: None,
+            server: router::ServerState::default(),
             server_version: None,
+            protocol_version: None,
             instructions: None,
             initialized: false,
             info: client_info,
-            notification_tx: None,
-            active_requests: Arc::new(RwLock::new(HashMap::new())),
+            notifications: Arc::default(),
+            elicitations: Arc::default(),
+            active_requests,
+            router: Arc::default(),
+            subscriptions: Arc::new(RwLock::new(Vec::new())),
+            reconnect_manager: None,
+            ready_queue: None,
+            closed: Arc::new(AtomicBool::new(false)),
+            oauth: None,
         }
     }
 
@@ -249,6 +343,23 @@ impl<T: Transport> Client<T> {
             return Err(Error::InvalidState("Client already initialized".into()));
         }
 
+        let result = self.send_initialize(capabilities).await;
+        if let Some(queue) = &self.ready_queue {
+            match &result {
+                Ok(init_result) => queue.ready(init_result.capabilities.clone()),
+                Err(e) => queue.failed(e),
+            }
+        }
+        if let (Ok(init_result), Some(manager)) = (&result, &self.reconnect_manager) {
+            manager.on_capabilities(&init_result.capabilities).await;
+        }
+        result
+    }
+
+    async fn send_initialize(
+        &mut self,
+        capabilities: ClientCapabilities,
+    ) -> Result<InitializeResult> {
         self.capabilities = Some(capabilities.clone());
 
         // Send initialize request
@@ -275,10 +386,12 @@ impl<T: Transport> Client<T> {
                         )));
                     }
 
-                    self.server_capabilities = Some(init_result.capabilities.clone());
+                    *self.server.capabilities.write() = Some(init_result.capabilities.clone());
                     self.server_version = Some(init_result.server_info.clone());
+                    self.protocol_version = Some(init_result.protocol_version.clone());
                     self.instructions.clone_from(&init_result.instructions);
                     self.initialized = true;
+                    self.router.session_restored();
 
                     // Send initialized notification
                     self.send_notification(Notification::Client(ClientNotification::Initialized))
@@ -296,8 +409,11 @@ impl<T: Transport> Client<T> {
     }
 
     /// Get server capabilities after initialization.
-    pub fn get_server_capabilities(&self) -> Option<&ServerCapabilities> {
-        self.server_capabilities.as_ref()
+    ///
+    /// Reflects capability changes the server announced since, once the client has
+    /// read them.
+    pub fn get_server_capabilities(&self) -> Option<ServerCapabilities> {
+        self.server.capabilities.read().clone()
     }
 
     /// Get server version information after initialization.
@@ -305,6 +421,13 @@ impl<T: Transport> Client<T> {
         self.server_version.as_ref()
     }
 
+    /// Get the protocol version negotiated with the server during initialization.
+    ///
+    /// Useful for branching on features that only exist in newer protocol versions.
+    pub fn protocol_version(&self) -> Option<&ProtocolVersion> {
+        self.protocol_version.as_ref()
+    }
+
     /// Get server instructions after initialization.
     pub fn get_instructions(&self) -> Option<&str> {
         self.instructions.as_deref()
@@ -312,7 +435,7 @@ impl<T: Transport> Client<T> {
 
     /// Send a ping to the server.
     pub async fn ping(&self) -> Result<()> {
-        self.ensure_initialized()?;
+        self.ensure_initialized().await?;
         let request = Request::Client(Box::new(ClientRequest::Ping));
         let request_id = RequestId::String(Uuid::new_v4().to_string());
         let response = self.send_request(request_id, request).await?;
@@ -327,8 +450,8 @@ impl<T: Transport> Client<T> {
 
     /// Set the logging level on the server.
     pub async fn set_logging_level(&self, level: LoggingLevel) -> Result<()> {
-        self.ensure_initialized()?;
-        self.assert_capability("logging", "logging/setLevel")?;
+        self.ensure_initialized().await?;
+        self.assert_capability("logging/setLevel").await?;
 
         let request = Request::Client(Box::new(ClientRequest::SetLoggingLevel { level }));
         let request_id = RequestId::String(Uuid::new_v4().to_string());
@@ -372,8 +495,8 @@ impl<T: Transport> Client<T> {
     ///
     /// * `cursor` - Optional pagination cursor for retrieving additional results
     pub async fn list_tools(&self, cursor: Option<String>) -> Result<ListToolsResult> {
-        self.ensure_initialized()?;
-        self.assert_capability("tools", "tools/list")?;
+        self.ensure_initialized().await?;
+        self.assert_capability("tools/list").await?;
 
         let request = Request::Client(Box::new(ClientRequest::ListTools(ListToolsRequest {
             cursor,
@@ -391,6 +514,80 @@ impl<T: Transport> Client<T> {
         }
     }
 
+    /// Stream all tools, following pagination cursors automatically.
+    ///
+    /// Pages are fetched lazily as the stream is polled. At most
+    /// [`MAX_AUTO_PAGES`] pages are requested; past that the stream yields an
+    /// error instead of looping on a misbehaving server.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::{Client, StdioTransport, ClientCapabilities};
+    /// use futures::TryStreamExt;
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let transport = StdioTransport::new();
+    /// let mut client = Client::new(transport);
+    /// client.initialize(ClientCapabilities::default()).await?;
+    ///
+    /// let tools: Vec<_> = client.tools_stream().try_collect().await?;
+    /// println!("Server exposes {} tools", tools.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tools_stream(&self) -> impl Stream<Item = Result<crate::types::ToolInfo>> + '_ {
+        paginate(move |cursor| async move {
+            let page = self.list_tools(cursor).await?;
+            Ok((page.tools, page.next_cursor))
+        })
+    }
+
+    /// Validate tool arguments against the tool's input schema without calling it.
+    ///
+    /// Schemas are fetched with `tools/list` the first time they are needed and
+    /// cached per tool, until the server announces that its tools changed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::{Client, StdioTransport, ClientCapabilities};
+    /// use serde_json::json;
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let mut client = Client::new(StdioTransport::new());
+    /// client.initialize(ClientCapabilities::default()).await?;
+    ///
+    /// let args = json!({"query": "rust"});
+    /// client.validate_tool_args("search", &args).await?;
+    /// let result = client.call_tool("search".to_string(), args).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Validation`] listing every violation, in the same format the
+    /// server uses, or a not found error if the server has no such tool. Schemas
+    /// using keywords that [`validate_json_schema`](crate::utils::validation::validate_json_schema)
+    /// does not enforce are reported as validation errors too.
+    pub async fn validate_tool_args(&self, name: &str, args: &serde_json::Value) -> Result<()> {
+        let cached = self.server.tool_schemas.read().await.get(name).cloned();
+        let schema = match cached {
+            Some(schema) => schema,
+            None => {
+                let tools: Vec<_> = self.tools_stream().try_collect().await?;
+                let mut schemas = self.server.tool_schemas.write().await;
+                schemas.extend(tools.into_iter().map(|tool| (tool.name, tool.input_schema)));
+                schemas
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| Error::not_found(format!("Tool '{}' not found", name)))?
+            },
+        };
+        crate::utils::validation::validate_json_schema(&schema, args)
+    }
+
     /// Call a tool.
     ///
     /// Invokes a server-provided tool with the specified name and arguments.
@@ -453,15 +650,291 @@ impl<T: Transport> Client<T> {
         name: String,
         arguments: serde_json::Value,
     ) -> Result<CallToolResult> {
-        self.ensure_initialized()?;
-        self.assert_capability("tools", "tools/call")?;
+        self.call_tool_with_options(name, arguments, RequestOptions::default())
+            .await
+    }
 
-        let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest {
-            name,
-            arguments,
-        })));
-        let request_id = RequestId::String(Uuid::new_v4().to_string());
-        let response = self.send_request(request_id, request).await?;
+    /// Call a tool with per-request options.
+    ///
+    /// `options.timeout` overrides how long to wait for the result, which otherwise
+    /// is [`ProtocolOptions::pending_timeout`] or, if that is unset,
+    /// [`DEFAULT_REQUEST_TIMEOUT_MS`](crate::DEFAULT_REQUEST_TIMEOUT_MS). Once it
+    /// expires the server is sent a cancellation notification for the call.
+    /// `options.on_progress` is not used.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::{Client, StdioTransport, ClientCapabilities};
+    /// use pmcp::shared::RequestOptions;
+    /// use serde_json::json;
+    /// use std::time::Duration;
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let mut client = Client::new(StdioTransport::new());
+    /// client.initialize(ClientCapabilities::default()).await?;
+    ///
+    /// let options = RequestOptions {
+    ///     timeout: Some(Duration::from_secs(300)),
+    ///     ..Default::default()
+    /// };
+    /// let result = client
+    ///     .call_tool_with_options("build".to_string(), json!({}), options)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Timeout`] if no response arrives in time, and otherwise the
+    /// same errors as [`Client::call_tool`].
+    pub async fn call_tool_with_options(
+        &self,
+        name: String,
+        arguments: serde_json::Value,
+        options: RequestOptions,
+    ) -> Result<CallToolResult> {
+        self.send_call_tool(
+            CallToolRequest::new(name, arguments),
+            RequestId::String(Uuid::new_v4().to_string()),
+            &options,
+        )
+        .await
+    }
+
+    /// Call a tool with typed arguments and result.
+    ///
+    /// `args` is serialized as the tool's arguments, so it can be the same type a
+    /// `#[tool]` handler takes as its parameters. The result must be a single text
+    /// item holding JSON, which is deserialized into `R`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::{Client, StdioTransport, ClientCapabilities};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Serialize)]
+    /// struct AddArgs {
+    ///     a: i64,
+    ///     b: i64,
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Sum {
+    ///     sum: i64,
+    /// }
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let mut client = Client::new(StdioTransport::new());
+    /// client.initialize(ClientCapabilities::default()).await?;
+    ///
+    /// let result: Sum = client.call_tool_typed("add", AddArgs { a: 2, b: 3 }).await?;
+    /// assert_eq!(result.sum, 5);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error if `args` cannot be serialized, an internal error
+    /// with the tool's message if the tool reports an error, a parse error if the
+    /// result is not a single JSON text item matching `R`, and otherwise the same
+    /// errors as [`Client::call_tool`].
+    pub async fn call_tool_typed<A, R>(&self, name: impl Into<String>, args: A) -> Result<R>
+    where
+        A: Serialize,
+        R: DeserializeOwned,
+    {
+        let name = name.into();
+        let arguments = serde_json::to_value(args)
+            .map_err(|e| Error::validation(format!("Invalid arguments for '{}': {}", name, e)))?;
+        let result = self.call_tool(name.clone(), arguments).await?;
+        if result.is_error {
+            return Err(Error::internal(format!(
+                "Tool '{}' failed: {}",
+                name,
+                result.text().unwrap_or_default()
+            )));
+        }
+        match result.content.as_slice() {
+            [Content::Text { text }] => serde_json::from_str(text).map_err(|e| {
+                Error::parse(format!("Unexpected result from tool '{}': {}", name, e))
+            }),
+            _ => Err(Error::parse(format!(
+                "Expected a single text item in the result of tool '{}'",
+                name
+            ))),
+        }
+    }
+
+    /// Call a tool, giving up after `timeout`.
+    ///
+    /// The deadline is sent to the server in `_meta.deadline`, so the server can
+    /// cancel the tool once the client has stopped waiting for it. The server is also
+    /// sent a cancellation notification when the timeout expires.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::{Client, StdioTransport, ClientCapabilities};
+    /// use serde_json::json;
+    /// use std::time::Duration;
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let mut client = Client::new(StdioTransport::new());
+    /// client.initialize(ClientCapabilities::default()).await?;
+    ///
+    /// let result = client.call_tool_with_timeout(
+    ///     "slow_search".to_string(),
+    ///     json!({"query": "rust"}),
+    ///     Duration::from_secs(5),
+    /// ).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Timeout`] if no response arrives within `timeout`, and
+    /// otherwise the same errors as [`Client::call_tool`].
+    pub async fn call_tool_with_timeout(
+        &self,
+        name: String,
+        arguments: serde_json::Value,
+        timeout: std::time::Duration,
+    ) -> Result<CallToolResult> {
+        let deadline = chrono::Duration::from_std(timeout)
+            .ok()
+            .and_then(|timeout| chrono::Utc::now().checked_add_signed(timeout));
+        let mut request = CallToolRequest::new(name, arguments);
+        request.meta = Some(RequestMeta {
+            deadline,
+            ..Default::default()
+        });
+
+        let options = RequestOptions {
+            timeout: Some(timeout),
+            ..Default::default()
+        };
+        self.send_call_tool(
+            request,
+            RequestId::String(Uuid::new_v4().to_string()),
+            &options,
+        )
+        .await
+    }
+
+    /// Call a tool whose arguments are too large to send in one message.
+    ///
+    /// `arguments` yields consecutive pieces of the arguments' JSON text. Each piece
+    /// is sent as it is produced in a `notifications/tools/argumentChunk`
+    /// notification, and the server joins them before invoking the tool (see
+    /// [`ArgumentStream`]). If `arguments` yields an error, the stream is cancelled and
+    /// the server discards what it received.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::{Client, StdioTransport, ClientCapabilities};
+    /// use futures::stream;
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let mut client = Client::new(StdioTransport::new());
+    /// client.initialize(ClientCapabilities::default()).await?;
+    ///
+    /// let contents = "x".repeat(10 * 1024 * 1024);
+    /// let json = serde_json::json!({"path": "big.txt", "contents": contents}).to_string();
+    /// // Split on characters, as a byte split could cut one in half
+    /// let chars: Vec<char> = json.chars().collect();
+    /// let pieces: Vec<pmcp::Result<String>> = chars
+    ///     .chunks(64 * 1024)
+    ///     .map(|piece| Ok(piece.iter().collect()))
+    ///     .collect();
+    ///
+    /// let result = client
+    ///     .call_tool_streaming("upload".to_string(), stream::iter(pieces))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error yielded by `arguments`, a validation error if it
+    /// yields nothing, and otherwise the same errors as [`Client::call_tool`].
+    pub async fn call_tool_streaming<S>(&self, name: String, arguments: S) -> Result<CallToolResult>
+    where
+        S: Stream<Item = Result<String>> + Send,
+    {
+        self.ensure_initialized().await?;
+        self.assert_capability("tools/call").await?;
+
+        let stream_id = Uuid::new_v4().to_string();
+        let mut chunks = 0;
+        futures::pin_mut!(arguments);
+        while let Some(data) = arguments.next().await {
+            let data = match data {
+                Ok(data) => data,
+                Err(error) => {
+                    if chunks > 0 {
+                        // The request ID of the call is the stream ID
+                        let _ = self
+                            .send_notification(Notification::Client(ClientNotification::Cancelled(
+                                CancelledNotification {
+                                    request_id: RequestId::String(stream_id),
+                                    reason: Some(format!("Argument stream failed: {}", error)),
+                                },
+                            )))
+                            .await;
+                    }
+                    return Err(error);
+                },
+            };
+            self.send_notification(Notification::Client(ClientNotification::ArgumentChunk(
+                ArgumentChunk {
+                    stream_id: stream_id.clone(),
+                    seq: chunks,
+                    data,
+                },
+            )))
+            .await?;
+            chunks += 1;
+        }
+        if chunks == 0 {
+            return Err(Error::validation("Argument stream yielded no data"));
+        }
+
+        let mut request = CallToolRequest::new(name, serde_json::json!({}));
+        request.meta = Some(RequestMeta {
+            argument_stream: Some(ArgumentStream {
+                stream_id: stream_id.clone(),
+                chunks,
+            }),
+            ..Default::default()
+        });
+        self.send_call_tool(
+            request,
+            RequestId::String(stream_id),
+            &RequestOptions::default(),
+        )
+        .await
+    }
+
+    async fn send_call_tool(
+        &self,
+        request: CallToolRequest,
+        request_id: RequestId,
+        options: &RequestOptions,
+    ) -> Result<CallToolResult> {
+        self.ensure_initialized().await?;
+        self.assert_capability("tools/call").await?;
+
+        let request = Request::Client(Box::new(ClientRequest::CallTool(request)));
+        let response = self
+            .send_request_with_options(request_id, request, options)
+            .await?;
 
         match response.payload {
             crate::types::jsonrpc::ResponsePayload::Result(result) => {
@@ -520,8 +993,8 @@ impl<T: Transport> Client<T> {
     /// - The server doesn't support prompts
     /// - Network or protocol errors occur
     pub async fn list_prompts(&self, cursor: Option<String>) -> Result<ListPromptsResult> {
-        self.ensure_initialized()?;
-        self.assert_capability("prompts", "prompts/list")?;
+        self.ensure_initialized().await?;
+        self.assert_capability("prompts/list").await?;
 
         let request = Request::Client(Box::new(ClientRequest::ListPrompts(ListPromptsRequest {
             cursor,
@@ -539,6 +1012,16 @@ impl<T: Transport> Client<T> {
         }
     }
 
+    /// Stream all prompts, following pagination cursors automatically.
+    ///
+    /// See [`Client::tools_stream`] for the paging behaviour.
+    pub fn prompts_stream(&self) -> impl Stream<Item = Result<crate::types::PromptInfo>> + '_ {
+        paginate(move |cursor| async move {
+            let page = self.list_prompts(cursor).await?;
+            Ok((page.prompts, page.next_cursor))
+        })
+    }
+
     /// Get a prompt.
     ///
     /// Retrieves a specific prompt from the server with the provided arguments.
@@ -576,11 +1059,13 @@ impl<T: Transport> Client<T> {
     /// // Process the prompt messages
     /// for message in prompt_result.messages {
     ///     println!("Role: {}", message.role);
-    ///     match &message.content {
-    ///         pmcp::Content::Text { text } => {
-    ///             println!("Content: {}", text);
+    ///     for content in &message.content {
+    ///         match content {
+    ///             pmcp::Content::Text { text } => {
+    ///                 println!("Content: {}", text);
+    ///             }
+    ///             _ => println!("Non-text content"),
     ///         }
-    ///         _ => println!("Non-text content"),
     ///     }
     /// }
     /// # Ok(())
@@ -600,12 +1085,13 @@ impl<T: Transport> Client<T> {
         name: String,
         arguments: HashMap<String, String>,
     ) -> Result<GetPromptResult> {
-        self.ensure_initialized()?;
-        self.assert_capability("prompts", "prompts/get")?;
+        self.ensure_initialized().await?;
+        self.assert_capability("prompts/get").await?;
 
         let request = Request::Client(Box::new(ClientRequest::GetPrompt(GetPromptRequest {
             name,
             arguments,
+            meta: None,
         })));
         let request_id = RequestId::String(Uuid::new_v4().to_string());
         let response = self.send_request(request_id, request).await?;
@@ -661,11 +1147,11 @@ impl<T: Transport> Client<T> {
     /// - The server doesn't support resources
     /// - Network or protocol errors occur
     pub async fn list_resources(&self, cursor: Option<String>) -> Result<ListResourcesResult> {
-        self.ensure_initialized()?;
-        self.assert_capability("resources", "resources/list")?;
+        self.ensure_initialized().await?;
+        self.assert_capability("resources/list").await?;
 
         let request = Request::Client(Box::new(ClientRequest::ListResources(
-            ListResourcesRequest { cursor },
+            ListResourcesRequest { cursor, meta: None },
         )));
         let request_id = RequestId::String(Uuid::new_v4().to_string());
         let response = self.send_request(request_id, request).await?;
@@ -680,6 +1166,16 @@ impl<T: Transport> Client<T> {
         }
     }
 
+    /// Stream all resources, following pagination cursors automatically.
+    ///
+    /// See [`Client::tools_stream`] for the paging behaviour.
+    pub fn resources_stream(&self) -> impl Stream<Item = Result<crate::types::ResourceInfo>> + '_ {
+        paginate(move |cursor| async move {
+            let page = self.list_resources(cursor).await?;
+            Ok((page.resources, page.next_cursor))
+        })
+    }
+
     /// List resource templates.
     ///
     /// Retrieves information about all resource templates available on the server.
@@ -721,11 +1217,11 @@ impl<T: Transport> Client<T> {
         &self,
         cursor: Option<String>,
     ) -> Result<ListResourceTemplatesResult> {
-        self.ensure_initialized()?;
-        self.assert_capability("resources", "resources/templates/list")?;
+        self.ensure_initialized().await?;
+        self.assert_capability("resources/templates/list").await?;
 
         let request = Request::Client(Box::new(ClientRequest::ListResourceTemplates(
-            ListResourceTemplatesRequest { cursor },
+            ListResourceTemplatesRequest { cursor, meta: None },
         )));
         let request_id = RequestId::String(Uuid::new_v4().to_string());
         let response = self.send_request(request_id, request).await?;
@@ -785,33 +1281,15 @@ impl<T: Transport> Client<T> {
     /// - Access to the resource is denied
     /// - Network or protocol errors occur
     pub async fn read_resource(&self, uri: String) -> Result<ReadResourceResult> {
-        self.ensure_initialized()?;
-        self.assert_capability("resources", "resources/read")?;
-
-        let request = Request::Client(Box::new(ClientRequest::ReadResource(ReadResourceRequest {
-            uri,
-        })));
-        let request_id = RequestId::String(Uuid::new_v4().to_string());
-        let response = self.send_request(request_id, request).await?;
-
-        match response.payload {
-            crate::types::jsonrpc::ResponsePayload::Result(result) => {
-                serde_json::from_value(result).map_err(|e| Error::parse(e.to_string()))
-            },
-            crate::types::jsonrpc::ResponsePayload::Error(error) => {
-                Err(Error::from_jsonrpc_error(error))
-            },
-        }
+        self.send_read_resource(ReadResourceRequest::new(uri)).await
     }
 
-    /// Subscribe to resource updates.
-    ///
-    /// Subscribes to receive notifications when a resource changes.
-    /// The server will send notifications when the subscribed resource is modified.
-    ///
-    /// # Arguments
+    /// Read a resource unless the copy the client has is still current.
     ///
-    /// * `uri` - The URI of the resource to subscribe to
+    /// `known_hash` is the [`content_hash`](ReadResourceResult::content_hash) of the
+    /// copy. Returns `None` if the resource still has that hash, so unchanged
+    /// resources are not transferred again. Servers without conditional reads always
+    /// return the contents.
     ///
     /// # Examples
     ///
@@ -823,75 +1301,290 @@ impl<T: Transport> Client<T> {
     /// let mut client = Client::new(transport);
     /// client.initialize(ClientCapabilities::default()).await?;
     ///
-    /// // Subscribe to a configuration file
-    /// client.subscribe_resource("file://config/settings.json".to_string()).await?;
-    ///
-    /// // Now the client will receive notifications when settings.json changes
-    /// // Handle notifications in your event loop
+    /// let cached = client.read_resource("file://data.csv".to_string()).await?;
+    /// if let Some(hash) = cached.content_hash.clone() {
+    ///     match client.read_resource_if_changed("file://data.csv".to_string(), hash).await? {
+    ///         Some(fresh) => println!("Changed: {} contents", fresh.contents.len()),
+    ///         None => println!("Cached copy is current"),
+    ///     }
+    /// }
     /// # Ok(())
     /// # }
     /// ```
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - The client is not initialized
-    /// - The server doesn't support resource subscriptions
-    /// - The resource URI doesn't exist
-    /// - Network or protocol errors occur
-    pub async fn subscribe_resource(&self, uri: String) -> Result<()> {
-        self.ensure_initialized()?;
-        self.assert_capability("resources", "resources/subscribe")?;
+    /// Returns the same errors as [`read_resource`](Self::read_resource).
+    pub async fn read_resource_if_changed(
+        &self,
+        uri: String,
+        known_hash: String,
+    ) -> Result<Option<ReadResourceResult>> {
+        let result = self
+            .send_read_resource(ReadResourceRequest {
+                uri,
+                meta: Some(RequestMeta {
+                    if_none_match: Some(known_hash),
+                    ..Default::default()
+                }),
+            })
+            .await?;
+        Ok((!result.not_modified).then_some(result))
+    }
 
-        // Check if server supports subscriptions
-        if let Some(resources) = &self
-            .server_capabilities
-            .as_ref()
-            .and_then(|c| c.resources.as_ref())
-        {
-            if !resources.subscribe.unwrap_or(false) {
-                return Err(Error::capability(
-                    "Server does not support resource subscriptions",
-                ));
-            }
-        }
+    async fn send_read_resource(&self, request: ReadResourceRequest) -> Result<ReadResourceResult> {
+        self.ensure_initialized().await?;
+        self.assert_capability("resources/read").await?;
 
-        let request = Request::Client(Box::new(ClientRequest::Subscribe(SubscribeRequest { uri })));
+        let request = Request::Client(Box::new(ClientRequest::ReadResource(request)));
         let request_id = RequestId::String(Uuid::new_v4().to_string());
         let response = self.send_request(request_id, request).await?;
 
         match response.payload {
-            crate::types::jsonrpc::ResponsePayload::Result(_) => Ok(()),
+            crate::types::jsonrpc::ResponsePayload::Result(result) => {
+                serde_json::from_value(result).map_err(|e| Error::parse(e.to_string()))
+            },
             crate::types::jsonrpc::ResponsePayload::Error(error) => {
                 Err(Error::from_jsonrpc_error(error))
             },
         }
     }
 
-    /// Unsubscribe from resource updates.
-    ///
-    /// Unsubscribes from notifications for a previously subscribed resource.
-    /// After unsubscribing, the client will no longer receive change notifications.
+    /// Resolve an embedded resource reference.
     ///
-    /// # Arguments
-    ///
-    /// * `uri` - The URI of the resource to unsubscribe from
+    /// Tools and prompts can return [`Content::Resource`] pointing at a large
+    /// resource instead of inlining it. This reads the referenced URI so the
+    /// content can be fetched on demand.
     ///
     /// # Examples
     ///
     /// ```rust,no_run
-    /// use pmcp::{Client, StdioTransport, ClientCapabilities};
+    /// use pmcp::{Client, StdioTransport, ClientCapabilities, Content};
+    /// use serde_json::json;
     ///
     /// # async fn example() -> pmcp::Result<()> {
     /// let transport = StdioTransport::new();
     /// let mut client = Client::new(transport);
     /// client.initialize(ClientCapabilities::default()).await?;
     ///
-    /// // Subscribe to a resource
-    /// client.subscribe_resource("file://config/settings.json".to_string()).await?;
-    ///
-    /// // Later, unsubscribe when no longer needed
-    /// client.unsubscribe_resource("file://config/settings.json".to_string()).await?;
+    /// let result = client.call_tool("export".to_string(), json!({})).await?;
+    /// for content in &result.content {
+    ///     if let Content::Resource { .. } = content {
+    ///         let resource = client.resolve_content(content).await?;
+    ///         println!("Resolved {} content items", resource.contents.len());
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `content` is not a [`Content::Resource`], or for any
+    /// reason [`Client::read_resource`] fails.
+    pub async fn resolve_content(&self, content: &Content) -> Result<ReadResourceResult> {
+        match content {
+            Content::Resource { uri, .. } => self.read_resource(uri.clone()).await,
+            _ => Err(Error::invalid_params(
+                "Only resource content can be resolved",
+            )),
+        }
+    }
+
+    /// Receive the notifications the server sends, such as resource updates and
+    /// progress.
+    ///
+    /// Notifications are read by the background loop that routes responses to their
+    /// requests, which keeps reading from the transport while the receiver is alive
+    /// rather than only while requests wait for a response. The transport must
+    /// therefore have a cancel safe [`receive`](Transport::receive). Replaces the
+    /// receiver of an earlier call, also made on a clone. Up to 256 notifications are
+    /// kept for a receiver that falls behind; later ones are dropped until it catches
+    /// up.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::types::{Notification, ServerNotification};
+    /// use pmcp::{Client, ClientCapabilities, StdioTransport};
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let mut client = Client::new(StdioTransport::new());
+    /// client.initialize(ClientCapabilities::default()).await?;
+    ///
+    /// let mut notifications = client.notifications();
+    /// client.subscribe_resource("file:///config.json".to_string()).await?;
+    /// while let Some(notification) = notifications.recv().await {
+    ///     if let Notification::Server(ServerNotification::ResourceUpdated(updated)) = notification {
+    ///         println!("{} changed", updated.uri);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn notifications(&self) -> mpsc::Receiver<Notification> {
+        let rx = self.notifications.subscribe();
+        self.start_receiving();
+        rx
+    }
+
+    /// Receive the requests in which the server asks the user for input.
+    ///
+    /// Like [`Client::notifications`], the requests are read by the background loop,
+    /// which keeps reading while the receiver is alive, and a later call replaces the
+    /// receiver of an earlier one. Answer each request with
+    /// [`Client::respond_elicitation`] before the elicitation timeout (see
+    /// [`ClientBuilder::elicitation_timeout`]) runs out. Requests arriving while nobody
+    /// receives them are dropped, leaving the server to time out.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::types::elicitation::ElicitInputResponse;
+    /// use pmcp::{Client, ClientCapabilities, StdioTransport};
+    /// use serde_json::json;
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let mut client = Client::new(StdioTransport::new());
+    /// client.initialize(ClientCapabilities::default()).await?;
+    ///
+    /// let mut elicitations = client.elicitation_requests();
+    /// while let Some(request) = elicitations.recv().await {
+    ///     println!("{}", request.prompt);
+    ///     let response = ElicitInputResponse {
+    ///         elicitation_id: request.elicitation_id.clone(),
+    ///         value: Some(json!("alice")),
+    ///         cancelled: false,
+    ///         error: None,
+    ///     };
+    ///     client.respond_elicitation(&request.elicitation_id, response).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn elicitation_requests(
+        &self,
+    ) -> mpsc::Receiver<crate::types::elicitation::ElicitInputRequest> {
+        let rx = self.elicitations.subscribe();
+        self.start_receiving();
+        rx
+    }
+
+    /// Answer the elicitation `id` received from [`Client::elicitation_requests`].
+    ///
+    /// The response is sent as an `elicitation/response` request, with its
+    /// `elicitation_id` set to `id`. To decline, set `cancelled`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Timeout`] without contacting the server if the elicitation
+    /// timeout ran out, and an invalid params error if `id` is unknown, was already
+    /// answered or was cancelled by the server. Otherwise fails like any request,
+    /// e.g. if the server no longer waits for the answer.
+    pub async fn respond_elicitation(
+        &self,
+        id: &str,
+        mut response: crate::types::elicitation::ElicitInputResponse,
+    ) -> Result<()> {
+        self.ensure_initialized().await?;
+        self.elicitations.take(id)?;
+        response.elicitation_id = id.to_string();
+
+        let request = Request::Client(Box::new(ClientRequest::ElicitInputResponse(response)));
+        let request_id = RequestId::String(Uuid::new_v4().to_string());
+        let response = self.send_request(request_id, request).await?;
+
+        match response.payload {
+            crate::types::jsonrpc::ResponsePayload::Result(_) => Ok(()),
+            crate::types::jsonrpc::ResponsePayload::Error(error) => {
+                Err(Error::from_jsonrpc_error(error))
+            },
+        }
+    }
+
+    /// Subscribe to resource updates.
+    ///
+    /// Subscribes to receive notifications when a resource changes.
+    /// The server will send notifications when the subscribed resource is modified.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of the resource to subscribe to
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::{Client, StdioTransport, ClientCapabilities};
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let transport = StdioTransport::new();
+    /// let mut client = Client::new(transport);
+    /// client.initialize(ClientCapabilities::default()).await?;
+    ///
+    /// // Subscribe to a configuration file
+    /// client.subscribe_resource("file://config/settings.json".to_string()).await?;
+    ///
+    /// // Now the client will receive notifications when settings.json changes,
+    /// // see `Client::notifications`
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The client is not initialized
+    /// - The server doesn't support resource subscriptions
+    /// - The resource URI doesn't exist
+    /// - Network or protocol errors occur
+    pub async fn subscribe_resource(&self, uri: String) -> Result<()> {
+        self.ensure_initialized().await?;
+        self.assert_capability("resources/subscribe").await?;
+
+        let request = Request::Client(Box::new(ClientRequest::Subscribe(SubscribeRequest {
+            uri: uri.clone(),
+        })));
+        let request_id = RequestId::String(Uuid::new_v4().to_string());
+        let response = self.send_request(request_id, request).await?;
+
+        match response.payload {
+            crate::types::jsonrpc::ResponsePayload::Result(_) => {
+                let mut subscriptions = self.subscriptions.write().await;
+                if !subscriptions.contains(&uri) {
+                    subscriptions.push(uri);
+                }
+                Ok(())
+            },
+            crate::types::jsonrpc::ResponsePayload::Error(error) => {
+                Err(Error::from_jsonrpc_error(error))
+            },
+        }
+    }
+
+    /// Unsubscribe from resource updates.
+    ///
+    /// Unsubscribes from notifications for a previously subscribed resource.
+    /// After unsubscribing, the client will no longer receive change notifications.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of the resource to unsubscribe from
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::{Client, StdioTransport, ClientCapabilities};
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let transport = StdioTransport::new();
+    /// let mut client = Client::new(transport);
+    /// client.initialize(ClientCapabilities::default()).await?;
+    ///
+    /// // Subscribe to a resource
+    /// client.subscribe_resource("file://config/settings.json".to_string()).await?;
+    ///
+    /// // Later, unsubscribe when no longer needed
+    /// client.unsubscribe_resource("file://config/settings.json".to_string()).await?;
     /// # Ok(())
     /// # }
     /// ```
@@ -904,27 +1597,121 @@ impl<T: Transport> Client<T> {
     /// - The resource URI was not previously subscribed to
     /// - Network or protocol errors occur
     pub async fn unsubscribe_resource(&self, uri: String) -> Result<()> {
-        self.ensure_initialized()?;
-        self.assert_capability("resources", "resources/unsubscribe")?;
+        self.ensure_initialized().await?;
+        self.assert_capability("resources/unsubscribe").await?;
 
         let request = Request::Client(Box::new(ClientRequest::Unsubscribe(UnsubscribeRequest {
-            uri,
+            uri: uri.clone(),
         })));
         let request_id = RequestId::String(Uuid::new_v4().to_string());
         let response = self.send_request(request_id, request).await?;
 
         match response.payload {
-            crate::types::jsonrpc::ResponsePayload::Result(_) => Ok(()),
+            crate::types::jsonrpc::ResponsePayload::Result(_) => {
+                self.subscriptions.write().await.retain(|s| s != &uri);
+                Ok(())
+            },
             crate::types::jsonrpc::ResponsePayload::Error(error) => {
                 Err(Error::from_jsonrpc_error(error))
             },
         }
     }
 
+    /// Get the resource URIs this client is currently subscribed to.
+    pub async fn active_subscriptions(&self) -> Vec<String> {
+        self.subscriptions.read().await.clone()
+    }
+
+    /// Attach a reconnection manager that is notified when the session is resumed.
+    ///
+    /// See [`Client::resume_session`].
+    pub fn set_reconnect_manager(&mut self, manager: Arc<ReconnectManager>) {
+        self.reconnect_manager = Some(manager);
+    }
+
+    /// Whether the server reported different capabilities on the latest connection
+    /// than on the one before it.
+    ///
+    /// Lets a UI refresh its tool, prompt and resource lists only when needed after
+    /// [`Client::resume_session`]. The snapshot of the previous capabilities is kept in
+    /// the attached [`ReconnectManager`], so this is always `false` without one.
+    pub fn capabilities_changed_since_last_connect(&self) -> bool {
+        self.reconnect_manager
+            .as_ref()
+            .is_some_and(|manager| manager.capabilities_changed())
+    }
+
+    /// Resume the session after the transport has reconnected.
+    ///
+    /// Repeats the initialize handshake with the capabilities passed to the original
+    /// [`Client::initialize`] call, then re-subscribes to every resource that was
+    /// subscribed before the connection dropped. Without this, the server has no record
+    /// of the old subscriptions and updates silently stop arriving.
+    ///
+    /// This is also needed once the server stops answering, e.g. when a hung stdio
+    /// server process is restarted (see [`StdioTransportConfig`](crate::StdioTransportConfig)):
+    /// the requests waiting then fail with [`TransportError::Unresponsive`](crate::error::TransportError::Unresponsive),
+    /// and later ones fail until the session is resumed.
+    ///
+    /// If a [`ReconnectManager`] is attached, it is marked connected and its
+    /// `on_resubscribed` callback fires once the replay completes. Its
+    /// `on_capabilities_changed` callback fires if the server now reports different
+    /// capabilities, see [`Client::capabilities_changed_since_last_connect`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::{Client, StdioTransport, ClientCapabilities};
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let mut client = Client::new(StdioTransport::new());
+    /// client.initialize(ClientCapabilities::default()).await?;
+    /// client.subscribe_resource("file://config/settings.json".to_string()).await?;
+    ///
+    /// // ... the transport drops and reconnects ...
+    ///
+    /// client.resume_session().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The client was never initialized
+    /// - The server rejects the new initialization
+    /// - Re-subscribing to a resource fails
+    pub async fn resume_session(&mut self) -> Result<InitializeResult> {
+        let capabilities = self
+            .capabilities
+            .clone()
+            .ok_or_else(|| Error::InvalidState("Client was never initialized".into()))?;
+
+        self.initialized = false;
+        if let Some(queue) = &self.ready_queue {
+            queue.reset();
+        }
+        let result = self.initialize(capabilities).await?;
+
+        let uris = self.active_subscriptions().await;
+        for uri in &uris {
+            self.subscribe_resource(uri.clone()).await?;
+        }
+
+        if let Some(manager) = &self.reconnect_manager {
+            manager.on_connected().await;
+            manager.on_resubscribed(&uris);
+        }
+
+        Ok(result)
+    }
+
     /// Request completion from the server.
     ///
     /// Requests auto-completion suggestions from the server for a given context.
     /// This is useful for implementing IDE-like features with contextual suggestions.
+    /// Servers send at most 100 values; `has_more` and `total` in the result tell
+    /// whether more are available.
     ///
     /// # Arguments
     ///
@@ -949,12 +1736,16 @@ impl<T: Transport> Client<T> {
     ///         name: "function_name".to_string(),
     ///         value: "calc_".to_string(),
     ///     },
+    ///     meta: None,
     /// };
     ///
-    /// let completions = client.complete(completion_request).await?;
-    /// for completion in completions.completion.values {
+    /// let completions = client.complete(completion_request).await?.completion;
+    /// for completion in &completions.values {
     ///     println!("Suggestion: {}", completion);
     /// }
+    /// if completions.has_more {
+    ///     println!("{:?} suggestions in total, keep typing to narrow them down", completions.total);
+    /// }
     /// # Ok(())
     /// # }
     /// ```
@@ -967,8 +1758,8 @@ impl<T: Transport> Client<T> {
     /// - The completion context is invalid
     /// - Network or protocol errors occur
     pub async fn complete(&self, params: CompleteRequest) -> Result<CompleteResult> {
-        self.ensure_initialized()?;
-        self.assert_capability("completions", "completion/complete")?;
+        self.ensure_initialized().await?;
+        self.assert_capability("completion/complete").await?;
 
         let request = Request::Client(Box::new(ClientRequest::Complete(params)));
         let request_id = RequestId::String(Uuid::new_v4().to_string());
@@ -1048,8 +1839,8 @@ impl<T: Transport> Client<T> {
         &self,
         params: CreateMessageRequest,
     ) -> Result<CreateMessageResult> {
-        self.ensure_initialized()?;
-        self.assert_capability("sampling", "sampling/createMessage")?;
+        self.ensure_initialized().await?;
+        self.assert_capability("sampling/createMessage").await?;
 
         let request = Request::Client(Box::new(ClientRequest::CreateMessage(params)));
         let request_id = RequestId::String(Uuid::new_v4().to_string());
@@ -1099,7 +1890,7 @@ impl<T: Transport> Client<T> {
     /// - The client doesn't support roots list changed notifications
     /// - Network or protocol errors occur
     pub async fn send_roots_list_changed(&self) -> Result<()> {
-        self.ensure_initialized()?;
+        self.ensure_initialized().await?;
         if let Some(roots) = &self.capabilities.as_ref().and_then(|c| c.roots.as_ref()) {
             if roots.list_changed {
                 // OK, we support it
@@ -1151,7 +1942,13 @@ impl<T: Transport> Client<T> {
     /// - Authentication fails
     /// - The server doesn't support authentication
     pub fn authenticate(&self, auth_info: &crate::types::AuthInfo) -> Result<()> {
-        self.ensure_initialized()?;
+        let ready = self
+            .ready_queue
+            .as_ref()
+            .is_some_and(|queue| queue.capabilities().is_some());
+        if !self.initialized && !ready {
+            return Err(Error::InvalidState("Client not initialized".into()));
+        }
 
         // In a real implementation, this would send an authentication request
         // For now, we'll just validate that we can authenticate
@@ -1218,142 +2015,616 @@ impl<T: Transport> Client<T> {
         .await?;
 
         // Cancel any local tracking
-        let sender = self.active_requests.write().await.remove(request_id);
-        if let Some(sender) = sender {
-            let _ = sender.send(());
-        }
+        self.active_requests.abort(request_id, Error::Cancelled);
 
         Ok(())
     }
 
-    /// Send a progress notification.
-    ///
-    /// Sends a progress update for a long-running operation.
-    /// This allows the server or client to track operation progress.
-    ///
-    /// # Arguments
+    /// Cancel every request waiting for a response.
     ///
-    /// * `progress` - The progress notification to send
+    /// Requests from this client and its clones resolve with [`Error::Cancelled`], and
+    /// the server is sent a cancellation notification for each of them. Use this when
+    /// the user abandons a whole agent run. Requests sent while this runs are not
+    /// affected.
     ///
     /// # Examples
     ///
     /// ```rust,no_run
-    /// use pmcp::{Client, StdioTransport, ClientCapabilities, ProgressNotification, RequestId};
+    /// use pmcp::{Client, StdioTransport, ClientCapabilities};
     ///
     /// # async fn example() -> pmcp::Result<()> {
-    /// let transport = StdioTransport::new();
-    /// let mut client = Client::new(transport);
+    /// let mut client = Client::new(StdioTransport::new());
     /// client.initialize(ClientCapabilities::default()).await?;
     ///
-    /// // Send progress update for a file processing operation
-    /// let progress = ProgressNotification {
-    ///     progress_token: pmcp::ProgressToken::String("file-processing".to_string()),
-    ///     progress: 75.0,
-    ///     message: Some("Processing files...".to_string()),
-    /// };
-    ///
-    /// client.send_progress(progress).await?;
+    /// // The user navigated away
+    /// client.cancel_all(Some("Run aborted".to_string())).await?;
     /// # Ok(())
     /// # }
     /// ```
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - Network or protocol errors occur while sending the notification
-    pub async fn send_progress(&self, progress: ProgressNotification) -> Result<()> {
-        self.send_notification(Notification::Progress(progress))
-            .await
-    }
-
-    /// Check if client is initialized.
-    fn ensure_initialized(&self) -> Result<()> {
-        if self.initialized {
-            Ok(())
-        } else {
-            Err(Error::InvalidState("Client not initialized".into()))
+    /// Returns the first error from sending the notifications. The requests are
+    /// cancelled locally either way.
+    pub async fn cancel_all(&self, reason: Option<String>) -> Result<()> {
+        // Abort first: a waiting request holds the transport until its wait is abandoned
+        let cancelled = self.active_requests.abort_all(|| Error::Cancelled);
+
+        let mut result = Ok(());
+        for request_id in cancelled {
+            let sent = self
+                .send_notification(Notification::Cancelled(CancelledNotification {
+                    request_id,
+                    reason: reason.clone(),
+                }))
+                .await;
+            if result.is_ok() {
+                result = sent;
+            }
         }
+        result
     }
 
-    /// Assert that the server has a specific capability.
-    fn assert_capability(&self, capability: &str, method: &str) -> Result<()> {
-        let has_capability = match capability {
-            "tools" => self
-                .server_capabilities
-                .as_ref()
-                .is_some_and(|c| c.tools.is_some()),
-            "prompts" => self
-                .server_capabilities
-                .as_ref()
-                .is_some_and(|c| c.prompts.is_some()),
-            "resources" => self
-                .server_capabilities
-                .as_ref()
-                .is_some_and(|c| c.resources.is_some()),
-            "logging" => self
-                .server_capabilities
-                .as_ref()
-                .is_some_and(|c| c.logging.is_some()),
-            "completions" => self
-                .server_capabilities
-                .as_ref()
-                .is_some_and(|c| c.completions.is_some()),
-            _ => false,
-        };
-
-        if has_capability {
-            Ok(())
-        } else {
-            Err(Error::capability(format!(
-                "Server does not support {} (required for {})",
-                capability, method
-            )))
-        }
+    /// Request statistics, such as how many requests are waiting for a response.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::{ClientBuilder, StdioTransport};
+    ///
+    /// let client = ClientBuilder::new(StdioTransport::new()).max_pending(16).build();
+    /// let stats = client.stats();
+    /// assert_eq!(stats.pending_requests, 0);
+    /// assert_eq!(stats.max_pending, 16);
+    /// ```
+    pub fn stats(&self) -> ClientStats {
+        self.active_requests.stats()
     }
 
-    /// Send a request and wait for response.
-    async fn send_request(
+    /// Send several requests at once, as one JSON-RPC batch if the server accepts
+    /// batches.
+    ///
+    /// Servers advertise batch support with
+    /// [`ServerCapabilities::supports_batch`]; otherwise the requests are sent one after
+    /// another. Either way the results are returned in request order, each request
+    /// succeeding or failing on its own. Responses may arrive as one batch frame or
+    /// separately; stray or duplicate responses are logged and ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::{Client, StdioTransport, ClientCapabilities};
+    /// use pmcp::types::{ClientRequest, ListToolsParams, ListPromptsParams};
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let mut client = Client::new(StdioTransport::new());
+    /// client.initialize(ClientCapabilities::default()).await?;
+    ///
+    /// let results = client
+    ///     .send_batch(vec![
+    ///         ClientRequest::ListTools(ListToolsParams { cursor: None }),
+    ///         ClientRequest::ListPrompts(ListPromptsParams { cursor: None }),
+    ///     ])
+    ///     .await?;
+    /// for result in results {
+    ///     println!("{:?}", result);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the client is not initialized, too many requests are
+    /// pending, or the transport fails. Errors returned by the server for individual
+    /// requests are reported in their result instead.
+    pub async fn send_batch(
         &self,
-        request_id: RequestId,
-        request: Request,
-    ) -> Result<crate::types::JSONRPCResponse> {
-        // Track request for cancellation
-        let (cancel_tx, _cancel_rx) = oneshot::channel();
-        self.active_requests
-            .write()
-            .await
-            .insert(request_id.clone(), cancel_tx);
-
-        // Send request through transport
-        let message = crate::types::TransportMessage::Request {
-            id: request_id.clone(),
-            request,
-        };
-
-        self.transport.write().await.send(message).await?;
-
-        // Wait for response (this would be implemented with proper response routing)
-        // For now, receive next message and assume it's our response
-        let response_message = self.transport.write().await.receive().await?;
-
-        // Remove from active requests
-        self.active_requests.write().await.remove(&request_id);
-
-        match response_message {
-            crate::types::TransportMessage::Response(response) => Ok(response),
-            _ => Err(Error::protocol_msg(
-                "Expected response, got different message type",
-            )),
+        requests: Vec<ClientRequest>,
+    ) -> Result<Vec<Result<serde_json::Value>>> {
+        self.ensure_initialized().await?;
+        self.ensure_open()?;
+        if requests.is_empty() {
+            return Ok(Vec::new());
         }
-    }
+
+        let ids: Vec<_> = requests
+            .iter()
+            .map(|_| RequestId::String(Uuid::new_v4().to_string()))
+            .collect();
+        let mut replies = Vec::with_capacity(ids.len());
+        for id in &ids {
+            match self.active_requests.insert(id.clone()) {
+                Ok(reply) => replies.push(reply),
+                Err(e) => {
+                    for id in &ids {
+                        self.active_requests.remove(id);
+                    }
+                    return Err(e);
+                },
+            }
+        }
+        let messages: Vec<_> = ids
+            .iter()
+            .cloned()
+            .zip(requests)
+            .map(|(id, request)| crate::types::TransportMessage::Request {
+                id,
+                request: Request::Client(Box::new(request)),
+            })
+            .collect();
+        let batch = self
+            .current_server_capabilities()
+            .is_some_and(|capabilities| capabilities.supports_batch());
+
+        let send = async {
+            let mut transport = self.router.lock(&self.transport).await;
+            if batch {
+                transport.send_batch(messages).await
+            } else {
+                for message in messages {
+                    transport.send(message).await?;
+                }
+                Ok(())
+            }
+        };
+        // Any request being cancelled or timing out fails the batch
+        let replies = futures::future::try_join_all(
+            replies
+                .into_iter()
+                .map(|reply| async { Self::replied(reply.await) }),
+        );
+        tokio::pin!(replies);
+        let replied_early = tokio::select! {
+            sent = send => sent.err().map(Err),
+            replied = &mut replies => Some(replied),
+        };
+        let responses = match replied_early {
+            Some(replied) => replied,
+            None => {
+                self.start_receiving();
+                replies.await
+            },
+        };
+        for id in &ids {
+            self.active_requests.remove(id);
+        }
+        let responses = responses.inspect_err(|e| self.check_connection_lost(e))?;
+
+        let field_case = self.protocol.read().await.options().field_case;
+        Ok(responses
+            .into_iter()
+            .map(|response| match response.payload {
+                crate::types::jsonrpc::ResponsePayload::Result(mut result) => {
+                    field_case.normalize(&mut result);
+                    Ok(result)
+                },
+                crate::types::jsonrpc::ResponsePayload::Error(error) => {
+                    Err(Error::from_jsonrpc_error(error))
+                },
+            })
+            .collect())
+    }
+
+    /// Change how long requests wait for a response before failing with
+    /// [`Error::Timeout`].
+    ///
+    /// Applies to requests sent after the call, from this client and its clones;
+    /// requests already waiting keep the timeout they were sent with. Useful for
+    /// adapting the timeout to observed latency. Sets
+    /// [`ProtocolOptions::pending_timeout`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::{Client, StdioTransport};
+    /// use std::time::Duration;
+    ///
+    /// # async fn example() {
+    /// let client = Client::new(StdioTransport::new());
+    /// client.set_timeout(Duration::from_secs(5)).await;
+    /// assert_eq!(client.options().await.pending_timeout, Some(Duration::from_secs(5)));
+    /// # }
+    /// ```
+    pub async fn set_timeout(&self, timeout: std::time::Duration) {
+        let mut protocol = self.protocol.write().await;
+        protocol.options_mut().pending_timeout = Some(timeout);
+        self.active_requests.set_timeout(Some(timeout));
+    }
+
+    /// Current protocol options, including changes made after construction.
+    pub async fn options(&self) -> ProtocolOptions {
+        self.protocol.read().await.options().clone()
+    }
+
+    /// Close the client and its transport.
+    ///
+    /// Requests still waiting for a response (including those issued through clones of
+    /// this client) resolve with [`Error::Cancelled`], then the transport is closed. Use
+    /// this for deterministic shutdown instead of relying on the transport being dropped.
+    /// Afterwards, requests and notifications from clones fail immediately with
+    /// [`TransportError::ConnectionClosed`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::{Client, StdioTransport, ClientCapabilities};
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let mut client = Client::new(StdioTransport::new());
+    /// client.initialize(ClientCapabilities::default()).await?;
+    ///
+    /// // ... use the client ...
+    ///
+    /// client.close().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transport fails to close.
+    pub async fn close(self) -> Result<()> {
+        self.closed.store(true, Ordering::Release);
+        self.active_requests.abort_all(|| Error::Cancelled);
+
+        self.router.lock(&self.transport).await.close().await
+    }
+
+    /// Send a progress notification.
+    ///
+    /// Sends a progress update for a long-running operation.
+    /// This allows the server or client to track operation progress.
+    ///
+    /// # Arguments
+    ///
+    /// * `progress` - The progress notification to send
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::{Client, StdioTransport, ClientCapabilities, ProgressNotification, RequestId};
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let transport = StdioTransport::new();
+    /// let mut client = Client::new(transport);
+    /// client.initialize(ClientCapabilities::default()).await?;
+    ///
+    /// // Send progress update for a file processing operation
+    /// let progress = ProgressNotification {
+    ///     progress_token: pmcp::ProgressToken::String("file-processing".to_string()),
+    ///     progress: 75.0,
+    ///     message: Some("Processing files...".to_string()),
+    /// };
+    ///
+    /// client.send_progress(progress).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Network or protocol errors occur while sending the notification
+    pub async fn send_progress(&self, progress: ProgressNotification) -> Result<()> {
+        self.send_notification(Notification::Progress(progress))
+            .await
+    }
+
+    /// Check if client is initialized, waiting for it when requests are queued.
+    async fn ensure_initialized(&self) -> Result<()> {
+        if self.router.session_lost() {
+            return Err(Error::InvalidState(
+                "Server stopped answering and lost the session; call resume_session to initialize it again"
+                    .into(),
+            ));
+        }
+        if self.initialized {
+            return Ok(());
+        }
+        match &self.ready_queue {
+            Some(queue) => queue.wait().await,
+            None => Err(Error::InvalidState("Client not initialized".into())),
+        }
+    }
+
+    /// Server capabilities, also for clones made before initialize completed.
+    fn current_server_capabilities(&self) -> Option<ServerCapabilities> {
+        // Clones made before initialize completed only see the shared capabilities
+        self.server.capabilities.read().clone().or_else(|| {
+            self.ready_queue
+                .as_ref()
+                .and_then(|queue| queue.capabilities())
+        })
+    }
+
+    /// Check that the server advertised the capability `method` needs before
+    /// sending it.
+    ///
+    /// A missing capability, or a sub-feature missing from a capability the server
+    /// did advertise, such as `subscribe` from `resources`, fails the request. With
+    /// [`ProtocolOptions::enforce_strict_capabilities`] unset, either is only logged
+    /// and the request is sent anyway.
+    async fn assert_capability(&self, method: &str) -> Result<()> {
+        let Some(required) = required_capability(method) else {
+            return Ok(());
+        };
+        let capability = required
+            .split_once('.')
+            .map_or(required, |(parent, _)| parent);
+        let missing = match self
+            .current_server_capabilities()
+            .filter(|c| advertises(c, capability))
+        {
+            Some(c) if advertises(&c, required) => return Ok(()),
+            Some(_) => required,
+            None => capability,
+        };
+
+        let message = format!(
+            "Server does not support {} (required for {})",
+            missing, method
+        );
+        if self
+            .protocol
+            .read()
+            .await
+            .options()
+            .enforce_strict_capabilities
+        {
+            return Err(Error::capability(message));
+        }
+        tracing::warn!("{}; sending anyway", message);
+        Ok(())
+    }
+
+    /// Send a request and wait for response.
+    async fn send_request(
+        &self,
+        request_id: RequestId,
+        request: Request,
+    ) -> Result<crate::types::JSONRPCResponse> {
+        self.send_request_with_options(request_id, request, &RequestOptions::default())
+            .await
+    }
+
+    /// Send a request and wait for its response until `options.timeout`, the
+    /// pending timeout or [`DEFAULT_REQUEST_TIMEOUT_MS`](crate::DEFAULT_REQUEST_TIMEOUT_MS)
+    /// expires, whichever is set first in that order.
+    ///
+    /// With OAuth tokens, a request rejected for its access token is sent once more
+    /// with a refreshed one.
+    async fn send_request_with_options(
+        &self,
+        request_id: RequestId,
+        request: Request,
+        options: &RequestOptions,
+    ) -> Result<crate::types::JSONRPCResponse> {
+        let Some(oauth) = &self.oauth else {
+            return self
+                .send_request_once(request_id, request, options, None)
+                .await;
+        };
+        let token = oauth.access_token(None).await?;
+        let response = self
+            .send_request_once(request_id.clone(), request.clone(), options, Some(&token))
+            .await;
+        if !auth::rejects_token(&response) {
+            return response;
+        }
+        match oauth.access_token(Some(&token)).await {
+            Ok(token) => {
+                self.send_request_once(request_id, request, options, Some(&token))
+                    .await
+            },
+            Err(e) => {
+                tracing::warn!("Failed to refresh the rejected access token: {}", e);
+                response
+            },
+        }
+    }
+
+    /// Send a request once, with the access `token` if given.
+    async fn send_request_once(
+        &self,
+        request_id: RequestId,
+        request: Request,
+        options: &RequestOptions,
+        token: Option<&str>,
+    ) -> Result<crate::types::JSONRPCResponse> {
+        self.ensure_open()?;
+        let timeout = options
+            .timeout
+            .or_else(|| self.active_requests.timeout())
+            .unwrap_or(std::time::Duration::from_millis(
+                crate::DEFAULT_REQUEST_TIMEOUT_MS,
+            ));
+        // Track request for cancellation and timeouts; fails if too many are waiting
+        let mut reply = self.active_requests.insert(request_id.clone())?;
+
+        let message = crate::types::TransportMessage::Request {
+            id: request_id.clone(),
+            request,
+        };
+        // Cancelling the request, closing the client or a timeout also abandons
+        // waiting for the transport
+        let replied_early = tokio::select! {
+            sent = async {
+                let mut transport = self.router.lock(&self.transport).await;
+                if let Some(token) = token {
+                    transport.set_access_token(token);
+                }
+                transport.send(message).await
+            } => {
+                if let Err(e) = sent {
+                    self.active_requests.remove(&request_id);
+                    self.check_connection_lost(&e);
+                    return Err(e);
+                }
+                None
+            },
+            reply = &mut reply => Some(reply),
+        };
+        let reply = match replied_early {
+            Some(reply) => reply,
+            None => {
+                // The receive loop delivers the response with our ID
+                self.start_receiving();
+                match tokio::time::timeout(timeout, reply).await {
+                    Ok(reply) => reply,
+                    Err(_) => {
+                        self.active_requests.remove(&request_id);
+                        Ok(Err(Error::Timeout(
+                            u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX),
+                        )))
+                    },
+                }
+            },
+        };
+        let response = Self::replied(reply);
+        if let Err(Error::Timeout(_)) = response {
+            // Let the server stop working on a request nobody waits for
+            let cancelled = self
+                .send_notification(Notification::Cancelled(CancelledNotification {
+                    request_id: request_id.clone(),
+                    reason: Some("Request timed out".to_string()),
+                }))
+                .await;
+            if let Err(e) = cancelled {
+                tracing::debug!("Failed to cancel timed out request {}: {}", request_id, e);
+            }
+        }
+        let mut response = response?;
+        if let crate::types::jsonrpc::ResponsePayload::Result(result) = &mut response.payload {
+            let field_case = self.protocol.read().await.options().field_case;
+            field_case.normalize(result);
+        }
+        Ok(response)
+    }
+
+    /// Response or error delivered to a waiting request.
+    fn replied(
+        reply: std::result::Result<
+            Result<crate::types::JSONRPCResponse>,
+            tokio::sync::oneshot::error::RecvError,
+        >,
+    ) -> Result<crate::types::JSONRPCResponse> {
+        // Removed without a reply, e.g. by a caller that gave up waiting
+        reply.unwrap_or(Err(Error::Cancelled))
+    }
+
+    /// Start receiving responses for the waiting requests.
+    fn start_receiving(&self) {
+        self.router.start(
+            &self.transport,
+            &self.active_requests,
+            &self.notifications,
+            &self.elicitations,
+            &self.server,
+        );
+    }
 
     /// Send a notification.
     async fn send_notification(&self, notification: Notification) -> Result<()> {
+        self.ensure_open()?;
         let message = crate::types::TransportMessage::Notification(notification);
-        self.transport.write().await.send(message).await
+        self.router
+            .lock(&self.transport)
+            .await
+            .send(message)
+            .await
+            .inspect_err(|e| self.check_connection_lost(e))
+    }
+
+    /// Fail with [`TransportError::ConnectionClosed`] once the client has been closed.
+    fn ensure_open(&self) -> Result<()> {
+        if self.closed.load(Ordering::Acquire) {
+            return Err(TransportError::ConnectionClosed.into());
+        }
+        Ok(())
+    }
+
+    /// Fail every waiting request if `error` shows the connection has dropped.
+    ///
+    /// Requests from clones may be waiting for the transport and would otherwise
+    /// only fail once they get to use it.
+    fn check_connection_lost(&self, error: &Error) {
+        if matches!(error, Error::Transport(TransportError::ConnectionClosed)) {
+            self.active_requests
+                .abort_all(|| TransportError::ConnectionClosed.into());
+        }
+    }
+}
+
+/// The server capability a request method needs, with a sub-feature after a dot
+/// as in `resources.subscribe`.
+fn required_capability(method: &str) -> Option<&'static str> {
+    match method {
+        "tools/list" | "tools/call" => Some("tools"),
+        "prompts/list" | "prompts/get" => Some("prompts"),
+        "resources/list" | "resources/templates/list" | "resources/read" => Some("resources"),
+        "resources/subscribe" | "resources/unsubscribe" => Some("resources.subscribe"),
+        "logging/setLevel" => Some("logging"),
+        "completion/complete" => Some("completions"),
+        "sampling/createMessage" => Some("sampling"),
+        _ => None,
     }
 }
 
+/// Whether `capabilities` advertise `capability`, named as in
+/// [`required_capability`].
+fn advertises(capabilities: &ServerCapabilities, capability: &str) -> bool {
+    let c = capabilities;
+    match capability {
+        "tools" => c.tools.is_some(),
+        "tools.listChanged" => c.tools.as_ref().and_then(|t| t.list_changed) == Some(true),
+        "prompts" => c.prompts.is_some(),
+        "prompts.listChanged" => c.prompts.as_ref().and_then(|p| p.list_changed) == Some(true),
+        "resources" => c.resources.is_some(),
+        "resources.subscribe" => c.resources.as_ref().and_then(|r| r.subscribe) == Some(true),
+        "resources.listChanged" => c.resources.as_ref().and_then(|r| r.list_changed) == Some(true),
+        "logging" => c.logging.is_some(),
+        "completions" => c.completions.is_some(),
+        "sampling" => c.sampling.is_some(),
+        _ => false,
+    }
+}
+
+/// Turn a page-fetching function into a stream of items.
+///
+/// Stops when a page has no `next_cursor`, or errors after [`MAX_AUTO_PAGES`].
+fn paginate<I, F, Fut>(fetch: F) -> impl Stream<Item = Result<I>>
+where
+    F: Fn(Option<String>) -> Fut,
+    Fut: Future<Output = Result<(Vec<I>, Option<String>)>>,
+{
+    // State is the cursor for the next page (`None` once the last page is seen)
+    // and the number of pages fetched so far.
+    stream::try_unfold((Some(None), 0usize), move |(cursor, pages)| {
+        let next = cursor.map(|cursor| {
+            if pages >= MAX_AUTO_PAGES {
+                Err(Error::protocol_msg(format!(
+                    "Pagination exceeded {} pages",
+                    MAX_AUTO_PAGES
+                )))
+            } else {
+                Ok(fetch(cursor))
+            }
+        });
+        async move {
+            let Some(fetch) = next else {
+                return Ok::<_, Error>(None);
+            };
+            let (items, next_cursor) = fetch?.await?;
+            Ok(Some((
+                stream::iter(items.into_iter().map(Ok)),
+                (next_cursor.map(Some), pages + 1),
+            )))
+        }
+    })
+    .try_flatten()
+}
+
 /// Builder for creating clients with custom configuration.
 ///
 /// # Examples
@@ -1390,6 +2661,9 @@ impl<T: Transport> Client<T> {
 pub struct ClientBuilder<T: Transport> {
     transport: T,
     options: ProtocolOptions,
+    queue_until_ready: bool,
+    oauth: Option<Arc<auth::OAuthSession>>,
+    elicitation_timeout: Option<std::time::Duration>,
 }
 
 impl<T: Transport> std::fmt::Debug for ClientBuilder<T> {
@@ -1397,20 +2671,29 @@ impl<T: Transport> std::fmt::Debug for ClientBuilder<T> {
         f.debug_struct("ClientBuilder")
             .field("transport", &"<Transport>")
             .field("options", &self.options)
+            .field("queue_until_ready", &self.queue_until_ready)
+            .field("oauth", &self.oauth.is_some())
+            .field("elicitation_timeout", &self.elicitation_timeout)
             .finish()
     }
 }
 
-impl<T: Transport> ClientBuilder<T> {
+impl<T: Transport + 'static> ClientBuilder<T> {
     /// Create a new client builder.
     pub fn new(transport: T) -> Self {
         Self {
             transport,
             options: ProtocolOptions::default(),
+            queue_until_ready: false,
+            oauth: None,
+            elicitation_timeout: None,
         }
     }
 
-    /// Set whether to enforce strict capabilities.
+    /// Set whether requests need a capability advertised by the server.
+    ///
+    /// See [`ProtocolOptions::enforce_strict_capabilities`]. Enabled by default; disable
+    /// it to try requests against servers with incomplete capability advertisement.
     pub fn enforce_strict_capabilities(mut self, enforce: bool) -> Self {
         self.options.enforce_strict_capabilities = enforce;
         self
@@ -1422,16 +2705,110 @@ impl<T: Transport> ClientBuilder<T> {
         self
     }
 
+    /// Set which field naming is accepted in responses.
+    ///
+    /// Use [`FieldCase::Lenient`] to talk to older servers that send `snake_case`
+    /// field names. Requests are still sent in `camelCase`.
+    pub fn field_case(mut self, field_case: FieldCase) -> Self {
+        self.options.field_case = field_case;
+        self
+    }
+
+    /// Set how invalid UTF-8 in messages from the server is handled.
+    ///
+    /// Use [`Utf8Mode::Lossy`] to accept servers that send malformed text; invalid
+    /// sequences are replaced with U+FFFD instead of the message being rejected.
+    /// Only transports that read raw bytes apply this.
+    pub fn utf8(mut self, mode: Utf8Mode) -> Self {
+        self.options.utf8 = mode;
+        self
+    }
+
+    /// Set the maximum nesting depth of messages from the server.
+    ///
+    /// Deeper messages are rejected with a parse error before deserialization.
+    /// Defaults to [`DEFAULT_MAX_JSON_DEPTH`](crate::shared::DEFAULT_MAX_JSON_DEPTH).
+    /// Only transports that parse raw frames apply this.
+    pub fn max_json_depth(mut self, depth: usize) -> Self {
+        self.options.max_json_depth = depth;
+        self
+    }
+
+    /// Set the maximum number of requests waiting for a response at once.
+    ///
+    /// Further requests fail with [`Error::InvalidState`] until a response arrives.
+    /// Defaults to [`DEFAULT_MAX_PENDING`](crate::shared::DEFAULT_MAX_PENDING).
+    pub fn max_pending(mut self, max_pending: usize) -> Self {
+        self.options.max_pending = max_pending;
+        self
+    }
+
+    /// Fail requests that wait longer than `timeout` for a response with
+    /// [`Error::Timeout`], so a server that stops responding does not leave them
+    /// waiting forever.
+    pub fn pending_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.options.pending_timeout = Some(timeout);
+        self
+    }
+
+    /// Set whether requests issued before `initialize` completes wait for it.
+    ///
+    /// When enabled, requests made through clones of the client while it is
+    /// initializing are held and sent once initialization succeeds, instead of
+    /// failing with [`Error::InvalidState`]. If initialization fails they fail with
+    /// its error, and if every client left is waiting, e.g. because the only client
+    /// was used before calling `initialize`, they fail right away instead of waiting
+    /// forever. At most 64 requests may wait at once; further requests fail
+    /// immediately. Disabled by default.
+    pub fn queue_until_ready(mut self, queue: bool) -> Self {
+        self.queue_until_ready = queue;
+        self
+    }
+
+    /// Authorize requests with OAuth 2.0 tokens.
+    ///
+    /// The access token from `tokens` is given to the transport before each request
+    /// (see [`Transport::set_access_token`]). It is refreshed with the refresh token
+    /// on behalf of `client` when it is about to expire, and when the server rejects
+    /// a request with `AUTHENTICATION_REQUIRED` or `PERMISSION_DENIED`, in which case
+    /// the request is retried once with the new token. HTTP transports report a
+    /// response with status 401 as [`Error::Authentication`], which is retried the
+    /// same way.
+    pub fn with_oauth(mut self, client: OAuthClient, tokens: Arc<auth::TokenStore>) -> Self {
+        self.oauth = Some(Arc::new(auth::OAuthSession { client, tokens }));
+        self
+    }
+
+    /// Set how long an elicitation from the server can be answered.
+    ///
+    /// Answering it later with [`Client::respond_elicitation`] fails with
+    /// [`Error::Timeout`]. Match it to the server's wait for answers; defaults to 5
+    /// minutes, the server's default.
+    pub fn elicitation_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.elicitation_timeout = Some(timeout);
+        self
+    }
+
     /// Build the client.
     pub fn build(self) -> Client<T> {
-        Client::with_options(
+        let mut client = Client::with_options(
             self.transport,
             Implementation {
                 name: "pmcp-client".to_string(),
                 version: env!("CARGO_PKG_VERSION").to_string(),
             },
             self.options,
-        )
+        );
+        client.oauth = self.oauth;
+        if let Some(timeout) = self.elicitation_timeout {
+            client.elicitations = Arc::new(elicitation::Elicitations::new(timeout));
+        }
+        if self.queue_until_ready {
+            client.ready_queue = Some(ready_queue::ReadyHandle::new(
+                ready_queue::DEFAULT_READY_QUEUE_SIZE,
+            ));
+        }
+        client
     }
 }
 
@@ -1441,13 +2818,21 @@ impl<T: Transport> Clone for Client<T> {
             transport: self.transport.clone(),
             protocol: self.protocol.clone(),
             capabilities: self.capabilities.clone(),
-            server_capabilities: self.server_capabilities.clone(),
+            server: self.server.clone(),
             server_version: self.server_version.clone(),
+            protocol_version: self.protocol_version.clone(),
             instructions: self.instructions.clone(),
             initialized: self.initialized,
             info: self.info.clone(),
-            notification_tx: self.notification_tx.clone(),
+            notifications: self.notifications.clone(),
+            elicitations: self.elicitations.clone(),
             active_requests: self.active_requests.clone(),
+            router: self.router.clone(),
+            subscriptions: self.subscriptions.clone(),
+            reconnect_manager: self.reconnect_manager.clone(),
+            ready_queue: self.ready_queue.clone(),
+            closed: self.closed.clone(),
+            oauth: self.oauth.clone(),
         }
     }
 }
@@ -1459,17 +2844,20 @@ mod tests {
     use crate::types::{
         jsonrpc::{JSONRPCError, ResponsePayload},
         JSONRPCResponse, ProgressNotification, ProgressToken, ResourceCapabilities,
-        ToolCapabilities, TransportMessage,
+        ServerNotification, ToolCapabilities, TransportMessage,
     };
     use async_trait::async_trait;
     use serde_json::json;
     use std::sync::{Arc, Mutex};
 
     /// Mock transport for testing
+    ///
+    /// Canned responses answer the sent requests in order, taking their IDs.
     #[derive(Debug)]
     struct MockTransport {
         responses: Arc<Mutex<Vec<TransportMessage>>>,
         sent_messages: Arc<Mutex<Vec<TransportMessage>>>,
+        unanswered: std::collections::VecDeque<RequestId>,
     }
 
     impl MockTransport {
@@ -1477,6 +2865,7 @@ mod tests {
             Self {
                 responses: Arc::new(Mutex::new(Vec::new())),
                 sent_messages: Arc::new(Mutex::new(Vec::new())),
+                unanswered: std::collections::VecDeque::new(),
             }
         }
 
@@ -1484,6 +2873,7 @@ mod tests {
             Self {
                 responses: Arc::new(Mutex::new(responses)),
                 sent_messages: Arc::new(Mutex::new(Vec::new())),
+                unanswered: std::collections::VecDeque::new(),
             }
         }
 
@@ -1496,16 +2886,26 @@ mod tests {
     #[async_trait]
     impl Transport for MockTransport {
         async fn send(&mut self, message: TransportMessage) -> Result<()> {
+            if let TransportMessage::Request { id, .. } = &message {
+                self.unanswered.push_back(id.clone());
+            }
             self.sent_messages.lock().unwrap().push(message);
             Ok(())
         }
 
         async fn receive(&mut self) -> Result<TransportMessage> {
-            self.responses
+            let mut message = self
+                .responses
                 .lock()
                 .unwrap()
                 .pop()
-                .ok_or_else(|| Error::protocol_msg("No more responses"))
+                .ok_or_else(|| Error::protocol_msg("No more responses"))?;
+            if let TransportMessage::Response(response) = &mut message {
+                if let Some(id) = self.unanswered.pop_front() {
+                    response.id = id;
+                }
+            }
+            Ok(message)
         }
 
         async fn close(&mut self) -> Result<()> {
@@ -1582,22 +2982,171 @@ mod tests {
         assert_eq!(client.server_version.as_ref().unwrap().name, "test-server");
     }
 
+    fn snake_case_init_response() -> TransportMessage {
+        TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(1i64),
+            payload: ResponsePayload::Result(json!({
+                "protocol_version": "2024-11-05",
+                "capabilities": {"tools": {"list_changed": true}},
+                "server_info": {"name": "legacy-server", "version": "0.1.0"}
+            })),
+        })
+    }
+
     #[tokio::test]
-    async fn test_ping() {
+    async fn test_lenient_field_case_accepts_snake_case_initialize() {
+        let transport = MockTransport::with_responses(vec![snake_case_init_response()]);
+        let sent = transport.sent_messages.clone();
+        let mut client = ClientBuilder::new(transport)
+            .field_case(FieldCase::Lenient)
+            .build();
+
+        let result = client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+        assert_eq!(result.server_info.name, "legacy-server");
+        assert_eq!(result.capabilities.tools.unwrap().list_changed, Some(true));
+
+        // Outgoing messages stay camelCase
+        let sent = serde_json::to_string(&sent.lock().unwrap()[0]).unwrap();
+        assert!(sent.contains("protocolVersion"), "{}", sent);
+        assert!(!sent.contains("protocol_version"), "{}", sent);
+    }
+
+    #[tokio::test]
+    async fn test_strict_field_case_rejects_snake_case_initialize() {
+        let transport = MockTransport::with_responses(vec![snake_case_init_response()]);
+        let mut client = Client::new(transport);
+        assert!(client
+            .initialize(ClientCapabilities::default())
+            .await
+            .is_err());
+    }
+
+    async fn wait_for_queued(client: &Client<MockTransport>, count: usize) {
+        let queue = client.ready_queue.as_ref().unwrap();
+        while queue.waiting() < count {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_requests_queued_until_ready_are_flushed() {
         let init_response = TransportMessage::Response(JSONRPCResponse {
             jsonrpc: "2.0".to_string(),
             id: RequestId::from(1i64),
             payload: ResponsePayload::Result(json!({
                 "protocolVersion": "2024-11-05",
-                "capabilities": {},
-                "serverInfo": {
-                    "name": "test-server",
-                    "version": "1.0.0"
-                }
+                "capabilities": {"tools": {}},
+                "serverInfo": {"name": "test-server", "version": "1.0.0"}
             })),
         });
-
-        let ping_response = TransportMessage::Response(JSONRPCResponse {
+        let tools_response = TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(2i64),
+            payload: ResponsePayload::Result(json!({"tools": [{
+                "name": "search",
+                "inputSchema": {"type": "object"}
+            }]})),
+        });
+
+        let transport = MockTransport::with_responses(vec![tools_response, init_response]);
+        let mut client = ClientBuilder::new(transport)
+            .queue_until_ready(true)
+            .build();
+
+        let early = client.clone();
+        let queued = tokio::spawn(async move { early.list_tools(None).await });
+        wait_for_queued(&client, 1).await;
+
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+        let tools = queued.await.unwrap().unwrap();
+        assert_eq!(tools.tools[0].name, "search");
+    }
+
+    #[tokio::test]
+    async fn test_queued_requests_fail_when_initialize_fails() {
+        let init_error = TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(1i64),
+            payload: ResponsePayload::Error(crate::types::jsonrpc::JSONRPCError::new(
+                -32600,
+                "unsupported client",
+            )),
+        });
+
+        let transport = MockTransport::with_responses(vec![init_error]);
+        let mut client = ClientBuilder::new(transport)
+            .queue_until_ready(true)
+            .build();
+
+        let early = client.clone();
+        let queued = tokio::spawn(async move { early.list_tools(None).await });
+        wait_for_queued(&client, 1).await;
+
+        assert!(client
+            .initialize(ClientCapabilities::default())
+            .await
+            .is_err());
+        let err = queued.await.unwrap().unwrap_err();
+        assert!(err.to_string().contains("initialization failed"), "{}", err);
+        assert!(err.to_string().contains("unsupported client"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_requests_before_initialize_fail_without_queue() {
+        let client = Client::new(MockTransport::new());
+        let err = client.list_tools(None).await.unwrap_err();
+        assert!(matches!(err, Error::InvalidState(_)));
+    }
+
+    #[tokio::test]
+    async fn test_protocol_version_reflects_negotiated_version() {
+        let init_response = TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(1i64),
+            payload: ResponsePayload::Result(json!({
+                "protocolVersion": "2025-03-26",
+                "capabilities": {},
+                "serverInfo": {
+                    "name": "test-server",
+                    "version": "1.0.0"
+                }
+            })),
+        });
+
+        let transport = MockTransport::with_responses(vec![init_response]);
+        let mut client = Client::new(transport);
+        assert!(client.protocol_version().is_none());
+
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+        assert_eq!(client.protocol_version().unwrap().as_str(), "2025-03-26");
+    }
+
+    #[tokio::test]
+    async fn test_ping() {
+        let init_response = TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(1i64),
+            payload: ResponsePayload::Result(json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": {},
+                "serverInfo": {
+                    "name": "test-server",
+                    "version": "1.0.0"
+                }
+            })),
+        });
+
+        let ping_response = TransportMessage::Response(JSONRPCResponse {
             jsonrpc: "2.0".to_string(),
             id: RequestId::from(2i64),
             payload: ResponsePayload::Result(json!({})),
@@ -1643,23 +3192,1120 @@ mod tests {
             })),
         });
 
-        let transport = MockTransport::with_responses(vec![tools_response, init_response]);
-        let mut client = Client::new(transport);
+        let transport = MockTransport::with_responses(vec![tools_response, init_response]);
+        let mut client = Client::new(transport);
+
+        // Initialize with tools capability
+        let _ = client
+            .initialize(ClientCapabilities {
+                tools: Some(ToolCapabilities::default()),
+                ..Default::default()
+            })
+            .await;
+
+        // List tools
+        let result = client.list_tools(None).await;
+        assert!(result.is_ok());
+        let tools = result.unwrap();
+        assert_eq!(tools.tools.len(), 1);
+        assert_eq!(tools.tools[0].name, "test-tool");
+    }
+
+    fn tools_page(names: &[&str], next_cursor: Option<&str>) -> TransportMessage {
+        let tools: Vec<_> = names
+            .iter()
+            .map(|name| json!({ "name": name, "inputSchema": {} }))
+            .collect();
+        TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(2i64),
+            payload: ResponsePayload::Result(json!({
+                "tools": tools,
+                "nextCursor": next_cursor,
+            })),
+        })
+    }
+
+    fn tools_init_response() -> TransportMessage {
+        TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(1i64),
+            payload: ResponsePayload::Result(json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "test-server", "version": "1.0.0" }
+            })),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_tools_stream_follows_cursors() {
+        use futures::TryStreamExt;
+
+        // Responses are popped from the end
+        let transport = MockTransport::with_responses(vec![
+            tools_page(&["c"], None),
+            tools_page(&["a", "b"], Some("page-2")),
+            tools_init_response(),
+        ]);
+        let mut client = Client::new(transport);
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+
+        let tools: Vec<_> = client.tools_stream().try_collect().await.unwrap();
+        let names: Vec<_> = tools.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_tools_stream_bounds_pages() {
+        use futures::TryStreamExt;
+
+        let mut responses: Vec<_> = (0..=MAX_AUTO_PAGES)
+            .map(|_| tools_page(&["loop"], Some("again")))
+            .collect();
+        responses.push(tools_init_response());
+        let mut client = Client::new(MockTransport::with_responses(responses));
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+
+        let result: Result<Vec<_>> = client.tools_stream().try_collect().await;
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Pagination exceeded"));
+    }
+
+    fn ok_response() -> TransportMessage {
+        TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(2i64),
+            payload: ResponsePayload::Result(json!({})),
+        })
+    }
+
+    fn subscribe_init_response() -> TransportMessage {
+        TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(1i64),
+            payload: ResponsePayload::Result(json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": { "resources": { "subscribe": true } },
+                "serverInfo": { "name": "test-server", "version": "1.0.0" }
+            })),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_resume_session_replays_subscriptions() {
+        use crate::shared::reconnect::{ReconnectCallbacks, ReconnectConfig};
+
+        // Responses are popped from the end
+        let transport = MockTransport::with_responses(vec![
+            ok_response(),
+            ok_response(),
+            ok_response(),
+            ok_response(),
+            subscribe_init_response(),
+        ]);
+        let sent = transport.sent_messages.clone();
+        let responses = transport.responses.clone();
+
+        let replayed = Arc::new(Mutex::new(Vec::new()));
+        let mut manager = ReconnectManager::new(ReconnectConfig::default());
+        manager.set_callbacks(ReconnectCallbacks {
+            on_resubscribed: Some(Box::new({
+                let replayed = replayed.clone();
+                move |uris| replayed.lock().unwrap().extend_from_slice(uris)
+            })),
+            ..Default::default()
+        });
+
+        let mut client = Client::new(transport);
+        client.set_reconnect_manager(Arc::new(manager));
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+        for uri in ["file:///a", "file:///b", "file:///c"] {
+            client.subscribe_resource(uri.to_string()).await.unwrap();
+        }
+        client
+            .unsubscribe_resource("file:///b".to_string())
+            .await
+            .unwrap();
+
+        // Simulate a dropped connection followed by a reconnect
+        sent.lock().unwrap().clear();
+        *responses.lock().unwrap() = vec![ok_response(), ok_response(), subscribe_init_response()];
+
+        client.resume_session().await.unwrap();
+
+        let resubscribed: Vec<_> = sent
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|msg| match msg {
+                TransportMessage::Request { request, .. } => match request {
+                    Request::Client(req) => match req.as_ref() {
+                        ClientRequest::Subscribe(sub) => Some(sub.uri.clone()),
+                        _ => None,
+                    },
+                    Request::Server(_) => None,
+                },
+                _ => None,
+            })
+            .collect();
+        assert_eq!(resubscribed, vec!["file:///a", "file:///c"]);
+        assert!(matches!(
+            sent.lock().unwrap().first(),
+            Some(TransportMessage::Request { request: Request::Client(req), .. })
+                if matches!(req.as_ref(), ClientRequest::Initialize(_))
+        ));
+        assert_eq!(*replayed.lock().unwrap(), vec!["file:///a", "file:///c"]);
+    }
+
+    #[tokio::test]
+    async fn test_resume_session_requires_initialize() {
+        let mut client = Client::new(MockTransport::new());
+        assert!(client.resume_session().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resume_session_detects_changed_capabilities() {
+        use crate::shared::reconnect::{ReconnectCallbacks, ReconnectConfig};
+
+        fn init_response(capabilities: serde_json::Value) -> TransportMessage {
+            TransportMessage::Response(JSONRPCResponse {
+                jsonrpc: "2.0".to_string(),
+                id: RequestId::from(1i64),
+                payload: ResponsePayload::Result(json!({
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": capabilities,
+                    "serverInfo": { "name": "test-server", "version": "1.0.0" }
+                })),
+            })
+        }
+
+        let transport = MockTransport::with_responses(vec![init_response(json!({ "tools": {} }))]);
+        let responses = transport.responses.clone();
+
+        let notified = Arc::new(Mutex::new(Vec::new()));
+        let mut manager = ReconnectManager::new(ReconnectConfig::default());
+        manager.set_callbacks(ReconnectCallbacks {
+            on_capabilities_changed: Some(Box::new({
+                let notified = notified.clone();
+                move |capabilities| notified.lock().unwrap().push(capabilities.clone())
+            })),
+            ..Default::default()
+        });
+
+        let mut client = Client::new(transport);
+        client.set_reconnect_manager(Arc::new(manager));
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+        assert!(!client.capabilities_changed_since_last_connect());
+
+        // Reconnect to a server with the same capabilities
+        *responses.lock().unwrap() = vec![init_response(json!({ "tools": {} }))];
+        client.resume_session().await.unwrap();
+        assert!(!client.capabilities_changed_since_last_connect());
+        assert!(notified.lock().unwrap().is_empty());
+
+        // Reconnect to a server that now also offers prompts
+        *responses.lock().unwrap() = vec![init_response(json!({ "tools": {}, "prompts": {} }))];
+        client.resume_session().await.unwrap();
+        assert!(client.capabilities_changed_since_last_connect());
+        assert_eq!(notified.lock().unwrap().len(), 1);
+        assert!(notified.lock().unwrap()[0].prompts.is_some());
+        assert!(client.get_server_capabilities().unwrap().prompts.is_some());
+
+        // The flag only covers the latest reconnect
+        *responses.lock().unwrap() = vec![init_response(json!({ "tools": {}, "prompts": {} }))];
+        client.resume_session().await.unwrap();
+        assert!(!client.capabilities_changed_since_last_connect());
+        assert_eq!(notified.lock().unwrap().len(), 1);
+    }
+
+    /// Transport whose `receive` never completes, recording whether it was closed
+    #[derive(Debug)]
+    struct HangingTransport {
+        closed: Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    #[async_trait]
+    impl Transport for HangingTransport {
+        async fn send(&mut self, _message: TransportMessage) -> Result<()> {
+            Ok(())
+        }
+
+        async fn receive(&mut self) -> Result<TransportMessage> {
+            std::future::pending().await
+        }
+
+        async fn close(&mut self) -> Result<()> {
+            self.closed.store(true, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_close_cancels_pending_requests() {
+        let closed = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let client = Client::new(HangingTransport {
+            closed: closed.clone(),
+        });
+
+        let pending = tokio::spawn({
+            let client = client.clone();
+            async move {
+                client
+                    .send_request(
+                        RequestId::from(1i64),
+                        Request::Client(Box::new(ClientRequest::Ping)),
+                    )
+                    .await
+            }
+        });
+        while client.active_requests.is_empty() {
+            tokio::task::yield_now().await;
+        }
+
+        client.close().await.unwrap();
+
+        let result = tokio::time::timeout(std::time::Duration::from_secs(1), pending)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert!(closed.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_all_cancels_in_flight_requests() {
+        let client = Client::new(HangingTransport {
+            closed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+        });
+
+        let requests: Vec<_> = (1..=3i64)
+            .map(|id| {
+                let client = client.clone();
+                tokio::spawn(async move {
+                    client
+                        .send_request(
+                            RequestId::from(id),
+                            Request::Client(Box::new(ClientRequest::Ping)),
+                        )
+                        .await
+                })
+            })
+            .collect();
+        while client.stats().pending_requests < 3 {
+            tokio::task::yield_now().await;
+        }
+
+        tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            client.cancel_all(Some("Run aborted".to_string())),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        for request in requests {
+            let result = tokio::time::timeout(std::time::Duration::from_secs(1), request)
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(matches!(result, Err(Error::Cancelled)));
+        }
+        assert_eq!(client.stats().pending_requests, 0);
+    }
+
+    /// Transport whose connection has dropped
+    #[derive(Debug)]
+    struct DroppedTransport;
+
+    #[async_trait]
+    impl Transport for DroppedTransport {
+        async fn send(&mut self, _message: TransportMessage) -> Result<()> {
+            Ok(())
+        }
+
+        async fn receive(&mut self) -> Result<TransportMessage> {
+            Err(TransportError::ConnectionClosed.into())
+        }
+
+        async fn close(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_requests_after_close_fail_with_connection_closed() {
+        let closed = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let client = Client::new(HangingTransport { closed });
+        let clone = client.clone();
+        client.close().await.unwrap();
+
+        // Fails promptly instead of waiting on the closed transport
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            clone.send_request(
+                RequestId::from(1i64),
+                Request::Client(Box::new(ClientRequest::Ping)),
+            ),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            result,
+            Err(Error::Transport(TransportError::ConnectionClosed))
+        ));
+        assert!(matches!(
+            clone.cancel_request(&RequestId::from(1i64)).await,
+            Err(Error::Transport(TransportError::ConnectionClosed))
+        ));
+        assert_eq!(clone.stats().pending_requests, 0);
+
+        // A dropped connection surfaces the same error and clears the pending map
+        let client = Client::new(DroppedTransport);
+        let result = client
+            .send_request(
+                RequestId::from(1i64),
+                Request::Client(Box::new(ClientRequest::Ping)),
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(Error::Transport(TransportError::ConnectionClosed))
+        ));
+        assert_eq!(client.stats().pending_requests, 0);
+    }
+
+    /// Server that answers pings and rejects everything else, recording the size of
+    /// each frame it receives
+    #[derive(Debug)]
+    struct BatchingServer {
+        capabilities: serde_json::Value,
+        incoming: std::collections::VecDeque<TransportMessage>,
+        frames: Arc<Mutex<Vec<usize>>>,
+    }
+
+    impl BatchingServer {
+        fn answer(&mut self, message: TransportMessage) {
+            let TransportMessage::Request { id, request } = message else {
+                return;
+            };
+            let payload = match request {
+                Request::Client(request) => match *request {
+                    ClientRequest::Initialize(_) => ResponsePayload::Result(json!({
+                        "protocolVersion": "2024-11-05",
+                        "capabilities": self.capabilities,
+                        "serverInfo": {"name": "test-server", "version": "1.0.0"}
+                    })),
+                    ClientRequest::Ping => ResponsePayload::Result(json!({})),
+                    _ => ResponsePayload::Error(JSONRPCError {
+                        code: -32601,
+                        message: "Method not found".to_string(),
+                        data: None,
+                    }),
+                },
+                Request::Server(_) => return,
+            };
+            self.incoming
+                .push_back(TransportMessage::Response(JSONRPCResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id,
+                    payload,
+                }));
+        }
+    }
+
+    #[async_trait]
+    impl Transport for BatchingServer {
+        async fn send(&mut self, message: TransportMessage) -> Result<()> {
+            self.frames.lock().unwrap().push(1);
+            self.answer(message);
+            Ok(())
+        }
+
+        async fn send_batch(&mut self, messages: Vec<TransportMessage>) -> Result<()> {
+            self.frames.lock().unwrap().push(messages.len());
+            // Answer out of order; the client matches responses by id
+            for message in messages.into_iter().rev() {
+                self.answer(message);
+            }
+            Ok(())
+        }
+
+        async fn receive(&mut self) -> Result<TransportMessage> {
+            self.incoming
+                .pop_front()
+                .ok_or_else(|| Error::protocol_msg("No more responses"))
+        }
+
+        async fn close(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_batch_only_batches_when_supported() {
+        // initialize and notifications/initialized, then the batch
+        for (capabilities, expected_frames) in [
+            (json!({"experimental": {"batch": {}}}), vec![1, 1, 2]),
+            (json!({}), vec![1, 1, 1, 1]),
+        ] {
+            let frames = Arc::new(Mutex::new(Vec::new()));
+            let mut client = Client::new(BatchingServer {
+                capabilities,
+                incoming: std::collections::VecDeque::new(),
+                frames: frames.clone(),
+            });
+            client
+                .initialize(ClientCapabilities::default())
+                .await
+                .unwrap();
+
+            let results = client
+                .send_batch(vec![
+                    ClientRequest::Ping,
+                    ClientRequest::ListTools(ListToolsRequest { cursor: None }),
+                ])
+                .await
+                .unwrap();
+            assert_eq!(results.len(), 2);
+            assert_eq!(results[0].as_ref().unwrap(), &json!({}));
+            let err = results[1].as_ref().unwrap_err();
+            assert!(err.to_string().contains("Method not found"), "{}", err);
+
+            assert_eq!(*frames.lock().unwrap(), expected_frames);
+            assert_eq!(client.stats().pending_requests, 0);
+        }
+    }
+
+    /// Server answering each batch with one array frame, which also holds a stray
+    /// response, an invalid element and a duplicate response, and single requests
+    /// with single frames
+    #[derive(Debug)]
+    struct ArrayFrameServer {
+        incoming: std::collections::VecDeque<TransportMessage>,
+    }
+
+    impl ArrayFrameServer {
+        fn response(message: &TransportMessage) -> Option<serde_json::Value> {
+            let TransportMessage::Request { id, request } = message else {
+                return None;
+            };
+            let result = match request {
+                Request::Client(request) if matches!(**request, ClientRequest::Initialize(_)) => {
+                    json!({
+                        "protocolVersion": "2024-11-05",
+                        "capabilities": {"experimental": {"batch": {}}},
+                        "serverInfo": {"name": "test-server", "version": "1.0.0"}
+                    })
+                },
+                _ => json!({"answered": id}),
+            };
+            Some(json!({"jsonrpc": "2.0", "id": id, "result": result}))
+        }
+
+        fn receive_frame(&mut self, frame: &serde_json::Value) {
+            let messages =
+                crate::shared::StdioTransport::parse_messages(frame.to_string().as_bytes())
+                    .unwrap();
+            self.incoming.extend(messages);
+        }
+    }
+
+    #[async_trait]
+    impl Transport for ArrayFrameServer {
+        async fn send(&mut self, message: TransportMessage) -> Result<()> {
+            if let Some(response) = Self::response(&message) {
+                self.receive_frame(&response);
+            }
+            Ok(())
+        }
+
+        async fn send_batch(&mut self, messages: Vec<TransportMessage>) -> Result<()> {
+            let mut frame = vec![
+                json!({"jsonrpc": "2.0", "id": "stray", "result": {}}),
+                json!({"not": "a message"}),
+            ];
+            // Answer out of order, repeating the first answer
+            for message in messages.iter().rev() {
+                frame.extend(Self::response(message));
+            }
+            let repeated = frame[2].clone();
+            frame.insert(3, repeated);
+            self.receive_frame(&json!(frame));
+            Ok(())
+        }
+
+        async fn receive(&mut self) -> Result<TransportMessage> {
+            self.incoming
+                .pop_front()
+                .ok_or_else(|| Error::protocol_msg("No more responses"))
+        }
+
+        async fn close(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_and_single_responses_interleave() {
+        let mut client = Client::new(ArrayFrameServer {
+            incoming: std::collections::VecDeque::new(),
+        });
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+
+        for _ in 0..2 {
+            let results = client
+                .send_batch(vec![ClientRequest::Ping, ClientRequest::Ping])
+                .await
+                .unwrap();
+            assert_eq!(results.len(), 2);
+            assert_ne!(results[0].as_ref().unwrap(), results[1].as_ref().unwrap());
+            for result in &results {
+                assert!(result.as_ref().unwrap()["answered"].is_string());
+            }
+
+            // Nothing is left over to be mistaken for the next single response
+            assert!(client.transport.read().await.incoming.is_empty());
+            client.ping().await.unwrap();
+        }
+        assert_eq!(client.stats().pending_requests, 0);
+    }
+
+    /// Transport that holds back its answers until two requests have arrived, then
+    /// answers the second one first, after a notification
+    #[derive(Debug, Default)]
+    struct OutOfOrderServer {
+        received: Vec<RequestId>,
+        incoming: std::collections::VecDeque<TransportMessage>,
+    }
+
+    #[async_trait]
+    impl Transport for OutOfOrderServer {
+        async fn send(&mut self, message: TransportMessage) -> Result<()> {
+            if let TransportMessage::Request { id, .. } = message {
+                self.received.push(id);
+            }
+            if self.received.len() == 2 {
+                self.incoming
+                    .push_back(TransportMessage::Notification(Notification::Server(
+                        ServerNotification::ToolsChanged,
+                    )));
+                for id in self.received.drain(..).rev() {
+                    self.incoming
+                        .push_back(TransportMessage::Response(JSONRPCResponse {
+                            jsonrpc: "2.0".to_string(),
+                            id: id.clone(),
+                            payload: ResponsePayload::Result(json!({"answered": id})),
+                        }));
+                }
+            }
+            Ok(())
+        }
+
+        async fn receive(&mut self) -> Result<TransportMessage> {
+            match self.incoming.pop_front() {
+                Some(message) => Ok(message),
+                // Waits for the client to interrupt it and send the next request
+                None => std::future::pending().await,
+            }
+        }
+
+        async fn close(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_get_their_own_responses() {
+        let client = Client::new(OutOfOrderServer::default());
+        let mut notifications = client.notifications();
+        let clone = client.clone();
+
+        let first = async {
+            // Sent first, so the receive loop is waiting when the second is sent
+            client
+                .send_request(
+                    RequestId::from("first"),
+                    Request::Client(Box::new(ClientRequest::Ping)),
+                )
+                .await
+        };
+        let second = async {
+            while client.stats().pending_requests == 0 {
+                tokio::task::yield_now().await;
+            }
+            clone
+                .send_request(
+                    RequestId::from("second"),
+                    Request::Client(Box::new(ClientRequest::Ping)),
+                )
+                .await
+        };
+        let (first, second) = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            tokio::join!(first, second)
+        })
+        .await
+        .expect("both requests should be answered");
+
+        for (response, id) in [(first.unwrap(), "first"), (second.unwrap(), "second")] {
+            assert_eq!(response.id, RequestId::from(id));
+            assert!(
+                matches!(&response.payload, ResponsePayload::Result(result) if result["answered"] == id),
+                "{:?}",
+                response
+            );
+        }
+        // The notification in between went to the notification channel
+        assert!(matches!(
+            notifications.try_recv(),
+            Ok(Notification::Server(ServerNotification::ToolsChanged))
+        ));
+        assert_eq!(client.stats().pending_requests, 0);
+    }
+
+    #[tokio::test]
+    async fn test_requests_from_the_server_are_answered() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let transport = MockTransport {
+            responses: Arc::new(Mutex::new(vec![
+                TransportMessage::Response(JSONRPCResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id: RequestId::from(1i64),
+                    payload: ResponsePayload::Result(json!({})),
+                }),
+                TransportMessage::Request {
+                    id: RequestId::from("roots"),
+                    request: Request::Server(Box::new(crate::types::ServerRequest::ListRoots)),
+                },
+                TransportMessage::Request {
+                    id: RequestId::from("ping"),
+                    request: Request::Server(Box::new(crate::types::ServerRequest::Ping)),
+                },
+            ])),
+            sent_messages: sent.clone(),
+            unanswered: std::collections::VecDeque::new(),
+        };
+        let client = Client::new(transport);
+
+        client
+            .send_request(
+                RequestId::from(1i64),
+                Request::Client(Box::new(ClientRequest::Ping)),
+            )
+            .await
+            .unwrap();
+
+        let answers: Vec<_> = sent
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|message| match message {
+                TransportMessage::Response(response) => Some(response.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(answers.len(), 2);
+        assert_eq!(answers[0].id, RequestId::from("ping"));
+        assert!(
+            matches!(&answers[0].payload, ResponsePayload::Result(result) if *result == json!({}))
+        );
+        assert_eq!(answers[1].id, RequestId::from("roots"));
+        assert!(matches!(
+            &answers[1].payload,
+            ResponsePayload::Error(error) if error.code == crate::error::ErrorCode::METHOD_NOT_FOUND.as_i32()
+        ));
+    }
+
+    /// Transport of a server that stopped answering keep-alive pings
+    #[derive(Debug)]
+    struct UnresponsiveServer;
+
+    #[async_trait]
+    impl Transport for UnresponsiveServer {
+        async fn send(&mut self, _message: TransportMessage) -> Result<()> {
+            Ok(())
+        }
+
+        async fn receive(&mut self) -> Result<TransportMessage> {
+            Err(TransportError::Unresponsive(std::time::Duration::from_secs(1)).into())
+        }
+
+        async fn close(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_requests_fail_until_resumed_once_the_server_stops_answering() {
+        let mut client = Client::new(UnresponsiveServer);
+        client.initialized = true;
+
+        let result = client
+            .send_request(
+                RequestId::from(1i64),
+                Request::Client(Box::new(ClientRequest::Ping)),
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(Error::Transport(TransportError::Unresponsive(_)))
+        ));
+
+        // A restarted server process knows nothing of the session
+        let err = client.ping().await.unwrap_err();
+        assert!(err.to_string().contains("resume_session"), "{}", err);
+    }
+
+    /// Transport answering `initialize` and `tools/list`, sending resource updates
+    /// before and after the tool list
+    #[derive(Debug, Default)]
+    struct UpdatingServer {
+        incoming: std::collections::VecDeque<TransportMessage>,
+    }
+
+    impl UpdatingServer {
+        fn updated(uri: &str) -> TransportMessage {
+            TransportMessage::Notification(Notification::Server(
+                ServerNotification::ResourceUpdated(
+                    crate::types::protocol::ResourceUpdatedParams {
+                        uri: uri.to_string(),
+                        change: Default::default(),
+                    },
+                ),
+            ))
+        }
+    }
+
+    #[async_trait]
+    impl Transport for UpdatingServer {
+        async fn send(&mut self, message: TransportMessage) -> Result<()> {
+            let TransportMessage::Request { id, request } = message else {
+                return Ok(());
+            };
+            let list_tools = matches!(&request, Request::Client(r) if matches!(**r, ClientRequest::ListTools(_)));
+            let result = if list_tools {
+                self.incoming.push_back(Self::updated("file:///before"));
+                json!({"tools": [{"name": "echo", "inputSchema": {}}]})
+            } else {
+                json!({
+                    "protocolVersion": crate::LATEST_PROTOCOL_VERSION,
+                    "capabilities": {"tools": {}, "resources": {"subscribe": true}},
+                    "serverInfo": {"name": "test-server", "version": "1.0.0"}
+                })
+            };
+            self.incoming
+                .push_back(TransportMessage::Response(JSONRPCResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id,
+                    payload: ResponsePayload::Result(result),
+                }));
+            if list_tools {
+                // Arrives once no request is waiting any more
+                self.incoming.push_back(Self::updated("file:///after"));
+            }
+            Ok(())
+        }
+
+        async fn receive(&mut self) -> Result<TransportMessage> {
+            match self.incoming.pop_front() {
+                Some(message) => Ok(message),
+                None => std::future::pending().await,
+            }
+        }
+
+        async fn close(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_notifications_arrive_alongside_responses() {
+        let mut client = Client::new(UpdatingServer::default());
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+        let mut notifications = client.notifications();
+
+        let tools =
+            tokio::time::timeout(std::time::Duration::from_secs(5), client.list_tools(None))
+                .await
+                .expect("tools/list should be answered")
+                .unwrap();
+        assert_eq!(tools.tools[0].name, "echo");
+
+        for uri in ["file:///before", "file:///after"] {
+            let notification =
+                tokio::time::timeout(std::time::Duration::from_secs(5), notifications.recv())
+                    .await
+                    .expect("notification should arrive");
+            match notification {
+                Some(Notification::Server(ServerNotification::ResourceUpdated(updated))) => {
+                    assert_eq!(updated.uri, uri);
+                },
+                other => panic!("Expected a resource update, got {:?}", other),
+            }
+        }
+    }
+
+    /// MCP server over HTTP answering `initialize` and `ping` sent with the access
+    /// token "fresh-token" `answered` times, and rejecting "expired-token" with HTTP
+    /// 401 `rejected` times
+    #[cfg(feature = "http")]
+    async fn mcp_endpoint(
+        answered: usize,
+        rejected: usize,
+    ) -> (mockito::ServerGuard, mockito::Mock, mockito::Mock) {
+        let mut server = mockito::Server::new_async().await;
+        let is_request = mockito::Matcher::Regex(r#""id":"#.to_string());
+        let answer = server
+            .mock("POST", "/")
+            .match_header("authorization", "Bearer fresh-token")
+            .match_body(is_request.clone())
+            .with_header("content-type", "application/json")
+            .with_body_from_request(|request| {
+                let request: serde_json::Value =
+                    serde_json::from_slice(request.body().unwrap()).unwrap();
+                let result = if request["method"] == "ping" {
+                    json!({})
+                } else {
+                    json!({
+                        "protocolVersion": crate::LATEST_PROTOCOL_VERSION,
+                        "capabilities": {},
+                        "serverInfo": {"name": "test-server", "version": "1.0.0"}
+                    })
+                };
+                serde_json::to_vec(
+                    &json!({"jsonrpc": "2.0", "id": request["id"], "result": result}),
+                )
+                .unwrap()
+            })
+            .expect(answered)
+            .create_async()
+            .await;
+        let reject = server
+            .mock("POST", "/")
+            .match_header("authorization", "Bearer expired-token")
+            .match_body(is_request)
+            .with_status(401)
+            .expect(rejected)
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/")
+            .match_body(mockito::Matcher::PartialJson(
+                json!({"method": "notifications/initialized"}),
+            ))
+            .with_status(202)
+            .create_async()
+            .await;
+        (server, answer, reject)
+    }
+
+    #[cfg(feature = "http")]
+    fn http_transport(server: &mockito::ServerGuard) -> crate::shared::http::HttpTransport {
+        crate::shared::http::HttpTransport::with_url(server.url().parse::<url::Url>().unwrap())
+            .unwrap()
+    }
+
+    #[cfg(feature = "http")]
+    fn oauth_client() -> OAuthClient {
+        OAuthClient {
+            client_id: "test-client".to_string(),
+            client_secret: None,
+            client_name: "Test Client".to_string(),
+            redirect_uris: vec![],
+            grant_types: vec![crate::server::auth::oauth2::GrantType::RefreshToken],
+            response_types: vec![],
+            scopes: vec![],
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[cfg(feature = "http")]
+    fn access_token(token: &str, expires_in: u64) -> crate::server::auth::oauth2::AccessToken {
+        crate::server::auth::oauth2::AccessToken {
+            access_token: token.to_string(),
+            token_type: crate::server::auth::oauth2::TokenType::Bearer,
+            expires_in: Some(expires_in),
+            refresh_token: Some("refresh-1".to_string()),
+            scope: None,
+            extra: HashMap::new(),
+        }
+    }
+
+    #[cfg(feature = "http")]
+    /// Token endpoint exchanging "refresh-1" for "fresh-token", `hits` times
+    async fn token_endpoint(hits: usize) -> (mockito::ServerGuard, mockito::Mock) {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/token")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("grant_type".into(), "refresh_token".into()),
+                mockito::Matcher::UrlEncoded("refresh_token".into(), "refresh-1".into()),
+                mockito::Matcher::UrlEncoded("client_id".into(), "test-client".into()),
+            ]))
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"access_token":"fresh-token","token_type":"Bearer","expires_in":3600}"#)
+            .expect(hits)
+            .create_async()
+            .await;
+        (server, mock)
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_rejected_token_is_refreshed_and_request_retried() {
+        let (endpoint, refresh) = token_endpoint(1).await;
+        let tokens = Arc::new(auth::TokenStore::new(
+            format!("{}/token", endpoint.url()),
+            access_token("expired-token", 3600),
+        ));
+        let (server, answer, reject) = mcp_endpoint(2, 1).await;
+        let mut client = ClientBuilder::new(http_transport(&server))
+            .with_oauth(oauth_client(), tokens.clone())
+            .build();
+
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+        client.ping().await.unwrap();
+
+        refresh.assert_async().await;
+        reject.assert_async().await;
+        answer.assert_async().await;
+        let refreshed = tokens.tokens().await;
+        assert_eq!(refreshed.access_token, "fresh-token");
+        // Kept since the token endpoint did not issue a new one
+        assert_eq!(refreshed.refresh_token.as_deref(), Some("refresh-1"));
+    }
+
+    #[cfg(feature = "http")]
+    #[tokio::test]
+    async fn test_expiring_token_is_refreshed_before_sending() {
+        let (endpoint, refresh) = token_endpoint(1).await;
+        let tokens = auth::TokenStore::new(
+            format!("{}/token", endpoint.url()),
+            access_token("expiring-token", 10),
+        )
+        .with_refresh_window(std::time::Duration::from_secs(30));
+        let (server, answer, reject) = mcp_endpoint(1, 0).await;
+        let mut client = ClientBuilder::new(http_transport(&server))
+            .with_oauth(oauth_client(), Arc::new(tokens))
+            .build();
+
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+
+        refresh.assert_async().await;
+        reject.assert_async().await;
+        answer.assert_async().await;
+    }
+
+    /// Transport that records what is sent and never answers
+    #[derive(Debug)]
+    struct SilentServer {
+        sent: Arc<Mutex<Vec<TransportMessage>>>,
+    }
+
+    #[async_trait]
+    impl Transport for SilentServer {
+        async fn send(&mut self, message: TransportMessage) -> Result<()> {
+            self.sent.lock().unwrap().push(message);
+            Ok(())
+        }
+
+        async fn receive(&mut self) -> Result<TransportMessage> {
+            std::future::pending().await
+        }
+
+        async fn close(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_cancels_request() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let client = Client::new(SilentServer { sent: sent.clone() });
+        let options = RequestOptions {
+            timeout: Some(std::time::Duration::from_millis(50)),
+            ..Default::default()
+        };
 
-        // Initialize with tools capability
-        let _ = client
-            .initialize(ClientCapabilities {
-                tools: Some(ToolCapabilities::default()),
-                ..Default::default()
-            })
+        let started = std::time::Instant::now();
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            client.send_request_with_options(
+                RequestId::from("slow"),
+                Request::Client(Box::new(ClientRequest::Ping)),
+                &options,
+            ),
+        )
+        .await
+        .expect("the request should time out");
+        assert!(matches!(result, Err(Error::Timeout(50))));
+        assert!(started.elapsed() >= std::time::Duration::from_millis(50));
+        assert!(client.active_requests.is_empty());
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        assert!(matches!(
+            &sent[1],
+            TransportMessage::Notification(Notification::Cancelled(cancelled))
+                if cancelled.request_id == RequestId::from("slow")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_set_timeout_applies_to_later_requests() {
+        let client = ClientBuilder::new(HangingTransport {
+            closed: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+        })
+        .pending_timeout(std::time::Duration::from_secs(3600))
+        .build();
+
+        client
+            .set_timeout(std::time::Duration::from_millis(50))
             .await;
+        assert_eq!(
+            client.options().await.pending_timeout,
+            Some(std::time::Duration::from_millis(50))
+        );
 
-        // List tools
-        let result = client.list_tools(None).await;
-        assert!(result.is_ok());
-        let tools = result.unwrap();
-        assert_eq!(tools.tools.len(), 1);
-        assert_eq!(tools.tools[0].name, "test-tool");
+        let started = std::time::Instant::now();
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            client.send_request(
+                RequestId::from(1i64),
+                Request::Client(Box::new(ClientRequest::Ping)),
+            ),
+        )
+        .await
+        .expect("the new timeout should fail the request");
+        assert!(matches!(result, Err(Error::Timeout(50))));
+        assert!(started.elapsed() >= std::time::Duration::from_millis(50));
+        assert!(client.active_requests.is_empty());
     }
 
     #[tokio::test]
@@ -1730,7 +4376,9 @@ mod tests {
         });
 
         let transport = MockTransport::with_responses(vec![init_response]);
-        let mut client = Client::new(transport);
+        let mut client = ClientBuilder::new(transport)
+            .enforce_strict_capabilities(true)
+            .build();
 
         // Initialize without tools capability
         let _ = client.initialize(ClientCapabilities::default()).await;
@@ -1741,6 +4389,253 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("not supported"));
     }
 
+    #[tokio::test]
+    async fn test_missing_sub_capability_fails_before_sending() {
+        let init_response = TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(1i64),
+            payload: ResponsePayload::Result(json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": {"resources": {"listChanged": true}},
+                "serverInfo": {"name": "test-server", "version": "1.0.0"}
+            })),
+        });
+        let transport = MockTransport::with_responses(vec![init_response.clone()]);
+        let sent = transport.sent_messages.clone();
+        let mut client = Client::new(transport);
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+        let sent_before = sent.lock().unwrap().len();
+
+        for result in [
+            client.subscribe_resource("file:///a".to_string()).await,
+            client.unsubscribe_resource("file:///a".to_string()).await,
+        ] {
+            let err = result.unwrap_err();
+            assert!(matches!(err, Error::UnsupportedCapability(_)), "{:?}", err);
+            assert!(
+                err.to_string()
+                    .contains("Server does not support resources.subscribe"),
+                "{}",
+                err
+            );
+        }
+        assert_eq!(sent.lock().unwrap().len(), sent_before);
+
+        // Without strict capabilities the request is sent anyway
+        let subscribed = TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(2i64),
+            payload: ResponsePayload::Result(json!({})),
+        });
+        let transport = MockTransport::with_responses(vec![subscribed, init_response]);
+        let sent = transport.sent_messages.clone();
+        let mut client = ClientBuilder::new(transport)
+            .enforce_strict_capabilities(false)
+            .build();
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+        client
+            .subscribe_resource("file:///a".to_string())
+            .await
+            .unwrap();
+        assert!(matches!(
+            sent.lock().unwrap().last(),
+            Some(TransportMessage::Request { request: Request::Client(request), .. })
+                if matches!(**request, ClientRequest::Subscribe(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_lenient_capabilities_send_anyway() {
+        let response = |result: serde_json::Value| {
+            TransportMessage::Response(JSONRPCResponse {
+                jsonrpc: "2.0".to_string(),
+                id: RequestId::from(1i64),
+                payload: ResponsePayload::Result(result),
+            })
+        };
+        let init_response = response(json!({
+            "protocolVersion": "2024-11-05",
+            "capabilities": {},
+            "serverInfo": {"name": "test-server", "version": "1.0.0"}
+        }));
+        // The server answers although it did not advertise tools
+        let list_response = response(json!({"tools": [{"name": "hidden", "inputSchema": {}}]}));
+
+        let transport = MockTransport::with_responses(vec![list_response, init_response]);
+        let mut client = ClientBuilder::new(transport)
+            .enforce_strict_capabilities(false)
+            .build();
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+
+        let tools = client.list_tools(None).await.unwrap();
+        assert_eq!(tools.tools[0].name, "hidden");
+    }
+
+    #[tokio::test]
+    async fn test_capabilities_changed_unblocks_calls() {
+        let response = |result: serde_json::Value| {
+            TransportMessage::Response(JSONRPCResponse {
+                jsonrpc: "2.0".to_string(),
+                id: RequestId::from(1i64),
+                payload: ResponsePayload::Result(result),
+            })
+        };
+        let init_response = response(json!({
+            "protocolVersion": "2024-11-05",
+            "capabilities": {},
+            "serverInfo": {"name": "test-server", "version": "1.0.0"}
+        }));
+        let call_response = response(json!({"content": []}));
+        // A plugin loads on the server and it announces tools, read with the ping
+        let capabilities_changed = TransportMessage::Notification(
+            crate::shared::protocol_helpers::parse_notification(json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/capabilities/changed",
+                "params": {"tools": {"listChanged": true}}
+            }))
+            .unwrap(),
+        );
+
+        let transport = MockTransport::with_responses(vec![
+            call_response,
+            response(json!({})),
+            capabilities_changed,
+            init_response,
+        ]);
+        let mut client = ClientBuilder::new(transport)
+            .enforce_strict_capabilities(true)
+            .build();
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+        let clone = client.clone();
+
+        let err = client
+            .call_tool("plugin-tool".to_string(), json!({}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not supported"), "{}", err);
+
+        client.ping().await.unwrap();
+
+        assert!(clone
+            .get_server_capabilities()
+            .is_some_and(|capabilities| capabilities.tools.is_some()));
+        client
+            .call_tool("plugin-tool".to_string(), json!({}))
+            .await
+            .unwrap();
+    }
+
+    fn tool_client(
+        call_result: serde_json::Value,
+    ) -> (Client<MockTransport>, Arc<Mutex<Vec<TransportMessage>>>) {
+        let response = |id: i64, result: serde_json::Value| {
+            TransportMessage::Response(JSONRPCResponse {
+                jsonrpc: "2.0".to_string(),
+                id: RequestId::from(id),
+                payload: ResponsePayload::Result(result),
+            })
+        };
+        let init_response = response(
+            1,
+            json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": {"tools": {}},
+                "serverInfo": {"name": "test-server", "version": "1.0.0"}
+            }),
+        );
+        let transport =
+            MockTransport::with_responses(vec![response(2, call_result), init_response]);
+        let sent = transport.sent_messages.clone();
+        (Client::new(transport), sent)
+    }
+
+    #[tokio::test]
+    async fn test_call_tool_typed() {
+        #[derive(Serialize)]
+        struct AddArgs {
+            a: i64,
+            b: i64,
+        }
+
+        #[derive(Debug, serde::Deserialize, PartialEq)]
+        struct Sum {
+            sum: i64,
+        }
+
+        let (mut client, sent) = tool_client(json!({
+            "content": [{"type": "text", "text": "{\"sum\": 5}"}]
+        }));
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+
+        let result: Sum = client
+            .call_tool_typed("add", AddArgs { a: 2, b: 3 })
+            .await
+            .unwrap();
+        assert_eq!(result, Sum { sum: 5 });
+
+        let sent = sent.lock().unwrap();
+        let Some(TransportMessage::Request { request, .. }) = sent.last() else {
+            panic!("expected a tools/call request");
+        };
+        let Request::Client(request) = request else {
+            panic!("expected a client request");
+        };
+        let ClientRequest::CallTool(call) = request.as_ref() else {
+            panic!("expected a tools/call request");
+        };
+        assert_eq!(call.name, "add");
+        assert_eq!(call.arguments, json!({"a": 2, "b": 3}));
+    }
+
+    #[tokio::test]
+    async fn test_call_tool_typed_errors() {
+        let (mut client, _) = tool_client(json!({
+            "content": [{"type": "text", "text": "division by zero"}],
+            "isError": true
+        }));
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+        let err = client
+            .call_tool_typed::<_, serde_json::Value>("divide", json!({"a": 1, "b": 0}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("division by zero"), "{}", err);
+
+        let (mut client, _) = tool_client(json!({
+            "content": [{"type": "text", "text": "{\"total\": 5}"}]
+        }));
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+        let err = client
+            .call_tool_typed::<_, std::collections::HashMap<String, String>>("add", json!({}))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::Protocol { code, .. } if code == crate::error::ErrorCode::PARSE_ERROR),
+            "{:?}",
+            err
+        );
+    }
+
     #[tokio::test]
     async fn test_send_progress() {
         let init_response = TransportMessage::Response(JSONRPCResponse {
@@ -1816,6 +4711,7 @@ mod tests {
                     name: "test".to_string(),
                     value: "t".to_string(),
                 },
+                meta: None,
             })
             .await;
         assert!(result.is_ok());
@@ -1869,4 +4765,378 @@ mod tests {
         let contents = result.unwrap();
         assert_eq!(contents.contents.len(), 1);
     }
+
+    #[tokio::test]
+    async fn test_read_resource_if_changed() {
+        let response = |result: serde_json::Value| {
+            TransportMessage::Response(JSONRPCResponse {
+                jsonrpc: "2.0".to_string(),
+                id: RequestId::from(1i64),
+                payload: ResponsePayload::Result(result),
+            })
+        };
+        let init_response = response(json!({
+            "protocolVersion": "2024-11-05",
+            "capabilities": {"resources": {}},
+            "serverInfo": {"name": "test-server", "version": "1.0.0"}
+        }));
+        let modified = response(json!({
+            "contents": [{"type": "text", "text": "new"}],
+            "contentHash": "sha256:new"
+        }));
+        let unchanged = response(json!({
+            "contents": [],
+            "contentHash": "sha256:new",
+            "notModified": true
+        }));
+
+        let transport = MockTransport::with_responses(vec![unchanged, modified, init_response]);
+        let sent = transport.sent_messages.clone();
+        let mut client = Client::new(transport);
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+
+        let result = client
+            .read_resource_if_changed("test://data".to_string(), "sha256:old".to_string())
+            .await
+            .unwrap()
+            .expect("a modified resource should be returned");
+        assert_eq!(result.content_hash.as_deref(), Some("sha256:new"));
+
+        let result = client
+            .read_resource_if_changed("test://data".to_string(), "sha256:new".to_string())
+            .await
+            .unwrap();
+        assert!(result.is_none());
+
+        let sent = sent.lock().unwrap();
+        let Some(TransportMessage::Request {
+            request: Request::Client(request),
+            ..
+        }) = sent.last()
+        else {
+            panic!("expected a client request");
+        };
+        let ClientRequest::ReadResource(read) = request.as_ref() else {
+            panic!("expected a resources/read request");
+        };
+        let known_hash = read
+            .meta
+            .as_ref()
+            .and_then(|meta| meta.if_none_match.as_deref());
+        assert_eq!(known_hash, Some("sha256:new"));
+    }
+
+    #[test]
+    fn test_apply_resource_patch_from_notification() {
+        use crate::types::protocol::ChangeKind;
+
+        let notification = crate::shared::protocol_helpers::parse_notification(json!({
+            "jsonrpc": "2.0",
+            "method": "notifications/resources/updated",
+            "params": {
+                "uri": "file:///state.json",
+                "change": {
+                    "kind": "patch",
+                    "patch": [
+                        {"op": "replace", "path": "/status", "value": "done"},
+                        {"op": "add", "path": "/items/-", "value": 3}
+                    ]
+                }
+            }
+        }))
+        .unwrap();
+        let Notification::Server(crate::types::ServerNotification::ResourceUpdated(params)) =
+            notification
+        else {
+            panic!("Expected resource updated notification");
+        };
+
+        let mut cached = json!({"status": "running", "items": [1, 2]});
+        assert!(params.change.apply(&mut cached).unwrap());
+        assert_eq!(cached, json!({"status": "done", "items": [1, 2, 3]}));
+
+        // A plain update carries no change and asks for a full re-read
+        let full: crate::types::protocol::ResourceUpdatedParams =
+            serde_json::from_value(json!({"uri": "file:///state.json"})).unwrap();
+        assert_eq!(full.change, ChangeKind::Full);
+        assert!(!full.change.apply(&mut cached).unwrap());
+
+        let mut deleted = cached.clone();
+        assert!(ChangeKind::Deleted.apply(&mut deleted).unwrap());
+        assert!(deleted.is_null());
+    }
+
+    #[test]
+    fn test_invalid_resource_patch_keeps_cached_copy() {
+        use crate::types::protocol::ChangeKind;
+
+        let change: ChangeKind = serde_json::from_value(json!({
+            "kind": "patch",
+            "patch": [{"op": "remove", "path": "/missing"}]
+        }))
+        .unwrap();
+        let mut cached = json!({"status": "running"});
+        assert!(change.apply(&mut cached).is_err());
+        assert_eq!(cached, json!({"status": "running"}));
+    }
+
+    #[tokio::test]
+    async fn test_boxed_clients_over_different_transports() {
+        let init_response = TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(1i64),
+            payload: ResponsePayload::Result(json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": {},
+                "serverInfo": {"name": "test-server", "version": "1.0.0"}
+            })),
+        });
+
+        let mut clients: Vec<BoxedClient> = vec![
+            Client::new_boxed(Box::new(MockTransport::with_responses(vec![init_response]))),
+            Client::new_boxed(Box::new(crate::shared::StdioTransport::new())),
+        ];
+        assert_eq!(clients.len(), 2);
+
+        let result = clients[0].initialize(ClientCapabilities::default()).await;
+        assert!(result.is_ok(), "{:?}", result.err());
+        assert!(clients[0].get_server_capabilities().is_some());
+        assert!(clients[1].get_server_capabilities().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_call_tool_exposes_meta() {
+        let init_response = TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(1i64),
+            payload: ResponsePayload::Result(json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": {"tools": {}},
+                "serverInfo": {"name": "test-server", "version": "1.0.0"}
+            })),
+        });
+        let call_response = TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(2i64),
+            payload: ResponsePayload::Result(json!({
+                "content": [{"type": "text", "text": "42"}],
+                "_meta": {"model": "small", "elapsedMs": 12}
+            })),
+        });
+
+        let transport = MockTransport::with_responses(vec![call_response, init_response]);
+        let mut client = Client::new(transport);
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+
+        let result = client
+            .call_tool("answer".to_string(), json!({}))
+            .await
+            .unwrap();
+        assert_eq!(
+            result.meta,
+            Some(json!({"model": "small", "elapsedMs": 12}))
+        );
+    }
+
+    fn tools_response(id: i64, schema: serde_json::Value) -> TransportMessage {
+        TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(id),
+            payload: ResponsePayload::Result(json!({
+                "tools": [{"name": "search", "inputSchema": schema}]
+            })),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_validate_tool_args() {
+        let init_response = TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(1i64),
+            payload: ResponsePayload::Result(json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": {"tools": {"listChanged": true}},
+                "serverInfo": {"name": "test-server", "version": "1.0.0"}
+            })),
+        });
+        let schema = json!({
+            "type": "object",
+            "properties": {"query": {"type": "string"}, "limit": {"type": "integer"}},
+            "required": ["query"]
+        });
+        let renamed_schema = json!({
+            "type": "object",
+            "properties": {"q": {"type": "string"}},
+            "required": ["q"]
+        });
+
+        let ping_response = TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(3i64),
+            payload: ResponsePayload::Result(json!({})),
+        });
+        let transport = MockTransport::with_responses(vec![
+            tools_response(5, renamed_schema.clone()),
+            tools_response(4, renamed_schema),
+            ping_response,
+            TransportMessage::Notification(Notification::Server(ServerNotification::ToolsChanged)),
+            tools_response(2, schema),
+            init_response,
+        ]);
+        let mut client = Client::new(transport);
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+
+        client
+            .validate_tool_args("search", &json!({"query": "rust", "limit": 5}))
+            .await
+            .unwrap();
+
+        // Served from the cache, without another tools/list
+        let err = client
+            .validate_tool_args("search", &json!({"limit": "five"}))
+            .await
+            .unwrap_err();
+        let Error::Validation(message) = err else {
+            panic!("Expected validation error, got {:?}", err);
+        };
+        assert!(message.contains("query"), "{}", message);
+        assert!(message.contains("/limit: expected integer"), "{}", message);
+
+        // A tool list change read with the ping refetches the schema
+        client.ping().await.unwrap();
+        assert!(client
+            .validate_tool_args("search", &json!({"query": "rust"}))
+            .await
+            .is_err());
+        client
+            .validate_tool_args("search", &json!({"q": "rust"}))
+            .await
+            .unwrap();
+
+        // Unknown tools trigger a refetch in case the tool was just added
+        let err = client
+            .validate_tool_args("missing", &json!({}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not found"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_call_tool_with_timeout_sends_deadline() {
+        let init_response = TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(1i64),
+            payload: ResponsePayload::Result(json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": {"tools": {}},
+                "serverInfo": {"name": "test-server", "version": "1.0.0"}
+            })),
+        });
+        let call_response = TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(2i64),
+            payload: ResponsePayload::Result(json!({"content": []})),
+        });
+
+        let transport = MockTransport::with_responses(vec![call_response, init_response]);
+        let sent = transport.sent_messages.clone();
+        let mut client = Client::new(transport);
+        client
+            .initialize(ClientCapabilities::default())
+            .await
+            .unwrap();
+
+        let before = chrono::Utc::now();
+        client
+            .call_tool_with_timeout(
+                "slow".to_string(),
+                json!({}),
+                std::time::Duration::from_secs(30),
+            )
+            .await
+            .unwrap();
+        assert!(client.active_requests.is_empty());
+
+        let sent = sent.lock().unwrap();
+        let Some(TransportMessage::Request { request, .. }) = sent.last() else {
+            panic!("expected a tools/call request");
+        };
+        let Request::Client(request) = request else {
+            panic!("expected a client request");
+        };
+        let ClientRequest::CallTool(call) = request.as_ref() else {
+            panic!("expected a tools/call request");
+        };
+        let deadline = call.meta.as_ref().and_then(|meta| meta.deadline).unwrap();
+        assert!(deadline > before + chrono::Duration::seconds(29));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_embedded_resource() {
+        let init_response = TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(1i64),
+            payload: ResponsePayload::Result(json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": {"resources": {}},
+                "serverInfo": {"name": "test-server", "version": "1.0.0"}
+            })),
+        });
+        let read_response = TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(2i64),
+            payload: ResponsePayload::Result(json!({
+                "contents": [{"type": "text", "text": "full report"}]
+            })),
+        });
+
+        let transport = MockTransport::with_responses(vec![read_response, init_response]);
+        let sent = transport.sent_messages.clone();
+        let mut client = Client::new(transport);
+        client
+            .initialize(ClientCapabilities {
+                resources: Some(ResourceCapabilities::default()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let reference = Content::Resource {
+            uri: "file:///reports/large.txt".to_string(),
+            text: None,
+            mime_type: Some("text/plain".to_string()),
+        };
+        let resolved = client.resolve_content(&reference).await.unwrap();
+        let Content::Text { text } = &resolved.contents[0] else {
+            panic!("Expected text content");
+        };
+        assert_eq!(text, "full report");
+
+        let read_request = sent.lock().unwrap().iter().any(|message| {
+            matches!(
+                message,
+                TransportMessage::Request { request: Request::Client(req), .. }
+                    if matches!(req.as_ref(), ClientRequest::ReadResource(r) if r.uri == "file:///reports/large.txt")
+            )
+        });
+        assert!(read_request);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_non_resource_content_fails() {
+        let client = Client::new(MockTransport::new());
+        let text = Content::Text {
+            text: "inline".to_string(),
+        };
+        assert!(client.resolve_content(&text).await.is_err());
+    }
 }
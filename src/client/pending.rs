@@ -0,0 +1,296 @@
+//! Requests waiting for a response from the server.
+//!
+//! The number of waiting requests is bounded by
+//! [`ProtocolOptions::max_pending`](crate::shared::ProtocolOptions::max_pending), so a
+//! server that stops answering cannot make the client grow without limit. With
+//! [`ProtocolOptions::pending_timeout`](crate::shared::ProtocolOptions::pending_timeout)
+//! set, a background sweep fails requests that have waited longer than that. The
+//! timeout can be changed with [`Client::set_timeout`](super::Client::set_timeout); each
+//! request keeps the timeout in effect when it was sent.
+//!
+//! Responses read by the client's receive loop (see [`router`](super::router)) are
+//! delivered to the request with the same ID.
+
+use crate::error::{Error, Result};
+use crate::types::{JSONRPCResponse, RequestId};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, Notify};
+
+/// Shortest interval between sweeps for stale requests.
+const MIN_SWEEP_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Request statistics of a [`Client`](super::Client).
+#[derive(Debug, Clone)]
+pub struct ClientStats {
+    /// Number of requests waiting for a response
+    pub pending_requests: usize,
+    /// Maximum number of requests that may wait at once
+    pub max_pending: usize,
+}
+
+/// A request waiting for a response.
+#[derive(Debug)]
+struct PendingRequest {
+    started: Instant,
+    /// Timeout in effect when the request was sent
+    timeout: Option<Duration>,
+    /// Completes the waiting request with its response or an error
+    reply: oneshot::Sender<Result<JSONRPCResponse>>,
+}
+
+/// Bounded set of requests waiting for a response, shared by a client and its clones.
+#[derive(Debug)]
+pub(crate) struct PendingRequests {
+    requests: parking_lot::Mutex<HashMap<RequestId, PendingRequest>>,
+    max_pending: usize,
+    timeout: parking_lot::Mutex<Option<Duration>>,
+    sweeping: AtomicBool,
+    /// Notified when the last waiting request is removed
+    emptied: Notify,
+}
+
+impl PendingRequests {
+    pub(crate) fn new(max_pending: usize, timeout: Option<Duration>) -> Self {
+        Self {
+            requests: parking_lot::Mutex::default(),
+            max_pending,
+            timeout: parking_lot::Mutex::new(timeout),
+            sweeping: AtomicBool::new(false),
+            emptied: Notify::new(),
+        }
+    }
+
+    /// Start tracking a request.
+    ///
+    /// The returned receiver yields the response once it is delivered, or an error if
+    /// the request is cancelled, the client is closed, or the request times out. Fails
+    /// if `max_pending` requests are already waiting.
+    pub(crate) fn insert(
+        self: &Arc<Self>,
+        id: RequestId,
+    ) -> Result<oneshot::Receiver<Result<JSONRPCResponse>>> {
+        let (reply, replied) = oneshot::channel();
+        {
+            let mut requests = self.requests.lock();
+            if requests.len() >= self.max_pending {
+                return Err(Error::InvalidState(format!(
+                    "Too many pending requests: {} are already waiting for a response (max_pending)",
+                    requests.len()
+                )));
+            }
+            requests.insert(
+                id,
+                PendingRequest {
+                    started: Instant::now(),
+                    timeout: *self.timeout.lock(),
+                    reply,
+                },
+            );
+        }
+        self.start_sweeper();
+        Ok(replied)
+    }
+
+    /// Change the timeout for requests sent from now on.
+    pub(crate) fn set_timeout(self: &Arc<Self>, timeout: Option<Duration>) {
+        *self.timeout.lock() = timeout;
+        self.start_sweeper();
+    }
+
+    /// Timeout for requests sent from now on.
+    pub(crate) fn timeout(&self) -> Option<Duration> {
+        *self.timeout.lock()
+    }
+
+    /// Stop tracking a request, e.g. because its caller gave up waiting.
+    pub(crate) fn remove(&self, id: &RequestId) {
+        self.take(id);
+    }
+
+    /// Complete the request answered by `response`.
+    ///
+    /// Returns `false` if no request with the response's ID is waiting.
+    pub(crate) fn deliver(&self, response: JSONRPCResponse) -> bool {
+        match self.take(&response.id) {
+            Some(request) => {
+                let _ = request.reply.send(Ok(response));
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Fail a waiting request with `error`.
+    pub(crate) fn abort(&self, id: &RequestId, error: Error) {
+        if let Some(request) = self.take(id) {
+            let _ = request.reply.send(Err(error));
+        }
+    }
+
+    /// Fail every waiting request with an error built by `error`.
+    ///
+    /// Returns the IDs of the failed requests. Requests inserted meanwhile are kept.
+    pub(crate) fn abort_all(&self, error: impl Fn() -> Error) -> Vec<RequestId> {
+        let requests: Vec<_> = self.requests.lock().drain().collect();
+        self.emptied.notify_waiters();
+        requests
+            .into_iter()
+            .map(|(id, request)| {
+                let _ = request.reply.send(Err(error()));
+                id
+            })
+            .collect()
+    }
+
+    /// Fail requests that have waited longer than their timeout.
+    ///
+    /// Returns the number of requests failed.
+    pub(crate) fn sweep(&self) -> usize {
+        let stale: Vec<_> = {
+            let mut requests = self.requests.lock();
+            let ids: Vec<_> = requests
+                .iter()
+                .filter(|(_, request)| {
+                    request
+                        .timeout
+                        .is_some_and(|timeout| request.started.elapsed() >= timeout)
+                })
+                .map(|(id, _)| id.clone())
+                .collect();
+            let stale: Vec<_> = ids
+                .into_iter()
+                .filter_map(|id| requests.remove(&id))
+                .collect();
+            if requests.is_empty() {
+                self.emptied.notify_waiters();
+            }
+            stale
+        };
+        let count = stale.len();
+        if count > 0 {
+            tracing::warn!("{} request(s) timed out without a response", count);
+        }
+        for request in stale {
+            let millis = request.timeout.map_or(u64::MAX, |timeout| {
+                u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX)
+            });
+            let _ = request.reply.send(Err(Error::Timeout(millis)));
+        }
+        count
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.requests.lock().len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.requests.lock().is_empty()
+    }
+
+    /// Wait until no request is waiting any more.
+    pub(crate) async fn wait_empty(&self) {
+        loop {
+            let emptied = self.emptied.notified();
+            tokio::pin!(emptied);
+            // Registered before checking, so a removal in between is not missed
+            emptied.as_mut().enable();
+            if self.is_empty() {
+                return;
+            }
+            emptied.await;
+        }
+    }
+
+    /// Remove a request, notifying if it was the last one.
+    fn take(&self, id: &RequestId) -> Option<PendingRequest> {
+        let mut requests = self.requests.lock();
+        let request = requests.remove(id);
+        if request.is_some() && requests.is_empty() {
+            self.emptied.notify_waiters();
+        }
+        request
+    }
+
+    pub(crate) fn stats(&self) -> ClientStats {
+        ClientStats {
+            pending_requests: self.len(),
+            max_pending: self.max_pending,
+        }
+    }
+
+    /// Start the periodic sweep once, if a timeout is set.
+    ///
+    /// The sweep runs at a quarter of the current timeout, and stops once the client
+    /// and all its clones are dropped or no request can time out any more.
+    fn start_sweeper(self: &Arc<Self>) {
+        if self.timeout().is_none() || self.sweeping.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let pending: Weak<Self> = Arc::downgrade(self);
+        tokio::spawn(async move {
+            while let Some(interval) = pending
+                .upgrade()
+                .and_then(|pending| pending.sweep_interval())
+            {
+                tokio::time::sleep(interval).await;
+                let Some(pending) = pending.upgrade() else {
+                    break;
+                };
+                pending.sweep();
+            }
+        });
+    }
+
+    /// Interval between sweeps, or `None` (stopping the sweep) if nothing can time out.
+    fn sweep_interval(&self) -> Option<Duration> {
+        let requests = self.requests.lock();
+        let shortest = requests
+            .values()
+            .filter_map(|request| request.timeout)
+            .chain(self.timeout())
+            .min();
+        if shortest.is_none() {
+            // Checked under the requests lock, so a request inserted with a timeout
+            // after this either sees the flag cleared or is covered by this sweep
+            self.sweeping.store(false, Ordering::Release);
+        }
+        shortest.map(|timeout| (timeout / 4).max(MIN_SWEEP_INTERVAL))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bound_is_enforced() {
+        let pending = Arc::new(PendingRequests::new(2, None));
+        let _first = pending.insert(RequestId::from(1i64)).unwrap();
+        let _second = pending.insert(RequestId::from(2i64)).unwrap();
+
+        let err = pending.insert(RequestId::from(3i64)).unwrap_err();
+        assert!(err.to_string().contains("max_pending"), "{}", err);
+        assert_eq!(pending.stats().pending_requests, 2);
+
+        // A response frees a slot
+        pending.remove(&RequestId::from(1i64));
+        assert!(pending.insert(RequestId::from(3i64)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_sweep_times_out_stale_requests() {
+        let pending = Arc::new(PendingRequests::new(10, Some(Duration::from_millis(20))));
+        let aborted = pending.insert(RequestId::from(1i64)).unwrap();
+
+        let err = tokio::time::timeout(Duration::from_secs(5), aborted)
+            .await
+            .expect("sweep should time out the request")
+            .unwrap()
+            .unwrap_err();
+        assert!(matches!(err, Error::Timeout(20)));
+        assert!(pending.is_empty());
+    }
+}
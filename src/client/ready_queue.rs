@@ -0,0 +1,235 @@
+//! Holding requests until `initialize` completes.
+//!
+//! With [`ClientBuilder::queue_until_ready`](super::ClientBuilder::queue_until_ready),
+//! requests issued through clones of a client while it is still initializing wait
+//! here instead of failing with [`Error::InvalidState`]. They fail once no client is
+//! left that could still initialize, e.g. because every remaining clone is itself
+//! waiting.
+
+use crate::error::{Error, Result};
+use crate::types::ServerCapabilities;
+use std::sync::Arc;
+use tokio::sync::{watch, Notify};
+
+/// Default number of requests that may wait for initialization at once.
+pub(crate) const DEFAULT_READY_QUEUE_SIZE: usize = 64;
+
+/// Initialization progress shared by a client and its clones.
+#[derive(Debug, Clone)]
+enum ReadyState {
+    Pending,
+    Ready(Box<ServerCapabilities>),
+    Failed(String),
+}
+
+/// Number of clients sharing the queue, and of requests waiting in it.
+#[derive(Debug, Default)]
+struct Counts {
+    handles: usize,
+    waiting: usize,
+}
+
+impl Counts {
+    /// Whether every client left is waiting, so none can initialize.
+    fn stuck(&self) -> bool {
+        self.waiting > 0 && self.waiting >= self.handles
+    }
+}
+
+/// Bounded queue of requests waiting for initialization.
+#[derive(Debug)]
+pub(crate) struct ReadyQueue {
+    state: watch::Sender<ReadyState>,
+    counts: parking_lot::Mutex<Counts>,
+    /// Wakes waiting requests once no client is left to initialize
+    stuck: Notify,
+    capacity: usize,
+}
+
+/// A client's share of a [`ReadyQueue`], counted so that waiting requests are
+/// released once every client is dropped or waiting.
+#[derive(Debug)]
+pub(crate) struct ReadyHandle(Arc<ReadyQueue>);
+
+impl ReadyHandle {
+    /// Create a queue holding at most `capacity` waiting requests.
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self(Arc::new(ReadyQueue {
+            state: watch::Sender::new(ReadyState::Pending),
+            counts: parking_lot::Mutex::new(Counts {
+                handles: 1,
+                waiting: 0,
+            }),
+            stuck: Notify::new(),
+            capacity,
+        }))
+    }
+}
+
+impl Clone for ReadyHandle {
+    fn clone(&self) -> Self {
+        self.0.counts.lock().handles += 1;
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl Drop for ReadyHandle {
+    fn drop(&mut self) {
+        let mut counts = self.0.counts.lock();
+        counts.handles -= 1;
+        if counts.stuck() {
+            self.0.stuck.notify_waiters();
+        }
+    }
+}
+
+impl std::ops::Deref for ReadyHandle {
+    type Target = ReadyQueue;
+
+    fn deref(&self) -> &ReadyQueue {
+        &self.0
+    }
+}
+
+/// Counts a request as waiting until dropped.
+struct Waiting<'a>(&'a ReadyQueue);
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.counts.lock().waiting -= 1;
+    }
+}
+
+impl ReadyQueue {
+    /// Mark initialization as started again, e.g. when resuming a session.
+    pub(crate) fn reset(&self) {
+        self.state.send_replace(ReadyState::Pending);
+    }
+
+    /// Release waiting requests after a successful initialize.
+    pub(crate) fn ready(&self, capabilities: ServerCapabilities) {
+        self.state
+            .send_replace(ReadyState::Ready(Box::new(capabilities)));
+    }
+
+    /// Fail waiting requests after initialize failed.
+    pub(crate) fn failed(&self, error: &Error) {
+        self.state
+            .send_replace(ReadyState::Failed(error.to_string()));
+    }
+
+    /// Server capabilities, once initialization has succeeded.
+    pub(crate) fn capabilities(&self) -> Option<ServerCapabilities> {
+        match &*self.state.borrow() {
+            ReadyState::Ready(capabilities) => Some((**capabilities).clone()),
+            _ => None,
+        }
+    }
+
+    /// Number of requests currently waiting.
+    #[cfg(test)]
+    pub(crate) fn waiting(&self) -> usize {
+        self.counts.lock().waiting
+    }
+
+    /// Wait until initialization finishes.
+    ///
+    /// Errors immediately if the queue is full, with the initialize error if
+    /// initialization fails, and once no client is left that is not waiting itself.
+    pub(crate) async fn wait(&self) -> Result<()> {
+        let stuck = self.stuck.notified();
+        tokio::pin!(stuck);
+        // Registered before counting, so a client dropped in between is not missed
+        stuck.as_mut().enable();
+        let abandoned = {
+            let mut counts = self.counts.lock();
+            if counts.waiting >= self.capacity {
+                return Err(Error::InvalidState(format!(
+                    "Client not initialized and too many requests ({}) are waiting for initialization",
+                    self.capacity
+                )));
+            }
+            counts.waiting += 1;
+            counts.stuck()
+        };
+        let _waiting = Waiting(self);
+        if abandoned && self.is_pending() {
+            return Err(Self::abandoned());
+        }
+
+        let mut state = self.state.subscribe();
+        let state = tokio::select! {
+            state = state.wait_for(|state| !matches!(state, ReadyState::Pending)) => state
+                .map_err(|_| Error::InvalidState("Client dropped during initialization".into()))?,
+            () = stuck => return Err(Self::abandoned()),
+        };
+        match &*state {
+            ReadyState::Failed(message) => Err(Error::InvalidState(format!(
+                "Client initialization failed: {}",
+                message
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    fn is_pending(&self) -> bool {
+        matches!(*self.state.borrow(), ReadyState::Pending)
+    }
+
+    fn abandoned() -> Error {
+        Error::InvalidState("Client not initialized and no client is left to initialize it".into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_queue_is_bounded() {
+        let queue = ReadyHandle::new(1);
+        // Another client that can still initialize
+        let _initializing = queue.clone();
+        let waiting = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.wait().await }
+        });
+        while queue.waiting() < 1 {
+            tokio::task::yield_now().await;
+        }
+
+        let err = queue.wait().await.unwrap_err();
+        assert!(err.to_string().contains("too many requests"), "{}", err);
+
+        queue.ready(ServerCapabilities::default());
+        waiting.await.unwrap().unwrap();
+        assert!(queue.capabilities().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_waiting_fails_without_a_client_to_initialize() {
+        // The only client waits itself, as when it is used before initialize
+        let queue = ReadyHandle::new(4);
+        let err = tokio::time::timeout(std::time::Duration::from_secs(1), queue.wait())
+            .await
+            .expect("waiting without a client to initialize hangs")
+            .unwrap_err();
+        assert!(err.to_string().contains("no client is left"), "{}", err);
+
+        // A clone waiting while the other client is dropped
+        let waiting = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.wait().await }
+        });
+        while queue.waiting() < 1 {
+            tokio::task::yield_now().await;
+        }
+        drop(queue);
+        let err = tokio::time::timeout(std::time::Duration::from_secs(1), waiting)
+            .await
+            .expect("waiting after the client was dropped hangs")
+            .unwrap()
+            .unwrap_err();
+        assert!(err.to_string().contains("no client is left"), "{}", err);
+    }
+}
@@ -0,0 +1,303 @@
+//! Routing of incoming messages to the requests waiting for them.
+//!
+//! While requests are waiting, a background task reads from the transport and
+//! delivers each response to the request with the same ID, so concurrent requests
+//! from clones of a client each get their own response whatever order the server
+//! answers in. Notifications read meanwhile first update the server capabilities and
+//! tool schemas the client keeps, then go to the subscriber of
+//! [`Client::notifications`](super::Client::notifications), and elicitation requests
+//! from the server to the subscriber of
+//! [`Client::elicitation_requests`](super::Client::elicitation_requests). `ping`
+//! requests from the server are answered right away, and any other request the
+//! client does not handle gets a method-not-found error, so the server is never left
+//! waiting.
+//!
+//! Sending and receiving share the transport, so a task about to send interrupts the
+//! loop's pending `receive`, which is why [`Transport::receive`] must be cancel safe.
+//! The loop stops once no request is waiting and nobody subscribes to notifications or
+//! elicitations, leaving later messages unread until the next request.
+
+use super::elicitation::Elicitations;
+use super::pending::PendingRequests;
+use crate::error::{Error, ErrorCode, TransportError};
+use crate::shared::{Transport, TransportMessage};
+use crate::types::{
+    JSONRPCError, JSONRPCResponse, Notification, Request, RequestId, ServerCapabilities,
+    ServerNotification, ServerRequest,
+};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Notify, RwLock, RwLockWriteGuard};
+
+/// Number of notifications kept for a subscriber that is not keeping up; later
+/// notifications are dropped until it catches up.
+const NOTIFICATION_CAPACITY: usize = 256;
+
+/// Sends notifications to the current subscriber, if any.
+///
+/// Notifications are sent without waiting, so a subscriber falling behind never
+/// holds up the responses read after them.
+#[derive(Debug, Default)]
+pub(crate) struct Notifications {
+    subscriber: parking_lot::RwLock<Option<mpsc::Sender<Notification>>>,
+}
+
+impl Notifications {
+    /// Replace the subscriber with a new one.
+    pub(crate) fn subscribe(&self) -> mpsc::Receiver<Notification> {
+        let (tx, rx) = mpsc::channel(NOTIFICATION_CAPACITY);
+        *self.subscriber.write() = Some(tx);
+        rx
+    }
+
+    /// The subscriber, unless there is none or it went away.
+    fn subscriber(&self) -> Option<mpsc::Sender<Notification>> {
+        self.subscriber
+            .read()
+            .as_ref()
+            .filter(|tx| !tx.is_closed())
+            .cloned()
+    }
+
+    fn send(&self, notification: Notification) {
+        match self.subscriber() {
+            Some(tx) => {
+                if let Err(e) = tx.try_send(notification) {
+                    tracing::warn!("Dropping notification, subscriber is behind: {:?}", e);
+                }
+            },
+            None => tracing::debug!("Dropping notification: {:?}", notification),
+        }
+    }
+}
+
+/// What the client knows about the server, kept up to date by its notifications.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ServerState {
+    /// Server capabilities, replaced when they change
+    pub(crate) capabilities: Arc<parking_lot::RwLock<Option<ServerCapabilities>>>,
+    /// Tool input schemas by tool name, cleared when the tool list changes
+    pub(crate) tool_schemas: Arc<RwLock<HashMap<String, serde_json::Value>>>,
+}
+
+impl ServerState {
+    /// Update the state changed by `notification`, if any.
+    async fn apply(&self, notification: &Notification) {
+        match notification {
+            Notification::Server(ServerNotification::ToolsChanged) => {
+                self.tool_schemas.write().await.clear();
+            },
+            Notification::Server(ServerNotification::CapabilitiesChanged(capabilities)) => {
+                *self.capabilities.write() = Some(capabilities.clone());
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Receive loop state shared by a client and its clones.
+#[derive(Debug, Default)]
+pub(crate) struct Router {
+    /// Whether the receive loop is running
+    running: AtomicBool,
+    /// Number of tasks waiting to lock the transport
+    waiting: AtomicUsize,
+    /// Wakes the loop to let go of the transport
+    interrupt: Notify,
+    /// Set when the server stopped answering, since a restarted server process
+    /// has to be initialized again
+    session_lost: AtomicBool,
+}
+
+/// Counts a task as waiting for the transport until dropped.
+struct Waiting<'a>(&'a AtomicUsize);
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl Router {
+    /// Whether the server stopped answering since the client was last initialized.
+    pub(crate) fn session_lost(&self) -> bool {
+        self.session_lost.load(Ordering::Acquire)
+    }
+
+    /// Record that the client was initialized again.
+    pub(crate) fn session_restored(&self) {
+        self.session_lost.store(false, Ordering::Release);
+    }
+
+    /// Lock the transport, interrupting the receive loop if it holds it.
+    pub(crate) async fn lock<'a, T>(&self, transport: &'a RwLock<T>) -> RwLockWriteGuard<'a, T> {
+        self.waiting.fetch_add(1, Ordering::AcqRel);
+        let _waiting = Waiting(&self.waiting);
+        self.interrupt.notify_one();
+        transport.write().await
+    }
+
+    /// Start the receive loop unless it is already running.
+    pub(crate) fn start<T: Transport + 'static>(
+        self: &Arc<Self>,
+        transport: &Arc<RwLock<T>>,
+        pending: &Arc<PendingRequests>,
+        notifications: &Arc<Notifications>,
+        elicitations: &Arc<Elicitations>,
+        server: &ServerState,
+    ) {
+        if self.running.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        // Spawned through the runtime abstraction so the loop also runs in the browser
+        crate::shared::runtime::spawn(Arc::clone(self).run(
+            Arc::clone(transport),
+            Arc::clone(pending),
+            Arc::clone(notifications),
+            Arc::clone(elicitations),
+            server.clone(),
+        ));
+    }
+
+    async fn run<T: Transport>(
+        self: Arc<Self>,
+        transport: Arc<RwLock<T>>,
+        pending: Arc<PendingRequests>,
+        notifications: Arc<Notifications>,
+        elicitations: Arc<Elicitations>,
+        server: ServerState,
+    ) {
+        // Cleared once receiving fails, so that a broken transport is only read
+        // again for new requests
+        let mut healthy = true;
+        loop {
+            let listening = || {
+                healthy
+                    && (notifications.subscriber().is_some() || elicitations.subscriber().is_some())
+            };
+            if !self.keep_running(&pending, listening) {
+                break;
+            }
+            let subscriber = notifications.subscriber().filter(|_| healthy);
+            let elicitation_subscriber = elicitations.subscriber().filter(|_| healthy);
+            let mut guard = transport.write().await;
+            if self.waiting.load(Ordering::Acquire) > 0 {
+                // A sender queued for the lock goes first
+                drop(guard);
+                tokio::task::yield_now().await;
+                continue;
+            }
+            let received = tokio::select! {
+                biased;
+                () = self.interrupt.notified() => continue,
+                () = pending.wait_empty(),
+                    if subscriber.is_none() && elicitation_subscriber.is_none() => continue,
+                () = closed(subscriber.as_ref()), if subscriber.is_some() => continue,
+                () = closed(elicitation_subscriber.as_ref()),
+                    if elicitation_subscriber.is_some() => continue,
+                received = guard.receive() => received,
+            };
+            drop(guard);
+
+            match received {
+                Ok(TransportMessage::Response(response)) => {
+                    let id = response.id.clone();
+                    if !pending.deliver(response) {
+                        tracing::warn!("Ignoring response to unknown request {}", id);
+                    }
+                },
+                Ok(TransportMessage::Notification(notification)) => {
+                    if let Notification::Cancelled(cancelled) = &notification {
+                        elicitations.cancel(&cancelled.request_id);
+                    }
+                    server.apply(&notification).await;
+                    notifications.send(notification);
+                },
+                Ok(TransportMessage::Request {
+                    id,
+                    request: Request::Server(request),
+                }) => match *request {
+                    ServerRequest::ElicitInput(elicitation) => {
+                        elicitations.deliver(id, *elicitation);
+                    },
+                    ServerRequest::Ping => {
+                        respond(
+                            &transport,
+                            JSONRPCResponse::success(id, serde_json::json!({})),
+                        )
+                        .await;
+                    },
+                    _ => respond(&transport, method_not_found(id)).await,
+                },
+                Ok(TransportMessage::Request { id, .. }) => {
+                    respond(&transport, method_not_found(id)).await;
+                },
+                Err(e) => {
+                    // The error cannot be told apart by request, so it fails them all
+                    tracing::warn!("Receiving from the transport failed: {}", e);
+                    if matches!(e, Error::Transport(TransportError::Unresponsive(_))) {
+                        // Whether or not the server process was restarted, its
+                        // session is gone
+                        self.session_lost.store(true, Ordering::Release);
+                    }
+                    let message = e.to_string();
+                    pending.abort_all(|| match &e {
+                        Error::Transport(TransportError::ConnectionClosed) => {
+                            TransportError::ConnectionClosed.into()
+                        },
+                        Error::Transport(TransportError::Unresponsive(timeout)) => {
+                            TransportError::Unresponsive(*timeout).into()
+                        },
+                        _ => TransportError::Io(message.clone()).into(),
+                    });
+                    healthy = false;
+                },
+            }
+        }
+    }
+
+    /// Whether the loop should go on, stopping it once no request is waiting unless
+    /// it is `listening` for notifications.
+    fn keep_running(&self, pending: &PendingRequests, listening: impl Fn() -> bool) -> bool {
+        if listening() || !pending.is_empty() {
+            return true;
+        }
+        self.running.store(false, Ordering::Release);
+        // A request or subscriber added meanwhile may have seen the loop as running
+        (listening() || !pending.is_empty()) && !self.running.swap(true, Ordering::AcqRel)
+    }
+}
+
+/// Send `response` to a request from the server.
+async fn respond<T: Transport>(transport: &RwLock<T>, response: JSONRPCResponse) {
+    let id = response.id.clone();
+    if let Err(e) = transport
+        .write()
+        .await
+        .send(TransportMessage::Response(response))
+        .await
+    {
+        tracing::warn!("Failed to answer request {} from the server: {}", id, e);
+    }
+}
+
+/// Error response to a request from the server the client does not handle.
+fn method_not_found(id: RequestId) -> JSONRPCResponse {
+    tracing::warn!("Rejecting unsupported request {} from the server", id);
+    JSONRPCResponse::error(
+        id,
+        JSONRPCError::new(
+            ErrorCode::METHOD_NOT_FOUND.as_i32(),
+            "Method not supported by the client",
+        ),
+    )
+}
+
+/// Wait until the receiver of `subscriber` is dropped.
+async fn closed<M>(subscriber: Option<&mpsc::Sender<M>>) {
+    match subscriber {
+        Some(tx) => tx.closed().await,
+        None => std::future::pending().await,
+    }
+}
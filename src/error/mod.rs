@@ -93,6 +93,10 @@ impl ErrorCode {
     pub const AUTHENTICATION_REQUIRED: Self = Self(-32003);
     /// Permission denied (-32004)
     pub const PERMISSION_DENIED: Self = Self(-32004);
+    /// Requested tool, prompt or resource does not exist (-32005)
+    pub const NOT_FOUND: Self = Self(-32005);
+    /// Client holds as many resource subscriptions as the server allows (-32011)
+    pub const SUBSCRIPTION_LIMIT_REACHED: Self = Self(-32011);
 
     /// Create a custom error code.
     ///
@@ -227,6 +231,8 @@ impl ErrorCode {
             -32002 => Some(Self::UNSUPPORTED_CAPABILITY),
             -32003 => Some(Self::AUTHENTICATION_REQUIRED),
             -32004 => Some(Self::PERMISSION_DENIED),
+            -32005 => Some(Self::NOT_FOUND),
+            -32011 => Some(Self::SUBSCRIPTION_LIMIT_REACHED),
             _ => None,
         }
     }
@@ -265,6 +271,15 @@ pub enum TransportError {
     #[error("Invalid message format: {0}")]
     InvalidMessage(String),
 
+    /// Request that could not be parsed, but whose ID could be recovered
+    #[error("Malformed request {id}: {message}")]
+    MalformedRequest {
+        /// ID of the malformed request, used to address the error response
+        id: crate::types::RequestId,
+        /// Description of what was wrong with the request
+        message: String,
+    },
+
     /// Serialization error
     #[error("Serialization error: {0}")]
     Serialization(String),
@@ -281,6 +296,45 @@ pub enum TransportError {
     #[error("Send error: {0}")]
     Send(String),
 
+    /// Host name of the server could not be resolved
+    #[error("DNS lookup failed for '{host}': {message}")]
+    Dns {
+        /// Host that was looked up
+        host: String,
+        /// Why the lookup failed
+        message: String,
+    },
+
+    /// TLS negotiation with the server failed, e.g. an untrusted certificate
+    #[error("TLS error: {0}")]
+    Tls(String),
+
+    /// Server answered the connection handshake with a non-success HTTP status
+    #[error("Handshake rejected with HTTP {status}: {reason}")]
+    HandshakeRejected {
+        /// HTTP status code of the response
+        status: u16,
+        /// Response body, or the status's reason phrase if the body is empty
+        reason: String,
+    },
+
+    /// Connection was not established in time
+    #[error("Connection timed out after {0:?}")]
+    ConnectTimeout(std::time::Duration),
+
+    /// Peer closed the connection with a close code
+    #[error("Connection closed with code {code}: {reason}")]
+    ClosedWithCode {
+        /// Close code sent by the peer, e.g. 1008 for a policy violation
+        code: u16,
+        /// Close reason sent by the peer, possibly empty
+        reason: String,
+    },
+
+    /// Peer process did not answer a keep-alive ping in time
+    #[error("Peer did not answer a keep-alive ping within {0:?}")]
+    Unresponsive(std::time::Duration),
+
     /// WebSocket error (when feature enabled)
     #[cfg(feature = "websocket")]
     #[error("WebSocket error: {0}")]
@@ -921,6 +975,37 @@ impl Error {
             _ => None,
         }
     }
+
+    /// The JSON-RPC error code used when this error is sent in a response.
+    ///
+    /// Protocol errors keep their own code; other variants map to the closest
+    /// standard or MCP-specific code, falling back to [`ErrorCode::INTERNAL_ERROR`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::{Error, error::ErrorCode};
+    ///
+    /// assert_eq!(Error::validation("bad input").json_rpc_code(), ErrorCode::INVALID_PARAMS);
+    /// assert_eq!(Error::not_found("tool 'x'").json_rpc_code(), ErrorCode::NOT_FOUND);
+    /// assert_eq!(Error::internal("oops").json_rpc_code(), ErrorCode::INTERNAL_ERROR);
+    /// ```
+    pub fn json_rpc_code(&self) -> ErrorCode {
+        match self {
+            Self::Protocol { code, .. } => *code,
+            Self::Validation(_) => ErrorCode::INVALID_PARAMS,
+            Self::NotFound(_) => ErrorCode::NOT_FOUND,
+            Self::Authentication(_) => ErrorCode::AUTHENTICATION_REQUIRED,
+            Self::Timeout(_) => ErrorCode::REQUEST_TIMEOUT,
+            Self::UnsupportedCapability(_) => ErrorCode::UNSUPPORTED_CAPABILITY,
+            Self::InvalidState(_) => ErrorCode::INVALID_REQUEST,
+            Self::Transport(_)
+            | Self::Serialization(_)
+            | Self::Internal(_)
+            | Self::Cancelled
+            | Self::Other(_) => ErrorCode::INTERNAL_ERROR,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -945,4 +1030,40 @@ mod tests {
         let err = Error::validation("invalid field");
         assert_eq!(err.error_code(), None);
     }
+
+    #[test]
+    fn json_rpc_code_mapping() {
+        let cases = [
+            (
+                Error::protocol(ErrorCode::METHOD_NOT_FOUND, "nope"),
+                ErrorCode::METHOD_NOT_FOUND,
+            ),
+            (Error::validation("bad"), ErrorCode::INVALID_PARAMS),
+            (Error::invalid_params("bad"), ErrorCode::INVALID_PARAMS),
+            (Error::not_found("tool"), ErrorCode::NOT_FOUND),
+            (
+                Error::authentication("token"),
+                ErrorCode::AUTHENTICATION_REQUIRED,
+            ),
+            (Error::Timeout(100), ErrorCode::REQUEST_TIMEOUT),
+            (
+                Error::capability("sampling"),
+                ErrorCode::UNSUPPORTED_CAPABILITY,
+            ),
+            (
+                Error::InvalidState("closed".to_string()),
+                ErrorCode::INVALID_REQUEST,
+            ),
+            (Error::internal("oops"), ErrorCode::INTERNAL_ERROR),
+            (
+                Error::Transport(TransportError::ConnectionClosed),
+                ErrorCode::INTERNAL_ERROR,
+            ),
+            (Error::Cancelled, ErrorCode::INTERNAL_ERROR),
+        ];
+        for (err, code) in cases {
+            assert_eq!(err.json_rpc_code(), code, "{}", err);
+        }
+        assert_eq!(ErrorCode::from_i32(-32005), ErrorCode::NOT_FOUND);
+    }
 }
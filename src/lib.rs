@@ -84,18 +84,22 @@ pub mod utils;
 #[cfg(feature = "simd")]
 pub mod simd;
 
+#[cfg(all(feature = "testing", not(target_arch = "wasm32")))]
+pub mod testing;
+
 // Re-export commonly used types
-pub use client::{Client, ClientBuilder};
+pub use client::{connect, BoxedClient, Client, ClientBuilder};
 pub use error::{Error, ErrorCode, Result};
 pub use server::{
-    cancellation::RequestHandlerExtra, PromptHandler, ResourceHandler, SamplingHandler, Server,
-    ServerBuilder, ToolHandler,
+    cancellation::RequestHandlerExtra, BlockingToolHandler, CompletionContext, CompletionHandler,
+    PromptHandler, RequestMiddleware, ResourceHandler, SamplingHandler, Server, ServerBuilder,
+    ToolHandler, ToolResult,
 };
 pub use shared::{
     batch::{BatchRequest, BatchResponse},
     uri_template::UriTemplate,
-    AuthMiddleware, LoggingMiddleware, Middleware, MiddlewareChain, RetryMiddleware,
-    StdioTransport, Transport,
+    AuthMiddleware, BoxedTransport, LoggingMiddleware, Middleware, MiddlewareChain,
+    RetryMiddleware, StdioTransport, StdioTransportConfig, Transport,
 };
 
 #[cfg(feature = "websocket")]
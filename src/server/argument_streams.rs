@@ -0,0 +1,255 @@
+//! Reassembly of tool call arguments streamed ahead of the request.
+//!
+//! A client with large arguments sends their JSON text in
+//! [`ArgumentChunk`] notifications, then a `tools/call` request naming the stream in
+//! `_meta.argumentStream` (see [`ArgumentStream`]). Notifications and requests are
+//! handled in the order they arrive, so every chunk has been buffered by the time the
+//! request is handled. A chunk arriving out of sequence, or a stream growing beyond
+//! [`MAX_STREAM_BYTES`], fails the stream: its data is dropped and the request errors.
+//! Cancelling the request ID equal to the stream ID discards the stream.
+//!
+//! Streams belong to the connection or session that sent them (see
+//! [`sessions`](super::sessions)), and are dropped when it ends. At most
+//! [`MAX_OPEN_STREAMS`] are received at once, and a stream without a chunk for
+//! [`STREAM_IDLE_TIMEOUT`] is dropped to make room, so a client that never sends the
+//! request cannot hold the server's memory.
+
+use super::sessions;
+use crate::error::{Error, Result};
+use crate::types::{ArgumentChunk, ArgumentStream};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Maximum size of the arguments' JSON text in one stream.
+const MAX_STREAM_BYTES: usize = 64 * 1024 * 1024;
+
+/// Maximum number of streams being received at once.
+const MAX_OPEN_STREAMS: usize = 64;
+
+/// Time after its last chunk that a stream is dropped.
+const STREAM_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Debug)]
+struct StreamBuffer {
+    data: String,
+    next_seq: u64,
+    /// Why the stream failed, once it has
+    error: Option<String>,
+    /// When the last chunk arrived
+    updated: Instant,
+}
+
+impl Default for StreamBuffer {
+    fn default() -> Self {
+        Self {
+            data: String::new(),
+            next_seq: 0,
+            error: None,
+            updated: Instant::now(),
+        }
+    }
+}
+
+impl StreamBuffer {
+    fn fail(&mut self, error: String) {
+        self.data = String::new();
+        self.error = Some(error);
+    }
+}
+
+/// Argument streams being received, by connection or session and stream ID.
+#[derive(Debug, Default)]
+pub(crate) struct ArgumentStreams {
+    streams: parking_lot::Mutex<HashMap<(String, String), StreamBuffer>>,
+}
+
+impl ArgumentStreams {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Buffer a chunk.
+    pub(crate) fn append(&self, chunk: ArgumentChunk) {
+        let key = (sessions::current_id(), chunk.stream_id);
+        let mut streams = self.streams.lock();
+        if !streams.contains_key(&key) && streams.len() >= MAX_OPEN_STREAMS {
+            streams.retain(|(_, stream_id), stream| {
+                let idle = stream.updated.elapsed() < STREAM_IDLE_TIMEOUT;
+                if !idle {
+                    tracing::warn!("Dropping idle argument stream '{}'", stream_id);
+                }
+                idle
+            });
+        }
+        if !streams.contains_key(&key) && streams.len() >= MAX_OPEN_STREAMS {
+            tracing::warn!(
+                "Dropping chunk for argument stream '{}': {} streams already open",
+                key.1,
+                MAX_OPEN_STREAMS
+            );
+            return;
+        }
+        let stream = streams.entry(key).or_default();
+        stream.updated = Instant::now();
+        if stream.error.is_some() {
+            return;
+        }
+        if chunk.seq != stream.next_seq {
+            stream.fail(format!(
+                "chunk {} arrived out of order, expected chunk {}",
+                chunk.seq, stream.next_seq
+            ));
+        } else if stream.data.len() + chunk.data.len() > MAX_STREAM_BYTES {
+            stream.fail(format!(
+                "arguments exceed the maximum of {} bytes",
+                MAX_STREAM_BYTES
+            ));
+        } else {
+            stream.data.push_str(&chunk.data);
+            stream.next_seq += 1;
+        }
+    }
+
+    /// Take the complete arguments of a stream.
+    ///
+    /// The stream is removed whether or not it is complete.
+    pub(crate) fn finish(&self, stream: &ArgumentStream) -> Result<Value> {
+        let buffer = self
+            .streams
+            .lock()
+            .remove(&(sessions::current_id(), stream.stream_id.clone()))
+            .ok_or_else(|| {
+                Error::invalid_params(format!("Unknown argument stream '{}'", stream.stream_id))
+            })?;
+        if let Some(error) = buffer.error {
+            return Err(Error::invalid_params(format!(
+                "Argument stream '{}' failed: {}",
+                stream.stream_id, error
+            )));
+        }
+        if buffer.next_seq != stream.chunks {
+            return Err(Error::invalid_params(format!(
+                "Argument stream '{}' is incomplete: received {} of {} chunks",
+                stream.stream_id, buffer.next_seq, stream.chunks
+            )));
+        }
+        serde_json::from_str(&buffer.data).map_err(|e| {
+            Error::invalid_params(format!(
+                "Argument stream '{}' is not valid JSON: {}",
+                stream.stream_id, e
+            ))
+        })
+    }
+
+    /// Drop a stream, e.g. because the client cancelled it.
+    pub(crate) fn discard(&self, stream_id: &str) {
+        self.streams
+            .lock()
+            .remove(&(sessions::current_id(), stream_id.to_string()));
+    }
+
+    /// Drop the streams of connection or session `owner`, which ended.
+    pub(crate) fn end(&self, owner: &str) {
+        self.streams.lock().retain(|(id, _), _| id != owner);
+    }
+
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.streams.lock().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn chunk(seq: u64, data: &str) -> ArgumentChunk {
+        ArgumentChunk {
+            stream_id: "s".to_string(),
+            seq,
+            data: data.to_string(),
+        }
+    }
+
+    fn reference(chunks: u64) -> ArgumentStream {
+        ArgumentStream {
+            stream_id: "s".to_string(),
+            chunks,
+        }
+    }
+
+    #[test]
+    fn test_chunks_reassemble_in_order() {
+        let streams = ArgumentStreams::new();
+        for (seq, data) in [r#"{"pa"#, r#"th": "a.txt", "#, r#""size": 3}"#]
+            .into_iter()
+            .enumerate()
+        {
+            streams.append(chunk(seq as u64, data));
+        }
+
+        let arguments = streams.finish(&reference(3)).unwrap();
+        assert_eq!(arguments, json!({"path": "a.txt", "size": 3}));
+        assert_eq!(streams.len(), 0);
+    }
+
+    #[test]
+    fn test_broken_streams_are_rejected() {
+        let streams = ArgumentStreams::new();
+        streams.append(chunk(0, "{"));
+        streams.append(chunk(2, "}"));
+        let err = streams.finish(&reference(3)).unwrap_err();
+        assert!(err.to_string().contains("out of order"), "{}", err);
+
+        streams.append(chunk(0, "{}"));
+        let err = streams.finish(&reference(2)).unwrap_err();
+        assert!(err.to_string().contains("received 1 of 2"), "{}", err);
+
+        streams.append(chunk(0, "{"));
+        streams.discard("s");
+        assert!(streams.finish(&reference(1)).is_err());
+    }
+
+    #[test]
+    fn test_idle_streams_make_room_for_new_ones() {
+        let streams = ArgumentStreams::new();
+        for i in 0..MAX_OPEN_STREAMS {
+            streams.append(ArgumentChunk {
+                stream_id: i.to_string(),
+                seq: 0,
+                data: "{".to_string(),
+            });
+        }
+        // Over the limit while the others are still active
+        streams.append(chunk(0, "{}"));
+        assert!(streams.finish(&reference(1)).is_err());
+
+        for stream in streams.streams.lock().values_mut() {
+            stream.updated -= STREAM_IDLE_TIMEOUT;
+        }
+        streams.append(chunk(0, "{}"));
+        assert_eq!(streams.finish(&reference(1)).unwrap(), json!({}));
+        assert_eq!(streams.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_streams_are_kept_per_session_and_dropped_when_it_ends() {
+        let streams = ArgumentStreams::new();
+        sessions::in_session(Some("a".to_string()), async {
+            streams.append(chunk(0, "{}"));
+        })
+        .await;
+        streams.append(chunk(0, "{"));
+
+        // Each session finishes its own stream
+        let finished = sessions::in_session(Some("a".to_string()), async {
+            streams.finish(&reference(1))
+        });
+        assert_eq!(finished.await.unwrap(), json!({}));
+
+        streams.end(sessions::CONNECTION_ID);
+        assert_eq!(streams.len(), 0);
+    }
+}
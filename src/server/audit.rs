@@ -0,0 +1,397 @@
+//! Audit trail of tool invocations.
+//!
+//! Unlike logging, which is for operators and may be filtered or sampled, an audit
+//! trail records every tool call: who made it, with what arguments, and how it
+//! ended. Register an [`AuditSink`] with [`ServerBuilder::audit`](super::ServerBuilder::audit)
+//! and it receives an [`AuditEntry`] for each call. [`JsonlAuditSink`] appends them to
+//! a hash-chained JSONL file so that edited or removed entries can be detected.
+
+use crate::error::{Error, Result};
+use crate::types::{content_hash, CallToolResult, Content};
+use async_trait::async_trait;
+use regex::RegexSet;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// Replaces argument values whose name matches a redaction pattern.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Longest result summary, in characters.
+const SUMMARY_LEN: usize = 200;
+
+/// One tool invocation.
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    /// When the call started.
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Session of the calling client, if the transport has sessions.
+    pub connection_id: Option<String>,
+    /// Name of the called tool.
+    pub tool_name: String,
+    /// Arguments of the call, with redacted values replaced by [`REDACTED`].
+    pub arguments: Value,
+    /// Shortened text of the result, if the tool returned one.
+    pub result_summary: Option<String>,
+    /// How long the call took.
+    pub duration: Duration,
+    /// Why the call failed, if it did.
+    ///
+    /// Set for protocol errors as well as for results marked as errors.
+    pub error: Option<String>,
+}
+
+/// Receives an [`AuditEntry`] for every tool call.
+///
+/// # Examples
+///
+/// ```rust
+/// use async_trait::async_trait;
+/// use pmcp::server::audit::{AuditEntry, AuditSink};
+///
+/// struct StderrSink;
+///
+/// #[async_trait]
+/// impl AuditSink for StderrSink {
+///     async fn record(&self, entry: &AuditEntry) -> pmcp::Result<()> {
+///         eprintln!("{} called {}", entry.connection_id.as_deref().unwrap_or("-"), entry.tool_name);
+///         Ok(())
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    /// Record a tool call.
+    ///
+    /// Called once the call has finished, before its response is sent. A failure is
+    /// logged and does not affect the response.
+    async fn record(&self, entry: &AuditEntry) -> Result<()>;
+}
+
+/// Appends audit entries to a JSONL file.
+///
+/// Each line holds one entry along with `prevHash`, the hash of the line before, and
+/// `hash`, the hash of the line itself. Editing, reordering or removing a line breaks
+/// the chain, which [`JsonlAuditSink::verify`] detects. Reopening a file continues
+/// its chain.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use pmcp::server::audit::JsonlAuditSink;
+/// use pmcp::Server;
+///
+/// # async fn example() -> pmcp::Result<()> {
+/// let server = Server::builder()
+///     .name("audited-server")
+///     .version("1.0.0")
+///     .audit(JsonlAuditSink::open("audit.jsonl").await?)
+///     .audit_redact("(?i)password|token")
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct JsonlAuditSink {
+    path: PathBuf,
+    writer: Mutex<JsonlWriter>,
+}
+
+#[derive(Debug)]
+struct JsonlWriter {
+    file: tokio::fs::File,
+    last_hash: Option<String>,
+}
+
+impl JsonlAuditSink {
+    /// Open `path` for appending, creating it if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened, or its last line is not an
+    /// audit entry.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let last_hash = match tokio::fs::read_to_string(&path).await {
+            Ok(contents) => match contents.lines().rev().find(|line| !line.is_empty()) {
+                Some(line) => Some(parse_line(line)?.1),
+                None => None,
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+        Ok(Self {
+            path,
+            writer: Mutex::new(JsonlWriter { file, last_hash }),
+        })
+    }
+
+    /// Path of the audit file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Check the hash chain of an audit file and return its number of entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns a validation error naming the first line that was altered, or does
+    /// not follow the line before it.
+    pub async fn verify(path: impl AsRef<Path>) -> Result<usize> {
+        let contents = tokio::fs::read_to_string(path).await?;
+        let mut last_hash = None;
+        let mut entries = 0;
+        for (index, line) in contents.lines().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let (record, hash) = parse_line(line)?;
+            let prev_hash = record.get("prevHash").and_then(Value::as_str);
+            if prev_hash != last_hash.as_deref() || chain_hash(&record)? != hash {
+                return Err(Error::validation(format!(
+                    "Audit line {} has been tampered with",
+                    index + 1
+                )));
+            }
+            last_hash = Some(hash);
+            entries += 1;
+        }
+        Ok(entries)
+    }
+}
+
+#[async_trait]
+impl AuditSink for JsonlAuditSink {
+    async fn record(&self, entry: &AuditEntry) -> Result<()> {
+        let mut writer = self.writer.lock().await;
+        let mut record = json!({
+            "timestamp": entry.timestamp.to_rfc3339(),
+            "connectionId": entry.connection_id,
+            "tool": entry.tool_name,
+            "arguments": entry.arguments,
+            "resultSummary": entry.result_summary,
+            "durationMs": u64::try_from(entry.duration.as_millis()).unwrap_or(u64::MAX),
+            "error": entry.error,
+            "prevHash": writer.last_hash,
+        });
+        let hash = chain_hash(&record)?;
+        record["hash"] = Value::String(hash.clone());
+
+        let mut line = serde_json::to_string(&record)?;
+        line.push('\n');
+        writer.file.write_all(line.as_bytes()).await?;
+        writer.file.flush().await?;
+        writer.last_hash = Some(hash);
+        Ok(())
+    }
+}
+
+/// Split an audit line into the hashed record and its hash.
+fn parse_line(line: &str) -> Result<(Value, String)> {
+    let mut record: Value = serde_json::from_str(line)?;
+    let hash = record
+        .as_object_mut()
+        .and_then(|object| object.remove("hash"))
+        .and_then(|hash| hash.as_str().map(str::to_string))
+        .ok_or_else(|| Error::validation("Audit line has no hash"))?;
+    Ok((record, hash))
+}
+
+fn chain_hash(record: &Value) -> Result<String> {
+    Ok(content_hash(serde_json::to_string(record)?.as_bytes()))
+}
+
+/// Sink and redaction rules configured with [`ServerBuilder::audit`](super::ServerBuilder::audit).
+pub(crate) struct Auditor {
+    sink: Arc<dyn AuditSink>,
+    redact: RegexSet,
+}
+
+impl Auditor {
+    pub(crate) fn new(sink: Arc<dyn AuditSink>, redact: &[String]) -> Result<Self> {
+        let redact = RegexSet::new(redact)
+            .map_err(|e| Error::validation(format!("Invalid audit redaction pattern: {}", e)))?;
+        Ok(Self { sink, redact })
+    }
+
+    /// Copy `arguments`, replacing values whose name matches a redaction pattern.
+    pub(crate) fn redact(&self, arguments: &Value) -> Value {
+        match arguments {
+            Value::Object(object) => Value::Object(
+                object
+                    .iter()
+                    .map(|(name, value)| {
+                        let value = if self.redact.is_match(name) {
+                            Value::String(REDACTED.to_string())
+                        } else {
+                            self.redact(value)
+                        };
+                        (name.clone(), value)
+                    })
+                    .collect(),
+            ),
+            Value::Array(items) => Value::Array(items.iter().map(|v| self.redact(v)).collect()),
+            other => other.clone(),
+        }
+    }
+
+    pub(crate) async fn record(&self, entry: AuditEntry) {
+        if let Err(e) = self.sink.record(&entry).await {
+            tracing::error!(
+                tool = %entry.tool_name,
+                error = %e,
+                "Failed to record audit entry"
+            );
+        }
+    }
+}
+
+/// Summary and error of a tool call's outcome.
+pub(crate) fn outcome(result: &Result<Value>) -> (Option<String>, Option<String>) {
+    let value = match result {
+        Ok(value) => value,
+        Err(e) => return (None, Some(e.to_string())),
+    };
+    let Ok(call_result) = serde_json::from_value::<CallToolResult>(value.clone()) else {
+        return (Some(truncate(value.to_string())), None);
+    };
+    let summary = truncate(
+        call_result
+            .content
+            .iter()
+            .map(|content| match content {
+                Content::Text { text } => text.clone(),
+                Content::Image { mime_type, .. } => format!("[{} image]", mime_type),
+                Content::Blob { mime_type, .. } => format!("[{} blob]", mime_type),
+                Content::Resource { uri, .. } => format!("[resource {}]", uri),
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
+    let error = call_result.is_error.then(|| summary.clone());
+    (Some(summary), error)
+}
+
+fn truncate(mut text: String) -> String {
+    if let Some((end, _)) = text.char_indices().nth(SUMMARY_LEN) {
+        text.truncate(end);
+        text.push('…');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(tool_name: &str) -> AuditEntry {
+        AuditEntry {
+            timestamp: chrono::Utc::now(),
+            connection_id: Some("session-1".to_string()),
+            tool_name: tool_name.to_string(),
+            arguments: json!({"path": "/tmp"}),
+            result_summary: Some("ok".to_string()),
+            duration: Duration::from_millis(5),
+            error: None,
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "pmcp-audit-{}-{}.jsonl",
+            name,
+            uuid::Uuid::new_v4()
+        ))
+    }
+
+    #[test]
+    fn test_redact_matches_names_at_any_depth() {
+        let auditor = Auditor::new(
+            Arc::new(NullSink),
+            &["(?i)password".to_string(), "^token$".to_string()],
+        )
+        .unwrap();
+        let redacted = auditor.redact(&json!({
+            "user": "alice",
+            "Password": "hunter2",
+            "nested": [{"token": "abc", "tokens": 3}],
+        }));
+        assert_eq!(
+            redacted,
+            json!({
+                "user": "alice",
+                "Password": REDACTED,
+                "nested": [{"token": REDACTED, "tokens": 3}],
+            })
+        );
+        assert!(Auditor::new(Arc::new(NullSink), &["(".to_string()]).is_err());
+    }
+
+    struct NullSink;
+
+    #[async_trait]
+    impl AuditSink for NullSink {
+        async fn record(&self, _entry: &AuditEntry) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_outcome() {
+        let ok = Ok(serde_json::to_value(CallToolResult {
+            content: vec![Content::Text {
+                text: "x".repeat(300),
+            }],
+            is_error: false,
+            meta: None,
+        })
+        .unwrap());
+        let (summary, error) = outcome(&ok);
+        assert_eq!(summary.unwrap().chars().count(), SUMMARY_LEN + 1);
+        assert!(error.is_none());
+
+        let (summary, error) = outcome(&Err(Error::not_found("Tool 'x' not found")));
+        assert!(summary.is_none());
+        assert!(error.unwrap().contains("Tool 'x' not found"));
+    }
+
+    #[tokio::test]
+    async fn test_jsonl_chain_survives_reopen_and_detects_tampering() {
+        let path = temp_path("chain");
+        let sink = JsonlAuditSink::open(&path).await.unwrap();
+        sink.record(&entry("first")).await.unwrap();
+        sink.record(&entry("second")).await.unwrap();
+        drop(sink);
+
+        let sink = JsonlAuditSink::open(&path).await.unwrap();
+        sink.record(&entry("third")).await.unwrap();
+        assert_eq!(JsonlAuditSink::verify(&path).await.unwrap(), 3);
+
+        let contents = tokio::fs::read_to_string(&path).await.unwrap();
+        let line: Value = serde_json::from_str(contents.lines().next().unwrap()).unwrap();
+        assert_eq!(line["tool"], "first");
+        assert_eq!(line["connectionId"], "session-1");
+        assert_eq!(line["durationMs"], 5);
+        assert!(line["prevHash"].is_null());
+
+        tokio::fs::write(&path, contents.replacen("first", "forged", 1))
+            .await
+            .unwrap();
+        assert!(JsonlAuditSink::verify(&path).await.is_err());
+
+        let mut lines: Vec<_> = contents.lines().collect();
+        lines.remove(1);
+        tokio::fs::write(&path, lines.join("\n")).await.unwrap();
+        assert!(JsonlAuditSink::verify(&path).await.is_err());
+
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+}
@@ -41,6 +41,7 @@ pub enum ResponseType {
 #[serde(rename_all = "lowercase")]
 pub enum TokenType {
     /// Bearer token type.
+    #[serde(alias = "Bearer")]
     Bearer,
 }
 
@@ -247,7 +248,7 @@ pub struct AuthorizationRequest {
 }
 
 /// OAuth 2.0 token request.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenRequest {
     /// Grant type.
     pub grant_type: GrantType,
@@ -1,12 +1,13 @@
 //! Server-side batch request handling.
 
-use crate::error::Result;
+use crate::error::{ErrorCode, Result};
 use crate::server::Server;
 use crate::shared::batch::{BatchRequest, BatchResponse};
 use crate::types::{JSONRPCRequest, JSONRPCResponse};
+use serde_json::Value;
 use std::sync::Arc;
 
-impl Server {
+impl<S: Send + Sync + 'static> Server<S> {
     /// Handle a batch request.
     ///
     /// Processes multiple JSON-RPC requests and returns their responses
@@ -42,6 +43,76 @@ impl Server {
 
         crate::shared::batch::process_batch_request(batch, handler).await
     }
+
+    /// Handle a JSON-RPC batch that may mix requests and notifications.
+    ///
+    /// Requests are handled concurrently and answered in batch order, each response
+    /// carrying the id of its request. Notifications produce no response, so a batch of
+    /// only notifications returns an empty vector. Elements that are not JSON-RPC
+    /// messages get an invalid request error with a `null` id, as does an empty batch.
+    ///
+    /// Batches are only sent by clients when the server advertises
+    /// [`ServerCapabilities::supports_batch`](crate::types::ServerCapabilities::supports_batch).
+    pub async fn handle_batch_messages(&self, messages: Vec<Value>) -> Vec<Value> {
+        if messages.is_empty() {
+            return vec![invalid_request("Invalid Request: empty batch")];
+        }
+        futures::future::join_all(
+            messages
+                .into_iter()
+                .map(|message| self.handle_batch_element(message)),
+        )
+        .await
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    async fn handle_batch_element(&self, message: Value) -> Option<Value> {
+        let has_id = message.get("id").is_some();
+        let method = message
+            .get("method")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let response = match (method, has_id) {
+            (Some(method), true) => {
+                let request: JSONRPCRequest = match serde_json::from_value(message) {
+                    Ok(request) => request,
+                    Err(e) => return Some(invalid_request(&format!("Invalid Request: {}", e))),
+                };
+                let id = request.id.clone();
+                match crate::shared::protocol_helpers::parse_request(request) {
+                    Ok((id, request)) => self.handle_request(id, request).await,
+                    Err(e) => Self::create_response(id, &method, Err(e)),
+                }
+            },
+            (Some(_), false) => {
+                match crate::shared::protocol_helpers::parse_notification(message) {
+                    Ok(notification) => self.handle_notification(notification).await,
+                    Err(e) => tracing::warn!("Ignoring invalid notification in batch: {}", e),
+                }
+                return None;
+            },
+            // Responses to server requests are not answered
+            (None, true) => return None,
+            (None, false) => {
+                return Some(invalid_request("Invalid Request: not a JSON-RPC message"))
+            },
+        };
+        serde_json::to_value(response).ok()
+    }
+}
+
+/// Invalid request error for a batch element whose id is unknown.
+fn invalid_request(message: &str) -> Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": null,
+        "error": {
+            "code": ErrorCode::INVALID_REQUEST.as_i32(),
+            "message": message,
+        },
+    })
 }
 
 #[cfg(test)]
@@ -175,4 +246,58 @@ mod tests {
             crate::types::jsonrpc::ResponsePayload::Result(_) => panic!("Expected error response"),
         }
     }
+
+    #[tokio::test]
+    async fn test_mixed_batch_answers_requests_only() {
+        let server = ServerBuilder::new()
+            .name("test-server")
+            .version("1.0.0")
+            .capabilities(crate::types::ServerCapabilities::builder().batch().build())
+            .build()
+            .unwrap();
+
+        let responses = server
+            .handle_batch_messages(vec![
+                json!({"jsonrpc": "2.0", "id": "a", "method": "ping"}),
+                json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+                json!({"jsonrpc": "2.0", "id": 7, "method": "no/such/method"}),
+                json!({"jsonrpc": "2.0", "method": "notifications/cancelled",
+                       "params": {"requestId": "gone"}}),
+                json!(42),
+                json!({"jsonrpc": "2.0", "id": 8, "method": "ping"}),
+            ])
+            .await;
+
+        // One response per request, in batch order; notifications get none
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses[0]["id"], json!("a"));
+        assert_eq!(responses[0]["result"], json!({}));
+        assert_eq!(responses[1]["id"], json!(7));
+        assert_eq!(responses[1]["error"]["code"], json!(-32601));
+        assert_eq!(responses[2]["id"], Value::Null);
+        assert_eq!(responses[2]["error"]["code"], json!(-32600));
+        assert_eq!(responses[3]["id"], json!(8));
+        assert_eq!(responses[3]["result"], json!({}));
+
+        let notifications_only = server
+            .handle_batch_messages(vec![
+                json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+            ])
+            .await;
+        assert!(notifications_only.is_empty());
+
+        // An id-bearing notification is an invalid request
+        let responses = server
+            .handle_batch_messages(vec![
+                json!({"jsonrpc": "2.0", "id": 9, "method": "notifications/initialized"}),
+            ])
+            .await;
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0]["id"], json!(9));
+        assert_eq!(responses[0]["error"]["code"], json!(-32600));
+
+        let empty = server.handle_batch_messages(vec![]).await;
+        assert_eq!(empty.len(), 1);
+        assert_eq!(empty[0]["error"]["code"], json!(-32600));
+    }
 }
@@ -2,14 +2,42 @@
 
 use crate::error::Result;
 use crate::types::protocol::{CancelledNotification, Notification};
+use crate::types::{ProgressNotification, ProgressToken, RequestId, ServerNotification};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio_util::sync::CancellationToken;
 
+/// Shared slot holding the reason a request was cancelled.
+type CancellationReason = Arc<parking_lot::RwLock<Option<String>>>;
+
+/// `_meta` a handler attaches to its response, shared with the server.
+type ResponseMeta = Arc<parking_lot::RwLock<Option<serde_json::Value>>>;
+
+/// Produces the result of a cancelled request, see [`RequestHandlerExtra::on_cancel`].
+type PartialResultFn = Box<dyn FnOnce() -> serde_json::Value + Send>;
+
+/// Shared slot holding the partial result registered by a handler.
+#[derive(Clone, Default)]
+struct PartialResult(Arc<parking_lot::Mutex<Option<PartialResultFn>>>);
+
+impl std::fmt::Debug for PartialResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("PartialResult")
+            .field(&self.0.lock().is_some())
+            .finish()
+    }
+}
+
 /// Manages cancellation tokens for requests.
+#[derive(Clone)]
 pub struct CancellationManager {
     tokens: Arc<RwLock<HashMap<String, CancellationToken>>>,
+    reasons: Arc<RwLock<HashMap<String, CancellationReason>>>,
+    /// Original JSON-RPC IDs, so notifications echo the client's ID type
+    request_ids: Arc<RwLock<HashMap<String, RequestId>>>,
+    /// Progress tokens the client sent with in-flight requests, by request ID
+    progress_tokens: Arc<RwLock<HashMap<String, ProgressToken>>>,
     notification_sender: Option<Arc<dyn Fn(Notification) + Send + Sync>>,
 }
 
@@ -18,6 +46,9 @@ impl CancellationManager {
     pub fn new() -> Self {
         Self {
             tokens: Arc::new(RwLock::new(HashMap::new())),
+            reasons: Arc::new(RwLock::new(HashMap::new())),
+            request_ids: Arc::new(RwLock::new(HashMap::new())),
+            progress_tokens: Arc::new(RwLock::new(HashMap::new())),
             notification_sender: None,
         }
     }
@@ -30,27 +61,82 @@ impl CancellationManager {
     /// Create a cancellation token for a request.
     pub async fn create_token(&self, request_id: String) -> CancellationToken {
         let token = CancellationToken::new();
+        self.reasons
+            .write()
+            .await
+            .insert(request_id.clone(), CancellationReason::default());
         let mut tokens = self.tokens.write().await;
         tokens.insert(request_id, token.clone());
         token
     }
 
-    /// Cancel a request by ID.
-    pub async fn cancel_request(&self, request_id: String, reason: Option<String>) -> Result<()> {
-        let token = {
-            let mut tokens = self.tokens.write().await;
-            tokens.remove(&request_id)
-        };
+    /// Register a request and build the handler context for it.
+    ///
+    /// The returned extra shares its cancellation token and reason with this
+    /// manager, so a later cancellation is visible to the running handler.
+    pub async fn create_extra(&self, request_id: String) -> RequestHandlerExtra {
+        let token = self.create_token(request_id.clone()).await;
+        let reason = self
+            .reasons
+            .read()
+            .await
+            .get(&request_id)
+            .cloned()
+            .unwrap_or_default();
+        let mut extra = RequestHandlerExtra::new(request_id, token);
+        extra.cancellation_reason = reason;
+        extra
+    }
 
-        if let Some(token) = token {
-            // Cancel the token
-            token.cancel();
+    /// Register a JSON-RPC request and build the handler context for it.
+    ///
+    /// Like [`create_extra`](Self::create_extra), but remembers the original ID so a
+    /// cancellation notification carries the same string or numeric ID the client sent.
+    pub async fn create_request_extra(&self, request_id: &RequestId) -> RequestHandlerExtra {
+        let key = request_id.to_string();
+        self.request_ids
+            .write()
+            .await
+            .insert(key.clone(), request_id.clone());
+        self.create_extra(key).await
+    }
+
+    /// Remember the progress token the client sent with request `request_id`, until
+    /// the request completes.
+    pub async fn track_progress(&self, request_id: &RequestId, progress_token: ProgressToken) {
+        self.progress_tokens
+            .write()
+            .await
+            .insert(request_id.to_string(), progress_token);
+    }
+
+    /// Progress token the client sent with request `request_id`, if it is in flight.
+    pub async fn progress_token(&self, request_id: &RequestId) -> Option<ProgressToken> {
+        self.progress_tokens
+            .read()
+            .await
+            .get(&request_id.to_string())
+            .cloned()
+    }
 
+    /// Whether `progress_token` was sent with a request that is still in flight.
+    pub async fn is_progress_active(&self, progress_token: &ProgressToken) -> bool {
+        self.progress_tokens
+            .read()
+            .await
+            .values()
+            .any(|token| token == progress_token)
+    }
+
+    /// Cancel a request by ID.
+    pub async fn cancel_request(&self, request_id: String, reason: Option<String>) -> Result<()> {
+        let original_id = self.request_ids.read().await.get(&request_id).cloned();
+        if self.cancel_token(&request_id, reason.clone()).await {
             // Send cancellation notification
             if let Some(sender) = &self.notification_sender {
                 let notification = Notification::Client(
                     crate::types::ClientNotification::Cancelled(CancelledNotification {
-                        request_id: crate::types::RequestId::String(request_id.clone()),
+                        request_id: original_id.unwrap_or(RequestId::String(request_id)),
                         reason: Some(reason.unwrap_or_else(|| "Cancelled by server".to_string())),
                     }),
                 );
@@ -61,10 +147,75 @@ impl CancellationManager {
         Ok(())
     }
 
+    /// Cancel a request once its deadline passes.
+    ///
+    /// A deadline already in the past cancels the request before returning. Otherwise a
+    /// timer is started; abort the returned handle once the request completes. No
+    /// notification is sent, since the client stopped waiting at the deadline.
+    pub async fn cancel_at_deadline(
+        &self,
+        request_id: &RequestId,
+        deadline: std::time::Instant,
+    ) -> Option<tokio::task::JoinHandle<()>> {
+        let key = request_id.to_string();
+        let reason = Some("Deadline exceeded".to_string());
+        if deadline <= std::time::Instant::now() {
+            self.cancel_token(&key, reason).await;
+            return None;
+        }
+
+        let manager = self.clone();
+        Some(tokio::spawn(async move {
+            tokio::time::sleep_until(deadline.into()).await;
+            manager.cancel_token(&key, reason).await;
+        }))
+    }
+
+    /// Cancel a request whose handler ran longer than the server's `timeout`.
+    ///
+    /// No notification is sent, since the client gets a timeout error as the response.
+    pub async fn cancel_timed_out(&self, request_id: &RequestId, timeout: std::time::Duration) {
+        let reason = format!("Request timed out after {:?}", timeout);
+        self.cancel_token(&request_id.to_string(), Some(reason))
+            .await;
+    }
+
+    /// Handle a `notifications/cancelled` sent by the client.
+    ///
+    /// Cancels the matching request and records the reason for its handler.
+    /// No notification is echoed back, since the client initiated it.
+    pub async fn handle_cancelled_notification(&self, notification: CancelledNotification) {
+        self.cancel_token(&notification.request_id.to_string(), notification.reason)
+            .await;
+    }
+
+    /// Record the reason and fire the token. Returns whether the request was active.
+    async fn cancel_token(&self, request_id: &str, reason: Option<String>) -> bool {
+        let token = {
+            let mut tokens = self.tokens.write().await;
+            tokens.remove(request_id)
+        };
+        let slot = self.reasons.write().await.remove(request_id);
+        self.request_ids.write().await.remove(request_id);
+        self.progress_tokens.write().await.remove(request_id);
+
+        let Some(token) = token else {
+            return false;
+        };
+        if let Some(slot) = slot {
+            *slot.write() = reason;
+        }
+        token.cancel();
+        true
+    }
+
     /// Remove a completed request's token.
     pub async fn remove_token(&self, request_id: &str) {
         let mut tokens = self.tokens.write().await;
         tokens.remove(request_id);
+        self.reasons.write().await.remove(request_id);
+        self.request_ids.write().await.remove(request_id);
+        self.progress_tokens.write().await.remove(request_id);
     }
 
     /// Check if a request is cancelled.
@@ -89,6 +240,9 @@ impl CancellationManager {
             token.cancel();
         }
         tokens.clear();
+        self.reasons.write().await.clear();
+        self.request_ids.write().await.clear();
+        self.progress_tokens.write().await.clear();
     }
 }
 
@@ -120,6 +274,24 @@ pub struct RequestHandlerExtra {
     pub session_id: Option<String>,
     /// Authentication info
     pub auth_info: Option<crate::types::auth::AuthInfo>,
+    /// Progress token the client sent in the request's `_meta`, if it wants progress
+    pub progress_token: Option<ProgressToken>,
+    /// Reason given when the request was cancelled
+    cancellation_reason: CancellationReason,
+    /// `_meta` to attach to the response
+    response_meta: ResponseMeta,
+    /// Result to respond with if the request is cancelled
+    partial_result: PartialResult,
+    /// When the client stops waiting for the response
+    deadline: Option<std::time::Instant>,
+    /// Connection for sending requests back to the client
+    client: Option<Arc<super::client_requests::ClientRequests>>,
+    /// Server's channel of notifications to the client
+    notifications: Option<tokio::sync::mpsc::Sender<Notification>>,
+    /// State shared by the server's handlers
+    state: Option<Arc<dyn std::any::Any + Send + Sync>>,
+    /// Values kept for the connection the request arrived on
+    context: crate::shared::ConnectionContext,
 }
 
 impl RequestHandlerExtra {
@@ -130,6 +302,15 @@ impl RequestHandlerExtra {
             request_id,
             session_id: None,
             auth_info: None,
+            progress_token: None,
+            cancellation_reason: CancellationReason::default(),
+            response_meta: ResponseMeta::default(),
+            partial_result: PartialResult::default(),
+            deadline: None,
+            client: None,
+            notifications: None,
+            state: None,
+            context: crate::shared::ConnectionContext::new(),
         }
     }
 
@@ -145,6 +326,127 @@ impl RequestHandlerExtra {
         self
     }
 
+    /// Set the deadline.
+    pub fn with_deadline(mut self, deadline: Option<std::time::Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Set the progress token sent by the client.
+    pub fn with_progress_token(mut self, progress_token: Option<ProgressToken>) -> Self {
+        self.progress_token = progress_token;
+        self
+    }
+
+    /// Set the channel used to send notifications to the client.
+    pub(crate) fn with_notifications(
+        mut self,
+        notifications: Option<tokio::sync::mpsc::Sender<Notification>>,
+    ) -> Self {
+        self.notifications = notifications;
+        self
+    }
+
+    /// Set the connection used to send requests back to the client.
+    pub(crate) fn with_client(
+        mut self,
+        client: Option<Arc<super::client_requests::ClientRequests>>,
+    ) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Set the state shared by the server's handlers.
+    pub fn with_state<S: Send + Sync + 'static>(mut self, state: Arc<S>) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Set the values kept for the connection the request arrived on.
+    pub fn with_context(mut self, context: crate::shared::ConnectionContext) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Get the values kept for the connection the request arrived on.
+    ///
+    /// [`RequestMiddleware`](super::RequestMiddleware) stores per-connection data here,
+    /// such as the authenticated user, and handlers read it. Values set by a handler
+    /// are seen by later requests on the same connection.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use async_trait::async_trait;
+    /// use pmcp::{RequestHandlerExtra, ToolHandler};
+    /// use serde_json::{json, Value};
+    ///
+    /// struct User(String);
+    ///
+    /// struct WhoAmI;
+    ///
+    /// #[async_trait]
+    /// impl ToolHandler for WhoAmI {
+    ///     async fn handle(&self, _args: Value, extra: RequestHandlerExtra) -> pmcp::Result<Value> {
+    ///         let user = extra
+    ///             .context()
+    ///             .get::<User>()
+    ///             .ok_or_else(|| pmcp::Error::invalid_request("not signed in"))?;
+    ///         Ok(json!({ "user": user.0 }))
+    ///     }
+    /// }
+    /// ```
+    pub fn context(&self) -> &crate::shared::ConnectionContext {
+        &self.context
+    }
+
+    /// Get the state given to [`ServerBuilder::state`](super::ServerBuilder::state).
+    ///
+    /// Returns `None` if the server has no state of type `S`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use async_trait::async_trait;
+    /// use pmcp::{RequestHandlerExtra, Server, ToolHandler};
+    /// use serde_json::{json, Value};
+    ///
+    /// struct AppState {
+    ///     greeting: String,
+    /// }
+    ///
+    /// struct Greet;
+    ///
+    /// #[async_trait]
+    /// impl ToolHandler for Greet {
+    ///     async fn handle(&self, _args: Value, extra: RequestHandlerExtra) -> pmcp::Result<Value> {
+    ///         let state = extra
+    ///             .state::<AppState>()
+    ///             .ok_or_else(|| pmcp::Error::internal("missing app state"))?;
+    ///         Ok(json!({ "message": state.greeting }))
+    ///     }
+    /// }
+    ///
+    /// let server = Server::builder()
+    ///     .name("greeter")
+    ///     .version("1.0.0")
+    ///     .state(AppState { greeting: "Hello".to_string() })
+    ///     .tool("greet", Greet)
+    ///     .build()?;
+    /// assert_eq!(server.state().greeting, "Hello");
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn state<S: Send + Sync + 'static>(&self) -> Option<Arc<S>> {
+        self.state.clone()?.downcast().ok()
+    }
+
+    /// Time after which the client no longer waits for the response, if it sent one.
+    ///
+    /// The request is cancelled automatically once the deadline passes.
+    pub fn deadline(&self) -> Option<std::time::Instant> {
+        self.deadline
+    }
+
     /// Check if the request has been cancelled.
     pub fn is_cancelled(&self) -> bool {
         self.cancellation_token.is_cancelled()
@@ -154,6 +456,238 @@ impl RequestHandlerExtra {
     pub async fn cancelled(&self) {
         self.cancellation_token.cancelled().await;
     }
+
+    /// Get the reason the request was cancelled, if one was given.
+    pub fn cancellation_reason(&self) -> Option<String> {
+        self.cancellation_reason.read().clone()
+    }
+
+    /// Respond with the result of `partial` if the request is cancelled.
+    ///
+    /// Without a registration, a cancelled tool call keeps running until the handler
+    /// returns, or fails once its deadline passes. With one, the server stops waiting
+    /// for the handler as soon as the request is cancelled, and responds with what
+    /// `partial` returns instead, e.g. a [`ToolResult`](super::ToolResult) holding the
+    /// output gathered so far. Registering again replaces the previous callback.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use async_trait::async_trait;
+    /// use pmcp::server::ToolResult;
+    /// use pmcp::{RequestHandlerExtra, ToolHandler};
+    /// use serde_json::Value;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// struct Crawl;
+    ///
+    /// #[async_trait]
+    /// impl ToolHandler for Crawl {
+    ///     async fn handle(&self, _args: Value, extra: RequestHandlerExtra) -> pmcp::Result<Value> {
+    ///         let pages = Arc::new(Mutex::new(Vec::new()));
+    ///         extra.on_cancel({
+    ///             let pages = pages.clone();
+    ///             move || {
+    ///                 let pages = pages.lock().unwrap();
+    ///                 ToolResult::text(format!("Cancelled after {} pages", pages.len()))
+    ///             }
+    ///         });
+    ///         for page in ["a", "b", "c"] {
+    ///             pages.lock().unwrap().push(page.to_string());
+    ///         }
+    ///         let pages = pages.lock().unwrap().join("\n");
+    ///         Ok(ToolResult::text(pages).into())
+    ///     }
+    /// }
+    /// ```
+    pub fn on_cancel<F, R>(&self, partial: F)
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Into<serde_json::Value>,
+    {
+        *self.partial_result.0.lock() = Some(Box::new(move || partial().into()));
+    }
+
+    /// Take the result registered with [`on_cancel`](Self::on_cancel), if any.
+    pub(crate) fn take_partial_result(&self) -> Option<serde_json::Value> {
+        let partial = self.partial_result.0.lock().take()?;
+        Some(partial())
+    }
+
+    /// Attach `_meta` to the response, e.g. timing or the model used.
+    ///
+    /// For tool calls the value is returned as [`CallToolResult::meta`]. Setting it
+    /// again replaces the previous value.
+    ///
+    /// [`CallToolResult::meta`]: crate::types::CallToolResult::meta
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use async_trait::async_trait;
+    /// use pmcp::{RequestHandlerExtra, ToolHandler};
+    /// use serde_json::{json, Value};
+    ///
+    /// struct Timed;
+    ///
+    /// #[async_trait]
+    /// impl ToolHandler for Timed {
+    ///     async fn handle(&self, _args: Value, extra: RequestHandlerExtra) -> pmcp::Result<Value> {
+    ///         extra.set_response_meta(json!({"elapsedMs": 12}));
+    ///         Ok(json!({"ok": true}))
+    ///     }
+    /// }
+    /// ```
+    pub fn set_response_meta(&self, meta: serde_json::Value) {
+        *self.response_meta.write() = Some(meta);
+    }
+
+    /// Get the `_meta` set with [`set_response_meta`](Self::set_response_meta).
+    pub fn response_meta(&self) -> Option<serde_json::Value> {
+        self.response_meta.read().clone()
+    }
+
+    /// Ask the client's LLM to sample a message while handling this request.
+    ///
+    /// Sends `sampling/createMessage` to the client and waits for its result, so a
+    /// tool can hand part of its work back to the model. Fails if the client did not
+    /// declare the sampling capability, or if the request is cancelled while waiting.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use async_trait::async_trait;
+    /// use pmcp::types::{Content, CreateMessageParams, Role, SamplingMessage};
+    /// use pmcp::{RequestHandlerExtra, ToolHandler};
+    /// use serde_json::{json, Value};
+    ///
+    /// struct Summarize;
+    ///
+    /// #[async_trait]
+    /// impl ToolHandler for Summarize {
+    ///     async fn handle(&self, args: Value, extra: RequestHandlerExtra) -> pmcp::Result<Value> {
+    ///         let text = args["text"].as_str().unwrap_or_default();
+    ///         let result = extra
+    ///             .create_message(CreateMessageParams {
+    ///                 messages: vec![SamplingMessage {
+    ///                     role: Role::User,
+    ///                     content: Content::Text {
+    ///                         text: format!("Summarize: {}", text),
+    ///                     },
+    ///                 }],
+    ///                 model_preferences: None,
+    ///                 system_prompt: None,
+    ///                 include_context: Default::default(),
+    ///                 temperature: None,
+    ///                 max_tokens: Some(200),
+    ///                 stop_sequences: None,
+    ///                 metadata: None,
+    ///             })
+    ///             .await?;
+    ///         Ok(json!({"summary": result.content}))
+    ///     }
+    /// }
+    /// ```
+    pub async fn create_message(
+        &self,
+        params: crate::types::CreateMessageParams,
+    ) -> Result<crate::types::CreateMessageResult> {
+        let client = self
+            .client
+            .as_ref()
+            .ok_or_else(|| crate::Error::InvalidState("Not connected to a client".to_string()))?;
+        client
+            .create_message(params, &self.cancellation_token)
+            .await
+    }
+
+    /// Send a request to the client while handling this request and wait for its result.
+    ///
+    /// Responses are matched to requests by ID, so several requests may be awaited
+    /// at once. Fails with the client's error if it answers with one, or if this
+    /// request is cancelled while waiting.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use async_trait::async_trait;
+    /// use pmcp::server::roots::ListRootsResult;
+    /// use pmcp::types::ServerRequest;
+    /// use pmcp::{RequestHandlerExtra, ToolHandler};
+    /// use serde_json::{json, Value};
+    ///
+    /// struct CountRoots;
+    ///
+    /// #[async_trait]
+    /// impl ToolHandler for CountRoots {
+    ///     async fn handle(&self, _args: Value, extra: RequestHandlerExtra) -> pmcp::Result<Value> {
+    ///         let roots: ListRootsResult =
+    ///             serde_json::from_value(extra.send_request(ServerRequest::ListRoots).await?)?;
+    ///         Ok(json!({"roots": roots.roots.len()}))
+    ///     }
+    /// }
+    /// ```
+    pub async fn send_request(
+        &self,
+        request: crate::types::ServerRequest,
+    ) -> Result<serde_json::Value> {
+        let client = self
+            .client
+            .as_ref()
+            .ok_or_else(|| crate::Error::InvalidState("Not connected to a client".to_string()))?;
+        client.request(request, &self.cancellation_token).await
+    }
+
+    /// Tell the client how far handling this request has got.
+    ///
+    /// Sends a `notifications/progress` with the request's
+    /// [`progress_token`](Self::progress_token), e.g. the fraction of the work done so
+    /// far. Does nothing if the client sent no token, since it then does not want
+    /// progress, or if the server is not connected to a client.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use async_trait::async_trait;
+    /// use pmcp::{RequestHandlerExtra, ToolHandler};
+    /// use serde_json::{json, Value};
+    ///
+    /// struct Import;
+    ///
+    /// #[async_trait]
+    /// impl ToolHandler for Import {
+    ///     async fn handle(&self, args: Value, extra: RequestHandlerExtra) -> pmcp::Result<Value> {
+    ///         let files = args["files"].as_array().cloned().unwrap_or_default();
+    ///         for (i, file) in files.iter().enumerate() {
+    ///             extra
+    ///                 .report_progress(i as f64 / files.len() as f64, Some(format!("Importing {}", file)))
+    ///                 .await;
+    ///         }
+    ///         Ok(json!({"imported": files.len()}))
+    ///     }
+    /// }
+    /// ```
+    pub async fn report_progress(&self, progress: f64, message: Option<String>) {
+        let (Some(progress_token), Some(tx)) = (&self.progress_token, &self.notifications) else {
+            return;
+        };
+        let notification = ServerNotification::Progress(ProgressNotification {
+            progress_token: progress_token.clone(),
+            progress,
+            message,
+        });
+        let _ = tx.send(Notification::Server(notification)).await;
+    }
+
+    /// Build the error a handler should return after being cancelled.
+    ///
+    /// The cancellation reason is included in the error message when known.
+    pub fn cancellation_error(&self) -> crate::Error {
+        crate::Error::cancelled(
+            self.cancellation_reason()
+                .unwrap_or_else(|| "Request cancelled".to_string()),
+        )
+    }
 }
 
 #[cfg(test)]
@@ -224,6 +758,26 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_cancel_notification_keeps_numeric_id() {
+        let notifications = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let sink = notifications.clone();
+
+        let mut manager = CancellationManager::new();
+        manager.set_notification_sender(Arc::new(move |notif| sink.lock().push(notif)));
+
+        let _extra = manager.create_request_extra(&RequestId::Number(5)).await;
+        manager.cancel_request("5".to_string(), None).await.unwrap();
+
+        let notifs = notifications.lock();
+        let Notification::Client(crate::types::ClientNotification::Cancelled(cancelled)) =
+            &notifs[0]
+        else {
+            panic!("Expected Cancelled notification");
+        };
+        assert_eq!(cancelled.request_id, RequestId::Number(5));
+    }
+
     #[tokio::test]
     async fn test_remove_token() {
         let manager = CancellationManager::new();
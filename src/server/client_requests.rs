@@ -0,0 +1,405 @@
+//! Requests from the server to the client, such as sampling, roots and ping.
+//!
+//! Handlers run in their own tasks while the server's message loop keeps reading the
+//! transport, so a handler sends its request and waits for the loop to hand it the
+//! client's answer. Once the connection ends, requests still waiting fail.
+
+use crate::error::{Error, Result, TransportError};
+use crate::server::roots::ListRootsResult;
+use crate::shared::{Transport, TransportMessage};
+use crate::types::jsonrpc::ResponsePayload;
+use crate::types::{
+    ClientCapabilities, CreateMessageParams, CreateMessageResult, JSONRPCResponse, Request,
+    RequestId, ServerRequest,
+};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{oneshot, RwLock, RwLockMappedWriteGuard};
+use tokio_util::sync::CancellationToken;
+
+/// The connection to the client, with the transport type erased.
+#[async_trait]
+pub(crate) trait ClientChannel: Send + Sync {
+    /// Wait for exclusive use of the transport.
+    async fn lock(&self) -> RwLockMappedWriteGuard<'_, dyn Transport>;
+}
+
+/// Requests sent to the client that are waiting for a response.
+#[derive(Debug)]
+pub(crate) struct PendingRequests {
+    waiters: parking_lot::Mutex<HashMap<RequestId, oneshot::Sender<JSONRPCResponse>>>,
+    max_pending: usize,
+    closed: AtomicBool,
+}
+
+impl PendingRequests {
+    /// Create an empty map holding at most `max_pending` waiting requests.
+    pub(crate) fn new(max_pending: usize) -> Self {
+        Self {
+            waiters: parking_lot::Mutex::default(),
+            max_pending,
+            closed: AtomicBool::new(false),
+        }
+    }
+
+    /// Start waiting for the response to `id`.
+    ///
+    /// Fails if `max_pending` requests are already waiting, or the connection ended.
+    pub(crate) fn register(&self, id: RequestId) -> Result<oneshot::Receiver<JSONRPCResponse>> {
+        let mut waiters = self.waiters.lock();
+        if self.closed.load(Ordering::Acquire) {
+            return Err(Error::Transport(TransportError::ConnectionClosed));
+        }
+        if waiters.len() >= self.max_pending {
+            return Err(Error::InvalidState(format!(
+                "Too many pending requests: {} are already waiting for the client (max_pending)",
+                waiters.len()
+            )));
+        }
+        let (tx, rx) = oneshot::channel();
+        waiters.insert(id, tx);
+        Ok(rx)
+    }
+
+    /// Stop waiting for the response to `id`.
+    pub(crate) fn remove(&self, id: &RequestId) {
+        self.waiters.lock().remove(id);
+    }
+
+    /// Hand a response to the request waiting for it.
+    ///
+    /// Returns `false` if no request with that ID is waiting.
+    pub(crate) fn complete(&self, response: JSONRPCResponse) -> bool {
+        match self.waiters.lock().remove(&response.id) {
+            Some(waiter) => waiter.send(response).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Number of requests waiting for a response.
+    pub(crate) fn len(&self) -> usize {
+        self.waiters.lock().len()
+    }
+
+    /// Fail the requests waiting for a response, and any sent later.
+    pub(crate) fn close(&self) {
+        let mut waiters = self.waiters.lock();
+        self.closed.store(true, Ordering::Release);
+        waiters.clear();
+    }
+}
+
+/// Sends requests to the client and correlates them with their responses.
+pub(crate) struct ClientRequests {
+    channel: Arc<dyn ClientChannel>,
+    client_capabilities: Arc<RwLock<Option<ClientCapabilities>>>,
+    next_id: AtomicI64,
+    pending: PendingRequests,
+}
+
+impl std::fmt::Debug for ClientRequests {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientRequests")
+            .field("pending", &self.pending.len())
+            .finish()
+    }
+}
+
+impl ClientRequests {
+    pub(crate) fn new(
+        channel: Arc<dyn ClientChannel>,
+        client_capabilities: Arc<RwLock<Option<ClientCapabilities>>>,
+    ) -> Self {
+        Self {
+            channel,
+            client_capabilities,
+            next_id: AtomicI64::new(1),
+            pending: PendingRequests::new(crate::shared::DEFAULT_MAX_PENDING),
+        }
+    }
+
+    /// Ask the client's LLM to sample a message.
+    pub(crate) async fn create_message(
+        &self,
+        params: CreateMessageParams,
+        cancellation_token: &CancellationToken,
+    ) -> Result<CreateMessageResult> {
+        let supports_sampling = self
+            .client_capabilities
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|caps| caps.sampling.is_some());
+        if !supports_sampling {
+            return Err(Error::capability("Client does not support sampling"));
+        }
+
+        let result = self
+            .request(
+                ServerRequest::CreateMessage(Box::new(params)),
+                cancellation_token,
+            )
+            .await?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Ask the client for the roots it exposes.
+    pub(crate) async fn list_roots(
+        &self,
+        cancellation_token: &CancellationToken,
+    ) -> Result<ListRootsResult> {
+        let supports_roots = self
+            .client_capabilities
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|caps| caps.roots.is_some());
+        if !supports_roots {
+            return Err(Error::capability("Client does not support roots"));
+        }
+
+        let result = self
+            .request(ServerRequest::ListRoots, cancellation_token)
+            .await?;
+        serde_json::from_value(result)
+            .map_err(|e| Error::protocol_msg(format!("Invalid roots response: {}", e)))
+    }
+
+    /// Send a request to the client and wait for its result.
+    ///
+    /// Gives up with a cancellation error once `cancellation_token` is cancelled.
+    pub(crate) async fn request(
+        &self,
+        request: ServerRequest,
+        cancellation_token: &CancellationToken,
+    ) -> Result<serde_json::Value> {
+        let id = RequestId::Number(self.next_id.fetch_add(1, Ordering::Relaxed));
+        let waiter = self.pending.register(id.clone())?;
+        let response = self
+            .send_and_wait(&id, request, waiter, cancellation_token)
+            .await;
+        self.pending.remove(&id);
+
+        match response?.payload {
+            ResponsePayload::Result(result) => Ok(result),
+            ResponsePayload::Error(error) => Err(Error::from_jsonrpc_error(error)),
+        }
+    }
+
+    async fn send_and_wait(
+        &self,
+        id: &RequestId,
+        request: ServerRequest,
+        waiter: oneshot::Receiver<JSONRPCResponse>,
+        cancellation_token: &CancellationToken,
+    ) -> Result<JSONRPCResponse> {
+        self.channel
+            .lock()
+            .await
+            .send(TransportMessage::Request {
+                id: id.clone(),
+                request: Request::Server(Box::new(request)),
+            })
+            .await?;
+
+        tokio::select! {
+            response = waiter => response.map_err(|_| {
+                Error::Transport(TransportError::ConnectionClosed)
+            }),
+            () = cancellation_token.cancelled() => Err(Error::cancelled(
+                "Request cancelled while waiting for the client",
+            )),
+        }
+    }
+
+    /// Hand a response to the request waiting for it.
+    ///
+    /// Returns `false` if no request with that ID is waiting.
+    pub(crate) fn deliver(&self, response: JSONRPCResponse) -> bool {
+        self.pending.complete(response)
+    }
+
+    /// Fail the requests waiting for the client, since the connection ended.
+    pub(crate) fn close(&self) {
+        self.pending.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::connection::Connection;
+    use serde_json::{json, Value};
+    use std::collections::VecDeque;
+    use tokio::sync::mpsc;
+
+    type Payload = ResponsePayload<Value, crate::types::JSONRPCError>;
+
+    fn response(id: i64, payload: Payload) -> JSONRPCResponse {
+        JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id: RequestId::from(id),
+            payload,
+        }
+    }
+
+    #[test]
+    fn test_pending_requests_route_by_id() {
+        let pending = PendingRequests::new(1);
+        let mut waiter = pending.register(RequestId::from(1i64)).unwrap();
+        let err = pending.register(RequestId::from(2i64)).unwrap_err();
+        assert!(err.to_string().contains("max_pending"), "{}", err);
+
+        assert!(!pending.complete(response(2, ResponsePayload::Result(json!({})))));
+        assert!(waiter.try_recv().is_err());
+
+        assert!(pending.complete(response(1, ResponsePayload::Result(json!({"ok": true})))));
+        assert_eq!(waiter.try_recv().unwrap().id, RequestId::from(1i64));
+        assert_eq!(pending.len(), 0);
+    }
+
+    /// Client that answers each server request with the next scripted payload.
+    #[derive(Debug)]
+    struct ScriptedClient {
+        answers: VecDeque<Payload>,
+        responses: mpsc::UnboundedSender<TransportMessage>,
+        incoming: mpsc::UnboundedReceiver<TransportMessage>,
+    }
+
+    impl ScriptedClient {
+        fn new(answers: impl Into<VecDeque<Payload>>) -> Self {
+            let (responses, incoming) = mpsc::unbounded_channel();
+            Self {
+                answers: answers.into(),
+                responses,
+                incoming,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Transport for ScriptedClient {
+        async fn send(&mut self, message: TransportMessage) -> Result<()> {
+            if let TransportMessage::Request { id, .. } = message {
+                let payload = self.answers.pop_front().expect("unexpected request");
+                let _ = self
+                    .responses
+                    .send(TransportMessage::Response(JSONRPCResponse {
+                        jsonrpc: "2.0".to_string(),
+                        id,
+                        payload,
+                    }));
+            }
+            Ok(())
+        }
+
+        async fn receive(&mut self) -> Result<TransportMessage> {
+            Ok(self
+                .incoming
+                .recv()
+                .await
+                .ok_or(TransportError::ConnectionClosed)?)
+        }
+
+        async fn close(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Hand the responses read from `connection` to `requests`, like the message loop.
+    fn spawn_reader(
+        connection: Arc<Connection<ScriptedClient>>,
+        requests: Arc<ClientRequests>,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            while let Ok(TransportMessage::Response(response)) = connection.receive().await {
+                requests.deliver(response);
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_request_awaits_client_response() {
+        let connection = Arc::new(Connection::new(ScriptedClient::new([
+            ResponsePayload::Result(json!({})),
+            ResponsePayload::Error(crate::types::JSONRPCError {
+                code: -32601,
+                message: "Method not found".to_string(),
+                data: None,
+            }),
+        ])));
+        let requests = Arc::new(ClientRequests::new(
+            connection.clone(),
+            Arc::new(RwLock::new(None)),
+        ));
+        let reader = spawn_reader(connection, requests.clone());
+        let token = CancellationToken::new();
+
+        let result = requests.request(ServerRequest::Ping, &token).await.unwrap();
+        assert_eq!(result, json!({}));
+
+        let err = requests
+            .request(ServerRequest::ListRoots, &token)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Method not found"), "{}", err);
+        assert_eq!(requests.pending.len(), 0);
+
+        // Sampling needs the client to have declared the capability
+        let err = requests
+            .create_message(
+                CreateMessageParams {
+                    messages: vec![],
+                    model_preferences: None,
+                    system_prompt: None,
+                    include_context: crate::types::IncludeContext::default(),
+                    temperature: None,
+                    max_tokens: None,
+                    stop_sequences: None,
+                    metadata: None,
+                },
+                &token,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::UnsupportedCapability(_)));
+        reader.abort();
+    }
+
+    #[tokio::test]
+    async fn test_requests_fail_once_connection_closes() {
+        let connection = Arc::new(Connection::new(ScriptedClient::new([
+            ResponsePayload::Result(json!({})),
+        ])));
+        let requests = Arc::new(ClientRequests::new(connection, Arc::new(RwLock::new(None))));
+        let token = CancellationToken::new();
+
+        // Nobody reads the answer, so the request waits until the connection ends
+        let waiting = tokio::spawn({
+            let requests = requests.clone();
+            async move { requests.request(ServerRequest::Ping, &token).await }
+        });
+        while requests.pending.len() == 0 {
+            tokio::task::yield_now().await;
+        }
+        requests.close();
+
+        let err = waiting.await.unwrap().unwrap_err();
+        assert!(
+            matches!(err, Error::Transport(TransportError::ConnectionClosed)),
+            "{}",
+            err
+        );
+        let err = requests
+            .request(ServerRequest::Ping, &CancellationToken::new())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::Transport(TransportError::ConnectionClosed)),
+            "{}",
+            err
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! The transport of a connection served by [`Server::run`](super::Server::run).
+//!
+//! The message loop keeps a `receive` pending on the transport while requests are
+//! handled in their own tasks, so responses, notifications and requests to the client
+//! are sent from other tasks. A task about to send interrupts the pending `receive`
+//! and takes the transport, which is why [`Transport::receive`] must be cancel safe;
+//! the loop resumes receiving once nobody else is waiting for the transport.
+
+use crate::error::Result;
+use crate::server::client_requests::ClientChannel;
+use crate::shared::{Transport, TransportMessage};
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::{Notify, RwLock, RwLockMappedWriteGuard, RwLockWriteGuard};
+
+/// A transport shared by the message loop and the tasks sending through it.
+#[derive(Debug)]
+pub(crate) struct Connection<T> {
+    transport: RwLock<T>,
+    /// Number of tasks waiting to lock the transport
+    waiting: AtomicUsize,
+    /// Wakes the loop to let go of the transport
+    interrupt: Notify,
+}
+
+/// Counts a task as waiting for the transport until dropped.
+struct Waiting<'a>(&'a AtomicUsize);
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl<T: Transport> Connection<T> {
+    pub(crate) fn new(transport: T) -> Self {
+        Self {
+            transport: RwLock::new(transport),
+            waiting: AtomicUsize::new(0),
+            interrupt: Notify::new(),
+        }
+    }
+
+    /// Lock the transport, interrupting the message loop if it is receiving.
+    pub(crate) async fn lock(&self) -> RwLockWriteGuard<'_, T> {
+        self.waiting.fetch_add(1, Ordering::AcqRel);
+        let _waiting = Waiting(&self.waiting);
+        self.interrupt.notify_one();
+        self.transport.write().await
+    }
+
+    /// Send a message to the client.
+    pub(crate) async fn send(&self, message: TransportMessage) -> Result<()> {
+        self.lock().await.send(message).await
+    }
+
+    /// Receive the next message, giving way to tasks that want to send meanwhile.
+    pub(crate) async fn receive(&self) -> Result<TransportMessage> {
+        loop {
+            let mut guard = self.transport.write().await;
+            if self.waiting.load(Ordering::Acquire) > 0 {
+                // A sender queued for the lock goes first
+                drop(guard);
+                tokio::task::yield_now().await;
+                continue;
+            }
+            tokio::select! {
+                biased;
+                () = self.interrupt.notified() => continue,
+                received = guard.receive() => return received,
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<T: Transport + 'static> ClientChannel for Connection<T> {
+    async fn lock(&self) -> RwLockMappedWriteGuard<'_, dyn Transport> {
+        RwLockWriteGuard::map(Connection::lock(self).await, |transport| {
+            transport as &mut dyn Transport
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Notification, ServerNotification};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    /// Transport whose `receive` waits until the test sends it a message.
+    #[derive(Debug)]
+    struct QuietTransport {
+        incoming: mpsc::Receiver<TransportMessage>,
+        sent: mpsc::UnboundedSender<TransportMessage>,
+    }
+
+    #[async_trait]
+    impl Transport for QuietTransport {
+        async fn send(&mut self, message: TransportMessage) -> Result<()> {
+            let _ = self.sent.send(message);
+            Ok(())
+        }
+
+        async fn receive(&mut self) -> Result<TransportMessage> {
+            self.incoming
+                .recv()
+                .await
+                .ok_or_else(|| crate::error::TransportError::ConnectionClosed.into())
+        }
+
+        async fn close(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_interrupts_pending_receive() {
+        let (incoming_tx, incoming) = mpsc::channel(1);
+        let (sent, mut sent_rx) = mpsc::unbounded_channel();
+        let connection = Arc::new(Connection::new(QuietTransport { incoming, sent }));
+        let receiving = tokio::spawn({
+            let connection = connection.clone();
+            async move { connection.receive().await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let notification =
+            TransportMessage::Notification(Notification::Server(ServerNotification::ToolsChanged));
+        tokio::time::timeout(Duration::from_secs(1), connection.send(notification))
+            .await
+            .expect("send waited for the pending receive")
+            .unwrap();
+        assert!(sent_rx.try_recv().is_ok());
+
+        // The loop goes back to receiving afterwards
+        let message = TransportMessage::Notification(Notification::Server(
+            ServerNotification::ResourcesChanged,
+        ));
+        incoming_tx.send(message).await.unwrap();
+        let received = tokio::time::timeout(Duration::from_secs(1), receiving)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            received,
+            TransportMessage::Notification(Notification::Server(
+                ServerNotification::ResourcesChanged
+            ))
+        ));
+    }
+}
@@ -39,9 +39,9 @@ type CapabilityListener = Box<dyn Fn(&ServerCapabilities) + Send + Sync>;
 /// # Ok(())
 /// # }
 /// ```
-pub struct DynamicServerManager {
+pub struct DynamicServerManager<S = ()> {
     /// The server instance
-    server: Arc<Server>,
+    server: Arc<Server<S>>,
 
     /// Dynamic tool registry
     dynamic_tools: Arc<RwLock<HashMap<String, Arc<dyn ToolHandler>>>>,
@@ -60,7 +60,7 @@ pub struct DynamicServerManager {
     capability_listeners: Arc<RwLock<Vec<CapabilityListener>>>,
 }
 
-impl DynamicServerManager {
+impl<S: Send + Sync + 'static> DynamicServerManager<S> {
     /// Create a new dynamic server manager
     ///
     /// # Examples
@@ -81,7 +81,7 @@ impl DynamicServerManager {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn new(server: Arc<Server>) -> Self {
+    pub fn new(server: Arc<Server<S>>) -> Self {
         Self {
             server,
             dynamic_tools: Arc::new(RwLock::new(HashMap::new())),
@@ -330,7 +330,7 @@ impl DynamicServerManager {
     }
 }
 
-impl std::fmt::Debug for DynamicServerManager {
+impl<S> std::fmt::Debug for DynamicServerManager<S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("DynamicServerManager")
             .field("server", &"Arc<Server>")
@@ -524,6 +524,9 @@ mod tests {
             name: "dynamic_test".to_string(),
             description: Some("Dynamic test tool".to_string()),
             input_schema: json!({}),
+            category: None,
+            tags: Vec::new(),
+            output_schema: None,
         };
 
         manager
@@ -539,6 +542,33 @@ mod tests {
         assert!(!manager.has_tool("dynamic_test").await);
     }
 
+    #[tokio::test]
+    async fn test_dynamic_tools_on_server_with_state() {
+        let server = Arc::new(
+            ServerBuilder::new()
+                .name("test")
+                .version("1.0.0")
+                .state(42u32)
+                .build()
+                .unwrap(),
+        );
+
+        let manager = DynamicServerManager::new(server);
+        let tool_info = ToolInfo {
+            name: "dynamic_test".to_string(),
+            description: None,
+            input_schema: json!({}),
+            category: None,
+            tags: Vec::new(),
+            output_schema: None,
+        };
+        manager
+            .add_tool("dynamic_test", Arc::new(TestTool), tool_info)
+            .await
+            .unwrap();
+        assert!(manager.has_tool("dynamic_test").await);
+    }
+
     #[tokio::test]
     async fn test_dynamic_configuration() {
         let server = Arc::new(
@@ -560,6 +590,9 @@ mod tests {
                     name: "tool1".to_string(),
                     description: Some("Tool 1".to_string()),
                     input_schema: json!({}),
+                    category: None,
+                    tags: Vec::new(),
+                    output_schema: None,
                 },
             )
             .prompt(
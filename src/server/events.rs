@@ -0,0 +1,93 @@
+//! Lifecycle events behind [`Server::events`](super::Server::events).
+
+use crate::types::{Implementation, Notification, RequestId};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Number of events kept for a subscriber that is not keeping up; later events are
+/// dropped until it catches up.
+const EVENT_CAPACITY: usize = 256;
+
+/// Something that happened in a [`Server`](super::Server), see
+/// [`Server::events`](super::Server::events).
+#[derive(Debug, Clone)]
+pub enum ServerEvent {
+    /// The server started serving a connection
+    ClientConnected,
+    /// The client completed `initialize`
+    ClientInitialized {
+        /// Name and version the client reported
+        client_info: Implementation,
+        /// Protocol version the client asked for
+        protocol_version: String,
+    },
+    /// A request arrived
+    RequestStarted {
+        /// Request ID
+        id: RequestId,
+        /// JSON-RPC method
+        method: String,
+    },
+    /// A request was answered with a result
+    RequestCompleted {
+        /// Request ID
+        id: RequestId,
+        /// JSON-RPC method
+        method: String,
+        /// Time taken to handle the request
+        duration: Duration,
+    },
+    /// A request was answered with an error
+    RequestFailed {
+        /// Request ID
+        id: RequestId,
+        /// JSON-RPC method
+        method: String,
+        /// Time taken to handle the request
+        duration: Duration,
+        /// JSON-RPC error code
+        code: i32,
+        /// Error message sent to the client
+        message: String,
+    },
+    /// A notification was sent to the client
+    NotificationSent(Notification),
+    /// The connection closed and the server stopped handling messages
+    Shutdown,
+}
+
+/// Sends events to the current subscriber, if any.
+///
+/// Events are built lazily and sent without waiting, so the server neither pays for
+/// events nobody receives nor slows down for a subscriber that falls behind.
+#[derive(Debug, Default)]
+pub(crate) struct EventEmitter {
+    subscriber: parking_lot::RwLock<Option<mpsc::Sender<ServerEvent>>>,
+}
+
+impl EventEmitter {
+    /// Replace the subscriber with a new one.
+    pub(crate) fn subscribe(&self) -> mpsc::Receiver<ServerEvent> {
+        let (tx, rx) = mpsc::channel(EVENT_CAPACITY);
+        *self.subscriber.write() = Some(tx);
+        rx
+    }
+
+    /// Whether someone is receiving events.
+    pub(crate) fn is_subscribed(&self) -> bool {
+        self.subscriber
+            .read()
+            .as_ref()
+            .is_some_and(|tx| !tx.is_closed())
+    }
+
+    /// Send the event built by `event` to the subscriber.
+    pub(crate) fn emit(&self, event: impl FnOnce() -> ServerEvent) {
+        let subscriber = self.subscriber.read();
+        let Some(tx) = subscriber.as_ref().filter(|tx| !tx.is_closed()) else {
+            return;
+        };
+        // A full channel drops the event rather than stalling the server
+        let _ = tx.try_send(event());
+    }
+}
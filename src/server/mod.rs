@@ -1,39 +1,98 @@
 //! MCP server implementation.
 
-use crate::error::{Error, Result};
+use crate::error::{Error, ErrorCode, Result, TransportError};
 use crate::shared::{Protocol, ProtocolOptions, TransportMessage};
 use crate::types::{
-    CallToolRequest, CallToolResult, ClientCapabilities, ClientRequest, GetPromptRequest,
-    Implementation, InitializeResult, JSONRPCResponse, ListPromptsRequest, ListPromptsResult,
-    ListResourceTemplatesRequest, ListResourceTemplatesResult, ListResourcesRequest,
-    ListResourcesResult, ListToolsRequest, ListToolsResult, Notification, ProtocolVersion,
-    ReadResourceRequest, Request, RequestId, ServerCapabilities, ServerNotification,
+    CallToolRequest, CallToolResult, ChangeKind, ClientCapabilities, ClientRequest,
+    GetPromptRequest, Implementation, InitializeResult, JSONRPCResponse, ListPromptsRequest,
+    ListPromptsResult, ListResourceTemplatesRequest, ListResourceTemplatesResult,
+    ListResourcesRequest, ListResourcesResult, ListToolsRequest, ListToolsResult, Notification,
+    ProtocolVersion, ReadResourceRequest, Request, RequestId, ServerCapabilities,
+    ServerNotification,
 };
 use async_trait::async_trait;
+use futures::FutureExt;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
+use tokio_util::sync::CancellationToken;
 
+mod argument_streams;
+pub mod audit;
 pub mod auth;
 pub mod batch;
 pub mod cancellation;
+mod client_requests;
+mod connection;
 pub mod dynamic;
 pub mod elicitation;
+mod events;
 pub mod notification_debouncer;
+pub mod plugins;
+pub mod priority;
+mod progress_backlog;
+pub mod resource_list;
 #[cfg(feature = "resource-watcher")]
 pub mod resource_watcher;
 pub mod roots;
+mod sessions;
+mod stats;
 #[cfg(feature = "streamable-http")]
 pub mod streamable_http_server;
 pub mod subscriptions;
+pub mod tool_result;
 pub mod transport;
+#[cfg(feature = "schema-generation")]
+pub mod typed_tool;
+
+pub use events::ServerEvent;
+pub use stats::{MethodStats, ServerStats};
+pub use tool_result::ToolResult;
+
+/// Number of notifications that may wait to be sent to the client.
+const NOTIFICATION_CAPACITY: usize = 100;
+
+/// Progress is coalesced once less than this fraction (one in N) of the notification
+/// slots is free, see [`ServerBuilder::coalesce_progress`].
+const COALESCE_PROGRESS_DIVISOR: usize = 4;
+
+/// Time a timed out handler is given to clean up after its request is cancelled.
+const TIMEOUT_CLEANUP_GRACE: std::time::Duration = std::time::Duration::from_secs(1);
 
 /// Handler for tool execution.
 #[async_trait]
 pub trait ToolHandler: Send + Sync {
     /// Handle a tool call with the given arguments.
+    ///
+    /// The returned value is sent to the client as JSON text content, so clients
+    /// decode every result the same way. Return a [`ToolResult`] converted into a value, or an
+    /// object shaped like a [`CallToolResult`] with a `content` array and optional
+    /// `isError`, to send several content items or an error result instead.
     async fn handle(&self, args: Value, extra: cancellation::RequestHandlerExtra) -> Result<Value>;
+
+    /// Describe the tool, including the JSON Schema of its arguments.
+    ///
+    /// Returns `None` by default, meaning the tool has no declared schema.
+    fn metadata(&self) -> Option<crate::types::ToolInfo> {
+        None
+    }
+}
+
+/// Synchronous handler for tools that block or do heavy computation.
+///
+/// Registered with [`ServerBuilder::blocking_tool`], it runs on Tokio's blocking thread
+/// pool so that it does not stall other requests.
+pub trait BlockingToolHandler: Send + Sync {
+    /// Handle a tool call with the given arguments.
+    fn handle(&self, args: Value, extra: cancellation::RequestHandlerExtra) -> Result<Value>;
+
+    /// Describe the tool, including the JSON Schema of its arguments.
+    ///
+    /// Returns `None` by default, meaning the tool has no declared schema.
+    fn metadata(&self) -> Option<crate::types::ToolInfo> {
+        None
+    }
 }
 
 /// Handler for prompt generation.
@@ -45,6 +104,48 @@ pub trait PromptHandler: Send + Sync {
         args: HashMap<String, String>,
         extra: cancellation::RequestHandlerExtra,
     ) -> Result<crate::types::GetPromptResult>;
+
+    /// Describe the prompt, including the arguments it accepts.
+    ///
+    /// Returns `None` by default, meaning the prompt's arguments are not declared.
+    fn metadata(&self) -> Option<crate::types::PromptInfo> {
+        None
+    }
+}
+
+/// Handler for argument completion (`completion/complete`).
+///
+/// The server validates the request's reference before calling the handler, so
+/// handlers receive the prompt or resource being completed instead of looking it
+/// up again.
+#[async_trait]
+pub trait CompletionHandler: Send + Sync {
+    /// Suggest values for `argument` of the referenced prompt or resource.
+    ///
+    /// The server sends at most [`CompletionResult::MAX_VALUES`] values, setting
+    /// `has_more` and `total` when it drops the rest.
+    ///
+    /// [`CompletionResult::MAX_VALUES`]: crate::types::CompletionResult::MAX_VALUES
+    async fn complete(
+        &self,
+        context: CompletionContext,
+        argument: crate::types::CompletionArgument,
+        extra: cancellation::RequestHandlerExtra,
+    ) -> Result<crate::types::CompletionResult>;
+}
+
+/// What a completion request refers to, resolved by the server.
+#[derive(Debug, Clone)]
+pub enum CompletionContext {
+    /// A registered prompt. If the prompt declares its arguments, the argument
+    /// being completed is one of them.
+    Prompt(crate::types::PromptInfo),
+    /// A resource or resource template URI. The URI is not checked against the
+    /// resource handler.
+    Resource {
+        /// Resource URI
+        uri: String,
+    },
 }
 
 /// Handler for resource access.
@@ -57,12 +158,49 @@ pub trait ResourceHandler: Send + Sync {
         extra: cancellation::RequestHandlerExtra,
     ) -> Result<crate::types::ReadResourceResult>;
 
+    /// Read a resource unless the client's copy, identified by its content hash, is
+    /// still current.
+    ///
+    /// Called instead of [`read`](Self::read) when the request carries
+    /// `_meta.ifNoneMatch`. The default reads the resource and answers with
+    /// [`ReadResourceResult::unchanged`](crate::types::ReadResourceResult::unchanged)
+    /// if its content hash, computed when `read` does not set one, equals
+    /// `known_hash`. Override it to check without reading the contents.
+    async fn read_if_changed(
+        &self,
+        uri: &str,
+        known_hash: &str,
+        extra: cancellation::RequestHandlerExtra,
+    ) -> Result<crate::types::ReadResourceResult> {
+        let mut result = self.read(uri, extra).await?;
+        if result.content_hash.is_none() {
+            result = result.with_content_hash();
+        }
+        if result.content_hash.as_deref() == Some(known_hash) {
+            return Ok(crate::types::ReadResourceResult::unchanged(known_hash));
+        }
+        Ok(result)
+    }
+
     /// List available resources.
     async fn list(
         &self,
         _cursor: Option<String>,
         extra: cancellation::RequestHandlerExtra,
     ) -> Result<crate::types::ListResourcesResult>;
+
+    /// List templates of the resources this handler can read, answering
+    /// `resources/templates/list`. The default lists none.
+    async fn list_templates(
+        &self,
+        _cursor: Option<String>,
+        _extra: cancellation::RequestHandlerExtra,
+    ) -> Result<ListResourceTemplatesResult> {
+        Ok(ListResourceTemplatesResult {
+            resource_templates: Vec::new(),
+            next_cursor: None,
+        })
+    }
 }
 
 /// Handler for message sampling (LLM operations).
@@ -76,6 +214,44 @@ pub trait SamplingHandler: Send + Sync {
     ) -> Result<crate::types::CreateMessageResult>;
 }
 
+/// Hook run around each client request.
+///
+/// Registered with [`ServerBuilder::middleware`]. Middleware runs in registration
+/// order and can reject a request, or store what it learns about the caller in the
+/// connection's [`ConnectionContext`](crate::shared::ConnectionContext) for handlers
+/// to read with [`RequestHandlerExtra::context`]. A server shared between sessions,
+/// like the streamable HTTP server, keeps one context per session, dropped when the
+/// session is deleted. Once the request is handled, it
+/// sees the response about to be sent and may change it.
+///
+/// [`RequestHandlerExtra::context`]: cancellation::RequestHandlerExtra::context
+#[async_trait]
+pub trait RequestMiddleware: Send + Sync {
+    /// Inspect `request` before it is handled.
+    ///
+    /// Returning an error answers the request with it, without calling the handler
+    /// or any later middleware.
+    async fn on_request(
+        &self,
+        request: &ClientRequest,
+        context: &crate::shared::ConnectionContext,
+    ) -> Result<()>;
+
+    /// Inspect or change the `response` to a request for `method` before it is sent.
+    ///
+    /// Also called for responses to requests rejected by middleware or failing
+    /// otherwise. Returning an error answers the request with it instead, without
+    /// calling later middleware. Does nothing by default.
+    async fn on_response(
+        &self,
+        _method: &str,
+        _response: &mut JSONRPCResponse,
+        _context: &crate::shared::ConnectionContext,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
 /// MCP server implementation.
 ///
 /// # Examples
@@ -105,29 +281,78 @@ pub trait SamplingHandler: Send + Sync {
 /// # Ok(())
 /// # }
 /// ```
+///
+/// `S` is the state shared by the handlers, set with [`ServerBuilder::state`]. Servers
+/// without state use `()`.
 #[allow(dead_code)]
-pub struct Server {
+pub struct Server<S = ()> {
     info: Implementation,
     capabilities: ServerCapabilities,
+    /// Usage guidance sent to clients when they initialize
+    instructions: Option<String>,
     tools: HashMap<String, Arc<dyn ToolHandler>>,
     prompts: HashMap<String, Arc<dyn PromptHandler>>,
     resources: Option<Arc<dyn ResourceHandler>>,
     sampling: Option<Arc<dyn SamplingHandler>>,
+    completions: Option<Arc<dyn CompletionHandler>>,
     client_capabilities: Arc<RwLock<Option<ClientCapabilities>>>,
-    initialized: Arc<RwLock<bool>>,
+    /// Connections and sessions whose client completed initialization
+    initialized: parking_lot::Mutex<std::collections::HashSet<String>>,
     /// Channel for sending notifications
     notification_tx: Option<mpsc::Sender<Notification>>,
+    /// Progress held back while the notification channel is congested
+    progress_backlog: Arc<progress_backlog::ProgressBacklog>,
     /// Cancellation manager for request cancellation
     cancellation_manager: cancellation::CancellationManager,
     /// Roots manager for directory/URI registration
     roots_manager: Arc<RwLock<roots::RootsManager>>,
+    /// Roots declared by connected clients
+    client_roots: roots::ClientRoots,
+    /// Tool call arguments being streamed by the client
+    argument_streams: argument_streams::ArgumentStreams,
     /// Subscription manager for resource subscriptions
     subscription_manager: Arc<RwLock<subscriptions::SubscriptionManager>>,
     /// Elicitation manager for user input requests
     elicitation_manager: Option<Arc<elicitation::ElicitationManager>>,
+    /// Whether to fill missing tool arguments from schema defaults
+    apply_schema_defaults: bool,
+    /// Whether to check tool results against their declared output schema
+    validate_tool_output: bool,
+    /// Whether a panicking tool handler is turned into an error result
+    catch_tool_panics: bool,
+    /// Whether notifications the client has not opted into are dropped
+    strict_notifications: bool,
+    /// Whether progress is coalesced while the notification channel is congested
+    coalesce_progress: bool,
+    /// Requests taking longer than this are logged as slow
+    slow_request_threshold: Option<std::time::Duration>,
+    /// Requests taking longer than this are cancelled and fail
+    request_timeout: Option<std::time::Duration>,
+    /// Maximum nesting depth of inbound messages
+    max_json_depth: usize,
+    /// Handling of invalid UTF-8 in inbound messages
+    utf8: crate::shared::Utf8Mode,
+    /// Most tools or prompts listed per page
+    page_size: Option<usize>,
+    /// Records every tool call, if auditing is enabled
+    auditor: Option<audit::Auditor>,
+    /// Requests handled so far, see [`Server::stats`]
+    request_counters: stats::RequestCounters,
+    /// Lifecycle events, see [`Server::events`]
+    events: Arc<events::EventEmitter>,
+    /// Hooks run before each client request
+    middleware: Vec<Arc<dyn RequestMiddleware>>,
+    /// Values kept for the connection or each session, cleared when it closes
+    connection_contexts: sessions::SessionContexts,
+    /// Optional priority dispatcher bounding concurrent request handling
+    dispatcher: Option<priority::PriorityDispatcher>,
+    /// Requests sent to the connected client, set while running
+    client_requests: Option<Arc<client_requests::ClientRequests>>,
+    /// State shared by the handlers
+    state: Arc<S>,
 }
 
-impl std::fmt::Debug for Server {
+impl<S> std::fmt::Debug for Server<S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Server")
             .field("info", &self.info)
@@ -142,6 +367,48 @@ impl std::fmt::Debug for Server {
 }
 
 impl Server {
+    /// Create a new server builder.
+    ///
+    /// Returns a `ServerBuilder` for configuring and constructing a new MCP server.
+    /// The builder pattern allows you to set server information, capabilities,
+    /// and register handlers before building the final server instance.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::{Server, ToolHandler};
+    /// use async_trait::async_trait;
+    /// use serde_json::Value;
+    ///
+    /// struct HelloTool;
+    ///
+    /// #[async_trait]
+    /// impl ToolHandler for HelloTool {
+    ///     async fn handle(&self, args: Value, _extra: pmcp::RequestHandlerExtra) -> pmcp::Result<Value> {
+    ///         Ok(serde_json::json!({"message": "Hello, World!"}))
+    ///     }
+    /// }
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let server = Server::builder()
+    ///     .name("greeting-server")
+    ///     .version("1.0.0")
+    ///     .tool("hello", HelloTool{})
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn builder() -> ServerBuilder {
+        ServerBuilder::new()
+    }
+}
+
+impl<S: Send + Sync + 'static> Server<S> {
+    /// Get the state shared by the handlers.
+    pub fn state(&self) -> &Arc<S> {
+        &self.state
+    }
+
     /// Check if a tool exists
     pub fn has_tool(&self, name: &str) -> bool {
         self.tools.contains_key(name)
@@ -151,10 +418,104 @@ impl Server {
     pub fn has_prompt(&self, name: &str) -> bool {
         self.prompts.contains_key(name)
     }
+
+    /// Get uptime and request counters, e.g. for an admin endpoint.
+    ///
+    /// Always available and cheap to keep: counters are atomics updated once per
+    /// request.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::Server;
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let server = Server::builder()
+    ///     .name("monitored-server")
+    ///     .version("1.0.0")
+    ///     .build()?;
+    ///
+    /// let stats = server.stats().await;
+    /// assert_eq!(stats.total_requests, 0);
+    /// println!("up for {:?}, {} errors", stats.uptime, stats.total_errors);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn stats(&self) -> ServerStats {
+        let active_subscriptions = self
+            .subscription_manager
+            .read()
+            .await
+            .get_stats()
+            .await
+            .total_subscriptions;
+        let connected_clients = self.initialized.lock().len();
+        self.request_counters
+            .snapshot(active_subscriptions, connected_clients)
+    }
+
+    /// Subscribe to lifecycle events, e.g. for a dashboard or a test.
+    ///
+    /// Events are structured counterparts of the server's log lines: connection,
+    /// initialization, each request's start and outcome, notifications sent and
+    /// shutdown. There is one subscriber at a time; subscribing again replaces the
+    /// previous one. Events are only built while someone is subscribed, and are
+    /// dropped while the subscriber is more than a few hundred events behind, so a
+    /// slow subscriber never slows the server down.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::server::ServerEvent;
+    /// use pmcp::Server;
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let server = Server::builder()
+    ///     .name("observed-server")
+    ///     .version("1.0.0")
+    ///     .build()?;
+    ///
+    /// let mut events = server.events();
+    /// tokio::spawn(async move {
+    ///     while let Some(event) = events.recv().await {
+    ///         if let ServerEvent::RequestFailed { method, message, .. } = event {
+    ///             eprintln!("{} failed: {}", method, message);
+    ///         }
+    ///     }
+    /// });
+    /// server.run_stdio().await
+    /// # }
+    /// ```
+    pub fn events(&self) -> mpsc::Receiver<ServerEvent> {
+        self.events.subscribe()
+    }
+
+    /// Forget what was kept for session `session_id` of a server shared between
+    /// sessions, once the session ended.
+    #[cfg_attr(not(feature = "streamable-http"), allow(dead_code))]
+    pub(crate) async fn end_session(&self, session_id: &str) {
+        self.initialized.lock().remove(session_id);
+        self.connection_contexts.end(session_id);
+        self.argument_streams.end(session_id);
+        self.client_roots.remove(session_id).await;
+        self.end_subscriptions(session_id).await;
+    }
+
+    /// Drop the resource subscriptions of connection or session `subscriber_id`.
+    async fn end_subscriptions(&self, subscriber_id: &str) {
+        let subscription_manager = self.subscription_manager.read().await;
+        if let Err(e) = subscription_manager.unsubscribe_all(subscriber_id).await {
+            tracing::warn!("Failed to drop subscriptions of {}: {}", subscriber_id, e);
+        }
+    }
+
     /// Send a notification.
     ///
     /// Sends a notification to the connected client. Notifications are one-way
-    /// messages that don't expect a response.
+    /// messages that don't expect a response. With
+    /// [`ServerBuilder::strict_notifications`] enabled, notifications the client
+    /// has not opted into are logged and dropped (see
+    /// [`Server::is_notification_supported`]).
     ///
     /// # Arguments
     ///
@@ -183,8 +544,76 @@ impl Server {
     /// # }
     /// ```
     pub async fn send_notification(&self, notification: ServerNotification) {
-        if let Some(tx) = &self.notification_tx {
-            let _ = tx.send(Notification::Server(notification)).await;
+        if self.strict_notifications && !self.is_notification_supported(&notification).await {
+            Self::log_warning(&format!(
+                "Dropping notification the client has not opted into: {:?}",
+                notification
+            ))
+            .await;
+            return;
+        }
+        let Some(tx) = &self.notification_tx else {
+            return;
+        };
+        let notification = match notification {
+            ServerNotification::Progress(progress) if self.coalesce_progress => {
+                let congested = tx.capacity() < tx.max_capacity() / COALESCE_PROGRESS_DIVISOR;
+                match self.progress_backlog.offer(progress, congested) {
+                    Some(progress) => ServerNotification::Progress(progress),
+                    None => return,
+                }
+            },
+            notification => notification,
+        };
+        let _ = tx.send(Notification::Server(notification)).await;
+    }
+
+    /// Check whether the client should receive a notification.
+    ///
+    /// Nothing is sent before initialization. After that:
+    /// - progress must carry the progress token of a request that is still in flight
+    /// - resource updates require an active subscription to the resource
+    /// - list change notifications require the server to advertise `listChanged`
+    /// - log messages require the server to advertise logging
+    pub async fn is_notification_supported(&self, notification: &ServerNotification) -> bool {
+        if self.initialized.lock().is_empty() {
+            return false;
+        }
+        match notification {
+            ServerNotification::Progress(progress) => {
+                self.cancellation_manager
+                    .is_progress_active(&progress.progress_token)
+                    .await
+            },
+            ServerNotification::ResourceUpdated(params) => !self
+                .subscription_manager
+                .read()
+                .await
+                .get_subscribers(&params.uri)
+                .await
+                .is_empty(),
+            ServerNotification::ToolsChanged => self
+                .capabilities
+                .tools
+                .as_ref()
+                .and_then(|tools| tools.list_changed)
+                .unwrap_or(false),
+            ServerNotification::PromptsChanged => self
+                .capabilities
+                .prompts
+                .as_ref()
+                .and_then(|prompts| prompts.list_changed)
+                .unwrap_or(false),
+            ServerNotification::ResourcesChanged => self
+                .capabilities
+                .resources
+                .as_ref()
+                .and_then(|resources| resources.list_changed)
+                .unwrap_or(false),
+            ServerNotification::LogMessage(_) => self.capabilities.logging.is_some(),
+            ServerNotification::RootsListChanged | ServerNotification::CapabilitiesChanged(_) => {
+                true
+            },
         }
     }
 
@@ -227,8 +656,10 @@ impl Server {
 
     /// Check if the server is initialized.
     ///
-    /// Returns true if the initialization handshake with a client has completed.
-    /// The server must be initialized before it can process most requests.
+    /// Returns true if the initialization handshake with the client of the current
+    /// connection, or of the session being handled, has completed and the client has
+    /// not disconnected since. The server must be initialized before it can process
+    /// most requests.
     ///
     /// # Examples
     ///
@@ -250,43 +681,8 @@ impl Server {
     /// # }
     /// ```
     pub async fn is_initialized(&self) -> bool {
-        *self.initialized.read().await
-    }
-    /// Create a new server builder.
-    ///
-    /// Returns a `ServerBuilder` for configuring and constructing a new MCP server.
-    /// The builder pattern allows you to set server information, capabilities,
-    /// and register handlers before building the final server instance.
-    ///
-    /// # Examples
-    ///
-    /// ```rust,no_run
-    /// use pmcp::{Server, ToolHandler};
-    /// use async_trait::async_trait;
-    /// use serde_json::Value;
-    ///
-    /// struct HelloTool;
-    ///
-    /// #[async_trait]
-    /// impl ToolHandler for HelloTool {
-    ///     async fn handle(&self, args: Value, _extra: pmcp::RequestHandlerExtra) -> pmcp::Result<Value> {
-    ///         Ok(serde_json::json!({"message": "Hello, World!"}))
-    ///     }
-    /// }
-    ///
-    /// # async fn example() -> pmcp::Result<()> {
-    /// let server = Server::builder()
-    ///     .name("greeting-server")
-    ///     .version("1.0.0")
-    ///     .tool("hello", HelloTool{})
-    ///     .build()?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    pub fn builder() -> ServerBuilder {
-        ServerBuilder::new()
+        self.initialized.lock().contains(&sessions::current_id())
     }
-
     /// Run the server with stdio transport.
     ///
     /// Starts the server using stdin/stdout for communication.
@@ -316,7 +712,7 @@ impl Server {
     ///     .tool("echo", EchoTool{})
     ///     .build()?;
     ///
-    /// // This will run indefinitely, handling client requests
+    /// // Handles client requests until the client disconnects
     /// server.run_stdio().await?;
     /// # Ok(())
     /// # }
@@ -380,109 +776,356 @@ impl Server {
     /// - The transport fails to initialize or operate
     /// - Communication with the client fails
     /// - The server encounters an unrecoverable error
-    pub async fn run<T: crate::shared::Transport + 'static>(mut self, transport: T) -> Result<()> {
-        let (notification_tx, notification_rx) = mpsc::channel(100);
+    pub async fn run<T: crate::shared::Transport + 'static>(self, transport: T) -> Result<()> {
+        self.run_with_shutdown(transport, CancellationToken::new())
+            .await
+    }
+
+    /// Run the server with a custom transport until `shutdown` is cancelled or the
+    /// client disconnects.
+    ///
+    /// Once cancelled, the server stops reading messages, finishes the requests it is
+    /// handling, sends the notifications still queued, closes the transport and
+    /// returns. The same happens when the transport stops delivering messages, e.g. at
+    /// the end of stdin.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::{Server, StdioTransport};
+    /// use tokio_util::sync::CancellationToken;
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let server = Server::builder()
+    ///     .name("stoppable-server")
+    ///     .version("1.0.0")
+    ///     .build()?;
+    ///
+    /// let shutdown = CancellationToken::new();
+    /// let on_signal = shutdown.clone();
+    /// tokio::spawn(async move {
+    ///     let _ = tokio::signal::ctrl_c().await;
+    ///     on_signal.cancel();
+    /// });
+    ///
+    /// server.run_with_shutdown(StdioTransport::new(), shutdown).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if closing the transport fails.
+    pub async fn run_with_shutdown<T: crate::shared::Transport + 'static>(
+        mut self,
+        transport: T,
+        shutdown: CancellationToken,
+    ) -> Result<()> {
+        let (notification_tx, notification_rx) = mpsc::channel(NOTIFICATION_CAPACITY);
         self.notification_tx = Some(notification_tx);
 
+        let mut transport = transport;
+        transport.set_max_json_depth(self.max_json_depth);
+        transport.set_utf8_mode(self.utf8);
+        let connection = Arc::new(connection::Connection::new(transport));
+        self.client_requests = Some(Arc::new(client_requests::ClientRequests::new(
+            connection.clone(),
+            self.client_capabilities.clone(),
+        )));
+
         let server = Arc::new(self);
-        let transport = Arc::new(RwLock::new(transport));
         let protocol = Arc::new(RwLock::new(Protocol::new(ProtocolOptions::default())));
 
-        Self::spawn_notification_handler(transport.clone(), notification_rx);
-        Self::spawn_message_handler(server.clone(), transport.clone(), protocol);
+        server.events.emit(|| ServerEvent::ClientConnected);
+        let drain = CancellationToken::new();
+        let notifications = Self::spawn_notification_handler(
+            connection.clone(),
+            notification_rx,
+            server.progress_backlog.clone(),
+            server.events.clone(),
+            drain.clone(),
+        );
+        let messages = Self::spawn_message_handler(
+            server.clone(),
+            connection.clone(),
+            protocol,
+            shutdown.clone(),
+        );
 
-        // Keep the main task alive
-        Self::run_main_loop().await
+        // The message loop ends on shutdown, or when the client disconnects, once the
+        // requests in flight are answered. Notifications they sent are drained too
+        if let Err(e) = messages.await {
+            Self::log_error(&format!("Message handler failed: {}", e)).await;
+        }
+        drain.cancel();
+        if let Err(e) = notifications.await {
+            Self::log_error(&format!("Notification handler failed: {}", e)).await;
+        }
+        let mut transport = connection.lock().await;
+        transport.close().await
     }
 
     /// Spawn task to handle outgoing notifications.
+    ///
+    /// Held-back progress is sent once the channel is empty, after any older progress
+    /// still queued for the same token. Once `drain` is cancelled, the task sends
+    /// what is queued and stops.
     fn spawn_notification_handler(
-        transport: Arc<RwLock<impl crate::shared::Transport + 'static>>,
+        connection: Arc<connection::Connection<impl crate::shared::Transport + 'static>>,
         mut notification_rx: mpsc::Receiver<Notification>,
-    ) {
+        progress_backlog: Arc<progress_backlog::ProgressBacklog>,
+        events: Arc<events::EventEmitter>,
+        drain: CancellationToken,
+    ) -> tokio::task::JoinHandle<()> {
         tokio::spawn(async move {
-            while let Some(notification) = notification_rx.recv().await {
-                if let Err(e) =
-                    Self::send_notification_through_transport(&transport, notification).await
-                {
-                    Self::log_error(&format!("Failed to send notification: {}", e)).await;
+            let mut draining = false;
+            while !draining {
+                let mut outgoing = Vec::new();
+                tokio::select! {
+                    received = notification_rx.recv() => match received {
+                        Some(notification) => outgoing.push(notification),
+                        None => break,
+                    },
+                    () = progress_backlog.held() => {},
+                    () = drain.cancelled() => {
+                        notification_rx.close();
+                        while let Some(notification) = notification_rx.recv().await {
+                            outgoing.push(notification);
+                        }
+                        draining = true;
+                    },
+                }
+                if notification_rx.is_empty() {
+                    outgoing.extend(progress_backlog.take().into_iter().map(|progress| {
+                        Notification::Server(ServerNotification::Progress(progress))
+                    }));
+                }
+                for notification in outgoing {
+                    let copy = events.is_subscribed().then(|| notification.clone());
+                    match connection
+                        .send(TransportMessage::Notification(notification))
+                        .await
+                    {
+                        Ok(()) => {
+                            if let Some(notification) = copy {
+                                events.emit(|| ServerEvent::NotificationSent(notification));
+                            }
+                        },
+                        Err(e) => {
+                            Self::log_error(&format!("Failed to send notification: {}", e)).await;
+                        },
+                    }
                 }
             }
-        });
+        })
     }
 
     /// Spawn task to handle incoming messages.
     fn spawn_message_handler(
         server: Arc<Self>,
-        transport: Arc<RwLock<impl crate::shared::Transport + 'static>>,
+        connection: Arc<connection::Connection<impl crate::shared::Transport + 'static>>,
         _protocol: Arc<RwLock<Protocol>>,
-    ) {
+        shutdown: CancellationToken,
+    ) -> tokio::task::JoinHandle<()> {
         tokio::spawn(async move {
-            loop {
-                let message = match Self::receive_message_from_transport(&transport).await {
-                    Ok(msg) => msg,
-                    Err(e) => {
-                        Self::log_error(&format!("Transport receive error: {}", e)).await;
-                        break;
-                    },
-                };
-
-                if let Err(e) = Self::handle_transport_message(&server, &transport, message).await {
-                    Self::log_error(&format!("Message handling error: {}", e)).await;
-                    break;
-                }
-            }
-        });
+            Self::process_transport_messages(&server, &connection, &shutdown).await;
+        })
     }
 
-    /// Send a notification through the transport.
-    async fn send_notification_through_transport(
-        transport: &Arc<RwLock<impl crate::shared::Transport>>,
-        notification: Notification,
-    ) -> Result<()> {
-        let mut t = transport.write().await;
-        t.send(TransportMessage::Notification(notification)).await
-    }
+    /// Receive and handle messages until the transport fails or `shutdown` is
+    /// cancelled.
+    ///
+    /// Each request is handled in its own task, so that the loop keeps reading pings,
+    /// cancellations and answers to the server's own requests while tools run. The
+    /// priority dispatcher, if configured, bounds how many of them run at once.
+    /// `initialize` is handled before reading on, so that later messages see its
+    /// outcome. Requests still in flight when the loop ends are answered before it
+    /// returns.
+    ///
+    /// Frames that cannot be parsed do not end the session: requests whose ID could be
+    /// recovered are answered with a parse error, anything else is logged and skipped.
+    async fn process_transport_messages(
+        server: &Arc<Self>,
+        connection: &Arc<connection::Connection<impl crate::shared::Transport + 'static>>,
+        shutdown: &CancellationToken,
+    ) {
+        let mut requests = tokio::task::JoinSet::new();
+        loop {
+            while requests.try_join_next().is_some() {}
+            let received = tokio::select! {
+                () = shutdown.cancelled() => break,
+                received = connection.receive() => received,
+            };
+            let message = match received {
+                Ok(msg) => msg,
+                Err(Error::Transport(TransportError::MalformedRequest { id, message })) => {
+                    Self::log_warning(&format!("Malformed request {}: {}", id, message)).await;
+                    let response = JSONRPCResponse {
+                        jsonrpc: "2.0".to_string(),
+                        id,
+                        payload: crate::types::jsonrpc::ResponsePayload::Error(
+                            crate::types::jsonrpc::JSONRPCError {
+                                code: ErrorCode::PARSE_ERROR.0,
+                                message,
+                                data: None,
+                            },
+                        ),
+                    };
+                    let sent = connection.send(TransportMessage::Response(response)).await;
+                    if let Err(e) = sent {
+                        Self::log_error(&format!("Failed to send parse error: {}", e)).await;
+                        break;
+                    }
+                    continue;
+                },
+                Err(
+                    e @ (Error::Serialization(_)
+                    | Error::Protocol {
+                        code: ErrorCode::PARSE_ERROR,
+                        ..
+                    }
+                    | Error::Transport(
+                        TransportError::InvalidMessage(_)
+                        | TransportError::Serialization(_)
+                        | TransportError::Deserialization(_),
+                    )),
+                ) => {
+                    Self::log_warning(&format!("Dropping unparseable message: {}", e)).await;
+                    continue;
+                },
+                Err(e) => {
+                    Self::log_error(&format!("Transport receive error: {}", e)).await;
+                    break;
+                },
+            };
 
-    /// Receive a message from the transport.
-    async fn receive_message_from_transport(
-        transport: &Arc<RwLock<impl crate::shared::Transport>>,
-    ) -> Result<TransportMessage> {
-        let mut t = transport.write().await;
-        t.receive().await
+            if let Err(e) =
+                Self::handle_transport_message(server, connection, message, &mut requests).await
+            {
+                Self::log_error(&format!("Message handling error: {}", e)).await;
+                break;
+            }
+        }
+        // No more answers from the client will be read, so requests waiting on it fail
+        if let Some(client) = &server.client_requests {
+            client.close();
+        }
+        while let Some(finished) = requests.join_next().await {
+            if let Err(e) = finished {
+                Self::log_error(&format!("Request handler failed: {}", e)).await;
+            }
+        }
+        // The connection is gone, so drop what was stored for it
+        server.initialized.lock().remove(sessions::CONNECTION_ID);
+        server.connection_contexts.clear();
+        server.argument_streams.end(sessions::CONNECTION_ID);
+        server.client_roots.remove(sessions::CONNECTION_ID).await;
+        server.end_subscriptions(sessions::CONNECTION_ID).await;
+        server.events.emit(|| ServerEvent::Shutdown);
     }
 
-    /// Handle a transport message.
-    async fn handle_transport_message(
+    /// Handle a transport message, spawning requests into `requests`.
+    async fn handle_transport_message<T: crate::shared::Transport + 'static>(
         server: &Arc<Self>,
-        transport: &Arc<RwLock<impl crate::shared::Transport>>,
+        connection: &Arc<connection::Connection<T>>,
         message: TransportMessage,
+        requests: &mut tokio::task::JoinSet<()>,
     ) -> Result<()> {
         match message {
             TransportMessage::Request { id, request } => {
-                Self::handle_request_message(server, transport, id, request).await
+                let initialize = matches!(
+                    &request,
+                    Request::Client(req) if matches!(**req, ClientRequest::Initialize(_))
+                );
+                if initialize {
+                    return Self::handle_request_message(server, connection, id, request).await;
+                }
+                let (server, connection) = (server.clone(), connection.clone());
+                requests.spawn(async move {
+                    if let Err(e) =
+                        Self::handle_request_message(&server, &connection, id, request).await
+                    {
+                        Self::log_error(&format!("Failed to send response: {}", e)).await;
+                    }
+                });
+                Ok(())
             },
-            TransportMessage::Response(_) => {
-                Self::log_warning("Server received unexpected response message").await;
+            TransportMessage::Response(response) => {
+                let delivered = server
+                    .client_requests
+                    .as_ref()
+                    .is_some_and(|client| client.deliver(response));
+                if !delivered {
+                    Self::log_warning("Server received unexpected response message").await;
+                }
                 Ok(())
             },
-            TransportMessage::Notification(_) => {
+            TransportMessage::Notification(notification) => {
                 Self::log_debug("Server received notification").await;
+                let roots_changed = matches!(
+                    notification,
+                    Notification::Client(
+                        crate::types::ClientNotification::Initialized
+                            | crate::types::ClientNotification::RootsListChanged
+                    )
+                );
+                server.handle_notification(notification).await;
+                if roots_changed {
+                    let server = server.clone();
+                    requests.spawn(async move { server.query_client_roots().await });
+                }
                 Ok(())
             },
         }
     }
 
-    /// Handle a request message.
-    async fn handle_request_message(
-        server: &Arc<Self>,
-        transport: &Arc<RwLock<impl crate::shared::Transport>>,
+    /// Handle a notification sent by the client.
+    async fn handle_notification(&self, notification: Notification) {
+        match notification {
+            Notification::Cancelled(cancelled)
+            | Notification::Client(crate::types::ClientNotification::Cancelled(cancelled)) => {
+                if let RequestId::String(stream_id) = &cancelled.request_id {
+                    self.argument_streams.discard(stream_id);
+                }
+                self.cancellation_manager
+                    .handle_cancelled_notification(cancelled)
+                    .await;
+            },
+            Notification::Client(crate::types::ClientNotification::RootsListChanged) => {
+                self.client_roots.remove(&sessions::current_id()).await;
+            },
+            Notification::Client(crate::types::ClientNotification::ArgumentChunk(chunk)) => {
+                self.argument_streams.append(chunk);
+            },
+            _ => {},
+        }
+    }
+
+    /// Query the client's roots, if it has any, while the connection is being served.
+    async fn query_client_roots(&self) {
+        let supports_roots = self
+            .client_capabilities
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|caps| caps.roots.is_some());
+        if !supports_roots {
+            return;
+        }
+        if let Err(e) = self.fetch_client_roots(&sessions::current_id()).await {
+            Self::log_warning(&format!("Failed to list client roots: {}", e)).await;
+        }
+    }
+
+    /// Handle a request message.
+    async fn handle_request_message(
+        server: &Arc<Self>,
+        connection: &connection::Connection<impl crate::shared::Transport>,
         id: RequestId,
         request: Request,
     ) -> Result<()> {
         let response = server.handle_request(id, request).await;
-        let mut t = transport.write().await;
-        t.send(TransportMessage::Response(response)).await
+        connection.send(TransportMessage::Response(response)).await
     }
 
     /// Log an error message.
@@ -500,14 +1143,77 @@ impl Server {
         crate::log(crate::types::protocol::LogLevel::Debug, message, None).await;
     }
 
-    /// Run the main event loop.
-    async fn run_main_loop() -> Result<()> {
-        loop {
-            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+    async fn handle_request(&self, id: RequestId, request: Request) -> JSONRPCResponse {
+        let method = match &request {
+            Request::Client(req) => req.method(),
+            Request::Server(_) => "server request",
+        };
+        self.events.emit(|| ServerEvent::RequestStarted {
+            id: id.clone(),
+            method: method.to_string(),
+        });
+        let started = std::time::Instant::now();
+        let response = self.dispatch_request(id, request).await;
+        let failed = matches!(
+            response.payload,
+            crate::types::jsonrpc::ResponsePayload::Error(_)
+        );
+        self.request_counters.record(method, failed);
+        self.events.emit(|| {
+            let (id, method, duration) =
+                (response.id.clone(), method.to_string(), started.elapsed());
+            match &response.payload {
+                crate::types::jsonrpc::ResponsePayload::Error(error) => {
+                    ServerEvent::RequestFailed {
+                        id,
+                        method,
+                        duration,
+                        code: error.code,
+                        message: error.message.clone(),
+                    }
+                },
+                crate::types::jsonrpc::ResponsePayload::Result(_) => {
+                    ServerEvent::RequestCompleted {
+                        id,
+                        method,
+                        duration,
+                    }
+                },
+            }
+        });
+        response
+    }
+
+    /// Route a request, running it and its response through the middleware.
+    async fn dispatch_request(&self, id: RequestId, request: Request) -> JSONRPCResponse {
+        let Request::Client(req) = &request else {
+            return self.route_request(id, request).await;
+        };
+        let method = req.method();
+        let context = self.connection_contexts.current();
+        let mut response = None;
+        for middleware in &self.middleware {
+            if let Err(e) = middleware.on_request(req, &context).await {
+                response = Some(Self::create_response(id.clone(), method, Err(e)));
+                break;
+            }
+        }
+        let mut response = match response {
+            Some(response) => response,
+            None => self.route_request(id, request).await,
+        };
+        for middleware in &self.middleware {
+            if let Err(e) = middleware
+                .on_response(method, &mut response, &context)
+                .await
+            {
+                return Self::create_response(response.id, method, Err(e));
+            }
         }
+        response
     }
 
-    async fn handle_request(&self, id: RequestId, request: Request) -> JSONRPCResponse {
+    async fn route_request(&self, id: RequestId, request: Request) -> JSONRPCResponse {
         match request {
             Request::Client(ref boxed_req)
                 if matches!(**boxed_req, ClientRequest::Initialize(_)) =>
@@ -515,15 +1221,25 @@ impl Server {
                 let ClientRequest::Initialize(init_req) = boxed_req.as_ref() else {
                     unreachable!("Pattern matched for Initialize");
                 };
+                if !self.initialized.lock().insert(sessions::current_id()) {
+                    return Self::create_response(
+                        id,
+                        "initialize",
+                        Err(Error::invalid_request("already initialized")),
+                    );
+                }
                 // Store client capabilities
                 *self.client_capabilities.write().await = Some(init_req.capabilities.clone());
-                *self.initialized.write().await = true;
+                self.events.emit(|| ServerEvent::ClientInitialized {
+                    client_info: init_req.client_info.clone(),
+                    protocol_version: init_req.protocol_version.clone(),
+                });
 
                 let result = InitializeResult {
                     protocol_version: ProtocolVersion("2024-11-05".to_string()),
                     capabilities: self.capabilities.clone(),
                     server_info: self.info.clone(),
-                    instructions: None,
+                    instructions: self.instructions.clone(),
                 };
                 JSONRPCResponse {
                     jsonrpc: "2.0".to_string(),
@@ -533,7 +1249,26 @@ impl Server {
                     ),
                 }
             },
-            Request::Client(boxed_req) => self.handle_client_request(id, *boxed_req).await,
+            Request::Client(boxed_req) => {
+                if !matches!(*boxed_req, ClientRequest::Ping) && !self.is_initialized().await {
+                    return Self::create_response(
+                        id,
+                        boxed_req.method(),
+                        Err(Error::protocol(
+                            ErrorCode::INVALID_REQUEST,
+                            "server not initialized",
+                        )),
+                    );
+                }
+                let _permit = match &self.dispatcher {
+                    Some(dispatcher) => match dispatcher.acquire(boxed_req.method()).await {
+                        Ok(permit) => permit,
+                        Err(e) => return Self::create_response(id, boxed_req.method(), Err(e)),
+                    },
+                    None => None,
+                };
+                self.handle_client_request(id, *boxed_req).await
+            },
             Request::Server(_) => JSONRPCResponse {
                 jsonrpc: "2.0".to_string(),
                 id,
@@ -553,8 +1288,89 @@ impl Server {
         id: RequestId,
         request: ClientRequest,
     ) -> JSONRPCResponse {
-        let result = self.process_client_request(id.clone(), request).await;
-        Self::create_response(id, result)
+        let method = request.method();
+        let started = self
+            .slow_request_threshold
+            .map(|threshold| (threshold, std::time::Instant::now()));
+        let result = self
+            .with_request_timeout(&id, self.process_client_request(id.clone(), request))
+            .await;
+        if let Some(token) = self.cancellation_manager.progress_token(&id).await {
+            // Held-back progress would reach the client after the response
+            self.progress_backlog.discard(&token);
+        }
+        self.cancellation_manager
+            .remove_token(&id.to_string())
+            .await;
+        if let Some((threshold, started)) = started {
+            Self::log_if_slow(&id, method, started.elapsed(), threshold);
+        }
+        Self::create_response(id, method, result)
+    }
+
+    /// Wait for `handler` to process request `id`, failing the request once the
+    /// request timeout passes.
+    ///
+    /// A timed out request is cancelled, then its handler is polled a little longer so
+    /// that one watching its cancellation token can clean up.
+    async fn with_request_timeout(
+        &self,
+        id: &RequestId,
+        handler: impl std::future::Future<Output = Result<Value>>,
+    ) -> Result<Value> {
+        let Some(timeout) = self.request_timeout else {
+            return handler.await;
+        };
+        tokio::pin!(handler);
+        if let Ok(result) = tokio::time::timeout(timeout, &mut handler).await {
+            return result;
+        }
+        self.cancellation_manager
+            .cancel_timed_out(id, timeout)
+            .await;
+        if tokio::time::timeout(TIMEOUT_CLEANUP_GRACE, handler)
+            .await
+            .is_err()
+        {
+            tracing::warn!(
+                "Handler of request {} kept running {:?} after timing out",
+                id,
+                TIMEOUT_CLEANUP_GRACE
+            );
+        }
+        Err(Error::Timeout(
+            u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX),
+        ))
+    }
+
+    /// Log a request that took longer than `threshold` to handle.
+    fn log_if_slow(
+        id: &RequestId,
+        method: &str,
+        duration: std::time::Duration,
+        threshold: std::time::Duration,
+    ) {
+        if duration <= threshold {
+            return;
+        }
+        let logger = match crate::shared::context::RequestContext::current() {
+            Some(context) => {
+                crate::shared::CorrelatedLogger::from_context("slow_request", &context)
+            },
+            None => crate::shared::CorrelatedLogger::from_context(
+                "slow_request",
+                &crate::shared::context::RequestContext::new(id.clone()),
+            ),
+        };
+        logger.in_scope(|| {
+            tracing::warn!(
+                method,
+                request_id = %id,
+                duration_ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+                threshold_ms = u64::try_from(threshold.as_millis()).unwrap_or(u64::MAX),
+                "Slow request"
+            );
+        });
     }
 
     /// Process a client request and return the result.
@@ -575,13 +1391,23 @@ impl Server {
             ClientRequest::ListResources(req) => self.handle_list_resources(request_id, req).await,
             ClientRequest::ReadResource(req) => self.handle_read_resource(request_id, req).await,
             ClientRequest::ListResourceTemplates(req) => {
-                Self::handle_list_resource_templates(self, req)
+                self.handle_list_resource_templates(request_id, req).await
+            },
+            ClientRequest::Complete(req) => self.handle_complete(request_id, req).await,
+            // Subscriptions belong to the connection, or session, they were made on
+            ClientRequest::Subscribe(req) => {
+                self.subscribe_resource(req.uri, sessions::current_id())
+                    .await?;
+                Ok(serde_json::json!({}))
+            },
+            ClientRequest::Unsubscribe(req) => {
+                self.unsubscribe_resource(req.uri, sessions::current_id())
+                    .await?;
+                Ok(serde_json::json!({}))
+            },
+            ClientRequest::SetLoggingLevel { level: _ } | ClientRequest::Ping => {
+                Ok(serde_json::json!({}))
             },
-            ClientRequest::Subscribe(_)
-            | ClientRequest::Unsubscribe(_)
-            | ClientRequest::Complete(_)
-            | ClientRequest::SetLoggingLevel { level: _ }
-            | ClientRequest::Ping => Ok(serde_json::json!({})),
             ClientRequest::CreateMessage(req) => self.handle_create_message(request_id, req).await,
             ClientRequest::ElicitInputResponse(response) => {
                 // Handle elicitation response if we have a manager
@@ -593,83 +1419,267 @@ impl Server {
         }
     }
 
-    /// Create a JSON-RPC response from a result.
-    fn create_response(id: RequestId, result: Result<serde_json::Value>) -> JSONRPCResponse {
+    /// Build the response for a request.
+    ///
+    /// Errors carry the JSON-RPC code for their [`Error`] variant, and the failing
+    /// method is added to their `data` as `{"method": ...}` when the handler gave no
+    /// `data` or object `data`, whose fields are kept. Any other `data` is sent
+    /// untouched, as clients may rely on its shape.
+    fn create_response(
+        id: RequestId,
+        method: &str,
+        result: Result<serde_json::Value>,
+    ) -> JSONRPCResponse {
         match result {
             Ok(value) => JSONRPCResponse {
                 jsonrpc: "2.0".to_string(),
                 id,
                 payload: crate::types::jsonrpc::ResponsePayload::Result(value),
             },
-            Err(e) => JSONRPCResponse {
-                jsonrpc: "2.0".to_string(),
-                id,
-                payload: crate::types::jsonrpc::ResponsePayload::Error(
-                    crate::types::jsonrpc::JSONRPCError {
-                        code: -32603,
-                        message: e.to_string(),
-                        data: None,
+            Err(e) => {
+                let mut error = crate::types::jsonrpc::JSONRPCError::from(e);
+                match &mut error.data {
+                    Some(Value::Object(data)) => {
+                        data.entry("method")
+                            .or_insert_with(|| serde_json::json!(method));
                     },
-                ),
+                    Some(_) => {},
+                    None => error.data = Some(serde_json::json!({"method": method})),
+                }
+                JSONRPCResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id,
+                    payload: crate::types::jsonrpc::ResponsePayload::Error(error),
+                }
             },
         }
     }
 
-    fn handle_list_tools(&self, _req: ListToolsRequest) -> Result<Value> {
-        let tools = self
+    fn handle_list_tools(&self, req: ListToolsRequest) -> Result<Value> {
+        let mut tools = self
             .tools
-            .keys()
-            .map(|name| crate::types::ToolInfo {
-                name: name.clone(),
-                description: None,
-                input_schema: serde_json::json!({}),
+            .iter()
+            .map(|(name, handler)| {
+                let mut info = handler
+                    .metadata()
+                    .unwrap_or_else(|| crate::types::ToolInfo {
+                        name: String::new(),
+                        description: None,
+                        input_schema: serde_json::json!({}),
+                        category: None,
+                        tags: Vec::new(),
+                        output_schema: None,
+                    });
+                // The registered name is what clients must call
+                info.name.clone_from(name);
+                info
             })
             .collect::<Vec<_>>();
+        // Sorted so that cursors point at the same entries across calls
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        let (tools, next_cursor) = paginate(tools, req.cursor, self.page_size)?;
 
         Ok(serde_json::to_value(ListToolsResult {
             tools,
-            next_cursor: None,
+            next_cursor,
         })?)
     }
 
-    async fn handle_call_tool(&self, request_id: RequestId, req: CallToolRequest) -> Result<Value> {
+    /// Register a request and build the handler context for it.
+    ///
+    /// The `progress_token` the client sent with the request is remembered while
+    /// it runs, see [`is_notification_supported`](Self::is_notification_supported).
+    async fn create_request_extra(
+        &self,
+        request_id: &RequestId,
+        progress_token: Option<crate::types::ProgressToken>,
+    ) -> cancellation::RequestHandlerExtra {
+        if let Some(progress_token) = &progress_token {
+            self.cancellation_manager
+                .track_progress(request_id, progress_token.clone())
+                .await;
+        }
+        self.cancellation_manager
+            .create_request_extra(request_id)
+            .await
+            .with_progress_token(progress_token)
+            .with_client(self.client_requests.clone())
+            .with_notifications(self.notification_tx.clone())
+            .with_state(self.state.clone())
+            .with_context(self.connection_contexts.current())
+    }
+
+    async fn handle_call_tool(
+        &self,
+        request_id: RequestId,
+        mut req: CallToolRequest,
+    ) -> Result<Value> {
+        if let Some(stream) = req
+            .meta
+            .as_ref()
+            .and_then(|meta| meta.argument_stream.as_ref())
+        {
+            req.arguments = self.argument_streams.finish(stream)?;
+        }
+        let Some(auditor) = &self.auditor else {
+            return self.call_tool(request_id, req).await;
+        };
+
+        let timestamp = chrono::Utc::now();
+        let start = std::time::Instant::now();
+        let connection_id = crate::shared::context::RequestContext::current()
+            .and_then(|ctx| ctx.session_id.clone());
+        let tool_name = req.name.clone();
+        let arguments = auditor.redact(&req.arguments);
+        let result = self.call_tool(request_id, req).await;
+        let (result_summary, error) = audit::outcome(&result);
+        auditor
+            .record(audit::AuditEntry {
+                timestamp,
+                connection_id,
+                tool_name,
+                arguments,
+                result_summary,
+                duration: start.elapsed(),
+                error,
+            })
+            .await;
+        result
+    }
+
+    async fn call_tool(&self, request_id: RequestId, req: CallToolRequest) -> Result<Value> {
         let handler = self
             .tools
             .get(&req.name)
             .ok_or_else(|| Error::not_found(format!("Tool '{}' not found", req.name)))?;
 
-        let cancellation_token = self
-            .cancellation_manager
-            .get_token(&request_id.to_string())
+        let metadata = if self.apply_schema_defaults || self.validate_tool_output {
+            handler.metadata()
+        } else {
+            None
+        };
+
+        let mut arguments = req.arguments;
+        if self.apply_schema_defaults {
+            if let Some(info) = &metadata {
+                apply_defaults(&info.input_schema, &mut arguments);
+            }
+        }
+
+        let deadline = req
+            .meta
+            .as_ref()
+            .and_then(|meta| meta.deadline)
+            .map(deadline_instant);
+        let extra = self
+            .create_request_extra(&request_id, progress_token(req.meta.as_ref()))
             .await
-            .unwrap_or_else(tokio_util::sync::CancellationToken::new);
-        let extra = crate::server::cancellation::RequestHandlerExtra::new(
-            request_id.to_string(),
-            cancellation_token,
-        );
-        let result = handler.handle(req.arguments, extra).await?;
+            .with_deadline(deadline);
+        let response_extra = extra.clone();
+        let deadline_timer = match deadline {
+            Some(deadline) => {
+                self.cancellation_manager
+                    .cancel_at_deadline(&request_id, deadline)
+                    .await
+            },
+            None => None,
+        };
+        if response_extra.is_cancelled() {
+            return Err(response_extra.cancellation_error());
+        }
+
+        let handle = {
+            let cancel_extra = response_extra.clone();
+            let handle = handler.handle(arguments, extra);
+            async move {
+                tokio::pin!(handle);
+                let result = tokio::select! {
+                    result = &mut handle => result,
+                    () = cancel_extra.cancelled() => match cancel_extra.take_partial_result() {
+                        Some(partial) => Ok(partial),
+                        // Abandon the work once the deadline cancels the request
+                        None if deadline_timer.is_some() => Err(cancel_extra.cancellation_error()),
+                        None => handle.await,
+                    },
+                };
+                if let Some(timer) = deadline_timer {
+                    timer.abort();
+                }
+                result
+            }
+        };
+        let result = if self.catch_tool_panics {
+            let outcome = std::panic::AssertUnwindSafe(handle).catch_unwind().await;
+            match outcome {
+                Ok(result) => result?,
+                Err(panic) => {
+                    let message = format!(
+                        "Internal error: tool '{}' panicked: {}",
+                        req.name,
+                        panic_message(panic.as_ref())
+                    );
+                    Self::log_error(&message).await;
+                    return Ok(serde_json::to_value(CallToolResult {
+                        content: vec![crate::types::Content::Text { text: message }],
+                        is_error: true,
+                        meta: None,
+                    })?);
+                },
+            }
+        } else {
+            handle.await?
+        };
+
+        if let Some(mut call_result) = tool_result::as_call_tool_result(&result) {
+            if call_result.meta.is_none() {
+                call_result.meta = response_extra.response_meta();
+            }
+            return Ok(serde_json::to_value(call_result)?);
+        }
+        if self.validate_tool_output {
+            if let Some(output_schema) = metadata.as_ref().and_then(|m| m.output_schema.as_ref()) {
+                crate::utils::validation::validate_json_schema(output_schema, &result).map_err(
+                    |e| {
+                        Error::internal(format!(
+                            "Tool '{}' returned a result that does not match its output schema: {}",
+                            req.name, e
+                        ))
+                    },
+                )?;
+            }
+        }
         Ok(serde_json::to_value(CallToolResult {
             content: vec![crate::types::Content::Text {
                 text: result.to_string(),
             }],
             is_error: false,
+            meta: response_extra.response_meta(),
         })?)
     }
 
-    fn handle_list_prompts(&self, _req: ListPromptsRequest) -> Result<Value> {
-        let prompts = self
+    fn handle_list_prompts(&self, req: ListPromptsRequest) -> Result<Value> {
+        let mut prompts = self
             .prompts
-            .keys()
-            .map(|name| crate::types::PromptInfo {
-                name: name.clone(),
-                description: None,
-                arguments: None,
+            .iter()
+            .map(|(name, handler)| {
+                let mut info = handler
+                    .metadata()
+                    .unwrap_or_else(|| crate::types::PromptInfo {
+                        name: String::new(),
+                        description: None,
+                        arguments: None,
+                    });
+                // The registered name is what clients must request
+                info.name.clone_from(name);
+                info
             })
             .collect::<Vec<_>>();
+        prompts.sort_by(|a, b| a.name.cmp(&b.name));
+        let (prompts, next_cursor) = paginate(prompts, req.cursor, self.page_size)?;
 
         Ok(serde_json::to_value(ListPromptsResult {
             prompts,
-            next_cursor: None,
+            next_cursor,
         })?)
     }
 
@@ -683,34 +1693,89 @@ impl Server {
             .get(&req.name)
             .ok_or_else(|| Error::not_found(format!("Prompt '{}' not found", req.name)))?;
 
-        let cancellation_token = self
-            .cancellation_manager
-            .get_token(&request_id.to_string())
-            .await
-            .unwrap_or_else(tokio_util::sync::CancellationToken::new);
-        let extra = crate::server::cancellation::RequestHandlerExtra::new(
-            request_id.to_string(),
-            cancellation_token,
-        );
+        let extra = self
+            .create_request_extra(&request_id, progress_token(req.meta.as_ref()))
+            .await;
         let result = handler.handle(req.arguments, extra).await?;
         Ok(serde_json::to_value(result)?)
     }
 
+    /// Resolve what a completion request refers to.
+    ///
+    /// Prompt references must name a registered prompt, and the argument must be
+    /// one the prompt declares (when it declares any).
+    fn resolve_completion_reference(
+        &self,
+        req: &crate::types::CompleteRequest,
+    ) -> Result<CompletionContext> {
+        match &req.r#ref {
+            crate::types::CompletionReference::Prompt { name } => {
+                let handler = self.prompts.get(name).ok_or_else(|| {
+                    Error::invalid_params(format!(
+                        "Unknown prompt '{}' in completion reference",
+                        name
+                    ))
+                })?;
+                let mut info = handler
+                    .metadata()
+                    .unwrap_or_else(|| crate::types::PromptInfo {
+                        name: String::new(),
+                        description: None,
+                        arguments: None,
+                    });
+                info.name.clone_from(name);
+                if let Some(arguments) = &info.arguments {
+                    if !arguments.iter().any(|arg| arg.name == req.argument.name) {
+                        return Err(Error::invalid_params(format!(
+                            "Prompt '{}' has no argument '{}'",
+                            name, req.argument.name
+                        )));
+                    }
+                }
+                Ok(CompletionContext::Prompt(info))
+            },
+            crate::types::CompletionReference::Resource { uri } => {
+                Ok(CompletionContext::Resource { uri: uri.clone() })
+            },
+        }
+    }
+
+    async fn handle_complete(
+        &self,
+        request_id: RequestId,
+        req: crate::types::CompleteRequest,
+    ) -> Result<Value> {
+        let context = self.resolve_completion_reference(&req)?;
+        let completion = match &self.completions {
+            Some(handler) => {
+                let extra = self
+                    .create_request_extra(&request_id, progress_token(req.meta.as_ref()))
+                    .await;
+                handler
+                    .complete(context, req.argument, extra)
+                    .await?
+                    .truncated(crate::types::CompletionResult::MAX_VALUES)
+            },
+            None => crate::types::CompletionResult {
+                values: Vec::new(),
+                total: None,
+                has_more: false,
+            },
+        };
+        Ok(serde_json::to_value(crate::types::CompleteResult {
+            completion,
+        })?)
+    }
+
     async fn handle_list_resources(
         &self,
         request_id: RequestId,
         req: ListResourcesRequest,
     ) -> Result<Value> {
         if let Some(handler) = &self.resources {
-            let cancellation_token = self
-                .cancellation_manager
-                .get_token(&request_id.to_string())
-                .await
-                .unwrap_or_else(tokio_util::sync::CancellationToken::new);
-            let extra = crate::server::cancellation::RequestHandlerExtra::new(
-                request_id.to_string(),
-                cancellation_token,
-            );
+            let extra = self
+                .create_request_extra(&request_id, progress_token(req.meta.as_ref()))
+                .await;
             let result = handler.list(req.cursor, extra).await?;
             Ok(serde_json::to_value(result)?)
         } else {
@@ -731,25 +1796,38 @@ impl Server {
             .as_ref()
             .ok_or_else(|| Error::not_found("No resource handler configured".to_string()))?;
 
-        let cancellation_token = self
-            .cancellation_manager
-            .get_token(&request_id.to_string())
-            .await
-            .unwrap_or_else(tokio_util::sync::CancellationToken::new);
-        let extra = crate::server::cancellation::RequestHandlerExtra::new(
-            request_id.to_string(),
-            cancellation_token,
-        );
-        let result = handler.read(&req.uri, extra).await?;
+        let extra = self
+            .create_request_extra(&request_id, progress_token(req.meta.as_ref()))
+            .await;
+        let known_hash = req.meta.and_then(|meta| meta.if_none_match);
+        let result = match known_hash {
+            Some(known_hash) => {
+                handler
+                    .read_if_changed(&req.uri, &known_hash, extra)
+                    .await?
+            },
+            None => handler.read(&req.uri, extra).await?,
+        };
         Ok(serde_json::to_value(result)?)
     }
 
-    #[allow(clippy::unused_self)]
-    fn handle_list_resource_templates(&self, _req: ListResourceTemplatesRequest) -> Result<Value> {
-        Ok(serde_json::to_value(ListResourceTemplatesResult {
-            resource_templates: vec![],
-            next_cursor: None,
-        })?)
+    async fn handle_list_resource_templates(
+        &self,
+        request_id: RequestId,
+        req: ListResourceTemplatesRequest,
+    ) -> Result<Value> {
+        if let Some(handler) = &self.resources {
+            let extra = self
+                .create_request_extra(&request_id, progress_token(req.meta.as_ref()))
+                .await;
+            let result = handler.list_templates(req.cursor, extra).await?;
+            Ok(serde_json::to_value(result)?)
+        } else {
+            Ok(serde_json::to_value(ListResourceTemplatesResult {
+                resource_templates: vec![],
+                next_cursor: None,
+            })?)
+        }
     }
 
     async fn handle_create_message(
@@ -762,15 +1840,7 @@ impl Server {
             .as_ref()
             .ok_or_else(|| Error::not_found("No sampling handler configured".to_string()))?;
 
-        let cancellation_token = self
-            .cancellation_manager
-            .get_token(&request_id.to_string())
-            .await
-            .unwrap_or_else(tokio_util::sync::CancellationToken::new);
-        let extra = crate::server::cancellation::RequestHandlerExtra::new(
-            request_id.to_string(),
-            cancellation_token,
-        );
+        let extra = self.create_request_extra(&request_id, None).await;
         let result = handler.create_message(req, extra).await?;
         Ok(serde_json::to_value(result)?)
     }
@@ -782,6 +1852,9 @@ impl Server {
     /// resource handlers that need to expose filesystem access or other
     /// URI-based resources.
     ///
+    /// These server-announced roots are unrelated to the roots a client
+    /// exposes; use [`Server::get_client_roots`] for those.
+    ///
     /// # Arguments
     ///
     /// * `uri` - The root URI to register (e.g., `file:///home/user/project`)
@@ -834,7 +1907,8 @@ impl Server {
     ///
     /// Returns a list of all currently registered root URIs and their
     /// associated names. Roots are directories or URIs that the server
-    /// has announced access to.
+    /// has announced access to, not the client's roots (see
+    /// [`Server::get_client_roots`]).
     ///
     /// # Returns
     ///
@@ -866,6 +1940,36 @@ impl Server {
         roots_manager.get_roots().await
     }
 
+    /// Ask the connected client for its roots and remember them for the connection.
+    ///
+    /// Sends `roots/list` to the client and waits for its answer. The server does this
+    /// itself once the client has initialized and whenever it sends
+    /// `notifications/roots/list_changed`, so calling it is only needed to retry after a
+    /// failure. Fails if the server is not running or the client did not advertise the
+    /// `roots` capability.
+    pub async fn refresh_client_roots(&self) -> Result<Vec<roots::Root>> {
+        self.fetch_client_roots(&sessions::current_id()).await
+    }
+
+    /// Query the client for its roots and cache them for connection or session `owner`.
+    async fn fetch_client_roots(&self, owner: &str) -> Result<Vec<roots::Root>> {
+        let client = self
+            .client_requests
+            .as_ref()
+            .ok_or_else(|| Error::InvalidState("Not connected to a client".to_string()))?;
+        let roots = client.list_roots(&CancellationToken::new()).await?.roots;
+        self.client_roots.set(owner, roots.clone()).await;
+        Ok(roots)
+    }
+
+    /// Get the roots a client declared, as of the last time the server asked.
+    ///
+    /// `connection_id` is the session ID on transports with sessions. Returns an empty
+    /// list if the client has not been queried, or its roots changed since.
+    pub async fn get_client_roots(&self, connection_id: &str) -> Vec<roots::Root> {
+        self.client_roots.get(connection_id).await
+    }
+
     /// Subscribe a client to resource updates.
     ///
     /// This method allows the server to track which clients are interested
@@ -877,6 +1981,13 @@ impl Server {
     /// * `uri` - The resource URI to subscribe to
     /// * `client_id` - Identifier for the subscribing client
     ///
+    /// # Errors
+    ///
+    /// Returns a protocol error with
+    /// [`ErrorCode::SUBSCRIPTION_LIMIT_REACHED`](crate::error::ErrorCode::SUBSCRIPTION_LIMIT_REACHED)
+    /// if the client already holds as many subscriptions as
+    /// [`ServerBuilder::max_subscriptions_per_client`] allows.
+    ///
     /// # Examples
     ///
     /// ```rust,no_run
@@ -1001,6 +2112,38 @@ impl Server {
     ///
     /// The number of subscribers that were notified.
     pub async fn notify_resource_updated(&self, uri: String) -> Result<usize> {
+        self.notify_resource_changed(uri, ChangeKind::Full).await
+    }
+
+    /// Notify subscribers that a resource has changed, describing how.
+    ///
+    /// Sending a [`ChangeKind::Patch`] lets clients update a cached copy of a large
+    /// resource without re-reading it. [`ChangeKind::Full`] behaves like
+    /// [`Server::notify_resource_updated`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::types::ChangeKind;
+    /// use pmcp::utils::json_patch::PatchOperation;
+    /// use serde_json::json;
+    ///
+    /// # async fn example(server: pmcp::Server) -> pmcp::Result<()> {
+    /// server.notify_resource_changed(
+    ///     "file:///project/state.json".to_string(),
+    ///     ChangeKind::Patch(vec![PatchOperation::Replace {
+    ///         path: "/status".to_string(),
+    ///         value: json!("done"),
+    ///     }]),
+    /// ).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// The number of subscribers that were notified.
+    pub async fn notify_resource_changed(&self, uri: String, change: ChangeKind) -> Result<usize> {
         let mut subscription_manager = self.subscription_manager.write().await;
         if let Some(tx) = &self.notification_tx {
             subscription_manager.set_notification_sender({
@@ -1010,26 +2153,66 @@ impl Server {
                 }
             });
         }
-        subscription_manager.notify_resource_updated(uri).await
+        subscription_manager
+            .notify_resource_changed(uri, change)
+            .await
     }
 }
 
 /// Builder for creating servers.
-pub struct ServerBuilder {
+///
+/// `S` is the state shared by the handlers, see [`state`](Self::state).
+pub struct ServerBuilder<S = ()> {
     name: Option<String>,
     version: Option<String>,
     capabilities: ServerCapabilities,
+    instructions: Option<String>,
     tools: HashMap<String, Arc<dyn ToolHandler>>,
     prompts: HashMap<String, Arc<dyn PromptHandler>>,
     resources: Option<Arc<dyn ResourceHandler>>,
     sampling: Option<Arc<dyn SamplingHandler>>,
+    completions: Option<Arc<dyn CompletionHandler>>,
     /// Cancellation manager for request cancellation
     cancellation_manager: cancellation::CancellationManager,
     /// Roots manager for directory/URI registration
     roots_manager: roots::RootsManager,
+    /// Whether to fill missing tool arguments from schema defaults
+    apply_schema_defaults: bool,
+    /// Whether to check tool results against their declared output schema
+    validate_tool_output: bool,
+    /// Whether a panicking tool handler is turned into an error result
+    catch_tool_panics: bool,
+    /// Whether notifications the client has not opted into are dropped
+    strict_notifications: bool,
+    /// Whether progress is coalesced while the notification channel is congested
+    coalesce_progress: bool,
+    /// Requests taking longer than this are logged as slow
+    slow_request_threshold: Option<std::time::Duration>,
+    /// Requests taking longer than this are cancelled and fail
+    request_timeout: Option<std::time::Duration>,
+    /// Maximum nesting depth of inbound messages
+    max_json_depth: usize,
+    /// Handling of invalid UTF-8 in inbound messages
+    utf8: crate::shared::Utf8Mode,
+    /// Most resources one client may subscribe to
+    max_subscriptions_per_client: Option<usize>,
+    /// Most tools or prompts listed per page
+    page_size: Option<usize>,
+    /// Receives an entry for every tool call
+    audit: Option<Arc<dyn audit::AuditSink>>,
+    /// Patterns of argument names whose values are left out of audit entries
+    audit_redact: Vec<String>,
+    /// Hooks run before each client request
+    middleware: Vec<Arc<dyn RequestMiddleware>>,
+    /// Priority dispatch configuration
+    priority: Option<priority::PriorityConfig>,
+    /// Why tools registered so far are invalid, reported by `build`
+    invalid_tools: Vec<String>,
+    /// State shared by the handlers
+    state: Arc<S>,
 }
 
-impl std::fmt::Debug for ServerBuilder {
+impl<S> std::fmt::Debug for ServerBuilder<S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ServerBuilder")
             .field("name", &self.name)
@@ -1068,13 +2251,96 @@ impl ServerBuilder {
         Self {
             name: None,
             version: None,
+            instructions: None,
             capabilities: ServerCapabilities::default(),
             tools: HashMap::new(),
             prompts: HashMap::new(),
             resources: None,
             sampling: None,
+            completions: None,
             cancellation_manager: cancellation::CancellationManager::new(),
             roots_manager: roots::RootsManager::new(),
+            apply_schema_defaults: false,
+            validate_tool_output: cfg!(debug_assertions),
+            catch_tool_panics: true,
+            strict_notifications: false,
+            coalesce_progress: false,
+            slow_request_threshold: None,
+            request_timeout: None,
+            max_json_depth: crate::shared::DEFAULT_MAX_JSON_DEPTH,
+            utf8: crate::shared::Utf8Mode::default(),
+            max_subscriptions_per_client: None,
+            page_size: None,
+            audit: None,
+            audit_redact: Vec::new(),
+            middleware: Vec::new(),
+            priority: None,
+            invalid_tools: Vec::new(),
+            state: Arc::new(()),
+        }
+    }
+}
+
+impl<S: Send + Sync + 'static> ServerBuilder<S> {
+    /// Share state, such as a database pool or configuration, with the handlers.
+    ///
+    /// Handlers get it from [`RequestHandlerExtra::state`], so they do not have to
+    /// capture it themselves. Replaces any state set before.
+    ///
+    /// [`RequestHandlerExtra::state`]: cancellation::RequestHandlerExtra::state
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::Server;
+    ///
+    /// struct Config {
+    ///     root: String,
+    /// }
+    ///
+    /// let server = Server::builder()
+    ///     .name("files")
+    ///     .version("1.0.0")
+    ///     .state(Config { root: "/srv".to_string() })
+    ///     .build()?;
+    /// assert_eq!(server.state().root, "/srv");
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn state<T: Send + Sync + 'static>(self, state: T) -> ServerBuilder<T> {
+        self.shared_state(Arc::new(state))
+    }
+
+    /// Share state the caller keeps a handle to, see [`state`](Self::state).
+    pub fn shared_state<T: Send + Sync + 'static>(self, state: Arc<T>) -> ServerBuilder<T> {
+        ServerBuilder {
+            name: self.name,
+            version: self.version,
+            capabilities: self.capabilities,
+            instructions: self.instructions,
+            tools: self.tools,
+            prompts: self.prompts,
+            resources: self.resources,
+            sampling: self.sampling,
+            completions: self.completions,
+            cancellation_manager: self.cancellation_manager,
+            roots_manager: self.roots_manager,
+            apply_schema_defaults: self.apply_schema_defaults,
+            validate_tool_output: self.validate_tool_output,
+            catch_tool_panics: self.catch_tool_panics,
+            strict_notifications: self.strict_notifications,
+            coalesce_progress: self.coalesce_progress,
+            slow_request_threshold: self.slow_request_threshold,
+            request_timeout: self.request_timeout,
+            max_json_depth: self.max_json_depth,
+            utf8: self.utf8,
+            max_subscriptions_per_client: self.max_subscriptions_per_client,
+            page_size: self.page_size,
+            audit: self.audit,
+            audit_redact: self.audit_redact,
+            middleware: self.middleware,
+            priority: self.priority,
+            invalid_tools: self.invalid_tools,
+            state,
         }
     }
 
@@ -1128,6 +2394,28 @@ impl ServerBuilder {
         self
     }
 
+    /// Set instructions telling clients how to use the server.
+    ///
+    /// They are sent in the `initialize` result, where clients can pass them on to
+    /// the model, e.g. as part of its system prompt.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::Server;
+    ///
+    /// let server = Server::builder()
+    ///     .name("data-processor")
+    ///     .version("2.1.0")
+    ///     .instructions("Call `list_datasets` before querying a dataset.")
+    ///     .build()?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn instructions(mut self, instructions: impl Into<String>) -> Self {
+        self.instructions = Some(instructions.into());
+        self
+    }
+
     /// Set server capabilities.
     ///
     /// Configures the capabilities that this server supports.
@@ -1201,12 +2489,246 @@ impl ServerBuilder {
         self
     }
 
-    /// Add a prompt handler.
+    /// Add a tool with a hand-written JSON Schema for its arguments.
     ///
-    /// Registers a prompt that clients can retrieve via the prompts/get method.
-    /// Prompts provide templates that clients can use for various tasks.
+    /// `schema` and `description` are advertised in `tools/list` in place of the
+    /// handler's own [`metadata`](ToolHandler::metadata), without deriving the schema
+    /// with `schemars`. The schema must be a JSON object, otherwise
+    /// [`build`](Self::build) fails.
     ///
-    /// # Arguments
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::{Server, ToolHandler};
+    /// use async_trait::async_trait;
+    /// use serde_json::{json, Value};
+    ///
+    /// struct Search;
+    ///
+    /// #[async_trait]
+    /// impl ToolHandler for Search {
+    ///     async fn handle(&self, args: Value, _extra: pmcp::RequestHandlerExtra) -> pmcp::Result<Value> {
+    ///         Ok(json!({"query": args["query"]}))
+    ///     }
+    /// }
+    ///
+    /// let server = Server::builder()
+    ///     .name("search-server")
+    ///     .version("1.0.0")
+    ///     .tool_with_schema(
+    ///         "search",
+    ///         json!({
+    ///             "type": "object",
+    ///             "properties": {"query": {"type": "string"}},
+    ///             "required": ["query"]
+    ///         }),
+    ///         "Search the index",
+    ///         Search,
+    ///     )
+    ///     .build()?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn tool_with_schema(
+        mut self,
+        name: impl Into<String>,
+        schema: Value,
+        description: impl Into<String>,
+        handler: impl ToolHandler + 'static,
+    ) -> Self {
+        let name = name.into();
+        if !schema.is_object() {
+            self.invalid_tools.push(format!(
+                "Input schema of tool '{}' must be a JSON object",
+                name
+            ));
+        }
+        let tool = SchemaTool {
+            description: description.into(),
+            input_schema: schema,
+            handler: Box::new(handler),
+        };
+        self.tool(name, tool)
+    }
+
+    /// Add a tool for every plugin manifest in `dir`.
+    ///
+    /// Each `*.json` file describes a tool handled by running an external command,
+    /// see [`plugins`] for the manifest format and how the command is run. If the
+    /// directory cannot be read or a manifest is invalid, [`build`](Self::build)
+    /// fails. Plugins replace tools of the same name added before.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::Server;
+    ///
+    /// let server = Server::builder()
+    ///     .name("gateway")
+    ///     .version("1.0.0")
+    ///     .load_plugins("/etc/mcp/plugins")
+    ///     .build()?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn load_plugins(mut self, dir: impl AsRef<std::path::Path>) -> Self {
+        let dir = dir.as_ref();
+        match plugins::discover(dir) {
+            Ok(manifests) => {
+                for manifest in manifests {
+                    let name = manifest.name.clone();
+                    self = self.tool(name, plugins::PluginTool::new(manifest, dir));
+                }
+            },
+            Err(Error::Validation(message)) => self.invalid_tools.push(message),
+            Err(e) => self.invalid_tools.push(e.to_string()),
+        }
+        self
+    }
+
+    /// Add a tool whose handler blocks, such as sync I/O or heavy computation.
+    ///
+    /// The handler runs via [`tokio::task::spawn_blocking`], keeping the runtime free
+    /// to answer other requests meanwhile. A panicking handler is treated like a
+    /// panicking [`ToolHandler`], see [`catch_tool_panics`](Self::catch_tool_panics).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::{BlockingToolHandler, RequestHandlerExtra, Server};
+    /// use serde_json::{json, Value};
+    ///
+    /// struct Checksum;
+    ///
+    /// impl BlockingToolHandler for Checksum {
+    ///     fn handle(&self, args: Value, _extra: RequestHandlerExtra) -> pmcp::Result<Value> {
+    ///         let path = args["path"].as_str().unwrap_or_default();
+    ///         let bytes = std::fs::read(path)?;
+    ///         let sum: u64 = bytes.iter().map(|&b| u64::from(b)).sum();
+    ///         Ok(json!({"checksum": sum}))
+    ///     }
+    /// }
+    ///
+    /// let server = Server::builder()
+    ///     .name("file-server")
+    ///     .version("1.0.0")
+    ///     .blocking_tool("checksum", Checksum)
+    ///     .build()?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn blocking_tool(
+        self,
+        name: impl Into<String>,
+        handler: impl BlockingToolHandler + 'static,
+    ) -> Self {
+        self.tool(
+            name,
+            BlockingTool {
+                handler: Arc::new(handler),
+            },
+        )
+    }
+
+    /// Add a tool with typed arguments.
+    ///
+    /// The JSON Schema of `P` is generated with `schemars` and advertised as the
+    /// tool's `inputSchema`, along with `description`. Arguments that do not
+    /// deserialize into `P` are rejected before the handler runs.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::Server;
+    /// use schemars::JsonSchema;
+    /// use serde::Deserialize;
+    /// use serde_json::json;
+    ///
+    /// #[derive(Deserialize, JsonSchema)]
+    /// struct SearchArgs {
+    ///     query: String,
+    ///     limit: Option<u32>,
+    /// }
+    ///
+    /// let server = Server::builder()
+    ///     .name("search-server")
+    ///     .version("1.0.0")
+    ///     .tool_typed("search", "Search the index", |args: SearchArgs, _extra| async move {
+    ///         Ok(json!({"query": args.query, "limit": args.limit}))
+    ///     })
+    ///     .build()?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    #[cfg(feature = "schema-generation")]
+    pub fn tool_typed<P, F, Fut>(
+        self,
+        name: impl Into<String>,
+        description: impl Into<String>,
+        handler: F,
+    ) -> Self
+    where
+        P: serde::de::DeserializeOwned + schemars::JsonSchema + Send + 'static,
+        F: Fn(P, cancellation::RequestHandlerExtra) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<Value>> + Send + 'static,
+    {
+        let name = name.into();
+        let tool = typed_tool::TypedTool::new(name.clone(), handler).with_description(description);
+        self.tool(name, tool)
+    }
+
+    /// Add a tool with typed arguments and a typed result.
+    ///
+    /// The JSON Schemas of `P` and `R` are generated with `schemars` and advertised
+    /// as the tool's `inputSchema` and `outputSchema`. See
+    /// [`validate_tool_output`](Self::validate_tool_output) for checking results
+    /// against the output schema.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::Server;
+    /// use schemars::JsonSchema;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Deserialize, JsonSchema)]
+    /// struct WeatherArgs {
+    ///     city: String,
+    /// }
+    ///
+    /// #[derive(Serialize, JsonSchema)]
+    /// struct Forecast {
+    ///     temperature: f64,
+    ///     summary: String,
+    /// }
+    ///
+    /// let server = Server::builder()
+    ///     .name("weather-server")
+    ///     .version("1.0.0")
+    ///     .tool_typed_io("forecast", |args: WeatherArgs, _extra| async move {
+    ///         Ok(Forecast {
+    ///             temperature: 21.5,
+    ///             summary: format!("Sunny in {}", args.city),
+    ///         })
+    ///     })
+    ///     .build()?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    #[cfg(feature = "schema-generation")]
+    pub fn tool_typed_io<P, R, F, Fut>(self, name: impl Into<String>, handler: F) -> Self
+    where
+        P: serde::de::DeserializeOwned + schemars::JsonSchema + Send + 'static,
+        R: serde::Serialize + schemars::JsonSchema + Send + 'static,
+        F: Fn(P, cancellation::RequestHandlerExtra) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<R>> + Send + 'static,
+    {
+        let name = name.into();
+        let tool = typed_tool::TypedToolIo::new(name.clone(), handler);
+        self.tool(name, tool)
+    }
+
+    /// Add a prompt handler.
+    ///
+    /// Registers a prompt that clients can retrieve via the prompts/get method.
+    /// Prompts provide templates that clients can use for various tasks.
+    ///
+    /// # Arguments
     ///
     /// * `name` - The name of the prompt (used by clients to retrieve it)
     /// * `handler` - The handler implementation for this prompt
@@ -1228,9 +2750,9 @@ impl ServerBuilder {
     ///             description: Some(format!("Code review prompt for {}", language)),
     ///             messages: vec![PromptMessage {
     ///                 role: pmcp::Role::User,
-    ///                 content: pmcp::Content::Text {
+    ///                 content: vec![pmcp::Content::Text {
     ///                     text: format!("Please review this {} code:", language),
-    ///                 },
+    ///                 }],
     ///             }],
     ///         })
     ///     }
@@ -1277,6 +2799,8 @@ impl ServerBuilder {
     ///             contents: vec![pmcp::Content::Text {
     ///                 text: "File content here".to_string(),
     ///             }],
+    ///             content_hash: None,
+    ///             not_modified: false,
     ///         })
     ///     }
     ///
@@ -1355,431 +2879,3846 @@ impl ServerBuilder {
         self
     }
 
-    /// Build the server.
+    /// Set the completion handler.
     ///
-    /// Constructs the final Server instance from the configured builder.
-    /// This validates that required fields (name and version) are set.
+    /// Registers a handler for `completion/complete` and enables the completions
+    /// capability. Requests referring to unknown prompts, or to arguments a prompt
+    /// does not declare, are rejected before the handler runs.
     ///
     /// # Examples
     ///
     /// ```rust,no_run
-    /// use pmcp::{Server, ToolHandler};
+    /// use pmcp::{CompletionContext, CompletionHandler, Server};
+    /// use pmcp::types::{CompletionArgument, CompletionResult};
     /// use async_trait::async_trait;
-    /// use serde_json::Value;
     ///
-    /// struct PingTool;
+    /// struct LanguageCompletions;
     ///
     /// #[async_trait]
-    /// impl ToolHandler for PingTool {
-    ///     async fn handle(&self, _args: Value, _extra: pmcp::RequestHandlerExtra) -> pmcp::Result<Value> {
-    ///         Ok(serde_json::json!({"response": "pong"}))
+    /// impl CompletionHandler for LanguageCompletions {
+    ///     async fn complete(
+    ///         &self,
+    ///         _context: CompletionContext,
+    ///         argument: CompletionArgument,
+    ///         _extra: pmcp::RequestHandlerExtra,
+    ///     ) -> pmcp::Result<CompletionResult> {
+    ///         let values: Vec<String> = ["python", "rust", "typescript"]
+    ///             .iter()
+    ///             .filter(|lang| lang.starts_with(&argument.value))
+    ///             .map(|lang| lang.to_string())
+    ///             .collect();
+    ///         Ok(CompletionResult::new(values))
     ///     }
     /// }
     ///
     /// let server = Server::builder()
-    ///     .name("ping-server")
+    ///     .name("code-server")
     ///     .version("1.0.0")
-    ///     .tool("ping", PingTool{})
+    ///     .completions(LanguageCompletions)
     ///     .build()?;
-    ///
-    /// // Server is now ready to run
-    /// // server.run_stdio().await?;
     /// # Ok::<(), pmcp::Error>(())
     /// ```
+    pub fn completions(mut self, handler: impl CompletionHandler + 'static) -> Self {
+        self.completions = Some(Arc::new(handler));
+        self.capabilities.completions = Some(crate::types::CompletionCapabilities::default());
+        self
+    }
+
+    /// Fill in missing tool arguments from `default` values in the tool's input schema.
     ///
-    /// # Errors
+    /// Defaults are merged before the handler runs, so clients can omit optional
+    /// fields. Only tools that return a schema from [`ToolHandler::metadata`] are
+    /// affected. Disabled by default.
     ///
-    /// Returns an error if:
-    /// - The server name is not set
-    /// - The server version is not set
-    pub fn build(self) -> Result<Server> {
-        let name = self
-            .name
-            .ok_or_else(|| crate::Error::validation("Server name is required"))?;
-        let version = self
-            .version
-            .ok_or_else(|| crate::Error::validation("Server version is required"))?;
-
-        Ok(Server {
-            info: Implementation { name, version },
-            capabilities: self.capabilities,
-            tools: self.tools,
-            prompts: self.prompts,
-            resources: self.resources,
-            sampling: self.sampling,
-            client_capabilities: Arc::new(RwLock::new(None)),
-            initialized: Arc::new(RwLock::new(false)),
-            notification_tx: None,
-            cancellation_manager: self.cancellation_manager,
-            roots_manager: Arc::new(RwLock::new(self.roots_manager)),
-            subscription_manager: Arc::new(RwLock::new(subscriptions::SubscriptionManager::new())),
-            elicitation_manager: None,
-        })
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::Server;
+    ///
+    /// let server = Server::builder()
+    ///     .name("search-server")
+    ///     .version("1.0.0")
+    ///     .apply_schema_defaults(true)
+    ///     .build()?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn apply_schema_defaults(mut self, enabled: bool) -> Self {
+        self.apply_schema_defaults = enabled;
+        self
     }
-}
 
-impl Default for ServerBuilder {
-    fn default() -> Self {
-        Self::new()
+    /// Check tool results against the tool's declared output schema.
+    ///
+    /// A result that does not match is reported to the client as an internal error,
+    /// which surfaces handler bugs early. Only tools whose [`ToolHandler::metadata`]
+    /// includes an `output_schema` are checked. Enabled by default in debug builds and
+    /// disabled in release builds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::Server;
+    ///
+    /// let server = Server::builder()
+    ///     .name("report-server")
+    ///     .version("1.0.0")
+    ///     .validate_tool_output(true)
+    ///     .build()?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn validate_tool_output(mut self, enabled: bool) -> Self {
+        self.validate_tool_output = enabled;
+        self
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::shared::Transport;
-    use crate::types::{
-        jsonrpc::ResponsePayload, ClientCapabilities, InitializeRequest, ServerCapabilities,
-        ToolCapabilities, TransportMessage,
-    };
-    use async_trait::async_trait;
-    use serde_json::json;
-    use std::sync::{Arc, Mutex};
-    use tokio::time::timeout;
 
-    /// Mock transport for testing
-    #[derive(Debug)]
-    struct MockTransport {
-        messages: Arc<Mutex<Vec<TransportMessage>>>,
-        responses: Arc<Mutex<Vec<TransportMessage>>>,
+    /// Turn a panicking tool handler into an error result.
+    ///
+    /// When enabled, a tool that panics returns a [`CallToolResult`] with `is_error` set
+    /// and an internal error message, and the server keeps serving other requests.
+    /// Disable it while debugging to let the panic propagate. Enabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::Server;
+    ///
+    /// let server = Server::builder()
+    ///     .name("debug-server")
+    ///     .version("1.0.0")
+    ///     .catch_tool_panics(false)
+    ///     .build()?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn catch_tool_panics(mut self, enabled: bool) -> Self {
+        self.catch_tool_panics = enabled;
+        self
     }
 
-    impl MockTransport {
-        #[allow(dead_code)]
-        fn new() -> Self {
-            Self {
-                messages: Arc::new(Mutex::new(Vec::new())),
-                responses: Arc::new(Mutex::new(Vec::new())),
-            }
-        }
-
-        fn with_requests(requests: Vec<TransportMessage>) -> Self {
-            Self {
-                messages: Arc::new(Mutex::new(requests)),
-                responses: Arc::new(Mutex::new(Vec::new())),
-            }
-        }
-
-        #[allow(dead_code)]
-        fn add_request(&self, request: TransportMessage) {
-            self.messages.lock().unwrap().push(request);
-        }
+    /// Drop notifications the client has not opted into.
+    ///
+    /// When enabled, [`Server::send_notification`] logs and drops notifications that
+    /// [`Server::is_notification_supported`] rejects, such as progress for a request
+    /// that already finished. Disabled by default, which sends every notification.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::Server;
+    ///
+    /// let server = Server::builder()
+    ///     .name("strict-server")
+    ///     .version("1.0.0")
+    ///     .strict_notifications(true)
+    ///     .build()?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn strict_notifications(mut self, enabled: bool) -> Self {
+        self.strict_notifications = enabled;
+        self
+    }
 
-        #[allow(dead_code)]
-        fn get_sent_responses(&self) -> Vec<TransportMessage> {
-            self.responses.lock().unwrap().clone()
-        }
+    /// Coalesce progress notifications while the client is slow to receive them.
+    ///
+    /// When the queue of outgoing notifications is nearly full, progress is not queued;
+    /// only the latest progress per token is kept and sent once the queue has drained.
+    /// This bounds memory and keeps a tool reporting progress in a tight loop from
+    /// blocking, at the cost of the client skipping intermediate updates. Progress
+    /// still held back when its request completes is dropped rather than sent after
+    /// the response. When disabled, sending progress waits for room in the queue.
+    /// Disabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::Server;
+    ///
+    /// let server = Server::builder()
+    ///     .name("batch-server")
+    ///     .version("1.0.0")
+    ///     .coalesce_progress(true)
+    ///     .build()?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn coalesce_progress(mut self, enabled: bool) -> Self {
+        self.coalesce_progress = enabled;
+        self
     }
 
-    #[async_trait]
-    impl Transport for MockTransport {
-        async fn send(&mut self, message: TransportMessage) -> Result<()> {
+    /// Log requests that take longer than `threshold` to handle.
+    ///
+    /// A slow request is logged at warning level with its method, ID and duration in
+    /// milliseconds, inside a [`CorrelatedLogger`](crate::shared::CorrelatedLogger)
+    /// span. Requests are only timed when a threshold is set. Disabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::Server;
+    /// use std::time::Duration;
+    ///
+    /// let server = Server::builder()
+    ///     .name("monitored-server")
+    ///     .version("1.0.0")
+    ///     .slow_request_threshold(Duration::from_millis(500))
+    ///     .build()?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn slow_request_threshold(mut self, threshold: std::time::Duration) -> Self {
+        self.slow_request_threshold = Some(threshold);
+        self
+    }
+
+    /// Fail requests whose handler takes longer than `timeout`.
+    ///
+    /// A request still running at the timeout has its cancellation token cancelled,
+    /// so a handler watching
+    /// [`RequestHandlerExtra::cancelled`](cancellation::RequestHandlerExtra::cancelled)
+    /// can stop and clean up,
+    /// and the client receives a [`REQUEST_TIMEOUT`](ErrorCode::REQUEST_TIMEOUT) error.
+    /// Handlers run without a time limit by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::Server;
+    /// use std::time::Duration;
+    ///
+    /// let server = Server::builder()
+    ///     .name("bounded-server")
+    ///     .version("1.0.0")
+    ///     .request_timeout(Duration::from_secs(60))
+    ///     .build()?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn request_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the maximum nesting depth of arrays and objects in inbound messages.
+    ///
+    /// Deeper messages are rejected with a parse error before they are deserialized,
+    /// so a malicious client cannot exhaust the stack. The limit is given to the
+    /// transport with [`Transport::set_max_json_depth`](crate::shared::Transport::set_max_json_depth)
+    /// when the server starts running. Defaults to
+    /// [`DEFAULT_MAX_JSON_DEPTH`](crate::shared::DEFAULT_MAX_JSON_DEPTH).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::Server;
+    ///
+    /// let server = Server::builder()
+    ///     .name("shallow-server")
+    ///     .version("1.0.0")
+    ///     .max_json_depth(16)
+    ///     .build()?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn max_json_depth(mut self, depth: usize) -> Self {
+        self.max_json_depth = depth;
+        self
+    }
+
+    /// Set how invalid UTF-8 in messages from the client is handled.
+    ///
+    /// Use [`Utf8Mode::Lossy`](crate::shared::Utf8Mode::Lossy) to accept clients that
+    /// send malformed text; invalid sequences are replaced with U+FFFD instead of the
+    /// message being rejected. The mode is given to the transport with
+    /// [`Transport::set_utf8_mode`](crate::shared::Transport::set_utf8_mode) when the
+    /// server starts running; only transports that read raw bytes apply it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::shared::Utf8Mode;
+    /// use pmcp::Server;
+    ///
+    /// let server = Server::builder()
+    ///     .name("lenient-server")
+    ///     .version("1.0.0")
+    ///     .utf8(Utf8Mode::Lossy)
+    ///     .build()?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn utf8(mut self, mode: crate::shared::Utf8Mode) -> Self {
+        self.utf8 = mode;
+        self
+    }
+
+    /// Limit how many resources a single client may subscribe to.
+    ///
+    /// `resources/subscribe` requests, and [`Server::subscribe_resource`], fail with
+    /// [`ErrorCode::SUBSCRIPTION_LIMIT_REACHED`](crate::error::ErrorCode::SUBSCRIPTION_LIMIT_REACHED)
+    /// once the client holds `max` subscriptions, until it unsubscribes from one.
+    /// Subscriptions requested by clients are counted per connection, or per session
+    /// on servers shared between sessions, and dropped when it ends. Unlimited by
+    /// default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::Server;
+    ///
+    /// let server = Server::builder()
+    ///     .name("file-server")
+    ///     .version("1.0.0")
+    ///     .max_subscriptions_per_client(100)
+    ///     .build()?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn max_subscriptions_per_client(mut self, max: usize) -> Self {
+        self.max_subscriptions_per_client = Some(max);
+        self
+    }
+
+    /// List at most `size` tools or prompts per page.
+    ///
+    /// `tools/list` and `prompts/list` return entries sorted by name, with a
+    /// `nextCursor` for the next page while entries remain. All entries are listed on
+    /// one page by default.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::Server;
+    ///
+    /// let server = Server::builder()
+    ///     .name("toolbox")
+    ///     .version("1.0.0")
+    ///     .page_size(50)
+    ///     .build()?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn page_size(mut self, size: usize) -> Self {
+        self.page_size = Some(size.max(1));
+        self
+    }
+
+    /// Run `middleware` around each client request, after any added before it.
+    ///
+    /// See [`RequestMiddleware`] for the hooks it can implement.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use async_trait::async_trait;
+    /// use pmcp::shared::ConnectionContext;
+    /// use pmcp::types::ClientRequest;
+    /// use pmcp::{RequestMiddleware, Server};
+    ///
+    /// struct Tenant(String);
+    ///
+    /// struct TenantMiddleware;
+    ///
+    /// #[async_trait]
+    /// impl RequestMiddleware for TenantMiddleware {
+    ///     async fn on_request(
+    ///         &self,
+    ///         request: &ClientRequest,
+    ///         context: &ConnectionContext,
+    ///     ) -> pmcp::Result<()> {
+    ///         if let ClientRequest::Initialize(init) = request {
+    ///             context.set(Tenant(init.client_info.name.clone()));
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let server = Server::builder()
+    ///     .name("multi-tenant-server")
+    ///     .version("1.0.0")
+    ///     .middleware(TenantMiddleware)
+    ///     .build()?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn middleware(mut self, middleware: impl RequestMiddleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Record every tool call in an audit trail.
+    ///
+    /// `sink` receives an [`AuditEntry`](audit::AuditEntry) with the caller's
+    /// connection, the tool name and arguments, a summary of the result, the duration
+    /// and any error, once per call. Use [`audit_redact`](Self::audit_redact) to keep
+    /// secrets out of the trail. See [`audit::JsonlAuditSink`] for a tamper-evident
+    /// file sink.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::server::audit::JsonlAuditSink;
+    /// use pmcp::Server;
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let server = Server::builder()
+    ///     .name("audited-server")
+    ///     .version("1.0.0")
+    ///     .audit(JsonlAuditSink::open("/var/log/mcp/audit.jsonl").await?)
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn audit(mut self, sink: impl audit::AuditSink + 'static) -> Self {
+        self.audit = Some(Arc::new(sink));
+        self
+    }
+
+    /// Redact audited arguments whose name matches the regular expression `pattern`.
+    ///
+    /// Matching values, at any depth, are replaced by [`audit::REDACTED`] before the
+    /// entry reaches the sink. Can be called several times.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::server::audit::JsonlAuditSink;
+    /// use pmcp::Server;
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let server = Server::builder()
+    ///     .name("audited-server")
+    ///     .version("1.0.0")
+    ///     .audit(JsonlAuditSink::open("audit.jsonl").await?)
+    ///     .audit_redact("(?i)password")
+    ///     .audit_redact("^api_key$")
+    ///     .build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn audit_redact(mut self, pattern: impl Into<String>) -> Self {
+        self.audit_redact.push(pattern.into());
+        self
+    }
+
+    /// Dispatch requests through a bounded priority queue.
+    ///
+    /// At most `max_concurrent` requests run at once; the rest wait in tiers so that
+    /// urgent requests are served before expensive tool calls. Methods in the
+    /// [`MessagePriority::High`](crate::shared::transport::MessagePriority::High) tier,
+    /// `ping` by default, skip the queue. [`Server::run`] handles each request in its
+    /// own task, as does the streamable HTTP transport, so without this option the
+    /// number of requests running at once is unbounded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::Server;
+    /// use pmcp::server::priority::PriorityConfig;
+    ///
+    /// let server = Server::builder()
+    ///     .name("busy-server")
+    ///     .version("1.0.0")
+    ///     .priority_dispatch(PriorityConfig {
+    ///         max_concurrent: 8,
+    ///         ..Default::default()
+    ///     })
+    ///     .build()?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn priority_dispatch(mut self, config: priority::PriorityConfig) -> Self {
+        self.priority = Some(config);
+        self
+    }
+
+    /// Assign a priority tier to a request method.
+    ///
+    /// Enables priority dispatch with the default configuration if it is not
+    /// already enabled. See [`ServerBuilder::priority_dispatch`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::Server;
+    /// use pmcp::shared::transport::MessagePriority;
+    ///
+    /// let server = Server::builder()
+    ///     .name("busy-server")
+    ///     .version("1.0.0")
+    ///     .request_priority("tools/list", MessagePriority::High)
+    ///     .request_priority("resources/read", MessagePriority::Low)
+    ///     .build()?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    pub fn request_priority(
+        mut self,
+        method: impl Into<String>,
+        priority: crate::shared::transport::MessagePriority,
+    ) -> Self {
+        self.priority = Some(
+            self.priority
+                .take()
+                .unwrap_or_default()
+                .with_tier(method, priority),
+        );
+        self
+    }
+
+    /// Build the server.
+    ///
+    /// Constructs the final Server instance from the configured builder.
+    /// This validates that required fields (name and version) are set.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::{Server, ToolHandler};
+    /// use async_trait::async_trait;
+    /// use serde_json::Value;
+    ///
+    /// struct PingTool;
+    ///
+    /// #[async_trait]
+    /// impl ToolHandler for PingTool {
+    ///     async fn handle(&self, _args: Value, _extra: pmcp::RequestHandlerExtra) -> pmcp::Result<Value> {
+    ///         Ok(serde_json::json!({"response": "pong"}))
+    ///     }
+    /// }
+    ///
+    /// let server = Server::builder()
+    ///     .name("ping-server")
+    ///     .version("1.0.0")
+    ///     .tool("ping", PingTool{})
+    ///     .build()?;
+    ///
+    /// // Server is now ready to run
+    /// // server.run_stdio().await?;
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The server name is not set
+    /// - The server version is not set
+    /// - A tool was added with an invalid schema
+    /// - A plugin directory cannot be read or holds an invalid manifest
+    /// - An audit redaction pattern is not a valid regular expression
+    pub fn build(self) -> Result<Server<S>> {
+        if let Some(invalid) = self.invalid_tools.first() {
+            return Err(crate::Error::validation(invalid.clone()));
+        }
+        let name = self
+            .name
+            .ok_or_else(|| crate::Error::validation("Server name is required"))?;
+        let version = self
+            .version
+            .ok_or_else(|| crate::Error::validation("Server version is required"))?;
+        let auditor = self
+            .audit
+            .map(|sink| audit::Auditor::new(sink, &self.audit_redact))
+            .transpose()?;
+        let mut subscription_manager = subscriptions::SubscriptionManager::new();
+        if let Some(max) = self.max_subscriptions_per_client {
+            subscription_manager = subscription_manager.with_max_per_subscriber(max);
+        }
+
+        Ok(Server {
+            info: Implementation { name, version },
+            capabilities: self.capabilities,
+            instructions: self.instructions,
+            tools: self.tools,
+            prompts: self.prompts,
+            resources: self.resources,
+            sampling: self.sampling,
+            completions: self.completions,
+            client_capabilities: Arc::new(RwLock::new(None)),
+            initialized: parking_lot::Mutex::default(),
+            notification_tx: None,
+            progress_backlog: Arc::new(progress_backlog::ProgressBacklog::new()),
+            cancellation_manager: self.cancellation_manager,
+            roots_manager: Arc::new(RwLock::new(self.roots_manager)),
+            client_roots: roots::ClientRoots::new(),
+            argument_streams: argument_streams::ArgumentStreams::new(),
+            subscription_manager: Arc::new(RwLock::new(subscription_manager)),
+            elicitation_manager: None,
+            apply_schema_defaults: self.apply_schema_defaults,
+            validate_tool_output: self.validate_tool_output,
+            catch_tool_panics: self.catch_tool_panics,
+            strict_notifications: self.strict_notifications,
+            coalesce_progress: self.coalesce_progress,
+            slow_request_threshold: self.slow_request_threshold,
+            request_timeout: self.request_timeout,
+            max_json_depth: self.max_json_depth,
+            utf8: self.utf8,
+            page_size: self.page_size,
+            auditor,
+            request_counters: stats::RequestCounters::new(),
+            events: Arc::new(events::EventEmitter::default()),
+            middleware: self.middleware,
+            connection_contexts: sessions::SessionContexts::default(),
+            dispatcher: self.priority.map(priority::PriorityDispatcher::new),
+            client_requests: None,
+            state: self.state,
+        })
+    }
+}
+
+/// Get the page of `items` starting at `cursor`, and the cursor of the next page.
+///
+/// Cursors are the base64 encoded offset of the page. Without a page size every item
+/// from the cursor on is returned.
+fn paginate<T>(
+    mut items: Vec<T>,
+    cursor: Option<String>,
+    page_size: Option<usize>,
+) -> Result<(Vec<T>, Option<String>)> {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+
+    let offset = match cursor {
+        None => 0,
+        Some(cursor) => URL_SAFE_NO_PAD
+            .decode(&cursor)
+            .ok()
+            .and_then(|decoded| String::from_utf8(decoded).ok())
+            .and_then(|decoded| decoded.parse::<usize>().ok())
+            .filter(|offset| *offset <= items.len())
+            .ok_or_else(|| Error::invalid_params(format!("Invalid cursor '{}'", cursor)))?,
+    };
+    let end = page_size.map_or(items.len(), |size| {
+        offset.saturating_add(size).min(items.len())
+    });
+    let next_cursor = (end < items.len()).then(|| URL_SAFE_NO_PAD.encode(end.to_string()));
+    items.truncate(end);
+    Ok((items.split_off(offset), next_cursor))
+}
+
+/// Tool advertised with a hand-written schema, see [`ServerBuilder::tool_with_schema`].
+struct SchemaTool {
+    description: String,
+    input_schema: Value,
+    handler: Box<dyn ToolHandler>,
+}
+
+#[async_trait]
+impl ToolHandler for SchemaTool {
+    async fn handle(&self, args: Value, extra: cancellation::RequestHandlerExtra) -> Result<Value> {
+        self.handler.handle(args, extra).await
+    }
+
+    fn metadata(&self) -> Option<crate::types::ToolInfo> {
+        let mut info = self
+            .handler
+            .metadata()
+            .unwrap_or_else(|| crate::types::ToolInfo {
+                name: String::new(),
+                description: None,
+                input_schema: Value::Null,
+                category: None,
+                tags: Vec::new(),
+                output_schema: None,
+            });
+        info.description = Some(self.description.clone());
+        info.input_schema.clone_from(&self.input_schema);
+        Some(info)
+    }
+}
+
+/// Tool running a blocking handler, see [`ServerBuilder::blocking_tool`].
+struct BlockingTool {
+    handler: Arc<dyn BlockingToolHandler>,
+}
+
+#[async_trait]
+impl ToolHandler for BlockingTool {
+    async fn handle(&self, args: Value, extra: cancellation::RequestHandlerExtra) -> Result<Value> {
+        let handler = self.handler.clone();
+        match tokio::task::spawn_blocking(move || handler.handle(args, extra)).await {
+            Ok(result) => result,
+            // Re-raise so the panic is reported like one from an async handler
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Err(e) => Err(Error::internal(format!(
+                "Blocking tool was cancelled: {}",
+                e
+            ))),
+        }
+    }
+
+    fn metadata(&self) -> Option<crate::types::ToolInfo> {
+        self.handler.metadata()
+    }
+}
+
+/// Merge `default` values from an object schema into `value` for any missing properties.
+///
+/// Nested object properties are filled recursively. A `null` value is treated as an
+/// empty object so that tools called without arguments still receive their defaults.
+fn apply_defaults(schema: &Value, value: &mut Value) {
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return;
+    };
+
+    if value.is_null() {
+        *value = Value::Object(serde_json::Map::new());
+    }
+    let Some(object) = value.as_object_mut() else {
+        return;
+    };
+
+    for (name, property) in properties {
+        match object.get_mut(name) {
+            Some(existing) => {
+                if existing.is_object() {
+                    apply_defaults(property, existing);
+                }
+            },
+            None => {
+                if let Some(default) = property.get("default") {
+                    object.insert(name.clone(), default.clone());
+                }
+            },
+        }
+    }
+}
+
+/// Convert a wall-clock deadline into an [`std::time::Instant`].
+///
+/// Deadlines in the past map to now.
+fn deadline_instant(deadline: chrono::DateTime<chrono::Utc>) -> std::time::Instant {
+    let remaining = (deadline - chrono::Utc::now()).to_std().unwrap_or_default();
+    std::time::Instant::now() + remaining
+}
+
+/// Progress token the client sent in a request's metadata, if any.
+fn progress_token(meta: Option<&crate::types::RequestMeta>) -> Option<crate::types::ProgressToken> {
+    meta.and_then(|meta| meta.progress_token.clone())
+}
+
+/// Extract the message from a panic payload.
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+impl Default for ServerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::Transport;
+    use crate::types::{
+        jsonrpc::ResponsePayload, ClientCapabilities, InitializeRequest, ServerCapabilities,
+        ToolCapabilities, TransportMessage,
+    };
+    use async_trait::async_trait;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+    use tokio::time::timeout;
+
+    /// Mock transport for testing
+    #[derive(Debug)]
+    struct MockTransport {
+        messages: Arc<Mutex<Vec<TransportMessage>>>,
+        responses: Arc<Mutex<Vec<TransportMessage>>>,
+    }
+
+    impl MockTransport {
+        #[allow(dead_code)]
+        fn new() -> Self {
+            Self {
+                messages: Arc::new(Mutex::new(Vec::new())),
+                responses: Arc::new(Mutex::new(Vec::new())),
+            }
+        }
+
+        fn with_requests(requests: Vec<TransportMessage>) -> Self {
+            Self {
+                messages: Arc::new(Mutex::new(requests)),
+                responses: Arc::new(Mutex::new(Vec::new())),
+            }
+        }
+
+        #[allow(dead_code)]
+        fn add_request(&self, request: TransportMessage) {
+            self.messages.lock().unwrap().push(request);
+        }
+
+        #[allow(dead_code)]
+        fn get_sent_responses(&self) -> Vec<TransportMessage> {
+            self.responses.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl Transport for MockTransport {
+        async fn send(&mut self, message: TransportMessage) -> Result<()> {
             self.responses.lock().unwrap().push(message);
             Ok(())
         }
 
         async fn receive(&mut self) -> Result<TransportMessage> {
-            let mut messages = self.messages.lock().unwrap();
-            messages
-                .pop()
-                .map_or_else(|| Err(Error::protocol_msg("No more messages")), Ok)
+            let mut messages = self.messages.lock().unwrap();
+            messages
+                .pop()
+                .map_or_else(|| Err(Error::protocol_msg("No more messages")), Ok)
+        }
+
+        async fn close(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn is_connected(&self) -> bool {
+            !self.messages.lock().unwrap().is_empty()
+        }
+
+        fn transport_type(&self) -> &'static str {
+            "mock"
+        }
+    }
+
+    /// Mock tool handler for testing
+    struct MockTool {
+        result: Value,
+    }
+
+    impl MockTool {
+        fn new(result: Value) -> Self {
+            Self { result }
+        }
+    }
+
+    #[async_trait]
+    impl ToolHandler for MockTool {
+        async fn handle(
+            &self,
+            _args: Value,
+            _extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<Value> {
+            Ok(self.result.clone())
+        }
+    }
+
+    /// Mock prompt handler for testing
+    struct MockPrompt {
+        result: crate::types::GetPromptResult,
+    }
+
+    impl MockPrompt {
+        fn new(result: crate::types::GetPromptResult) -> Self {
+            Self { result }
+        }
+    }
+
+    #[async_trait]
+    impl PromptHandler for MockPrompt {
+        async fn handle(
+            &self,
+            _args: HashMap<String, String>,
+            _extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<crate::types::GetPromptResult> {
+            Ok(self.result.clone())
+        }
+    }
+
+    /// Mock resource handler for testing
+    struct MockResource {
+        resources: Vec<crate::types::ResourceInfo>,
+        templates: Vec<crate::types::ResourceTemplate>,
+        contents: HashMap<String, crate::types::ReadResourceResult>,
+    }
+
+    impl MockResource {
+        fn new() -> Self {
+            Self {
+                resources: Vec::new(),
+                templates: Vec::new(),
+                contents: HashMap::new(),
+            }
+        }
+
+        fn with_resource(mut self, uri: String, content: crate::types::ReadResourceResult) -> Self {
+            self.contents.insert(uri, content);
+            self
+        }
+    }
+
+    #[async_trait]
+    impl ResourceHandler for MockResource {
+        async fn read(
+            &self,
+            uri: &str,
+            _extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<crate::types::ReadResourceResult> {
+            self.contents
+                .get(uri)
+                .cloned()
+                .ok_or_else(|| Error::not_found(format!("Resource '{}' not found", uri)))
+        }
+
+        async fn list(
+            &self,
+            _cursor: Option<String>,
+            _extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<crate::types::ListResourcesResult> {
+            Ok(crate::types::ListResourcesResult {
+                resources: self.resources.clone(),
+                next_cursor: None,
+            })
+        }
+
+        async fn list_templates(
+            &self,
+            _cursor: Option<String>,
+            _extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<ListResourceTemplatesResult> {
+            Ok(ListResourceTemplatesResult {
+                resource_templates: self.templates.clone(),
+                next_cursor: None,
+            })
+        }
+    }
+
+    /// Complete the initialize handshake so the server accepts further requests.
+    async fn initialize<S: Send + Sync + 'static>(server: &Server<S>) {
+        let request = Request::Client(Box::new(ClientRequest::Initialize(InitializeRequest {
+            protocol_version: "2024-11-05".to_string(),
+            capabilities: ClientCapabilities::default(),
+            client_info: Implementation {
+                name: "test-client".to_string(),
+                version: "1.0.0".to_string(),
+            },
+        })));
+        server.handle_request(RequestId::from(0i64), request).await;
+    }
+
+    #[test]
+    fn test_server_builder() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .capabilities(ServerCapabilities::tools_only())
+            .tool("test-tool", MockTool::new(json!({"result": "success"})))
+            .build()
+            .unwrap();
+
+        assert_eq!(server.info.name, "test-server");
+        assert_eq!(server.info.version, "1.0.0");
+        assert!(server.tools.contains_key("test-tool"));
+    }
+
+    #[test]
+    fn test_server_builder_validation() {
+        // Missing name
+        let result = Server::builder().version("1.0.0").build();
+        assert!(result.is_err());
+
+        // Missing version
+        let result = Server::builder().name("test-server").build();
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_server_initialization() {
+        let init_request = TransportMessage::Request {
+            id: RequestId::from(1i64),
+            request: Request::Client(Box::new(ClientRequest::Initialize(InitializeRequest {
+                protocol_version: "2024-11-05".to_string(),
+                capabilities: ClientCapabilities {
+                    tools: Some(ToolCapabilities::default()),
+                    ..Default::default()
+                },
+                client_info: Implementation {
+                    name: "test-client".to_string(),
+                    version: "1.0.0".to_string(),
+                },
+            }))),
+        };
+
+        let transport = MockTransport::with_requests(vec![init_request]);
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .capabilities(ServerCapabilities::tools_only())
+            .build()
+            .unwrap();
+
+        // Test server run for a short time
+        let server_handle = tokio::spawn(async move {
+            let _ = timeout(std::time::Duration::from_millis(100), server.run(transport)).await;
+        });
+
+        // Wait for server to process
+        let _ = timeout(std::time::Duration::from_millis(200), server_handle).await;
+    }
+
+    #[tokio::test]
+    async fn test_server_capabilities() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .capabilities(ServerCapabilities::tools_only())
+            .build()
+            .unwrap();
+
+        assert!(!server.is_initialized().await);
+        assert!(server.get_client_capabilities().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_server_notifications() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .build()
+            .unwrap();
+
+        // Send notification (should not panic even without transport)
+        server
+            .send_notification(ServerNotification::ToolsChanged)
+            .await;
+    }
+
+    #[test]
+    fn test_server_builder_with_all_handlers() {
+        let prompt_result = crate::types::GetPromptResult {
+            description: Some("Test prompt".to_string()),
+            messages: vec![],
+        };
+
+        let resource_content = crate::types::ReadResourceResult {
+            contents: vec![crate::types::Content::Text {
+                text: "Hello, world!".to_string(),
+            }],
+            content_hash: None,
+            not_modified: false,
+        };
+
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("test-tool", MockTool::new(json!({"result": "success"})))
+            .prompt("test-prompt", MockPrompt::new(prompt_result))
+            .resources(
+                MockResource::new().with_resource("test://uri".to_string(), resource_content),
+            )
+            .build()
+            .unwrap();
+
+        assert!(server.tools.contains_key("test-tool"));
+        assert!(server.prompts.contains_key("test-prompt"));
+        assert!(server.resources.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_handle_request_initialize() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .capabilities(ServerCapabilities::tools_only())
+            .build()
+            .unwrap();
+
+        let request = Request::Client(Box::new(ClientRequest::Initialize(InitializeRequest {
+            protocol_version: "2024-11-05".to_string(),
+            capabilities: ClientCapabilities::default(),
+            client_info: Implementation {
+                name: "test-client".to_string(),
+                version: "1.0.0".to_string(),
+            },
+        })));
+
+        let response = server.handle_request(RequestId::from(1i64), request).await;
+
+        assert_eq!(response.id, RequestId::from(1i64));
+        match response.payload {
+            ResponsePayload::Result(_) => {
+                assert!(server.is_initialized().await);
+            },
+            ResponsePayload::Error(_) => panic!("Expected success response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_double_initialize_rejected() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .capabilities(ServerCapabilities::tools_only())
+            .build()
+            .unwrap();
+
+        initialize(&server).await;
+        assert!(server.is_initialized().await);
+
+        let request = Request::Client(Box::new(ClientRequest::Initialize(InitializeRequest {
+            protocol_version: "2024-11-05".to_string(),
+            capabilities: ClientCapabilities::default(),
+            client_info: Implementation {
+                name: "other-client".to_string(),
+                version: "2.0.0".to_string(),
+            },
+        })));
+        let response = server.handle_request(RequestId::from(2i64), request).await;
+
+        match response.payload {
+            ResponsePayload::Error(error) => {
+                assert_eq!(error.code, ErrorCode::INVALID_REQUEST.as_i32());
+                assert_eq!(error.message, "already initialized");
+            },
+            ResponsePayload::Result(_) => panic!("Expected error response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_before_initialize_rejected() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("test-tool", MockTool::new(json!({"result": "success"})))
+            .build()
+            .unwrap();
+
+        let request = Request::Client(Box::new(ClientRequest::ListTools(ListToolsRequest {
+            cursor: None,
+        })));
+        let response = server.handle_request(RequestId::from(1i64), request).await;
+
+        match response.payload {
+            ResponsePayload::Error(error) => {
+                assert_eq!(error.code, ErrorCode::INVALID_REQUEST.as_i32());
+                assert_eq!(error.message, "server not initialized");
+            },
+            ResponsePayload::Result(_) => panic!("Expected error response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_list_tools() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("test-tool", MockTool::new(json!({"result": "success"})))
+            .build()
+            .unwrap();
+
+        initialize(&server).await;
+        let request = Request::Client(Box::new(ClientRequest::ListTools(ListToolsRequest {
+            cursor: None,
+        })));
+        let response = server.handle_request(RequestId::from(1i64), request).await;
+
+        match response.payload {
+            ResponsePayload::Result(result) => {
+                let tools_result: ListToolsResult = serde_json::from_value(result).unwrap();
+                assert_eq!(tools_result.tools.len(), 1);
+                assert_eq!(tools_result.tools[0].name, "test-tool");
+            },
+            ResponsePayload::Error(_) => panic!("Expected success response"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_call_tool() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("test-tool", MockTool::new(json!({"result": "success"})))
+            .build()
+            .unwrap();
+
+        initialize(&server).await;
+        let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest {
+            name: "test-tool".to_string(),
+            arguments: json!({"input": "test"}),
+            meta: None,
+        })));
+
+        let response = server.handle_request(RequestId::from(1i64), request).await;
+
+        match response.payload {
+            ResponsePayload::Result(result) => {
+                let call_result: CallToolResult = serde_json::from_value(result).unwrap();
+                assert!(!call_result.is_error);
+                assert_eq!(call_result.content.len(), 1);
+            },
+            ResponsePayload::Error(_) => panic!("Expected success response"),
+        }
+    }
+
+    /// Tool that echoes its arguments and declares schema defaults
+    struct SchemaEchoTool;
+
+    #[async_trait]
+    impl ToolHandler for SchemaEchoTool {
+        async fn handle(
+            &self,
+            args: Value,
+            _extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<Value> {
+            Ok(args)
+        }
+
+        fn metadata(&self) -> Option<crate::types::ToolInfo> {
+            Some(crate::types::ToolInfo {
+                name: "search".to_string(),
+                description: None,
+                input_schema: json!({
+                    "type": "object",
+                    "properties": {
+                        "query": { "type": "string" },
+                        "limit": { "type": "integer", "default": 10 },
+                        "options": {
+                            "type": "object",
+                            "properties": {
+                                "fuzzy": { "type": "boolean", "default": false }
+                            }
+                        }
+                    }
+                }),
+                category: None,
+                tags: Vec::new(),
+                output_schema: None,
+            })
+        }
+    }
+
+    /// Tool that declares a category and tags
+    struct CategorizedTool;
+
+    #[async_trait]
+    impl ToolHandler for CategorizedTool {
+        async fn handle(
+            &self,
+            _args: Value,
+            _extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<Value> {
+            Ok(json!({}))
+        }
+
+        fn metadata(&self) -> Option<crate::types::ToolInfo> {
+            Some(crate::types::ToolInfo {
+                name: "grep".to_string(),
+                description: Some("Search files".to_string()),
+                input_schema: json!({"type": "object"}),
+                category: Some("search".to_string()),
+                tags: vec!["files".to_string()],
+                output_schema: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_tools_exposes_category() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("grep", CategorizedTool)
+            .tool("plain", MockTool::new(json!({})))
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        let request = Request::Client(Box::new(ClientRequest::ListTools(ListToolsRequest {
+            cursor: None,
+        })));
+        let response = server.handle_request(RequestId::from(1i64), request).await;
+        let ResponsePayload::Result(result) = response.payload else {
+            panic!("Expected success response");
+        };
+        let list: ListToolsResult = serde_json::from_value(result).unwrap();
+
+        let grep = list.tools.iter().find(|t| t.name == "grep").unwrap();
+        assert_eq!(grep.category.as_deref(), Some("search"));
+        assert_eq!(grep.tags, vec!["files"]);
+        assert_eq!(grep.description.as_deref(), Some("Search files"));
+
+        let plain = list.tools.iter().find(|t| t.name == "plain").unwrap();
+        assert!(plain.category.is_none());
+        assert!(plain.tags.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tool_with_schema_is_listed() {
+        let schema = json!({
+            "type": "object",
+            "properties": {"path": {"type": "string"}},
+            "required": ["path"]
+        });
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool_with_schema(
+                "read",
+                schema.clone(),
+                "Read a file",
+                MockTool::new(json!({})),
+            )
+            .tool_with_schema(
+                "grep",
+                json!({"type": "object"}),
+                "Find text",
+                CategorizedTool,
+            )
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        let request = Request::Client(Box::new(ClientRequest::ListTools(ListToolsRequest {
+            cursor: None,
+        })));
+        let response = server.handle_request(RequestId::from(1i64), request).await;
+        let ResponsePayload::Result(result) = response.payload else {
+            panic!("Expected success response");
+        };
+        let list: ListToolsResult = serde_json::from_value(result).unwrap();
+
+        let read = list.tools.iter().find(|t| t.name == "read").unwrap();
+        assert_eq!(read.input_schema, schema);
+        assert_eq!(read.description.as_deref(), Some("Read a file"));
+
+        // The handler's own metadata is kept where not overridden
+        let grep = list.tools.iter().find(|t| t.name == "grep").unwrap();
+        assert_eq!(grep.description.as_deref(), Some("Find text"));
+        assert_eq!(grep.category.as_deref(), Some("search"));
+
+        let err = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool_with_schema("bad", json!(["path"]), "Bad", MockTool::new(json!({})))
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("must be a JSON object"), "{}", err);
+    }
+
+    /// Tool that echoes its arguments and declares an output schema
+    struct SumEchoTool;
+
+    #[async_trait]
+    impl ToolHandler for SumEchoTool {
+        async fn handle(
+            &self,
+            args: Value,
+            _extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<Value> {
+            Ok(args)
+        }
+
+        fn metadata(&self) -> Option<crate::types::ToolInfo> {
+            Some(crate::types::ToolInfo {
+                name: "sum".to_string(),
+                description: None,
+                input_schema: json!({"type": "object"}),
+                category: None,
+                tags: Vec::new(),
+                output_schema: Some(json!({
+                    "type": "object",
+                    "properties": { "sum": { "type": "integer" } },
+                    "required": ["sum"]
+                })),
+            })
+        }
+    }
+
+    async fn call_sum(
+        server: &Server,
+        arguments: Value,
+    ) -> ResponsePayload<Value, crate::types::jsonrpc::JSONRPCError> {
+        let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest {
+            name: "sum".to_string(),
+            arguments,
+            meta: None,
+        })));
+        server
+            .handle_request(RequestId::from(1i64), request)
+            .await
+            .payload
+    }
+
+    #[tokio::test]
+    async fn test_tool_output_validated_against_schema() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("sum", SumEchoTool)
+            .validate_tool_output(true)
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        assert!(matches!(
+            call_sum(&server, json!({"sum": 3})).await,
+            ResponsePayload::Result(_)
+        ));
+        match call_sum(&server, json!({"sum": "three"})).await {
+            ResponsePayload::Error(error) => {
+                assert_eq!(error.code, ErrorCode::INTERNAL_ERROR.as_i32());
+                assert!(error.message.contains("output schema"), "{}", error.message);
+            },
+            ResponsePayload::Result(_) => panic!("Expected error response"),
+        }
+
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("sum", SumEchoTool)
+            .validate_tool_output(false)
+            .build()
+            .unwrap();
+        initialize(&server).await;
+        assert!(matches!(
+            call_sum(&server, json!({"sum": "three"})).await,
+            ResponsePayload::Result(_)
+        ));
+    }
+
+    #[cfg(feature = "schema-generation")]
+    #[tokio::test]
+    async fn test_tool_typed_advertises_schema() {
+        #[derive(serde::Deserialize, schemars::JsonSchema)]
+        struct SearchArgs {
+            query: String,
+            #[allow(dead_code)]
+            limit: Option<u32>,
+        }
+
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool_typed(
+                "search",
+                "Search the index",
+                |args: SearchArgs, _extra| async move { Ok(json!({"query": args.query})) },
+            )
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        let request = Request::Client(Box::new(ClientRequest::ListTools(ListToolsRequest {
+            cursor: None,
+        })));
+        let response = server.handle_request(RequestId::from(1i64), request).await;
+        let ResponsePayload::Result(result) = response.payload else {
+            panic!("Expected success response");
+        };
+        let tool = &result["tools"][0];
+        assert_eq!(tool["name"], "search");
+        assert_eq!(tool["description"], "Search the index");
+        assert_eq!(tool["inputSchema"]["properties"]["query"]["type"], "string");
+        assert!(tool["inputSchema"]["properties"]["limit"].is_object());
+        assert_eq!(tool["inputSchema"]["required"], json!(["query"]));
+        assert!(tool.get("outputSchema").is_none());
+
+        let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest {
+            name: "search".to_string(),
+            arguments: json!({"limit": 3}),
+            meta: None,
+        })));
+        let response = server.handle_request(RequestId::from(2i64), request).await;
+        assert!(matches!(response.payload, ResponsePayload::Error(_)));
+    }
+
+    #[cfg(feature = "schema-generation")]
+    #[tokio::test]
+    async fn test_tool_typed_io_advertises_schemas() {
+        #[derive(serde::Deserialize, schemars::JsonSchema)]
+        struct AddArgs {
+            a: i64,
+            b: i64,
+        }
+
+        #[derive(serde::Serialize, schemars::JsonSchema)]
+        struct Sum {
+            sum: i64,
+        }
+
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool_typed_io("sum", |args: AddArgs, _extra| async move {
+                Ok(Sum {
+                    sum: args.a + args.b,
+                })
+            })
+            .validate_tool_output(true)
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        let request = Request::Client(Box::new(ClientRequest::ListTools(ListToolsRequest {
+            cursor: None,
+        })));
+        let response = server.handle_request(RequestId::from(1i64), request).await;
+        let ResponsePayload::Result(result) = response.payload else {
+            panic!("Expected success response");
+        };
+        assert_eq!(
+            result["tools"][0]["inputSchema"]["properties"]["a"]["type"],
+            "integer"
+        );
+        assert_eq!(
+            result["tools"][0]["outputSchema"]["properties"]["sum"]["type"],
+            "integer"
+        );
+
+        assert!(matches!(
+            call_sum(&server, json!({"a": 1, "b": 2})).await,
+            ResponsePayload::Result(_)
+        ));
+        assert!(matches!(
+            call_sum(&server, json!({"a": "one"})).await,
+            ResponsePayload::Error(_)
+        ));
+    }
+
+    /// Prompt that declares a single `language` argument
+    struct LanguagePrompt;
+
+    #[async_trait]
+    impl PromptHandler for LanguagePrompt {
+        async fn handle(
+            &self,
+            _args: HashMap<String, String>,
+            _extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<crate::types::GetPromptResult> {
+            Ok(crate::types::GetPromptResult {
+                description: None,
+                messages: vec![],
+            })
+        }
+
+        fn metadata(&self) -> Option<crate::types::PromptInfo> {
+            Some(crate::types::PromptInfo {
+                name: "ignored".to_string(),
+                description: Some("Review code".to_string()),
+                arguments: Some(vec![crate::types::PromptArgument {
+                    name: "language".to_string(),
+                    description: None,
+                    required: true,
+                    completion: None,
+                }]),
+            })
+        }
+    }
+
+    /// Completion handler that reports the context it was given
+    struct ContextCompletions;
+
+    #[async_trait]
+    impl CompletionHandler for ContextCompletions {
+        async fn complete(
+            &self,
+            context: CompletionContext,
+            argument: crate::types::CompletionArgument,
+            _extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<crate::types::CompletionResult> {
+            let described = match context {
+                CompletionContext::Prompt(info) => format!(
+                    "prompt:{}:{}",
+                    info.name,
+                    info.description.unwrap_or_default()
+                ),
+                CompletionContext::Resource { uri } => format!("resource:{}", uri),
+            };
+            Ok(crate::types::CompletionResult {
+                values: vec![described, argument.value],
+                total: None,
+                has_more: false,
+            })
+        }
+    }
+
+    async fn complete(
+        server: &Server,
+        reference: crate::types::CompletionReference,
+        argument: &str,
+    ) -> ResponsePayload<Value, crate::types::jsonrpc::JSONRPCError> {
+        let request = Request::Client(Box::new(ClientRequest::Complete(
+            crate::types::CompleteRequest {
+                r#ref: reference,
+                argument: crate::types::CompletionArgument {
+                    name: argument.to_string(),
+                    value: "ru".to_string(),
+                },
+                meta: None,
+            },
+        )));
+        server
+            .handle_request(RequestId::from(1i64), request)
+            .await
+            .payload
+    }
+
+    #[tokio::test]
+    async fn test_completion_references_are_validated() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .prompt("review", LanguagePrompt)
+            .prompt(
+                "plain",
+                MockPrompt::new(crate::types::GetPromptResult {
+                    description: None,
+                    messages: vec![],
+                }),
+            )
+            .completions(ContextCompletions)
+            .build()
+            .unwrap();
+        assert!(server.capabilities.completions.is_some());
+        initialize(&server).await;
+
+        let prompt = |name: &str| crate::types::CompletionReference::Prompt {
+            name: name.to_string(),
+        };
+
+        let ResponsePayload::Error(error) = complete(&server, prompt("missing"), "language").await
+        else {
+            panic!("Expected error for unknown prompt");
+        };
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS.as_i32());
+        assert!(
+            error.message.contains("Unknown prompt 'missing'"),
+            "{}",
+            error.message
+        );
+
+        let ResponsePayload::Error(error) = complete(&server, prompt("review"), "framework").await
+        else {
+            panic!("Expected error for undeclared argument");
+        };
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS.as_i32());
+        assert!(
+            error.message.contains("no argument 'framework'"),
+            "{}",
+            error.message
+        );
+
+        // The handler receives the resolved prompt under its registered name
+        let ResponsePayload::Result(result) = complete(&server, prompt("review"), "language").await
+        else {
+            panic!("Expected completion result");
+        };
+        assert_eq!(
+            result["completion"]["values"],
+            json!(["prompt:review:Review code", "ru"])
+        );
+
+        // Prompts without declared arguments accept any argument name
+        assert!(matches!(
+            complete(&server, prompt("plain"), "anything").await,
+            ResponsePayload::Result(_)
+        ));
+
+        let resource = crate::types::CompletionReference::Resource {
+            uri: "file:///{path}".to_string(),
+        };
+        let ResponsePayload::Result(result) = complete(&server, resource, "path").await else {
+            panic!("Expected completion result");
+        };
+        assert_eq!(result["completion"]["values"][0], "resource:file:///{path}");
+    }
+
+    /// Completion handler offering more values than a result may hold
+    struct ManyCompletions;
+
+    #[async_trait]
+    impl CompletionHandler for ManyCompletions {
+        async fn complete(
+            &self,
+            _context: CompletionContext,
+            _argument: crate::types::CompletionArgument,
+            _extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<crate::types::CompletionResult> {
+            let values = (0..150).map(|i| format!("value-{}", i)).collect();
+            Ok(crate::types::CompletionResult::new(values))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_completion_values_are_capped() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .completions(ManyCompletions)
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        let resource = crate::types::CompletionReference::Resource {
+            uri: "file:///{path}".to_string(),
+        };
+        let ResponsePayload::Result(result) = complete(&server, resource, "path").await else {
+            panic!("Expected completion result");
+        };
+        let completion: crate::types::CompletionResult =
+            serde_json::from_value(result["completion"].clone()).unwrap();
+        assert_eq!(
+            completion.values.len(),
+            crate::types::CompletionResult::MAX_VALUES
+        );
+        assert_eq!(completion.values[99], "value-99");
+        assert_eq!(completion.total, Some(150));
+        assert!(completion.has_more);
+    }
+
+    #[tokio::test]
+    async fn test_list_prompts_uses_prompt_metadata() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .prompt("review", LanguagePrompt)
+            .build()
+            .unwrap();
+
+        let result = server
+            .handle_list_prompts(ListPromptsRequest { cursor: None })
+            .unwrap();
+        assert_eq!(result["prompts"][0]["name"], "review");
+        assert_eq!(result["prompts"][0]["arguments"][0]["name"], "language");
+    }
+
+    #[test]
+    fn test_list_tools_pages_through_all_tools() {
+        let mut builder = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .page_size(3);
+        for i in 0..10 {
+            builder = builder.tool(format!("tool-{:02}", i), MockTool::new(json!(i)));
+        }
+        let server = builder.build().unwrap();
+
+        let mut names = Vec::new();
+        let mut cursor = None;
+        loop {
+            let result = server
+                .handle_list_tools(ListToolsRequest { cursor })
+                .unwrap();
+            let page = result["tools"].as_array().unwrap();
+            assert!(page.len() <= 3);
+            names.extend(page.iter().map(|t| t["name"].as_str().unwrap().to_string()));
+            match result.get("nextCursor") {
+                Some(next) => cursor = Some(next.as_str().unwrap().to_string()),
+                None => break,
+            }
+        }
+        let expected: Vec<_> = (0..10).map(|i| format!("tool-{:02}", i)).collect();
+        assert_eq!(names, expected);
+
+        let err = server
+            .handle_list_tools(ListToolsRequest {
+                cursor: Some("not a cursor".to_string()),
+            })
+            .unwrap_err();
+        assert_eq!(err.error_code(), Some(ErrorCode::INVALID_PARAMS));
+    }
+
+    #[test]
+    fn test_list_prompts_pages_through_all_prompts() {
+        let mut builder = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .page_size(2);
+        for name in ["c", "a", "d", "b", "e"] {
+            builder = builder.prompt(
+                name,
+                MockPrompt::new(crate::types::GetPromptResult {
+                    description: None,
+                    messages: Vec::new(),
+                }),
+            );
+        }
+        let server = builder.build().unwrap();
+
+        let first = server
+            .handle_list_prompts(ListPromptsRequest { cursor: None })
+            .unwrap();
+        assert_eq!(first["prompts"][0]["name"], "a");
+        assert_eq!(first["prompts"][1]["name"], "b");
+        let cursor = first["nextCursor"].as_str().map(str::to_string);
+        let second = server
+            .handle_list_prompts(ListPromptsRequest { cursor })
+            .unwrap();
+        assert_eq!(second["prompts"][0]["name"], "c");
+        let cursor = second["nextCursor"].as_str().map(str::to_string);
+        let last = server
+            .handle_list_prompts(ListPromptsRequest { cursor })
+            .unwrap();
+        assert_eq!(last["prompts"].as_array().unwrap().len(), 1);
+        assert_eq!(last["prompts"][0]["name"], "e");
+        assert!(last.get("nextCursor").is_none());
+    }
+
+    /// Tool that fails with the error named in its arguments
+    struct FailingTool;
+
+    #[async_trait]
+    impl ToolHandler for FailingTool {
+        async fn handle(
+            &self,
+            args: Value,
+            _extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<Value> {
+            Err(match args["error"].as_str().unwrap_or_default() {
+                "validation" => Error::validation("bad input"),
+                "invalid_params" => Error::invalid_params("missing field"),
+                "not_found" => Error::not_found("record 7"),
+                "authentication" => Error::authentication("no token"),
+                "timeout" => Error::Timeout(10),
+                "capability" => Error::capability("sampling"),
+                "protocol" => Error::protocol_with_data(
+                    ErrorCode::other(-32050),
+                    "quota exceeded",
+                    json!({"limit": 10}),
+                ),
+                "invalid_params_data" => Error::protocol_with_data(
+                    ErrorCode::INVALID_PARAMS,
+                    "age must be positive",
+                    json!({"field": "age"}),
+                ),
+                "protocol_scalar" => {
+                    Error::protocol_with_data(ErrorCode::other(-32050), "quota exceeded", json!(10))
+                },
+                _ => Error::internal("boom"),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_error_responses_map_codes_and_include_method() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("fail", FailingTool)
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        let cases = [
+            ("validation", ErrorCode::INVALID_PARAMS),
+            ("invalid_params", ErrorCode::INVALID_PARAMS),
+            ("not_found", ErrorCode::NOT_FOUND),
+            ("authentication", ErrorCode::AUTHENTICATION_REQUIRED),
+            ("timeout", ErrorCode::REQUEST_TIMEOUT),
+            ("capability", ErrorCode::UNSUPPORTED_CAPABILITY),
+            ("protocol", ErrorCode::other(-32050)),
+            ("internal", ErrorCode::INTERNAL_ERROR),
+        ];
+        for (kind, code) in cases {
+            let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest::new(
+                "fail",
+                json!({"error": kind}),
+            ))));
+            let response = server.handle_request(RequestId::from(1i64), request).await;
+            let ResponsePayload::Error(error) = response.payload else {
+                panic!("Expected error response for {}", kind);
+            };
+            assert_eq!(error.code, code.as_i32(), "{}", kind);
+            assert_eq!(error.data.as_ref().unwrap()["method"], "tools/call");
+        }
+
+        // Handler data is kept alongside the method
+        let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest::new(
+            "fail",
+            json!({"error": "protocol"}),
+        ))));
+        let response = server.handle_request(RequestId::from(2i64), request).await;
+        let ResponsePayload::Error(error) = response.payload else {
+            panic!("Expected error response");
+        };
+        assert_eq!(
+            error.data,
+            Some(json!({"limit": 10, "method": "tools/call"}))
+        );
+
+        let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest::new(
+            "fail",
+            json!({"error": "invalid_params_data"}),
+        ))));
+        let response = server.handle_request(RequestId::from(4i64), request).await;
+        let ResponsePayload::Error(error) = response.payload else {
+            panic!("Expected error response");
+        };
+        assert_eq!(error.code, -32602);
+        assert_eq!(error.message, "age must be positive");
+        assert_eq!(
+            error.data,
+            Some(json!({"field": "age", "method": "tools/call"}))
+        );
+
+        let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest::new(
+            "fail",
+            json!({"error": "protocol_scalar"}),
+        ))));
+        let response = server.handle_request(RequestId::from(3i64), request).await;
+        let ResponsePayload::Error(error) = response.payload else {
+            panic!("Expected error response");
+        };
+        // Data that is not an object is left as the handler gave it
+        assert_eq!(error.data, Some(json!(10)));
+
+        // Unknown tools are reported as not found
+        let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest::new(
+            "missing",
+            json!({}),
+        ))));
+        let response = server.handle_request(RequestId::from(4i64), request).await;
+        let ResponsePayload::Error(error) = response.payload else {
+            panic!("Expected error response");
+        };
+        assert_eq!(error.code, ErrorCode::NOT_FOUND.as_i32());
+    }
+
+    /// Tool that always panics
+    struct PanickingTool;
+
+    #[async_trait]
+    impl ToolHandler for PanickingTool {
+        async fn handle(
+            &self,
+            _args: Value,
+            _extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<Value> {
+            panic!("boom");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_panicking_tool_returns_error_result() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("explode", PanickingTool)
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest {
+            name: "explode".to_string(),
+            arguments: json!({}),
+            meta: None,
+        })));
+        let response = server.handle_request(RequestId::from(1i64), request).await;
+        let ResponsePayload::Result(result) = response.payload else {
+            panic!("Expected success response");
+        };
+        let call_result: CallToolResult = serde_json::from_value(result).unwrap();
+        assert!(call_result.is_error);
+        let crate::types::Content::Text { text } = &call_result.content[0] else {
+            panic!("Expected text content");
+        };
+        assert!(text.contains("Internal error"), "{}", text);
+        assert!(text.contains("boom"), "{}", text);
+
+        // The server keeps answering after the panic
+        let ping = Request::Client(Box::new(ClientRequest::Ping));
+        let response = server.handle_request(RequestId::from(2i64), ping).await;
+        assert!(matches!(response.payload, ResponsePayload::Result(_)));
+    }
+
+    /// Tool returning a multi-item error result
+    struct RejectingTool;
+
+    #[async_trait]
+    impl ToolHandler for RejectingTool {
+        async fn handle(
+            &self,
+            _args: Value,
+            _extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<Value> {
+            Ok(ToolResult::text("checked input")
+                .error("quantity must be positive")
+                .into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_result_is_returned_as_is() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("reject", RejectingTool)
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest {
+            name: "reject".to_string(),
+            arguments: json!({}),
+            meta: None,
+        })));
+        let response = server.handle_request(RequestId::from(1i64), request).await;
+        let ResponsePayload::Result(result) = response.payload else {
+            panic!("Expected success response");
+        };
+        let call_result: CallToolResult = serde_json::from_value(result).unwrap();
+        assert!(call_result.is_error);
+        assert_eq!(call_result.content.len(), 2);
+        let crate::types::Content::Text { text } = &call_result.content[1] else {
+            panic!("Expected text content");
+        };
+        assert_eq!(text, "quantity must be positive");
+    }
+
+    #[tokio::test]
+    async fn test_tool_results_on_the_wire() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("text", MockTool::new(json!("plain text")))
+            .tool("object", MockTool::new(json!({"total": 3})))
+            .tool(
+                "multi",
+                MockTool::new(json!({
+                    "content": [
+                        {"type": "text", "text": "Plotted 3 points"},
+                        {"type": "image", "data": "cG5n", "mimeType": "image/png"}
+                    ]
+                })),
+            )
+            .tool(
+                "failing",
+                MockTool::new(json!({
+                    "content": [{"type": "text", "text": "quantity must be positive"}],
+                    "isError": true
+                })),
+            )
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        let cases = [
+            (
+                "text",
+                json!({"content": [{"type": "text", "text": r#""plain text""#}], "isError": false}),
+            ),
+            (
+                "object",
+                json!({"content": [{"type": "text", "text": r#"{"total":3}"#}], "isError": false}),
+            ),
+            (
+                "multi",
+                json!({
+                    "content": [
+                        {"type": "text", "text": "Plotted 3 points"},
+                        {"type": "image", "data": "cG5n", "mimeType": "image/png"}
+                    ],
+                    "isError": false
+                }),
+            ),
+            (
+                "failing",
+                json!({
+                    "content": [{"type": "text", "text": "quantity must be positive"}],
+                    "isError": true
+                }),
+            ),
+        ];
+        for (i, (name, expected)) in cases.into_iter().enumerate() {
+            let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest {
+                name: name.to_string(),
+                arguments: json!({}),
+                meta: None,
+            })));
+            let id = RequestId::from(i64::try_from(i).unwrap());
+            let response = server.handle_request(id, request).await;
+            let ResponsePayload::Result(result) = response.payload else {
+                panic!("Expected success response for {}", name);
+            };
+            assert_eq!(result, expected, "{}", name);
+        }
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "boom")]
+    async fn test_tool_panics_propagate_when_not_caught() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("explode", PanickingTool)
+            .catch_tool_panics(false)
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest {
+            name: "explode".to_string(),
+            arguments: json!({}),
+            meta: None,
+        })));
+        server.handle_request(RequestId::from(1i64), request).await;
+    }
+
+    #[tokio::test]
+    async fn test_stats_count_requests() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("echo", MockTool::new(json!({"ok": true})))
+            .build()
+            .unwrap();
+        let stats = server.stats().await;
+        assert_eq!(stats.total_requests, 0);
+        assert_eq!(stats.connected_clients, 0);
+
+        initialize(&server).await;
+        for name in ["echo", "echo", "missing"] {
+            let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest {
+                name: name.to_string(),
+                arguments: json!({}),
+                meta: None,
+            })));
+            server.handle_request(RequestId::from(1i64), request).await;
+        }
+        server
+            .handle_request(
+                RequestId::from(2i64),
+                Request::Client(Box::new(ClientRequest::Ping)),
+            )
+            .await;
+        server
+            .subscribe_resource("file:///a".to_string(), "client".to_string())
+            .await
+            .unwrap();
+
+        let stats = server.stats().await;
+        assert_eq!(stats.total_requests, 5);
+        assert_eq!(stats.total_errors, 1);
+        assert_eq!(
+            stats.methods["tools/call"],
+            MethodStats {
+                requests: 3,
+                errors: 1
+            }
+        );
+        assert_eq!(stats.methods["initialize"].requests, 1);
+        assert_eq!(stats.methods["ping"].requests, 1);
+        assert_eq!(stats.active_subscriptions, 1);
+        assert_eq!(stats.connected_clients, 1);
+        assert!(stats.uptime > std::time::Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_stats_count_each_initialized_session() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .build()
+            .unwrap();
+
+        // Sessions initialize independently
+        sessions::in_session(Some("a".to_string()), initialize(&server)).await;
+        sessions::in_session(Some("b".to_string()), initialize(&server)).await;
+        assert_eq!(server.stats().await.connected_clients, 2);
+        assert!(!server.is_initialized().await);
+
+        server.end_session("a").await;
+        assert_eq!(server.stats().await.connected_clients, 1);
+        assert!(!sessions::in_session(Some("a".to_string()), server.is_initialized()).await);
+        assert!(sessions::in_session(Some("b".to_string()), server.is_initialized()).await);
+    }
+
+    #[tokio::test]
+    async fn test_max_subscriptions_per_client() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .max_subscriptions_per_client(2)
+            .build()
+            .unwrap();
+        let subscribe = |uri: &str, client: &str| {
+            server.subscribe_resource(uri.to_string(), client.to_string())
+        };
+
+        subscribe("file:///a", "client").await.unwrap();
+        subscribe("file:///b", "client").await.unwrap();
+        let err = subscribe("file:///c", "client").await.unwrap_err();
+        assert!(err.is_error_code(ErrorCode::SUBSCRIPTION_LIMIT_REACHED));
+        assert_eq!(err.error_code(), Some(ErrorCode::other(-32011)));
+        assert!(
+            err.to_string().contains("subscription limit reached"),
+            "{}",
+            err
+        );
+
+        // Other clients have their own allowance
+        subscribe("file:///c", "other").await.unwrap();
+
+        // Unsubscribing frees a slot
+        server
+            .unsubscribe_resource("file:///a".to_string(), "client".to_string())
+            .await
+            .unwrap();
+        subscribe("file:///c", "client").await.unwrap();
+        assert_eq!(server.stats().await.active_subscriptions, 3);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_requests_are_limited_per_session() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .max_subscriptions_per_client(1)
+            .build()
+            .unwrap();
+        initialize(&server).await;
+        let subscribe = |uri: &str| {
+            let request = ClientRequest::Subscribe(crate::types::SubscribeRequest {
+                uri: uri.to_string(),
+            });
+            server.handle_request(RequestId::from(1i64), Request::Client(Box::new(request)))
+        };
+
+        let response = subscribe("file:///a").await;
+        assert!(matches!(response.payload, ResponsePayload::Result(_)));
+        let response = subscribe("file:///b").await;
+        let ResponsePayload::Error(error) = response.payload else {
+            panic!("Expected the subscription limit to be reached");
+        };
+        assert_eq!(error.code, ErrorCode::SUBSCRIPTION_LIMIT_REACHED.as_i32());
+
+        // A session has its own allowance, released when it ends
+        sessions::in_session(Some("b".to_string()), initialize(&server)).await;
+        let response = sessions::in_session(Some("b".to_string()), subscribe("file:///b")).await;
+        assert!(matches!(response.payload, ResponsePayload::Result(_)));
+        assert!(
+            server
+                .is_notification_supported(&resource_updated("file:///b"))
+                .await
+        );
+        server.end_session("b").await;
+        assert!(
+            !server
+                .is_notification_supported(&resource_updated("file:///b"))
+                .await
+        );
+
+        let request = ClientRequest::Unsubscribe(crate::types::UnsubscribeRequest {
+            uri: "file:///a".to_string(),
+        });
+        server
+            .handle_request(RequestId::from(2i64), Request::Client(Box::new(request)))
+            .await;
+        let response = subscribe("file:///b").await;
+        assert!(matches!(response.payload, ResponsePayload::Result(_)));
+    }
+
+    fn resource_updated(uri: &str) -> ServerNotification {
+        ServerNotification::ResourceUpdated(crate::types::protocol::ResourceUpdatedParams::new(uri))
+    }
+
+    /// Records the method of every request and tags every result
+    #[derive(Default)]
+    struct CountingMiddleware {
+        requests: parking_lot::Mutex<Vec<String>>,
+        responses: parking_lot::Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl RequestMiddleware for CountingMiddleware {
+        async fn on_request(
+            &self,
+            request: &ClientRequest,
+            _context: &crate::shared::ConnectionContext,
+        ) -> Result<()> {
+            self.requests.lock().push(request.method().to_string());
+            Ok(())
+        }
+
+        async fn on_response(
+            &self,
+            method: &str,
+            response: &mut JSONRPCResponse,
+            _context: &crate::shared::ConnectionContext,
+        ) -> Result<()> {
+            self.responses.lock().push(method.to_string());
+            if let ResponsePayload::Result(Value::Object(result)) = &mut response.payload {
+                result.insert("_meta".to_string(), json!({"counted": true}));
+            }
+            Ok(())
+        }
+    }
+
+    /// Forwards to a shared [`CountingMiddleware`]
+    struct SharedMiddleware(Arc<CountingMiddleware>);
+
+    #[async_trait]
+    impl RequestMiddleware for SharedMiddleware {
+        async fn on_request(
+            &self,
+            request: &ClientRequest,
+            context: &crate::shared::ConnectionContext,
+        ) -> Result<()> {
+            self.0.on_request(request, context).await
+        }
+
+        async fn on_response(
+            &self,
+            method: &str,
+            response: &mut JSONRPCResponse,
+            context: &crate::shared::ConnectionContext,
+        ) -> Result<()> {
+            self.0.on_response(method, response, context).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middleware_sees_requests_and_responses() {
+        let counting = Arc::new(CountingMiddleware::default());
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("whoami", WhoAmITool)
+            .middleware(SharedMiddleware(counting.clone()))
+            .middleware(SignInMiddleware)
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        let response = server
+            .handle_request(
+                RequestId::from(1i64),
+                Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest::new(
+                    "whoami",
+                    json!({}),
+                )))),
+            )
+            .await;
+        let ResponsePayload::Result(result) = response.payload else {
+            panic!("Expected tool result, got {:?}", response.payload);
+        };
+        assert_eq!(result["_meta"], json!({"counted": true}));
+        assert!(result["content"].is_array());
+
+        assert_eq!(*counting.requests.lock(), ["initialize", "tools/call"]);
+        assert_eq!(*counting.responses.lock(), ["initialize", "tools/call"]);
+    }
+
+    /// User signed in by [`SignInMiddleware`]
+    struct SignedInUser(String);
+
+    /// Signs the client in on initialize and rejects tool calls from anyone else
+    struct SignInMiddleware;
+
+    #[async_trait]
+    impl RequestMiddleware for SignInMiddleware {
+        async fn on_request(
+            &self,
+            request: &ClientRequest,
+            context: &crate::shared::ConnectionContext,
+        ) -> Result<()> {
+            match request {
+                ClientRequest::Initialize(init) => {
+                    context.set(SignedInUser(init.client_info.name.clone()));
+                },
+                ClientRequest::CallTool(_) if context.get::<SignedInUser>().is_none() => {
+                    return Err(Error::invalid_request("not signed in"));
+                },
+                _ => {},
+            }
+            Ok(())
+        }
+    }
+
+    /// Tool answering with the user signed in on its connection
+    struct WhoAmITool;
+
+    #[async_trait]
+    impl ToolHandler for WhoAmITool {
+        async fn handle(
+            &self,
+            _args: Value,
+            extra: cancellation::RequestHandlerExtra,
+        ) -> Result<Value> {
+            let user = extra
+                .context()
+                .get::<SignedInUser>()
+                .ok_or_else(|| Error::internal("no signed in user"))?;
+            Ok(json!({ "user": user.0 }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middleware_passes_context_to_tools() {
+        let server = Arc::new(
+            Server::builder()
+                .name("test-server")
+                .version("1.0.0")
+                .tool("whoami", WhoAmITool)
+                .middleware(SignInMiddleware)
+                .build()
+                .unwrap(),
+        );
+        let call = || {
+            Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest::new(
+                "whoami",
+                json!({}),
+            ))))
+        };
+
+        let response = server.handle_request(RequestId::from(1i64), call()).await;
+        match response.payload {
+            ResponsePayload::Error(error) => assert_eq!(error.message, "not signed in"),
+            ResponsePayload::Result(result) => panic!("Expected rejection, got {}", result),
+        }
+
+        initialize(&server).await;
+        let response = server.handle_request(RequestId::from(2i64), call()).await;
+        let ResponsePayload::Result(result) = response.payload else {
+            panic!("Expected tool result, got {:?}", response.payload);
+        };
+        let text = result["content"][0]["text"].as_str().unwrap();
+        let answer: Value = serde_json::from_str(text).unwrap();
+        assert_eq!(answer["user"], "test-client");
+
+        // Closing the connection forgets the signed in user
+        let transport = Arc::new(connection::Connection::new(MockTransport::with_requests(
+            Vec::new(),
+        )));
+        Server::process_transport_messages(&server, &transport, &CancellationToken::new()).await;
+        assert!(server
+            .connection_contexts
+            .current()
+            .get::<SignedInUser>()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sessions_keep_separate_context() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("whoami", WhoAmITool)
+            .middleware(SignInMiddleware)
+            .build()
+            .unwrap();
+        let call = || {
+            Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest::new(
+                "whoami",
+                json!({}),
+            ))))
+        };
+
+        sessions::in_session(Some("a".to_string()), initialize(&server)).await;
+        let response = sessions::in_session(
+            Some("a".to_string()),
+            server.handle_request(RequestId::from(1i64), call()),
+        )
+        .await;
+        assert!(matches!(response.payload, ResponsePayload::Result(_)));
+
+        // The user signed in on session a is unknown to session b
+        let response = sessions::in_session(
+            Some("b".to_string()),
+            server.handle_request(RequestId::from(2i64), call()),
+        )
+        .await;
+        match response.payload {
+            ResponsePayload::Error(error) => assert_eq!(error.message, "not signed in"),
+            ResponsePayload::Result(result) => panic!("Expected rejection, got {}", result),
+        }
+
+        // Ending session a forgets its user
+        server.end_session("a").await;
+        let response = sessions::in_session(
+            Some("a".to_string()),
+            server.handle_request(RequestId::from(3i64), call()),
+        )
+        .await;
+        assert!(matches!(response.payload, ResponsePayload::Error(_)));
+    }
+
+    #[tokio::test]
+    async fn test_events_for_tool_call() {
+        let server = Arc::new(
+            Server::builder()
+                .name("test-server")
+                .version("1.0.0")
+                .tool("echo", MockTool::new(json!({"ok": true})))
+                .build()
+                .unwrap(),
+        );
+        let mut events = server.events();
+
+        initialize(&server).await;
+        for (id, name) in [(1i64, "echo"), (2i64, "missing")] {
+            let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest::new(
+                name,
+                json!({}),
+            ))));
+            server.handle_request(RequestId::from(id), request).await;
+        }
+
+        let mut received = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            received.push(event);
+        }
+        assert_eq!(received.len(), 7, "{:?}", received);
+        assert!(matches!(
+            &received[0],
+            ServerEvent::RequestStarted { id, method } if *id == RequestId::from(0i64) && method == "initialize"
+        ));
+        assert!(matches!(
+            &received[1],
+            ServerEvent::ClientInitialized { client_info, .. } if client_info.name == "test-client"
+        ));
+        assert!(matches!(
+            &received[2],
+            ServerEvent::RequestCompleted { method, .. } if method == "initialize"
+        ));
+        assert!(matches!(
+            &received[3],
+            ServerEvent::RequestStarted { id, method } if *id == RequestId::from(1i64) && method == "tools/call"
+        ));
+        assert!(matches!(
+            &received[4],
+            ServerEvent::RequestCompleted { id, method, .. } if *id == RequestId::from(1i64) && method == "tools/call"
+        ));
+        assert!(matches!(
+            &received[5],
+            ServerEvent::RequestStarted { id, .. } if *id == RequestId::from(2i64)
+        ));
+        assert!(matches!(
+            &received[6],
+            ServerEvent::RequestFailed { id, method, message, .. }
+                if *id == RequestId::from(2i64) && method == "tools/call" && message.contains("missing")
+        ));
+
+        // Notifications are reported once sent, and the end of the connection last
+        let transport = Arc::new(connection::Connection::new(MockTransport::with_requests(
+            Vec::new(),
+        )));
+        let (tx, rx) = mpsc::channel(1);
+        Server::<()>::spawn_notification_handler(
+            transport.clone(),
+            rx,
+            server.progress_backlog.clone(),
+            server.events.clone(),
+            CancellationToken::new(),
+        );
+        tx.send(Notification::Server(ServerNotification::ToolsChanged))
+            .await
+            .unwrap();
+        let event = timeout(std::time::Duration::from_secs(5), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            event,
+            ServerEvent::NotificationSent(Notification::Server(ServerNotification::ToolsChanged))
+        ));
+        Server::process_transport_messages(&server, &transport, &CancellationToken::new()).await;
+        assert!(matches!(events.try_recv(), Ok(ServerEvent::Shutdown)));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_plugin_tool_from_manifest() {
+        let dir = std::env::temp_dir().join(format!("pmcp-plugins-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        // Echoes its arguments back, wrapped in an object
+        std::fs::write(
+            dir.join("echo.sh"),
+            "#!/bin/sh\nprintf '{\"received\": '\ncat\nprintf '}'\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("echo.json"),
+            json!({
+                "name": "echo",
+                "description": "Echo the arguments",
+                "inputSchema": {"type": "object", "properties": {"text": {"type": "string"}}},
+                "command": ["sh", "./echo.sh"],
+                "timeoutMs": 5000
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .load_plugins(&dir)
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        let tools = server
+            .handle_list_tools(ListToolsRequest { cursor: None })
+            .unwrap();
+        assert_eq!(tools["tools"][0]["name"], "echo");
+        assert_eq!(tools["tools"][0]["description"], "Echo the arguments");
+
+        // Shell syntax in arguments reaches the command as plain data
+        let arguments = json!({"text": "$(touch pwned); rm -rf \"$HOME\" `id`"});
+        let response = server
+            .handle_request(
+                RequestId::from(1i64),
+                Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest::new(
+                    "echo",
+                    arguments.clone(),
+                )))),
+            )
+            .await;
+        let ResponsePayload::Result(result) = response.payload else {
+            panic!("Expected tool result, got {:?}", response.payload);
+        };
+        let text = result["content"][0]["text"].as_str().unwrap();
+        let answer: Value = serde_json::from_str(text).unwrap();
+        assert_eq!(answer, json!({"received": arguments}));
+        assert!(!dir.join("pwned").exists());
+
+        std::fs::write(dir.join("broken.json"), "{\"name\": \"broken\"}").unwrap();
+        let err = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .load_plugins(&dir)
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("broken.json"), "{}", err);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    /// Audit sink keeping its entries in memory
+    #[derive(Clone, Default)]
+    struct RecordingSink(Arc<std::sync::Mutex<Vec<audit::AuditEntry>>>);
+
+    #[async_trait]
+    impl audit::AuditSink for RecordingSink {
+        async fn record(&self, entry: &audit::AuditEntry) -> Result<()> {
+            self.0.lock().unwrap().push(entry.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_audit_entry_per_tool_call() {
+        let sink = RecordingSink::default();
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("echo", MockTool::new(json!({"ok": true})))
+            .tool("fail", FailingTool)
+            .audit(sink.clone())
+            .audit_redact("(?i)secret")
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        for (name, arguments) in [
+            ("echo", json!({"user": "alice", "Secret": "hunter2"})),
+            ("fail", json!({"error": "not_found"})),
+            ("missing", json!({})),
+        ] {
+            let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest {
+                name: name.to_string(),
+                arguments,
+                meta: None,
+            })));
+            server.handle_request(RequestId::from(1i64), request).await;
+        }
+        // Other requests are not audited
+        server
+            .handle_request(
+                RequestId::from(2i64),
+                Request::Client(Box::new(ClientRequest::Ping)),
+            )
+            .await;
+
+        let entries = sink.0.lock().unwrap();
+        assert_eq!(entries.len(), 3);
+
+        assert_eq!(entries[0].tool_name, "echo");
+        assert_eq!(
+            entries[0].arguments,
+            json!({"user": "alice", "Secret": audit::REDACTED})
+        );
+        assert!(entries[0].result_summary.as_deref().unwrap().contains("ok"));
+        assert!(entries[0].error.is_none());
+
+        assert_eq!(entries[1].tool_name, "fail");
+        assert!(entries[1].result_summary.is_none());
+        assert!(entries[1].error.as_deref().unwrap().contains("record 7"));
+
+        assert_eq!(entries[2].tool_name, "missing");
+        assert!(entries[2].error.as_deref().unwrap().contains("not found"));
+    }
+
+    #[test]
+    fn test_invalid_audit_redaction_is_rejected() {
+        let result = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .audit(RecordingSink::default())
+            .audit_redact("(")
+            .build();
+        assert!(matches!(result, Err(Error::Validation(_))));
+    }
+
+    /// CPU-bound tool spinning until released
+    struct SpinningTool {
+        started: Arc<std::sync::atomic::AtomicBool>,
+        release: Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    impl BlockingToolHandler for SpinningTool {
+        fn handle(
+            &self,
+            _args: Value,
+            _extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<Value> {
+            use std::sync::atomic::Ordering;
+            self.started.store(true, Ordering::SeqCst);
+            let give_up = std::time::Instant::now() + std::time::Duration::from_secs(5);
+            while !self.release.load(Ordering::SeqCst) {
+                if std::time::Instant::now() > give_up {
+                    return Err(Error::internal("never released"));
+                }
+                std::hint::spin_loop();
+            }
+            Ok(json!({"done": true}))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_blocking_tool_does_not_block_pings() {
+        use std::sync::atomic::Ordering;
+        let started = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let release = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .blocking_tool(
+                "spin",
+                SpinningTool {
+                    started: started.clone(),
+                    release: release.clone(),
+                },
+            )
+            .build()
+            .unwrap();
+        initialize(&server).await;
+        let mut client = ChannelClient::connect(server);
+
+        client
+            .request(
+                1,
+                ClientRequest::CallTool(CallToolRequest::new("spin", json!({}))),
+            )
+            .await;
+        while !started.load(Ordering::SeqCst) {
+            tokio::task::yield_now().await;
+        }
+
+        // The single-threaded test runtime still answers while the tool spins
+        client.request(2, ClientRequest::Ping).await;
+        let response = timeout(std::time::Duration::from_secs(1), client.response())
+            .await
+            .expect("ping should be answered while the tool spins");
+        assert_eq!(response.id, RequestId::from(2i64));
+        assert!(matches!(response.payload, ResponsePayload::Result(_)));
+
+        release.store(true, Ordering::SeqCst);
+        let response = client.response().await;
+        assert_eq!(response.id, RequestId::from(1i64));
+        let ResponsePayload::Result(result) = response.payload else {
+            panic!("Expected success response");
+        };
+        let call_result: CallToolResult = serde_json::from_value(result).unwrap();
+        assert!(!call_result.is_error);
+    }
+
+    /// Tool that takes a while to answer
+    struct SleepingTool;
+
+    #[async_trait]
+    impl ToolHandler for SleepingTool {
+        async fn handle(
+            &self,
+            _args: Value,
+            _extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<Value> {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            Ok(json!({}))
+        }
+    }
+
+    /// Log output captured by a test subscriber
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_request_is_logged() {
+        let logs = LogBuffer::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer({
+                let logs = logs.clone();
+                move || logs.clone()
+            })
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("slow", SleepingTool)
+            .slow_request_threshold(std::time::Duration::from_millis(10))
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        let ping = Request::Client(Box::new(ClientRequest::Ping));
+        server.handle_request(RequestId::from(1i64), ping).await;
+        assert!(!String::from_utf8_lossy(&logs.0.lock().unwrap()).contains("Slow request"));
+
+        let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest {
+            name: "slow".to_string(),
+            arguments: json!({}),
+            meta: None,
+        })));
+        server.handle_request(RequestId::from(2i64), request).await;
+
+        let output = String::from_utf8_lossy(&logs.0.lock().unwrap()).into_owned();
+        let line = output
+            .lines()
+            .find(|line| line.contains("Slow request"))
+            .unwrap_or_else(|| panic!("no slow request log in {:?}", output));
+        assert!(line.contains("WARN"), "{}", line);
+        assert!(line.contains("method=\"tools/call\""), "{}", line);
+        assert!(line.contains("request_id=2"), "{}", line);
+        assert!(line.contains("duration_ms="), "{}", line);
+    }
+
+    /// Tool that sleeps, recording whether it started and the deadline it saw
+    #[derive(Default)]
+    struct DeadlineTool {
+        started: Arc<std::sync::atomic::AtomicBool>,
+        deadline: Arc<Mutex<Option<std::time::Instant>>>,
+    }
+
+    #[async_trait]
+    impl ToolHandler for DeadlineTool {
+        async fn handle(
+            &self,
+            _args: Value,
+            extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<Value> {
+            self.started
+                .store(true, std::sync::atomic::Ordering::SeqCst);
+            *self.deadline.lock().unwrap() = extra.deadline();
+            tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+            Ok(json!("done"))
+        }
+    }
+
+    fn call_with_deadline(deadline: chrono::DateTime<chrono::Utc>) -> Request {
+        let mut call = CallToolRequest::new("slow", json!({}));
+        call.meta = Some(crate::types::RequestMeta {
+            deadline: Some(deadline),
+            ..Default::default()
+        });
+        Request::Client(Box::new(ClientRequest::CallTool(call)))
+    }
+
+    #[tokio::test]
+    async fn test_past_deadline_cancels_immediately() {
+        let tool = DeadlineTool::default();
+        let started = tool.started.clone();
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("slow", tool)
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        let request = call_with_deadline(chrono::Utc::now() - chrono::Duration::seconds(1));
+        let response = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            server.handle_request(RequestId::from(1i64), request),
+        )
+        .await
+        .expect("an expired deadline should not wait for the tool");
+
+        let ResponsePayload::Error(error) = response.payload else {
+            panic!("Expected error response");
+        };
+        assert!(
+            error.message.contains("Deadline exceeded"),
+            "{}",
+            error.message
+        );
+        assert!(!started.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_deadline_cancels_running_tool() {
+        let tool = DeadlineTool::default();
+        let started = tool.started.clone();
+        let seen_deadline = tool.deadline.clone();
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("slow", tool)
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        let request = call_with_deadline(chrono::Utc::now() + chrono::Duration::milliseconds(50));
+        let response = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            server.handle_request(RequestId::from(1i64), request),
+        )
+        .await
+        .expect("the deadline should abandon the tool");
+
+        assert!(matches!(response.payload, ResponsePayload::Error(_)));
+        assert!(started.load(std::sync::atomic::Ordering::SeqCst));
+        assert!(seen_deadline.lock().unwrap().is_some());
+    }
+
+    /// Tool that sleeps until cancelled, then records the reason it cleaned up for
+    #[derive(Default)]
+    struct CleanupTool {
+        cleaned_up: Arc<Mutex<Option<String>>>,
+    }
+
+    #[async_trait]
+    impl ToolHandler for CleanupTool {
+        async fn handle(
+            &self,
+            _args: Value,
+            extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<Value> {
+            tokio::select! {
+                () = tokio::time::sleep(std::time::Duration::from_secs(30)) => Ok(json!("done")),
+                () = extra.cancelled() => {
+                    *self.cleaned_up.lock().unwrap() = extra.cancellation_reason();
+                    Err(extra.cancellation_error())
+                },
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_request_timeout_cancels_slow_tool() {
+        let tool = CleanupTool::default();
+        let cleaned_up = tool.cleaned_up.clone();
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("slow", tool)
+            .request_timeout(std::time::Duration::from_millis(50))
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest::new(
+            "slow",
+            json!({}),
+        ))));
+        let response = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            server.handle_request(RequestId::from(1i64), request),
+        )
+        .await
+        .expect("the request timeout should stop waiting for the tool");
+
+        let ResponsePayload::Error(error) = response.payload else {
+            panic!("Expected a timeout error");
+        };
+        assert_eq!(error.code, ErrorCode::REQUEST_TIMEOUT.as_i32());
+        let reason = cleaned_up.lock().unwrap().clone();
+        assert!(reason.unwrap().contains("timed out"));
+        assert!(server
+            .cancellation_manager
+            .get_token(&RequestId::from(1i64).to_string())
+            .await
+            .is_none());
+    }
+
+    /// Client that answers `roots/list` with one root and otherwise stays quiet.
+    #[derive(Debug)]
+    struct RootsClientTransport {
+        incoming: std::collections::VecDeque<TransportMessage>,
+    }
+
+    #[async_trait]
+    impl Transport for RootsClientTransport {
+        async fn send(&mut self, message: TransportMessage) -> Result<()> {
+            if let TransportMessage::Request {
+                id,
+                request: Request::Server(request),
+            } = message
+            {
+                assert!(matches!(*request, crate::types::ServerRequest::ListRoots));
+                self.incoming
+                    .push_back(TransportMessage::Response(JSONRPCResponse::success(
+                        id,
+                        json!({"roots": [{"uri": "file:///client/project"}]}),
+                    )));
+            }
+            Ok(())
+        }
+
+        async fn receive(&mut self) -> Result<TransportMessage> {
+            match self.incoming.pop_front() {
+                Some(message) => Ok(message),
+                // Stay connected
+                None => std::future::pending().await,
+            }
         }
 
         async fn close(&mut self) -> Result<()> {
             Ok(())
         }
+    }
 
-        fn is_connected(&self) -> bool {
-            !self.messages.lock().unwrap().is_empty()
-        }
+    #[tokio::test]
+    async fn test_client_roots_are_separate_from_server_roots() {
+        let mut server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .build()
+            .unwrap();
+        let transport = Arc::new(connection::Connection::new(RootsClientTransport {
+            incoming: [TransportMessage::Notification(Notification::Client(
+                crate::types::ClientNotification::Initialized,
+            ))]
+            .into(),
+        }));
+        server.client_requests = Some(Arc::new(client_requests::ClientRequests::new(
+            transport.clone(),
+            server.client_capabilities.clone(),
+        )));
+        let server = Arc::new(server);
+
+        // The client has no roots to ask for until it advertises them
+        assert!(matches!(
+            server.refresh_client_roots().await,
+            Err(Error::UnsupportedCapability(_))
+        ));
+        *server.client_capabilities.write().await = Some(ClientCapabilities {
+            roots: Some(crate::types::RootsCapabilities { list_changed: true }),
+            ..ClientCapabilities::default()
+        });
 
-        fn transport_type(&self) -> &'static str {
-            "mock"
+        let _unregister = server
+            .register_root("file:///server/data", Some("Server".to_string()))
+            .await
+            .unwrap();
+
+        // Initialization is followed by a `roots/list` to the client
+        let messages = server.clone();
+        let transport_for_loop = transport.clone();
+        let cancel = CancellationToken::new();
+        let loop_cancel = cancel.clone();
+        let handle = tokio::spawn(async move {
+            Server::process_transport_messages(&messages, &transport_for_loop, &loop_cancel).await;
+        });
+        timeout(std::time::Duration::from_secs(5), async {
+            while server
+                .get_client_roots(sessions::CONNECTION_ID)
+                .await
+                .is_empty()
+            {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("the server should list the client's roots");
+
+        let server_roots = server.get_roots().await;
+        assert_eq!(server_roots.len(), 1);
+        assert_eq!(server_roots[0].uri, "file:///server/data");
+
+        // A roots change from the client invalidates the cached copy only
+        server
+            .handle_notification(Notification::Client(
+                crate::types::ClientNotification::RootsListChanged,
+            ))
+            .await;
+        assert!(server
+            .get_client_roots(sessions::CONNECTION_ID)
+            .await
+            .is_empty());
+        assert_eq!(server.get_roots().await.len(), 1);
+
+        let client_roots = server.refresh_client_roots().await.unwrap();
+        assert_eq!(client_roots.len(), 1);
+        assert_eq!(client_roots[0].uri, "file:///client/project");
+        assert_eq!(
+            server.get_client_roots(sessions::CONNECTION_ID).await,
+            client_roots
+        );
+        cancel.cancel();
+        handle.await.unwrap();
+    }
+
+    async fn send_argument_chunks(server: &Server, stream_id: &str, pieces: &[&str]) {
+        for (seq, data) in pieces.iter().enumerate() {
+            server
+                .handle_notification(Notification::Client(
+                    crate::types::ClientNotification::ArgumentChunk(crate::types::ArgumentChunk {
+                        stream_id: stream_id.to_string(),
+                        seq: seq as u64,
+                        data: (*data).to_string(),
+                    }),
+                ))
+                .await;
         }
     }
 
-    /// Mock tool handler for testing
-    struct MockTool {
-        result: Value,
+    fn streamed_call(stream_id: &str, chunks: u64) -> Request {
+        let mut call = CallToolRequest::new("sum", json!({}));
+        call.meta = Some(crate::types::RequestMeta {
+            argument_stream: Some(crate::types::ArgumentStream {
+                stream_id: stream_id.to_string(),
+                chunks,
+            }),
+            ..Default::default()
+        });
+        Request::Client(Box::new(ClientRequest::CallTool(call)))
+    }
+
+    #[tokio::test]
+    async fn test_streamed_arguments_are_reassembled() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("sum", SumEchoTool)
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        let contents = "x".repeat(1000);
+        let json = json!({"sum": 3, "contents": contents}).to_string();
+        let pieces: Vec<&str> = json
+            .as_bytes()
+            .chunks(100)
+            .map(|piece| std::str::from_utf8(piece).unwrap())
+            .collect();
+        send_argument_chunks(&server, "upload", &pieces).await;
+
+        let response = server
+            .handle_request(
+                RequestId::from("upload"),
+                streamed_call("upload", pieces.len() as u64),
+            )
+            .await;
+        let ResponsePayload::Result(result) = response.payload else {
+            panic!("expected a result");
+        };
+        let result: CallToolResult = serde_json::from_value(result).unwrap();
+        let crate::types::Content::Text { text } = &result.content[0] else {
+            panic!("expected text content");
+        };
+        let echoed: Value = serde_json::from_str(text).unwrap();
+        assert_eq!(echoed, json!({"sum": 3, "contents": contents}));
+
+        // Cancelling mid-stream discards the chunks received so far
+        send_argument_chunks(&server, "cancelled", &[r#"{"sum": "#]).await;
+        server
+            .handle_notification(Notification::Client(
+                crate::types::ClientNotification::Cancelled(crate::types::CancelledNotification {
+                    request_id: RequestId::from("cancelled"),
+                    reason: None,
+                }),
+            ))
+            .await;
+        let response = server
+            .handle_request(RequestId::from("cancelled"), streamed_call("cancelled", 2))
+            .await;
+        assert!(matches!(response.payload, ResponsePayload::Error(_)));
     }
 
-    impl MockTool {
-        fn new(result: Value) -> Self {
-            Self { result }
-        }
-    }
+    /// Tool that reports timing through response `_meta`
+    struct TimedTool;
 
     #[async_trait]
-    impl ToolHandler for MockTool {
+    impl ToolHandler for TimedTool {
         async fn handle(
             &self,
             _args: Value,
-            _extra: crate::server::cancellation::RequestHandlerExtra,
+            extra: crate::server::cancellation::RequestHandlerExtra,
         ) -> Result<Value> {
-            Ok(self.result.clone())
+            extra.set_response_meta(json!({"elapsedMs": 7}));
+            Ok(json!({"ok": true}))
         }
     }
 
-    /// Mock prompt handler for testing
-    struct MockPrompt {
-        result: crate::types::GetPromptResult,
+    #[tokio::test]
+    async fn test_tool_sets_response_meta() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("timed", TimedTool)
+            .tool("echo", MockTool::new(json!({"ok": true})))
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        for (id, name, expected) in [
+            (1i64, "timed", Some(json!({"elapsedMs": 7}))),
+            (2, "echo", None),
+        ] {
+            let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest {
+                name: name.to_string(),
+                arguments: json!({}),
+                meta: None,
+            })));
+            let response = server.handle_request(RequestId::from(id), request).await;
+            let ResponsePayload::Result(result) = response.payload else {
+                panic!("Expected success response");
+            };
+            assert_eq!(result.get("_meta").cloned(), expected);
+        }
     }
 
-    impl MockPrompt {
-        fn new(result: crate::types::GetPromptResult) -> Self {
-            Self { result }
-        }
+    /// Shared state counting the calls of [`CountingTool`]
+    #[derive(Default)]
+    struct CallCounter {
+        calls: std::sync::atomic::AtomicUsize,
     }
 
+    /// Tool that counts its calls in the server's state
+    struct CountingTool;
+
     #[async_trait]
-    impl PromptHandler for MockPrompt {
+    impl ToolHandler for CountingTool {
         async fn handle(
             &self,
-            _args: HashMap<String, String>,
-            _extra: crate::server::cancellation::RequestHandlerExtra,
-        ) -> Result<crate::types::GetPromptResult> {
-            Ok(self.result.clone())
+            _args: Value,
+            extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<Value> {
+            let counter = extra
+                .state::<CallCounter>()
+                .ok_or_else(|| Error::internal("no call counter"))?;
+            let calls = counter
+                .calls
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+                + 1;
+            Ok(json!({ "calls": calls }))
         }
     }
 
-    /// Mock resource handler for testing
-    struct MockResource {
-        resources: Vec<crate::types::ResourceInfo>,
-        contents: HashMap<String, crate::types::ReadResourceResult>,
-    }
-
-    impl MockResource {
-        fn new() -> Self {
-            Self {
-                resources: Vec::new(),
-                contents: HashMap::new(),
-            }
+    #[tokio::test]
+    async fn test_handlers_share_server_state() {
+        let counter = Arc::new(CallCounter::default());
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .shared_state(counter.clone())
+            .tool("count", CountingTool)
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        for id in 1..=2i64 {
+            let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest::new(
+                "count",
+                json!({}),
+            ))));
+            let response = server.handle_request(RequestId::from(id), request).await;
+            assert!(matches!(response.payload, ResponsePayload::Result(_)));
         }
+        assert_eq!(counter.calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert!(Arc::ptr_eq(server.state(), &counter));
 
-        fn with_resource(mut self, uri: String, content: crate::types::ReadResourceResult) -> Self {
-            self.contents.insert(uri, content);
-            self
-        }
+        // Without state the handler cannot find its counter
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("count", CountingTool)
+            .build()
+            .unwrap();
+        initialize(&server).await;
+        let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest::new(
+            "count",
+            json!({}),
+        ))));
+        let response = server.handle_request(RequestId::from(3i64), request).await;
+        assert!(matches!(response.payload, ResponsePayload::Error(_)));
     }
 
-    #[async_trait]
-    impl ResourceHandler for MockResource {
-        async fn read(
-            &self,
-            uri: &str,
-            _extra: crate::server::cancellation::RequestHandlerExtra,
-        ) -> Result<crate::types::ReadResourceResult> {
-            self.contents
-                .get(uri)
-                .cloned()
-                .ok_or_else(|| Error::not_found(format!("Resource '{}' not found", uri)))
-        }
-
-        async fn list(
-            &self,
-            _cursor: Option<String>,
-            _extra: crate::server::cancellation::RequestHandlerExtra,
-        ) -> Result<crate::types::ListResourcesResult> {
-            Ok(crate::types::ListResourcesResult {
-                resources: self.resources.clone(),
-                next_cursor: None,
-            })
-        }
+    fn progress(token: &str) -> ServerNotification {
+        ServerNotification::Progress(crate::types::ProgressNotification {
+            progress_token: crate::types::ProgressToken::String(token.to_string()),
+            progress: 50.0,
+            message: None,
+        })
     }
 
-    #[test]
-    fn test_server_builder() {
-        let server = Server::builder()
+    #[tokio::test]
+    async fn test_strict_notifications_suppress_unrequested_progress() {
+        let mut server = Server::builder()
             .name("test-server")
             .version("1.0.0")
-            .capabilities(ServerCapabilities::tools_only())
-            .tool("test-tool", MockTool::new(json!({"result": "success"})))
+            .strict_notifications(true)
             .build()
             .unwrap();
+        let (tx, mut rx) = mpsc::channel(10);
+        server.notification_tx = Some(tx);
+        initialize(&server).await;
 
-        assert_eq!(server.info.name, "test-server");
-        assert_eq!(server.info.version, "1.0.0");
-        assert!(server.tools.contains_key("test-tool"));
+        // No request with this token is in flight
+        server.send_notification(progress("unknown")).await;
+        assert!(rx.try_recv().is_err());
+
+        // A request ID is not a progress token
+        server
+            .create_request_extra(&RequestId::from("task-1".to_string()), None)
+            .await;
+        server.send_notification(progress("task-1")).await;
+        assert!(rx.try_recv().is_err());
+
+        // Progress with the token of an active request goes through
+        let request_id = RequestId::from("task-2".to_string());
+        server
+            .create_request_extra(
+                &request_id,
+                Some(crate::types::ProgressToken::String("job-2".to_string())),
+            )
+            .await;
+        server.send_notification(progress("job-2")).await;
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(Notification::Server(ServerNotification::Progress(_)))
+        ));
+
+        // Not anymore once the request completed
+        server
+            .cancellation_manager
+            .remove_token(&request_id.to_string())
+            .await;
+        server.send_notification(progress("job-2")).await;
+        assert!(rx.try_recv().is_err());
+
+        // Resource updates need a subscription
+        let updated = ServerNotification::ResourceUpdated(
+            crate::types::protocol::ResourceUpdatedParams::new("file:///a.txt"),
+        );
+        assert!(!server.is_notification_supported(&updated).await);
+        server
+            .subscribe_resource("file:///a.txt".to_string(), "client".to_string())
+            .await
+            .unwrap();
+        assert!(server.is_notification_supported(&updated).await);
     }
 
-    #[test]
-    fn test_server_builder_validation() {
-        // Missing name
-        let result = Server::builder().version("1.0.0").build();
-        assert!(result.is_err());
+    #[tokio::test]
+    async fn test_lenient_notifications_send_everything() {
+        let mut server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .build()
+            .unwrap();
+        let (tx, mut rx) = mpsc::channel(10);
+        server.notification_tx = Some(tx);
 
-        // Missing version
-        let result = Server::builder().name("test-server").build();
-        assert!(result.is_err());
+        assert!(!server.is_notification_supported(&progress("unknown")).await);
+        server.send_notification(progress("unknown")).await;
+        assert!(rx.try_recv().is_ok());
     }
 
     #[tokio::test]
-    async fn test_server_initialization() {
-        let init_request = TransportMessage::Request {
-            id: RequestId::from(1i64),
-            request: Request::Client(Box::new(ClientRequest::Initialize(InitializeRequest {
-                protocol_version: "2024-11-05".to_string(),
-                capabilities: ClientCapabilities {
-                    tools: Some(ToolCapabilities::default()),
-                    ..Default::default()
-                },
-                client_info: Implementation {
-                    name: "test-client".to_string(),
-                    version: "1.0.0".to_string(),
-                },
-            }))),
+    async fn test_progress_flood_is_coalesced() {
+        let mut server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .coalesce_progress(true)
+            .build()
+            .unwrap();
+        let (tx, rx) = mpsc::channel(NOTIFICATION_CAPACITY);
+        server.notification_tx = Some(tx);
+
+        // Nobody drains the channel, yet the tool is never blocked
+        let flood = async {
+            for i in 0..10_000 {
+                server
+                    .send_notification(ServerNotification::Progress(
+                        crate::types::ProgressNotification {
+                            progress_token: crate::types::ProgressToken::String("task".into()),
+                            progress: f64::from(i),
+                            message: None,
+                        },
+                    ))
+                    .await;
+            }
         };
+        timeout(std::time::Duration::from_secs(5), flood)
+            .await
+            .expect("progress should not block on a full channel");
+        let queued = rx.len();
+        assert!(queued < NOTIFICATION_CAPACITY, "{}", queued);
+
+        let transport = MockTransport::new();
+        let sent = transport.responses.clone();
+        Server::<()>::spawn_notification_handler(
+            Arc::new(connection::Connection::new(transport)),
+            rx,
+            server.progress_backlog.clone(),
+            server.events.clone(),
+            CancellationToken::new(),
+        );
 
-        let transport = MockTransport::with_requests(vec![init_request]);
-        let server = Server::builder()
+        // Queued progress is sent, then the latest held-back value
+        let latest = |sent: &[TransportMessage]| match sent.last() {
+            Some(TransportMessage::Notification(Notification::Server(
+                ServerNotification::Progress(progress),
+            ))) => progress.progress,
+            _ => -1.0,
+        };
+        timeout(std::time::Duration::from_secs(5), async {
+            while (latest(&sent.lock().unwrap()) - 9_999.0).abs() > f64::EPSILON {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("latest progress should be delivered");
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), queued + 1);
+    }
+
+    /// Tool reporting its progress halfway and when done.
+    struct ProgressTool;
+
+    #[async_trait]
+    impl ToolHandler for ProgressTool {
+        async fn handle(
+            &self,
+            _args: Value,
+            extra: cancellation::RequestHandlerExtra,
+        ) -> Result<Value> {
+            extra
+                .report_progress(0.5, Some("Halfway".to_string()))
+                .await;
+            extra.report_progress(1.0, None).await;
+            Ok(json!({"done": true}))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_reports_progress_with_request_token() {
+        let mut server = Server::builder()
             .name("test-server")
             .version("1.0.0")
-            .capabilities(ServerCapabilities::tools_only())
+            .tool("import", ProgressTool)
             .build()
             .unwrap();
+        let (tx, rx) = mpsc::channel(10);
+        server.notification_tx = Some(tx);
+        initialize(&server).await;
+
+        let transport = MockTransport::new();
+        let sent = transport.responses.clone();
+        Server::<()>::spawn_notification_handler(
+            Arc::new(connection::Connection::new(transport)),
+            rx,
+            server.progress_backlog.clone(),
+            server.events.clone(),
+            CancellationToken::new(),
+        );
 
-        // Test server run for a short time
-        let server_handle = tokio::spawn(async move {
-            let _ = timeout(std::time::Duration::from_millis(100), server.run(transport)).await;
+        // Without a token the client gets no progress
+        let response = server
+            .handle_request(
+                RequestId::from(1i64),
+                Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest::new(
+                    "import",
+                    json!({}),
+                )))),
+            )
+            .await;
+        assert!(matches!(response.payload, ResponsePayload::Result(_)));
+
+        let mut call = CallToolRequest::new("import", json!({}));
+        call.meta = Some(crate::types::RequestMeta {
+            progress_token: Some(crate::types::ProgressToken::String("import-1".to_string())),
+            ..Default::default()
         });
+        let response = server
+            .handle_request(
+                RequestId::from(2i64),
+                Request::Client(Box::new(ClientRequest::CallTool(call))),
+            )
+            .await;
+        assert!(matches!(response.payload, ResponsePayload::Result(_)));
 
-        // Wait for server to process
-        let _ = timeout(std::time::Duration::from_millis(200), server_handle).await;
+        timeout(std::time::Duration::from_secs(5), async {
+            while sent.lock().unwrap().len() < 2 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("progress should be sent");
+        let sent = sent.lock().unwrap();
+        let progress: Vec<_> = sent
+            .iter()
+            .map(|message| match message {
+                TransportMessage::Notification(Notification::Server(
+                    ServerNotification::Progress(progress),
+                )) => progress,
+                other => panic!("Expected progress, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(progress.len(), 2);
+        for progress in &progress {
+            assert_eq!(
+                progress.progress_token,
+                crate::types::ProgressToken::String("import-1".to_string())
+            );
+        }
+        assert!((progress[0].progress - 0.5).abs() < f64::EPSILON);
+        assert_eq!(progress[0].message.as_deref(), Some("Halfway"));
+        assert!((progress[1].progress - 1.0).abs() < f64::EPSILON);
     }
 
     #[tokio::test]
-    async fn test_server_capabilities() {
-        let server = Server::builder()
+    async fn test_held_progress_is_dropped_when_the_request_completes() {
+        let mut server = Server::builder()
             .name("test-server")
             .version("1.0.0")
-            .capabilities(ServerCapabilities::tools_only())
+            .tool("import", MockTool::new(json!({"done": true})))
+            .coalesce_progress(true)
             .build()
             .unwrap();
+        // A client that is not reading keeps the channel congested
+        let (tx, _rx) = mpsc::channel(COALESCE_PROGRESS_DIVISOR);
+        for _ in 0..COALESCE_PROGRESS_DIVISOR {
+            tx.send(Notification::Server(ServerNotification::ToolsChanged))
+                .await
+                .unwrap();
+        }
+        server.notification_tx = Some(tx);
+        initialize(&server).await;
 
-        assert!(!server.is_initialized().await);
-        assert!(server.get_client_capabilities().await.is_none());
+        let token = crate::types::ProgressToken::String("import-1".to_string());
+        server
+            .send_notification(ServerNotification::Progress(
+                crate::types::ProgressNotification {
+                    progress_token: token.clone(),
+                    progress: 0.5,
+                    message: None,
+                },
+            ))
+            .await;
+
+        let mut call = CallToolRequest::new("import", json!({}));
+        call.meta = Some(crate::types::RequestMeta {
+            progress_token: Some(token),
+            ..Default::default()
+        });
+        let response = server
+            .handle_request(
+                RequestId::from(1i64),
+                Request::Client(Box::new(ClientRequest::CallTool(call))),
+            )
+            .await;
+        assert!(matches!(response.payload, ResponsePayload::Result(_)));
+        assert!(server.progress_backlog.take().is_empty());
+    }
+
+    /// Prompt describing itself with the progress token it was given
+    struct ProgressTokenPrompt;
+
+    #[async_trait]
+    impl PromptHandler for ProgressTokenPrompt {
+        async fn handle(
+            &self,
+            _args: HashMap<String, String>,
+            extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<crate::types::GetPromptResult> {
+            Ok(crate::types::GetPromptResult {
+                description: Some(format!("{:?}", extra.progress_token)),
+                messages: vec![],
+            })
+        }
     }
 
     #[tokio::test]
-    async fn test_server_notifications() {
+    async fn test_prompt_receives_request_progress_token() {
         let server = Server::builder()
             .name("test-server")
             .version("1.0.0")
+            .prompt("summarize", ProgressTokenPrompt)
             .build()
             .unwrap();
+        initialize(&server).await;
 
-        // Send notification (should not panic even without transport)
-        server
-            .send_notification(ServerNotification::ToolsChanged)
+        let request: ClientRequest = serde_json::from_value(json!({
+            "method": "prompts/get",
+            "params": {
+                "name": "summarize",
+                "_meta": { "progressToken": "summary-1" }
+            }
+        }))
+        .unwrap();
+        let response = server
+            .handle_request(RequestId::from(1i64), Request::Client(Box::new(request)))
             .await;
+        let ResponsePayload::Result(result) = response.payload else {
+            panic!("Expected success response");
+        };
+        assert_eq!(result["description"], r#"Some(String("summary-1"))"#);
     }
 
-    #[test]
-    fn test_server_builder_with_all_handlers() {
-        let prompt_result = crate::types::GetPromptResult {
-            description: Some("Test prompt".to_string()),
-            messages: vec![],
+    async fn call_echo(server: &Server, arguments: Value) -> Value {
+        let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest {
+            name: "search".to_string(),
+            arguments,
+            meta: None,
+        })));
+        let response = server.handle_request(RequestId::from(1i64), request).await;
+        let ResponsePayload::Result(result) = response.payload else {
+            panic!("Expected success response");
         };
-
-        let resource_content = crate::types::ReadResourceResult {
-            contents: vec![crate::types::Content::Text {
-                text: "Hello, world!".to_string(),
-            }],
+        let call_result: CallToolResult = serde_json::from_value(result).unwrap();
+        let crate::types::Content::Text { text } = &call_result.content[0] else {
+            panic!("Expected text content");
         };
+        serde_json::from_str(text).unwrap()
+    }
 
+    #[tokio::test]
+    async fn test_schema_defaults_injected() {
         let server = Server::builder()
             .name("test-server")
             .version("1.0.0")
-            .tool("test-tool", MockTool::new(json!({"result": "success"})))
-            .prompt("test-prompt", MockPrompt::new(prompt_result))
-            .resources(
-                MockResource::new().with_resource("test://uri".to_string(), resource_content),
-            )
+            .tool("search", SchemaEchoTool)
+            .apply_schema_defaults(true)
             .build()
             .unwrap();
+        initialize(&server).await;
 
-        assert!(server.tools.contains_key("test-tool"));
-        assert!(server.prompts.contains_key("test-prompt"));
-        assert!(server.resources.is_some());
+        let args = call_echo(&server, json!({"query": "rust", "options": {}})).await;
+        assert_eq!(
+            args,
+            json!({"query": "rust", "limit": 10, "options": {"fuzzy": false}})
+        );
+
+        // Supplied values win over defaults
+        let args = call_echo(&server, json!({"query": "rust", "limit": 3})).await;
+        assert_eq!(args["limit"], 3);
+
+        // Missing arguments still receive defaults
+        let args = call_echo(&server, Value::Null).await;
+        assert_eq!(args, json!({"limit": 10}));
     }
 
     #[tokio::test]
-    async fn test_handle_request_initialize() {
+    async fn test_schema_defaults_disabled_by_default() {
         let server = Server::builder()
             .name("test-server")
             .version("1.0.0")
-            .capabilities(ServerCapabilities::tools_only())
+            .tool("search", SchemaEchoTool)
             .build()
             .unwrap();
+        initialize(&server).await;
 
-        let request = Request::Client(Box::new(ClientRequest::Initialize(InitializeRequest {
-            protocol_version: "2024-11-05".to_string(),
-            capabilities: ClientCapabilities::default(),
-            client_info: Implementation {
-                name: "test-client".to_string(),
-                version: "1.0.0".to_string(),
-            },
-        })));
+        let args = call_echo(&server, json!({"query": "rust"})).await;
+        assert_eq!(args, json!({"query": "rust"}));
+    }
 
-        let response = server.handle_request(RequestId::from(1i64), request).await;
+    /// Tool that waits for cancellation and reports the reason it observed
+    struct CancelAwareTool;
 
-        assert_eq!(response.id, RequestId::from(1i64));
-        match response.payload {
-            ResponsePayload::Result(_) => {
-                assert!(server.is_initialized().await);
-            },
-            ResponsePayload::Error(_) => panic!("Expected success response"),
+    #[async_trait]
+    impl ToolHandler for CancelAwareTool {
+        async fn handle(
+            &self,
+            _args: Value,
+            extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<Value> {
+            extra.cancelled().await;
+            Ok(json!({ "reason": extra.cancellation_reason() }))
         }
     }
 
     #[tokio::test]
-    async fn test_handle_list_tools() {
+    async fn test_cancellation_reason_reaches_handler() {
         let server = Server::builder()
             .name("test-server")
             .version("1.0.0")
-            .tool("test-tool", MockTool::new(json!({"result": "success"})))
+            .tool("wait", CancelAwareTool)
             .build()
             .unwrap();
+        initialize(&server).await;
+        let cancellation = server.cancellation_manager.clone();
+        let mut client = ChannelClient::connect(server);
+
+        client
+            .request(
+                7,
+                ClientRequest::CallTool(CallToolRequest::new("wait", json!({}))),
+            )
+            .await;
+        while cancellation.get_token("7").await.is_none() {
+            tokio::task::yield_now().await;
+        }
+        client
+            .notify(crate::types::ClientNotification::Cancelled(
+                crate::types::CancelledNotification {
+                    request_id: RequestId::from(7i64),
+                    reason: Some("user pressed stop".to_string()),
+                },
+            ))
+            .await;
 
-        let request = Request::Client(Box::new(ClientRequest::ListTools(ListToolsRequest {
-            cursor: None,
-        })));
-        let response = server.handle_request(RequestId::from(1i64), request).await;
-
+        let response = client.response().await;
+        assert_eq!(response.id, RequestId::from(7i64));
         match response.payload {
             ResponsePayload::Result(result) => {
-                let tools_result: ListToolsResult = serde_json::from_value(result).unwrap();
-                assert_eq!(tools_result.tools.len(), 1);
-                assert_eq!(tools_result.tools[0].name, "test-tool");
+                let call_result: CallToolResult = serde_json::from_value(result).unwrap();
+                let crate::types::Content::Text { text } = &call_result.content[0] else {
+                    panic!("Expected text content");
+                };
+                assert!(text.contains("user pressed stop"));
             },
             ResponsePayload::Error(_) => panic!("Expected success response"),
         }
     }
 
+    /// Tool that produces two chunks and then hangs, offering them if cancelled
+    struct PartialTool;
+
+    #[async_trait]
+    impl ToolHandler for PartialTool {
+        async fn handle(
+            &self,
+            _args: Value,
+            extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<Value> {
+            let chunks = Arc::new(std::sync::Mutex::new(Vec::new()));
+            extra.on_cancel({
+                let chunks = chunks.clone();
+                move || ToolResult::text(chunks.lock().unwrap().join(" ")).error("cancelled")
+            });
+            chunks.lock().unwrap().extend(["first", "second"]);
+            std::future::pending().await
+        }
+    }
+
     #[tokio::test]
-    async fn test_handle_call_tool() {
+    async fn test_cancelled_tool_returns_partial_result() {
         let server = Server::builder()
             .name("test-server")
             .version("1.0.0")
-            .tool("test-tool", MockTool::new(json!({"result": "success"})))
+            .tool("partial", PartialTool)
             .build()
             .unwrap();
+        initialize(&server).await;
+        let cancellation = server.cancellation_manager.clone();
+        let mut client = ChannelClient::connect(server);
+
+        client
+            .request(
+                7,
+                ClientRequest::CallTool(CallToolRequest::new("partial", json!({}))),
+            )
+            .await;
+        while cancellation.get_token("7").await.is_none() {
+            tokio::task::yield_now().await;
+        }
+        client
+            .notify(crate::types::ClientNotification::Cancelled(
+                crate::types::CancelledNotification {
+                    request_id: RequestId::from(7i64),
+                    reason: None,
+                },
+            ))
+            .await;
 
-        let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest {
-            name: "test-tool".to_string(),
-            arguments: json!({"input": "test"}),
-        })));
+        let response = client.response().await;
+        assert_eq!(response.id, RequestId::from(7i64));
+        let ResponsePayload::Result(result) = response.payload else {
+            panic!("Expected success response");
+        };
+        let call_result: CallToolResult = serde_json::from_value(result).unwrap();
+        assert!(call_result.is_error);
+        assert!(matches!(
+            &call_result.content[..],
+            [crate::types::Content::Text { text }, crate::types::Content::Text { text: reason }]
+                if text == "first second" && reason == "cancelled"
+        ));
+    }
 
-        let response = server.handle_request(RequestId::from(1i64), request).await;
+    /// Tool that blocks until released
+    struct SlowTool {
+        release: Arc<tokio::sync::Notify>,
+    }
 
-        match response.payload {
-            ResponsePayload::Result(result) => {
-                let call_result: CallToolResult = serde_json::from_value(result).unwrap();
-                assert!(!call_result.is_error);
-                assert_eq!(call_result.content.len(), 1);
-            },
-            ResponsePayload::Error(_) => panic!("Expected success response"),
+    #[async_trait]
+    impl ToolHandler for SlowTool {
+        async fn handle(
+            &self,
+            _args: Value,
+            _extra: crate::server::cancellation::RequestHandlerExtra,
+        ) -> Result<Value> {
+            self.release.notified().await;
+            Ok(json!({"done": true}))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_priority_dispatch_answers_ping_during_slow_tool() {
+        let release = Arc::new(tokio::sync::Notify::new());
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool(
+                "slow",
+                SlowTool {
+                    release: release.clone(),
+                },
+            )
+            .priority_dispatch(priority::PriorityConfig {
+                max_concurrent: 1,
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+        initialize(&server).await;
+        let cancellation = server.cancellation_manager.clone();
+        let mut client = ChannelClient::connect(server);
+
+        let call = || ClientRequest::CallTool(CallToolRequest::new("slow", json!({})));
+        client.request(1, call()).await;
+        while cancellation.get_token("1").await.is_none() {
+            tokio::task::yield_now().await;
+        }
+        client.request(2, call()).await;
+
+        // The ping overtakes both calls, the second of which waits for the slot
+        client.request(3, ClientRequest::Ping).await;
+        let response = timeout(std::time::Duration::from_secs(1), client.response())
+            .await
+            .expect("ping should not wait behind the slow tool");
+        assert_eq!(response.id, RequestId::from(3i64));
+        assert!(matches!(response.payload, ResponsePayload::Result(_)));
+        assert!(cancellation.get_token("2").await.is_none());
+
+        // Each release lets one slow call finish, then hands the slot on
+        release.notify_one();
+        assert_eq!(client.response().await.id, RequestId::from(1i64));
+        while cancellation.get_token("2").await.is_none() {
+            tokio::task::yield_now().await;
         }
+        release.notify_one();
+        let response = client.response().await;
+        assert_eq!(response.id, RequestId::from(2i64));
+        assert!(matches!(response.payload, ResponsePayload::Result(_)));
     }
 
     #[tokio::test]
@@ -1790,9 +6729,11 @@ mod tests {
             .build()
             .unwrap();
 
+        initialize(&server).await;
         let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest {
             name: "nonexistent-tool".to_string(),
             arguments: json!({}),
+            meta: None,
         })));
 
         let response = server.handle_request(RequestId::from(1i64), request).await;
@@ -1819,6 +6760,7 @@ mod tests {
             .build()
             .unwrap();
 
+        initialize(&server).await;
         let request = Request::Client(Box::new(ClientRequest::ListPrompts(ListPromptsRequest {
             cursor: None,
         })));
@@ -1848,9 +6790,11 @@ mod tests {
             .build()
             .unwrap();
 
+        initialize(&server).await;
         let request = Request::Client(Box::new(ClientRequest::GetPrompt(GetPromptRequest {
             name: "test-prompt".to_string(),
             arguments: HashMap::new(),
+            meta: None,
         })));
 
         let response = server.handle_request(RequestId::from(1i64), request).await;
@@ -1871,6 +6815,8 @@ mod tests {
             contents: vec![crate::types::Content::Text {
                 text: "Hello, world!".to_string(),
             }],
+            content_hash: None,
+            not_modified: false,
         };
 
         let server = Server::builder()
@@ -1882,8 +6828,9 @@ mod tests {
             .build()
             .unwrap();
 
+        initialize(&server).await;
         let request = Request::Client(Box::new(ClientRequest::ListResources(
-            ListResourcesRequest { cursor: None },
+            ListResourcesRequest::default(),
         )));
         let response = server.handle_request(RequestId::from(1i64), request).await;
 
@@ -1896,12 +6843,47 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_handle_list_resource_templates() {
+        let mut resources = MockResource::new();
+        resources.templates.push(crate::types::ResourceTemplate {
+            uri_template: "file:///{path}".to_string(),
+            name: "file".to_string(),
+            description: None,
+            mime_type: Some("text/plain".to_string()),
+        });
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .resources(resources)
+            .build()
+            .unwrap();
+
+        initialize(&server).await;
+        let request = Request::Client(Box::new(ClientRequest::ListResourceTemplates(
+            ListResourceTemplatesRequest::default(),
+        )));
+        let response = server.handle_request(RequestId::from(1i64), request).await;
+
+        let ResponsePayload::Result(result) = response.payload else {
+            panic!("Expected success response");
+        };
+        let templates: ListResourceTemplatesResult = serde_json::from_value(result).unwrap();
+        assert_eq!(templates.resource_templates.len(), 1);
+        assert_eq!(
+            templates.resource_templates[0].uri_template,
+            "file:///{path}"
+        );
+    }
+
     #[tokio::test]
     async fn test_handle_read_resource() {
         let resource_content = crate::types::ReadResourceResult {
             contents: vec![crate::types::Content::Text {
                 text: "Hello, world!".to_string(),
             }],
+            content_hash: None,
+            not_modified: false,
         };
 
         let server = Server::builder()
@@ -1914,9 +6896,10 @@ mod tests {
             .build()
             .unwrap();
 
-        let request = Request::Client(Box::new(ClientRequest::ReadResource(ReadResourceRequest {
-            uri: "test://uri".to_string(),
-        })));
+        initialize(&server).await;
+        let request = Request::Client(Box::new(ClientRequest::ReadResource(
+            ReadResourceRequest::new("test://uri".to_string()),
+        )));
 
         let response = server.handle_request(RequestId::from(1i64), request).await;
 
@@ -1930,6 +6913,57 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_conditional_read_resource() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .resources(MockResource::new().with_resource(
+                "test://uri".to_string(),
+                crate::types::ReadResourceResult::new(vec![crate::types::Content::Text {
+                    text: "Hello, world!".to_string(),
+                }]),
+            ))
+            .build()
+            .unwrap();
+        initialize(&server).await;
+
+        let read = |known_hash: &str| {
+            Request::Client(Box::new(ClientRequest::ReadResource(ReadResourceRequest {
+                uri: "test://uri".to_string(),
+                meta: Some(crate::types::RequestMeta {
+                    if_none_match: Some(known_hash.to_string()),
+                    ..Default::default()
+                }),
+            })))
+        };
+        let result = |response: JSONRPCResponse| -> crate::types::ReadResourceResult {
+            match response.payload {
+                ResponsePayload::Result(result) => serde_json::from_value(result).unwrap(),
+                ResponsePayload::Error(error) => panic!("Unexpected error: {}", error.message),
+            }
+        };
+
+        let current = crate::types::content_hash(b"Hello, world!");
+        let modified = result(
+            server
+                .handle_request(RequestId::from(1i64), read("sha256:stale"))
+                .await,
+        );
+        assert!(!modified.not_modified);
+        assert_eq!(modified.contents.len(), 1);
+        assert_eq!(modified.content_hash.as_ref(), Some(&current));
+
+        let unchanged = result(
+            server
+                .handle_request(RequestId::from(2i64), read(&current))
+                .await,
+        );
+        assert!(unchanged.not_modified);
+        assert!(unchanged.contents.is_empty());
+        assert_eq!(unchanged.content_hash, Some(current));
+    }
+
     #[tokio::test]
     async fn test_handle_read_resource_not_found() {
         let server = Server::builder()
@@ -1939,9 +6973,10 @@ mod tests {
             .build()
             .unwrap();
 
-        let request = Request::Client(Box::new(ClientRequest::ReadResource(ReadResourceRequest {
-            uri: "nonexistent://uri".to_string(),
-        })));
+        initialize(&server).await;
+        let request = Request::Client(Box::new(ClientRequest::ReadResource(
+            ReadResourceRequest::new("nonexistent://uri".to_string()),
+        )));
 
         let response = server.handle_request(RequestId::from(1i64), request).await;
 
@@ -2002,4 +7037,442 @@ mod tests {
             ResponsePayload::Result(_) => panic!("Expected error response"),
         }
     }
+
+    #[tokio::test]
+    async fn test_response_ids_preserve_type() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .build()
+            .unwrap();
+
+        for raw_id in [json!(1), json!("1"), json!("init-1"), json!(0)] {
+            let id: RequestId = serde_json::from_value(raw_id.clone()).unwrap();
+            let requests = [
+                // Initialize succeeds once, then fails as a duplicate
+                Request::Client(Box::new(ClientRequest::Initialize(InitializeRequest {
+                    protocol_version: "2024-11-05".to_string(),
+                    capabilities: ClientCapabilities::default(),
+                    client_info: Implementation {
+                        name: "test-client".to_string(),
+                        version: "1.0.0".to_string(),
+                    },
+                }))),
+                Request::Client(Box::new(ClientRequest::Ping)),
+                Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest {
+                    name: "missing".to_string(),
+                    arguments: json!({}),
+                    meta: None,
+                }))),
+                Request::Server(Box::new(crate::types::ServerRequest::ListRoots)),
+            ];
+
+            for request in requests {
+                let response = server.handle_request(id.clone(), request).await;
+                assert_eq!(response.id, id);
+                assert_eq!(serde_json::to_value(&response).unwrap()["id"], raw_id);
+            }
+        }
+    }
+
+    /// Transport that parses raw frames the way the stdio transport does.
+    #[derive(Debug)]
+    struct FrameTransport {
+        frames: std::collections::VecDeque<&'static [u8]>,
+        sent: Arc<Mutex<Vec<TransportMessage>>>,
+    }
+
+    #[async_trait]
+    impl Transport for FrameTransport {
+        async fn send(&mut self, message: TransportMessage) -> Result<()> {
+            self.sent.lock().unwrap().push(message);
+            Ok(())
+        }
+
+        async fn receive(&mut self) -> Result<TransportMessage> {
+            let frame = self
+                .frames
+                .pop_front()
+                .ok_or(TransportError::ConnectionClosed)?;
+            crate::shared::StdioTransport::parse_message(frame)
+        }
+
+        async fn close(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_malformed_frames_do_not_close_connection() {
+        let server = Arc::new(
+            Server::builder()
+                .name("test-server")
+                .version("1.0.0")
+                .build()
+                .unwrap(),
+        );
+        initialize(&server).await;
+
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let transport = Arc::new(connection::Connection::new(FrameTransport {
+            frames: [
+                &b"not json at all"[..],
+                br#"{"jsonrpc":"2.0","id":7,"method":"tools/call","params":"bad"}"#,
+                br#"{"jsonrpc":"2.0","id":8,"method":"ping"}"#,
+            ]
+            .into(),
+            sent: sent.clone(),
+        }));
+
+        timeout(
+            std::time::Duration::from_secs(5),
+            Server::process_transport_messages(&server, &transport, &CancellationToken::new()),
+        )
+        .await
+        .expect("loop should stop once the connection closes");
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        match &sent[0] {
+            TransportMessage::Response(JSONRPCResponse {
+                id,
+                payload: ResponsePayload::Error(error),
+                ..
+            }) => {
+                assert_eq!(*id, RequestId::from(7i64));
+                assert_eq!(error.code, ErrorCode::PARSE_ERROR.as_i32());
+            },
+            other => panic!("Expected parse error response, got {:?}", other),
+        }
+        match &sent[1] {
+            TransportMessage::Response(JSONRPCResponse {
+                id,
+                payload: ResponsePayload::Result(_),
+                ..
+            }) => assert_eq!(*id, RequestId::from(8i64)),
+            other => panic!("Expected ping response, got {:?}", other),
+        }
+    }
+
+    /// Transport fed through a channel, disconnected once the sender is dropped.
+    #[derive(Debug)]
+    struct ChannelTransport {
+        incoming: mpsc::Receiver<TransportMessage>,
+        sent: mpsc::UnboundedSender<TransportMessage>,
+        closed: Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    #[async_trait]
+    impl Transport for ChannelTransport {
+        async fn send(&mut self, message: TransportMessage) -> Result<()> {
+            let _ = self.sent.send(message);
+            Ok(())
+        }
+
+        async fn receive(&mut self) -> Result<TransportMessage> {
+            self.incoming
+                .recv()
+                .await
+                .ok_or_else(|| TransportError::ConnectionClosed.into())
+        }
+
+        async fn close(&mut self) -> Result<()> {
+            self.closed.store(true, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    /// Client side of a server running over a [`ChannelTransport`].
+    struct ChannelClient {
+        incoming: mpsc::Sender<TransportMessage>,
+        sent: mpsc::UnboundedReceiver<TransportMessage>,
+    }
+
+    impl ChannelClient {
+        /// Run `server` until the client is dropped.
+        fn connect<S: Send + Sync + 'static>(server: Server<S>) -> Self {
+            let (incoming_tx, incoming) = mpsc::channel(16);
+            let (sent, sent_rx) = mpsc::unbounded_channel();
+            tokio::spawn(server.run(ChannelTransport {
+                incoming,
+                sent,
+                closed: Arc::default(),
+            }));
+            Self {
+                incoming: incoming_tx,
+                sent: sent_rx,
+            }
+        }
+
+        async fn request(&self, id: i64, request: ClientRequest) {
+            self.incoming
+                .send(TransportMessage::Request {
+                    id: RequestId::from(id),
+                    request: Request::Client(Box::new(request)),
+                })
+                .await
+                .unwrap();
+        }
+
+        async fn notify(&self, notification: crate::types::ClientNotification) {
+            self.incoming
+                .send(TransportMessage::Notification(Notification::Client(
+                    notification,
+                )))
+                .await
+                .unwrap();
+        }
+
+        /// The next response the server sends, skipping its notifications.
+        async fn response(&mut self) -> JSONRPCResponse {
+            timeout(std::time::Duration::from_secs(5), async {
+                loop {
+                    match self.sent.recv().await {
+                        Some(TransportMessage::Response(response)) => return response,
+                        Some(_) => {},
+                        None => panic!("Server stopped"),
+                    }
+                }
+            })
+            .await
+            .expect("server should respond")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_with_shutdown_closes_transport() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .build()
+            .unwrap();
+        let (incoming_tx, incoming) = mpsc::channel(1);
+        let (sent, mut sent_rx) = mpsc::unbounded_channel();
+        let closed = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let transport = ChannelTransport {
+            incoming,
+            sent,
+            closed: closed.clone(),
+        };
+        let shutdown = CancellationToken::new();
+        let running = tokio::spawn(server.run_with_shutdown(transport, shutdown.clone()));
+
+        incoming_tx
+            .send(TransportMessage::Request {
+                id: RequestId::from(1i64),
+                request: Request::Client(Box::new(ClientRequest::Ping)),
+            })
+            .await
+            .unwrap();
+        let response = timeout(std::time::Duration::from_secs(5), sent_rx.recv())
+            .await
+            .unwrap();
+        assert!(matches!(
+            response,
+            Some(TransportMessage::Response(JSONRPCResponse { ref id, .. })) if *id == RequestId::from(1i64)
+        ));
+        assert!(!closed.load(std::sync::atomic::Ordering::SeqCst));
+
+        shutdown.cancel();
+        timeout(std::time::Duration::from_secs(5), running)
+            .await
+            .expect("server should stop once shut down")
+            .unwrap()
+            .unwrap();
+        assert!(closed.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_run_returns_once_the_client_disconnects() {
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .build()
+            .unwrap();
+        let (incoming_tx, incoming) = mpsc::channel(1);
+        let (sent, _sent_rx) = mpsc::unbounded_channel();
+        let closed = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let transport = ChannelTransport {
+            incoming,
+            sent,
+            closed: closed.clone(),
+        };
+        let running = tokio::spawn(server.run(transport));
+
+        drop(incoming_tx);
+        timeout(std::time::Duration::from_secs(5), running)
+            .await
+            .expect("server should stop once the client is gone")
+            .unwrap()
+            .unwrap();
+        assert!(closed.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    /// Tool that asks the client's LLM for its answer.
+    struct SamplingTool;
+
+    #[async_trait]
+    impl ToolHandler for SamplingTool {
+        async fn handle(
+            &self,
+            args: Value,
+            extra: cancellation::RequestHandlerExtra,
+        ) -> Result<Value> {
+            let result = extra
+                .create_message(crate::types::CreateMessageParams {
+                    messages: vec![crate::types::SamplingMessage {
+                        role: crate::types::Role::User,
+                        content: crate::types::Content::Text {
+                            text: args["question"].as_str().unwrap_or_default().to_string(),
+                        },
+                    }],
+                    model_preferences: None,
+                    system_prompt: None,
+                    include_context: crate::types::IncludeContext::default(),
+                    temperature: None,
+                    max_tokens: Some(50),
+                    stop_sequences: None,
+                    metadata: None,
+                })
+                .await?;
+            Ok(json!({"model": result.model, "answer": result.content}))
+        }
+    }
+
+    /// Client that answers sampling requests, sending a ping before each answer.
+    #[derive(Debug)]
+    struct SamplingClientTransport {
+        incoming: std::collections::VecDeque<TransportMessage>,
+        sent: Arc<Mutex<Vec<TransportMessage>>>,
+    }
+
+    #[async_trait]
+    impl Transport for SamplingClientTransport {
+        async fn send(&mut self, message: TransportMessage) -> Result<()> {
+            if let TransportMessage::Request {
+                id,
+                request: Request::Server(request),
+            } = &message
+            {
+                assert!(matches!(
+                    **request,
+                    crate::types::ServerRequest::CreateMessage(_)
+                ));
+                self.incoming.push_back(TransportMessage::Request {
+                    id: RequestId::from(2i64),
+                    request: Request::Client(Box::new(ClientRequest::Ping)),
+                });
+                self.incoming
+                    .push_back(TransportMessage::Response(JSONRPCResponse {
+                        jsonrpc: "2.0".to_string(),
+                        id: id.clone(),
+                        payload: ResponsePayload::Result(json!({
+                            "content": {"type": "text", "text": "42"},
+                            "model": "test-model"
+                        })),
+                    }));
+            }
+            self.sent.lock().unwrap().push(message);
+            Ok(())
+        }
+
+        async fn receive(&mut self) -> Result<TransportMessage> {
+            if let Some(message) = self.incoming.pop_front() {
+                return Ok(message);
+            }
+            // Stay connected until the tool result and the ping response are sent
+            if self.sent.lock().unwrap().len() < 3 {
+                std::future::pending::<()>().await;
+            }
+            Err(TransportError::ConnectionClosed.into())
+        }
+
+        async fn close(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_samples_from_client() {
+        let mut server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool("ask", SamplingTool)
+            .build()
+            .unwrap();
+
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let transport = Arc::new(connection::Connection::new(SamplingClientTransport {
+            incoming: [TransportMessage::Request {
+                id: RequestId::from(1i64),
+                request: Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest::new(
+                    "ask",
+                    json!({"question": "meaning of life?"}),
+                )))),
+            }]
+            .into(),
+            sent: sent.clone(),
+        }));
+        server.client_requests = Some(Arc::new(client_requests::ClientRequests::new(
+            transport.clone(),
+            server.client_capabilities.clone(),
+        )));
+        let server = Arc::new(server);
+        initialize(&server).await;
+
+        // Without the sampling capability the tool fails instead of asking
+        let response = server
+            .handle_request(
+                RequestId::from(1i64),
+                Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest::new(
+                    "ask",
+                    json!({}),
+                )))),
+            )
+            .await;
+        assert!(matches!(response.payload, ResponsePayload::Error(_)));
+
+        *server.client_capabilities.write().await = Some(ClientCapabilities {
+            sampling: Some(crate::types::SamplingCapabilities::default()),
+            ..ClientCapabilities::default()
+        });
+        timeout(
+            std::time::Duration::from_secs(5),
+            Server::process_transport_messages(&server, &transport, &CancellationToken::new()),
+        )
+        .await
+        .expect("loop should stop once the connection closes");
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 3, "{:?}", sent);
+        assert!(matches!(
+            &sent[0],
+            TransportMessage::Request {
+                request: Request::Server(_),
+                ..
+            }
+        ));
+        // The ping that arrived while the tool waited is answered too
+        let response = |wanted: i64| {
+            sent[1..]
+                .iter()
+                .find_map(|message| match message {
+                    TransportMessage::Response(response)
+                        if response.id == RequestId::from(wanted) =>
+                    {
+                        Some(response.payload.clone())
+                    },
+                    _ => None,
+                })
+                .unwrap_or_else(|| panic!("No response to {} in {:?}", wanted, sent))
+        };
+        let ResponsePayload::Result(result) = response(1) else {
+            panic!("Expected tool result");
+        };
+        let text = result["content"][0]["text"].as_str().unwrap();
+        let answer: Value = serde_json::from_str(text).unwrap();
+        assert_eq!(answer["model"], "test-model");
+        assert_eq!(answer["answer"]["text"], "42");
+        assert!(matches!(response(2), ResponsePayload::Result(_)));
+    }
 }
@@ -0,0 +1,451 @@
+//! Tools defined by manifests in a plugin directory.
+//!
+//! [`ServerBuilder::load_plugins`](super::ServerBuilder::load_plugins) registers a
+//! [`PluginTool`] for every `*.json` manifest in a directory, so a server can be
+//! extended without recompiling it. A manifest names the tool, describes its
+//! arguments and gives the command handling it:
+//!
+//! ```json
+//! {
+//!   "name": "word_count",
+//!   "description": "Count the words in a text",
+//!   "inputSchema": {"type": "object", "properties": {"text": {"type": "string"}}},
+//!   "command": ["python3", "word_count.py"],
+//!   "timeoutMs": 5000,
+//!   "maxOutputBytes": 65536
+//! }
+//! ```
+//!
+//! A call runs the command directly, without a shell, in the plugin directory and
+//! with only `PATH` left in its environment. The call's arguments are written to the
+//! command's stdin as JSON, so they never become part of a command line. Its stdout
+//! is the result, parsed as JSON if valid and returned as text otherwise; a command
+//! writing more than its output limit is killed and the call fails. Its stderr is
+//! logged rather than returned, so internal details do not reach clients. A non-zero
+//! exit status fails the call, and a command still running after its timeout is
+//! killed.
+
+use super::cancellation::RequestHandlerExtra;
+use super::ToolHandler;
+use crate::error::{Error, Result};
+use crate::types::ToolInfo;
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Time a plugin command may run when its manifest sets no timeout.
+pub const DEFAULT_PLUGIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Bytes a plugin command may write to stdout when its manifest sets no limit.
+pub const DEFAULT_PLUGIN_MAX_OUTPUT: usize = 1024 * 1024;
+
+/// Bytes of a plugin command's stderr kept for the log; the rest is discarded.
+const MAX_LOGGED_STDERR: usize = 16 * 1024;
+
+/// Description of a tool handled by an external command.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PluginManifest {
+    /// Name clients call the tool by
+    pub name: String,
+    /// Human-readable description
+    #[serde(default)]
+    pub description: Option<String>,
+    /// JSON Schema of the tool's arguments
+    #[serde(default = "empty_object_schema")]
+    pub input_schema: Value,
+    /// Program and arguments to run; a relative program path with a directory part is
+    /// resolved against the plugin directory
+    pub command: Vec<String>,
+    /// Time the command may run, in milliseconds
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Bytes the command may write to stdout
+    #[serde(default)]
+    pub max_output_bytes: Option<usize>,
+}
+
+fn empty_object_schema() -> Value {
+    serde_json::json!({"type": "object"})
+}
+
+impl PluginManifest {
+    /// Read and validate the manifest at `path`.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let invalid = |reason: String| {
+            Error::validation(format!(
+                "Invalid plugin manifest {}: {}",
+                path.display(),
+                reason
+            ))
+        };
+        let text = std::fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        let manifest: Self = serde_json::from_str(&text).map_err(|e| invalid(e.to_string()))?;
+        manifest.validate().map_err(invalid)?;
+        Ok(manifest)
+    }
+
+    /// Check that the manifest describes a callable tool.
+    fn validate(&self) -> std::result::Result<(), String> {
+        let valid_name = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.');
+        if self.name.is_empty() || !self.name.chars().all(valid_name) {
+            return Err(format!(
+                "tool name '{}' must be non-empty and use only letters, digits, '_', '-' and '.'",
+                self.name
+            ));
+        }
+        if self.command.first().is_none_or(String::is_empty) {
+            return Err("command must name a program".to_string());
+        }
+        if !self.input_schema.is_object() {
+            return Err("inputSchema must be a JSON object".to_string());
+        }
+        if self.timeout_ms == Some(0) {
+            return Err("timeoutMs must be greater than zero".to_string());
+        }
+        if self.max_output_bytes == Some(0) {
+            return Err("maxOutputBytes must be greater than zero".to_string());
+        }
+        Ok(())
+    }
+
+    /// Time the command may run.
+    pub fn timeout(&self) -> Duration {
+        self.timeout_ms
+            .map_or(DEFAULT_PLUGIN_TIMEOUT, Duration::from_millis)
+    }
+
+    /// Bytes the command may write to stdout.
+    pub fn max_output(&self) -> usize {
+        self.max_output_bytes.unwrap_or(DEFAULT_PLUGIN_MAX_OUTPUT)
+    }
+}
+
+/// Read and validate every `*.json` manifest in `dir`, ordered by file name.
+///
+/// Fails on the first invalid manifest, or if two manifests name the same tool.
+pub fn discover(dir: &Path) -> Result<Vec<PluginManifest>> {
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        Error::validation(format!(
+            "Cannot read plugin directory {}: {}",
+            dir.display(),
+            e
+        ))
+    })?;
+    let mut paths = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    let mut names = HashSet::new();
+    let mut manifests = Vec::with_capacity(paths.len());
+    for path in paths {
+        let manifest = PluginManifest::from_file(&path)?;
+        if !names.insert(manifest.name.clone()) {
+            return Err(Error::validation(format!(
+                "Invalid plugin manifest {}: tool '{}' is already defined",
+                path.display(),
+                manifest.name
+            )));
+        }
+        manifests.push(manifest);
+    }
+    Ok(manifests)
+}
+
+/// Tool handled by running the command of a [`PluginManifest`].
+#[derive(Debug, Clone)]
+pub struct PluginTool {
+    manifest: PluginManifest,
+    dir: PathBuf,
+}
+
+impl PluginTool {
+    /// Create a tool running `manifest`'s command in `dir`.
+    pub fn new(manifest: PluginManifest, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            manifest,
+            dir: dir.into(),
+        }
+    }
+
+    /// Program to run, resolving relative paths like `./tool.sh` against the plugin
+    /// directory; bare names are looked up in `PATH`.
+    fn program(&self) -> PathBuf {
+        let program = Path::new(&self.manifest.command[0]);
+        if program.is_relative() && program.components().count() > 1 {
+            self.dir.join(program)
+        } else {
+            program.to_path_buf()
+        }
+    }
+
+    async fn run(&self, args: &Value) -> Result<std::process::Output> {
+        let mut command = tokio::process::Command::new(self.program());
+        command
+            .args(&self.manifest.command[1..])
+            .current_dir(&self.dir)
+            .env_clear()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(path) = std::env::var_os("PATH") {
+            command.env("PATH", path);
+        }
+        let mut child = command.spawn().map_err(|e| {
+            Error::internal(format!(
+                "Failed to start plugin '{}': {}",
+                self.manifest.name, e
+            ))
+        })?;
+
+        let input = serde_json::to_vec(args)?;
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let write = async move {
+            // A command that ignores its arguments may exit without reading them
+            match stdin.write_all(&input).await {
+                Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(e),
+                _ => Ok(()),
+            }
+        };
+        let max_output = self.manifest.max_output();
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let read_stdout = async move {
+            let mut output = Vec::new();
+            stdout
+                .take(max_output as u64 + 1)
+                .read_to_end(&mut output)
+                .await?;
+            if output.len() > max_output {
+                return Err(Error::internal(format!(
+                    "Plugin '{}' output exceeds {} bytes",
+                    self.manifest.name, max_output
+                )));
+            }
+            Ok(output)
+        };
+        let mut stderr = child.stderr.take().expect("stderr is piped");
+        let read_stderr = async move {
+            let mut logged = Vec::new();
+            (&mut stderr)
+                .take(MAX_LOGGED_STDERR as u64)
+                .read_to_end(&mut logged)
+                .await?;
+            // Drain the rest so the command never blocks on a full pipe
+            tokio::io::copy(&mut stderr, &mut tokio::io::sink()).await?;
+            Ok(logged)
+        };
+        // Stops at the first failure, such as too much output; dropping the child
+        // kills it
+        let ((), stdout, stderr) = tokio::try_join!(
+            async { Ok::<_, Error>(write.await?) },
+            read_stdout,
+            read_stderr
+        )?;
+        let status = child.wait().await?;
+        Ok(std::process::Output {
+            status,
+            stdout,
+            stderr,
+        })
+    }
+}
+
+#[async_trait]
+impl ToolHandler for PluginTool {
+    async fn handle(&self, args: Value, _extra: RequestHandlerExtra) -> Result<Value> {
+        let timeout = self.manifest.timeout();
+        // Dropping the timed out future kills the command
+        let output = tokio::time::timeout(timeout, self.run(&args))
+            .await
+            .map_err(|_| {
+                Error::Timeout(u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX))
+            })??;
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success() {
+            tracing::warn!(
+                "Plugin '{}' failed ({}): {}",
+                self.manifest.name,
+                output.status,
+                stderr.trim()
+            );
+            return Err(Error::internal(format!(
+                "Plugin '{}' failed ({})",
+                self.manifest.name, output.status
+            )));
+        }
+        if !stderr.trim().is_empty() {
+            tracing::debug!("Plugin '{}' stderr: {}", self.manifest.name, stderr.trim());
+        }
+        Ok(serde_json::from_slice(&output.stdout).unwrap_or_else(|_| {
+            Value::String(
+                String::from_utf8_lossy(&output.stdout)
+                    .trim_end()
+                    .to_string(),
+            )
+        }))
+    }
+
+    fn metadata(&self) -> Option<ToolInfo> {
+        Some(ToolInfo {
+            name: self.manifest.name.clone(),
+            description: self.manifest.description.clone(),
+            input_schema: self.manifest.input_schema.clone(),
+            category: None,
+            tags: Vec::new(),
+            output_schema: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("pmcp-plugins-{}-{}", name, uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn manifest(command: &[&str]) -> PluginManifest {
+        PluginManifest {
+            name: "tool".to_string(),
+            description: None,
+            input_schema: empty_object_schema(),
+            command: command.iter().map(ToString::to_string).collect(),
+            timeout_ms: None,
+            max_output_bytes: None,
+        }
+    }
+
+    fn extra() -> RequestHandlerExtra {
+        RequestHandlerExtra::new("1".to_string(), tokio_util::sync::CancellationToken::new())
+    }
+
+    #[test]
+    fn test_discover_validates_manifests() {
+        let dir = temp_dir("discover");
+        std::fs::write(
+            dir.join("b.json"),
+            json!({"name": "second", "command": ["true"]}).to_string(),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("a.json"),
+            json!({"name": "first", "command": ["true"], "timeoutMs": 50}).to_string(),
+        )
+        .unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a manifest").unwrap();
+
+        let manifests = discover(&dir).unwrap();
+        let names: Vec<_> = manifests.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["first", "second"]);
+        assert_eq!(manifests[0].timeout(), Duration::from_millis(50));
+        assert_eq!(manifests[1].timeout(), DEFAULT_PLUGIN_TIMEOUT);
+        assert_eq!(manifests[1].max_output(), DEFAULT_PLUGIN_MAX_OUTPUT);
+        assert_eq!(manifests[1].input_schema, json!({"type": "object"}));
+
+        for (invalid, reason) in [
+            (json!({"name": "", "command": ["true"]}), "tool name"),
+            (json!({"name": "a b", "command": ["true"]}), "tool name"),
+            (json!({"name": "x", "command": []}), "command"),
+            (
+                json!({"name": "x", "command": ["true"], "inputSchema": []}),
+                "inputSchema",
+            ),
+            (
+                json!({"name": "x", "command": ["true"], "timeoutMs": 0}),
+                "timeoutMs",
+            ),
+            (
+                json!({"name": "x", "command": ["true"], "maxOutputBytes": 0}),
+                "maxOutputBytes",
+            ),
+            (
+                json!({"name": "x", "command": ["true"], "shell": true}),
+                "unknown field",
+            ),
+            (
+                json!({"name": "first", "command": ["true"]}),
+                "already defined",
+            ),
+        ] {
+            std::fs::write(dir.join("c.json"), invalid.to_string()).unwrap();
+            let err = discover(&dir).unwrap_err().to_string();
+            assert!(err.contains("c.json") && err.contains(reason), "{}", err);
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_plugin_tool_output_and_failures() {
+        let dir = temp_dir("run");
+
+        let text = PluginTool::new(manifest(&["echo", "plain text"]), &dir);
+        assert_eq!(
+            text.handle(json!({}), extra()).await.unwrap(),
+            json!("plain text")
+        );
+
+        // Stderr is logged, not returned to the client
+        let failing = PluginTool::new(manifest(&["sh", "-c", "echo secret >&2; exit 3"]), &dir);
+        let err = failing
+            .handle(json!({}), extra())
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("failed") && !err.contains("secret"), "{}", err);
+
+        let chatty = PluginTool::new(
+            PluginManifest {
+                max_output_bytes: Some(1000),
+                ..manifest(&["yes"])
+            },
+            &dir,
+        );
+        let started = std::time::Instant::now();
+        let err = chatty.handle(json!({}), extra()).await.unwrap_err();
+        assert!(err.to_string().contains("exceeds 1000 bytes"), "{}", err);
+        assert!(started.elapsed() < DEFAULT_PLUGIN_TIMEOUT);
+
+        // Stderr beyond what is logged is drained, so the command is not blocked
+        let noisy = PluginTool::new(
+            manifest(&["sh", "-c", "head -c 100000 /dev/zero >&2; echo done"]),
+            &dir,
+        );
+        assert_eq!(
+            noisy.handle(json!({}), extra()).await.unwrap(),
+            json!("done")
+        );
+
+        let slow = PluginTool::new(
+            PluginManifest {
+                timeout_ms: Some(100),
+                ..manifest(&["sleep", "5"])
+            },
+            &dir,
+        );
+        let started = std::time::Instant::now();
+        let err = slow.handle(json!({}), extra()).await.unwrap_err();
+        assert!(matches!(err, Error::Timeout(100)), "{}", err);
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let missing = PluginTool::new(manifest(&["./missing.sh"]), &dir);
+        let err = missing.handle(json!({}), extra()).await.unwrap_err();
+        assert!(err.to_string().contains("Failed to start"), "{}", err);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,276 @@
+//! Priority-based request dispatch.
+//!
+//! When a server is saturated with long-running tool calls, cheap requests such as
+//! `ping` should not wait behind them. The dispatcher bounds how many requests run at
+//! once and, when the limit is reached, queues the rest by priority tier so that
+//! higher tiers are served first.
+
+use crate::error::{Error, ErrorCode, Result};
+use crate::shared::transport::MessagePriority;
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::oneshot;
+
+/// Configuration for priority-based request dispatch.
+///
+/// Requests whose method maps to [`MessagePriority::High`] bypass the queue entirely
+/// and run immediately. All other requests share `max_concurrent` slots; when every
+/// slot is busy they wait in a queue of at most `max_queued` entries, ordered by tier.
+/// Notifications such as `notifications/cancelled` are never queued.
+///
+/// # Examples
+///
+/// ```rust
+/// use pmcp::server::priority::PriorityConfig;
+/// use pmcp::shared::transport::MessagePriority;
+///
+/// let config = PriorityConfig::default()
+///     .with_tier("tools/list", MessagePriority::High)
+///     .with_tier("resources/read", MessagePriority::Low);
+///
+/// assert_eq!(config.priority_for("ping"), MessagePriority::High);
+/// assert_eq!(config.priority_for("tools/call"), MessagePriority::Normal);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PriorityConfig {
+    /// Priority tier for each request method
+    pub tiers: HashMap<String, MessagePriority>,
+    /// Tier for methods not listed in `tiers`
+    pub default_priority: MessagePriority,
+    /// Maximum number of queued requests running at once
+    pub max_concurrent: usize,
+    /// Maximum number of requests waiting for a slot before new ones are rejected
+    pub max_queued: usize,
+}
+
+impl Default for PriorityConfig {
+    fn default() -> Self {
+        Self {
+            tiers: HashMap::from([("ping".to_string(), MessagePriority::High)]),
+            default_priority: MessagePriority::Normal,
+            max_concurrent: 4,
+            max_queued: 256,
+        }
+    }
+}
+
+impl PriorityConfig {
+    /// Assign a priority tier to a request method.
+    pub fn with_tier(mut self, method: impl Into<String>, priority: MessagePriority) -> Self {
+        self.tiers.insert(method.into(), priority);
+        self
+    }
+
+    /// Get the priority tier for a request method.
+    pub fn priority_for(&self, method: &str) -> MessagePriority {
+        self.tiers
+            .get(method)
+            .copied()
+            .unwrap_or(self.default_priority)
+    }
+}
+
+/// Requests waiting for a slot, one queue per tier below `High`.
+#[derive(Debug, Default)]
+struct DispatchState {
+    running: usize,
+    normal: VecDeque<oneshot::Sender<DispatchPermit>>,
+    low: VecDeque<oneshot::Sender<DispatchPermit>>,
+}
+
+impl DispatchState {
+    fn queued(&self) -> usize {
+        self.normal.len() + self.low.len()
+    }
+
+    /// Forget waiters whose request was dropped while queued.
+    fn prune(&mut self) {
+        self.normal.retain(|tx| !tx.is_closed());
+        self.low.retain(|tx| !tx.is_closed());
+    }
+
+    fn next_waiter(&mut self) -> Option<oneshot::Sender<DispatchPermit>> {
+        self.normal.pop_front().or_else(|| self.low.pop_front())
+    }
+}
+
+#[derive(Debug)]
+struct DispatcherInner {
+    config: PriorityConfig,
+    state: Mutex<DispatchState>,
+}
+
+/// Bounded priority dispatcher shared by all requests of a server.
+#[derive(Debug, Clone)]
+pub(crate) struct PriorityDispatcher {
+    inner: Arc<DispatcherInner>,
+}
+
+/// A running slot; releasing it hands the slot to the highest-priority waiter.
+#[derive(Debug)]
+pub(crate) struct DispatchPermit {
+    inner: Option<Arc<DispatcherInner>>,
+}
+
+impl PriorityDispatcher {
+    /// Create a dispatcher from the given configuration.
+    pub(crate) fn new(config: PriorityConfig) -> Self {
+        Self {
+            inner: Arc::new(DispatcherInner {
+                config,
+                state: Mutex::new(DispatchState::default()),
+            }),
+        }
+    }
+
+    /// Wait for a slot to run a request with the given method.
+    ///
+    /// Returns `None` for high-priority methods, which run without taking a slot.
+    pub(crate) async fn acquire(&self, method: &str) -> Result<Option<DispatchPermit>> {
+        let priority = self.inner.config.priority_for(method);
+        if priority == MessagePriority::High {
+            return Ok(None);
+        }
+
+        let receiver = {
+            let mut state = self.inner.state.lock();
+            if state.running < self.inner.config.max_concurrent.max(1) {
+                state.running += 1;
+                return Ok(Some(DispatchPermit {
+                    inner: Some(self.inner.clone()),
+                }));
+            }
+
+            state.prune();
+            if state.queued() >= self.inner.config.max_queued {
+                return Err(Error::protocol(
+                    ErrorCode::INTERNAL_ERROR,
+                    format!("Server overloaded, rejected {} request", method),
+                ));
+            }
+
+            let (tx, rx) = oneshot::channel();
+            match priority {
+                MessagePriority::Low => state.low.push_back(tx),
+                _ => state.normal.push_back(tx),
+            }
+            rx
+        };
+
+        receiver
+            .await
+            .map(Some)
+            .map_err(|_| Error::internal("Priority dispatcher dropped a queued request"))
+    }
+}
+
+impl Drop for DispatchPermit {
+    fn drop(&mut self) {
+        let Some(inner) = self.inner.take() else {
+            return;
+        };
+
+        loop {
+            let waiter = {
+                let mut state = inner.state.lock();
+                match state.next_waiter() {
+                    Some(waiter) => waiter,
+                    None => {
+                        state.running -= 1;
+                        return;
+                    },
+                }
+            };
+
+            // Hand the slot over; a waiter that gave up returns the permit and the
+            // next one in line gets it instead.
+            match waiter.send(DispatchPermit {
+                inner: Some(inner.clone()),
+            }) {
+                Ok(()) => return,
+                Err(mut permit) => {
+                    permit.inner = None;
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn dispatcher(max_concurrent: usize, max_queued: usize) -> PriorityDispatcher {
+        PriorityDispatcher::new(
+            PriorityConfig {
+                max_concurrent,
+                max_queued,
+                ..Default::default()
+            }
+            .with_tier("resources/read", MessagePriority::Low),
+        )
+    }
+
+    #[tokio::test]
+    async fn high_priority_bypasses_slots() {
+        let dispatcher = dispatcher(1, 1);
+        let _busy = dispatcher.acquire("tools/call").await.unwrap();
+
+        let ping = tokio::time::timeout(Duration::from_millis(100), dispatcher.acquire("ping"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(ping.is_none());
+    }
+
+    #[tokio::test]
+    async fn higher_tiers_are_served_first() {
+        let dispatcher = dispatcher(1, 8);
+        let busy = dispatcher.acquire("tools/call").await.unwrap();
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut handles = Vec::new();
+        for method in ["resources/read", "tools/call"] {
+            let waiter = dispatcher.clone();
+            let order = order.clone();
+            handles.push(tokio::spawn(async move {
+                let _permit = waiter.acquire(method).await.unwrap();
+                order.lock().push(method);
+            }));
+            // Let the task enqueue before spawning the next one
+            while dispatcher.inner.state.lock().queued() < handles.len() {
+                tokio::task::yield_now().await;
+            }
+        }
+
+        drop(busy);
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(*order.lock(), vec!["tools/call", "resources/read"]);
+    }
+
+    #[tokio::test]
+    async fn full_queue_rejects_requests() {
+        let dispatcher = dispatcher(1, 0);
+        let _busy = dispatcher.acquire("tools/call").await.unwrap();
+
+        assert!(dispatcher.acquire("tools/call").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn abandoned_waiter_does_not_leak_slot() {
+        let dispatcher = dispatcher(1, 8);
+        let busy = dispatcher.acquire("tools/call").await.unwrap();
+
+        let abandoned =
+            tokio::time::timeout(Duration::from_millis(10), dispatcher.acquire("tools/call")).await;
+        assert!(abandoned.is_err());
+
+        drop(busy);
+        assert_eq!(dispatcher.inner.state.lock().running, 0);
+        assert!(dispatcher.acquire("tools/call").await.unwrap().is_some());
+    }
+}
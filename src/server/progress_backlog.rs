@@ -0,0 +1,111 @@
+//! Progress held back while the notification channel is congested.
+//!
+//! A tool reporting progress faster than the transport drains would otherwise block
+//! on the full channel. Instead, only the latest progress per token is kept here and
+//! sent once the channel has emptied, so memory stays bounded by the number of
+//! tokens and the client still sees the most recent progress last. Progress still
+//! held when its request completes is dropped, so that it never follows the response.
+
+use crate::types::{ProgressNotification, ProgressToken};
+use tokio::sync::Notify;
+
+/// Latest held-back progress per token, in the order the tokens were first held.
+#[derive(Debug, Default)]
+pub(crate) struct ProgressBacklog {
+    held: parking_lot::Mutex<Vec<(ProgressToken, ProgressNotification)>>,
+    wake: Notify,
+}
+
+impl ProgressBacklog {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hold `progress` back if the channel is `congested` or its token is already held.
+    ///
+    /// Returns the progress if it should be sent right away. A token that is already
+    /// held keeps being held, so that older progress is never sent after newer.
+    pub(crate) fn offer(
+        &self,
+        progress: ProgressNotification,
+        congested: bool,
+    ) -> Option<ProgressNotification> {
+        let mut held = self.held.lock();
+        if let Some((_, latest)) = held
+            .iter_mut()
+            .find(|(token, _)| *token == progress.progress_token)
+        {
+            *latest = progress;
+            return None;
+        }
+        if !congested {
+            return Some(progress);
+        }
+        held.push((progress.progress_token.clone(), progress));
+        drop(held);
+        self.wake.notify_one();
+        None
+    }
+
+    /// Drop the progress held for `token`, whose request has completed.
+    pub(crate) fn discard(&self, token: &ProgressToken) {
+        self.held.lock().retain(|(held, _)| held != token);
+    }
+
+    /// Take all held progress.
+    pub(crate) fn take(&self) -> Vec<ProgressNotification> {
+        self.held
+            .lock()
+            .drain(..)
+            .map(|(_, progress)| progress)
+            .collect()
+    }
+
+    /// Wait until progress has been held since the last call.
+    pub(crate) async fn held(&self) {
+        self.wake.notified().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(token: &str, value: f64) -> ProgressNotification {
+        ProgressNotification {
+            progress_token: ProgressToken::String(token.to_string()),
+            progress: value,
+            message: None,
+        }
+    }
+
+    #[test]
+    fn test_latest_progress_wins() {
+        let backlog = ProgressBacklog::new();
+        assert!(backlog.offer(progress("a", 1.0), false).is_some());
+
+        assert!(backlog.offer(progress("a", 2.0), true).is_none());
+        assert!(backlog.offer(progress("b", 1.0), true).is_none());
+        // Held tokens stay held once the channel frees up
+        assert!(backlog.offer(progress("a", 3.0), false).is_none());
+
+        let held = backlog.take();
+        assert_eq!(held.len(), 2);
+        assert_eq!(held[0].progress_token, ProgressToken::String("a".into()));
+        assert!((held[0].progress - 3.0).abs() < f64::EPSILON);
+        assert_eq!(held[1].progress_token, ProgressToken::String("b".into()));
+        assert!(backlog.take().is_empty());
+    }
+
+    #[test]
+    fn test_discarded_progress_is_not_sent() {
+        let backlog = ProgressBacklog::new();
+        assert!(backlog.offer(progress("a", 1.0), true).is_none());
+        assert!(backlog.offer(progress("b", 1.0), true).is_none());
+
+        backlog.discard(&ProgressToken::String("a".into()));
+        let held = backlog.take();
+        assert_eq!(held.len(), 1);
+        assert_eq!(held[0].progress_token, ProgressToken::String("b".into()));
+    }
+}
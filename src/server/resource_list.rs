@@ -0,0 +1,229 @@
+//! A paginated resource list with cursors that detect concurrent changes.
+//!
+//! Each cursor records the version of the list it was issued for. Adding or removing a
+//! resource bumps the version, so a client resuming pagination from a cursor issued
+//! before the change gets a `cursor expired` error (invalid params) instead of a page
+//! that silently skips or repeats entries. The client should restart listing from the
+//! first page. Replacing an existing resource in place does not move any entries and
+//! keeps cursors valid.
+
+use crate::error::{Error, Result};
+use crate::types::{ListResourcesResult, ResourceInfo};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use parking_lot::RwLock;
+
+/// Default number of resources per page.
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Resources served in pages, for use from [`ResourceHandler::list`](super::ResourceHandler::list).
+///
+/// # Examples
+///
+/// ```rust
+/// use pmcp::server::resource_list::ResourceList;
+/// use pmcp::ResourceInfo;
+///
+/// let list = ResourceList::with_page_size(1);
+/// for name in ["a.txt", "b.txt"] {
+///     list.insert(ResourceInfo {
+///         uri: format!("file:///{}", name),
+///         name: name.to_string(),
+///         description: None,
+///         mime_type: None,
+///     });
+/// }
+///
+/// let first = list.page(None)?;
+/// assert_eq!(first.resources[0].name, "a.txt");
+///
+/// // The list changes before the next page is requested
+/// list.remove("file:///a.txt");
+/// assert!(list.page(first.next_cursor).is_err());
+/// # Ok::<(), pmcp::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct ResourceList {
+    state: RwLock<ListState>,
+    page_size: usize,
+}
+
+#[derive(Debug, Default)]
+struct ListState {
+    resources: Vec<ResourceInfo>,
+    version: u64,
+}
+
+impl Default for ResourceList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResourceList {
+    /// Create an empty list with [`DEFAULT_PAGE_SIZE`] resources per page.
+    pub fn new() -> Self {
+        Self::with_page_size(DEFAULT_PAGE_SIZE)
+    }
+
+    /// Create an empty list with `page_size` resources per page.
+    pub fn with_page_size(page_size: usize) -> Self {
+        Self {
+            state: RwLock::new(ListState::default()),
+            page_size: page_size.max(1),
+        }
+    }
+
+    /// Add a resource, or replace the resource with the same URI.
+    ///
+    /// Adding a new resource expires outstanding cursors; replacing one does not.
+    pub fn insert(&self, resource: ResourceInfo) {
+        let mut state = self.state.write();
+        if let Some(existing) = state.resources.iter_mut().find(|r| r.uri == resource.uri) {
+            *existing = resource;
+        } else {
+            state.resources.push(resource);
+            state.version += 1;
+        }
+    }
+
+    /// Remove the resource with `uri`, returning it if it was present.
+    ///
+    /// Removing a resource expires outstanding cursors.
+    pub fn remove(&self, uri: &str) -> Option<ResourceInfo> {
+        let mut state = self.state.write();
+        let index = state.resources.iter().position(|r| r.uri == uri)?;
+        state.version += 1;
+        Some(state.resources.remove(index))
+    }
+
+    /// Number of resources in the list.
+    pub fn len(&self) -> usize {
+        self.state.read().resources.len()
+    }
+
+    /// Whether the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.state.read().resources.is_empty()
+    }
+
+    /// Current version of the list, bumped whenever a resource is added or removed.
+    pub fn version(&self) -> u64 {
+        self.state.read().version
+    }
+
+    /// Get the page starting at `cursor`, or the first page if `cursor` is `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an invalid params error if the cursor is malformed, or if it was
+    /// issued before the list last changed (`cursor expired`).
+    pub fn page(&self, cursor: Option<String>) -> Result<ListResourcesResult> {
+        let state = self.state.read();
+        let offset = match cursor {
+            None => 0,
+            Some(cursor) => {
+                let (version, offset) = decode_cursor(&cursor)?;
+                if version != state.version {
+                    return Err(Error::invalid_params(
+                        "cursor expired: the resource list changed, restart from the first page",
+                    ));
+                }
+                offset
+            },
+        };
+
+        let end = offset
+            .saturating_add(self.page_size)
+            .min(state.resources.len());
+        let resources = state
+            .resources
+            .get(offset..end)
+            .unwrap_or_default()
+            .to_vec();
+        let next_cursor = (end < state.resources.len()).then(|| encode_cursor(state.version, end));
+        Ok(ListResourcesResult {
+            resources,
+            next_cursor,
+        })
+    }
+}
+
+fn encode_cursor(version: u64, offset: usize) -> String {
+    URL_SAFE_NO_PAD.encode(format!("{}:{}", version, offset))
+}
+
+fn decode_cursor(cursor: &str) -> Result<(u64, usize)> {
+    let invalid = || Error::invalid_params(format!("Invalid cursor '{}'", cursor));
+    let decoded = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+    let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+    let (version, offset) = decoded.split_once(':').ok_or_else(invalid)?;
+    Ok((
+        version.parse().map_err(|_| invalid())?,
+        offset.parse().map_err(|_| invalid())?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resource(name: &str) -> ResourceInfo {
+        ResourceInfo {
+            uri: format!("file:///{}", name),
+            name: name.to_string(),
+            description: None,
+            mime_type: None,
+        }
+    }
+
+    fn names(result: &ListResourcesResult) -> Vec<&str> {
+        result.resources.iter().map(|r| r.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_pages_cover_list() {
+        let list = ResourceList::with_page_size(2);
+        for name in ["a", "b", "c"] {
+            list.insert(resource(name));
+        }
+
+        let first = list.page(None).unwrap();
+        assert_eq!(names(&first), ["a", "b"]);
+        let second = list.page(first.next_cursor).unwrap();
+        assert_eq!(names(&second), ["c"]);
+        assert!(second.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_mutation_mid_pagination_expires_cursor() {
+        let list = ResourceList::with_page_size(2);
+        for name in ["a", "b", "c", "d"] {
+            list.insert(resource(name));
+        }
+
+        let first = list.page(None).unwrap();
+        // Removing "a" would shift "c" onto the first page and skip it
+        list.remove("file:///a");
+        let err = list.page(first.next_cursor.clone()).unwrap_err();
+        assert!(err.to_string().contains("cursor expired"), "{}", err);
+
+        // Restarting from the first page sees the new list
+        let restarted = list.page(None).unwrap();
+        assert_eq!(names(&restarted), ["b", "c"]);
+
+        // Replacing a resource in place keeps cursors valid
+        let mut updated = resource("c");
+        updated.description = Some("changed".to_string());
+        list.insert(updated);
+        let next = list.page(restarted.next_cursor).unwrap();
+        assert_eq!(names(&next), ["d"]);
+    }
+
+    #[test]
+    fn test_malformed_cursor_is_rejected() {
+        let list = ResourceList::new();
+        list.insert(resource("a"));
+        assert!(list.page(Some("not a cursor".to_string())).is_err());
+        assert!(list.page(Some(URL_SAFE_NO_PAD.encode("1"))).is_err());
+    }
+}
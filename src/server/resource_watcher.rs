@@ -1,15 +1,84 @@
 //! Resource watcher for monitoring file system changes.
+//!
+//! Ignore patterns follow `.gitignore` rules: a pattern without a `/` matches a file or
+//! directory name at any depth (`*.swp`), a pattern containing a `/` is matched against
+//! the path relative to the watched directory (`build/out`, `**/target/**`), a trailing
+//! `/` only matches directories (`node_modules/`), and a leading `!` re-includes paths
+//! ignored by an earlier pattern. Ignoring a directory ignores everything below it.
+//! Patterns starting with `#` are comments; escape a literal leading `#` or `!` with a
+//! backslash (`\#*#`).
+//!
+//! Changes are coalesced per resource with a [`MessageDebouncer`], so a burst of saves
+//! produces one `notifications/resources/updated` once the resource has been quiet for
+//! the debounce interval. The watcher remembers a [`content_hash`] of each resource and
+//! skips the notification when a file is saved without its contents changing.
 
 use crate::error::{Error, ErrorCode, Result};
-use crate::types::protocol::{ResourceInfo, ServerNotification};
+use crate::types::protocol::{ChangeKind, ResourceInfo, ResourceUpdatedParams, ServerNotification};
+use crate::types::{content_hash, Notification};
+use crate::utils::{DebouncingConfig, MessageDebouncer};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{mpsc, RwLock};
-use tokio::time::{interval, Instant};
 use tracing::{debug, error, info, warn};
 
+/// Debounce and ignore settings for watching a `file://` root.
+///
+/// # Examples
+///
+/// ```rust
+/// use pmcp::server::resource_watcher::ResourceWatcherConfig;
+/// use std::time::Duration;
+///
+/// let mut config = ResourceWatcherConfig::default();
+/// config.debounce = Duration::from_millis(250);
+/// config.ignore_globs.push("*.log".to_string());
+/// config.ignore_globs.push("!keep.log".to_string());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResourceWatcherConfig {
+    /// How long a resource must be quiet before its change is announced.
+    pub debounce: Duration,
+    /// `.gitignore`-style patterns of paths whose changes are not announced.
+    pub ignore_globs: Vec<String>,
+}
+
+impl Default for ResourceWatcherConfig {
+    /// Ignores version control and build directories and common editor temp files.
+    fn default() -> Self {
+        Self {
+            debounce: Duration::from_millis(500),
+            ignore_globs: [
+                ".git/",
+                "node_modules/",
+                "target/",
+                ".DS_Store",
+                "*.swp",
+                "*.swx",
+                "*~",
+                ".#*",
+                "\\#*#",
+                "4913",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+        }
+    }
+}
+
+impl From<ResourceWatcherConfig> for WatchConfig {
+    fn from(config: ResourceWatcherConfig) -> Self {
+        Self {
+            debounce: config.debounce,
+            ignore_patterns: config.ignore_globs,
+            ..Self::default()
+        }
+    }
+}
+
 /// Configuration for resource watching.
 ///
 /// # Examples
@@ -35,7 +104,7 @@ pub struct WatchConfig {
     pub debounce: Duration,
     /// Patterns to watch (glob patterns).
     pub patterns: Vec<String>,
-    /// Patterns to ignore.
+    /// Patterns to ignore, with `.gitignore` rules (see the [module docs](self)).
     pub ignore_patterns: Vec<String>,
     /// Maximum number of resources to track.
     pub max_resources: usize,
@@ -62,8 +131,6 @@ impl Default for WatchConfig {
 struct FileEvent {
     /// Path that changed.
     path: PathBuf,
-    /// Time of the event.
-    timestamp: Instant,
     /// Type of change.
     kind: FileEventKind,
 }
@@ -84,12 +151,10 @@ pub struct ResourceWatcher {
     base_dir: PathBuf,
     /// Tracked resources by URI.
     resources: Arc<RwLock<HashMap<String, ResourceInfo>>>,
-    /// Pending file events for debouncing.
-    pending_events: Arc<RwLock<HashMap<PathBuf, FileEvent>>>,
+    /// Last announced content hash of tracked resources by URI.
+    hashes: Arc<RwLock<HashMap<String, String>>>,
     /// Channel for sending notifications.
     notification_tx: mpsc::Sender<ServerNotification>,
-    /// Channel for receiving file events.
-    event_rx: Arc<RwLock<Option<mpsc::Receiver<FileEvent>>>>,
     /// Shutdown signal.
     shutdown_tx: Option<mpsc::Sender<()>>,
 }
@@ -105,63 +170,113 @@ impl std::fmt::Debug for ResourceWatcher {
     }
 }
 
-/// Process file events with debouncing (helper function).
-#[allow(clippy::cognitive_complexity)]
+/// Announce changes to tracked resources, skipping ignored paths and coalescing bursts.
+///
+/// Changes that leave a file's content hash unchanged are not announced. Runs until
+/// the event channel closes, then announces changes still waiting for their
+/// debounce interval.
 async fn process_events(
     resources: Arc<RwLock<HashMap<String, ResourceInfo>>>,
-    pending_events: Arc<RwLock<HashMap<PathBuf, FileEvent>>>,
+    hashes: Arc<RwLock<HashMap<String, String>>>,
     notification_tx: mpsc::Sender<ServerNotification>,
-    debounce: Duration,
-    event_rx: Arc<RwLock<Option<mpsc::Receiver<FileEvent>>>>,
+    config: WatchConfig,
+    base_dir: PathBuf,
+    mut event_rx: mpsc::Receiver<FileEvent>,
 ) {
-    let mut timer = interval(Duration::from_millis(100));
+    let debouncer = MessageDebouncer::new(DebouncingConfig {
+        wait_time: config.debounce,
+        debounced_methods: HashMap::new(),
+    });
 
     loop {
-        timer.tick().await;
-
-        // Check for new events
-        if let Some(rx) = &mut *event_rx.write().await {
-            while let Ok(event) = rx.try_recv() {
-                let mut pending = pending_events.write().await;
-                pending.insert(event.path.clone(), event);
-            }
+        let event = tokio::select! {
+            event = event_rx.recv() => event,
+            Some(notification) = debouncer.receive() => {
+                send_update(&notification_tx, &hashes, notification).await;
+                continue;
+            },
+        };
+        let Some(event) = event else {
+            break;
+        };
+        if !ResourceWatcher::matches_patterns(
+            &event.path,
+            &base_dir,
+            &config.patterns,
+            &config.ignore_patterns,
+        ) {
+            continue;
         }
-
-        // Process debounced events
-        let now = Instant::now();
-        let mut events_to_process = Vec::new();
-
-        {
-            let mut pending = pending_events.write().await;
-            pending.retain(|path, event| {
-                if now.duration_since(event.timestamp) >= debounce {
-                    events_to_process.push((path.clone(), event.kind));
-                    false
-                } else {
-                    true
-                }
-            });
+        let uri = format!("file://{}", event.path.display());
+        if !resources.read().await.contains_key(&uri) {
+            continue;
         }
+        debug!("Resource {:?} changed: {}", event.kind, uri);
 
-        // Send notifications for processed events
-        for (path, kind) in events_to_process {
-            let uri = format!("file://{}", path.display());
-
-            let resources = resources.read().await;
-            if resources.contains_key(&uri) {
-                debug!("Resource {:?} changed: {}", kind, uri);
+        // The last change of a burst wins, e.g. a save followed by a delete
+        let change = match event.kind {
+            FileEventKind::Deleted => ChangeKind::Deleted,
+            _ => ChangeKind::Full,
+        };
+        let notification = ServerNotification::ResourceUpdated(ResourceUpdatedParams {
+            uri: uri.clone(),
+            change,
+        });
+        if let Err(e) = debouncer.add(uri, Notification::Server(notification)).await {
+            error!("Failed to debounce resource update notification: {}", e);
+        }
+    }
 
-                // Send resource update notification
-                let notification = ServerNotification::ResourceUpdated(
-                    crate::types::protocol::ResourceUpdatedParams { uri: uri.clone() },
-                );
+    // Changes whose interval already elapsed, then those still waiting
+    while let Ok(Some(notification)) =
+        tokio::time::timeout(Duration::ZERO, debouncer.receive()).await
+    {
+        send_update(&notification_tx, &hashes, notification).await;
+    }
+    for notification in debouncer.flush().await {
+        send_update(&notification_tx, &hashes, notification).await;
+    }
+}
 
-                if let Err(e) = notification_tx.send(notification).await {
-                    error!("Failed to send resource update notification: {}", e);
+/// Announce a change, unless the resource's content hash is unchanged.
+async fn send_update(
+    notification_tx: &mpsc::Sender<ServerNotification>,
+    hashes: &RwLock<HashMap<String, String>>,
+    notification: Notification,
+) {
+    let Notification::Server(notification) = notification else {
+        return;
+    };
+    if let ServerNotification::ResourceUpdated(update) = &notification {
+        match update.change {
+            ChangeKind::Deleted => {
+                hashes.write().await.remove(&update.uri);
+            },
+            _ => {
+                // Files that cannot be read are always announced
+                if let Some(hash) = read_content_hash(&update.uri).await {
+                    let mut hashes = hashes.write().await;
+                    if hashes.get(&update.uri) == Some(&hash) {
+                        debug!("Resource {} saved without changes", update.uri);
+                        return;
+                    }
+                    hashes.insert(update.uri.clone(), hash);
                 }
-            }
+            },
         }
     }
+    if let Err(e) = notification_tx.send(notification).await {
+        error!("Failed to send resource update notification: {}", e);
+    }
+}
+
+/// Hash of the file behind a `file://` URI, or `None` if it cannot be read.
+async fn read_content_hash(uri: &str) -> Option<String> {
+    let path = uri.strip_prefix("file://")?;
+    tokio::fs::read(path)
+        .await
+        .ok()
+        .map(|bytes| content_hash(&bytes))
 }
 
 impl ResourceWatcher {
@@ -175,63 +290,70 @@ impl ResourceWatcher {
             config,
             base_dir: base_dir.as_ref().to_path_buf(),
             resources: Arc::new(RwLock::new(HashMap::new())),
-            pending_events: Arc::new(RwLock::new(HashMap::new())),
+            hashes: Arc::new(RwLock::new(HashMap::new())),
             notification_tx,
-            event_rx: Arc::new(RwLock::new(None)),
             shutdown_tx: None,
         }
     }
 
+    /// Create a watcher for a `file://` root, e.g. one registered with
+    /// [`Server::register_root`](crate::Server::register_root).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::server::resource_watcher::{ResourceWatcher, ResourceWatcherConfig};
+    /// use tokio::sync::mpsc;
+    ///
+    /// let (tx, _rx) = mpsc::channel(100);
+    /// let watcher =
+    ///     ResourceWatcher::for_root("file:///home/user/project", ResourceWatcherConfig::default(), tx)?;
+    /// assert!(ResourceWatcher::for_root("https://example.com", Default::default(), mpsc::channel(1).0).is_err());
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an invalid params error if `root_uri` is not a `file://` URI.
+    pub fn for_root(
+        root_uri: &str,
+        config: ResourceWatcherConfig,
+        notification_tx: mpsc::Sender<ServerNotification>,
+    ) -> Result<Self> {
+        let path = root_uri.strip_prefix("file://").ok_or_else(|| {
+            Error::invalid_params(format!("Can only watch file:// roots, got '{}'", root_uri))
+        })?;
+        Ok(Self::new(path, config.into(), notification_tx))
+    }
+
     /// Start watching for changes.
     pub async fn start(&mut self) -> Result<()> {
         info!("Starting resource watcher for {:?}", self.base_dir);
 
         // Create event channel
         let (event_tx, event_rx) = mpsc::channel(1000);
-        *self.event_rx.write().await = Some(event_rx);
 
         // Create shutdown channel
         let (shutdown_tx, mut shutdown_rx) = mpsc::channel::<()>(1);
         self.shutdown_tx = Some(shutdown_tx);
 
-        // Start file system watcher
-        let base_dir = self.base_dir.clone();
-        let patterns = self.config.patterns.clone();
-        let ignore_patterns = self.config.ignore_patterns.clone();
-
         // Spawn file watcher task
-        let event_tx_clone = event_tx.clone();
+        let base_dir = self.base_dir.clone();
         tokio::spawn(async move {
-            if let Err(e) = Self::watch_filesystem(
-                base_dir,
-                patterns,
-                ignore_patterns,
-                event_tx_clone,
-                &mut shutdown_rx,
-            )
-            .await
-            {
+            if let Err(e) = Self::watch_filesystem(base_dir, event_tx, &mut shutdown_rx).await {
                 error!("File system watcher error: {}", e);
             }
         });
 
-        // Start event processor
-        let resources = self.resources.clone();
-        let pending_events = self.pending_events.clone();
-        let notification_tx = self.notification_tx.clone();
-        let debounce = self.config.debounce;
-        let event_rx = self.event_rx.clone();
-
-        tokio::spawn(async move {
-            process_events(
-                resources,
-                pending_events,
-                notification_tx,
-                debounce,
-                event_rx,
-            )
-            .await;
-        });
+        // Start event processor; it stops once the watcher task drops the event sender
+        tokio::spawn(process_events(
+            self.resources.clone(),
+            self.hashes.clone(),
+            self.notification_tx.clone(),
+            self.config.clone(),
+            self.base_dir.clone(),
+            event_rx,
+        ));
 
         Ok(())
     }
@@ -246,15 +368,24 @@ impl ResourceWatcher {
     }
 
     /// Add a resource to watch.
+    ///
+    /// The current contents of a `file://` resource are hashed, so saving it unchanged
+    /// is not announced.
     pub async fn add_resource(&self, uri: String, info: ResourceInfo) -> Result<()> {
-        let mut resources = self.resources.write().await;
+        {
+            let mut resources = self.resources.write().await;
+
+            if resources.len() >= self.config.max_resources {
+                warn!("Resource limit reached, not adding {}", uri);
+                return Ok(());
+            }
 
-        if resources.len() >= self.config.max_resources {
-            warn!("Resource limit reached, not adding {}", uri);
-            return Ok(());
+            resources.insert(uri.clone(), info);
         }
 
-        resources.insert(uri, info);
+        if let Some(hash) = read_content_hash(&uri).await {
+            self.hashes.write().await.insert(uri, hash);
+        }
         Ok(())
     }
 
@@ -262,6 +393,7 @@ impl ResourceWatcher {
     pub async fn remove_resource(&self, uri: &str) -> Result<()> {
         let mut resources = self.resources.write().await;
         resources.remove(uri);
+        self.hashes.write().await.remove(uri);
         Ok(())
     }
 
@@ -274,8 +406,6 @@ impl ResourceWatcher {
     /// Watch the file system for changes.
     async fn watch_filesystem(
         base_dir: PathBuf,
-        patterns: Vec<String>,
-        ignore_patterns: Vec<String>,
         event_tx: mpsc::Sender<FileEvent>,
         shutdown_rx: &mut mpsc::Receiver<()>,
     ) -> Result<()> {
@@ -327,16 +457,7 @@ impl ResourceWatcher {
                         };
 
                         for path in event.paths {
-                            // Check if path matches patterns
-                            if !Self::matches_patterns(&path, &base_dir, &patterns, &ignore_patterns) {
-                                continue;
-                            }
-
-                            let file_event = FileEvent {
-                                path,
-                                timestamp: Instant::now(),
-                                kind,
-                            };
+                            let file_event = FileEvent { path, kind };
 
                             if let Err(e) = event_tx.send(file_event).await {
                                 error!("Failed to send file event: {}", e);
@@ -368,14 +489,10 @@ impl ResourceWatcher {
             return false;
         };
 
-        let path_str = relative_path.to_string_lossy();
-
-        // Check ignore patterns first
-        for pattern in ignore_patterns {
-            if glob_match::glob_match(pattern, &path_str) {
-                return false;
-            }
+        if is_ignored(relative_path, ignore_patterns) {
+            return false;
         }
+        let path_str = relative_path.to_string_lossy();
 
         // Check include patterns
         for pattern in patterns {
@@ -388,6 +505,53 @@ impl ResourceWatcher {
     }
 }
 
+/// Whether `.gitignore`-style `patterns` ignore `relative_path`.
+///
+/// Each pattern is tried against every directory on the path and the path itself; the
+/// last matching pattern decides, so `!` patterns can re-include paths.
+fn is_ignored(relative_path: &Path, patterns: &[String]) -> bool {
+    let components: Vec<_> = relative_path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect();
+    let mut ignored = false;
+    for pattern in patterns {
+        let pattern = pattern.trim();
+        if pattern.is_empty() || pattern.starts_with('#') {
+            continue;
+        }
+        // `\#` and `\!` reach here as-is and glob matching treats them as literals
+        let (negated, pattern) = match pattern.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, pattern),
+        };
+        let (dir_only, pattern) = match pattern.strip_suffix('/') {
+            Some(pattern) => (true, pattern),
+            None => (false, pattern),
+        };
+        let anchored = pattern.contains('/');
+        let pattern = pattern.trim_start_matches('/');
+
+        // Directories are the proper prefixes of the path
+        let last = if dir_only {
+            components.len().saturating_sub(1)
+        } else {
+            components.len()
+        };
+        let matched = (1..=last).any(|len| {
+            if anchored {
+                glob_match::glob_match(pattern, &components[..len].join("/"))
+            } else {
+                glob_match::glob_match(pattern, &components[len - 1])
+            }
+        });
+        if matched {
+            ignored = !negated;
+        }
+    }
+    ignored
+}
+
 /// Builder for creating a resource watcher.
 #[derive(Debug)]
 pub struct ResourceWatcherBuilder {
@@ -531,4 +695,201 @@ mod tests {
         let resources = watcher.get_resources().await;
         assert_eq!(resources.len(), 0);
     }
+
+    #[test]
+    fn test_gitignore_style_ignore_patterns() {
+        let patterns: Vec<String> = ResourceWatcherConfig::default().ignore_globs;
+        let ignored = |path: &str| is_ignored(Path::new(path), &patterns);
+
+        // Names without a slash match at any depth
+        assert!(ignored("src/.main.rs.swp"));
+        assert!(ignored("notes.md~"));
+        assert!(ignored("docs/.#draft.md"));
+        assert!(ignored("docs/#draft.md#"));
+        assert!(ignored("4913"));
+        assert!(!ignored("docs/draft#1.md"));
+        // Ignoring a directory ignores everything below it
+        assert!(ignored(".git/index"));
+        assert!(ignored("web/node_modules/pkg/index.js"));
+        // Directory patterns do not match files of the same name
+        assert!(!ignored("target"));
+        assert!(!ignored("src/main.rs"));
+
+        let patterns = vec![
+            "/build/out".to_string(),
+            "*.log".to_string(),
+            "!keep.log".to_string(),
+        ];
+        let ignored = |path: &str| is_ignored(Path::new(path), &patterns);
+        assert!(ignored("build/out/app"));
+        assert!(!ignored("src/build/out"));
+        assert!(ignored("logs/today.log"));
+        assert!(!ignored("logs/keep.log"));
+
+        // A backslash makes a leading `#` or `!` literal
+        let patterns = vec!["#*#".to_string(), "\\!*".to_string()];
+        let ignored = |path: &str| is_ignored(Path::new(path), &patterns);
+        assert!(!ignored("#notes#"));
+        assert!(ignored("!important"));
+    }
+
+    #[tokio::test]
+    async fn test_ignored_paths_and_bursts_are_filtered() {
+        let base_dir = PathBuf::from("/project");
+        let (notification_tx, mut notifications) = mpsc::channel(10);
+        let (event_tx, event_rx) = mpsc::channel(100);
+        let resources = Arc::new(RwLock::new(HashMap::new()));
+        for name in ["a.txt", ".a.txt.swp", ".git/index"] {
+            let uri = format!("file:///project/{}", name);
+            resources.write().await.insert(
+                uri.clone(),
+                ResourceInfo {
+                    uri,
+                    name: name.to_string(),
+                    description: None,
+                    mime_type: None,
+                },
+            );
+        }
+        let config: WatchConfig = ResourceWatcherConfig {
+            debounce: Duration::from_millis(50),
+            ..Default::default()
+        }
+        .into();
+        let processor = tokio::spawn(process_events(
+            resources,
+            Arc::default(),
+            notification_tx,
+            config,
+            base_dir.clone(),
+            event_rx,
+        ));
+        let event = |name: &str, kind| FileEvent {
+            path: base_dir.join(name),
+            kind,
+        };
+
+        // A burst of saves, with editor swap files and git updates in between
+        for _ in 0..5 {
+            event_tx
+                .send(event("a.txt", FileEventKind::Modified))
+                .await
+                .unwrap();
+            event_tx
+                .send(event(".a.txt.swp", FileEventKind::Modified))
+                .await
+                .unwrap();
+            event_tx
+                .send(event(".git/index", FileEventKind::Modified))
+                .await
+                .unwrap();
+        }
+        let Some(ServerNotification::ResourceUpdated(update)) =
+            tokio::time::timeout(Duration::from_secs(5), notifications.recv())
+                .await
+                .unwrap()
+        else {
+            panic!("expected a resource update");
+        };
+        assert_eq!(update.uri, "file:///project/a.txt");
+        assert_eq!(update.change, ChangeKind::Full);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(200), notifications.recv())
+                .await
+                .is_err(),
+            "burst should produce a single notification"
+        );
+
+        // The last change of a burst wins, and pending changes are sent on shutdown
+        event_tx
+            .send(event("a.txt", FileEventKind::Modified))
+            .await
+            .unwrap();
+        event_tx
+            .send(event("a.txt", FileEventKind::Deleted))
+            .await
+            .unwrap();
+        drop(event_tx);
+        processor.await.unwrap();
+        let Some(ServerNotification::ResourceUpdated(update)) = notifications.recv().await else {
+            panic!("expected a resource update");
+        };
+        assert_eq!(update.change, ChangeKind::Deleted);
+        assert!(notifications.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_contents_are_not_announced() {
+        let base_dir =
+            std::env::temp_dir().join(format!("pmcp-resource-watcher-{}", std::process::id()));
+        std::fs::create_dir_all(&base_dir).unwrap();
+        let path = base_dir.join("notes.txt");
+        std::fs::write(&path, "first").unwrap();
+        let uri = format!("file://{}", path.display());
+
+        let (notification_tx, mut notifications) = mpsc::channel(10);
+        let watcher = ResourceWatcher::new(&base_dir, WatchConfig::default(), notification_tx);
+        watcher
+            .add_resource(
+                uri.clone(),
+                ResourceInfo {
+                    uri: uri.clone(),
+                    name: "notes.txt".to_string(),
+                    description: None,
+                    mime_type: None,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            watcher.hashes.read().await.get(&uri),
+            Some(&content_hash(b"first"))
+        );
+
+        let (event_tx, event_rx) = mpsc::channel(100);
+        let processor = tokio::spawn(process_events(
+            watcher.resources.clone(),
+            watcher.hashes.clone(),
+            watcher.notification_tx.clone(),
+            WatchConfig {
+                debounce: Duration::from_millis(20),
+                ..Default::default()
+            },
+            base_dir.clone(),
+            event_rx,
+        ));
+        drop(watcher);
+        let modified = || FileEvent {
+            path: path.clone(),
+            kind: FileEventKind::Modified,
+        };
+        async fn next(
+            notifications: &mut mpsc::Receiver<ServerNotification>,
+        ) -> std::result::Result<Option<ServerNotification>, tokio::time::error::Elapsed> {
+            tokio::time::timeout(Duration::from_millis(300), notifications.recv()).await
+        }
+
+        // Saved without changes
+        event_tx.send(modified()).await.unwrap();
+        assert!(next(&mut notifications).await.is_err());
+
+        std::fs::write(&path, "second").unwrap();
+        event_tx.send(modified()).await.unwrap();
+        let Ok(Some(ServerNotification::ResourceUpdated(update))) = next(&mut notifications).await
+        else {
+            panic!("expected a resource update");
+        };
+        assert_eq!(update.uri, uri);
+
+        // Changed and changed back within one burst
+        std::fs::write(&path, "third").unwrap();
+        event_tx.send(modified()).await.unwrap();
+        std::fs::write(&path, "second").unwrap();
+        event_tx.send(modified()).await.unwrap();
+        assert!(next(&mut notifications).await.is_err());
+
+        drop(event_tx);
+        processor.await.unwrap();
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
 }
@@ -1,11 +1,15 @@
 //! Server-side roots management.
 //!
-//! Roots represent directories or files that the server can operate on.
-//! Servers can register roots to inform clients about their working directories.
+//! Roots represent directories or files that can be operated on. There are two
+//! separate sets:
+//! - server-announced roots, registered with [`RootsManager::register_root`]
+//! - client roots, which each client owns and reports in its `roots/list`
+//!   response, cached per connection by [`ClientRoots`]
 
 use crate::error::Result;
 use crate::types::{ServerNotification, ServerRequest};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::info;
@@ -31,7 +35,10 @@ pub struct ListRootsResult {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RootsListChangedParams {}
 
-/// Manages server roots.
+/// Manages server-announced roots.
+///
+/// These are the server's own roots. The roots a client exposes are tracked
+/// separately by [`ClientRoots`].
 #[derive(Clone)]
 pub struct RootsManager {
     /// The registered roots.
@@ -180,10 +187,71 @@ impl RootsManager {
     }
 }
 
+/// Roots declared by clients, cached per connection.
+///
+/// Populated from the client's `roots/list` responses with [`ClientRoots::set`].
+#[derive(Debug, Clone, Default)]
+pub struct ClientRoots {
+    roots: Arc<RwLock<HashMap<String, Vec<Root>>>>,
+}
+
+impl ClientRoots {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cache the roots a client reported for `connection_id`.
+    pub async fn set(&self, connection_id: &str, roots: Vec<Root>) {
+        self.roots
+            .write()
+            .await
+            .insert(connection_id.to_string(), roots);
+    }
+
+    /// Get the cached roots of a client connection.
+    ///
+    /// Returns an empty list if the client has not been queried yet.
+    pub async fn get(&self, connection_id: &str) -> Vec<Root> {
+        self.roots
+            .read()
+            .await
+            .get(connection_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Forget the roots of a client connection.
+    pub async fn remove(&self, connection_id: &str) {
+        self.roots.write().await.remove(connection_id);
+    }
+
+    /// Forget the roots of all client connections.
+    pub async fn clear(&self) {
+        self.roots.write().await.clear();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_client_roots_cached_per_connection() {
+        let client_roots = ClientRoots::new();
+        let roots = vec![Root {
+            uri: "file:///client/workspace".to_string(),
+            name: Some("Workspace".to_string()),
+        }];
+        client_roots.set("conn-1", roots.clone()).await;
+
+        assert_eq!(client_roots.get("conn-1").await, roots);
+        assert!(client_roots.get("conn-2").await.is_empty());
+
+        client_roots.remove("conn-1").await;
+        assert!(client_roots.get("conn-1").await.is_empty());
+    }
+
     #[tokio::test]
     async fn test_register_root() {
         let manager = RootsManager::new();
@@ -0,0 +1,102 @@
+//! State kept per session by a server shared between sessions.
+//!
+//! Transports serving many clients with one [`Server`](super::Server), like the
+//! streamable HTTP server, handle each request inside [`in_session`], so that what
+//! the server keeps for a connection is kept for each session instead. Requests
+//! handled outside of a session belong to the server's single connection.
+
+use crate::shared::ConnectionContext;
+use std::collections::HashMap;
+use std::future::Future;
+
+tokio::task_local! {
+    /// Session of the request being handled
+    static CURRENT_SESSION: String;
+}
+
+/// Run `f`, which handles requests of session `session_id`, if any.
+#[cfg_attr(not(feature = "streamable-http"), allow(dead_code))] // Used by session transports
+pub(crate) async fn in_session<F: Future>(session_id: Option<String>, f: F) -> F::Output {
+    match session_id {
+        Some(session_id) => CURRENT_SESSION.scope(session_id, f).await,
+        None => f.await,
+    }
+}
+
+/// Session of the request being handled, if it was handled inside [`in_session`].
+pub(crate) fn current() -> Option<String> {
+    CURRENT_SESSION.try_with(Clone::clone).ok()
+}
+
+/// Identifier of the single connection, for requests handled outside of a session
+pub(crate) const CONNECTION_ID: &str = "connection";
+
+/// Identifier of the session the request being handled belongs to, or
+/// [`CONNECTION_ID`] outside of a session.
+pub(crate) fn current_id() -> String {
+    current().unwrap_or_else(|| CONNECTION_ID.to_string())
+}
+
+/// Connection contexts, one for the single connection and one per session.
+#[derive(Debug, Default)]
+pub(crate) struct SessionContexts {
+    /// Context of requests handled outside of a session
+    connection: ConnectionContext,
+    sessions: parking_lot::RwLock<HashMap<String, ConnectionContext>>,
+}
+
+impl SessionContexts {
+    /// Context of the connection or session the current request belongs to.
+    pub(crate) fn current(&self) -> ConnectionContext {
+        match current() {
+            Some(session_id) => self.sessions.write().entry(session_id).or_default().clone(),
+            None => self.connection.clone(),
+        }
+    }
+
+    /// Drop the context of session `session_id`, which ended.
+    #[cfg_attr(not(feature = "streamable-http"), allow(dead_code))]
+    pub(crate) fn end(&self, session_id: &str) {
+        if let Some(context) = self.sessions.write().remove(session_id) {
+            context.clear();
+        }
+    }
+
+    /// Drop every context, once the transport closed.
+    pub(crate) fn clear(&self) {
+        self.connection.clear();
+        for (_, context) in self.sessions.write().drain() {
+            context.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sessions_get_their_own_context() {
+        let contexts = SessionContexts::default();
+        in_session(Some("a".to_string()), async {
+            contexts.current().set(1u32)
+        })
+        .await;
+
+        let in_a = in_session(Some("a".to_string()), async {
+            contexts.current().get::<u32>()
+        });
+        assert_eq!(in_a.await.as_deref(), Some(&1));
+        let in_b = in_session(Some("b".to_string()), async {
+            contexts.current().get::<u32>()
+        });
+        assert!(in_b.await.is_none());
+        assert!(contexts.current().get::<u32>().is_none());
+
+        contexts.end("a");
+        let in_a = in_session(Some("a".to_string()), async {
+            contexts.current().get::<u32>()
+        });
+        assert!(in_a.await.is_none());
+    }
+}
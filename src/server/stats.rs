@@ -0,0 +1,106 @@
+//! Request counters behind [`Server::stats`](super::Server::stats).
+
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Counters of a [`Server`](super::Server), see [`Server::stats`](super::Server::stats).
+#[derive(Debug, Clone)]
+pub struct ServerStats {
+    /// Time since the server was built
+    pub uptime: Duration,
+    /// Number of requests handled, including failed ones
+    pub total_requests: u64,
+    /// Number of requests answered with an error
+    pub total_errors: u64,
+    /// Requests and errors per JSON-RPC method
+    pub methods: HashMap<String, MethodStats>,
+    /// Number of resource subscriptions held by clients
+    pub active_subscriptions: usize,
+    /// Number of clients that completed initialization and are still connected
+    ///
+    /// Counts the connection a server runs on, or each session of a server shared
+    /// between sessions, like the streamable HTTP server's.
+    pub connected_clients: usize,
+}
+
+/// Counters of one JSON-RPC method.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MethodStats {
+    /// Number of requests handled
+    pub requests: u64,
+    /// Number of requests answered with an error
+    pub errors: u64,
+}
+
+#[derive(Debug, Default)]
+struct MethodCounters {
+    requests: AtomicU64,
+    errors: AtomicU64,
+}
+
+/// Lock-free request counters, updated once per handled request.
+#[derive(Debug)]
+pub(crate) struct RequestCounters {
+    started: Instant,
+    total_requests: AtomicU64,
+    total_errors: AtomicU64,
+    methods: DashMap<&'static str, MethodCounters>,
+}
+
+impl RequestCounters {
+    pub(crate) fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            total_requests: AtomicU64::new(0),
+            total_errors: AtomicU64::new(0),
+            methods: DashMap::new(),
+        }
+    }
+
+    /// Count a handled request for `method`.
+    pub(crate) fn record(&self, method: &'static str, failed: bool) {
+        self.total_requests.fetch_add(1, Ordering::Relaxed);
+        if failed {
+            self.total_errors.fetch_add(1, Ordering::Relaxed);
+        }
+        let count = |counters: &MethodCounters| {
+            counters.requests.fetch_add(1, Ordering::Relaxed);
+            if failed {
+                counters.errors.fetch_add(1, Ordering::Relaxed);
+            }
+        };
+        // Known methods only take a shared lock on their shard
+        match self.methods.get(method) {
+            Some(counters) => count(&counters),
+            None => count(&self.methods.entry(method).or_default()),
+        }
+    }
+
+    /// Current counts, with the figures only the server knows filled in.
+    pub(crate) fn snapshot(
+        &self,
+        active_subscriptions: usize,
+        connected_clients: usize,
+    ) -> ServerStats {
+        ServerStats {
+            uptime: self.started.elapsed(),
+            total_requests: self.total_requests.load(Ordering::Relaxed),
+            total_errors: self.total_errors.load(Ordering::Relaxed),
+            methods: self
+                .methods
+                .iter()
+                .map(|entry| {
+                    let stats = MethodStats {
+                        requests: entry.requests.load(Ordering::Relaxed),
+                        errors: entry.errors.load(Ordering::Relaxed),
+                    };
+                    ((*entry.key()).to_string(), stats)
+                })
+                .collect(),
+            active_subscriptions,
+            connected_clients,
+        }
+    }
+}
@@ -2,7 +2,8 @@
 use crate::error::Result;
 use crate::server::Server;
 use crate::shared::http_constants::{
-    APPLICATION_JSON, LAST_EVENT_ID, MCP_PROTOCOL_VERSION, MCP_SESSION_ID, TEXT_EVENT_STREAM,
+    is_json_content_type, APPLICATION_JSON, LAST_EVENT_ID, MCP_PROTOCOL_VERSION, MCP_SESSION_ID,
+    TEXT_EVENT_STREAM,
 };
 use crate::shared::TransportMessage;
 use crate::types::{ClientRequest, Request};
@@ -16,7 +17,7 @@ use axum::{
 };
 use futures_util::StreamExt;
 use parking_lot::RwLock;
-use serde_json::json;
+use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::convert::Infallible;
 use std::net::SocketAddr;
@@ -205,9 +206,8 @@ struct SessionInfo {
 }
 
 /// Server state shared across routes
-#[derive(Clone)]
-struct ServerState {
-    server: Arc<tokio::sync::Mutex<Server>>,
+struct ServerState<S = ()> {
+    server: Arc<Server<S>>,
     config: Arc<StreamableHttpServerConfig>,
     /// Active SSE streams by session ID
     sse_streams: Arc<RwLock<HashMap<String, mpsc::UnboundedSender<TransportMessage>>>>,
@@ -215,13 +215,27 @@ struct ServerState {
     sessions: Arc<RwLock<HashMap<String, SessionInfo>>>,
 }
 
+// Derived `Clone` would require `S: Clone`
+impl<S> Clone for ServerState<S> {
+    fn clone(&self) -> Self {
+        Self {
+            server: Arc::clone(&self.server),
+            config: Arc::clone(&self.config),
+            sse_streams: Arc::clone(&self.sse_streams),
+            sessions: Arc::clone(&self.sessions),
+        }
+    }
+}
+
 /// A streamable HTTP server for MCP.
-pub struct StreamableHttpServer {
+///
+/// Serves a [`Server`] with any state type `S`, see [`ServerBuilder::state`](crate::server::ServerBuilder::state).
+pub struct StreamableHttpServer<S = ()> {
     addr: SocketAddr,
-    state: ServerState,
+    state: ServerState<S>,
 }
 
-impl std::fmt::Debug for StreamableHttpServer {
+impl<S> std::fmt::Debug for StreamableHttpServer<S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("StreamableHttpServer")
             .field("addr", &self.addr)
@@ -244,16 +258,16 @@ fn create_error_response(status: StatusCode, code: i32, message: &str) -> Respon
     (status, Json(error_body)).into_response()
 }
 
-impl StreamableHttpServer {
+impl<S: Send + Sync + 'static> StreamableHttpServer<S> {
     /// Creates a new `StreamableHttpServer` with default config
-    pub fn new(addr: SocketAddr, server: Arc<tokio::sync::Mutex<Server>>) -> Self {
+    pub fn new(addr: SocketAddr, server: Arc<Server<S>>) -> Self {
         Self::with_config(addr, server, StreamableHttpServerConfig::default())
     }
 
     /// Creates a new `StreamableHttpServer` with custom config
     pub fn with_config(
         addr: SocketAddr,
-        server: Arc<tokio::sync::Mutex<Server>>,
+        server: Arc<Server<S>>,
         config: StreamableHttpServerConfig,
     ) -> Self {
         let state = ServerState {
@@ -291,7 +305,7 @@ fn validate_headers(headers: &HeaderMap, method: &str) -> std::result::Result<()
             // Validate Content-Type
             if let Some(content_type) = headers.get(header::CONTENT_TYPE) {
                 let ct = content_type.to_str().unwrap_or("");
-                if !ct.contains(APPLICATION_JSON) {
+                if !is_json_content_type(ct) {
                     return Err(create_error_response(
                         StatusCode::UNSUPPORTED_MEDIA_TYPE,
                         -32700,
@@ -351,8 +365,8 @@ fn validate_headers(headers: &HeaderMap, method: &str) -> std::result::Result<()
 }
 
 /// Process session for initialization request
-fn process_init_session(
-    state: &ServerState,
+fn process_init_session<S: Send + Sync + 'static>(
+    state: &ServerState<S>,
     session_id: Option<String>,
     protocol_version: Option<String>,
 ) -> std::result::Result<(Option<String>, bool), Response> {
@@ -395,8 +409,8 @@ fn process_init_session(
 }
 
 /// Validate session for non-initialization request
-fn validate_non_init_session(
-    state: &ServerState,
+fn validate_non_init_session<S: Send + Sync + 'static>(
+    state: &ServerState<S>,
     session_id: Option<String>,
 ) -> std::result::Result<Option<String>, Response> {
     if state.config.session_id_generator.is_some() {
@@ -445,8 +459,8 @@ fn extract_negotiated_version(response: &TransportMessage) -> Option<String> {
 }
 
 /// Update session info after initialization
-fn update_session_after_init(
-    state: &ServerState,
+fn update_session_after_init<S: Send + Sync + 'static>(
+    state: &ServerState<S>,
     session_id: Option<&String>,
     negotiated_version: Option<String>,
 ) {
@@ -460,8 +474,8 @@ fn update_session_after_init(
 }
 
 /// Build response with appropriate format (JSON or SSE)
-fn build_response(
-    state: &ServerState,
+fn build_response<S: Send + Sync + 'static>(
+    state: &ServerState<S>,
     response: TransportMessage,
     session_id: Option<&String>,
 ) -> Response {
@@ -501,8 +515,8 @@ fn build_response(
 }
 
 /// Validate protocol version for non-init requests
-fn validate_protocol_version(
-    state: &ServerState,
+fn validate_protocol_version<S: Send + Sync + 'static>(
+    state: &ServerState<S>,
     session_id: Option<&String>,
     protocol_version: Option<&String>,
 ) -> std::result::Result<(), Response> {
@@ -543,8 +557,8 @@ fn validate_protocol_version(
 }
 
 /// Handle POST requests
-async fn handle_post_request(
-    State(state): State<ServerState>,
+async fn handle_post_request<S: Send + Sync + 'static>(
+    State(state): State<ServerState<S>>,
     headers: HeaderMap,
     body: String,
 ) -> impl IntoResponse {
@@ -553,6 +567,15 @@ async fn handle_post_request(
         return error_response;
     }
 
+    let max_depth = state.server.max_json_depth;
+    if let Err(e) = crate::shared::stdio::check_json_depth(body.as_bytes(), max_depth) {
+        return create_error_response(StatusCode::BAD_REQUEST, -32700, &e.to_string());
+    }
+
+    if body.trim_start().starts_with('[') {
+        return handle_batch_post(&state, &headers, &body).await;
+    }
+
     // Parse the JSON body
     let message: TransportMessage = match serde_json::from_str(&body) {
         Ok(msg) => msg,
@@ -609,8 +632,11 @@ async fn handle_post_request(
     // Process the message
     match message {
         TransportMessage::Request { id, request } => {
-            let server = state.server.lock().await;
-            let json_response = server.handle_request(id, request).await;
+            let json_response = super::sessions::in_session(
+                response_session_id.clone(),
+                state.server.handle_request(id, request),
+            )
+            .await;
             let response = TransportMessage::Response(json_response.clone());
 
             // Handle initialization response
@@ -667,16 +693,100 @@ async fn handle_post_request(
 
             response
         },
-        TransportMessage::Notification { .. } => {
-            // Notifications get 202 Accepted
+        TransportMessage::Notification(notification) => {
+            // Notifications, e.g. cancelling a running request, get 202 Accepted
+            super::sessions::in_session(
+                response_session_id,
+                state.server.handle_notification(notification),
+            )
+            .await;
             StatusCode::ACCEPTED.into_response()
         },
         TransportMessage::Response(_) => StatusCode::ACCEPTED.into_response(),
     }
 }
 
+/// Handle a POST whose body is a JSON-RPC batch.
+///
+/// Batches are rejected unless the server advertises
+/// [`ServerCapabilities::supports_batch`](crate::types::ServerCapabilities::supports_batch).
+/// They may not contain `initialize`, and are always answered with a JSON array, or
+/// 202 Accepted if the batch holds no requests.
+async fn handle_batch_post<S: Send + Sync + 'static>(
+    state: &ServerState<S>,
+    headers: &HeaderMap,
+    body: &str,
+) -> Response {
+    let messages: Vec<Value> = match serde_json::from_str(body) {
+        Ok(messages) => messages,
+        Err(e) => {
+            return create_error_response(
+                StatusCode::BAD_REQUEST,
+                -32700,
+                &format!("Invalid JSON: {}", e),
+            );
+        },
+    };
+    if messages
+        .iter()
+        .any(|message| message.get("method").and_then(Value::as_str) == Some("initialize"))
+    {
+        return create_error_response(
+            StatusCode::BAD_REQUEST,
+            -32600,
+            "initialize must not be part of a batch",
+        );
+    }
+
+    let session_id = headers
+        .get(MCP_SESSION_ID)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+    let protocol_version = headers
+        .get(MCP_PROTOCOL_VERSION)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+    let response_session_id = match validate_non_init_session(state, session_id.clone()) {
+        Ok(sid) => sid,
+        Err(error_response) => return error_response,
+    };
+    if let Err(error_response) =
+        validate_protocol_version(state, session_id.as_ref(), protocol_version.as_ref())
+    {
+        return error_response;
+    }
+
+    if !state.server.capabilities.supports_batch() {
+        return create_error_response(
+            StatusCode::BAD_REQUEST,
+            -32600,
+            "Batch requests are not supported by this server",
+        );
+    }
+    let responses = super::sessions::in_session(
+        response_session_id.clone(),
+        state.server.handle_batch_messages(messages),
+    )
+    .await;
+
+    let mut response = if responses.is_empty() {
+        StatusCode::ACCEPTED.into_response()
+    } else {
+        (StatusCode::OK, Json(responses)).into_response()
+    };
+    if let Some(sid) = &response_session_id {
+        response
+            .headers_mut()
+            .insert(MCP_SESSION_ID, sid.parse().unwrap());
+    }
+    response
+}
+
 /// Handle GET requests for SSE streams
-async fn handle_get_sse(State(state): State<ServerState>, headers: HeaderMap) -> impl IntoResponse {
+async fn handle_get_sse<S: Send + Sync + 'static>(
+    State(state): State<ServerState<S>>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
     // Validate headers
     if let Err(error_response) = validate_headers(&headers, "GET") {
         return error_response;
@@ -795,8 +905,8 @@ async fn handle_get_sse(State(state): State<ServerState>, headers: HeaderMap) ->
 }
 
 /// Handle DELETE requests to terminate sessions
-async fn handle_delete_session(
-    State(state): State<ServerState>,
+async fn handle_delete_session<S: Send + Sync + 'static>(
+    State(state): State<ServerState<S>>,
     headers: HeaderMap,
 ) -> impl IntoResponse {
     // Extract session ID
@@ -819,6 +929,7 @@ async fn handle_delete_session(
 
         // Remove session from tracking
         state.sessions.write().remove(&sid);
+        state.server.end_session(&sid).await;
 
         // Notify callback
         if let Some(callback) = &state.config.on_session_closed {
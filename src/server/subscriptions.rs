@@ -1,19 +1,67 @@
 //! Server-side resource subscription management.
 
-use crate::error::Result;
-use crate::types::{protocol::ResourceUpdatedParams, ServerNotification};
+use crate::error::{Error, ErrorCode, Result};
+use crate::types::{protocol::ResourceUpdatedParams, ChangeKind, ServerNotification};
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// Subscribers per resource, with the number of subscriptions each holds.
+#[derive(Debug, Default)]
+struct SubscriptionTable {
+    /// Map of resource URI to set of subscriber IDs
+    by_uri: HashMap<String, HashSet<String>>,
+    /// Number of resources each subscriber is subscribed to
+    per_subscriber: HashMap<String, usize>,
+}
+
+impl SubscriptionTable {
+    fn is_subscribed(&self, uri: &str, subscriber_id: &str) -> bool {
+        self.by_uri
+            .get(uri)
+            .is_some_and(|subscribers| subscribers.contains(subscriber_id))
+    }
+
+    fn insert(&mut self, uri: String, subscriber_id: String) {
+        if self
+            .by_uri
+            .entry(uri)
+            .or_default()
+            .insert(subscriber_id.clone())
+        {
+            *self.per_subscriber.entry(subscriber_id).or_default() += 1;
+        }
+    }
+
+    fn remove(&mut self, uri: &str, subscriber_id: &str) {
+        let Some(subscribers) = self.by_uri.get_mut(uri) else {
+            return;
+        };
+        if !subscribers.remove(subscriber_id) {
+            return;
+        }
+        if subscribers.is_empty() {
+            self.by_uri.remove(uri);
+        }
+        if let Some(count) = self.per_subscriber.get_mut(subscriber_id) {
+            *count -= 1;
+            if *count == 0 {
+                self.per_subscriber.remove(subscriber_id);
+            }
+        }
+    }
+}
+
 /// Manages resource subscriptions for the server.
 ///
 /// This struct keeps track of which resources are subscribed to
 /// and provides methods to notify subscribers when resources change.
 #[derive(Clone)]
 pub struct SubscriptionManager {
-    /// Map of resource URI to set of subscriber IDs
-    subscriptions: Arc<RwLock<HashMap<String, HashSet<String>>>>,
+    /// Subscribers per resource
+    subscriptions: Arc<RwLock<SubscriptionTable>>,
+    /// Most resources a single subscriber may be subscribed to
+    max_per_subscriber: Option<usize>,
     /// Optional callback for sending notifications
     notification_sender: Option<Arc<dyn Fn(ServerNotification) + Send + Sync>>,
 }
@@ -29,8 +77,13 @@ impl std::fmt::Debug for SubscriptionManager {
         f.debug_struct("SubscriptionManager")
             .field(
                 "subscriptions",
-                &self.subscriptions.try_read().map(|s| s.len()).unwrap_or(0),
+                &self
+                    .subscriptions
+                    .try_read()
+                    .map(|s| s.by_uri.len())
+                    .unwrap_or(0),
             )
+            .field("max_per_subscriber", &self.max_per_subscriber)
             .finish()
     }
 }
@@ -39,11 +92,18 @@ impl SubscriptionManager {
     /// Create a new subscription manager.
     pub fn new() -> Self {
         Self {
-            subscriptions: Arc::new(RwLock::new(HashMap::new())),
+            subscriptions: Arc::new(RwLock::new(SubscriptionTable::default())),
+            max_per_subscriber: None,
             notification_sender: None,
         }
     }
 
+    /// Limit how many resources a single subscriber may be subscribed to.
+    pub fn with_max_per_subscriber(mut self, max: usize) -> Self {
+        self.max_per_subscriber = Some(max);
+        self
+    }
+
     /// Set the notification sender callback.
     ///
     /// This should be called after the server is initialized with a transport.
@@ -56,17 +116,34 @@ impl SubscriptionManager {
 
     /// Subscribe to a resource.
     ///
+    /// Subscribing again to the same resource has no effect.
+    ///
     /// # Arguments
     ///
     /// * `uri` - The resource URI to subscribe to
     /// * `subscriber_id` - Unique identifier for the subscriber (usually session ID)
+    ///
+    /// # Errors
+    ///
+    /// Returns a protocol error with [`ErrorCode::SUBSCRIPTION_LIMIT_REACHED`] if
+    /// the subscriber already holds as many subscriptions as allowed.
     pub async fn subscribe(&self, uri: String, subscriber_id: String) -> Result<()> {
-        self.subscriptions
-            .write()
-            .await
-            .entry(uri)
-            .or_default()
-            .insert(subscriber_id);
+        let mut subs = self.subscriptions.write().await;
+        if let Some(max) = self.max_per_subscriber {
+            let held = subs
+                .per_subscriber
+                .get(&subscriber_id)
+                .copied()
+                .unwrap_or(0);
+            if held >= max && !subs.is_subscribed(&uri, &subscriber_id) {
+                return Err(Error::protocol(
+                    ErrorCode::SUBSCRIPTION_LIMIT_REACHED,
+                    "subscription limit reached",
+                ));
+            }
+        }
+        subs.insert(uri, subscriber_id);
+        drop(subs);
         Ok(())
     }
 
@@ -77,14 +154,10 @@ impl SubscriptionManager {
     /// * `uri` - The resource URI to unsubscribe from
     /// * `subscriber_id` - Unique identifier for the subscriber
     pub async fn unsubscribe(&self, uri: String, subscriber_id: String) -> Result<()> {
-        let mut subs = self.subscriptions.write().await;
-        if let Some(subscribers) = subs.get_mut(&uri) {
-            subscribers.remove(&subscriber_id);
-            if subscribers.is_empty() {
-                subs.remove(&uri);
-                drop(subs);
-            }
-        }
+        self.subscriptions
+            .write()
+            .await
+            .remove(&uri, &subscriber_id);
         Ok(())
     }
 
@@ -97,18 +170,11 @@ impl SubscriptionManager {
     /// * `subscriber_id` - Unique identifier for the subscriber
     pub async fn unsubscribe_all(&self, subscriber_id: &str) -> Result<()> {
         let mut subs = self.subscriptions.write().await;
-        let mut empty_uris = Vec::new();
-
-        for (uri, subscribers) in subs.iter_mut() {
-            subscribers.remove(subscriber_id);
-            if subscribers.is_empty() {
-                empty_uris.push(uri.clone());
-            }
-        }
-
-        // Remove empty subscription entries
-        for uri in empty_uris {
-            subs.remove(&uri);
+        if subs.per_subscriber.remove(subscriber_id).is_some() {
+            subs.by_uri.retain(|_, subscribers| {
+                subscribers.remove(subscriber_id);
+                !subscribers.is_empty()
+            });
         }
         drop(subs);
 
@@ -122,7 +188,7 @@ impl SubscriptionManager {
     /// * `uri` - The resource URI to check
     pub async fn has_subscribers(&self, uri: &str) -> bool {
         let subs = self.subscriptions.read().await;
-        subs.get(uri).is_some_and(|s| !s.is_empty())
+        subs.by_uri.get(uri).is_some_and(|s| !s.is_empty())
     }
 
     /// Get all subscribed resources for a subscriber.
@@ -132,7 +198,8 @@ impl SubscriptionManager {
     /// * `subscriber_id` - Unique identifier for the subscriber
     pub async fn get_subscriptions(&self, subscriber_id: &str) -> Vec<String> {
         let subs = self.subscriptions.read().await;
-        subs.iter()
+        subs.by_uri
+            .iter()
             .filter_map(|(uri, subscribers)| {
                 if subscribers.contains(subscriber_id) {
                     Some(uri.clone())
@@ -150,7 +217,8 @@ impl SubscriptionManager {
     /// * `uri` - The resource URI
     pub async fn get_subscribers(&self, uri: &str) -> Vec<String> {
         let subs = self.subscriptions.read().await;
-        subs.get(uri)
+        subs.by_uri
+            .get(uri)
             .map(|s| s.iter().cloned().collect())
             .unwrap_or_default()
     }
@@ -165,9 +233,23 @@ impl SubscriptionManager {
     ///
     /// The number of subscribers notified
     pub async fn notify_resource_updated(&self, uri: String) -> Result<usize> {
+        self.notify_resource_changed(uri, ChangeKind::Full).await
+    }
+
+    /// Notify subscribers that a resource has changed, describing the change.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The resource URI that changed
+    /// * `change` - How the resource changed
+    ///
+    /// # Returns
+    ///
+    /// The number of subscribers notified
+    pub async fn notify_resource_changed(&self, uri: String, change: ChangeKind) -> Result<usize> {
         let subs = self.subscriptions.read().await;
 
-        if let Some(subscribers) = subs.get(&uri) {
+        if let Some(subscribers) = subs.by_uri.get(&uri) {
             let subscriber_count = subscribers.len();
             drop(subs);
             if subscriber_count > 0 {
@@ -175,6 +257,7 @@ impl SubscriptionManager {
                 if let Some(sender) = &self.notification_sender {
                     let notification = ServerNotification::ResourceUpdated(ResourceUpdatedParams {
                         uri: uri.clone(),
+                        change,
                     });
                     sender(notification);
                 }
@@ -189,21 +272,15 @@ impl SubscriptionManager {
     /// Get statistics about current subscriptions.
     pub async fn get_stats(&self) -> SubscriptionStats {
         let subs = self.subscriptions.read().await;
-        let total_resources = subs.len();
-        let total_subscriptions = subs.values().map(std::collections::HashSet::len).sum();
-
-        let mut subscriber_counts = HashMap::new();
-        for subscribers in subs.values() {
-            for subscriber in subscribers {
-                *subscriber_counts.entry(subscriber.clone()).or_insert(0) += 1;
-            }
-        }
+        let total_resources = subs.by_uri.len();
+        let total_subscriptions = subs.per_subscriber.values().sum();
+        let unique_subscribers = subs.per_subscriber.len();
         drop(subs);
 
         SubscriptionStats {
             total_resources,
             total_subscriptions,
-            unique_subscribers: subscriber_counts.len(),
+            unique_subscribers,
             subscriptions_per_resource: if total_resources > 0 {
                 #[allow(clippy::cast_precision_loss)]
                 {
@@ -326,6 +403,49 @@ mod tests {
         assert!(!manager.has_subscribers("file://test3.txt").await);
     }
 
+    #[tokio::test]
+    async fn test_max_per_subscriber() {
+        let manager = SubscriptionManager::new().with_max_per_subscriber(2);
+        for uri in ["file://a.txt", "file://b.txt"] {
+            manager
+                .subscribe(uri.to_string(), "client1".to_string())
+                .await
+                .unwrap();
+        }
+        // Repeating a subscription does not take another slot
+        manager
+            .subscribe("file://a.txt".to_string(), "client1".to_string())
+            .await
+            .unwrap();
+
+        let err = manager
+            .subscribe("file://c.txt".to_string(), "client1".to_string())
+            .await
+            .unwrap_err();
+        assert!(err.is_error_code(ErrorCode::SUBSCRIPTION_LIMIT_REACHED));
+        assert!(!manager.has_subscribers("file://c.txt").await);
+
+        // The limit is per subscriber
+        manager
+            .subscribe("file://c.txt".to_string(), "client2".to_string())
+            .await
+            .unwrap();
+
+        manager
+            .unsubscribe("file://a.txt".to_string(), "client1".to_string())
+            .await
+            .unwrap();
+        manager
+            .subscribe("file://c.txt".to_string(), "client1".to_string())
+            .await
+            .unwrap();
+
+        manager.unsubscribe_all("client1").await.unwrap();
+        let stats = manager.get_stats().await;
+        assert_eq!(stats.total_subscriptions, 1);
+        assert_eq!(stats.unique_subscribers, 1);
+    }
+
     #[tokio::test]
     async fn test_stats() {
         let manager = SubscriptionManager::new();
@@ -385,7 +505,46 @@ mod tests {
         let notifs = notifications.lock().unwrap();
         assert_eq!(notifs.len(), 1);
         match &notifs[0] {
-            ServerNotification::ResourceUpdated(n) => assert_eq!(n.uri, "file://test.txt"),
+            ServerNotification::ResourceUpdated(n) => {
+                assert_eq!(n.uri, "file://test.txt");
+                assert_eq!(n.change, ChangeKind::Full);
+            },
+            _ => panic!("Wrong notification type"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_notify_resource_changed_with_patch() {
+        use crate::utils::json_patch::PatchOperation;
+        use std::sync::Mutex;
+
+        let mut manager = SubscriptionManager::new();
+        let notifications = Arc::new(Mutex::new(Vec::new()));
+        let notifications_clone = notifications.clone();
+        manager.set_notification_sender(move |notif| {
+            notifications_clone.lock().unwrap().push(notif);
+        });
+        manager
+            .subscribe("file://data.json".to_string(), "client1".to_string())
+            .await
+            .unwrap();
+
+        let patch = vec![PatchOperation::Remove {
+            path: "/stale".to_string(),
+        }];
+        manager
+            .notify_resource_changed(
+                "file://data.json".to_string(),
+                ChangeKind::Patch(patch.clone()),
+            )
+            .await
+            .unwrap();
+
+        let notifs = notifications.lock().unwrap();
+        match &notifs[0] {
+            ServerNotification::ResourceUpdated(n) => {
+                assert_eq!(n.change, ChangeKind::Patch(patch));
+            },
             _ => panic!("Wrong notification type"),
         }
     }
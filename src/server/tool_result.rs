@@ -0,0 +1,182 @@
+//! Builder for tool results with several content items or an error.
+//!
+//! A [`ToolHandler`](super::ToolHandler) normally returns a JSON value, which the
+//! server sends as a single text item. Return a [`ToolResult`] instead to control the
+//! content of the [`CallToolResult`] or to report a tool-level error.
+
+use crate::types::{CallToolResult, Content};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde_json::Value;
+
+/// Builder for a [`CallToolResult`].
+///
+/// Convert it into a `Value` to return it from
+/// [`ToolHandler::handle`](super::ToolHandler::handle).
+///
+/// # Examples
+///
+/// ```rust
+/// use async_trait::async_trait;
+/// use pmcp::server::ToolResult;
+/// use pmcp::{RequestHandlerExtra, ToolHandler};
+/// use serde_json::{json, Value};
+///
+/// struct Chart;
+///
+/// #[async_trait]
+/// impl ToolHandler for Chart {
+///     async fn handle(&self, args: Value, _extra: RequestHandlerExtra) -> pmcp::Result<Value> {
+///         let Some(series) = args["series"].as_array() else {
+///             return Ok(ToolResult::new().error("series must be an array").into());
+///         };
+///         Ok(ToolResult::text(format!("Plotted {} points", series.len()))
+///             .image([0x89, b'P', b'N', b'G'], "image/png")
+///             .json(json!({"points": series.len()}))
+///             .into())
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ToolResult {
+    content: Vec<Content>,
+    is_error: bool,
+}
+
+impl ToolResult {
+    /// Create an empty result.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a result with a text item.
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::new().with_text(text)
+    }
+
+    /// Add a text item.
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.content.push(Content::Text { text: text.into() });
+        self
+    }
+
+    /// Add an image item, base64-encoding `data`.
+    pub fn image(mut self, data: impl AsRef<[u8]>, mime_type: impl Into<String>) -> Self {
+        self.content.push(Content::Image {
+            data: STANDARD.encode(data),
+            mime_type: mime_type.into(),
+        });
+        self
+    }
+
+    /// Add a text item holding `value` as JSON.
+    pub fn json(self, value: impl serde::Serialize) -> Self {
+        let text = serde_json::to_string(&value).unwrap_or_else(|e| e.to_string());
+        self.with_text(text)
+    }
+
+    /// Add a text item with `message` and mark the result as an error.
+    ///
+    /// Use this for failures the model should see and may recover from, such as a
+    /// bad argument; return an [`Error`](crate::Error) for protocol-level failures.
+    pub fn error(mut self, message: impl Into<String>) -> Self {
+        self.is_error = true;
+        self.with_text(message)
+    }
+
+    /// Build the result.
+    pub fn build(self) -> CallToolResult {
+        CallToolResult {
+            content: self.content,
+            is_error: self.is_error,
+            meta: None,
+        }
+    }
+}
+
+impl From<ToolResult> for CallToolResult {
+    fn from(result: ToolResult) -> Self {
+        result.build()
+    }
+}
+
+impl From<ToolResult> for Value {
+    fn from(result: ToolResult) -> Self {
+        serde_json::to_value(result.build()).expect("CallToolResult always serializes to JSON")
+    }
+}
+
+/// Interpret a tool's return value as a complete [`CallToolResult`].
+///
+/// Only objects with a `content` array and no fields besides those of a
+/// `CallToolResult` qualify, so ordinary values are still sent as text.
+pub(crate) fn as_call_tool_result(value: &Value) -> Option<CallToolResult> {
+    let object = value.as_object()?;
+    if !object.get("content").is_some_and(Value::is_array)
+        || object
+            .keys()
+            .any(|key| !matches!(key.as_str(), "content" | "isError" | "_meta"))
+    {
+        return None;
+    }
+    serde_json::from_value(value.clone()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_text() {
+        let result = ToolResult::text("hello").build();
+        assert!(!result.is_error);
+        assert!(matches!(&result.content[..], [Content::Text { text }] if text == "hello"));
+    }
+
+    #[test]
+    fn test_image_is_base64_encoded() {
+        let result = ToolResult::new().image(b"png", "image/png").build();
+        assert!(matches!(
+            &result.content[..],
+            [Content::Image { data, mime_type }] if data == "cG5n" && mime_type == "image/png"
+        ));
+    }
+
+    #[test]
+    fn test_json() {
+        let result = ToolResult::new().json(json!({"a": 1})).build();
+        assert!(matches!(&result.content[..], [Content::Text { text }] if text == r#"{"a":1}"#));
+    }
+
+    #[test]
+    fn test_error() {
+        let result = ToolResult::new().error("bad input").build();
+        assert!(result.is_error);
+        assert!(matches!(&result.content[..], [Content::Text { text }] if text == "bad input"));
+    }
+
+    #[test]
+    fn test_items_keep_their_order() {
+        let result: CallToolResult = ToolResult::text("first")
+            .image([1, 2, 3], "image/gif")
+            .with_text("last")
+            .into();
+        assert_eq!(result.content.len(), 3);
+        assert!(matches!(&result.content[0], Content::Text { text } if text == "first"));
+        assert!(matches!(&result.content[1], Content::Image { .. }));
+        assert!(matches!(&result.content[2], Content::Text { text } if text == "last"));
+    }
+
+    #[test]
+    fn test_value_round_trips() {
+        let value = Value::from(ToolResult::text("done").error("but failed"));
+        let result = as_call_tool_result(&value).unwrap();
+        assert!(result.is_error);
+        assert_eq!(result.content.len(), 2);
+
+        // Ordinary values are not mistaken for results
+        assert!(as_call_tool_result(&json!({"content": "plain"})).is_none());
+        assert!(as_call_tool_result(&json!({"content": [], "extra": 1})).is_none());
+        assert!(as_call_tool_result(&json!([1, 2])).is_none());
+    }
+}
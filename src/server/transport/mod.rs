@@ -1,7 +1,11 @@
 //! Server-specific transport implementations.
 
+#[cfg(feature = "http")]
+pub mod sse;
 #[cfg(feature = "websocket")]
 pub mod websocket;
 
+#[cfg(feature = "http")]
+pub use sse::{SseServerBuilder, SseServerConfig, SseServerTransport};
 #[cfg(feature = "websocket")]
 pub use websocket::{WebSocketServerBuilder, WebSocketServerConfig, WebSocketServerTransport};
@@ -0,0 +1,971 @@
+//! HTTP/SSE server transport implementation.
+//!
+//! Clients POST JSON-RPC messages to one endpoint and read the server's responses
+//! and notifications as Server-Sent Events from another. This is the server side of
+//! [`HttpTransport`](crate::shared::HttpTransport), and the defaults match its
+//! defaults.
+//!
+//! Opening the event stream starts a session, whose ID is sent in the `Mcp-Session-Id`
+//! header; POSTs must carry it, and a client reopening its stream with it gets the
+//! messages sent meanwhile. One session is served at a time: a new client replaces the
+//! previous session once its stream is closed, and the previous session's messages are
+//! dropped rather than sent to the new client.
+//!
+//! Requests from browser pages are only accepted from the origins in
+//! [`SseServerConfig::allowed_origins`], so other sites cannot reach a local server
+//! through DNS rebinding.
+
+use crate::error::{Error, Result, TransportError};
+use crate::shared::http_constants::MCP_SESSION_ID;
+use crate::shared::sse_parser::SseEvent;
+use crate::shared::stdio::StdioTransport;
+use crate::shared::{Transport, TransportMessage, Utf8Mode, DEFAULT_MAX_JSON_DEPTH};
+use async_trait::async_trait;
+use http_body_util::combinators::UnsyncBoxBody;
+use http_body_util::{BodyExt, Empty, Full, LengthLimitError, Limited, StreamBody};
+use hyper::body::{Bytes, Frame, Incoming};
+use hyper::header::{HeaderMap, HeaderValue, CACHE_CONTROL, CONTENT_TYPE, ORIGIN};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::{mpsc, Mutex, Notify, OwnedMutexGuard};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+type Body = UnsyncBoxBody<Bytes, Infallible>;
+
+/// Default limit on the size of a POSTed body, in bytes.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 4 * 1024 * 1024;
+
+/// Messages kept for a session while its event stream is not being read.
+const OUTGOING_CAPACITY: usize = 100;
+
+/// Configuration for HTTP/SSE server transport.
+#[derive(Debug, Clone)]
+pub struct SseServerConfig {
+    /// Address to bind to
+    pub bind_addr: SocketAddr,
+    /// Path clients POST messages to
+    pub message_path: String,
+    /// Path clients read the event stream from
+    pub events_path: String,
+    /// Origins browser pages may send requests from, e.g. `https://app.example.com`
+    ///
+    /// When empty, only pages served from this machine (`localhost`, `127.0.0.1` or
+    /// `[::1]`, on any port) are allowed. Requests without an `Origin` header do not
+    /// come from a browser page and are always allowed.
+    pub allowed_origins: Vec<String>,
+    /// Largest POSTed body accepted, in bytes
+    pub max_body_size: usize,
+}
+
+impl Default for SseServerConfig {
+    fn default() -> Self {
+        Self {
+            bind_addr: "127.0.0.1:8080".parse().expect("Valid default address"),
+            message_path: "/".to_string(),
+            events_path: "/events".to_string(),
+            allowed_origins: Vec::new(),
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+        }
+    }
+}
+
+/// The client being served, and the messages sent to it.
+#[derive(Debug)]
+struct Session {
+    id: String,
+    tx: mpsc::Sender<TransportMessage>,
+    // Held by the open event stream
+    rx: Arc<Mutex<mpsc::Receiver<TransportMessage>>>,
+    /// Notified when the event stream closes
+    closed: Arc<Notify>,
+}
+
+impl Session {
+    fn new() -> Self {
+        let (tx, rx) = mpsc::channel(OUTGOING_CAPACITY);
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            tx,
+            rx: Arc::new(Mutex::new(rx)),
+            closed: Arc::new(Notify::new()),
+        }
+    }
+}
+
+/// Whether an event stream is reading the messages of a session.
+fn streaming(rx: &Mutex<mpsc::Receiver<TransportMessage>>) -> bool {
+    rx.try_lock().is_err()
+}
+
+/// HTTP/SSE server transport.
+///
+/// Messages POSTed by clients are received in order. Sent messages are streamed to
+/// the session's event stream. While the stream is closed, up to 100 messages are kept
+/// until it is reopened; further messages, and messages sent before any client opened
+/// a stream, are dropped with a warning instead of holding up the server.
+#[derive(Debug)]
+pub struct SseServerTransport {
+    config: SseServerConfig,
+    local_addr: Option<SocketAddr>,
+    // Moved into the endpoints on bind
+    incoming_tx: Option<mpsc::Sender<TransportMessage>>,
+    incoming_rx: mpsc::Receiver<TransportMessage>,
+    session: Arc<parking_lot::Mutex<Option<Session>>>,
+    shutdown: CancellationToken,
+    /// Maximum nesting depth of POSTed messages
+    max_json_depth: Arc<AtomicUsize>,
+    /// Handling of invalid UTF-8 in POSTed messages
+    utf8: Arc<parking_lot::Mutex<Utf8Mode>>,
+}
+
+impl SseServerTransport {
+    /// Create a new HTTP/SSE server transport with the given configuration.
+    pub fn new(config: SseServerConfig) -> Self {
+        let (incoming_tx, incoming_rx) = mpsc::channel(100);
+        Self {
+            config,
+            local_addr: None,
+            incoming_tx: Some(incoming_tx),
+            incoming_rx,
+            session: Arc::default(),
+            shutdown: CancellationToken::new(),
+            max_json_depth: Arc::new(AtomicUsize::new(DEFAULT_MAX_JSON_DEPTH)),
+            utf8: Arc::default(),
+        }
+    }
+
+    /// Create a new HTTP/SSE server transport with default configuration.
+    pub fn default_server() -> Self {
+        Self::new(SseServerConfig::default())
+    }
+
+    /// Bind and start serving the endpoints in the background.
+    pub async fn bind(&mut self) -> Result<()> {
+        let incoming = self
+            .incoming_tx
+            .take()
+            .ok_or_else(|| Error::internal("Server already bound"))?;
+        let listener = TcpListener::bind(&self.config.bind_addr)
+            .await
+            .map_err(|e| {
+                Error::internal(format!(
+                    "Failed to bind to {}: {}",
+                    self.config.bind_addr, e
+                ))
+            })?;
+        let local_addr = listener
+            .local_addr()
+            .map_err(|e| Error::internal(format!("Failed to get local address: {}", e)))?;
+        info!("SSE server listening on {}", local_addr);
+
+        let endpoints = Arc::new(Endpoints {
+            message_path: self.config.message_path.clone(),
+            events_path: self.config.events_path.clone(),
+            allowed_origins: self.config.allowed_origins.clone(),
+            max_body_size: self.config.max_body_size,
+            incoming,
+            session: Arc::clone(&self.session),
+            max_json_depth: Arc::clone(&self.max_json_depth),
+            utf8: Arc::clone(&self.utf8),
+        });
+        tokio::spawn(serve(listener, endpoints, self.shutdown.clone()));
+        self.local_addr = Some(local_addr);
+        Ok(())
+    }
+
+    /// The address the server listens on, once bound.
+    ///
+    /// Useful when binding to port 0.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+}
+
+#[async_trait]
+impl Transport for SseServerTransport {
+    async fn send(&mut self, message: TransportMessage) -> Result<()> {
+        if self.shutdown.is_cancelled() {
+            return Err(TransportError::ConnectionClosed.into());
+        }
+        let Some((tx, rx, closed)) = self.session.lock().as_ref().map(|session| {
+            (
+                session.tx.clone(),
+                Arc::clone(&session.rx),
+                Arc::clone(&session.closed),
+            )
+        }) else {
+            warn!("Dropping message: no client has opened the event stream");
+            return Ok(());
+        };
+
+        // Wait for an open stream to take messages, but not for a closed one to reopen
+        let closing = closed.notified();
+        tokio::pin!(closing);
+        closing.as_mut().enable();
+        match tx.try_send(message) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(message)) if streaming(&rx) => tokio::select! {
+                sent = tx.send(message) => sent.map_err(|_| TransportError::ConnectionClosed.into()),
+                () = closing => {
+                    warn!("Dropping message: the event stream closed while it was full");
+                    Ok(())
+                },
+            },
+            Err(TrySendError::Full(_)) => {
+                warn!(
+                    "Dropping message: no event stream is open and {} messages are waiting",
+                    OUTGOING_CAPACITY
+                );
+                Ok(())
+            },
+            Err(TrySendError::Closed(_)) => Err(TransportError::ConnectionClosed.into()),
+        }
+    }
+
+    async fn receive(&mut self) -> Result<TransportMessage> {
+        if self.local_addr.is_none() {
+            return Err(Error::internal("Server not bound"));
+        }
+        self.incoming_rx
+            .recv()
+            .await
+            .ok_or_else(|| TransportError::ConnectionClosed.into())
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        // Ends the event stream once its queued messages are sent
+        self.session.lock().take();
+        self.shutdown.cancel();
+
+        info!("SSE server transport closed");
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.local_addr.is_some() && !self.shutdown.is_cancelled()
+    }
+
+    fn transport_type(&self) -> &'static str {
+        "sse-server"
+    }
+
+    fn set_utf8_mode(&mut self, mode: Utf8Mode) {
+        *self.utf8.lock() = mode;
+    }
+
+    fn set_max_json_depth(&mut self, depth: usize) {
+        self.max_json_depth.store(depth, Ordering::Relaxed);
+    }
+}
+
+/// An open event stream, reading the messages of its session.
+struct EventStream {
+    outgoing: OwnedMutexGuard<mpsc::Receiver<TransportMessage>>,
+    closed: Arc<Notify>,
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        self.closed.notify_waiters();
+    }
+}
+
+/// State shared by the connections.
+#[derive(Debug)]
+struct Endpoints {
+    message_path: String,
+    events_path: String,
+    allowed_origins: Vec<String>,
+    max_body_size: usize,
+    incoming: mpsc::Sender<TransportMessage>,
+    session: Arc<parking_lot::Mutex<Option<Session>>>,
+    max_json_depth: Arc<AtomicUsize>,
+    utf8: Arc<parking_lot::Mutex<Utf8Mode>>,
+}
+
+impl Endpoints {
+    async fn handle(&self, request: Request<Incoming>) -> Response<Body> {
+        if !self.origin_allowed(request.headers()) {
+            return text(StatusCode::FORBIDDEN, "Origin not allowed");
+        }
+        let path = request.uri().path();
+        let (messages, events) = (path == self.message_path, path == self.events_path);
+        let method = request.method().clone();
+        match method {
+            Method::POST if messages => self.post(request).await,
+            Method::GET if events => self.events(request.headers()),
+            _ if messages || events => empty(StatusCode::METHOD_NOT_ALLOWED),
+            _ => empty(StatusCode::NOT_FOUND),
+        }
+    }
+
+    /// Whether a request may come from the browser page it was sent by, if any.
+    fn origin_allowed(&self, headers: &HeaderMap) -> bool {
+        let Some(origin) = headers.get(ORIGIN) else {
+            return true;
+        };
+        let Ok(origin) = origin.to_str() else {
+            return false;
+        };
+        if !self.allowed_origins.is_empty() {
+            return self.allowed_origins.iter().any(|allowed| allowed == origin);
+        }
+        url::Url::parse(origin)
+            .is_ok_and(|url| matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]")))
+    }
+
+    /// Queue the POSTed message or batch, to be answered on the event stream.
+    async fn post(&self, request: Request<Incoming>) -> Response<Body> {
+        let session_id = request
+            .headers()
+            .get(MCP_SESSION_ID)
+            .and_then(|id| id.to_str().ok());
+        let Some(session_id) = session_id else {
+            return text(
+                StatusCode::BAD_REQUEST,
+                "Missing Mcp-Session-Id header; open the event stream first",
+            );
+        };
+        let known = self
+            .session
+            .lock()
+            .as_ref()
+            .is_some_and(|session| session.id == session_id);
+        if !known {
+            return text(StatusCode::NOT_FOUND, "Unknown session");
+        }
+
+        let body = match Limited::new(request.into_body(), self.max_body_size)
+            .collect()
+            .await
+        {
+            Ok(body) => body.to_bytes(),
+            Err(e) if e.is::<LengthLimitError>() => {
+                return text(
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    format!("Body exceeds {} bytes", self.max_body_size),
+                )
+            },
+            Err(e) => return text(StatusCode::BAD_REQUEST, format!("Invalid body: {}", e)),
+        };
+        let mode = *self.utf8.lock();
+        let body = match mode.decode(&body) {
+            Ok(body) => body,
+            Err(e) => return text(StatusCode::BAD_REQUEST, e.to_string()),
+        };
+        let max_depth = self.max_json_depth.load(Ordering::Relaxed);
+        let messages = match StdioTransport::parse_messages_with_max_depth(&body, max_depth) {
+            Ok(messages) => messages,
+            Err(e) => return text(StatusCode::BAD_REQUEST, e.to_string()),
+        };
+        for message in messages {
+            if self.incoming.send(message).await.is_err() {
+                return empty(StatusCode::SERVICE_UNAVAILABLE);
+            }
+        }
+        empty(StatusCode::ACCEPTED)
+    }
+
+    /// Stream the messages sent to the session as events.
+    ///
+    /// Resumes the session named by the `Mcp-Session-Id` header, or starts a new one.
+    fn events(&self, headers: &HeaderMap) -> Response<Body> {
+        let requested = headers.get(MCP_SESSION_ID).and_then(|id| id.to_str().ok());
+        let mut current = self.session.lock();
+        match (requested, current.as_ref()) {
+            (Some(id), Some(session)) if session.id == id => {},
+            (Some(_), _) => return text(StatusCode::NOT_FOUND, "Unknown session"),
+            (None, Some(session)) if streaming(&session.rx) => {
+                return text(StatusCode::CONFLICT, "An event stream is already open")
+            },
+            // A new client, so what was kept for the previous one is dropped
+            (None, _) => *current = Some(Session::new()),
+        }
+        let Some(session) = current.as_ref() else {
+            return empty(StatusCode::INTERNAL_SERVER_ERROR);
+        };
+        // A second stream would take messages meant for the first
+        let Ok(outgoing) = Arc::clone(&session.rx).try_lock_owned() else {
+            return text(StatusCode::CONFLICT, "An event stream is already open");
+        };
+        let stream = EventStream {
+            outgoing,
+            closed: Arc::clone(&session.closed),
+        };
+        let session_id = HeaderValue::from_str(&session.id).ok();
+        drop(current);
+
+        let frames = futures::stream::unfold(stream, |mut stream| async move {
+            loop {
+                let message = stream.outgoing.recv().await?;
+                let json = match StdioTransport::serialize_message(&message).and_then(|json| {
+                    String::from_utf8(json).map_err(|e| Error::internal(e.to_string()))
+                }) {
+                    Ok(json) => json,
+                    Err(e) => {
+                        error!("Failed to serialize message: {}", e);
+                        continue;
+                    },
+                };
+                let event = SseEvent::new(json).to_string();
+                return Some((Ok(Frame::data(Bytes::from(event))), stream));
+            }
+        });
+
+        let mut response = Response::new(StreamBody::new(frames).boxed_unsync());
+        let headers = response.headers_mut();
+        headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static(crate::shared::http_constants::TEXT_EVENT_STREAM),
+        );
+        headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+        if let Some(session_id) = session_id {
+            headers.insert(MCP_SESSION_ID, session_id);
+        }
+        response
+    }
+}
+
+async fn serve(listener: TcpListener, endpoints: Arc<Endpoints>, shutdown: CancellationToken) {
+    loop {
+        let (stream, peer_addr) = tokio::select! {
+            () = shutdown.cancelled() => break,
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    error!("Failed to accept connection: {}", e);
+                    continue;
+                },
+            },
+        };
+
+        let endpoints = Arc::clone(&endpoints);
+        let shutdown = shutdown.clone();
+        tokio::spawn(async move {
+            let service = service_fn(move |request| {
+                let endpoints = Arc::clone(&endpoints);
+                async move { Ok::<_, Infallible>(endpoints.handle(request).await) }
+            });
+            let connection = http1::Builder::new().serve_connection(TokioIo::new(stream), service);
+            tokio::select! {
+                result = connection => {
+                    if let Err(e) = result {
+                        warn!("Connection from {} failed: {}", peer_addr, e);
+                    }
+                },
+                () = shutdown.cancelled() => {},
+            }
+        });
+    }
+}
+
+fn empty(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Empty::new().boxed_unsync());
+    *response.status_mut() = status;
+    response
+}
+
+fn text(status: StatusCode, message: impl Into<String>) -> Response<Body> {
+    let mut response = Response::new(Full::new(Bytes::from(message.into())).boxed_unsync());
+    *response.status_mut() = status;
+    response
+}
+
+/// Builder for HTTP/SSE server transport.
+#[derive(Debug)]
+pub struct SseServerBuilder {
+    config: SseServerConfig,
+}
+
+impl SseServerBuilder {
+    /// Create a new builder with default configuration.
+    pub fn new() -> Self {
+        Self {
+            config: SseServerConfig::default(),
+        }
+    }
+
+    /// Set the bind address.
+    pub fn bind_addr(mut self, addr: SocketAddr) -> Self {
+        self.config.bind_addr = addr;
+        self
+    }
+
+    /// Set the path clients POST messages to.
+    pub fn message_path(mut self, path: impl Into<String>) -> Self {
+        self.config.message_path = path.into();
+        self
+    }
+
+    /// Set the path clients read the event stream from.
+    pub fn events_path(mut self, path: impl Into<String>) -> Self {
+        self.config.events_path = path.into();
+        self
+    }
+
+    /// Allow requests from browser pages served from `origin`, e.g.
+    /// `https://app.example.com`.
+    ///
+    /// See [`SseServerConfig::allowed_origins`].
+    pub fn allowed_origin(mut self, origin: impl Into<String>) -> Self {
+        self.config.allowed_origins.push(origin.into());
+        self
+    }
+
+    /// Set the largest POSTed body accepted, in bytes.
+    pub fn max_body_size(mut self, size: usize) -> Self {
+        self.config.max_body_size = size;
+        self
+    }
+
+    /// Build the transport.
+    pub fn build(self) -> SseServerTransport {
+        SseServerTransport::new(self.config)
+    }
+}
+
+impl Default for SseServerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::Server;
+    use crate::shared::sse_parser::SseParser;
+    use crate::types::{Notification, ProgressNotification, ProgressToken};
+    use hyper_util::client::legacy::{connect::HttpConnector, Client};
+    use hyper_util::rt::TokioExecutor;
+    use serde_json::{json, Value};
+    use std::collections::VecDeque;
+    use std::time::Duration;
+
+    type HttpClient = Client<HttpConnector, Full<Bytes>>;
+
+    /// Messages read from an event stream.
+    struct Events {
+        session_id: String,
+        body: Incoming,
+        parser: SseParser,
+        parsed: VecDeque<Value>,
+    }
+
+    impl Events {
+        /// Open a stream for a new session.
+        async fn open(client: &HttpClient, addr: SocketAddr) -> Self {
+            Self::resume(client, addr, None).await
+        }
+
+        async fn resume(client: &HttpClient, addr: SocketAddr, session_id: Option<&str>) -> Self {
+            let mut request = Request::get(format!("http://{}/events", addr));
+            if let Some(session_id) = session_id {
+                request = request.header(MCP_SESSION_ID, session_id);
+            }
+            let response = client
+                .request(request.body(Full::default()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()[CONTENT_TYPE], "text/event-stream");
+            Self {
+                session_id: response.headers()[MCP_SESSION_ID]
+                    .to_str()
+                    .unwrap()
+                    .to_string(),
+                body: response.into_body(),
+                parser: SseParser::new(),
+                parsed: VecDeque::new(),
+            }
+        }
+
+        /// POST `body` to the session.
+        async fn post(
+            &self,
+            client: &HttpClient,
+            addr: SocketAddr,
+            body: impl Into<Bytes>,
+        ) -> Response<Incoming> {
+            let request = Request::post(format!("http://{}/", addr))
+                .header(CONTENT_TYPE, "application/json")
+                .header(MCP_SESSION_ID, &self.session_id)
+                .body(Full::new(body.into()))
+                .unwrap();
+            client.request(request).await.unwrap()
+        }
+
+        async fn next(&mut self) -> Value {
+            loop {
+                if let Some(message) = self.parsed.pop_front() {
+                    return message;
+                }
+                let frame = tokio::time::timeout(Duration::from_secs(5), self.body.frame())
+                    .await
+                    .expect("event in time")
+                    .expect("open stream")
+                    .unwrap();
+                if let Some(data) = frame.data_ref() {
+                    let events = self.parser.feed(&String::from_utf8_lossy(data));
+                    self.parsed.extend(
+                        events
+                            .iter()
+                            .map(|event| serde_json::from_str::<Value>(&event.data).unwrap()),
+                    );
+                }
+            }
+        }
+    }
+
+    async fn bound() -> SseServerTransport {
+        let mut transport = SseServerBuilder::new()
+            .bind_addr("127.0.0.1:0".parse().unwrap())
+            .build();
+        transport.bind().await.unwrap();
+        transport
+    }
+
+    fn client() -> HttpClient {
+        Client::builder(TokioExecutor::new()).build_http()
+    }
+
+    #[test]
+    fn test_config_default() {
+        let config = SseServerConfig::default();
+        assert_eq!(config.bind_addr.to_string(), "127.0.0.1:8080");
+        assert_eq!(config.message_path, "/");
+        assert_eq!(config.events_path, "/events");
+    }
+
+    #[test]
+    fn test_builder() {
+        let transport = SseServerBuilder::new()
+            .bind_addr("127.0.0.1:9003".parse().unwrap())
+            .message_path("/mcp")
+            .events_path("/mcp/events")
+            .build();
+
+        assert_eq!(transport.config.bind_addr.to_string(), "127.0.0.1:9003");
+        assert_eq!(transport.config.message_path, "/mcp");
+        assert_eq!(transport.config.events_path, "/mcp/events");
+        assert!(!transport.is_connected());
+    }
+
+    #[tokio::test]
+    async fn test_initialize_is_answered_over_sse() {
+        let transport = bound().await;
+        let addr = transport.local_addr().unwrap();
+        let server = Server::builder()
+            .name("sse-server")
+            .version("1.0.0")
+            .build()
+            .unwrap();
+        tokio::spawn(server.run(transport));
+
+        let client = client();
+        let mut events = Events::open(&client, addr).await;
+        let initialize = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": crate::LATEST_PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": {"name": "sse-client", "version": "1.0.0"}
+            }
+        });
+        let response = events.post(&client, addr, initialize.to_string()).await;
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        let message = events.next().await;
+        assert_eq!(message["id"], 1);
+        assert_eq!(message["result"]["serverInfo"]["name"], "sse-server");
+    }
+
+    #[tokio::test]
+    async fn test_notification_is_streamed_over_sse() {
+        let mut transport = bound().await;
+        let addr = transport.local_addr().unwrap();
+        let client = client();
+        let mut events = Events::open(&client, addr).await;
+
+        transport
+            .send(TransportMessage::Notification(Notification::Progress(
+                ProgressNotification {
+                    progress_token: ProgressToken::String("task-1".to_string()),
+                    progress: 50.0,
+                    message: None,
+                },
+            )))
+            .await
+            .unwrap();
+
+        let message = events.next().await;
+        assert_eq!(message["method"], "notifications/progress");
+        assert_eq!(message["params"]["progressToken"], "task-1");
+
+        // The stream is taken until the first one goes away
+        let request = Request::get(format!("http://{}/events", addr))
+            .body(Full::default())
+            .unwrap();
+        let response = client.request(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_server_max_json_depth_is_applied_to_posts() {
+        let transport = bound().await;
+        let addr = transport.local_addr().unwrap();
+        let server = Server::builder()
+            .name("sse-server")
+            .version("1.0.0")
+            .max_json_depth(4)
+            .build()
+            .unwrap();
+        tokio::spawn(server.run(transport));
+        let client = client();
+        let events = Events::open(&client, addr).await;
+
+        let post = |body: Value| events.post(&client, addr, body.to_string());
+        let deep = json!({"jsonrpc": "2.0", "method": "notifications/progress",
+            "params": {"a": {"b": {"c": {}}}}});
+        let response = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                // The limit is set once the server runs
+                let response = post(deep.clone()).await;
+                if response.status() != StatusCode::ACCEPTED {
+                    return response;
+                }
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("deep message should be rejected");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(String::from_utf8_lossy(&body).contains("max depth exceeded"));
+
+        let shallow = json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+        let response = post(shallow).await;
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+    }
+
+    #[tokio::test]
+    async fn test_server_utf8_mode_is_applied_to_posts() {
+        let transport = bound().await;
+        let addr = transport.local_addr().unwrap();
+        let client = client();
+        let events = Events::open(&client, addr).await;
+        let body = &b"{\"jsonrpc\":\"2.0\",\"method\":\"notifications/cancelled\",\
+            \"params\":{\"requestId\":1,\"reason\":\"caf\xE9\"}}"[..];
+
+        // Invalid UTF-8 is rejected by default
+        let response = events.post(&client, addr, body).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let text = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(String::from_utf8_lossy(&text).contains("Invalid UTF-8"));
+
+        let server = Server::builder()
+            .name("sse-server")
+            .version("1.0.0")
+            .utf8(Utf8Mode::Lossy)
+            .build()
+            .unwrap();
+        tokio::spawn(server.run(transport));
+        tokio::time::timeout(Duration::from_secs(5), async {
+            // The mode is set once the server runs
+            while events.post(&client, addr, body).await.status() != StatusCode::ACCEPTED {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("lossy server should accept invalid UTF-8");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_post_is_rejected() {
+        let transport = bound().await;
+        let addr = transport.local_addr().unwrap();
+        let client = client();
+        let events = Events::open(&client, addr).await;
+
+        let response = events.post(&client, addr, "not json").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let request = Request::get(format!("http://{}/unknown", addr))
+            .body(Full::default())
+            .unwrap();
+        let response = client.request(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_posts_need_the_open_session() {
+        let transport = bound().await;
+        let addr = transport.local_addr().unwrap();
+        let client = client();
+        let ping = json!({"jsonrpc": "2.0", "id": 1, "method": "ping"}).to_string();
+        let post = |session_id: Option<&str>| {
+            let mut request = Request::post(format!("http://{}/", addr));
+            if let Some(session_id) = session_id {
+                request = request.header(MCP_SESSION_ID, session_id);
+            }
+            client.request(request.body(Full::new(Bytes::from(ping.clone()))).unwrap())
+        };
+
+        // Before any stream is opened there is no session to post to
+        assert_eq!(post(None).await.unwrap().status(), StatusCode::BAD_REQUEST);
+        let events = Events::open(&client, addr).await;
+        assert_eq!(post(None).await.unwrap().status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            post(Some("other")).await.unwrap().status(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            post(Some(&events.session_id)).await.unwrap().status(),
+            StatusCode::ACCEPTED
+        );
+
+        // Another client cannot take over the open session
+        let request = Request::get(format!("http://{}/events", addr))
+            .header(MCP_SESSION_ID, "other")
+            .body(Full::default())
+            .unwrap();
+        let response = client.request(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_sending_without_an_open_stream_does_not_block() {
+        let mut transport = bound().await;
+        let addr = transport.local_addr().unwrap();
+        let notification = |n: usize| {
+            TransportMessage::Notification(Notification::Progress(ProgressNotification {
+                progress_token: ProgressToken::String(format!("task-{}", n)),
+                progress: 50.0,
+                message: None,
+            }))
+        };
+
+        // Nobody to send to yet
+        transport.send(notification(0)).await.unwrap();
+
+        let session = Session::new();
+        let session_id = session.id.clone();
+        *transport.session.lock() = Some(session);
+        tokio::time::timeout(Duration::from_secs(5), async {
+            for n in 1..=OUTGOING_CAPACITY + 10 {
+                transport.send(notification(n)).await.unwrap();
+            }
+        })
+        .await
+        .expect("messages beyond the buffer should be dropped");
+
+        // Reopening the stream delivers what was kept for the session
+        let client = client();
+        let mut events = Events::resume(&client, addr, Some(&session_id)).await;
+        assert_eq!(events.session_id, session_id);
+        assert_eq!(events.next().await["params"]["progressToken"], "task-1");
+    }
+
+    #[tokio::test]
+    async fn test_origins_are_checked() {
+        let transport = bound().await;
+        let addr = transport.local_addr().unwrap();
+        let client = client();
+        let open = |origin: &'static str| {
+            let request = Request::get(format!("http://{}/events", addr))
+                .header(ORIGIN, origin)
+                .body(Full::default())
+                .unwrap();
+            client.request(request)
+        };
+
+        let response = open("https://evil.example.com").await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = open("http://localhost:3000").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let mut transport = SseServerBuilder::new()
+            .bind_addr("127.0.0.1:0".parse().unwrap())
+            .allowed_origin("https://app.example.com")
+            .build();
+        transport.bind().await.unwrap();
+        let addr = transport.local_addr().unwrap();
+        let open = |origin: &'static str| {
+            let request = Request::get(format!("http://{}/events", addr))
+                .header(ORIGIN, origin)
+                .body(Full::default())
+                .unwrap();
+            client.request(request)
+        };
+        let response = open("http://localhost:3000").await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let response = open("https://app.example.com").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_large_posts_are_rejected() {
+        let mut transport = SseServerBuilder::new()
+            .bind_addr("127.0.0.1:0".parse().unwrap())
+            .max_body_size(64)
+            .build();
+        transport.bind().await.unwrap();
+        let addr = transport.local_addr().unwrap();
+        let client = client();
+        let events = Events::open(&client, addr).await;
+
+        let large = json!({"jsonrpc": "2.0", "method": "notifications/initialized",
+            "params": {"padding": "x".repeat(64)}});
+        let response = events.post(&client, addr, large.to_string()).await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let small = json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+        let response = events.post(&client, addr, small.to_string()).await;
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+    }
+
+    #[tokio::test]
+    async fn test_http_transport_joins_the_session() {
+        let transport = bound().await;
+        let addr = transport.local_addr().unwrap();
+        let server = Server::builder()
+            .name("sse-server")
+            .version("1.0.0")
+            .build()
+            .unwrap();
+        tokio::spawn(server.run(transport));
+
+        let mut client = crate::shared::HttpTransport::with_url(
+            url::Url::parse(&format!("http://{}/", addr)).unwrap(),
+        )
+        .unwrap();
+        client.connect_sse().await.unwrap();
+        client
+            .send(TransportMessage::Request {
+                id: crate::types::RequestId::from(1i64),
+                request: crate::types::Request::Client(Box::new(crate::types::ClientRequest::Ping)),
+            })
+            .await
+            .unwrap();
+
+        let message = tokio::time::timeout(Duration::from_secs(5), client.receive())
+            .await
+            .expect("ping should be answered")
+            .unwrap();
+        assert!(matches!(
+            message,
+            TransportMessage::Response(response) if response.id == crate::types::RequestId::from(1i64)
+        ));
+    }
+}
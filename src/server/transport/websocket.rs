@@ -1,10 +1,11 @@
 //! WebSocket server transport implementation.
 
 use crate::error::{Error, Result};
-use crate::shared::{Transport, TransportMessage};
+use crate::shared::{Transport, TransportMessage, DEFAULT_MAX_JSON_DEPTH};
 use async_trait::async_trait;
 use futures::{SinkExt, StreamExt};
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::net::TcpListener;
 use tokio::sync::{mpsc, Mutex};
@@ -42,6 +43,8 @@ pub struct WebSocketServerTransport {
     // Channels for communicating with the active connection
     incoming_rx: Arc<Mutex<Option<mpsc::Receiver<TransportMessage>>>>,
     outgoing_tx: Arc<Mutex<Option<mpsc::Sender<TransportMessage>>>>,
+    /// Maximum nesting depth of inbound messages
+    max_json_depth: Arc<AtomicUsize>,
 }
 
 impl WebSocketServerTransport {
@@ -52,6 +55,7 @@ impl WebSocketServerTransport {
             listener: None,
             incoming_rx: Arc::new(Mutex::new(None)),
             outgoing_tx: Arc::new(Mutex::new(None)),
+            max_json_depth: Arc::new(AtomicUsize::new(DEFAULT_MAX_JSON_DEPTH)),
         }
     }
 
@@ -150,11 +154,15 @@ impl WebSocketServerTransport {
         });
 
         // Spawn task to handle incoming messages
+        let max_json_depth = Arc::clone(&self.max_json_depth);
         tokio::spawn(async move {
             while let Some(result) = ws_stream.next().await {
                 match result {
                     Ok(Message::Text(text)) => {
-                        match crate::shared::stdio::StdioTransport::parse_message(text.as_bytes()) {
+                        match crate::shared::stdio::StdioTransport::parse_message_with_max_depth(
+                            text.as_bytes(),
+                            max_json_depth.load(Ordering::Relaxed),
+                        ) {
                             Ok(msg) => {
                                 if let Err(e) = incoming_tx.send(msg).await {
                                     error!("Failed to queue incoming message: {}", e);
@@ -247,6 +255,10 @@ impl Transport for WebSocketServerTransport {
     fn transport_type(&self) -> &'static str {
         "websocket-server"
     }
+
+    fn set_max_json_depth(&mut self, depth: usize) {
+        self.max_json_depth.store(depth, Ordering::Relaxed);
+    }
 }
 
 impl std::fmt::Debug for WebSocketServerTransport {
@@ -0,0 +1,222 @@
+//! Tools with typed arguments and results.
+//!
+//! [`TypedTool`] wraps an async function taking a deserializable argument type, and
+//! [`TypedToolIo`] one also returning a serializable result type. The JSON Schemas of
+//! these types are generated with `schemars` and advertised through
+//! [`ToolHandler::metadata`], so clients see `inputSchema` (and `outputSchema`) in
+//! `tools/list`.
+
+use super::cancellation::RequestHandlerExtra;
+use super::ToolHandler;
+use crate::error::{Error, Result};
+use crate::types::ToolInfo;
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use std::future::Future;
+use std::marker::PhantomData;
+
+/// A tool handler with typed arguments `P`.
+///
+/// Arguments that do not deserialize into `P` are rejected with a validation error
+/// before the handler runs. The handler's result is sent as is.
+///
+/// # Examples
+///
+/// ```rust
+/// use pmcp::server::typed_tool::TypedTool;
+/// use pmcp::ToolHandler;
+/// use schemars::JsonSchema;
+/// use serde::Deserialize;
+/// use serde_json::json;
+///
+/// #[derive(Deserialize, JsonSchema)]
+/// struct GreetArgs {
+///     name: String,
+/// }
+///
+/// let tool = TypedTool::new("greet", |args: GreetArgs, _extra| async move {
+///     Ok(json!({"greeting": format!("Hello, {}!", args.name)}))
+/// })
+/// .with_description("Greet someone by name");
+///
+/// let info = tool.metadata().unwrap();
+/// assert_eq!(info.input_schema["properties"]["name"]["type"], "string");
+/// assert_eq!(info.description.as_deref(), Some("Greet someone by name"));
+/// ```
+pub struct TypedTool<P, F> {
+    name: String,
+    description: Option<String>,
+    input_schema: Value,
+    handler: F,
+    _types: PhantomData<fn(P)>,
+}
+
+impl<P, F, Fut> TypedTool<P, F>
+where
+    P: DeserializeOwned + JsonSchema + Send + 'static,
+    F: Fn(P, RequestHandlerExtra) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Value>> + Send + 'static,
+{
+    /// Create a typed tool, generating the schema of `P`.
+    pub fn new(name: impl Into<String>, handler: F) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            input_schema: schemars::schema_for!(P).to_value(),
+            handler,
+            _types: PhantomData,
+        }
+    }
+
+    /// Set the description advertised in `tools/list`.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+impl<P, F> fmt::Debug for TypedTool<P, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypedTool")
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .field("input_schema", &self.input_schema)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl<P, F, Fut> ToolHandler for TypedTool<P, F>
+where
+    P: DeserializeOwned + JsonSchema + Send + 'static,
+    F: Fn(P, RequestHandlerExtra) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Value>> + Send + 'static,
+{
+    async fn handle(&self, args: Value, extra: RequestHandlerExtra) -> Result<Value> {
+        let params: P = serde_json::from_value(args).map_err(|e| {
+            Error::validation(format!("Invalid arguments for tool '{}': {}", self.name, e))
+        })?;
+        (self.handler)(params, extra).await
+    }
+
+    fn metadata(&self) -> Option<ToolInfo> {
+        Some(ToolInfo {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            input_schema: self.input_schema.clone(),
+            category: None,
+            tags: Vec::new(),
+            output_schema: None,
+        })
+    }
+}
+
+/// A tool handler with typed arguments `P` and a typed result `R`.
+///
+/// Arguments that do not deserialize into `P` are rejected with a validation error
+/// before the handler runs.
+///
+/// # Examples
+///
+/// ```rust
+/// use pmcp::server::typed_tool::TypedToolIo;
+/// use pmcp::ToolHandler;
+/// use schemars::JsonSchema;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Deserialize, JsonSchema)]
+/// struct AddArgs {
+///     a: i64,
+///     b: i64,
+/// }
+///
+/// #[derive(Serialize, JsonSchema)]
+/// struct Sum {
+///     sum: i64,
+/// }
+///
+/// let tool = TypedToolIo::new("add", |args: AddArgs, _extra| async move {
+///     Ok(Sum { sum: args.a + args.b })
+/// });
+///
+/// let info = tool.metadata().unwrap();
+/// assert_eq!(info.input_schema["properties"]["a"]["type"], "integer");
+/// assert!(info.output_schema.is_some());
+/// ```
+pub struct TypedToolIo<P, R, F> {
+    name: String,
+    description: Option<String>,
+    input_schema: Value,
+    output_schema: Value,
+    handler: F,
+    _types: PhantomData<fn(P) -> R>,
+}
+
+impl<P, R, F, Fut> TypedToolIo<P, R, F>
+where
+    P: DeserializeOwned + JsonSchema + Send + 'static,
+    R: Serialize + JsonSchema + Send + 'static,
+    F: Fn(P, RequestHandlerExtra) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<R>> + Send + 'static,
+{
+    /// Create a typed tool, generating the schemas of `P` and `R`.
+    pub fn new(name: impl Into<String>, handler: F) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            input_schema: schemars::schema_for!(P).to_value(),
+            output_schema: schemars::schema_for!(R).to_value(),
+            handler,
+            _types: PhantomData,
+        }
+    }
+
+    /// Set the description advertised in `tools/list`.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+impl<P, R, F> fmt::Debug for TypedToolIo<P, R, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypedToolIo")
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .field("input_schema", &self.input_schema)
+            .field("output_schema", &self.output_schema)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl<P, R, F, Fut> ToolHandler for TypedToolIo<P, R, F>
+where
+    P: DeserializeOwned + JsonSchema + Send + 'static,
+    R: Serialize + JsonSchema + Send + 'static,
+    F: Fn(P, RequestHandlerExtra) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<R>> + Send + 'static,
+{
+    async fn handle(&self, args: Value, extra: RequestHandlerExtra) -> Result<Value> {
+        let params: P = serde_json::from_value(args).map_err(|e| {
+            Error::validation(format!("Invalid arguments for tool '{}': {}", self.name, e))
+        })?;
+        let result = (self.handler)(params, extra).await?;
+        Ok(serde_json::to_value(result)?)
+    }
+
+    fn metadata(&self) -> Option<ToolInfo> {
+        Some(ToolInfo {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            input_schema: self.input_schema.clone(),
+            category: None,
+            tags: Vec::new(),
+            output_schema: Some(self.output_schema.clone()),
+        })
+    }
+}
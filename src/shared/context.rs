@@ -5,6 +5,7 @@
 
 use crate::types::RequestId;
 use serde::{Deserialize, Serialize};
+use std::any::{Any, TypeId};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::task_local;
@@ -312,6 +313,76 @@ impl RequestContext {
     }
 }
 
+/// Values kept for the lifetime of one connection, keyed by their type.
+///
+/// Request middleware stores what it learns about the caller, such as the
+/// authenticated user or tenant, and handlers read it back with
+/// [`RequestHandlerExtra::context`](crate::RequestHandlerExtra::context). Clones share
+/// the same values. The server clears them when the connection closes.
+///
+/// # Examples
+///
+/// ```rust
+/// use pmcp::shared::context::ConnectionContext;
+///
+/// struct Tenant(String);
+///
+/// let context = ConnectionContext::new();
+/// context.set(Tenant("acme".to_string()));
+///
+/// let tenant = context.get::<Tenant>().unwrap();
+/// assert_eq!(tenant.0, "acme");
+/// assert!(context.get::<String>().is_none());
+///
+/// context.clear();
+/// assert!(context.get::<Tenant>().is_none());
+/// ```
+#[derive(Clone, Default)]
+pub struct ConnectionContext {
+    values: Arc<parking_lot::RwLock<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>>,
+}
+
+impl ConnectionContext {
+    /// Create an empty context.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the value of type `T`, if one is set.
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        let value = self.values.read().get(&TypeId::of::<T>())?.clone();
+        value.downcast().ok()
+    }
+
+    /// Set the value of type `T`, returning the one it replaces.
+    pub fn set<T: Send + Sync + 'static>(&self, value: T) -> Option<Arc<T>> {
+        let previous = self
+            .values
+            .write()
+            .insert(TypeId::of::<T>(), Arc::new(value))?;
+        previous.downcast().ok()
+    }
+
+    /// Remove and return the value of type `T`.
+    pub fn remove<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        let previous = self.values.write().remove(&TypeId::of::<T>())?;
+        previous.downcast().ok()
+    }
+
+    /// Remove all values.
+    pub fn clear(&self) {
+        self.values.write().clear();
+    }
+}
+
+impl std::fmt::Debug for ConnectionContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConnectionContext")
+            .field("values", &self.values.read().len())
+            .finish()
+    }
+}
+
 /// Context propagator for middleware integration.
 ///
 /// # Examples
@@ -460,6 +531,20 @@ mod tests {
         assert_eq!(result, 42);
     }
 
+    #[test]
+    fn test_connection_context_is_shared_by_clones() {
+        let context = ConnectionContext::new();
+        let handle = context.clone();
+
+        assert!(handle.set(7u32).is_none());
+        assert_eq!(context.get::<u32>().as_deref(), Some(&7));
+        assert_eq!(context.set(8u32).as_deref(), Some(&7));
+        assert!(context.get::<u64>().is_none());
+
+        assert_eq!(handle.remove::<u32>().as_deref(), Some(&8));
+        assert!(context.get::<u32>().is_none());
+    }
+
     #[tokio::test]
     async fn test_child_context() {
         let parent = RequestContext::new(RequestId::from(123i64));
@@ -1,15 +1,20 @@
 //! HTTP/SSE transport implementation for MCP.
 
 use crate::error::Result;
+use crate::shared::http_constants::{
+    is_json_content_type, ACCEPT, APPLICATION_JSON, CONTENT_TYPE, MCP_SESSION_ID, TEXT_EVENT_STREAM,
+};
 use crate::shared::sse_parser::SseParser;
-use crate::shared::{Transport, TransportMessage};
+use crate::shared::{Transport, TransportMessage, DEFAULT_MAX_JSON_DEPTH};
 use async_trait::async_trait;
 use bytes::Bytes;
 use http_body_util::{BodyExt, Full};
+use hyper::header::AUTHORIZATION;
 use hyper::{Method, Request, StatusCode};
 use hyper_util::client::legacy::Client;
 use hyper_util::rt::TokioExecutor;
 use parking_lot::RwLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc;
@@ -29,6 +34,8 @@ pub struct HttpConfig {
     pub timeout: Duration,
     /// Additional headers to include in requests
     pub headers: Vec<(String, String)>,
+    /// `Content-Type` sent with request bodies (e.g. `application/json-rpc` for strict gateways)
+    pub content_type: String,
     /// Enable connection pooling
     pub enable_pooling: bool,
     /// Maximum idle connections in pool
@@ -42,6 +49,7 @@ impl Default for HttpConfig {
             sse_endpoint: Some("/events".to_string()),
             timeout: Duration::from_secs(30),
             headers: vec![],
+            content_type: APPLICATION_JSON.to_string(),
             enable_pooling: true,
             max_idle_per_host: 10,
         }
@@ -55,6 +63,12 @@ pub struct HttpTransport {
     message_queue: Arc<AsyncMutex<mpsc::Receiver<TransportMessage>>>,
     message_tx: mpsc::Sender<TransportMessage>,
     connected: Arc<RwLock<bool>>,
+    /// OAuth access token sent as a bearer token
+    access_token: Option<String>,
+    /// Session the server started when the event stream was opened
+    session_id: Arc<RwLock<Option<String>>>,
+    /// Maximum nesting depth of inbound messages
+    max_json_depth: Arc<AtomicUsize>,
 }
 
 impl std::fmt::Debug for HttpTransport {
@@ -83,6 +97,9 @@ impl HttpTransport {
             message_queue: Arc::new(AsyncMutex::new(rx)),
             message_tx: tx,
             connected: Arc::new(RwLock::new(false)),
+            access_token: None,
+            session_id: Arc::new(RwLock::new(None)),
+            max_json_depth: Arc::new(AtomicUsize::new(DEFAULT_MAX_JSON_DEPTH)),
         }
     }
 
@@ -95,6 +112,9 @@ impl HttpTransport {
     }
 
     /// Connect to SSE endpoint for receiving notifications.
+    ///
+    /// If the server starts a session (an `Mcp-Session-Id` response header), its ID is
+    /// sent with every further request, and reconnecting resumes the session.
     pub async fn connect_sse(&self) -> Result<()> {
         if let Some(sse_path) = &self.config.sse_endpoint {
             let sse_url = self
@@ -104,11 +124,18 @@ impl HttpTransport {
                 .map_err(|e| crate::error::TransportError::InvalidMessage(e.to_string()))?;
             info!("Connecting to SSE endpoint: {}", sse_url);
 
-            let req = Request::builder()
+            let mut req = Request::builder()
                 .method(Method::GET)
                 .uri(sse_url.as_str())
-                .header("Accept", "text/event-stream")
-                .header("Cache-Control", "no-cache")
+                .header(ACCEPT, TEXT_EVENT_STREAM)
+                .header("Cache-Control", "no-cache");
+            if let Some(token) = &self.access_token {
+                req = req.header(AUTHORIZATION, format!("Bearer {}", token));
+            }
+            if let Some(session_id) = self.session_id.read().as_deref() {
+                req = req.header(MCP_SESSION_ID, session_id);
+            }
+            let req = req
                 .body(Full::new(Bytes::new()))
                 .map_err(|e| crate::error::TransportError::InvalidMessage(e.to_string()))?;
 
@@ -126,10 +153,18 @@ impl HttpTransport {
                     )),
                 ));
             }
+            if let Some(session_id) = response
+                .headers()
+                .get(MCP_SESSION_ID)
+                .and_then(|id| id.to_str().ok())
+            {
+                *self.session_id.write() = Some(session_id.to_string());
+            }
 
             // Spawn SSE reader task
             let message_tx = self.message_tx.clone();
             let connected = self.connected.clone();
+            let max_json_depth = self.max_json_depth.clone();
 
             tokio::spawn(async move {
                 *connected.write() = true;
@@ -146,13 +181,16 @@ impl HttpTransport {
 
                                 for event in events {
                                     // Process SSE event data as JSON-RPC message
-                                    match crate::shared::stdio::StdioTransport::parse_message(
+                                    match crate::shared::stdio::StdioTransport::parse_messages_with_max_depth(
                                         event.data.as_bytes(),
+                                        max_json_depth.load(Ordering::Relaxed),
                                     ) {
-                                        Ok(msg) => {
-                                            if message_tx.send(msg).await.is_err() {
-                                                error!("Failed to send SSE message");
-                                                break;
+                                        Ok(messages) => {
+                                            for msg in messages {
+                                                if message_tx.send(msg).await.is_err() {
+                                                    error!("Failed to send SSE message");
+                                                    break;
+                                                }
                                             }
                                         },
                                         Err(e) => {
@@ -188,10 +226,17 @@ impl HttpTransport {
             )))
         })?;
 
-        let req = Request::builder()
+        let mut req = Request::builder()
             .method(Method::POST)
             .uri(self.config.base_url.as_str())
-            .header("Content-Type", "application/json")
+            .header(CONTENT_TYPE, &self.config.content_type);
+        if let Some(token) = &self.access_token {
+            req = req.header(AUTHORIZATION, format!("Bearer {}", token));
+        }
+        if let Some(session_id) = self.session_id.read().as_deref() {
+            req = req.header(MCP_SESSION_ID, session_id);
+        }
+        let req = req
             .body(Full::new(Bytes::from(json)))
             .map_err(|e| crate::error::TransportError::InvalidMessage(e.to_string()))?;
 
@@ -204,6 +249,18 @@ impl HttpTransport {
                 ))
             })?;
 
+        // Servers answering over the SSE stream accept the message with no body
+        if response.status() == StatusCode::ACCEPTED {
+            return Ok(());
+        }
+
+        // Rejected credentials are told apart, so that clients can refresh them
+        if response.status() == StatusCode::UNAUTHORIZED {
+            return Err(crate::error::Error::authentication(format!(
+                "HTTP request failed with status: {}",
+                response.status()
+            )));
+        }
         if response.status() != StatusCode::OK {
             return Err(crate::error::Error::Transport(
                 crate::error::TransportError::InvalidMessage(format!(
@@ -213,6 +270,17 @@ impl HttpTransport {
             ));
         }
 
+        // Some servers label JSON bodies loosely, so only warn on a mismatch
+        if let Some(content_type) = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+        {
+            if !is_json_content_type(content_type) {
+                warn!("Unexpected response content type: {}", content_type);
+            }
+        }
+
         // Process response
         let body_bytes = response
             .collect()
@@ -223,12 +291,17 @@ impl HttpTransport {
                 ))
             })?
             .to_bytes();
-        let response_msg = crate::shared::stdio::StdioTransport::parse_message(&body_bytes)?;
-
-        // Send response through message queue
-        self.message_tx.send(response_msg).await.map_err(|_| {
-            crate::error::Error::Transport(crate::error::TransportError::ConnectionClosed)
-        })?;
+        let messages = crate::shared::stdio::StdioTransport::parse_messages_with_max_depth(
+            &body_bytes,
+            self.max_json_depth.load(Ordering::Relaxed),
+        )?;
+
+        // Send responses through message queue, one per batch element
+        for message in messages {
+            self.message_tx.send(message).await.map_err(|_| {
+                crate::error::Error::Transport(crate::error::TransportError::ConnectionClosed)
+            })?;
+        }
 
         Ok(())
     }
@@ -257,6 +330,14 @@ impl Transport for HttpTransport {
     fn is_connected(&self) -> bool {
         *self.connected.read()
     }
+
+    fn set_access_token(&mut self, token: &str) {
+        self.access_token = Some(token.to_string());
+    }
+
+    fn set_max_json_depth(&mut self, depth: usize) {
+        self.max_json_depth.store(depth, Ordering::Relaxed);
+    }
 }
 
 #[cfg(test)]
@@ -272,6 +353,7 @@ mod tests {
         assert_eq!(config.sse_endpoint, Some("/events".to_string()));
         assert_eq!(config.max_idle_per_host, 10);
         assert_eq!(config.headers.len(), 0);
+        assert_eq!(config.content_type, "application/json");
     }
 
     #[test]
@@ -281,6 +363,7 @@ mod tests {
             sse_endpoint: None,
             timeout: Duration::from_secs(60),
             headers: vec![("X-Custom".to_string(), "value".to_string())],
+            content_type: "application/json-rpc".to_string(),
             enable_pooling: false,
             max_idle_per_host: 5,
         };
@@ -288,6 +371,7 @@ mod tests {
         assert!(config.sse_endpoint.is_none());
         assert_eq!(config.timeout, Duration::from_secs(60));
         assert_eq!(config.headers.len(), 1);
+        assert_eq!(config.content_type, "application/json-rpc");
         assert!(!config.enable_pooling);
         assert_eq!(config.max_idle_per_host, 5);
     }
@@ -360,6 +444,51 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_send_request_uses_configured_content_type() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 4096];
+            let n = socket.read(&mut buf).await.unwrap();
+            let request = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+
+            let body = r#"{"jsonrpc":"2.0","id":1,"result":{}}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json-rpc\r\ncontent-length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            request
+        });
+
+        let config = HttpConfig {
+            base_url: format!("http://{}", addr).parse().unwrap(),
+            sse_endpoint: None,
+            content_type: "application/json-rpc".to_string(),
+            ..Default::default()
+        };
+        let mut transport = HttpTransport::new(config);
+
+        let message = TransportMessage::Request {
+            id: RequestId::from(1i64),
+            request: Request::Client(Box::new(ClientRequest::Ping)),
+        };
+        transport.send(message).await.unwrap();
+
+        let request = server.await.unwrap();
+        assert!(request.contains("content-type: application/json-rpc"));
+        assert!(matches!(
+            transport.receive().await.unwrap(),
+            TransportMessage::Response(_)
+        ));
+    }
+
     #[test]
     fn test_http_config_with_headers() {
         let config = HttpConfig {
@@ -397,6 +526,9 @@ mod tests {
             message_queue: Arc::new(AsyncMutex::new(rx)),
             message_tx: transport.message_tx,
             connected: transport.connected,
+            access_token: None,
+            session_id: transport.session_id,
+            max_json_depth: transport.max_json_depth,
         };
 
         // Receive should error with ConnectionClosed
@@ -406,4 +538,26 @@ mod tests {
             assert!(matches!(e, crate::error::TransportError::ConnectionClosed));
         }
     }
+
+    #[tokio::test]
+    async fn test_access_token_is_sent_as_bearer() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/")
+            .match_header("authorization", "Bearer token-1")
+            .with_status(202)
+            .create_async()
+            .await;
+
+        let mut transport = HttpTransport::with_url(server.url().parse::<Url>().unwrap()).unwrap();
+        transport.set_access_token("token-1");
+        transport
+            .send(TransportMessage::Notification(
+                crate::types::Notification::Client(crate::types::ClientNotification::Initialized),
+            ))
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+    }
 }
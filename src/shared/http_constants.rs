@@ -20,8 +20,38 @@ pub const CONTENT_TYPE: &str = "Content-Type";
 /// JSON content type value
 pub const APPLICATION_JSON: &str = "application/json";
 
+/// JSON-RPC content type value required by some gateways
+pub const APPLICATION_JSON_RPC: &str = "application/json-rpc";
+
 /// Server-Sent Events content type value
 pub const TEXT_EVENT_STREAM: &str = "text/event-stream";
 
 /// Accept header value for streamable HTTP (both JSON and SSE)
 pub const ACCEPT_STREAMABLE: &str = "application/json, text/event-stream";
+
+/// Check whether a `Content-Type` value carries a JSON body.
+///
+/// Matching is lenient: parameters such as `charset` are ignored, the comparison is
+/// case-insensitive, and `application/json-rpc` and `+json` suffixes are accepted.
+///
+/// # Examples
+///
+/// ```rust
+/// use pmcp::shared::http_constants::is_json_content_type;
+///
+/// assert!(is_json_content_type("application/json"));
+/// assert!(is_json_content_type("Application/JSON; charset=utf-8"));
+/// assert!(is_json_content_type("application/json-rpc"));
+/// assert!(!is_json_content_type("text/event-stream"));
+/// ```
+pub fn is_json_content_type(value: &str) -> bool {
+    let media_type = value
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    media_type == APPLICATION_JSON
+        || media_type == APPLICATION_JSON_RPC
+        || (media_type.starts_with("application/") && media_type.ends_with("+json"))
+}
@@ -34,7 +34,7 @@ pub mod streamable_http;
 
 // Re-export commonly used types
 pub use batch::{BatchRequest, BatchResponse};
-pub use context::{ClientInfo, ContextPropagator, RequestContext};
+pub use context::{ClientInfo, ConnectionContext, ContextPropagator, RequestContext};
 pub use event_store::{
     EventStore, EventStoreConfig, InMemoryEventStore, MessageDirection, ResumptionManager,
     ResumptionState, ResumptionToken, StoredEvent,
@@ -43,14 +43,18 @@ pub use logging::{init_logging, CorrelatedLogger, LogConfig, LogEntry, LogFormat
 pub use middleware::{
     AuthMiddleware, LoggingMiddleware, Middleware, MiddlewareChain, RetryMiddleware,
 };
-pub use protocol::{ProgressCallback, Protocol, ProtocolOptions, RequestOptions};
+pub use protocol::{
+    FieldCase, ProgressCallback, Protocol, ProtocolOptions, RequestOptions, Utf8Mode,
+    DEFAULT_MAX_JSON_DEPTH, DEFAULT_MAX_PENDING,
+};
 pub use protocol_helpers::{
     create_notification, create_request, parse_notification, parse_request,
+    try_create_notification, try_create_request,
 };
-pub use reconnect::{ReconnectConfig, ReconnectGuard, ReconnectManager};
+pub use reconnect::{ReconnectConfig, ReconnectGuard, ReconnectManager, ReconnectPredicate};
 pub use session::{Session, SessionConfig, SessionManager};
-pub use stdio::StdioTransport;
-pub use transport::{Transport, TransportMessage};
+pub use stdio::{StdioTransport, StdioTransportConfig};
+pub use transport::{BoxedTransport, Transport, TransportMessage};
 pub use uri_template::UriTemplate;
 
 #[cfg(all(feature = "websocket", not(target_arch = "wasm32")))]
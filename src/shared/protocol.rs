@@ -12,13 +12,221 @@ use tokio::sync::oneshot;
 /// Progress callback type.
 pub type ProgressCallback = Box<dyn Fn(u64, Option<u64>) + Send + Sync>;
 
+/// Default maximum number of requests waiting for a response at once.
+pub const DEFAULT_MAX_PENDING: usize = 1024;
+
+/// Default maximum nesting depth of inbound JSON messages.
+pub const DEFAULT_MAX_JSON_DEPTH: usize = 64;
+
 /// Protocol options for configuring behavior.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct ProtocolOptions {
-    /// Whether to enforce strict capability checking.
+    /// Whether the client refuses requests the server has no capability for.
+    ///
+    /// When set, calling e.g. `tools/list` on a server that did not advertise `tools`
+    /// fails with [`Error::UnsupportedCapability`](crate::Error::UnsupportedCapability)
+    /// without contacting the server; this is the default. When unset, a warning is
+    /// logged and the request is sent anyway, which works with servers that serve
+    /// methods they forgot to advertise; a server that really lacks the method answers
+    /// with its own error instead, costing a round trip.
     pub enforce_strict_capabilities: bool,
     /// Methods that should be debounced.
     pub debounced_notification_methods: Vec<String>,
+    /// Preserve the exact digits of JSON numbers in tool arguments and results.
+    ///
+    /// By default `serde_json` stores numbers as `u64`, `i64` or `f64`, so integers beyond
+    /// 64 bits and high-precision decimals lose digits. Setting this requires the
+    /// `arbitrary-precision` cargo feature, which enables `serde_json/arbitrary_precision`;
+    /// number representation is fixed at compile time, so without the feature the flag
+    /// only logs a warning.
+    ///
+    /// Trade-offs: the feature applies to every crate in the build that uses `serde_json`,
+    /// numbers are kept as strings internally (slower to parse and compare), and values
+    /// are only converted to `f64` or integers when explicitly requested.
+    pub arbitrary_precision: bool,
+    /// How strictly field names in responses must follow the spec's `camelCase`.
+    ///
+    /// Only affects how responses are read; outgoing messages are always `camelCase`.
+    pub field_case: FieldCase,
+    /// How invalid UTF-8 in inbound frames is handled.
+    ///
+    /// Applied by transports that read raw bytes before JSON parsing; see
+    /// [`Transport::set_utf8_mode`](crate::shared::Transport::set_utf8_mode).
+    pub utf8: Utf8Mode,
+    /// Maximum number of requests waiting for a response at once.
+    ///
+    /// Further requests fail immediately, so a peer that never responds cannot make
+    /// the pending request map grow without bound. Defaults to [`DEFAULT_MAX_PENDING`].
+    pub max_pending: usize,
+    /// Maximum nesting depth of arrays and objects in inbound messages.
+    ///
+    /// Deeper messages are rejected with a parse error before they are deserialized,
+    /// so a malicious peer cannot exhaust the stack. Applied by transports that parse
+    /// raw frames; see [`Transport::set_max_json_depth`](crate::shared::Transport::set_max_json_depth).
+    /// `serde_json` rejects anything deeper than 128 on its own, so larger values
+    /// have no effect. Defaults to [`DEFAULT_MAX_JSON_DEPTH`].
+    pub max_json_depth: usize,
+    /// Time after which a request still waiting for a response fails with
+    /// [`Error::Timeout`](crate::Error::Timeout).
+    ///
+    /// Stale requests are swept periodically. With `None` (the default), single
+    /// requests fail after [`DEFAULT_REQUEST_TIMEOUT_MS`](crate::DEFAULT_REQUEST_TIMEOUT_MS)
+    /// unless they set their own timeout, and batches wait indefinitely.
+    pub pending_timeout: Option<Duration>,
+}
+
+impl Default for ProtocolOptions {
+    fn default() -> Self {
+        Self {
+            enforce_strict_capabilities: true,
+            debounced_notification_methods: Vec::new(),
+            arbitrary_precision: false,
+            field_case: FieldCase::default(),
+            utf8: Utf8Mode::default(),
+            max_pending: DEFAULT_MAX_PENDING,
+            max_json_depth: DEFAULT_MAX_JSON_DEPTH,
+            pending_timeout: None,
+        }
+    }
+}
+
+/// Handling of invalid UTF-8 in inbound frames.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Utf8Mode {
+    /// Reject frames containing invalid UTF-8
+    #[default]
+    Strict,
+    /// Replace invalid sequences with U+FFFD, for peers that send malformed text
+    Lossy,
+}
+
+impl Utf8Mode {
+    /// Validate `bytes` as UTF-8 before JSON parsing.
+    ///
+    /// Uses the SIMD validator when the `simd` feature is enabled and the CPU
+    /// supports AVX2. Valid input is returned as is; invalid input is rejected
+    /// (`Strict`) or has invalid sequences replaced (`Lossy`).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::shared::Utf8Mode;
+    ///
+    /// let frame = b"{\"text\":\"caf\xE9\"}";
+    /// assert!(Utf8Mode::Strict.decode(frame).is_err());
+    /// let decoded = Utf8Mode::Lossy.decode(frame).unwrap();
+    /// assert_eq!(&*decoded, "{\"text\":\"caf\u{FFFD}\"}".as_bytes());
+    /// ```
+    pub fn decode(self, bytes: &[u8]) -> Result<std::borrow::Cow<'_, [u8]>> {
+        if is_valid_utf8(bytes) {
+            return Ok(std::borrow::Cow::Borrowed(bytes));
+        }
+        match self {
+            Self::Strict => Err(crate::error::TransportError::InvalidMessage(
+                "Invalid UTF-8 in message".to_string(),
+            )
+            .into()),
+            Self::Lossy => Ok(std::borrow::Cow::Owned(
+                String::from_utf8_lossy(bytes).into_owned().into_bytes(),
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "simd")]
+fn is_valid_utf8(bytes: &[u8]) -> bool {
+    crate::simd::validate_utf8(bytes)
+}
+
+#[cfg(not(feature = "simd"))]
+fn is_valid_utf8(bytes: &[u8]) -> bool {
+    std::str::from_utf8(bytes).is_ok()
+}
+
+/// Field naming accepted when reading responses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FieldCase {
+    /// Only accept the spec's `camelCase` field names
+    #[default]
+    Strict,
+    /// Also accept `snake_case` field names, as sent by some older servers
+    Lenient,
+}
+
+/// Fields whose contents belong to the server or user rather than the protocol,
+/// so their keys are never renamed.
+const OPAQUE_FIELDS: &[&str] = &[
+    "arguments",
+    "experimental",
+    "inputSchema",
+    "outputSchema",
+    "structuredContent",
+];
+
+impl FieldCase {
+    /// Rewrite `snake_case` keys to `camelCase` so the value deserializes into
+    /// protocol types. `Strict` leaves the value untouched.
+    ///
+    /// Keys starting with `_` (such as `_meta`), keys whose `camelCase` form is
+    /// already present, and the contents of schemas, arguments and other
+    /// free-form fields are left as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::shared::FieldCase;
+    /// use serde_json::json;
+    ///
+    /// let mut value = json!({"protocol_version": "2024-11-05", "server_info": {"name": "legacy"}});
+    /// FieldCase::Lenient.normalize(&mut value);
+    /// assert_eq!(value, json!({"protocolVersion": "2024-11-05", "serverInfo": {"name": "legacy"}}));
+    /// ```
+    pub fn normalize(self, value: &mut serde_json::Value) {
+        if self == Self::Lenient {
+            normalize_keys(value);
+        }
+    }
+}
+
+fn normalize_keys(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            let renames: Vec<(String, String)> = map
+                .keys()
+                .filter(|key| !key.starts_with('_') && key.contains('_'))
+                .map(|key| (key.clone(), snake_to_camel(key)))
+                .filter(|(_, camel)| !map.contains_key(camel))
+                .collect();
+            for (snake, camel) in renames {
+                if let Some(field) = map.remove(&snake) {
+                    map.insert(camel, field);
+                }
+            }
+            for (key, field) in map.iter_mut() {
+                if !OPAQUE_FIELDS.contains(&key.as_str()) && !key.starts_with('_') {
+                    normalize_keys(field);
+                }
+            }
+        },
+        serde_json::Value::Array(items) => items.iter_mut().for_each(normalize_keys),
+        _ => {},
+    }
+}
+
+fn snake_to_camel(key: &str) -> String {
+    let mut camel = String::with_capacity(key.len());
+    let mut upper = false;
+    for c in key.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            camel.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            camel.push(c);
+        }
+    }
+    camel
 }
 
 /// Request options for individual requests.
@@ -114,7 +322,10 @@ impl Protocol {
     /// # Examples
     ///
     /// ```rust
-    /// use pmcp::shared::protocol::{Protocol, ProtocolOptions};
+    /// use pmcp::shared::protocol::{
+    ///     FieldCase, Protocol, ProtocolOptions, Utf8Mode, DEFAULT_MAX_JSON_DEPTH,
+    ///     DEFAULT_MAX_PENDING,
+    /// };
     ///
     /// // Create with default options
     /// let protocol = Protocol::new(ProtocolOptions::default());
@@ -123,10 +334,23 @@ impl Protocol {
     /// let options = ProtocolOptions {
     ///     enforce_strict_capabilities: true,
     ///     debounced_notification_methods: vec!["progress".to_string()],
+    ///     arbitrary_precision: false,
+    ///     field_case: FieldCase::Strict,
+    ///     utf8: Utf8Mode::Strict,
+    ///     max_pending: DEFAULT_MAX_PENDING,
+    ///     max_json_depth: DEFAULT_MAX_JSON_DEPTH,
+    ///     pending_timeout: None,
     /// };
     /// let protocol = Protocol::new(options);
     /// ```
     pub fn new(options: ProtocolOptions) -> Self {
+        if options.arbitrary_precision && !cfg!(feature = "arbitrary-precision") {
+            tracing::warn!(
+                "arbitrary_precision requested but pmcp was built without the \
+                 `arbitrary-precision` feature; large numbers may lose precision"
+            );
+        }
+
         Self {
             options,
             pending_requests: HashMap::new(),
@@ -164,6 +388,15 @@ impl Protocol {
         &self.options
     }
 
+    /// Get protocol options for changing them after construction.
+    ///
+    /// Changes apply to messages handled from then on. Owners such as
+    /// [`Client`](crate::Client) may copy some options at construction; prefer their
+    /// setters (e.g. [`Client::set_timeout`](crate::Client::set_timeout)) where available.
+    pub fn options_mut(&mut self) -> &mut ProtocolOptions {
+        &mut self.options
+    }
+
     /// Get the transport ID for this protocol instance.
     pub fn transport_id(&self) -> &TransportId {
         &self.transport_id
@@ -283,15 +516,73 @@ mod tests {
         let options = ProtocolOptions {
             enforce_strict_capabilities: true,
             debounced_notification_methods: vec!["test".to_string()],
+            arbitrary_precision: false,
+            field_case: FieldCase::Strict,
+            utf8: Utf8Mode::Strict,
+            max_pending: DEFAULT_MAX_PENDING,
+            max_json_depth: DEFAULT_MAX_JSON_DEPTH,
+            pending_timeout: None,
         };
         assert!(options.enforce_strict_capabilities);
         assert_eq!(options.debounced_notification_methods, vec!["test"]);
 
         let default_options = ProtocolOptions::default();
-        assert!(!default_options.enforce_strict_capabilities);
+        assert!(default_options.enforce_strict_capabilities);
         assert!(default_options.debounced_notification_methods.is_empty());
     }
 
+    #[test]
+    fn test_lenient_field_case_skips_opaque_fields() {
+        let mut value = serde_json::json!({
+            "tools": [{
+                "name": "read",
+                "input_schema": {"properties": {"file_path": {"type": "string"}}}
+            }],
+            "next_cursor": "2",
+            "_meta": {"trace_id": "abc"}
+        });
+        FieldCase::Lenient.normalize(&mut value);
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "tools": [{
+                    "name": "read",
+                    "inputSchema": {"properties": {"file_path": {"type": "string"}}}
+                }],
+                "nextCursor": "2",
+                    "_meta": {"trace_id": "abc"}
+            })
+        );
+
+        let mut both = serde_json::json!({"next_cursor": "1", "nextCursor": "2"});
+        FieldCase::Lenient.normalize(&mut both);
+        assert_eq!(both["nextCursor"], "2");
+
+        let mut strict = serde_json::json!({"next_cursor": "2"});
+        FieldCase::Strict.normalize(&mut strict);
+        assert_eq!(strict, serde_json::json!({"next_cursor": "2"}));
+    }
+
+    #[test]
+    fn test_utf8_modes_on_invalid_sequence() {
+        // Overlong encoding of '/' followed by a truncated multi-byte sequence
+        let frame =
+            b"{\"jsonrpc\":\"2.0\",\"method\":\"ping\",\"params\":{\"x\":\"\xC0\xAF\xE2\x82\"}}";
+
+        let err = Utf8Mode::Strict.decode(frame).unwrap_err();
+        assert!(err.to_string().contains("Invalid UTF-8"), "{}", err);
+
+        let decoded = Utf8Mode::Lossy.decode(frame).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&decoded).unwrap();
+        assert_eq!(value["params"]["x"], "\u{FFFD}\u{FFFD}\u{FFFD}");
+
+        let valid = br#"{"jsonrpc":"2.0","method":"ping"}"#;
+        assert!(matches!(
+            Utf8Mode::Strict.decode(valid).unwrap(),
+            std::borrow::Cow::Borrowed(_)
+        ));
+    }
+
     #[test]
     fn test_request_options() {
         let options = RequestOptions {
@@ -310,7 +601,7 @@ mod tests {
     fn test_protocol_creation() {
         let options = ProtocolOptions::default();
         let protocol = Protocol::new(options);
-        assert!(!protocol.options().enforce_strict_capabilities);
+        assert!(protocol.options().enforce_strict_capabilities);
         assert_eq!(protocol.pending_requests.len(), 0);
     }
 
@@ -413,6 +704,22 @@ mod tests {
         assert!(called.load(std::sync::atomic::Ordering::SeqCst));
     }
 
+    #[cfg(feature = "arbitrary-precision")]
+    #[test]
+    fn test_arbitrary_precision_round_trip() {
+        use crate::shared::stdio::StdioTransport;
+        use crate::shared::TransportMessage;
+
+        let raw = br#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"ledger","arguments":{"amount":98765432109876543210,"rate":0.1000000000000000055511151231257827}}}"#;
+        let message = StdioTransport::parse_message(raw).unwrap();
+        let bytes = StdioTransport::serialize_message(&message).unwrap();
+        let text = String::from_utf8(bytes).unwrap();
+
+        assert!(matches!(message, TransportMessage::Request { .. }));
+        assert!(text.contains("98765432109876543210"));
+        assert!(text.contains("0.1000000000000000055511151231257827"));
+    }
+
     #[test]
     fn test_protocol_with_enforced_capabilities() {
         let options = ProtocolOptions {
@@ -421,6 +728,12 @@ mod tests {
                 "notifications/progress".to_string(),
                 "notifications/cancelled".to_string(),
             ],
+            arbitrary_precision: false,
+            field_case: FieldCase::Strict,
+            utf8: Utf8Mode::Strict,
+            max_pending: DEFAULT_MAX_PENDING,
+            max_json_depth: DEFAULT_MAX_JSON_DEPTH,
+            pending_timeout: None,
         };
 
         let protocol = Protocol::new(options);
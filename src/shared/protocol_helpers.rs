@@ -7,10 +7,46 @@ use crate::types::{
 };
 use serde_json::Value;
 
+/// Methods that are only valid as requests, i.e. with an `id`.
+const REQUEST_METHODS: &[&str] = &[
+    "initialize",
+    "ping",
+    "logging/setLevel",
+    "tools/list",
+    "tools/call",
+    "prompts/list",
+    "prompts/get",
+    "resources/list",
+    "resources/templates/list",
+    "resources/read",
+    "resources/subscribe",
+    "resources/unsubscribe",
+    "completion/complete",
+    "sampling/createMessage",
+    "elicitation/elicitInput",
+    "elicitation/input",
+    "elicitation/response",
+    "roots/list",
+];
+
+/// Whether `method` names a notification, which must be sent without an `id`.
+fn is_notification_method(method: &str) -> bool {
+    method.starts_with("notifications/")
+}
+
 /// Parse a JSON-RPC request into a typed Request.
+///
+/// Fails with an invalid request error if `method` is a notification method, since
+/// notifications must not carry an `id`.
 pub fn parse_request(request: JSONRPCRequest<Value>) -> Result<(RequestId, Request)> {
     let id = request.id;
     let method = &request.method;
+    if is_notification_method(method) {
+        return Err(Error::invalid_request(format!(
+            "{} is a notification and must not have an id",
+            method
+        )));
+    }
     let params = request.params.unwrap_or(Value::Null);
 
     // Try to parse as client request first
@@ -27,11 +63,23 @@ pub fn parse_request(request: JSONRPCRequest<Value>) -> Result<(RequestId, Reque
 }
 
 /// Parse a notification from JSON.
+///
+/// Fails with an invalid request error if the message has an `id` or `method` is a
+/// request method, since such a message expects a response.
 pub fn parse_notification(value: Value) -> Result<Notification> {
+    if value.get("id").is_some() {
+        return Err(Error::invalid_request("Notifications must not have an id"));
+    }
     let notification: JSONRPCNotification<Value> = serde_json::from_value(value)
         .map_err(|e| Error::parse(format!("Invalid notification: {}", e)))?;
 
     let method = &notification.method;
+    if REQUEST_METHODS.contains(&method.as_str()) {
+        return Err(Error::invalid_request(format!(
+            "{} is a request and needs an id",
+            method
+        )));
+    }
     let params = notification.params.unwrap_or(Value::Null);
 
     // Check for special notification types
@@ -61,44 +109,61 @@ pub fn parse_notification(value: Value) -> Result<Notification> {
 }
 
 /// Create a JSON-RPC request from typed request.
+///
+/// Use this for MCP request types, which always serialize. Use
+/// [`try_create_request`] when the request carries caller-supplied data whose
+/// serialization may fail.
+///
+/// # Panics
+///
+/// Panics if serialization to JSON fails (should never happen with valid MCP types).
 pub fn create_request(id: RequestId, request: Request) -> JSONRPCRequest<Value> {
-    match request {
-        Request::Client(boxed_req) => {
-            let client_req = *boxed_req;
-            let (method, params) = client_request_to_jsonrpc(client_req);
-            JSONRPCRequest::new(id, method, params)
-        },
-        Request::Server(server_req) => {
-            let (method, params) = server_request_to_jsonrpc(*server_req);
-            JSONRPCRequest::new(id, method, params)
-        },
-    }
+    try_create_request(id, request).expect("MCP request types always serialize to JSON")
+}
+
+/// Create a JSON-RPC request from typed request, returning an error if
+/// serialization fails.
+///
+/// Prefer this over [`create_request`] when the request embeds custom
+/// metadata that may not serialize to JSON.
+pub fn try_create_request(id: RequestId, request: Request) -> Result<JSONRPCRequest<Value>> {
+    let (method, params) = match request {
+        Request::Client(boxed_req) => client_request_to_jsonrpc(*boxed_req)?,
+        Request::Server(server_req) => server_request_to_jsonrpc(*server_req)?,
+    };
+    Ok(JSONRPCRequest::new(id, method, params))
 }
 
 /// Create a JSON-RPC notification from typed notification.
 ///
+/// Use this for MCP notification types, which always serialize. Use
+/// [`try_create_notification`] when the notification carries caller-supplied
+/// data whose serialization may fail.
+///
 /// # Panics
 ///
 /// Panics if serialization to JSON fails (should never happen with valid MCP types).
 pub fn create_notification(notification: Notification) -> JSONRPCNotification<Value> {
-    match notification {
-        Notification::Client(client_notif) => {
-            let (method, params) = client_notification_to_jsonrpc(client_notif);
-            JSONRPCNotification::new(method, params)
+    try_create_notification(notification).expect("MCP notification types always serialize to JSON")
+}
+
+/// Create a JSON-RPC notification from typed notification, returning an error
+/// if serialization fails.
+///
+/// Prefer this over [`create_notification`] when the notification embeds
+/// custom metadata that may not serialize to JSON.
+pub fn try_create_notification(notification: Notification) -> Result<JSONRPCNotification<Value>> {
+    let (method, params) = match notification {
+        Notification::Client(client_notif) => client_notification_to_jsonrpc(client_notif)?,
+        Notification::Server(server_notif) => server_notification_to_jsonrpc(server_notif)?,
+        Notification::Progress(progress) => {
+            create_method_params("notifications/progress", progress)?
         },
-        Notification::Server(server_notif) => {
-            let (method, params) = server_notification_to_jsonrpc(server_notif);
-            JSONRPCNotification::new(method, params)
+        Notification::Cancelled(cancelled) => {
+            create_method_params("notifications/cancelled", cancelled)?
         },
-        Notification::Progress(progress) => JSONRPCNotification::new(
-            "notifications/progress",
-            Some(serde_json::to_value(progress).unwrap()),
-        ),
-        Notification::Cancelled(cancelled) => JSONRPCNotification::new(
-            "notifications/cancelled",
-            Some(serde_json::to_value(cancelled).unwrap()),
-        ),
-    }
+    };
+    Ok(JSONRPCNotification::new(method, params))
 }
 
 // Helper functions for parsing
@@ -143,87 +208,92 @@ fn parse_server_notification(method: &str, params: &Value) -> Result<ServerNotif
         .map_err(|e| Error::parse(format!("Invalid server notification: {}", e)))
 }
 
-fn client_request_to_jsonrpc(req: ClientRequest) -> (String, Option<Value>) {
-    match req {
+fn client_request_to_jsonrpc(req: ClientRequest) -> Result<(String, Option<Value>)> {
+    Ok(match req {
         // Core protocol requests
-        ClientRequest::Initialize(params) => create_method_params("initialize", params),
+        ClientRequest::Initialize(params) => create_method_params("initialize", params)?,
         ClientRequest::Ping => ("ping".to_string(), None),
         ClientRequest::SetLoggingLevel { level } => (
             "logging/setLevel".to_string(),
             Some(serde_json::json!({"level": level})),
         ),
         // Tool requests
-        ClientRequest::ListTools(params) => create_method_params("tools/list", params),
-        ClientRequest::CallTool(params) => create_method_params("tools/call", params),
+        ClientRequest::ListTools(params) => create_method_params("tools/list", params)?,
+        ClientRequest::CallTool(params) => create_method_params("tools/call", params)?,
         // Prompt requests
-        ClientRequest::ListPrompts(params) => create_method_params("prompts/list", params),
-        ClientRequest::GetPrompt(params) => create_method_params("prompts/get", params),
+        ClientRequest::ListPrompts(params) => create_method_params("prompts/list", params)?,
+        ClientRequest::GetPrompt(params) => create_method_params("prompts/get", params)?,
         // Resource requests
-        ClientRequest::ListResources(params) => create_method_params("resources/list", params),
+        ClientRequest::ListResources(params) => create_method_params("resources/list", params)?,
         ClientRequest::ListResourceTemplates(params) => {
-            create_method_params("resources/templates/list", params)
+            create_method_params("resources/templates/list", params)?
+        },
+        ClientRequest::ReadResource(params) => create_method_params("resources/read", params)?,
+        ClientRequest::Subscribe(params) => create_method_params("resources/subscribe", params)?,
+        ClientRequest::Unsubscribe(params) => {
+            create_method_params("resources/unsubscribe", params)?
         },
-        ClientRequest::ReadResource(params) => create_method_params("resources/read", params),
-        ClientRequest::Subscribe(params) => create_method_params("resources/subscribe", params),
-        ClientRequest::Unsubscribe(params) => create_method_params("resources/unsubscribe", params),
         // Completion requests
-        ClientRequest::Complete(params) => create_method_params("completion/complete", params),
+        ClientRequest::Complete(params) => create_method_params("completion/complete", params)?,
         // Sampling requests
         ClientRequest::CreateMessage(params) => {
-            create_method_params("sampling/createMessage", params)
+            create_method_params("sampling/createMessage", params)?
         },
         // Elicitation response
         ClientRequest::ElicitInputResponse(params) => {
-            create_method_params("elicitation/response", params)
+            create_method_params("elicitation/response", params)?
         },
-    }
+    })
 }
 
 /// Helper function to create method and params tuple.
-fn create_method_params<T: serde::Serialize>(method: &str, params: T) -> (String, Option<Value>) {
-    (
-        method.to_string(),
-        Some(serde_json::to_value(params).unwrap()),
-    )
+fn create_method_params<T: serde::Serialize>(
+    method: &str,
+    params: T,
+) -> Result<(String, Option<Value>)> {
+    Ok((method.to_string(), to_params(params)?))
 }
 
-fn server_request_to_jsonrpc(req: ServerRequest) -> (String, Option<Value>) {
-    match req {
-        ServerRequest::CreateMessage(params) => (
-            "sampling/createMessage".to_string(),
-            Some(serde_json::to_value(params).unwrap()),
-        ),
+/// Serialize notification or request params.
+fn to_params<T: serde::Serialize>(params: T) -> Result<Option<Value>> {
+    Ok(Some(serde_json::to_value(params)?))
+}
+
+fn server_request_to_jsonrpc(req: ServerRequest) -> Result<(String, Option<Value>)> {
+    Ok(match req {
+        ServerRequest::CreateMessage(params) => {
+            ("sampling/createMessage".to_string(), to_params(params)?)
+        },
         ServerRequest::ListRoots => ("roots/list".to_string(), None),
-        ServerRequest::ElicitInput(params) => (
-            "elicitation/input".to_string(),
-            Some(serde_json::to_value(params).unwrap()),
-        ),
-    }
+        ServerRequest::ElicitInput(params) => ("elicitation/input".to_string(), to_params(params)?),
+        ServerRequest::Ping => ("ping".to_string(), None),
+    })
 }
 
-fn client_notification_to_jsonrpc(notif: ClientNotification) -> (String, Option<Value>) {
-    match notif {
+fn client_notification_to_jsonrpc(notif: ClientNotification) -> Result<(String, Option<Value>)> {
+    Ok(match notif {
         ClientNotification::Initialized => ("notifications/initialized".to_string(), None),
         ClientNotification::RootsListChanged => {
             ("notifications/roots/list_changed".to_string(), None)
         },
-        ClientNotification::Cancelled(params) => (
-            "notifications/cancelled".to_string(),
-            Some(serde_json::to_value(params).unwrap()),
-        ),
-        ClientNotification::Progress(params) => (
-            "notifications/progress".to_string(),
-            Some(serde_json::to_value(params).unwrap()),
+        ClientNotification::Cancelled(params) => {
+            ("notifications/cancelled".to_string(), to_params(params)?)
+        },
+        ClientNotification::Progress(params) => {
+            ("notifications/progress".to_string(), to_params(params)?)
+        },
+        ClientNotification::ArgumentChunk(chunk) => (
+            "notifications/tools/argumentChunk".to_string(),
+            to_params(chunk)?,
         ),
-    }
+    })
 }
 
-fn server_notification_to_jsonrpc(notif: ServerNotification) -> (String, Option<Value>) {
-    match notif {
-        ServerNotification::Progress(params) => (
-            "notifications/progress".to_string(),
-            Some(serde_json::to_value(params).unwrap()),
-        ),
+fn server_notification_to_jsonrpc(notif: ServerNotification) -> Result<(String, Option<Value>)> {
+    Ok(match notif {
+        ServerNotification::Progress(params) => {
+            ("notifications/progress".to_string(), to_params(params)?)
+        },
         ServerNotification::ToolsChanged => ("notifications/tools/list_changed".to_string(), None),
         ServerNotification::PromptsChanged => {
             ("notifications/prompts/list_changed".to_string(), None)
@@ -236,13 +306,16 @@ fn server_notification_to_jsonrpc(notif: ServerNotification) -> (String, Option<
         },
         ServerNotification::ResourceUpdated(params) => (
             "notifications/resources/updated".to_string(),
-            Some(serde_json::to_value(params).unwrap()),
+            to_params(params)?,
         ),
-        ServerNotification::LogMessage(params) => (
-            "notifications/message".to_string(),
-            Some(serde_json::to_value(params).unwrap()),
+        ServerNotification::LogMessage(params) => {
+            ("notifications/message".to_string(), to_params(params)?)
+        },
+        ServerNotification::CapabilitiesChanged(capabilities) => (
+            "notifications/capabilities/changed".to_string(),
+            to_params(capabilities)?,
         ),
-    }
+    })
 }
 
 #[cfg(test)]
@@ -362,6 +435,135 @@ mod tests {
         assert!(error_str.contains("Method not found"));
     }
 
+    #[test]
+    fn test_parse_request_rejects_notification_method() {
+        let request = JSONRPCRequest::new(
+            RequestId::from(7i64),
+            "notifications/initialized".to_string(),
+            None,
+        );
+        let err = parse_request(request).unwrap_err();
+        assert!(err.is_error_code(crate::error::ErrorCode::INVALID_REQUEST));
+        assert!(err.to_string().contains("must not have an id"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_notification_rejects_id() {
+        let err = parse_notification(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "notifications/initialized"
+        }))
+        .unwrap_err();
+        assert!(err.is_error_code(crate::error::ErrorCode::INVALID_REQUEST));
+    }
+
+    #[test]
+    fn test_parse_notification_rejects_request_method() {
+        for method in ["ping", "tools/call", "roots/list"] {
+            let err = parse_notification(json!({"jsonrpc": "2.0", "method": method})).unwrap_err();
+            assert!(err.is_error_code(crate::error::ErrorCode::INVALID_REQUEST));
+            assert!(err.to_string().contains("needs an id"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_request_methods_are_known() {
+        let client_requests = [
+            ClientRequest::Initialize(InitializeRequest {
+                protocol_version: "2024-11-05".to_string(),
+                capabilities: ClientCapabilities::default(),
+                client_info: Implementation {
+                    name: "test-client".to_string(),
+                    version: "1.0.0".to_string(),
+                },
+            }),
+            ClientRequest::ListTools(ListToolsRequest { cursor: None }),
+            ClientRequest::CallTool(CallToolRequest::new("test", json!({}))),
+            ClientRequest::ListPrompts(ListPromptsRequest { cursor: None }),
+            ClientRequest::GetPrompt(GetPromptRequest {
+                name: "test".to_string(),
+                arguments: std::collections::HashMap::new(),
+                meta: None,
+            }),
+            ClientRequest::ListResources(ListResourcesRequest::default()),
+            ClientRequest::ListResourceTemplates(ListResourceTemplatesRequest::default()),
+            ClientRequest::ReadResource(ReadResourceRequest::new("test://uri")),
+            ClientRequest::Subscribe(SubscribeRequest {
+                uri: "test://uri".to_string(),
+            }),
+            ClientRequest::Unsubscribe(UnsubscribeRequest {
+                uri: "test://uri".to_string(),
+            }),
+            ClientRequest::Complete(CompleteRequest {
+                r#ref: CompletionReference::Resource {
+                    uri: "test://uri".to_string(),
+                },
+                argument: CompletionArgument {
+                    name: "test".to_string(),
+                    value: "val".to_string(),
+                },
+                meta: None,
+            }),
+            ClientRequest::SetLoggingLevel {
+                level: LoggingLevel::Info,
+            },
+            ClientRequest::Ping,
+            ClientRequest::CreateMessage(create_message_params()),
+            ClientRequest::ElicitInputResponse(
+                serde_json::from_value(json!({"elicitationId": "e1", "value": "alice"})).unwrap(),
+            ),
+        ];
+        let server_requests = [
+            ServerRequest::CreateMessage(Box::new(create_message_params())),
+            ServerRequest::ListRoots,
+            ServerRequest::ElicitInput(Box::new(
+                serde_json::from_value(json!({
+                    "elicitationId": "e1",
+                    "inputType": "text",
+                    "prompt": "Name?"
+                }))
+                .unwrap(),
+            )),
+            ServerRequest::Ping,
+        ];
+        let requests = client_requests
+            .into_iter()
+            .map(|request| Request::Client(Box::new(request)))
+            .chain(
+                server_requests
+                    .into_iter()
+                    .map(|request| Request::Server(Box::new(request))),
+            );
+
+        // Both the name requests are sent with and the one they serialize to are known
+        for request in requests {
+            let tagged = match &request {
+                Request::Client(request) => serde_json::to_value(request),
+                Request::Server(request) => serde_json::to_value(request),
+            }
+            .unwrap();
+            let sent = create_request(RequestId::from(1i64), request);
+            for method in [tagged["method"].as_str().unwrap(), sent.method.as_str()] {
+                assert!(REQUEST_METHODS.contains(&method), "{} is not known", method);
+                assert!(!is_notification_method(method));
+            }
+        }
+    }
+
+    fn create_message_params() -> crate::types::protocol::CreateMessageParams {
+        crate::types::protocol::CreateMessageParams {
+            messages: vec![],
+            model_preferences: None,
+            system_prompt: None,
+            include_context: crate::types::protocol::IncludeContext::None,
+            temperature: None,
+            max_tokens: None,
+            stop_sequences: None,
+            metadata: None,
+        }
+    }
+
     #[test]
     fn test_parse_notification_progress() {
         let notification_json = json!({
@@ -500,6 +702,7 @@ mod tests {
         let request = Request::Client(Box::new(ClientRequest::CallTool(CallToolRequest {
             name: "test-tool".to_string(),
             arguments: json!({"input": "test"}),
+            meta: None,
         })));
 
         let jsonrpc_request = create_request(id.clone(), request);
@@ -631,21 +834,20 @@ mod tests {
                 ClientRequest::GetPrompt(GetPromptRequest {
                     name: "test".to_string(),
                     arguments: std::collections::HashMap::new(),
+                    meta: None,
                 }),
                 "prompts/get",
             ),
             (
-                ClientRequest::ListResources(ListResourcesRequest { cursor: None }),
+                ClientRequest::ListResources(ListResourcesRequest::default()),
                 "resources/list",
             ),
             (
-                ClientRequest::ListResourceTemplates(ListResourceTemplatesRequest { cursor: None }),
+                ClientRequest::ListResourceTemplates(ListResourceTemplatesRequest::default()),
                 "resources/templates/list",
             ),
             (
-                ClientRequest::ReadResource(ReadResourceRequest {
-                    uri: "test://uri".to_string(),
-                }),
+                ClientRequest::ReadResource(ReadResourceRequest::new("test://uri".to_string())),
                 "resources/read",
             ),
             (
@@ -669,13 +871,14 @@ mod tests {
                         name: "test".to_string(),
                         value: "val".to_string(),
                     },
+                    meta: None,
                 }),
                 "completion/complete",
             ),
         ];
 
         for (request, expected_method) in test_cases {
-            let (method, params) = client_request_to_jsonrpc(request);
+            let (method, params) = client_request_to_jsonrpc(request).unwrap();
             assert_eq!(method, expected_method);
             assert!(params.is_some());
         }
@@ -707,7 +910,7 @@ mod tests {
         ];
 
         for (notification, expected_method, should_have_params) in test_cases {
-            let (method, params) = client_notification_to_jsonrpc(notification);
+            let (method, params) = client_notification_to_jsonrpc(notification).unwrap();
             assert_eq!(method, expected_method);
             assert_eq!(params.is_some(), should_have_params);
         }
@@ -720,9 +923,7 @@ mod tests {
             progress: 25.0,
             message: None,
         };
-        let resource_updated = crate::types::protocol::ResourceUpdatedParams {
-            uri: "test://uri".to_string(),
-        };
+        let resource_updated = crate::types::protocol::ResourceUpdatedParams::new("test://uri");
         let log_message = crate::types::protocol::LogMessageParams {
             level: crate::types::protocol::LogLevel::Info,
             message: String::new(),
@@ -749,7 +950,7 @@ mod tests {
         ];
 
         for (notification, expected_method, should_have_params) in test_cases {
-            let (method, params) = server_notification_to_jsonrpc(notification);
+            let (method, params) = server_notification_to_jsonrpc(notification).unwrap();
             assert_eq!(method, expected_method);
             assert_eq!(params.is_some(), should_have_params);
         }
@@ -829,4 +1030,30 @@ mod tests {
             _ => panic!("Roundtrip failed"),
         }
     }
+
+    #[test]
+    fn test_try_create_matches_infallible_variants() {
+        let notification = Notification::Server(ServerNotification::ToolsChanged);
+        let created = try_create_notification(notification.clone()).unwrap();
+        assert_eq!(
+            serde_json::to_value(created).unwrap(),
+            serde_json::to_value(create_notification(notification)).unwrap()
+        );
+
+        let request = Request::Client(Box::new(ClientRequest::Ping));
+        let created = try_create_request(RequestId::from(1i64), request).unwrap();
+        assert_eq!(created.method, "ping");
+    }
+
+    #[test]
+    fn test_fallible_path_reports_unserializable_params() {
+        // JSON object keys must be strings, so a map keyed by tuples cannot serialize
+        let mut metadata = std::collections::HashMap::new();
+        metadata.insert((1, 2), "value");
+
+        let err = to_params(&metadata).unwrap_err();
+        assert!(matches!(err, Error::Serialization(_)));
+        let err = create_method_params("custom/method", metadata).unwrap_err();
+        assert!(err.to_string().contains("Serialization error"), "{}", err);
+    }
 }
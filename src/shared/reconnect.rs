@@ -3,7 +3,8 @@
 //! This module provides sophisticated reconnection strategies for network transports,
 //! including exponential backoff, jitter, and circuit breaker patterns.
 
-use crate::error::{Error, ErrorCode, Result};
+use crate::error::{Error, ErrorCode, Result, TransportError};
+use crate::types::ServerCapabilities;
 use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -11,8 +12,11 @@ use tokio::sync::{Mutex, RwLock};
 use tokio::time::sleep;
 use tracing::{debug, info, warn};
 
+/// Decides whether a failed connection attempt is worth retrying.
+pub type ReconnectPredicate = Arc<dyn Fn(&Error) -> bool + Send + Sync>;
+
 /// Reconnection configuration.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct ReconnectConfig {
     /// Initial delay before first reconnection attempt.
     pub initial_delay: Duration,
@@ -40,6 +44,12 @@ pub struct ReconnectConfig {
 
     /// Circuit breaker reset timeout.
     pub circuit_breaker_timeout: Duration,
+
+    /// Whether to keep reconnecting after a connection attempt fails with an error.
+    ///
+    /// Defaults to [`default_should_reconnect`], which gives up on errors the server
+    /// will keep returning, such as rejected credentials.
+    pub should_reconnect: ReconnectPredicate,
 }
 
 impl Default for ReconnectConfig {
@@ -54,10 +64,61 @@ impl Default for ReconnectConfig {
             success_threshold: Duration::from_secs(60),
             circuit_breaker_threshold: Some(5),
             circuit_breaker_timeout: Duration::from_secs(60),
+            should_reconnect: Arc::new(default_should_reconnect),
         }
     }
 }
 
+impl std::fmt::Debug for ReconnectConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReconnectConfig")
+            .field("initial_delay", &self.initial_delay)
+            .field("max_delay", &self.max_delay)
+            .field("growth_factor", &self.growth_factor)
+            .field("max_retries", &self.max_retries)
+            .field("jitter_factor", &self.jitter_factor)
+            .field("reset_on_success", &self.reset_on_success)
+            .field("success_threshold", &self.success_threshold)
+            .field("circuit_breaker_threshold", &self.circuit_breaker_threshold)
+            .field("circuit_breaker_timeout", &self.circuit_breaker_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The default [`ReconnectConfig::should_reconnect`] predicate.
+///
+/// Transport failures, timeouts and other errors that may clear up are retried.
+/// Authentication and validation failures, unsupported capabilities and handshakes
+/// rejected with 401 or 403 are not, since retrying would only hammer a server that
+/// will never accept the client.
+///
+/// # Examples
+///
+/// ```rust
+/// use pmcp::error::TransportError;
+/// use pmcp::shared::reconnect::default_should_reconnect;
+/// use pmcp::Error;
+///
+/// assert!(default_should_reconnect(&Error::Transport(TransportError::ConnectionClosed)));
+/// assert!(default_should_reconnect(&Error::Timeout(5_000)));
+/// assert!(!default_should_reconnect(&Error::authentication("token expired")));
+/// ```
+pub fn default_should_reconnect(error: &Error) -> bool {
+    match error {
+        Error::Authentication(_) | Error::Validation(_) | Error::UnsupportedCapability(_) => false,
+        Error::Protocol { code, .. } => !matches!(
+            *code,
+            ErrorCode::AUTHENTICATION_REQUIRED
+                | ErrorCode::PERMISSION_DENIED
+                | ErrorCode::UNSUPPORTED_CAPABILITY
+        ),
+        Error::Transport(TransportError::HandshakeRejected { status, .. }) => {
+            !matches!(status, 401 | 403)
+        },
+        _ => true,
+    }
+}
+
 /// Connection state for reconnection logic.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ConnectionState {
@@ -109,6 +170,12 @@ pub struct ReconnectManager {
     /// Whether reconnection is enabled.
     enabled: AtomicBool,
 
+    /// Server capabilities reported by the most recent connection.
+    capabilities: Arc<Mutex<Option<ServerCapabilities>>>,
+
+    /// Whether the most recent connection reported different capabilities than the one before.
+    capabilities_changed: AtomicBool,
+
     /// Callbacks.
     callbacks: Arc<ReconnectCallbacks>,
 }
@@ -129,6 +196,10 @@ impl std::fmt::Debug for ReconnectManager {
                 &self.total_successes.load(Ordering::Relaxed),
             )
             .field("enabled", &self.enabled.load(Ordering::Relaxed))
+            .field(
+                "capabilities_changed",
+                &self.capabilities_changed.load(Ordering::Relaxed),
+            )
             .finish()
     }
 }
@@ -140,6 +211,10 @@ pub type ConnectingCallback = Box<dyn Fn(u32) + Send + Sync>;
 pub type ConnectionCallback = Box<dyn Fn() + Send + Sync>;
 /// Callback invoked on connection failures with error details
 pub type FailureCallback = Box<dyn Fn(&Error) + Send + Sync>;
+/// Callback invoked with the resource URIs re-subscribed after a reconnect
+pub type ResubscribedCallback = Box<dyn Fn(&[String]) + Send + Sync>;
+/// Callback invoked with the new server capabilities when they changed across a reconnect
+pub type CapabilitiesChangedCallback = Box<dyn Fn(&ServerCapabilities) + Send + Sync>;
 
 /// Callbacks for reconnection events.
 #[derive(Default)]
@@ -158,6 +233,12 @@ pub struct ReconnectCallbacks {
 
     /// Called when circuit breaker closes.
     pub on_circuit_close: Option<ConnectionCallback>,
+
+    /// Called once resource subscriptions have been replayed after a reconnect.
+    pub on_resubscribed: Option<ResubscribedCallback>,
+
+    /// Called when the server reports different capabilities than on the previous connection.
+    pub on_capabilities_changed: Option<CapabilitiesChangedCallback>,
 }
 
 impl std::fmt::Debug for ReconnectCallbacks {
@@ -168,6 +249,11 @@ impl std::fmt::Debug for ReconnectCallbacks {
             .field("on_failed", &self.on_failed.is_some())
             .field("on_circuit_open", &self.on_circuit_open.is_some())
             .field("on_circuit_close", &self.on_circuit_close.is_some())
+            .field("on_resubscribed", &self.on_resubscribed.is_some())
+            .field(
+                "on_capabilities_changed",
+                &self.on_capabilities_changed.is_some(),
+            )
             .finish()
     }
 }
@@ -186,6 +272,8 @@ impl ReconnectManager {
             last_success: Arc::new(Mutex::new(None)),
             circuit_opened_at: Arc::new(Mutex::new(None)),
             enabled: AtomicBool::new(true),
+            capabilities: Arc::new(Mutex::new(None)),
+            capabilities_changed: AtomicBool::new(false),
             callbacks: Arc::new(ReconnectCallbacks::default()),
         }
     }
@@ -357,7 +445,47 @@ impl ReconnectManager {
         info!("Connection lost");
     }
 
+    /// Notify that resource subscriptions were replayed after reconnecting.
+    pub fn on_resubscribed(&self, uris: &[String]) {
+        info!("Replayed {} resource subscriptions", uris.len());
+
+        if let Some(callback) = &self.callbacks.on_resubscribed {
+            callback(uris);
+        }
+    }
+
+    /// Record the server capabilities reported by a new connection.
+    ///
+    /// Compares them with the previous connection's and fires `on_capabilities_changed`
+    /// if they differ. The first recorded capabilities never count as changed.
+    /// Returns whether they changed.
+    pub async fn on_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        let previous = self.capabilities.lock().await.replace(capabilities.clone());
+        let changed = previous.is_some_and(|previous| {
+            serde_json::to_value(&previous).ok() != serde_json::to_value(capabilities).ok()
+        });
+        self.capabilities_changed.store(changed, Ordering::Relaxed);
+
+        if changed {
+            info!("Server capabilities changed since the last connection");
+
+            if let Some(callback) = &self.callbacks.on_capabilities_changed {
+                callback(capabilities);
+            }
+        }
+        changed
+    }
+
+    /// Whether the most recent connection reported different server capabilities
+    /// than the one before it.
+    pub fn capabilities_changed(&self) -> bool {
+        self.capabilities_changed.load(Ordering::Relaxed)
+    }
+
     /// Execute reconnection with the provided connect function.
+    ///
+    /// Stops at the first error rejected by [`ReconnectConfig::should_reconnect`]
+    /// and returns it.
     pub async fn reconnect_with<F, Fut>(&self, connect: F) -> Result<()>
     where
         F: Fn() -> Fut,
@@ -381,6 +509,11 @@ impl ReconnectManager {
                 Err(e) => {
                     self.on_connection_failed(&e).await;
 
+                    if !(self.config.should_reconnect)(&e) {
+                        warn!("Not reconnecting after unrecoverable error: {}", e);
+                        *self.state.write().await = ConnectionState::Disconnected;
+                        return Err(e);
+                    }
                     if !self.should_reconnect().await {
                         return Err(e);
                     }
@@ -581,4 +714,92 @@ mod tests {
         assert_eq!(attempt_count.load(Ordering::Relaxed), 3);
         assert_eq!(manager.state().await, ConnectionState::Connected);
     }
+
+    #[test]
+    fn test_default_should_reconnect() {
+        let retried = [
+            Error::Transport(TransportError::ConnectionClosed),
+            Error::Transport(TransportError::ConnectTimeout(Duration::from_secs(1))),
+            Error::Transport(TransportError::HandshakeRejected {
+                status: 503,
+                reason: "Service Unavailable".to_string(),
+            }),
+            Error::Timeout(1_000),
+            Error::internal("connection reset"),
+            Error::protocol(ErrorCode::INTERNAL_ERROR, "overloaded"),
+        ];
+        for error in &retried {
+            assert!(default_should_reconnect(error), "{}", error);
+        }
+
+        let fatal = [
+            Error::authentication("invalid token"),
+            Error::validation("unsupported protocol version"),
+            Error::capability("tools"),
+            Error::protocol(ErrorCode::AUTHENTICATION_REQUIRED, "login first"),
+            Error::protocol(ErrorCode::PERMISSION_DENIED, "forbidden"),
+            Error::Transport(TransportError::HandshakeRejected {
+                status: 401,
+                reason: "Unauthorized".to_string(),
+            }),
+            Error::Transport(TransportError::HandshakeRejected {
+                status: 403,
+                reason: "Forbidden".to_string(),
+            }),
+        ];
+        for error in &fatal {
+            assert!(!default_should_reconnect(error), "{}", error);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_with_gives_up_on_fatal_error() {
+        let manager = ReconnectManager::new(ReconnectConfig {
+            initial_delay: Duration::from_millis(10),
+            max_retries: Some(5),
+            ..Default::default()
+        });
+        let attempt_count = AtomicU32::new(0);
+
+        let result = manager
+            .reconnect_with(|| {
+                attempt_count.fetch_add(1, Ordering::Relaxed);
+                async { Err(Error::authentication("invalid token")) }
+            })
+            .await;
+
+        assert!(matches!(result, Err(Error::Authentication(_))));
+        assert_eq!(attempt_count.load(Ordering::Relaxed), 1);
+        assert_eq!(manager.state().await, ConnectionState::Disconnected);
+    }
+
+    #[tokio::test]
+    async fn test_custom_should_reconnect() {
+        // Retry timeouts only, so the closed connection after one ends the loop
+        let manager = ReconnectManager::new(ReconnectConfig {
+            initial_delay: Duration::from_millis(10),
+            should_reconnect: Arc::new(|error| matches!(error, Error::Timeout(_))),
+            ..Default::default()
+        });
+        let attempt_count = AtomicU32::new(0);
+
+        let result = manager
+            .reconnect_with(|| {
+                let count = attempt_count.fetch_add(1, Ordering::Relaxed);
+                async move {
+                    if count == 0 {
+                        Err(Error::Timeout(10))
+                    } else {
+                        Err(Error::Transport(TransportError::ConnectionClosed))
+                    }
+                }
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(Error::Transport(TransportError::ConnectionClosed))
+        ));
+        assert_eq!(attempt_count.load(Ordering::Relaxed), 2);
+    }
 }
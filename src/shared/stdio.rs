@@ -3,18 +3,274 @@
 //! This transport uses stdin/stdout for communication, with length-prefixed
 //! framing to ensure message boundaries are preserved.
 
-use crate::error::{Result, TransportError};
+use crate::error::{Error, Result, TransportError};
+use crate::shared::protocol::{Utf8Mode, DEFAULT_MAX_JSON_DEPTH};
 use crate::shared::transport::{Transport, TransportMessage};
 use async_trait::async_trait;
-use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use std::collections::{HashSet, VecDeque};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, ReadBuf};
+use tokio::process::{Child, ChildStdin, ChildStdout};
 use tokio::sync::Mutex;
 
 /// Line-delimited JSON framing header.
 const CONTENT_LENGTH_HEADER: &str = "Content-Length: ";
 
+/// Number of recent child stderr lines kept for diagnostics.
+const STDERR_HISTORY: usize = 100;
+
+/// Prefix of the IDs of keep-alive pings, whose responses the transport consumes.
+const KEEPALIVE_ID_PREFIX: &str = "pmcp-keepalive-";
+
+/// Liveness checking of a server launched with [`StdioTransport::spawn_with_config`].
+///
+/// A server that crashed closes its stdout and is noticed right away, but one that
+/// hangs keeps its pipes open. With a liveness timeout, a server that stays silent
+/// that long while a message is awaited is sent a `ping`; if that also goes
+/// unanswered for as long, the server is killed and `receive` fails with
+/// [`TransportError::Unresponsive`]. No pings are sent while a request sent through
+/// the transport awaits its response, since the server may be busy with it.
+///
+/// # Examples
+///
+/// ```rust
+/// use pmcp::StdioTransportConfig;
+/// use std::time::Duration;
+///
+/// let config = StdioTransportConfig {
+///     liveness_timeout: Some(Duration::from_secs(10)),
+///     restart_on_hang: true,
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StdioTransportConfig {
+    /// Silence after which the server is pinged, and the time it has to answer;
+    /// `None` disables liveness checks
+    pub liveness_timeout: Option<Duration>,
+    /// Launch the server again after killing a hung one, instead of closing the
+    /// transport; the client must then initialize the new server
+    pub restart_on_hang: bool,
+}
+
+/// Command line of a child process, kept to restart it.
+#[derive(Debug, Clone)]
+struct Launch {
+    command: String,
+    args: Vec<String>,
+    env: Vec<(String, String)>,
+}
+
+impl Launch {
+    /// Start the process, collecting its stderr into `stderr`.
+    fn start(&self, stderr: &Arc<StderrCapture>) -> Result<(Child, ChildStdin, ChildStdout)> {
+        let mut child = tokio::process::Command::new(&self.command)
+            .args(&self.args)
+            .envs(self.env.iter().map(|(key, value)| (key, value)))
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                TransportError::Io(format!("Failed to spawn '{}': {}", self.command, e))
+            })?;
+
+        let pipe_missing =
+            || TransportError::Io(format!("Missing stdio pipe for '{}'", self.command));
+        let stdin = child.stdin.take().ok_or_else(pipe_missing)?;
+        let stdout = child.stdout.take().ok_or_else(pipe_missing)?;
+        if let Some(child_stderr) = child.stderr.take() {
+            tokio::spawn(StdioTransport::capture_stderr(child_stderr, stderr.clone()));
+        }
+        Ok((child, stdin, stdout))
+    }
+}
+
+/// Source of stdin bytes.
+///
+/// Windows console and pipe handles cannot be read asynchronously, so there stdin is
+/// read on a dedicated thread that [`StdioTransport::close`] can detach from.
+#[cfg(not(windows))]
+type StdinReader = tokio::io::Stdin;
+#[cfg(windows)]
+type StdinReader = blocking_reader::BlockingReader;
+
+#[cfg(not(windows))]
+fn stdin_reader() -> StdinReader {
+    tokio::io::stdin()
+}
+
+#[cfg(windows)]
+fn stdin_reader() -> StdinReader {
+    blocking_reader::BlockingReader::spawn(std::io::stdin())
+}
+
+/// Callback invoked with each stderr line of a child process.
+type StderrCallback = Arc<dyn Fn(String) + Send + Sync>;
+
+/// Stderr lines captured from a child process.
+#[derive(Default)]
+struct StderrCapture {
+    /// Most recent lines, oldest first
+    history: std::sync::Mutex<VecDeque<String>>,
+    /// Callback registered with [`StdioTransport::on_stderr`]
+    callback: std::sync::Mutex<Option<StderrCallback>>,
+}
+
+impl StderrCapture {
+    /// Record a line and hand it to the callback, if any.
+    fn push(&self, line: String) {
+        // Record the line while holding the callback slot, so a callback being
+        // registered either replays it or receives it below
+        let slot = self.callback.lock();
+        if let Ok(mut history) = self.history.lock() {
+            if history.len() == STDERR_HISTORY {
+                history.pop_front();
+            }
+            history.push_back(line.clone());
+        }
+        let callback = slot.ok().and_then(|cb| cb.clone());
+        if let Some(callback) = callback {
+            callback(line);
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        self.history
+            .lock()
+            .map(|lines| lines.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+impl std::fmt::Debug for StderrCapture {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StderrCapture")
+            .field("lines", &self.lines().len())
+            .finish()
+    }
+}
+
+/// Where incoming messages are read from.
+#[derive(Debug)]
+enum Input {
+    /// This process's stdin
+    Stdin(StdinReader),
+    /// A child process's stdout
+    Child(ChildStdout),
+}
+
+impl AsyncRead for Input {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Self::Stdin(stdin) => Pin::new(stdin).poll_read(cx, buf),
+            Self::Child(stdout) => Pin::new(stdout).poll_read(cx, buf),
+        }
+    }
+}
+
+/// Where outgoing messages are written to.
+#[derive(Debug)]
+enum Output {
+    /// This process's stdout
+    Stdout(tokio::io::Stdout),
+    /// A child process's stdin
+    Child(ChildStdin),
+}
+
+impl AsyncWrite for Output {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            Self::Stdout(stdout) => Pin::new(stdout).poll_write(cx, buf),
+            Self::Child(stdin) => Pin::new(stdin).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Self::Stdout(stdout) => Pin::new(stdout).poll_flush(cx),
+            Self::Child(stdin) => Pin::new(stdin).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Self::Stdout(stdout) => Pin::new(stdout).poll_shutdown(cx),
+            Self::Child(stdin) => Pin::new(stdin).poll_shutdown(cx),
+        }
+    }
+}
+
+/// Output with the frames, or parts of frames, not written yet.
+///
+/// A frame is queued whole before anything is written, and bytes leave the queue
+/// only once written. Cancelling a write, e.g. the keep-alive ping sent by a
+/// cancelled `receive`, thus never leaves half a frame behind: the rest is written
+/// ahead of the next frame.
+#[derive(Debug)]
+struct FrameWriter<W = Output> {
+    output: W,
+    unwritten: Vec<u8>,
+}
+
+impl<W: AsyncWrite + Unpin> FrameWriter<W> {
+    fn new(output: W) -> Self {
+        Self {
+            output,
+            unwritten: Vec::new(),
+        }
+    }
+
+    /// Queue `frame` and write out everything queued.
+    async fn write_frame(&mut self, frame: &[u8]) -> std::io::Result<()> {
+        self.unwritten.extend_from_slice(frame);
+        self.flush().await
+    }
+
+    /// Write out everything queued and flush the output.
+    async fn flush(&mut self) -> std::io::Result<()> {
+        while !self.unwritten.is_empty() {
+            // Unlike `write_all`, nothing is written if this is cancelled
+            let written = self.output.write(&self.unwritten).await?;
+            if written == 0 {
+                return Err(std::io::ErrorKind::WriteZero.into());
+            }
+            self.unwritten.drain(..written);
+        }
+        self.output.flush().await
+    }
+}
+
+/// Part of an incoming frame that has been read.
+///
+/// Kept in the transport rather than in `receive`, so that cancelling `receive`
+/// (e.g. to send a message) loses no input.
+#[derive(Debug, Default)]
+struct PartialFrame {
+    /// Header line read so far
+    line: Vec<u8>,
+    /// Content length, once its header is read
+    content_length: Option<usize>,
+    /// Body read so far, once the headers are complete
+    body: Option<Vec<u8>>,
+}
+
 /// stdio transport for MCP communication.
 ///
-/// Uses length-prefixed framing compatible with the TypeScript SDK.
+/// Uses length-prefixed framing compatible with the TypeScript SDK. Servers use
+/// [`StdioTransport::new`] to talk over their own stdin/stdout; clients use
+/// [`StdioTransport::spawn`] to launch a server and talk over its pipes.
 ///
 /// # Examples
 ///
@@ -29,9 +285,31 @@ const CONTENT_LENGTH_HEADER: &str = "Content-Length: ";
 /// ```
 #[derive(Debug)]
 pub struct StdioTransport {
-    stdin: Mutex<BufReader<tokio::io::Stdin>>,
-    stdout: Mutex<tokio::io::Stdout>,
+    stdin: Mutex<BufReader<Input>>,
+    stdout: Mutex<FrameWriter>,
     closed: std::sync::atomic::AtomicBool,
+    /// Child process launched by [`StdioTransport::spawn`], killed on drop
+    child: Option<Mutex<Child>>,
+    /// Stderr lines of the child process
+    stderr: Arc<StderrCapture>,
+    /// Handling of invalid UTF-8 in received messages
+    utf8: Utf8Mode,
+    /// Maximum nesting depth of received messages
+    max_json_depth: usize,
+    /// How the child process was launched, to restart it
+    launch: Option<Launch>,
+    /// Liveness checking of the child process
+    config: StdioTransportConfig,
+    /// Messages of a batch frame not yet returned by `receive`
+    queued: VecDeque<TransportMessage>,
+    /// Frame read so far, kept if `receive` is cancelled
+    frame: PartialFrame,
+    /// Number of keep-alive pings sent
+    keepalive_pings: u64,
+    /// Requests sent to the child process that await a response
+    outstanding: HashSet<crate::types::RequestId>,
+    /// Number of times a hung child process was restarted
+    restarts: u32,
 }
 
 impl StdioTransport {
@@ -47,9 +325,155 @@ impl StdioTransport {
     /// ```
     pub fn new() -> Self {
         Self {
-            stdin: Mutex::new(BufReader::new(tokio::io::stdin())),
-            stdout: Mutex::new(tokio::io::stdout()),
+            stdin: Mutex::new(BufReader::new(Input::Stdin(stdin_reader()))),
+            stdout: Mutex::new(FrameWriter::new(Output::Stdout(tokio::io::stdout()))),
+            closed: std::sync::atomic::AtomicBool::new(false),
+            child: None,
+            stderr: Arc::default(),
+            utf8: Utf8Mode::default(),
+            max_json_depth: DEFAULT_MAX_JSON_DEPTH,
+            launch: None,
+            config: StdioTransportConfig::default(),
+            queued: VecDeque::new(),
+            frame: PartialFrame::default(),
+            keepalive_pings: 0,
+            outstanding: HashSet::new(),
+            restarts: 0,
+        }
+    }
+
+    /// Launch a server as a child process and talk to it over its stdin/stdout.
+    ///
+    /// The child's stderr is captured for diagnostics (see
+    /// [`StdioTransport::stderr_lines`] and [`StdioTransport::on_stderr`]). The child is killed when the transport is
+    /// closed or dropped, and once it exits `receive` fails with
+    /// [`TransportError::ConnectionClosed`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::{Client, StdioTransport};
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let transport = StdioTransport::spawn(
+    ///     "python",
+    ///     &["server.py"],
+    ///     &[("LOG_LEVEL".to_string(), "debug".to_string())],
+    /// )?;
+    /// let client = Client::new(transport);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn spawn(command: &str, args: &[&str], env: &[(String, String)]) -> Result<Self> {
+        Self::spawn_with_config(command, args, env, StdioTransportConfig::default())
+    }
+
+    /// Launch a server like [`StdioTransport::spawn`], checking that it stays
+    /// responsive as described by `config`.
+    ///
+    /// Keep-alive pings are ordinary MCP `ping` requests whose responses are consumed
+    /// by the transport. They are only sent while no request sent through the
+    /// transport awaits its response, so a server that takes long to answer one is
+    /// not mistaken for a hung server.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::{Client, StdioTransport, StdioTransportConfig};
+    /// use std::time::Duration;
+    ///
+    /// # async fn example() -> pmcp::Result<()> {
+    /// let config = StdioTransportConfig {
+    ///     liveness_timeout: Some(Duration::from_secs(10)),
+    ///     restart_on_hang: true,
+    /// };
+    /// let transport = StdioTransport::spawn_with_config("my-mcp-server", &[], &[], config)?;
+    /// let client = Client::new(transport);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn spawn_with_config(
+        command: &str,
+        args: &[&str],
+        env: &[(String, String)],
+        config: StdioTransportConfig,
+    ) -> Result<Self> {
+        let launch = Launch {
+            command: command.to_string(),
+            args: args.iter().map(ToString::to_string).collect(),
+            env: env.to_vec(),
+        };
+        let stderr = Arc::new(StderrCapture::default());
+        let (child, stdin, stdout) = launch.start(&stderr)?;
+
+        Ok(Self {
+            stdin: Mutex::new(BufReader::new(Input::Child(stdout))),
+            stdout: Mutex::new(FrameWriter::new(Output::Child(stdin))),
             closed: std::sync::atomic::AtomicBool::new(false),
+            child: Some(Mutex::new(child)),
+            stderr,
+            utf8: Utf8Mode::default(),
+            max_json_depth: DEFAULT_MAX_JSON_DEPTH,
+            launch: Some(launch),
+            config,
+            queued: VecDeque::new(),
+            frame: PartialFrame::default(),
+            keepalive_pings: 0,
+            outstanding: HashSet::new(),
+            restarts: 0,
+        })
+    }
+
+    /// Number of times the child process was restarted after it stopped responding.
+    pub fn restarts(&self) -> u32 {
+        self.restarts
+    }
+
+    /// Most recent stderr lines written by the child process, oldest first.
+    ///
+    /// Always empty for transports created with [`StdioTransport::new`].
+    pub fn stderr_lines(&self) -> Vec<String> {
+        self.stderr.lines()
+    }
+
+    /// Call `callback` with every line the child process writes to stderr.
+    ///
+    /// Lines captured before the callback was registered are replayed first, so
+    /// output from a server that failed during startup is not lost. Has no effect
+    /// for transports created with [`StdioTransport::new`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use pmcp::StdioTransport;
+    ///
+    /// # fn example() -> pmcp::Result<()> {
+    /// let transport = StdioTransport::spawn("my-mcp-server", &[], &[])?
+    ///     .on_stderr(|line| eprintln!("[server] {}", line));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_stderr(self, callback: impl Fn(String) + Send + Sync + 'static) -> Self {
+        let callback: StderrCallback = Arc::new(callback);
+        // Hold the slot while replaying so new lines are delivered after older ones
+        if let Ok(mut slot) = self.stderr.callback.lock() {
+            for line in self.stderr.lines() {
+                callback(line);
+            }
+            *slot = Some(callback);
+        }
+        self
+    }
+
+    /// Collect the child's stderr lines until it closes the pipe.
+    async fn capture_stderr(
+        child_stderr: tokio::process::ChildStderr,
+        capture: Arc<StderrCapture>,
+    ) {
+        let mut lines = BufReader::new(child_stderr).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            tracing::debug!("server stderr: {}", line);
+            capture.push(line);
         }
     }
 
@@ -76,7 +500,11 @@ impl Transport for StdioTransport {
         }
 
         let json_bytes = Self::serialize_message(&message)?;
-        self.write_message(&json_bytes).await
+        self.write_message(&json_bytes).await?;
+        if self.child.is_some() {
+            self.track_outstanding(&message);
+        }
+        Ok(())
     }
 
     async fn receive(&mut self) -> Result<TransportMessage> {
@@ -84,20 +512,54 @@ impl Transport for StdioTransport {
             return Err(TransportError::ConnectionClosed.into());
         }
 
-        let content_length = self.read_headers().await?;
-        let buffer = self.read_message_body(content_length).await?;
-        Self::parse_message(&buffer)
+        loop {
+            if let Some(message) = self.queued.pop_front() {
+                if !Self::is_keepalive_response(&message) {
+                    if let TransportMessage::Response(response) = &message {
+                        self.outstanding.remove(&response.id);
+                    }
+                    return Ok(message);
+                }
+                continue;
+            }
+
+            if let Some(timeout) = self
+                .config
+                .liveness_timeout
+                .filter(|_| self.child.is_some() && self.outstanding.is_empty())
+            {
+                self.await_output(timeout).await?;
+            }
+
+            let buffer = self.read_frame().await?;
+            let messages = Self::parse_messages_with_max_depth(
+                &self.utf8.decode(&buffer)?,
+                self.max_json_depth,
+            )?;
+            self.queued.extend(messages);
+        }
     }
 
     async fn close(&mut self) -> Result<()> {
         self.closed
             .store(true, std::sync::atomic::Ordering::Release);
 
+        // Stop waiting on the reader thread so pending reads see end of input
+        #[cfg(windows)]
+        if let Input::Stdin(stdin) = self.stdin.lock().await.get_mut() {
+            stdin.close();
+        }
+
         // Flush any pending output
         let mut stdout = self.stdout.lock().await;
         stdout.flush().await.map_err(TransportError::from)?;
         drop(stdout);
 
+        if let Some(child) = &self.child {
+            // The child may already have exited
+            let _ = child.lock().await.start_kill();
+        }
+
         Ok(())
     }
 
@@ -108,6 +570,14 @@ impl Transport for StdioTransport {
     fn transport_type(&self) -> &'static str {
         "stdio"
     }
+
+    fn set_utf8_mode(&mut self, mode: Utf8Mode) {
+        self.utf8 = mode;
+    }
+
+    fn set_max_json_depth(&mut self, depth: usize) {
+        self.max_json_depth = depth;
+    }
 }
 
 impl StdioTransport {
@@ -139,90 +609,288 @@ impl StdioTransport {
     }
 
     /// Write framed message to stdout.
+    ///
+    /// Cancel safe: a frame is either written whole, or queued to be finished by the
+    /// next write.
     async fn write_message(&self, json_bytes: &[u8]) -> Result<()> {
-        let mut stdout = self.stdout.lock().await;
-
-        // Write content-length header
         let header = format!("{}{}\r\n\r\n", CONTENT_LENGTH_HEADER, json_bytes.len());
-        stdout
-            .write_all(header.as_bytes())
-            .await
-            .map_err(TransportError::from)?;
+        let mut frame = header.into_bytes();
+        frame.extend_from_slice(json_bytes);
 
-        // Write message payload
+        let mut stdout = self.stdout.lock().await;
         stdout
-            .write_all(json_bytes)
+            .write_frame(&frame)
             .await
             .map_err(TransportError::from)?;
-
-        // Always flush stdio
-        stdout.flush().await.map_err(TransportError::from)?;
         drop(stdout);
 
         Ok(())
     }
 
-    /// Read headers and extract content length.
-    async fn read_headers(&self) -> Result<usize> {
-        let mut stdin = self.stdin.lock().await;
-        let mut line = String::new();
-        let mut content_length = None;
-
-        // Read headers until we find content-length
+    /// Wait until the child process has output, pinging it once it has been silent
+    /// for `timeout` and handling it as hung if the ping goes unanswered as long.
+    async fn await_output(&mut self, timeout: Duration) -> Result<()> {
+        let mut pinged = false;
         loop {
-            line.clear();
-            let bytes_read = stdin
-                .read_line(&mut line)
-                .await
-                .map_err(TransportError::from)?;
+            // Unlike reading a line, waiting for buffered input can be cancelled
+            // without losing data
+            let ready = tokio::time::timeout(timeout, async {
+                let mut stdin = self.stdin.lock().await;
+                let ready = stdin.fill_buf().await.map(|_| ());
+                drop(stdin);
+                ready
+            })
+            .await;
+            match ready {
+                Ok(result) => return result.map_err(|e| TransportError::from(e).into()),
+                Err(_) if !pinged => {
+                    pinged = true;
+                    match tokio::time::timeout(timeout, self.send_keepalive()).await {
+                        // A child that exited is reported once its stdout closes
+                        Ok(sent) => {
+                            if let Err(e) = sent {
+                                tracing::debug!("Failed to send keep-alive ping: {}", e);
+                            }
+                        },
+                        Err(_) => break,
+                    }
+                },
+                Err(_) => break,
+            }
+        }
+        Err(self.handle_hang(timeout))
+    }
 
-            if bytes_read == 0 {
+    /// Send a `ping` whose response [`StdioTransport::receive`] consumes.
+    async fn send_keepalive(&mut self) -> Result<()> {
+        self.keepalive_pings += 1;
+        let ping = TransportMessage::Request {
+            id: crate::types::RequestId::String(format!(
+                "{}{}",
+                KEEPALIVE_ID_PREFIX, self.keepalive_pings
+            )),
+            request: crate::types::Request::Client(Box::new(crate::types::ClientRequest::Ping)),
+        };
+        let json_bytes = Self::serialize_message(&ping)?;
+        self.write_message(&json_bytes).await
+    }
+
+    /// Record a request sent to the child process until it is answered or cancelled.
+    fn track_outstanding(&mut self, message: &TransportMessage) {
+        match message {
+            TransportMessage::Request { id, .. } => {
+                self.outstanding.insert(id.clone());
+            },
+            TransportMessage::Notification(
+                crate::types::Notification::Cancelled(cancelled)
+                | crate::types::Notification::Client(crate::types::ClientNotification::Cancelled(
+                    cancelled,
+                )),
+            ) => {
+                self.outstanding.remove(&cancelled.request_id);
+            },
+            _ => {},
+        }
+    }
+
+    /// Whether `message` answers a keep-alive ping.
+    fn is_keepalive_response(message: &TransportMessage) -> bool {
+        let TransportMessage::Response(response) = message else {
+            return false;
+        };
+        matches!(&response.id, crate::types::RequestId::String(id) if id.starts_with(KEEPALIVE_ID_PREFIX))
+    }
+
+    /// Kill the hung child process and launch it again if configured to, closing
+    /// the transport otherwise.
+    fn handle_hang(&mut self, timeout: Duration) -> Error {
+        tracing::warn!(
+            "Server process did not answer a keep-alive ping within {:?}; recent stderr: {:?}",
+            timeout,
+            self.stderr_lines()
+        );
+        if let Some(child) = &mut self.child {
+            // The child may have exited in the meantime
+            let _ = child.get_mut().start_kill();
+        }
+
+        let relaunched = match &self.launch {
+            Some(launch) if self.config.restart_on_hang => launch
+                .start(&self.stderr)
+                .map_err(|e| tracing::error!("Failed to restart server process: {}", e))
+                .ok(),
+            _ => None,
+        };
+        match relaunched {
+            Some((child, stdin, stdout)) => {
+                self.child = Some(Mutex::new(child));
+                self.stdin = Mutex::new(BufReader::new(Input::Child(stdout)));
+                self.frame = PartialFrame::default();
+                self.stdout = Mutex::new(FrameWriter::new(Output::Child(stdin)));
+                self.outstanding.clear();
+                self.restarts += 1;
+            },
+            None => self
+                .closed
+                .store(true, std::sync::atomic::Ordering::Release),
+        }
+        TransportError::Unresponsive(timeout).into()
+    }
+
+    /// Read the headers and body of a frame.
+    ///
+    /// Only reads what is already buffered, so it can be cancelled at any await
+    /// point; the next call continues the frame.
+    async fn read_frame(&mut self) -> Result<Vec<u8>> {
+        let mut stdin = self.stdin.lock().await;
+        let frame = &mut self.frame;
+
+        // Read headers until the empty line ending them
+        while frame.body.is_none() {
+            let available = stdin.fill_buf().await.map_err(TransportError::from)?;
+            if available.is_empty() {
                 // EOF reached
                 drop(stdin);
                 self.closed
                     .store(true, std::sync::atomic::Ordering::Release);
+                if self.child.is_some() {
+                    tracing::warn!(
+                        "Server process closed its stdout; recent stderr: {:?}",
+                        self.stderr_lines()
+                    );
+                }
                 return Err(TransportError::ConnectionClosed.into());
             }
+            let Some(end) = available.iter().position(|&byte| byte == b'\n') else {
+                let read = available.len();
+                frame.line.extend_from_slice(available);
+                stdin.consume(read);
+                continue;
+            };
+            frame.line.extend_from_slice(&available[..=end]);
+            stdin.consume(end + 1);
 
+            let line = std::mem::take(&mut frame.line);
+            let line = String::from_utf8_lossy(&line);
             let line = line.trim();
-
             if line.is_empty() {
-                // End of headers
-                break;
+                let Some(length) = frame.content_length else {
+                    return Err(TransportError::InvalidMessage(
+                        "Missing Content-Length header".to_string(),
+                    )
+                    .into());
+                };
+                frame.body = Some(Vec::with_capacity(length));
+            } else if let Some(length) = Self::parse_content_length(line) {
+                frame.content_length = Some(length);
             }
+        }
 
-            if let Some(length) = Self::parse_content_length(line) {
-                content_length = Some(length);
+        // Read the body
+        let length = frame.content_length.unwrap_or_default();
+        let body = frame.body.get_or_insert_with(Vec::new);
+        while body.len() < length {
+            let available = stdin.fill_buf().await.map_err(TransportError::from)?;
+            if available.is_empty() {
+                return Err(TransportError::from(std::io::Error::from(
+                    std::io::ErrorKind::UnexpectedEof,
+                ))
+                .into());
             }
+            let read = available.len().min(length - body.len());
+            body.extend_from_slice(&available[..read]);
+            stdin.consume(read);
         }
+        let body = std::mem::take(body);
+        *frame = PartialFrame::default();
         drop(stdin);
-
-        content_length.ok_or_else(|| {
-            TransportError::InvalidMessage("Missing Content-Length header".to_string()).into()
-        })
-    }
-
-    /// Read message body with specified content length.
-    async fn read_message_body(&self, content_length: usize) -> Result<Vec<u8>> {
-        let mut stdin = self.stdin.lock().await;
-        let mut buffer = vec![0u8; content_length];
-        stdin
-            .read_exact(&mut buffer)
-            .await
-            .map_err(TransportError::from)?;
-        drop(stdin);
-        Ok(buffer)
+        Ok(body)
     }
 
     /// Parse JSON message and determine its type.
+    ///
+    /// Messages nested deeper than [`DEFAULT_MAX_JSON_DEPTH`] are rejected, see
+    /// [`StdioTransport::parse_message_with_max_depth`].
     pub fn parse_message(buffer: &[u8]) -> Result<TransportMessage> {
+        Self::parse_message_with_max_depth(buffer, DEFAULT_MAX_JSON_DEPTH)
+    }
+
+    /// Parse JSON message nested at most `max_depth` arrays and objects deep.
+    ///
+    /// The depth is checked before deserializing, so deeply nested input cannot
+    /// overflow the stack.
+    ///
+    /// # Errors
+    ///
+    /// Returns a parse error if the message is nested too deeply.
+    pub fn parse_message_with_max_depth(
+        buffer: &[u8],
+        max_depth: usize,
+    ) -> Result<TransportMessage> {
+        check_json_depth(buffer, max_depth)?;
         let json_value: serde_json::Value = serde_json::from_slice(buffer)
             .map_err(|e| TransportError::InvalidMessage(format!("Invalid JSON: {}", e)))?;
+        Self::parse_value(json_value)
+    }
+
+    /// Parse a frame holding either one message or a JSON-RPC batch of them.
+    ///
+    /// Messages nested deeper than [`DEFAULT_MAX_JSON_DEPTH`] are rejected, see
+    /// [`StdioTransport::parse_messages_with_max_depth`].
+    pub fn parse_messages(buffer: &[u8]) -> Result<Vec<TransportMessage>> {
+        Self::parse_messages_with_max_depth(buffer, DEFAULT_MAX_JSON_DEPTH)
+    }
 
+    /// Parse a frame holding either one message or a JSON-RPC batch of them, nested
+    /// at most `max_depth` arrays and objects deep, counting the batch itself.
+    ///
+    /// A single message is parsed like [`StdioTransport::parse_message_with_max_depth`].
+    /// Batch elements that are not valid messages are logged and skipped, so one bad
+    /// element does not lose the others.
+    ///
+    /// # Errors
+    ///
+    /// Returns a parse error if the frame is not valid JSON, is nested too deeply or
+    /// is an empty batch.
+    pub fn parse_messages_with_max_depth(
+        buffer: &[u8],
+        max_depth: usize,
+    ) -> Result<Vec<TransportMessage>> {
+        if buffer.iter().find(|byte| !byte.is_ascii_whitespace()) != Some(&b'[') {
+            return Self::parse_message_with_max_depth(buffer, max_depth).map(|m| vec![m]);
+        }
+
+        check_json_depth(buffer, max_depth)?;
+        let elements: Vec<serde_json::Value> = serde_json::from_slice(buffer)
+            .map_err(|e| TransportError::InvalidMessage(format!("Invalid JSON: {}", e)))?;
+        if elements.is_empty() {
+            return Err(TransportError::InvalidMessage("Empty batch".to_string()).into());
+        }
+        Ok(elements
+            .into_iter()
+            .filter_map(|element| {
+                Self::parse_value(element)
+                    .map_err(|e| tracing::warn!("Skipping invalid batch element: {}", e))
+                    .ok()
+            })
+            .collect())
+    }
+
+    /// Determine the type of a parsed JSON message.
+    fn parse_value(json_value: serde_json::Value) -> Result<TransportMessage> {
         if json_value.get("method").is_some() {
             Self::parse_method_message(json_value)
         } else if json_value.get("result").is_some() || json_value.get("error").is_some() {
             Self::parse_response_message(json_value)
+        } else if let Some(id) = json_value
+            .get("id")
+            .and_then(|id| serde_json::from_value(id.clone()).ok())
+        {
+            Err(TransportError::MalformedRequest {
+                id,
+                message: "Unknown message type".to_string(),
+            }
+            .into())
         } else {
             Err(TransportError::InvalidMessage("Unknown message type".to_string()).into())
         }
@@ -230,15 +898,22 @@ impl StdioTransport {
 
     /// Parse message with method field (request or notification).
     fn parse_method_message(json_value: serde_json::Value) -> Result<TransportMessage> {
-        if json_value.get("id").is_some() {
-            // It's a request
+        if let Some(id) = json_value.get("id") {
+            // It's a request. Keep the ID, if usable, so the peer can be sent an error
+            // response instead of the frame being dropped.
+            let id = serde_json::from_value::<crate::types::RequestId>(id.clone()).ok();
+            let invalid = |e: &dyn std::fmt::Display| match &id {
+                Some(id) => TransportError::MalformedRequest {
+                    id: id.clone(),
+                    message: format!("Invalid request: {}", e),
+                },
+                None => TransportError::InvalidMessage(format!("Invalid request: {}", e)),
+            };
+
             let request: crate::types::JSONRPCRequest<serde_json::Value> =
-                serde_json::from_value(json_value).map_err(|e| {
-                    TransportError::InvalidMessage(format!("Invalid request: {}", e))
-                })?;
+                serde_json::from_value(json_value).map_err(|e| invalid(&e))?;
 
-            let parsed_request = crate::shared::parse_request(request)
-                .map_err(|e| TransportError::InvalidMessage(format!("Invalid request: {}", e)))?;
+            let parsed_request = crate::shared::parse_request(request).map_err(|e| invalid(&e))?;
 
             Ok(TransportMessage::Request {
                 id: parsed_request.0,
@@ -264,9 +939,138 @@ impl StdioTransport {
     }
 }
 
+/// Async reader fed by a dedicated blocking thread.
+#[cfg(any(windows, test))]
+mod blocking_reader {
+    use std::io::Read;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use tokio::io::{AsyncRead, ReadBuf};
+    use tokio::sync::mpsc;
+
+    /// Bytes requested from the source per blocking read.
+    const CHUNK_SIZE: usize = 8192;
+
+    /// Chunks buffered between the reader thread and the async side.
+    const CHANNEL_CAPACITY: usize = 16;
+
+    /// Reads a blocking source on its own thread and exposes it as [`AsyncRead`].
+    ///
+    /// The thread exits at end of input, on a read error, or once the reader is closed
+    /// and the thread's current read returns.
+    #[derive(Debug)]
+    pub(super) struct BlockingReader {
+        chunks: mpsc::Receiver<std::io::Result<Vec<u8>>>,
+        pending: Vec<u8>,
+        offset: usize,
+    }
+
+    impl BlockingReader {
+        /// Start reading `source` on a new thread.
+        pub(super) fn spawn<R: Read + Send + 'static>(mut source: R) -> Self {
+            let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+            let spawned = std::thread::Builder::new()
+                .name("pmcp-stdin".to_string())
+                .spawn(move || {
+                    let mut buf = vec![0u8; CHUNK_SIZE];
+                    loop {
+                        let chunk = match source.read(&mut buf) {
+                            Ok(0) => break,
+                            Ok(n) => Ok(buf[..n].to_vec()),
+                            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                            Err(e) => Err(e),
+                        };
+                        let failed = chunk.is_err();
+                        if tx.blocking_send(chunk).is_err() || failed {
+                            break;
+                        }
+                    }
+                });
+            if let Err(e) = spawned {
+                tracing::error!("Failed to spawn stdin reader thread: {}", e);
+            }
+
+            Self {
+                chunks: rx,
+                pending: Vec::new(),
+                offset: 0,
+            }
+        }
+
+        /// Stop accepting input; reads return end of input once buffered chunks drain.
+        pub(super) fn close(&mut self) {
+            self.chunks.close();
+        }
+    }
+
+    impl AsyncRead for BlockingReader {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<std::io::Result<()>> {
+            let this = &mut *self;
+            if this.offset >= this.pending.len() {
+                match std::task::ready!(this.chunks.poll_recv(cx)) {
+                    Some(Ok(chunk)) => {
+                        this.pending = chunk;
+                        this.offset = 0;
+                    },
+                    Some(Err(e)) => return Poll::Ready(Err(e)),
+                    None => return Poll::Ready(Ok(())),
+                }
+            }
+
+            let available = &this.pending[this.offset..];
+            let n = available.len().min(buf.remaining());
+            buf.put_slice(&available[..n]);
+            this.offset += n;
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+/// Reject `buffer` if its arrays and objects nest deeper than `max_depth`.
+///
+/// Brackets inside strings are skipped. Malformed JSON is left for the parser to
+/// report.
+pub(crate) fn check_json_depth(buffer: &[u8], max_depth: usize) -> Result<()> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for &byte in buffer {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {},
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                depth += 1;
+                if depth > max_depth {
+                    return Err(Error::parse(format!(
+                        "max depth exceeded: message is nested deeper than {}",
+                        max_depth
+                    )));
+                }
+            },
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {},
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::Error;
+    use std::time::Duration;
 
     #[test]
     fn parse_content_length_valid() {
@@ -307,6 +1111,166 @@ mod tests {
         assert_eq!(StdioTransport::parse_content_length("Content-Length"), None);
     }
 
+    #[test]
+    fn parse_malformed_request_keeps_id() {
+        let err = StdioTransport::parse_message(
+            br#"{"jsonrpc":"2.0","id":"abc","method":"tools/call","params":"bad"}"#,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Transport(TransportError::MalformedRequest { id, .. })
+                if id == crate::types::RequestId::from("abc")
+        ));
+
+        let err = StdioTransport::parse_message(b"{not json").unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Transport(TransportError::InvalidMessage(_))
+        ));
+    }
+
+    #[test]
+    fn parse_rejects_deeply_nested_messages() {
+        let nested = |depth: usize| {
+            format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{{"name":"t","arguments":{}{}}}}}"#,
+                "[".repeat(depth),
+                "]".repeat(depth)
+            )
+        };
+        // The envelope and params add two levels
+        assert!(StdioTransport::parse_message_with_max_depth(nested(8).as_bytes(), 10).is_ok());
+
+        let err =
+            StdioTransport::parse_message_with_max_depth(nested(9).as_bytes(), 10).unwrap_err();
+        assert!(err.is_error_code(crate::ErrorCode::PARSE_ERROR));
+        assert!(err.to_string().contains("max depth exceeded"), "{}", err);
+
+        // The default limit applies to every transport
+        let err = StdioTransport::parse_message(nested(100_000).as_bytes()).unwrap_err();
+        assert!(err.is_error_code(crate::ErrorCode::PARSE_ERROR));
+
+        // Brackets inside strings do not count
+        let frame = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"result":{{"text":"{}\"{}"}}}}"#,
+            "[".repeat(20),
+            "{".repeat(20)
+        );
+        assert!(StdioTransport::parse_message_with_max_depth(frame.as_bytes(), 2).is_ok());
+    }
+
+    #[test]
+    fn parse_messages_splits_batches() {
+        let single =
+            StdioTransport::parse_messages(br#"{"jsonrpc":"2.0","id":1,"result":{}}"#).unwrap();
+        assert!(matches!(single.as_slice(), [TransportMessage::Response(_)]));
+
+        let batch = StdioTransport::parse_messages(
+            br#" [{"jsonrpc":"2.0","id":1,"result":{}},
+                 {"jsonrpc":"2.0","id":2,"error":{"code":-32601,"message":"nope"}},
+                 {"unexpected":true},
+                 {"jsonrpc":"2.0","method":"notifications/initialized"}]"#,
+        )
+        .unwrap();
+        assert_eq!(batch.len(), 3);
+        assert!(
+            matches!(&batch[0], TransportMessage::Response(r) if r.id == crate::types::RequestId::from(1i64))
+        );
+        assert!(
+            matches!(&batch[1], TransportMessage::Response(r) if r.id == crate::types::RequestId::from(2i64))
+        );
+        assert!(matches!(&batch[2], TransportMessage::Notification(_)));
+
+        let err = StdioTransport::parse_messages(b"[]").unwrap_err();
+        assert!(err.to_string().contains("Empty batch"), "{}", err);
+
+        // The batch counts towards the nesting depth
+        let err = StdioTransport::parse_messages_with_max_depth(
+            br#"[{"jsonrpc":"2.0","id":1,"result":{}}]"#,
+            2,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("nested"), "{}", err);
+    }
+
+    #[test]
+    fn parse_mis_shaped_messages() {
+        // A notification method with an id is answered as an invalid request
+        let err = StdioTransport::parse_message(
+            br#"{"jsonrpc":"2.0","id":3,"method":"notifications/initialized"}"#,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Transport(TransportError::MalformedRequest { id, ref message })
+                if id == crate::types::RequestId::from(3i64) && message.contains("must not have an id")
+        ));
+
+        // A request method without an id cannot be answered
+        let err = StdioTransport::parse_message(br#"{"jsonrpc":"2.0","method":"tools/list"}"#)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Transport(TransportError::InvalidMessage(ref message))
+                if message.contains("needs an id")
+        ));
+    }
+
+    /// Blocking source that hands out whatever the test sends it.
+    struct ChannelSource(std::sync::mpsc::Receiver<Vec<u8>>);
+
+    impl std::io::Read for ChannelSource {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.0.recv() {
+                Ok(bytes) => {
+                    buf[..bytes.len()].copy_from_slice(&bytes);
+                    Ok(bytes.len())
+                },
+                Err(_) => Ok(0),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn blocking_reader_keeps_runtime_responsive() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut reader = BufReader::new(blocking_reader::BlockingReader::spawn(ChannelSource(rx)));
+
+        tx.send(b"Content-Length: 2\r\n".to_vec()).unwrap();
+        let mut line = String::new();
+        reader.read_line(&mut line).await.unwrap();
+        assert_eq!(line, "Content-Length: 2\r\n");
+
+        // The source is now blocked; on this single-threaded runtime the timeout can
+        // only fire if the pending read yields instead of blocking the thread.
+        line.clear();
+        let pending =
+            tokio::time::timeout(Duration::from_millis(50), reader.read_line(&mut line)).await;
+        assert!(pending.is_err());
+
+        reader.get_mut().close();
+        let n = tokio::time::timeout(Duration::from_secs(1), reader.read_line(&mut line))
+            .await
+            .expect("close should unblock the reader")
+            .unwrap();
+        assert_eq!(n, 0);
+        drop(tx);
+    }
+
+    #[cfg(windows)]
+    #[tokio::test]
+    async fn close_unblocks_windows_stdin() {
+        let mut transport = StdioTransport::new();
+        let _ = tokio::time::timeout(Duration::from_millis(50), transport.receive()).await;
+
+        transport.close().await.unwrap();
+        let result = tokio::time::timeout(Duration::from_secs(1), transport.receive())
+            .await
+            .expect("receive should not hang after close");
+        assert!(result.is_err());
+    }
+
     #[tokio::test]
     async fn transport_properties() {
         let transport = StdioTransport::new();
@@ -322,4 +1286,350 @@ mod tests {
         transport.close().await.unwrap();
         assert!(!transport.is_connected());
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn spawn_round_trips_through_child() {
+        // `cat` echoes every framed message straight back
+        let mut transport = StdioTransport::spawn("cat", &[], &[]).unwrap();
+        let notification = TransportMessage::Notification(crate::types::Notification::Client(
+            crate::types::ClientNotification::Initialized,
+        ));
+        transport.send(notification).await.unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(5), transport.receive())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(
+            received,
+            TransportMessage::Notification(crate::types::Notification::Client(
+                crate::types::ClientNotification::Initialized
+            ))
+        ));
+        transport.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn cancelled_write_is_finished_before_the_next_frame() {
+        let (output, mut peer) = tokio::io::duplex(8);
+        let mut writer = FrameWriter::new(output);
+
+        // Only part of the frame fits until the peer reads
+        let cancelled = tokio::time::timeout(
+            Duration::from_millis(50),
+            writer.write_frame(b"first frame;"),
+        )
+        .await;
+        assert!(cancelled.is_err());
+
+        let reader = tokio::spawn(async move {
+            let mut received = vec![0; 24];
+            tokio::io::AsyncReadExt::read_exact(&mut peer, &mut received)
+                .await
+                .unwrap();
+            received
+        });
+        writer.write_frame(b"second frame").await.unwrap();
+        assert_eq!(reader.await.unwrap(), b"first frame;second frame");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn cancelled_receive_keeps_partial_frame() {
+        let mut transport = StdioTransport::spawn("cat", &[], &[]).unwrap();
+        let body = br#"{"jsonrpc":"2.0","id":7,"result":{}}"#;
+        let header = format!("{}{}\r\n\r\n", CONTENT_LENGTH_HEADER, body.len());
+        let (first, rest) = body.split_at(10);
+        let mut frame = header.into_bytes();
+        frame.extend_from_slice(first);
+        transport
+            .stdout
+            .lock()
+            .await
+            .write_frame(&frame)
+            .await
+            .unwrap();
+
+        // Gives up waiting for the rest of the body
+        let cancelled = tokio::time::timeout(Duration::from_millis(200), transport.receive()).await;
+        assert!(cancelled.is_err());
+
+        transport
+            .stdout
+            .lock()
+            .await
+            .write_frame(rest)
+            .await
+            .unwrap();
+        let received = tokio::time::timeout(Duration::from_secs(5), transport.receive())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(
+            matches!(&received, TransportMessage::Response(r) if r.id == crate::types::RequestId::from(7i64)),
+            "{:?}",
+            received
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn receive_returns_batch_elements_in_order() {
+        let mut transport = StdioTransport::spawn("cat", &[], &[]).unwrap();
+        transport
+            .write_message(
+                br#"[{"jsonrpc":"2.0","id":1,"result":{}},{"jsonrpc":"2.0","id":2,"result":{}}]"#,
+            )
+            .await
+            .unwrap();
+        transport
+            .write_message(br#"{"jsonrpc":"2.0","id":3,"result":{}}"#)
+            .await
+            .unwrap();
+
+        for expected in 1..=3i64 {
+            let received = tokio::time::timeout(Duration::from_secs(5), transport.receive())
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(
+                matches!(&received, TransportMessage::Response(r) if r.id == crate::types::RequestId::from(expected)),
+                "{:?}",
+                received
+            );
+        }
+        transport.close().await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn utf8_mode_applies_to_received_frames() {
+        let mut transport = StdioTransport::spawn("cat", &[], &[]).unwrap();
+        let frame = b"{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{\"text\":\"caf\xE9\"}}";
+        transport.write_message(frame).await.unwrap();
+        transport.write_message(frame).await.unwrap();
+
+        let err = tokio::time::timeout(Duration::from_secs(5), transport.receive())
+            .await
+            .unwrap()
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid UTF-8"), "{}", err);
+
+        transport.set_utf8_mode(Utf8Mode::Lossy);
+        let received = tokio::time::timeout(Duration::from_secs(5), transport.receive())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(received, TransportMessage::Response(_)));
+        transport.close().await.unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn spawn_reports_child_exit_as_connection_closed() {
+        let mut transport = StdioTransport::spawn(
+            "sh",
+            &["-c", "echo \"startup failed: $REASON\" >&2"],
+            &[("REASON".to_string(), "missing config".to_string())],
+        )
+        .unwrap();
+
+        let result = tokio::time::timeout(Duration::from_secs(5), transport.receive())
+            .await
+            .unwrap();
+        assert!(matches!(
+            result,
+            Err(Error::Transport(TransportError::ConnectionClosed))
+        ));
+        assert!(!transport.is_connected());
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while transport.stderr_lines().is_empty() && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(
+            transport.stderr_lines(),
+            vec!["startup failed: missing config".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn spawn_missing_command_fails() {
+        let result = StdioTransport::spawn("pmcp-no-such-command", &[], &[]);
+        assert!(matches!(
+            result,
+            Err(Error::Transport(TransportError::Io(_)))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn on_stderr_receives_child_stderr() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut transport = StdioTransport::spawn(
+            "sh",
+            &[
+                "-c",
+                "echo 'loading config' >&2; sleep 0.2; echo 'config missing' >&2",
+            ],
+            &[],
+        )
+        .unwrap()
+        .on_stderr(move |line| {
+            let _ = tx.send(line);
+        });
+
+        let mut lines = Vec::new();
+        while lines.len() < 2 {
+            let line = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+                .await
+                .expect("stderr line should arrive")
+                .unwrap();
+            lines.push(line);
+        }
+        assert_eq!(lines, vec!["loading config", "config missing"]);
+
+        let result = tokio::time::timeout(Duration::from_secs(5), transport.receive())
+            .await
+            .unwrap();
+        assert!(matches!(
+            result,
+            Err(Error::Transport(TransportError::ConnectionClosed))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn liveness_check_kills_child_that_stops_answering_pings() {
+        // Answers the first keep-alive ping, then hangs
+        let pong = r#"{"jsonrpc":"2.0","id":"pmcp-keepalive-1","result":{}}"#;
+        let frame = format!("{}{}\r\n\r\n{}", CONTENT_LENGTH_HEADER, pong.len(), pong);
+        let config = StdioTransportConfig {
+            liveness_timeout: Some(Duration::from_millis(100)),
+            restart_on_hang: false,
+        };
+        let mut transport = StdioTransport::spawn_with_config(
+            "sh",
+            &[
+                "-c",
+                "head -c 1 >/dev/null; printf '%s' \"$PONG\"; exec sleep 30",
+            ],
+            &[("PONG".to_string(), frame)],
+            config,
+        )
+        .unwrap();
+
+        let result = tokio::time::timeout(Duration::from_secs(5), transport.receive())
+            .await
+            .unwrap();
+        assert!(
+            matches!(
+                result,
+                Err(Error::Transport(TransportError::Unresponsive(timeout)))
+                    if timeout == Duration::from_millis(100)
+            ),
+            "{:?}",
+            result
+        );
+        assert_eq!(transport.keepalive_pings, 2);
+        assert!(!transport.is_connected());
+        assert_eq!(transport.restarts(), 0);
+
+        let child = transport.child.as_mut().unwrap().get_mut();
+        let status = tokio::time::timeout(Duration::from_secs(5), child.wait())
+            .await
+            .expect("hung child should be killed")
+            .unwrap();
+        assert!(!status.success());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn liveness_check_spares_child_busy_with_a_request() {
+        // Takes several liveness timeouts to answer, and never answers pings
+        let response = r#"{"jsonrpc":"2.0","id":1,"result":{}}"#;
+        let frame = format!(
+            "{}{}\r\n\r\n{}",
+            CONTENT_LENGTH_HEADER,
+            response.len(),
+            response
+        );
+        let config = StdioTransportConfig {
+            liveness_timeout: Some(Duration::from_millis(100)),
+            restart_on_hang: false,
+        };
+        let mut transport = StdioTransport::spawn_with_config(
+            "sh",
+            &[
+                "-c",
+                "head -c 1 >/dev/null; sleep 0.5; printf '%s' \"$RESPONSE\"; exec sleep 30",
+            ],
+            &[("RESPONSE".to_string(), frame)],
+            config,
+        )
+        .unwrap();
+
+        transport
+            .send(TransportMessage::Request {
+                id: crate::types::RequestId::from(1i64),
+                request: crate::types::Request::Client(Box::new(crate::types::ClientRequest::Ping)),
+            })
+            .await
+            .unwrap();
+        let received = tokio::time::timeout(Duration::from_secs(5), transport.receive())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(
+            matches!(&received, TransportMessage::Response(r) if r.id == crate::types::RequestId::from(1i64)),
+            "{:?}",
+            received
+        );
+        assert_eq!(transport.keepalive_pings, 0);
+        assert!(transport.is_connected());
+
+        // Once answered, silence is checked again
+        let result = tokio::time::timeout(Duration::from_secs(5), transport.receive())
+            .await
+            .unwrap();
+        assert!(matches!(
+            result,
+            Err(Error::Transport(TransportError::Unresponsive(_)))
+        ));
+        assert_eq!(transport.keepalive_pings, 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn liveness_check_restarts_hung_child() {
+        let config = StdioTransportConfig {
+            liveness_timeout: Some(Duration::from_millis(100)),
+            restart_on_hang: true,
+        };
+        let mut transport = StdioTransport::spawn_with_config(
+            "sh",
+            &["-c", "echo started >&2; exec sleep 30"],
+            &[],
+            config,
+        )
+        .unwrap();
+
+        let result = tokio::time::timeout(Duration::from_secs(5), transport.receive())
+            .await
+            .unwrap();
+        assert!(matches!(
+            result,
+            Err(Error::Transport(TransportError::Unresponsive(_)))
+        ));
+        assert!(transport.is_connected());
+        assert_eq!(transport.restarts(), 1);
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while transport.stderr_lines().len() < 2 && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(transport.stderr_lines(), vec!["started", "started"]);
+        transport.close().await.unwrap();
+    }
 }
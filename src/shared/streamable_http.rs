@@ -1,18 +1,28 @@
 use crate::error::{Error, Result, TransportError};
 use crate::shared::http_constants::{
-    ACCEPT, ACCEPT_STREAMABLE, APPLICATION_JSON, CONTENT_TYPE, LAST_EVENT_ID, MCP_PROTOCOL_VERSION,
-    MCP_SESSION_ID, TEXT_EVENT_STREAM,
+    is_json_content_type, ACCEPT, ACCEPT_STREAMABLE, APPLICATION_JSON, CONTENT_TYPE, LAST_EVENT_ID,
+    MCP_PROTOCOL_VERSION, MCP_SESSION_ID, TEXT_EVENT_STREAM,
 };
 use crate::shared::sse_parser::SseParser;
-use crate::shared::{Transport, TransportMessage};
+use crate::shared::{Transport, TransportMessage, DEFAULT_MAX_JSON_DEPTH};
 use async_trait::async_trait;
 use parking_lot::RwLock;
 use reqwest::{Client, RequestBuilder, Response};
 use std::fmt::Debug;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use url::Url;
 
+/// Delay before resuming an interrupted event stream, unless the server set one
+/// with the SSE `retry` field.
+const DEFAULT_RESUME_DELAY: Duration = Duration::from_secs(1);
+
+/// Number of times in a row an interrupted event stream is resumed without
+/// receiving any event before giving up on it.
+const MAX_RESUME_ATTEMPTS: u32 = 3;
+
 /// Options for sending messages over streamable HTTP transport.
 ///
 /// # Examples
@@ -60,6 +70,8 @@ pub struct SendOptions {
 ///     auth_provider: None,
 ///     session_id: None,
 ///     enable_json_response: false,
+///     accept: None,
+///     sse_accept: None,
 ///     on_resumption_token: None,
 /// };
 ///
@@ -72,6 +84,8 @@ pub struct SendOptions {
 ///     auth_provider: None,
 ///     session_id: Some("session-123".to_string()),
 ///     enable_json_response: false,
+///     accept: None,
+///     sse_accept: None,
 ///     on_resumption_token: None,
 /// };
 ///
@@ -82,6 +96,8 @@ pub struct SendOptions {
 ///     auth_provider: None,
 ///     session_id: None,
 ///     enable_json_response: true,  // JSON instead of SSE
+///     accept: Some("application/json".to_string()),
+///     sse_accept: None,
 ///     on_resumption_token: None,
 /// };
 /// ```
@@ -97,6 +113,10 @@ pub struct StreamableHttpTransportConfig {
     pub session_id: Option<String>,
     /// Enable JSON responses instead of SSE (for simple request/response)
     pub enable_json_response: bool,
+    /// `Accept` header for POST requests (defaults to `application/json, text/event-stream`)
+    pub accept: Option<String>,
+    /// `Accept` header for the GET SSE stream (defaults to `text/event-stream`)
+    pub sse_accept: Option<String>,
     /// Callback when resumption token is received
     pub on_resumption_token: Option<Arc<dyn Fn(String) + Send + Sync>>,
 }
@@ -109,6 +129,8 @@ impl Debug for StreamableHttpTransportConfig {
             .field("auth_provider", &self.auth_provider.is_some())
             .field("session_id", &self.session_id)
             .field("enable_json_response", &self.enable_json_response)
+            .field("accept", &self.accept)
+            .field("sse_accept", &self.sse_accept)
             .field("on_resumption_token", &self.on_resumption_token.is_some())
             .finish()
     }
@@ -121,6 +143,12 @@ impl Debug for StreamableHttpTransportConfig {
 /// - Stateful: Optional session ID tracking for persistent sessions
 ///
 /// The transport can handle both JSON responses and SSE streams based on server response.
+///
+/// The session ID the server assigns with the `Mcp-Session-Id` header is kept and sent
+/// with every later request. Events on SSE streams are delivered as they arrive, and
+/// when a stream of a session is interrupted, it is resumed with a GET request carrying
+/// the ID of the last event received as `Last-Event-ID`, so that the server replays the
+/// events sent meanwhile.
 #[derive(Clone)]
 pub struct StreamableHttpTransport {
     config: Arc<RwLock<StreamableHttpTransportConfig>>,
@@ -133,8 +161,14 @@ pub struct StreamableHttpTransport {
     protocol_version: Arc<RwLock<Option<String>>>,
     /// Abort controller for SSE streams
     abort_handle: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
-    /// Last event ID for resumability
+    /// Tasks reading the SSE streams answering POST requests
+    post_streams: Arc<parking_lot::Mutex<Vec<tokio::task::JoinHandle<()>>>>,
+    /// ID of the last event received on any stream
     last_event_id: Arc<RwLock<Option<String>>>,
+    /// OAuth access token set by the client, used without an auth provider
+    access_token: Arc<RwLock<Option<String>>>,
+    /// Maximum nesting depth of inbound messages
+    max_json_depth: Arc<AtomicUsize>,
 }
 
 impl Debug for StreamableHttpTransport {
@@ -158,7 +192,10 @@ impl StreamableHttpTransport {
             sender,
             protocol_version: Arc::new(RwLock::new(None)),
             abort_handle: Arc::new(RwLock::new(None)),
+            post_streams: Arc::new(parking_lot::Mutex::new(Vec::new())),
             last_event_id: Arc::new(RwLock::new(None)),
+            access_token: Arc::new(RwLock::new(None)),
+            max_json_depth: Arc::new(AtomicUsize::new(DEFAULT_MAX_JSON_DEPTH)),
         }
     }
 
@@ -182,7 +219,10 @@ impl StreamableHttpTransport {
         *self.protocol_version.write() = version;
     }
 
-    /// Get the last event ID (for resumability)
+    /// Get the ID of the last event received on any stream.
+    ///
+    /// Each stream is resumed after the last event received on it, which may be
+    /// older than this one if events arrive on several streams.
     pub fn last_event_id(&self) -> Option<String> {
         self.last_event_id.read().clone()
     }
@@ -195,53 +235,157 @@ impl StreamableHttpTransport {
             handle.abort();
         }
 
-        let url = self.config.read().url.clone();
+        let Some(response) = self.open_sse(resumption_token.clone()).await? else {
+            // Server doesn't support GET SSE, which is OK
+            return Ok(());
+        };
+        let handle = tokio::spawn(self.clone().stream_events(response, resumption_token));
+        *self.abort_handle.write() = Some(handle);
+        Ok(())
+    }
+
+    /// Send the GET request opening an SSE stream, resuming after `resumption_token`
+    /// if given.
+    ///
+    /// Returns `None` if the server does not offer a stream (405).
+    async fn open_sse(&self, resumption_token: Option<String>) -> Result<Option<Response>> {
+        let (url, accept) = {
+            let config = self.config.read();
+            (
+                config.url.clone(),
+                config
+                    .sse_accept
+                    .clone()
+                    .unwrap_or_else(|| TEXT_EVENT_STREAM.to_string()),
+            )
+        };
         let mut builder = self.build_request(reqwest::Method::GET, url).await?;
 
-        builder = builder.header(ACCEPT, TEXT_EVENT_STREAM);
+        builder = builder.header(ACCEPT, accept);
 
         // Add Last-Event-ID for resumability
         if let Some(token) = resumption_token {
             builder = builder.header(LAST_EVENT_ID, token);
         }
 
-        let response = builder.send().await;
-
-        // Handle 405 (SSE not supported) gracefully
-        if let Ok(resp) = &response {
-            if resp.status().as_u16() == 405 {
-                // Server doesn't support GET SSE, which is OK
-                return Ok(());
-            }
-
-            if !resp.status().is_success() {
-                return Err(Error::Transport(TransportError::Request(format!(
-                    "SSE request failed with status: {}",
-                    resp.status()
-                ))));
-            }
-        } else if let Err(e) = response {
-            return Err(Error::Transport(TransportError::Request(e.to_string())));
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| Error::Transport(TransportError::Request(e.to_string())))?;
+        if response.status().as_u16() == 405 {
+            return Ok(None);
+        }
+        if response.status().as_u16() == 401 {
+            return Err(Error::authentication(format!(
+                "SSE request failed with status: {}",
+                response.status()
+            )));
+        }
+        if !response.status().is_success() {
+            return Err(Error::Transport(TransportError::Request(format!(
+                "SSE request failed with status: {}",
+                response.status()
+            ))));
         }
 
-        let response = response.unwrap();
         self.process_response_headers(&response);
+        Ok(Some(response))
+    }
 
-        // Start streaming task
-        let sender = self.sender.clone();
-        let on_resumption = self.config.read().on_resumption_token.clone();
-        let last_event_id = self.last_event_id.clone();
+    /// Deliver the events of an SSE stream, resuming it with GET requests while it
+    /// keeps being interrupted.
+    ///
+    /// The stream is resumed after the last event received on it, starting from
+    /// `last_event_id` for a stream that resumes an earlier one.
+    async fn stream_events(self, mut response: Response, mut last_event_id: Option<String>) {
+        let mut attempts = 0;
+        loop {
+            let end = self.read_events(response, &mut last_event_id).await;
+            if !end.interrupted {
+                return;
+            }
+            // Only a session's streams can be resumed, and only after an event ID
+            let resumption_token = match (self.session_id(), &last_event_id) {
+                (Some(_), Some(last_event_id)) => last_event_id.clone(),
+                _ => {
+                    tracing::warn!("SSE stream interrupted, and it cannot be resumed");
+                    return;
+                },
+            };
+            attempts = if end.received { 1 } else { attempts + 1 };
+            if attempts > MAX_RESUME_ATTEMPTS {
+                tracing::warn!(
+                    "Giving up resuming SSE stream after {} attempts",
+                    MAX_RESUME_ATTEMPTS
+                );
+                return;
+            }
 
-        let handle = tokio::spawn(async move {
-            let mut sse_parser = SseParser::new();
-            let body = response.text().await.unwrap_or_default();
+            let delay = end
+                .retry
+                .map_or(DEFAULT_RESUME_DELAY, Duration::from_millis);
+            tokio::time::sleep(delay).await;
+            tracing::debug!("Resuming SSE stream after event {}", resumption_token);
+            response = loop {
+                match self.open_sse(Some(resumption_token.clone())).await {
+                    Ok(Some(response)) => break response,
+                    Ok(None) => return,
+                    Err(e) if attempts < MAX_RESUME_ATTEMPTS => {
+                        tracing::warn!("Resuming SSE stream failed: {}", e);
+                        attempts += 1;
+                        tokio::time::sleep(delay).await;
+                    },
+                    Err(e) => {
+                        tracing::warn!("Giving up resuming SSE stream: {}", e);
+                        return;
+                    },
+                }
+            };
+        }
+    }
 
-            // For now, parse the whole body - later we can stream with hyper Body
-            let events = sse_parser.feed(&body);
-            for event in events {
+    /// Deliver the events of an SSE stream as they arrive, until it ends, keeping
+    /// the ID of the last one in `last_event_id`.
+    async fn read_events(
+        &self,
+        mut response: Response,
+        last_event_id: &mut Option<String>,
+    ) -> StreamEnd {
+        let on_resumption = self.config.read().on_resumption_token.clone();
+        let mut sse_parser = SseParser::new();
+        let mut end = StreamEnd::default();
+        // Bytes of a UTF-8 character split across chunks
+        let mut partial = Vec::new();
+        loop {
+            let chunk = match response.chunk().await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => return end,
+                Err(e) => {
+                    tracing::debug!("SSE stream interrupted: {}", e);
+                    end.interrupted = true;
+                    return end;
+                },
+            };
+            partial.extend_from_slice(&chunk);
+            let valid = match std::str::from_utf8(&partial) {
+                Ok(text) => text.len(),
+                Err(e) if e.error_len().is_none() => e.valid_up_to(),
+                Err(_) => {
+                    tracing::warn!("SSE stream is not valid UTF-8");
+                    return end;
+                },
+            };
+            let text = String::from_utf8(partial.drain(..valid).collect()).unwrap_or_default();
+
+            for event in sse_parser.feed(&text) {
+                end.received = true;
+                if event.retry.is_some() {
+                    end.retry = event.retry;
+                }
                 // Update last event ID and notify callback
                 if let Some(id) = &event.id {
-                    *last_event_id.write() = Some(id.clone());
+                    last_event_id.clone_from(&event.id);
+                    *self.last_event_id.write() = Some(id.clone());
                     if let Some(callback) = &on_resumption {
                         callback(id.clone());
                     }
@@ -249,15 +393,25 @@ impl StreamableHttpTransport {
 
                 // Only process "message" events or no event type
                 if event.event.as_deref() == Some("message") || event.event.is_none() {
-                    if let Ok(msg) = serde_json::from_str::<TransportMessage>(&event.data) {
-                        let _ = sender.send(msg);
+                    match self.parse_messages(event.data.as_bytes()) {
+                        Ok(messages) => {
+                            for message in messages {
+                                let _ = self.sender.send(message);
+                            }
+                        },
+                        Err(e) => tracing::warn!("Skipping invalid SSE event: {}", e),
                     }
                 }
             }
-        });
+        }
+    }
 
-        *self.abort_handle.write() = Some(handle);
-        Ok(())
+    /// Parse a message or batch of messages within the maximum nesting depth.
+    fn parse_messages(&self, body: &[u8]) -> Result<Vec<TransportMessage>> {
+        crate::shared::stdio::StdioTransport::parse_messages_with_max_depth(
+            body,
+            self.max_json_depth.load(Ordering::Relaxed),
+        )
     }
 
     async fn build_request(&self, method: reqwest::Method, url: Url) -> Result<RequestBuilder> {
@@ -282,6 +436,8 @@ impl StreamableHttpTransport {
         if let Some(auth_provider) = auth_provider {
             let token = auth_provider.get_access_token().await?;
             builder = builder.bearer_auth(token);
+        } else if let Some(token) = self.access_token.read().as_ref() {
+            builder = builder.bearer_auth(token);
         }
 
         // Add session ID header if we have one
@@ -328,13 +484,33 @@ impl StreamableHttpTransport {
 
         let body = serde_json::to_string(&message)
             .map_err(|e| Error::Transport(TransportError::Serialization(e.to_string())))?;
+        self.post(
+            body,
+            matches!(message, TransportMessage::Notification { .. }),
+        )
+        .await
+    }
 
-        let url = self.config.read().url.clone();
+    /// POST a serialized message or batch and route the response.
+    ///
+    /// `notifications_only` is set when the body holds no requests, so the server
+    /// acknowledges it with 202 Accepted.
+    async fn post(&mut self, body: String, notifications_only: bool) -> Result<()> {
+        let (url, accept) = {
+            let config = self.config.read();
+            (
+                config.url.clone(),
+                config
+                    .accept
+                    .clone()
+                    .unwrap_or_else(|| ACCEPT_STREAMABLE.to_string()),
+            )
+        };
         let builder = self.build_request(reqwest::Method::POST, url).await?;
 
         let response = builder
             .header(CONTENT_TYPE, APPLICATION_JSON)
-            .header(ACCEPT, ACCEPT_STREAMABLE)
+            .header(ACCEPT, accept)
             .body(body)
             .send()
             .await
@@ -347,12 +523,19 @@ impl StreamableHttpTransport {
             // Special handling for 202 Accepted (notification acknowledged)
             if response.status().as_u16() == 202 {
                 // For initialization messages, try to start SSE stream
-                if matches!(message, TransportMessage::Notification { .. }) {
+                if notifications_only {
                     // Try to start GET SSE (tolerate 405)
                     let _ = self.start_sse(None).await;
                 }
                 return Ok(());
             }
+            // Rejected credentials are told apart, so that clients can refresh them
+            if response.status().as_u16() == 401 {
+                return Err(Error::authentication(format!(
+                    "Request failed with status: {}",
+                    response.status()
+                )));
+            }
 
             return Err(Error::Transport(TransportError::Request(format!(
                 "Request failed with status: {}",
@@ -366,58 +549,24 @@ impl StreamableHttpTransport {
             .and_then(|v| v.to_str().ok())
             .unwrap_or("");
 
-        if content_type.contains(APPLICATION_JSON) {
+        if is_json_content_type(content_type) {
             // JSON response (single or batch)
             let response_bytes = response
                 .bytes()
                 .await
                 .map_err(|e| Error::Transport(TransportError::Request(e.to_string())))?;
 
-            // Try to parse as array first (batch response)
-            if let Ok(batch) = serde_json::from_slice::<Vec<TransportMessage>>(&response_bytes) {
-                for msg in batch {
-                    self.sender
-                        .send(msg)
-                        .map_err(|e| Error::Transport(TransportError::Send(e.to_string())))?;
-                }
-            } else {
-                // Single message
-                let message = serde_json::from_slice(&response_bytes).map_err(|e| {
-                    Error::Transport(TransportError::Deserialization(e.to_string()))
-                })?;
+            for message in self.parse_messages(&response_bytes)? {
                 self.sender
                     .send(message)
                     .map_err(|e| Error::Transport(TransportError::Send(e.to_string())))?;
             }
         } else if content_type.contains(TEXT_EVENT_STREAM) {
             // SSE stream response - handle streaming
-            let sender = self.sender.clone();
-            let on_resumption = self.config.read().on_resumption_token.clone();
-            let last_event_id = self.last_event_id.clone();
-
-            tokio::spawn(async move {
-                let mut sse_parser = SseParser::new();
-                let body = response.text().await.unwrap_or_default();
-
-                // Parse the SSE body
-                let events = sse_parser.feed(&body);
-                for event in events {
-                    // Update last event ID and notify callback
-                    if let Some(id) = &event.id {
-                        *last_event_id.write() = Some(id.clone());
-                        if let Some(callback) = &on_resumption {
-                            callback(id.clone());
-                        }
-                    }
-
-                    // Only process "message" events
-                    if event.event.as_deref() == Some("message") || event.event.is_none() {
-                        if let Ok(msg) = serde_json::from_str::<TransportMessage>(&event.data) {
-                            let _ = sender.send(msg);
-                        }
-                    }
-                }
-            });
+            let handle = tokio::spawn(self.clone().stream_events(response, None));
+            let mut post_streams = self.post_streams.lock();
+            post_streams.retain(|stream| !stream.is_finished());
+            post_streams.push(handle);
         } else if response.status().as_u16() == 202 {
             // 202 Accepted with no body is valid
             return Ok(());
@@ -439,6 +588,15 @@ impl Transport for StreamableHttpTransport {
             .await
     }
 
+    async fn send_batch(&mut self, messages: Vec<TransportMessage>) -> Result<()> {
+        let notifications_only = messages
+            .iter()
+            .all(|message| matches!(message, TransportMessage::Notification { .. }));
+        let body = serde_json::to_string(&messages)
+            .map_err(|e| Error::Transport(TransportError::Serialization(e.to_string())))?;
+        self.post(body, notifications_only).await
+    }
+
     async fn receive(&mut self) -> Result<TransportMessage> {
         // Receive from channel - this will block until a message is available
         let mut receiver = self.receiver.lock().await;
@@ -454,6 +612,9 @@ impl Transport for StreamableHttpTransport {
         if let Some(handle) = handle {
             handle.abort();
         }
+        for stream in self.post_streams.lock().drain(..) {
+            stream.abort();
+        }
 
         // Optionally send a DELETE request to terminate the session
         if let Some(_session_id) = self.session_id() {
@@ -481,6 +642,25 @@ impl Transport for StreamableHttpTransport {
         // we can make requests. There's no persistent connection.
         true
     }
+
+    fn set_access_token(&mut self, token: &str) {
+        *self.access_token.write() = Some(token.to_string());
+    }
+
+    fn set_max_json_depth(&mut self, depth: usize) {
+        self.max_json_depth.store(depth, Ordering::Relaxed);
+    }
+}
+
+/// How an SSE stream ended.
+#[derive(Debug, Default)]
+struct StreamEnd {
+    /// Whether the connection broke before the server ended the stream
+    interrupted: bool,
+    /// Whether any event was received
+    received: bool,
+    /// Reconnection time the server set, in milliseconds
+    retry: Option<u64>,
 }
 
 /// A trait for providing authentication tokens.
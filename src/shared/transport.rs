@@ -4,6 +4,7 @@
 //! implementations must satisfy.
 
 use crate::error::Result;
+use crate::shared::protocol::Utf8Mode;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
@@ -141,22 +142,17 @@ pub struct MessageMetadata {
 /// assert_eq!(messages[1].0, "msg3"); // Normal priority second
 /// assert_eq!(messages[2].0, "msg1"); // Low priority last
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub enum MessagePriority {
     /// Low priority
     Low,
     /// Normal priority (default)
+    #[default]
     Normal,
     /// High priority
     High,
 }
 
-impl Default for MessagePriority {
-    fn default() -> Self {
-        Self::Normal
-    }
-}
-
 /// Core transport trait for MCP communication.
 ///
 /// All transport implementations (stdio, WebSocket, HTTP) must implement
@@ -202,10 +198,25 @@ pub trait Transport: Send + Sync + Debug {
     /// is sent atomically.
     async fn send(&mut self, message: TransportMessage) -> Result<()>;
 
+    /// Send several messages as one JSON-RPC batch.
+    ///
+    /// Only used when the peer accepts batches. Default implementation sends the
+    /// messages one at a time, for transports without a batch framing.
+    async fn send_batch(&mut self, messages: Vec<TransportMessage>) -> Result<()> {
+        for message in messages {
+            self.send(message).await?;
+        }
+        Ok(())
+    }
+
     /// Receive a message from the transport.
     ///
     /// This method should block until a complete message is available.
     /// It should handle any necessary buffering and framing internally.
+    ///
+    /// It should also be cancel safe: a [`Client`](crate::Client) or
+    /// [`Server`](crate::Server) drops a pending `receive` to send a message, and a
+    /// message partly read by then must be returned by the next call rather than lost.
     async fn receive(&mut self) -> Result<TransportMessage>;
 
     /// Close the transport.
@@ -225,6 +236,79 @@ pub trait Transport: Send + Sync + Debug {
     fn transport_type(&self) -> &'static str {
         "unknown"
     }
+
+    /// Set how invalid UTF-8 in inbound frames is handled.
+    ///
+    /// Called by the client with [`ProtocolOptions::utf8`](crate::shared::ProtocolOptions::utf8),
+    /// and by the server with the mode set by
+    /// [`ServerBuilder::utf8`](crate::ServerBuilder::utf8).
+    /// Default implementation does nothing, for transports whose frames are
+    /// already decoded as text.
+    fn set_utf8_mode(&mut self, _mode: Utf8Mode) {}
+
+    /// Set the maximum nesting depth of inbound JSON messages.
+    ///
+    /// Called by the client with
+    /// [`ProtocolOptions::max_json_depth`](crate::shared::ProtocolOptions::max_json_depth),
+    /// and by the server with the depth set by
+    /// [`ServerBuilder::max_json_depth`](crate::ServerBuilder::max_json_depth).
+    /// Default implementation does nothing, for transports that keep
+    /// [`DEFAULT_MAX_JSON_DEPTH`](crate::shared::DEFAULT_MAX_JSON_DEPTH).
+    fn set_max_json_depth(&mut self, _depth: usize) {}
+
+    /// Set the OAuth access token sent with later messages.
+    ///
+    /// Called by the client before each request when it has a
+    /// [`TokenStore`](crate::client::auth::TokenStore), see
+    /// [`ClientBuilder::with_oauth`](crate::ClientBuilder::with_oauth).
+    /// Default implementation does nothing, for transports without a place to
+    /// carry credentials.
+    fn set_access_token(&mut self, _token: &str) {}
+}
+
+/// A transport chosen at runtime.
+pub type BoxedTransport = Box<dyn Transport>;
+
+/// Boxed transports forward to the inner transport, so `Box<dyn Transport>` can be
+/// used wherever a [`Transport`] is expected, e.g. when the transport is chosen at
+/// runtime.
+#[async_trait]
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    async fn send(&mut self, message: TransportMessage) -> Result<()> {
+        (**self).send(message).await
+    }
+
+    async fn send_batch(&mut self, messages: Vec<TransportMessage>) -> Result<()> {
+        (**self).send_batch(messages).await
+    }
+
+    async fn receive(&mut self) -> Result<TransportMessage> {
+        (**self).receive().await
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        (**self).close().await
+    }
+
+    fn is_connected(&self) -> bool {
+        (**self).is_connected()
+    }
+
+    fn transport_type(&self) -> &'static str {
+        (**self).transport_type()
+    }
+
+    fn set_utf8_mode(&mut self, mode: Utf8Mode) {
+        (**self).set_utf8_mode(mode);
+    }
+
+    fn set_max_json_depth(&mut self, depth: usize) {
+        (**self).set_max_json_depth(depth);
+    }
+
+    fn set_access_token(&mut self, token: &str) {
+        (**self).set_access_token(token);
+    }
 }
 
 /// Options for sending messages.
@@ -5,7 +5,7 @@
 
 #![cfg(target_arch = "wasm32")]
 
-use crate::error::{Error, Result};
+use crate::error::{Error, Result, TransportError};
 use crate::shared::transport::{Transport, TransportMessage};
 use async_trait::async_trait;
 use futures::channel::mpsc;
@@ -15,7 +15,7 @@ use std::cell::RefCell;
 use std::rc::Rc;
 use wasm_bindgen::closure::Closure;
 use wasm_bindgen::JsCast;
-use web_sys::{ErrorEvent, MessageEvent, WebSocket};
+use web_sys::{CloseEvent, ErrorEvent, MessageEvent, WebSocket};
 
 /// WebSocket transport for WASM environments.
 ///
@@ -35,12 +35,26 @@ pub struct WasmWebSocketTransport {
     tx: mpsc::UnboundedSender<TransportMessage>,
     _on_message: Closure<dyn FnMut(MessageEvent)>,
     _on_error: Closure<dyn FnMut(ErrorEvent)>,
-    _on_close: Closure<dyn FnMut()>,
+    _on_close: Closure<dyn FnMut(CloseEvent)>,
 }
 
+/// Close code and reason of the last close event.
+type CloseInfo = Rc<RefCell<Option<(u16, String)>>>;
+
+/// How long to wait for the connection to open.
+const CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
 impl WasmWebSocketTransport {
     /// Connect to a WebSocket server.
     ///
+    /// # Errors
+    ///
+    /// Returns [`TransportError::ClosedWithCode`] with the close code and reason if
+    /// the server closes the socket before it opens, and
+    /// [`TransportError::ConnectTimeout`] if it does not open in time. Browsers do
+    /// not expose DNS, TLS or HTTP details to scripts; a failed connection is
+    /// reported with close code 1006.
+    ///
     /// # Examples
     ///
     /// ```rust,ignore
@@ -88,12 +102,16 @@ impl WasmWebSocketTransport {
         }) as Box<dyn FnMut(ErrorEvent)>);
 
         // Setup close handler
+        let close_info = CloseInfo::default();
         let on_close = {
             let tx = tx_clone;
-            Closure::wrap(Box::new(move || {
-                web_sys::console::log_1(&"WebSocket closed".into());
-                // Could send a close notification through the channel
-            }) as Box<dyn FnMut()>)
+            let close_info = close_info.clone();
+            Closure::wrap(Box::new(move |e: CloseEvent| {
+                web_sys::console::log_1(
+                    &format!("WebSocket closed with code {}: {}", e.code(), e.reason()).into(),
+                );
+                *close_info.borrow_mut() = Some((e.code(), e.reason()));
+            }) as Box<dyn FnMut(CloseEvent)>)
         };
 
         // Register event handlers
@@ -102,7 +120,7 @@ impl WasmWebSocketTransport {
         ws.set_onclose(Some(on_close.as_ref().unchecked_ref()));
 
         // Wait for connection to open
-        wait_for_open(&ws).await?;
+        wait_for_open(&ws, &close_info).await?;
 
         Ok(Self {
             ws,
@@ -156,31 +174,36 @@ impl Transport for WasmWebSocketTransport {
 }
 
 /// Wait for the WebSocket to open
-async fn wait_for_open(ws: &WebSocket) -> Result<()> {
-    let mut attempts = 0;
-    const MAX_ATTEMPTS: u32 = 100; // 10 seconds with 100ms intervals
+async fn wait_for_open(ws: &WebSocket, close_info: &CloseInfo) -> Result<()> {
+    const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+    let mut waited = std::time::Duration::ZERO;
 
     while ws.ready_state() == WebSocket::CONNECTING {
-        if attempts >= MAX_ATTEMPTS {
-            return Err(Error::TransportError(
-                "WebSocket connection timeout".to_string(),
-            ));
+        if waited >= CONNECT_TIMEOUT {
+            return Err(Error::Transport(TransportError::ConnectTimeout(
+                CONNECT_TIMEOUT,
+            )));
         }
 
-        // Sleep for 100ms
-        crate::shared::runtime::sleep(std::time::Duration::from_millis(100)).await;
-        attempts += 1;
+        crate::shared::runtime::sleep(POLL_INTERVAL).await;
+        waited += POLL_INTERVAL;
     }
 
     if ws.ready_state() != WebSocket::OPEN {
-        return Err(Error::TransportError(
-            "WebSocket failed to connect".to_string(),
-        ));
+        return Err(Error::Transport(closed_error(close_info.borrow().clone())));
     }
 
     Ok(())
 }
 
+/// Error for a socket closed before it opened, from its close event if one arrived.
+fn closed_error(close_info: Option<(u16, String)>) -> TransportError {
+    match close_info {
+        Some((code, reason)) => TransportError::ClosedWithCode { code, reason },
+        None => TransportError::ConnectionClosed,
+    }
+}
+
 /// Parse a JSON value into a TransportMessage
 fn parse_transport_message(value: Value) -> Result<TransportMessage> {
     // Check if it's a request
@@ -255,4 +278,16 @@ mod tests {
         assert_eq!(config.max_reconnect_attempts, 5);
         assert_eq!(config.reconnect_delay_ms, 1000);
     }
+
+    #[wasm_bindgen_test]
+    fn test_closed_error_carries_close_code() {
+        assert!(matches!(
+            closed_error(Some((1008, "policy violation".to_string()))),
+            TransportError::ClosedWithCode { code: 1008, ref reason } if reason == "policy violation"
+        ));
+        assert!(matches!(
+            closed_error(None),
+            TransportError::ConnectionClosed
+        ));
+    }
 }
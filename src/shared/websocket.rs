@@ -1,21 +1,63 @@
 //! WebSocket transport implementation for MCP.
+//!
+//! With [`WebSocketConfig::auto_reconnect`], a connection lost after it was
+//! established is re-established with exponential backoff. Requests sent on it and
+//! not answered yet are then sent again on the new connection, after repeating the
+//! session's `initialize` handshake, so that the client gets their responses as if
+//! nothing happened. Only requests that are safe to run twice are replayed by
+//! default, see [`is_idempotent`]; others, rejected by [`WebSocketConfig::replayable`],
+//! are failed with an error response instead.
 
-use crate::error::Result;
-use crate::shared::{Transport, TransportMessage};
+use crate::error::{Error, ErrorCode, Result, TransportError};
+use crate::shared::reconnect::{ReconnectConfig, ReconnectManager};
+use crate::shared::{Transport, TransportMessage, DEFAULT_MAX_JSON_DEPTH};
+use crate::types::jsonrpc::JSONRPCError;
+use crate::types::{
+    ClientNotification, ClientRequest, JSONRPCResponse, Notification, Request, RequestId,
+};
 use async_trait::async_trait;
+use futures::future::BoxFuture;
 use futures::{SinkExt, StreamExt};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc;
+use tokio::sync::oneshot;
 use tokio::sync::Mutex as AsyncMutex;
-use tokio::time::{interval, sleep};
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+use tokio::sync::Notify;
+use tokio::time::sleep;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::{protocol::Message, Error as WsError};
 use tracing::{debug, error, info, warn};
 use url::Url;
 
+/// Decides whether an unanswered request may be sent again on a new connection.
+pub type ReplayPredicate = Arc<dyn Fn(&Request) -> bool + Send + Sync>;
+
+/// Whether running `request` twice has the same effect as running it once.
+///
+/// This is the default [`WebSocketConfig::replayable`]: it accepts `initialize`,
+/// `ping`, `resources/read` and the list requests, and rejects requests with side
+/// effects such as `tools/call`.
+pub fn is_idempotent(request: &Request) -> bool {
+    let Request::Client(request) = request else {
+        return false;
+    };
+    matches!(
+        **request,
+        ClientRequest::Initialize(_)
+            | ClientRequest::ListTools(_)
+            | ClientRequest::ListPrompts(_)
+            | ClientRequest::ListResources(_)
+            | ClientRequest::ListResourceTemplates(_)
+            | ClientRequest::ReadResource(_)
+            | ClientRequest::Ping
+    )
+}
+
 /// WebSocket transport configuration.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct WebSocketConfig {
     /// WebSocket URL to connect to
     pub url: Url,
@@ -27,10 +69,30 @@ pub struct WebSocketConfig {
     pub max_reconnect_delay: Duration,
     /// Maximum number of reconnection attempts (None = infinite)
     pub max_reconnect_attempts: Option<u32>,
-    /// Ping interval for keepalive
-    pub ping_interval: Option<Duration>,
+    /// Time without frames from the peer after which a WebSocket Ping is sent to
+    /// keep the connection alive and detect dead ones.
+    ///
+    /// These are protocol-level control frames, separate from MCP `ping` requests.
+    /// Any frame received restarts the interval, so a busy connection is not pinged.
+    /// If nothing arrives within [`ws_pong_timeout`](Self::ws_pong_timeout) of a Ping,
+    /// the connection is treated as lost. Pings from the peer are always answered,
+    /// whether or not this is set.
+    pub ws_ping_interval: Option<Duration>,
+    /// Time allowed for the peer to answer a Ping, `None` to allow one ping interval.
+    pub ws_pong_timeout: Option<Duration>,
     /// Request timeout
     pub request_timeout: Duration,
+    /// Time allowed for each connection attempt, including DNS lookup and handshake
+    pub connect_timeout: Duration,
+    /// Backoff for re-establishing a lost connection and replaying unanswered
+    /// requests, see [`auto_reconnect`](Self::auto_reconnect). `None` by default.
+    pub reconnect: Option<ReconnectConfig>,
+    /// Whether an unanswered request may be replayed after reconnecting.
+    ///
+    /// Requests it rejects fail with an error response once the connection is lost.
+    /// Defaults to [`is_idempotent`]; use [`replay_if`](Self::replay_if) to opt other
+    /// requests in.
+    pub replayable: ReplayPredicate,
 }
 
 impl Default for WebSocketConfig {
@@ -41,18 +103,114 @@ impl Default for WebSocketConfig {
             reconnect_delay: Duration::from_secs(1),
             max_reconnect_delay: Duration::from_secs(60),
             max_reconnect_attempts: None,
-            ping_interval: Some(Duration::from_secs(30)),
+            ws_ping_interval: Some(Duration::from_secs(30)),
+            ws_pong_timeout: None,
             request_timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(10),
+            reconnect: None,
+            replayable: Arc::new(is_idempotent),
         }
     }
 }
 
+impl std::fmt::Debug for WebSocketConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebSocketConfig")
+            .field("url", &self.url)
+            .field("auto_reconnect", &self.auto_reconnect)
+            .field("reconnect_delay", &self.reconnect_delay)
+            .field("max_reconnect_delay", &self.max_reconnect_delay)
+            .field("max_reconnect_attempts", &self.max_reconnect_attempts)
+            .field("ws_ping_interval", &self.ws_ping_interval)
+            .field("ws_pong_timeout", &self.ws_pong_timeout)
+            .field("request_timeout", &self.request_timeout)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("reconnect", &self.reconnect)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WebSocketConfig {
+    /// Re-establish a lost connection with the backoff of `config`, replaying the
+    /// requests left unanswered.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::shared::ReconnectConfig;
+    /// use pmcp::types::{ClientRequest, Request};
+    /// use pmcp::WebSocketConfig;
+    ///
+    /// let config = WebSocketConfig {
+    ///     url: "ws://localhost:3000/mcp".parse().unwrap(),
+    ///     ..Default::default()
+    /// }
+    /// .auto_reconnect(ReconnectConfig::default())
+    /// // Tool calls may have side effects, so they are never sent twice
+    /// .replay_if(|request| {
+    ///     !matches!(request, Request::Client(r) if matches!(**r, ClientRequest::CallTool(_)))
+    /// });
+    /// assert!(config.reconnect.is_some());
+    /// ```
+    pub fn auto_reconnect(mut self, config: ReconnectConfig) -> Self {
+        self.auto_reconnect = true;
+        self.reconnect = Some(config);
+        self
+    }
+
+    /// Ping the peer after `interval` without traffic, see
+    /// [`ws_ping_interval`](Self::ws_ping_interval).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::WebSocketConfig;
+    /// use std::time::Duration;
+    ///
+    /// // Stay below the idle timeout of proxies on the way
+    /// let config = WebSocketConfig::default()
+    ///     .keepalive_interval(Duration::from_secs(20))
+    ///     .keepalive_timeout(Duration::from_secs(5));
+    /// assert_eq!(config.ws_ping_interval, Some(Duration::from_secs(20)));
+    /// ```
+    pub fn keepalive_interval(mut self, interval: Duration) -> Self {
+        self.ws_ping_interval = Some(interval);
+        self
+    }
+
+    /// Treat the connection as lost when a keepalive Ping is not answered within
+    /// `timeout`, see [`ws_pong_timeout`](Self::ws_pong_timeout).
+    pub fn keepalive_timeout(mut self, timeout: Duration) -> Self {
+        self.ws_pong_timeout = Some(timeout);
+        self
+    }
+
+    /// Only replay the unanswered requests `predicate` accepts, see
+    /// [`replayable`](Self::replayable).
+    pub fn replay_if(
+        mut self,
+        predicate: impl Fn(&Request) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.replayable = Arc::new(predicate);
+        self
+    }
+}
+
 /// WebSocket transport implementation.
 pub struct WebSocketTransport {
     config: WebSocketConfig,
     state: Arc<RwLock<ConnectionState>>,
-    message_tx: mpsc::Sender<TransportMessage>,
-    message_rx: Arc<AsyncMutex<mpsc::Receiver<TransportMessage>>>,
+    /// Messages read from the socket, and the error ending a lost connection
+    message_tx: mpsc::Sender<Result<TransportMessage>>,
+    message_rx: Arc<AsyncMutex<mpsc::Receiver<Result<TransportMessage>>>>,
+    /// Frames to write to the socket of the current connection
+    writer: Arc<RwLock<Option<mpsc::Sender<Message>>>>,
+    /// Requests to replay on a new connection, if reconnecting is enabled
+    replay: Option<Arc<Replay>>,
+    /// Set once closed, so that the connection going away is not reconnected
+    closed: Arc<AtomicBool>,
+    /// Maximum nesting depth of inbound messages
+    max_json_depth: Arc<AtomicUsize>,
 }
 
 #[derive(Debug)]
@@ -76,11 +234,20 @@ impl WebSocketTransport {
     /// Create a new WebSocket transport with the given configuration.
     pub fn new(config: WebSocketConfig) -> Self {
         let (tx, rx) = mpsc::channel(100);
+        let replay = config
+            .reconnect
+            .clone()
+            .filter(|_| config.auto_reconnect)
+            .map(|reconnect| Arc::new(Replay::new(reconnect)));
         Self {
             config,
             state: Arc::new(RwLock::new(ConnectionState::Disconnected)),
             message_tx: tx,
             message_rx: Arc::new(AsyncMutex::new(rx)),
+            writer: Arc::new(RwLock::new(None)),
+            replay,
+            closed: Arc::new(AtomicBool::new(false)),
+            max_json_depth: Arc::new(AtomicUsize::new(DEFAULT_MAX_JSON_DEPTH)),
         }
     }
 
@@ -93,16 +260,29 @@ impl WebSocketTransport {
     }
 
     /// Connect to the WebSocket server.
+    ///
+    /// # Errors
+    ///
+    /// Once the configured attempts are used up, returns the error of the last one,
+    /// telling why it failed:
+    /// - [`TransportError::Dns`] if the host name could not be resolved
+    /// - [`TransportError::Tls`] if TLS negotiation failed
+    /// - [`TransportError::HandshakeRejected`] if the server refused the upgrade,
+    ///   with its HTTP status
+    /// - [`TransportError::ConnectTimeout`] if an attempt took longer than
+    ///   [`WebSocketConfig::connect_timeout`]
     pub async fn connect(&self) -> Result<()> {
+        self.closed.store(false, Ordering::SeqCst);
         self.connect_with_retry().await
     }
 
     async fn connect_with_retry(&self) -> Result<()> {
+        let connection = self.connection();
         let mut attempts = 0;
         let mut delay = self.config.reconnect_delay;
 
         loop {
-            match self.connect_once().await {
+            match connection.connect_once().await {
                 Ok(()) => return Ok(()),
                 Err(e) => {
                     attempts += 1;
@@ -125,6 +305,33 @@ impl WebSocketTransport {
         }
     }
 
+    /// Handle on the shared connection state, for the tasks serving a connection.
+    fn connection(&self) -> Connection {
+        Connection {
+            config: self.config.clone(),
+            state: self.state.clone(),
+            message_tx: self.message_tx.clone(),
+            writer: self.writer.clone(),
+            replay: self.replay.clone(),
+            closed: self.closed.clone(),
+            max_json_depth: self.max_json_depth.clone(),
+        }
+    }
+}
+
+/// The parts of a [`WebSocketTransport`] shared with its background tasks.
+#[derive(Clone)]
+struct Connection {
+    config: WebSocketConfig,
+    state: Arc<RwLock<ConnectionState>>,
+    message_tx: mpsc::Sender<Result<TransportMessage>>,
+    writer: Arc<RwLock<Option<mpsc::Sender<Message>>>>,
+    replay: Option<Arc<Replay>>,
+    closed: Arc<AtomicBool>,
+    max_json_depth: Arc<AtomicUsize>,
+}
+
+impl Connection {
     async fn connect_once(&self) -> Result<()> {
         {
             let mut state = self.state.write();
@@ -133,11 +340,38 @@ impl WebSocketTransport {
 
         info!("Connecting to WebSocket at {}", self.config.url);
 
-        let (ws_stream, _) = connect_async(self.config.url.as_str())
+        let timeout = self.config.connect_timeout;
+        let attempt = async {
+            resolve_host(&self.config.url).await?;
+            connect_async(self.config.url.as_str())
+                .await
+                .map_err(connect_error)
+        };
+        let connected = tokio::time::timeout(timeout, attempt)
             .await
-            .map_err(crate::error::TransportError::from)?;
+            .unwrap_or(Err(TransportError::ConnectTimeout(timeout)));
+        let (ws_stream, _) = match connected {
+            Ok(connected) => connected,
+            Err(e) => {
+                *self.state.write() = ConnectionState::Disconnected;
+                return Err(e.into());
+            },
+        };
         let (sink, stream) = ws_stream.split();
 
+        // Spawn writer task
+        let (write_tx, mut write_rx) = mpsc::channel::<Message>(100);
+        let _writer_handle = tokio::spawn(async move {
+            let mut sink = sink;
+            while let Some(msg) = write_rx.recv().await {
+                if let Err(e) = sink.send(msg).await {
+                    error!("Failed to send WebSocket message: {}", e);
+                    break;
+                }
+            }
+        });
+
+        *self.writer.write() = Some(write_tx.clone());
         {
             let mut state = self.state.write();
             *state = ConnectionState::Connected;
@@ -145,129 +379,485 @@ impl WebSocketTransport {
 
         info!("WebSocket connected");
 
-        // Spawn reader task
-        let message_tx = self.message_tx.clone();
+        // Spawn reader task, which handles the connection going away once it is done
+        // or the keepalive gives up on it
+        let activity = Arc::new(Notify::new());
+        let dead = Arc::new(Notify::new());
+        let reader = read_frames(
+            stream,
+            self.message_tx.clone(),
+            write_tx.clone(),
+            activity.clone(),
+            self.replay.clone(),
+            self.max_json_depth.clone(),
+        );
+        let connection = self.clone();
+        let reader_dead = dead.clone();
         let _reader_handle = tokio::spawn(async move {
-            let mut stream = stream;
-            while let Some(result) = stream.next().await {
-                match result {
-                    Ok(Message::Text(text)) => {
-                        match serde_json::from_str::<serde_json::Value>(&text) {
-                            Ok(_json) => {
-                                // Parse the JSON value into a TransportMessage
-                                match crate::shared::stdio::StdioTransport::parse_message(
-                                    text.as_bytes(),
-                                ) {
-                                    Ok(msg) => {
-                                        if message_tx.send(msg).await.is_err() {
-                                            error!("Failed to send message to channel");
-                                            break;
-                                        }
-                                    },
-                                    Err(e) => {
-                                        error!("Failed to parse message: {}", e);
-                                    },
-                                }
-                            },
-                            Err(e) => {
-                                error!("Failed to parse WebSocket message: {}", e);
-                            },
-                        }
-                    },
-                    Ok(Message::Close(_)) => {
-                        info!("WebSocket closed by remote");
-                        break;
-                    },
-                    Ok(Message::Ping(data)) => {
-                        debug!("Received ping: {:?}", data);
-                    },
-                    Ok(Message::Pong(_)) => {
-                        debug!("Received pong");
-                    },
-                    Ok(Message::Binary(_)) => {
-                        warn!("Received unexpected binary message");
-                    },
-                    Ok(Message::Frame(_)) => {
-                        warn!("Received unexpected frame message");
-                    },
-                    Err(e) => {
-                        error!("WebSocket error: {}", e);
-                        break;
-                    },
-                }
+            tokio::select! {
+                () = reader => {},
+                () = reader_dead.notified() => {},
             }
+            connection.connection_lost().await;
         });
 
-        // Spawn writer task
-        let (write_tx, mut write_rx) = mpsc::channel::<Message>(100);
-        let _writer_handle = tokio::spawn(async move {
-            let mut sink = sink;
-            while let Some(msg) = write_rx.recv().await {
-                if let Err(e) = sink.send(msg).await {
-                    error!("Failed to send WebSocket message: {}", e);
-                    break;
-                }
+        if let Some(ping_interval) = self.config.ws_ping_interval {
+            let pong_timeout = self.config.ws_pong_timeout.unwrap_or(ping_interval);
+            tokio::spawn(keep_alive(
+                ping_interval,
+                pong_timeout,
+                activity,
+                write_tx,
+                dead,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Handle the socket going away, reconnecting and replaying if enabled.
+    ///
+    /// Boxed since reconnecting spawns the reader task that calls it again.
+    fn connection_lost(self) -> BoxFuture<'static, ()> {
+        Box::pin(async move {
+            *self.writer.write() = None;
+            *self.state.write() = ConnectionState::Disconnected;
+            if self.closed.load(Ordering::SeqCst) {
+                return;
             }
-        });
+            let Some(replay) = self.replay.clone() else {
+                self.surface_closed().await;
+                return;
+            };
 
-        // Spawn ping task if configured
-        if let Some(ping_interval) = self.config.ping_interval {
-            tokio::spawn(async move {
-                let mut ticker = interval(ping_interval);
-                loop {
-                    ticker.tick().await;
-                    if write_tx.send(Message::Ping(vec![].into())).await.is_err() {
-                        break;
+            replay.manager.on_disconnected().await;
+            for id in replay.drop_unreplayable(&self.config.replayable) {
+                self.fail(id, "Connection lost before the request was answered")
+                    .await;
+            }
+
+            match replay.manager.reconnect_with(|| self.connect_once()).await {
+                Ok(()) => {
+                    if let Err(e) = self.replay_requests(&replay).await {
+                        // Anything not yet answered is replayed on the next connection
+                        warn!("Replaying requests after reconnecting failed: {}", e);
                     }
-                }
-            });
-        }
+                },
+                Err(e) => {
+                    error!("Giving up reconnecting: {}", e);
+                    for id in replay.drop_all() {
+                        self.fail(id, "Connection lost and reconnecting failed")
+                            .await;
+                    }
+                    self.surface_closed().await;
+                },
+            }
+        })
+    }
 
-        // Store the writer channel for sending messages
-        // This is a simplified approach - in production you'd want better state management
+    /// Repeat the session's handshake on a new connection, then send the
+    /// unanswered requests again.
+    async fn replay_requests(&self, replay: &Replay) -> Result<()> {
+        let (handshake, requests) = replay.pending();
+        if let Some(handshake) = handshake {
+            debug!("Repeating the initialize handshake");
+            self.write(&handshake.initialize).await?;
+            let timeout = self.config.request_timeout;
+            tokio::time::timeout(timeout, handshake.answered)
+                .await
+                .map_err(|_| {
+                    Error::Timeout(u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX))
+                })?
+                .map_err(|_| Error::Transport(TransportError::ConnectionClosed))?;
+            if handshake.initialized {
+                let initialized = TransportMessage::Notification(Notification::Client(
+                    ClientNotification::Initialized,
+                ));
+                self.write(&initialized).await?;
+            }
+        }
 
+        info!("Replaying {} unanswered requests", requests.len());
+        for request in &requests {
+            self.write(request).await?;
+        }
         Ok(())
     }
-}
 
-#[async_trait]
-impl Transport for WebSocketTransport {
-    async fn send(&mut self, message: TransportMessage) -> Result<()> {
-        let json_bytes = crate::shared::stdio::StdioTransport::serialize_message(&message)?;
+    /// Write a message to the socket of the current connection.
+    async fn write(&self, message: &TransportMessage) -> Result<()> {
+        let json_bytes = crate::shared::stdio::StdioTransport::serialize_message(message)?;
         let json = String::from_utf8(json_bytes).map_err(|e| {
-            crate::error::Error::Transport(crate::error::TransportError::InvalidMessage(format!(
+            Error::Transport(TransportError::InvalidMessage(format!(
                 "Invalid UTF-8: {}",
                 e
             )))
         })?;
 
-        // In a real implementation, we'd need to store the write channel
-        // This is simplified for the example
-        match &*self.state.read() {
-            ConnectionState::Connected => {
-                debug!("Sending WebSocket message: {}", json);
-                Ok(())
+        let writer = match &*self.state.read() {
+            ConnectionState::Connected => self.writer.read().clone(),
+            _ => None,
+        };
+        let writer = writer.ok_or(Error::Transport(TransportError::ConnectionClosed))?;
+        debug!("Sending WebSocket message: {}", json);
+        writer
+            .send(Message::Text(json.into()))
+            .await
+            .map_err(|_| Error::Transport(TransportError::ConnectionClosed))
+    }
+
+    /// Answer request `id` with an error, for a request that will not be replayed.
+    async fn fail(&self, id: RequestId, message: &str) {
+        warn!("Failing request {}: {}", id, message);
+        let response = JSONRPCResponse::error(
+            id,
+            JSONRPCError::new(ErrorCode::INTERNAL_ERROR.as_i32(), message),
+        );
+        if self
+            .message_tx
+            .send(Ok(TransportMessage::Response(response)))
+            .await
+            .is_err()
+        {
+            debug!("Transport dropped, not failing request");
+        }
+    }
+
+    /// Let the receiver know the connection is gone for good.
+    async fn surface_closed(&self) {
+        let closed = Err(Error::Transport(TransportError::ConnectionClosed));
+        if self.message_tx.send(closed).await.is_err() {
+            debug!("Transport dropped, not reporting the lost connection");
+        }
+    }
+}
+
+/// Requests to send again on a new connection, see [`WebSocketConfig::auto_reconnect`].
+struct Replay {
+    manager: ReconnectManager,
+    log: Mutex<ReplayLog>,
+}
+
+#[derive(Default)]
+struct ReplayLog {
+    /// Requests sent and not answered yet, oldest first
+    unanswered: Vec<(RequestId, TransportMessage)>,
+    /// The session's initialize request, repeated on a new connection
+    initialize: Option<(RequestId, TransportMessage)>,
+    /// Whether the initialized notification followed it
+    initialized: bool,
+    /// Waiting for the response to a repeated initialize request, which is not
+    /// passed on to the client
+    handshake: Option<(RequestId, oneshot::Sender<()>)>,
+}
+
+/// The initialize handshake to repeat on a new connection.
+struct Handshake {
+    initialize: TransportMessage,
+    initialized: bool,
+    answered: oneshot::Receiver<()>,
+}
+
+impl Replay {
+    fn new(config: ReconnectConfig) -> Self {
+        Self {
+            manager: ReconnectManager::new(config),
+            log: Mutex::new(ReplayLog::default()),
+        }
+    }
+
+    /// Record a message about to be sent.
+    fn sending(&self, message: &TransportMessage) {
+        let mut log = self.log.lock();
+        match message {
+            TransportMessage::Request { id, request } => {
+                if matches!(request, Request::Client(r) if matches!(**r, ClientRequest::Initialize(_)))
+                {
+                    log.initialize = Some((id.clone(), message.clone()));
+                    log.initialized = false;
+                }
+                log.unanswered.push((id.clone(), message.clone()));
             },
-            _ => Err(crate::error::Error::Transport(
-                crate::error::TransportError::ConnectionClosed,
-            )),
+            TransportMessage::Notification(Notification::Client(
+                ClientNotification::Initialized,
+            )) => log.initialized = true,
+            TransportMessage::Notification(Notification::Cancelled(cancelled))
+            | TransportMessage::Notification(Notification::Client(
+                ClientNotification::Cancelled(cancelled),
+            )) => log.unanswered.retain(|(id, _)| *id != cancelled.request_id),
+            _ => {},
         }
     }
 
+    /// Forget request `id`, which failed to send.
+    fn not_sent(&self, id: &RequestId) {
+        self.log.lock().unanswered.retain(|(sent, _)| sent != id);
+    }
+
+    /// Record the response to request `id`, returning whether it is passed on.
+    fn answered(&self, id: &RequestId) -> bool {
+        let mut log = self.log.lock();
+        if log
+            .handshake
+            .as_ref()
+            .is_some_and(|(handshake, _)| handshake == id)
+        {
+            if let Some((_, answered)) = log.handshake.take() {
+                let _ = answered.send(());
+            }
+            return false;
+        }
+        log.unanswered.retain(|(sent, _)| sent != id);
+        true
+    }
+
+    /// Remove the unanswered requests that may not be replayed, returning their IDs.
+    fn drop_unreplayable(&self, replayable: &ReplayPredicate) -> Vec<RequestId> {
+        let mut log = self.log.lock();
+        let mut dropped = Vec::new();
+        log.unanswered.retain(|(id, message)| match message {
+            TransportMessage::Request { request, .. } if !replayable(request) => {
+                dropped.push(id.clone());
+                false
+            },
+            _ => true,
+        });
+        dropped
+    }
+
+    /// Remove every unanswered request, returning their IDs.
+    fn drop_all(&self) -> Vec<RequestId> {
+        let mut log = self.log.lock();
+        log.handshake = None;
+        log.unanswered.drain(..).map(|(id, _)| id).collect()
+    }
+
+    /// The handshake to repeat, unless the initialize request itself is
+    /// unanswered, and the requests to send again.
+    fn pending(&self) -> (Option<Handshake>, Vec<TransportMessage>) {
+        let mut log = self.log.lock();
+        let repeat = log
+            .initialize
+            .clone()
+            .filter(|(id, _)| !log.unanswered.iter().any(|(sent, _)| sent == id));
+        let handshake = repeat.map(|(id, initialize)| {
+            let (tx, answered) = oneshot::channel();
+            log.handshake = Some((id, tx));
+            Handshake {
+                initialize,
+                initialized: log.initialized,
+                answered,
+            }
+        });
+        let requests = log
+            .unanswered
+            .iter()
+            .map(|(_, message)| message.clone())
+            .collect();
+        (handshake, requests)
+    }
+}
+
+/// Resolve the host of `url` ahead of connecting, so a failed lookup is reported
+/// as such rather than as a generic I/O error.
+async fn resolve_host(url: &Url) -> std::result::Result<(), TransportError> {
+    let Some(url::Host::Domain(host)) = url.host() else {
+        return Ok(());
+    };
+    let port = url.port_or_known_default().unwrap_or(80);
+    let dns_error = |message: String| TransportError::Dns {
+        host: host.to_string(),
+        message,
+    };
+    let mut addresses = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|e| dns_error(e.to_string()))?;
+    match addresses.next() {
+        Some(_) => Ok(()),
+        None => Err(dns_error("no addresses found".to_string())),
+    }
+}
+
+/// Longest handshake response body kept in a [`TransportError::HandshakeRejected`].
+const MAX_REJECTION_REASON: usize = 200;
+
+/// Map a failed connection attempt to the most specific [`TransportError`].
+fn connect_error(error: WsError) -> TransportError {
+    match error {
+        WsError::Http(response) => {
+            let status = response.status();
+            let body = response
+                .body()
+                .as_deref()
+                .map(|body| String::from_utf8_lossy(body).trim().to_string())
+                .unwrap_or_default();
+            let reason = if body.is_empty() {
+                status
+                    .canonical_reason()
+                    .unwrap_or("Unknown status")
+                    .to_string()
+            } else {
+                body.chars().take(MAX_REJECTION_REASON).collect()
+            };
+            TransportError::HandshakeRejected {
+                status: status.as_u16(),
+                reason,
+            }
+        },
+        WsError::Tls(e) => TransportError::Tls(e.to_string()),
+        other => TransportError::WebSocket(other),
+    }
+}
+
+/// Ping through `write_tx` whenever the connection is quiet for `ping_interval`,
+/// waking `dead` if nothing is received within `pong_timeout` of a Ping.
+///
+/// `activity` is notified by the reader for every frame received, which proves the
+/// connection alive as well as a Pong does.
+async fn keep_alive(
+    ping_interval: Duration,
+    pong_timeout: Duration,
+    activity: Arc<Notify>,
+    write_tx: mpsc::Sender<Message>,
+    dead: Arc<Notify>,
+) {
+    loop {
+        if tokio::time::timeout(ping_interval, activity.notified())
+            .await
+            .is_ok()
+        {
+            continue;
+        }
+        if write_tx.send(Message::Ping(vec![].into())).await.is_err() {
+            return;
+        }
+        if tokio::time::timeout(pong_timeout, activity.notified())
+            .await
+            .is_err()
+        {
+            warn!(
+                "No WebSocket pong received within {:?}, treating connection as dead",
+                pong_timeout
+            );
+            dead.notify_one();
+            let _ = write_tx.send(Message::Close(None)).await;
+            return;
+        }
+    }
+}
+
+/// Read frames from the socket until it closes.
+///
+/// Text frames are parsed and forwarded as MCP messages, one per batch element. Ping
+/// frames are answered with a Pong through `write_tx`; neither Pings nor Pongs are
+/// surfaced as MCP messages. Every frame notifies `activity` for the keepalive.
+/// Responses are recorded in `replay`, which keeps those to a repeated handshake
+/// from the client.
+async fn read_frames<S>(
+    mut stream: S,
+    message_tx: mpsc::Sender<Result<TransportMessage>>,
+    write_tx: mpsc::Sender<Message>,
+    activity: Arc<Notify>,
+    replay: Option<Arc<Replay>>,
+    max_json_depth: Arc<AtomicUsize>,
+) where
+    S: futures::Stream<Item = std::result::Result<Message, WsError>> + Unpin,
+{
+    while let Some(result) = stream.next().await {
+        if result.is_ok() {
+            activity.notify_one();
+        }
+        match result {
+            Ok(Message::Text(text)) => {
+                let max_depth = max_json_depth.load(Ordering::Relaxed);
+                match crate::shared::stdio::StdioTransport::parse_messages_with_max_depth(
+                    text.as_bytes(),
+                    max_depth,
+                ) {
+                    Ok(messages) => {
+                        for msg in messages {
+                            if let (TransportMessage::Response(response), Some(replay)) =
+                                (&msg, &replay)
+                            {
+                                if !replay.answered(&response.id) {
+                                    continue;
+                                }
+                            }
+                            if message_tx.send(Ok(msg)).await.is_err() {
+                                error!("Failed to send message to channel");
+                                return;
+                            }
+                        }
+                    },
+                    Err(e) => {
+                        error!("Failed to parse WebSocket message: {}", e);
+                    },
+                }
+            },
+            Ok(Message::Close(_)) => {
+                info!("WebSocket closed by remote");
+                break;
+            },
+            Ok(Message::Ping(data)) => {
+                debug!("Received ping: {:?}", data);
+                if write_tx.send(Message::Pong(data)).await.is_err() {
+                    break;
+                }
+            },
+            Ok(Message::Pong(_)) => {
+                debug!("Received pong");
+            },
+            Ok(Message::Binary(_)) => {
+                warn!("Received unexpected binary message");
+            },
+            Ok(Message::Frame(_)) => {
+                warn!("Received unexpected frame message");
+            },
+            Err(e) => {
+                error!("WebSocket error: {}", e);
+                break;
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for WebSocketTransport {
+    async fn send(&mut self, message: TransportMessage) -> Result<()> {
+        // Recorded first, so that a response arriving right away is matched
+        if let Some(replay) = &self.replay {
+            replay.sending(&message);
+        }
+        let result = self.connection().write(&message).await;
+        if let (Err(_), Some(replay), TransportMessage::Request { id, .. }) =
+            (&result, &self.replay, &message)
+        {
+            replay.not_sent(id);
+        }
+        result
+    }
+
     async fn receive(&mut self) -> Result<TransportMessage> {
         let mut rx = self.message_rx.lock().await;
-        rx.recv().await.ok_or_else(|| {
-            crate::error::Error::Transport(crate::error::TransportError::ConnectionClosed)
+        rx.recv().await.unwrap_or_else(|| {
+            Err(crate::error::Error::Transport(
+                crate::error::TransportError::ConnectionClosed,
+            ))
         })
     }
 
     async fn close(&mut self) -> Result<()> {
+        self.closed.store(true, Ordering::SeqCst);
         {
             let mut state = self.state.write();
             *state = ConnectionState::Closing;
         }
 
         info!("Closing WebSocket connection");
+        let writer = self.writer.write().take();
+        if let Some(writer) = writer {
+            let _ = writer.send(Message::Close(None)).await;
+        }
 
         {
             let mut state = self.state.write();
@@ -280,6 +870,10 @@ impl Transport for WebSocketTransport {
     fn is_connected(&self) -> bool {
         matches!(&*self.state.read(), ConnectionState::Connected)
     }
+
+    fn set_max_json_depth(&mut self, depth: usize) {
+        self.max_json_depth.store(depth, Ordering::Relaxed);
+    }
 }
 
 #[cfg(test)]
@@ -293,7 +887,7 @@ mod tests {
         assert_eq!(config.reconnect_delay, Duration::from_secs(1));
         assert_eq!(config.max_reconnect_delay, Duration::from_secs(60));
         assert!(config.max_reconnect_attempts.is_none());
-        assert_eq!(config.ping_interval, Some(Duration::from_secs(30)));
+        assert_eq!(config.ws_ping_interval, Some(Duration::from_secs(30)));
         assert_eq!(config.request_timeout, Duration::from_secs(30));
         assert_eq!(config.url.as_str(), "ws://localhost:8080/");
     }
@@ -306,14 +900,16 @@ mod tests {
             reconnect_delay: Duration::from_secs(2),
             max_reconnect_delay: Duration::from_secs(120),
             max_reconnect_attempts: Some(5),
-            ping_interval: None,
+            ws_ping_interval: None,
             request_timeout: Duration::from_secs(60),
+            connect_timeout: Duration::from_secs(5),
+            ..Default::default()
         };
         assert_eq!(config.url.as_str(), "wss://example.com:9000/ws");
         assert!(!config.auto_reconnect);
         assert_eq!(config.reconnect_delay, Duration::from_secs(2));
         assert_eq!(config.max_reconnect_attempts, Some(5));
-        assert!(config.ping_interval.is_none());
+        assert!(config.ws_ping_interval.is_none());
     }
 
     #[test]
@@ -436,6 +1032,8 @@ mod tests {
         let mut transport = WebSocketTransport::new(config);
 
         // Set to connected state
+        let (write_tx, mut write_rx) = mpsc::channel(1);
+        *transport.writer.write() = Some(write_tx);
         {
             let mut state = transport.state.write();
             *state = ConnectionState::Connected;
@@ -446,9 +1044,13 @@ mod tests {
             request: Request::Client(Box::new(ClientRequest::Ping)),
         };
 
-        // Should succeed when connected (though simplified implementation just returns Ok)
+        // Should succeed when connected, writing the message as a text frame
         let result = transport.send(message).await;
         assert!(result.is_ok());
+        match write_rx.recv().await {
+            Some(Message::Text(text)) => assert!(text.contains(r#""method":"ping""#), "{}", text),
+            other => panic!("Expected text frame, got {:?}", other),
+        }
     }
 
     #[tokio::test]
@@ -457,12 +1059,10 @@ mod tests {
         let transport = WebSocketTransport::new(config);
 
         // Create a new receiver that's already closed
-        let (_, rx) = mpsc::channel::<TransportMessage>(1);
+        let (_, rx) = mpsc::channel::<Result<TransportMessage>>(1);
         let mut transport = WebSocketTransport {
-            config: transport.config,
-            state: transport.state,
-            message_tx: transport.message_tx,
             message_rx: Arc::new(AsyncMutex::new(rx)),
+            ..transport
         };
 
         // Receive should error with ConnectionClosed
@@ -484,13 +1084,373 @@ mod tests {
         assert_eq!(config.max_reconnect_attempts, Some(3));
     }
 
+    #[tokio::test]
+    async fn test_ping_frame_is_answered_with_pong() {
+        let (message_tx, mut message_rx) = mpsc::channel(10);
+        let (write_tx, mut write_rx) = mpsc::channel(10);
+        let activity = Arc::new(Notify::new());
+        let frames = futures::stream::iter(vec![
+            Ok(Message::Ping(b"hello".to_vec().into())),
+            Ok(Message::Pong(vec![].into())),
+            Ok(Message::Text(
+                r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#.into(),
+            )),
+        ]);
+
+        read_frames(
+            frames,
+            message_tx,
+            write_tx,
+            activity.clone(),
+            None,
+            Arc::new(AtomicUsize::new(DEFAULT_MAX_JSON_DEPTH)),
+        )
+        .await;
+
+        match write_rx.recv().await {
+            Some(Message::Pong(data)) => assert_eq!(&data[..], b"hello"),
+            other => panic!("Expected pong, got {:?}", other),
+        }
+        assert!(write_rx.recv().await.is_none());
+        assert!(futures::FutureExt::now_or_never(activity.notified()).is_some());
+
+        // Only the text frame surfaces as an MCP message
+        assert!(matches!(
+            message_rx.recv().await,
+            Some(Ok(TransportMessage::Request { .. }))
+        ));
+        assert!(message_rx.recv().await.is_none());
+    }
+
     #[test]
     fn test_websocket_config_with_no_ping() {
         let config = WebSocketConfig {
             url: "ws://localhost:8080".parse().unwrap(),
-            ping_interval: None,
+            ws_ping_interval: None,
             ..Default::default()
         };
-        assert!(config.ping_interval.is_none());
+        assert!(config.ws_ping_interval.is_none());
+    }
+
+    fn single_attempt(url: &str, connect_timeout: Duration) -> WebSocketTransport {
+        WebSocketTransport::new(WebSocketConfig {
+            url: url.parse().unwrap(),
+            max_reconnect_attempts: Some(1),
+            connect_timeout,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_handshake_rejection_is_mapped() {
+        use tokio_tungstenite::tungstenite::http::Response;
+
+        let response = Response::builder()
+            .status(401)
+            .body(Some(b"invalid token\n".to_vec()))
+            .unwrap();
+        assert!(matches!(
+            connect_error(WsError::Http(response)),
+            TransportError::HandshakeRejected { status: 401, ref reason } if reason == "invalid token"
+        ));
+
+        // Without a body, the reason phrase explains the status
+        let response = Response::builder().status(404).body(None).unwrap();
+        assert!(matches!(
+            connect_error(WsError::Http(response)),
+            TransportError::HandshakeRejected { status: 404, ref reason } if reason == "Not Found"
+        ));
+
+        assert!(matches!(
+            connect_error(WsError::ConnectionClosed),
+            TransportError::WebSocket(WsError::ConnectionClosed)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_connect_reports_rejected_handshake() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = socket.read(&mut request).await;
+            socket
+                .write_all(b"HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\n\r\n")
+                .await
+                .unwrap();
+        });
+
+        let transport = single_attempt(
+            &format!("ws://127.0.0.1:{}/mcp", port),
+            Duration::from_secs(5),
+        );
+        let err = transport.connect().await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                crate::Error::Transport(TransportError::HandshakeRejected { status: 403, .. })
+            ),
+            "{}",
+            err
+        );
+        assert!(!transport.is_connected());
+    }
+
+    #[tokio::test]
+    async fn test_connect_times_out_on_silent_server() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        // Accept the connection but never answer the handshake
+        let server = tokio::spawn(async move {
+            let (_socket, _) = listener.accept().await.unwrap();
+            std::future::pending::<()>().await;
+        });
+
+        let timeout = Duration::from_millis(100);
+        let transport = single_attempt(&format!("ws://127.0.0.1:{}", port), timeout);
+        let err = transport.connect().await.unwrap_err();
+        assert!(
+            matches!(err, crate::Error::Transport(TransportError::ConnectTimeout(t)) if t == timeout),
+            "{}",
+            err
+        );
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_connect_reports_dns_failure() {
+        // The .invalid top-level domain never resolves
+        let transport = single_attempt("ws://mcp.invalid:8080", Duration::from_secs(10));
+        let err = transport.connect().await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                crate::Error::Transport(TransportError::Dns { ref host, .. }) if host == "mcp.invalid"
+            ),
+            "{}",
+            err
+        );
+    }
+
+    /// Accept a WebSocket connection on `listener`.
+    async fn accept(
+        listener: &tokio::net::TcpListener,
+    ) -> tokio_tungstenite::WebSocketStream<tokio::net::TcpStream> {
+        let (socket, _) = listener.accept().await.unwrap();
+        tokio_tungstenite::accept_async(socket).await.unwrap()
+    }
+
+    /// Read the next JSON-RPC message from a server-side socket.
+    async fn next_message(
+        ws: &mut tokio_tungstenite::WebSocketStream<tokio::net::TcpStream>,
+    ) -> serde_json::Value {
+        loop {
+            match ws.next().await {
+                Some(Ok(Message::Text(text))) => return serde_json::from_str(&text).unwrap(),
+                Some(Ok(_)) => {},
+                other => panic!("Expected a message, got {:?}", other),
+            }
+        }
+    }
+
+    /// Answer an initialize or ping request on a server-side socket.
+    async fn answer(
+        ws: &mut tokio_tungstenite::WebSocketStream<tokio::net::TcpStream>,
+        request: &serde_json::Value,
+    ) {
+        let result = match request["method"].as_str() {
+            Some("initialize") => serde_json::json!({
+                "protocolVersion": crate::LATEST_PROTOCOL_VERSION,
+                "capabilities": {},
+                "serverInfo": {"name": "mock", "version": "1.0.0"}
+            }),
+            _ => serde_json::json!({}),
+        };
+        let response = serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "result": result});
+        ws.send(Message::Text(response.to_string().into()))
+            .await
+            .unwrap();
+    }
+
+    fn reconnecting(port: u16) -> WebSocketConfig {
+        WebSocketConfig {
+            url: format!("ws://127.0.0.1:{}", port).parse().unwrap(),
+            ws_ping_interval: None,
+            ..Default::default()
+        }
+        .auto_reconnect(ReconnectConfig {
+            initial_delay: Duration::from_millis(10),
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_replays_unanswered_request() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            // The first connection handles the handshake, then drops mid-request
+            let mut ws = accept(&listener).await;
+            let initialize = next_message(&mut ws).await;
+            assert_eq!(initialize["method"], "initialize");
+            answer(&mut ws, &initialize).await;
+            assert_eq!(
+                next_message(&mut ws).await["method"],
+                "notifications/initialized"
+            );
+            let ping = next_message(&mut ws).await;
+            assert_eq!(ping["method"], "ping");
+            drop(ws);
+
+            // The second connection sees the handshake again, then the ping
+            let mut ws = accept(&listener).await;
+            let repeated = next_message(&mut ws).await;
+            assert_eq!(repeated["method"], "initialize");
+            answer(&mut ws, &repeated).await;
+            assert_eq!(
+                next_message(&mut ws).await["method"],
+                "notifications/initialized"
+            );
+            let replayed = next_message(&mut ws).await;
+            assert_eq!(replayed, ping);
+            answer(&mut ws, &replayed).await;
+            // Keep the connection open until the client is done
+            while ws.next().await.is_some() {}
+        });
+
+        let transport = WebSocketTransport::new(reconnecting(port));
+        transport.connect().await.unwrap();
+        let mut client = crate::Client::new(transport);
+        client
+            .initialize(crate::ClientCapabilities::default())
+            .await
+            .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), client.ping())
+            .await
+            .expect("ping not answered after reconnecting")
+            .unwrap();
+        drop(client);
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_unreplayable_request_fails_on_disconnect() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let mut ws = accept(&listener).await;
+            next_message(&mut ws).await;
+            drop(ws);
+            // Accept the reconnection, which must not see the request again
+            let mut ws = accept(&listener).await;
+            if let Some(Ok(Message::Text(text))) = ws.next().await {
+                panic!("Unexpected replay of {}", text);
+            }
+        });
+
+        // Tool calls are not replayed by default
+        let mut transport = WebSocketTransport::new(reconnecting(port));
+        transport.connect().await.unwrap();
+        transport
+            .send(TransportMessage::Request {
+                id: RequestId::from(7i64),
+                request: Request::Client(Box::new(ClientRequest::CallTool(
+                    crate::types::CallToolRequest {
+                        name: "delete".to_string(),
+                        arguments: serde_json::json!({}),
+                        meta: None,
+                    },
+                ))),
+            })
+            .await
+            .unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(5), transport.receive())
+            .await
+            .unwrap()
+            .unwrap();
+        match received {
+            TransportMessage::Response(response) => {
+                assert_eq!(response.id, RequestId::from(7i64));
+                assert!(!response.is_success());
+            },
+            other => panic!("Expected an error response, got {:?}", other),
+        }
+        transport.close().await.unwrap();
+        server.abort();
+    }
+
+    fn keepalive(port: u16, interval: Duration) -> WebSocketConfig {
+        WebSocketConfig {
+            url: format!("ws://127.0.0.1:{}", port).parse().unwrap(),
+            auto_reconnect: false,
+            ..Default::default()
+        }
+        .keepalive_interval(interval)
+    }
+
+    #[tokio::test]
+    async fn test_idle_connection_is_pinged_at_keepalive_interval() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            // Reading answers the pings, keeping the connection alive
+            let mut ws = accept(&listener).await;
+            let start = tokio::time::Instant::now();
+            let mut pings = Vec::new();
+            while pings.len() < 3 {
+                match ws.next().await {
+                    Some(Ok(Message::Ping(_))) => pings.push(start.elapsed()),
+                    Some(Ok(_)) => {},
+                    other => panic!("Expected a ping, got {:?}", other),
+                }
+            }
+            pings
+        });
+
+        let mut transport = WebSocketTransport::new(keepalive(port, Duration::from_millis(50)));
+        transport.connect().await.unwrap();
+
+        let pings = tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("idle connection not pinged")
+            .unwrap();
+        for (n, elapsed) in pings.iter().enumerate() {
+            let due = Duration::from_millis(50) * (n as u32 + 1);
+            assert!(*elapsed >= due.saturating_sub(Duration::from_millis(10)));
+        }
+        assert!(transport.is_connected());
+        transport.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_missing_pong_closes_connection() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            // Never reading leaves the pings unanswered
+            let ws = accept(&listener).await;
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            drop(ws);
+        });
+
+        let config =
+            keepalive(port, Duration::from_millis(50)).keepalive_timeout(Duration::from_millis(50));
+        let mut transport = WebSocketTransport::new(config);
+        transport.connect().await.unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(5), transport.receive())
+            .await
+            .expect("dead connection not detected");
+        assert!(matches!(
+            received,
+            Err(Error::Transport(TransportError::ConnectionClosed))
+        ));
+        assert!(!transport.is_connected());
+        server.abort();
     }
 }
@@ -6,6 +6,8 @@
 #[cfg(target_arch = "x86_64")]
 use std::arch::x86_64::*;
 use std::mem;
+#[cfg(target_arch = "x86_64")]
+use std::sync::OnceLock;
 
 /// SIMD-accelerated JSON parsing utilities
 pub mod json {
@@ -59,8 +61,8 @@ pub mod json {
                 _mm256_or_si256(newline_mask, carriage_mask),
             );
 
-            // Extract mask as integer
-            let mask = _mm256_movemask_epi8(ws_mask);
+            // Extract mask as unsigned, so shifting it out below terminates
+            let mask = _mm256_movemask_epi8(ws_mask) as u32;
 
             // Find positions of set bits
             let mut m = mask;
@@ -135,17 +137,20 @@ pub mod json {
     }
 
     /// Processes a 32-byte chunk with SIMD, falling back to scalar for non-ASCII
+    ///
+    /// Returns the offset to continue from, which is past the chunk when its last
+    /// sequence runs into the next one, or `None` if the chunk is invalid.
     #[cfg(target_arch = "x86_64")]
     #[target_feature(enable = "avx2")]
-    unsafe fn process_simd_chunk(input: &[u8], offset: usize) -> bool {
+    unsafe fn process_simd_chunk(input: &[u8], offset: usize) -> Option<usize> {
         let data = _mm256_loadu_si256(input.as_ptr().add(offset) as *const __m256i);
 
-        // Check for ASCII bytes (< 0x80) - fast path
-        let ascii_mask = _mm256_cmpgt_epi8(_mm256_setzero_si256(), data);
-        let is_ascii = _mm256_movemask_epi8(ascii_mask);
+        // Bytes >= 0x80 are negative as i8, so the mask has a bit per non-ASCII byte
+        let non_ascii_mask = _mm256_cmpgt_epi8(_mm256_setzero_si256(), data);
+        let non_ascii = _mm256_movemask_epi8(non_ascii_mask);
 
-        if is_ascii == -1 {
-            return true; // All bytes are ASCII
+        if non_ascii == 0 {
+            return Some(offset + 32); // All bytes are ASCII
         }
 
         // Fall back to scalar validation for non-ASCII bytes
@@ -153,7 +158,9 @@ pub mod json {
     }
 
     /// Validates a chunk using scalar processing
-    fn validate_chunk_scalar(input: &[u8], start: usize, chunk_size: usize) -> bool {
+    ///
+    /// Returns the offset after the last sequence starting in the chunk.
+    fn validate_chunk_scalar(input: &[u8], start: usize, chunk_size: usize) -> Option<usize> {
         let len = input.len();
         let end = std::cmp::min(start + chunk_size, len);
 
@@ -167,14 +174,14 @@ pub mod json {
 
             // Multi-byte sequence - validate and advance
             if !validate_multibyte_sequence(input, i, byte) {
-                return false;
+                return None;
             }
 
             // Advance by the correct number of bytes for this sequence
             i += get_utf8_sequence_length(byte);
         }
 
-        true
+        Some(i)
     }
 
     /// Gets the length of a UTF-8 sequence from its first byte
@@ -219,10 +226,10 @@ pub mod json {
 
         // Process 32 bytes at a time with SIMD
         while i + 32 <= len {
-            if !process_simd_chunk(input, i) {
-                return false;
+            match process_simd_chunk(input, i) {
+                Some(next) => i = next,
+                None => return false,
             }
-            i += 32;
         }
 
         // Process remaining bytes with scalar validation
@@ -273,7 +280,7 @@ pub mod json {
             let quote_mask = _mm256_cmpeq_epi8(data, quote);
             let escape_mask = _mm256_or_si256(backslash_mask, quote_mask);
 
-            let mask = _mm256_movemask_epi8(escape_mask);
+            let mask = _mm256_movemask_epi8(escape_mask) as u32;
 
             let mut m = mask;
             let mut bit_pos = 0;
@@ -457,13 +464,15 @@ pub mod compression {
         let first = needle[0];
         let first_vec = _mm256_set1_epi8(first as i8);
 
-        let chunks = (haystack.len() - needle.len() + 1) / 32;
+        // Number of positions a match can start at; the guard above keeps this >= 1
+        let starts = haystack.len() - needle.len() + 1;
+        let chunks = starts / 32;
 
         for i in 0..chunks {
             let offset = i * 32;
             let data = _mm256_loadu_si256(haystack.as_ptr().add(offset) as *const __m256i);
             let cmp = _mm256_cmpeq_epi8(data, first_vec);
-            let mask = _mm256_movemask_epi8(cmp);
+            let mask = _mm256_movemask_epi8(cmp) as u32;
 
             if mask != 0 {
                 // Found potential matches, check each one
@@ -485,8 +494,7 @@ pub mod compression {
         }
 
         // Check remaining positions
-        ((chunks * 32)..(haystack.len() - needle.len() + 1))
-            .find(|&i| &haystack[i..i + needle.len()] == needle)
+        ((chunks * 32)..starts).find(|&i| &haystack[i..i + needle.len()] == needle)
     }
 }
 
@@ -509,7 +517,8 @@ pub mod batch {
         for i in 0..chunks {
             let offset = i * 8;
             let data = _mm256_loadu_si256(lengths.as_ptr().add(offset) as *const __m256i);
-            let cmp = _mm256_cmpgt_epi32(max_vec, data);
+            // Unsigned `data <= max_length`, matching the scalar tail below
+            let cmp = _mm256_cmpeq_epi32(_mm256_max_epu32(data, max_vec), max_vec);
 
             // Extract comparison results
             let mask = _mm256_movemask_ps(_mm256_castsi256_ps(cmp));
@@ -569,8 +578,64 @@ pub mod batch {
     }
 }
 
-// Fallback implementations for non-x86_64 or when SIMD is not available
-#[cfg(not(all(target_arch = "x86_64", target_feature = "avx2")))]
+/// Whether the AVX2 implementations can run on this CPU.
+///
+/// Detected on first use and cached, so a binary built without
+/// `-C target-feature=+avx2` still takes the AVX2 paths on CPUs that have it.
+pub fn avx2_available() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        static AVX2: OnceLock<bool> = OnceLock::new();
+        *AVX2.get_or_init(|| is_x86_feature_detected!("avx2"))
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        false
+    }
+}
+
+/// Find all whitespace positions in the input buffer, with AVX2 when available.
+pub fn find_whitespace(input: &[u8]) -> Vec<usize> {
+    #[cfg(target_arch = "x86_64")]
+    if avx2_available() {
+        // SAFETY: AVX2 support was checked above
+        return unsafe { json::find_whitespace_simd(input) };
+    }
+    fallback::find_whitespace(input)
+}
+
+/// Validate that the input is valid UTF-8, with AVX2 when available.
+pub fn validate_utf8(input: &[u8]) -> bool {
+    #[cfg(target_arch = "x86_64")]
+    if avx2_available() {
+        // SAFETY: AVX2 support was checked above
+        return unsafe { json::validate_utf8_simd(input) };
+    }
+    fallback::validate_utf8(input)
+}
+
+/// Find all escape character positions in the input buffer, with AVX2 when available.
+pub fn find_escapes(input: &[u8]) -> Vec<usize> {
+    #[cfg(target_arch = "x86_64")]
+    if avx2_available() {
+        // SAFETY: AVX2 support was checked above
+        return unsafe { json::find_escapes_simd(input) };
+    }
+    fallback::find_escapes(input)
+}
+
+/// Encode the input as standard, padded base64, with AVX2 when available.
+pub fn base64_encode(input: &[u8]) -> String {
+    #[cfg(target_arch = "x86_64")]
+    if avx2_available() {
+        let mut output = Vec::new();
+        // SAFETY: AVX2 support was checked above
+        unsafe { serialization::base64_encode_simd(input, &mut output) };
+        return output.into_iter().map(char::from).collect();
+    }
+    fallback::base64_encode(input)
+}
+
 /// Fallback implementations for SIMD operations when hardware SIMD is not available.
 pub mod fallback {
     /// Find all whitespace positions in the input buffer.
@@ -597,6 +662,13 @@ pub mod fallback {
             .map(|(i, _)| i)
             .collect()
     }
+
+    /// Encode the input as standard, padded base64.
+    pub fn base64_encode(input: &[u8]) -> String {
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+        STANDARD.encode(input)
+    }
 }
 
 #[cfg(test)]
@@ -604,8 +676,11 @@ mod tests {
     use super::*;
 
     #[test]
-    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    #[cfg(target_arch = "x86_64")]
     fn test_whitespace_detection() {
+        if !has_avx2() {
+            return;
+        }
         let input = b"hello world\ttab\nnewline\rcarriage  spaces";
         let positions = unsafe { json::find_whitespace_simd(input) };
 
@@ -614,8 +689,11 @@ mod tests {
     }
 
     #[test]
-    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    #[cfg(target_arch = "x86_64")]
     fn test_utf8_validation() {
+        if !has_avx2() {
+            return;
+        }
         let valid = "Hello, 世界! 🦀".as_bytes();
         let invalid = &[0xFF, 0xFE, 0xFD];
 
@@ -626,8 +704,11 @@ mod tests {
     }
 
     #[test]
-    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    #[cfg(target_arch = "x86_64")]
     fn test_xor_mask() {
+        if !has_avx2() {
+            return;
+        }
         let mut data = vec![1, 2, 3, 4, 5, 6, 7, 8];
         let mask = [0xAA, 0xBB, 0xCC, 0xDD];
 
@@ -648,9 +729,181 @@ mod tests {
         assert_eq!(data, expected);
     }
 
+    /// Input sizes around the 32-byte SIMD chunk boundary.
+    #[cfg(target_arch = "x86_64")]
+    const EDGE_SIZES: [usize; 6] = [0, 1, 31, 32, 33, 64];
+
+    /// Whether the SIMD functions can run on this CPU.
+    #[cfg(target_arch = "x86_64")]
+    fn has_avx2() -> bool {
+        is_x86_feature_detected!("avx2")
+    }
+
+    /// Deterministic input of `len` bytes with some whitespace and escapes mixed in.
+    #[cfg(target_arch = "x86_64")]
+    fn edge_input(len: usize) -> Vec<u8> {
+        (0..len)
+            .map(|i| match i % 7 {
+                0 => b' ',
+                3 => b'"',
+                5 => b'\\',
+                _ => b'a' + (i % 26) as u8,
+            })
+            .collect()
+    }
+
     #[test]
-    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    #[cfg(target_arch = "x86_64")]
+    fn test_scans_handle_edge_sizes() {
+        if !has_avx2() {
+            return;
+        }
+        for len in EDGE_SIZES {
+            let input = edge_input(len);
+            let whitespace: Vec<usize> = (0..len).filter(|&i| input[i] == b' ').collect();
+            let escapes: Vec<usize> = (0..len)
+                .filter(|&i| input[i] == b'"' || input[i] == b'\\')
+                .collect();
+
+            unsafe {
+                assert_eq!(json::find_whitespace_simd(&input), whitespace, "len {len}");
+                assert_eq!(json::find_escapes_simd(&input), escapes, "len {len}");
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn test_utf8_validation_edge_sizes() {
+        if !has_avx2() {
+            return;
+        }
+        unsafe {
+            for len in EDGE_SIZES {
+                assert!(json::validate_utf8_simd(&edge_input(len)), "len {len}");
+                // A full chunk of invalid bytes must not pass the ASCII fast path
+                let invalid = vec![0xFF; len];
+                assert_eq!(json::validate_utf8_simd(&invalid), len == 0, "len {len}");
+            }
+
+            // Multi-byte characters straddling the end of the first chunk
+            for prefix in [29, 30, 31] {
+                let mut input = vec![b'a'; prefix];
+                input.extend_from_slice("🦀".as_bytes());
+                input.extend_from_slice(&[b'b'; 40]);
+                assert!(json::validate_utf8_simd(&input), "prefix {prefix}");
+
+                // Truncated right at the chunk boundary
+                assert!(
+                    !json::validate_utf8_simd(&input[..prefix + 2]),
+                    "prefix {prefix}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn test_pattern_search_edge_sizes() {
+        if !has_avx2() {
+            return;
+        }
+        unsafe {
+            assert_eq!(compression::find_pattern_simd(b"", b"a"), None);
+            assert_eq!(compression::find_pattern_simd(b"a", b""), None);
+            assert_eq!(compression::find_pattern_simd(b"a", b"a"), Some(0));
+            assert_eq!(compression::find_pattern_simd(b"a", b"ab"), None);
+
+            for len in EDGE_SIZES.into_iter().filter(|&len| len >= 2) {
+                let mut haystack = vec![b'x'; len];
+                haystack[len - 2..].copy_from_slice(b"yz");
+                assert_eq!(
+                    compression::find_pattern_simd(&haystack, b"yz"),
+                    Some(len - 2),
+                    "len {len}"
+                );
+                assert_eq!(
+                    compression::find_pattern_simd(&haystack, &haystack),
+                    Some(0),
+                    "len {len}"
+                );
+                assert_eq!(compression::find_pattern_simd(&haystack, b"zz"), None);
+
+                let longer = vec![b'x'; len + 1];
+                assert_eq!(compression::find_pattern_simd(&haystack, &longer), None);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn test_buffer_operations_edge_sizes() {
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+        if !has_avx2() {
+            return;
+        }
+        let mask = [0xAA, 0xBB, 0xCC, 0xDD];
+        for len in EDGE_SIZES {
+            let input = edge_input(len);
+
+            let mut copied = vec![0; len];
+            unsafe { serialization::copy_simd(&input, &mut copied) };
+            assert_eq!(copied, input, "len {len}");
+
+            let mut encoded = Vec::new();
+            unsafe { serialization::base64_encode_simd(&input, &mut encoded) };
+            assert_eq!(encoded, STANDARD.encode(&input).into_bytes(), "len {len}");
+
+            let mut masked = input.clone();
+            unsafe { serialization::xor_mask_simd(&mut masked, mask) };
+            let expected: Vec<u8> = input
+                .iter()
+                .enumerate()
+                .map(|(i, byte)| byte ^ mask[i % 4])
+                .collect();
+            assert_eq!(masked, expected, "len {len}");
+
+            let runs = vec![7; len];
+            let mut rle = Vec::new();
+            unsafe { compression::rle_encode_simd(&runs, &mut rle) };
+            let decoded: Vec<u8> = rle
+                .chunks(2)
+                .flat_map(|pair| std::iter::repeat_n(pair[1], pair[0] as usize))
+                .collect();
+            assert_eq!(decoded, runs, "len {len}");
+
+            let sum = input.iter().map(|&byte| u32::from(byte)).sum::<u32>();
+            let sums = unsafe { batch::batch_checksum_simd(&[&input]) };
+            assert_eq!(sums, vec![sum], "len {len}");
+        }
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
+    fn test_batch_validation_edge_sizes() {
+        if !has_avx2() {
+            return;
+        }
+        let max = 500;
+        for len in [0, 1, 7, 8, 9, 16] {
+            // Cycle through values just below, at and above the limit
+            let lengths: Vec<u32> = (0..len)
+                .map(|i| [max - 1, max, max + 1, u32::MAX][i % 4])
+                .collect();
+            let expected: Vec<bool> = lengths.iter().map(|&length| length <= max).collect();
+
+            let results = unsafe { batch::batch_validate_lengths(&lengths, max) };
+            assert_eq!(results, expected, "len {len}");
+        }
+    }
+
+    #[test]
+    #[cfg(target_arch = "x86_64")]
     fn test_batch_validation() {
+        if !has_avx2() {
+            return;
+        }
         let lengths = vec![100, 200, 50, 1000, 75, 150, 2000, 80];
         let max = 500;
 
@@ -659,4 +912,21 @@ mod tests {
         let expected = vec![true, true, true, false, true, true, false, true];
         assert_eq!(results, expected);
     }
+
+    #[test]
+    fn test_dispatch_matches_fallback() {
+        let mut inputs: Vec<Vec<u8>> = [0, 1, 31, 32, 33, 64, 100]
+            .into_iter()
+            .map(|len| (0..len).map(|i| b" \t\n\r\"\\ab{}:,"[i % 12]).collect())
+            .collect();
+        inputs.push("Hello, 世界! 🦀 \"quoted\"".repeat(4).into_bytes());
+        inputs.push(vec![0xFF; 40]);
+
+        for input in &inputs {
+            assert_eq!(find_whitespace(input), fallback::find_whitespace(input));
+            assert_eq!(validate_utf8(input), fallback::validate_utf8(input));
+            assert_eq!(find_escapes(input), fallback::find_escapes(input));
+            assert_eq!(base64_encode(input), fallback::base64_encode(input));
+        }
+    }
 }
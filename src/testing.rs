@@ -0,0 +1,211 @@
+//! Helpers for testing MCP servers and clients in-process.
+//!
+//! [`duplex`] connects a real [`Client`](crate::Client) to a real
+//! [`Server`](crate::Server) through memory, so integration tests need neither a
+//! mock transport nor a child process. Messages are encoded as JSON-RPC on the way
+//! through, as they would be on the wire. Requires the `testing` feature.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use pmcp::testing::duplex;
+//! use pmcp::{
+//!     Client, ClientCapabilities, RequestHandlerExtra, Server, ServerCapabilities, ToolHandler,
+//! };
+//! use async_trait::async_trait;
+//! use serde_json::{json, Value};
+//!
+//! struct Echo;
+//!
+//! #[async_trait]
+//! impl ToolHandler for Echo {
+//!     async fn handle(&self, args: Value, _extra: RequestHandlerExtra) -> pmcp::Result<Value> {
+//!         Ok(args)
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> pmcp::Result<()> {
+//! let server = Server::builder()
+//!     .name("echo-server")
+//!     .version("1.0.0")
+//!     .capabilities(ServerCapabilities::tools_only())
+//!     .tool("echo", Echo)
+//!     .build()?;
+//!
+//! let (client_transport, server_transport) = duplex();
+//! tokio::spawn(server.run(server_transport));
+//!
+//! let mut client = Client::new(client_transport);
+//! client.initialize(ClientCapabilities::default()).await?;
+//! let result = client.call_tool("echo".to_string(), json!({"hello": "world"})).await?;
+//! assert!(!result.is_error);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{Result, TransportError};
+use crate::shared::{StdioTransport, Transport, TransportMessage};
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+/// Client end of a [`duplex`] connection.
+pub type ClientTransport = DuplexTransport;
+
+/// Server end of a [`duplex`] connection.
+pub type ServerTransport = DuplexTransport;
+
+/// One end of an in-memory connection, see [`duplex`].
+///
+/// Closing or dropping one end makes the other end's `receive` fail with
+/// [`TransportError::ConnectionClosed`].
+#[derive(Debug)]
+pub struct DuplexTransport {
+    tx: Option<mpsc::UnboundedSender<Vec<u8>>>,
+    rx: mpsc::UnboundedReceiver<Vec<u8>>,
+}
+
+/// Create a connected pair of in-memory transports.
+///
+/// Hand the first to a [`Client`](crate::Client) and the second to
+/// [`Server::run`](crate::Server::run).
+pub fn duplex() -> (ClientTransport, ServerTransport) {
+    let (client_tx, server_rx) = mpsc::unbounded_channel();
+    let (server_tx, client_rx) = mpsc::unbounded_channel();
+    (
+        DuplexTransport {
+            tx: Some(client_tx),
+            rx: client_rx,
+        },
+        DuplexTransport {
+            tx: Some(server_tx),
+            rx: server_rx,
+        },
+    )
+}
+
+#[async_trait]
+impl Transport for DuplexTransport {
+    async fn send(&mut self, message: TransportMessage) -> Result<()> {
+        let tx = self.tx.as_ref().ok_or(TransportError::ConnectionClosed)?;
+        let frame = StdioTransport::serialize_message(&message)?;
+        tx.send(frame)
+            .map_err(|_| TransportError::ConnectionClosed.into())
+    }
+
+    async fn receive(&mut self) -> Result<TransportMessage> {
+        let frame = self
+            .rx
+            .recv()
+            .await
+            .ok_or(TransportError::ConnectionClosed)?;
+        StdioTransport::parse_message(&frame)
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        self.tx = None;
+        self.rx.close();
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.tx.as_ref().is_some_and(|tx| !tx.is_closed())
+    }
+
+    fn transport_type(&self) -> &'static str {
+        "duplex"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ClientRequest, Request, RequestId};
+
+    #[tokio::test]
+    async fn test_messages_cross_over() {
+        let (mut client, mut server) = duplex();
+        client
+            .send(TransportMessage::Request {
+                id: RequestId::from(1i64),
+                request: Request::Client(Box::new(ClientRequest::Ping)),
+            })
+            .await
+            .unwrap();
+
+        let TransportMessage::Request { id, request } = server.receive().await.unwrap() else {
+            panic!("Expected request");
+        };
+        assert_eq!(id, RequestId::from(1i64));
+        assert!(matches!(request, Request::Client(ref r) if matches!(**r, ClientRequest::Ping)));
+    }
+
+    #[tokio::test]
+    async fn test_close_disconnects_peer() {
+        let (mut client, mut server) = duplex();
+        assert!(client.is_connected());
+
+        server.close().await.unwrap();
+        assert!(!client.is_connected());
+        assert!(matches!(
+            client.receive().await,
+            Err(crate::Error::Transport(TransportError::ConnectionClosed))
+        ));
+        assert!(matches!(
+            server
+                .send(TransportMessage::Request {
+                    id: RequestId::from(1i64),
+                    request: Request::Client(Box::new(ClientRequest::Ping)),
+                })
+                .await,
+            Err(crate::Error::Transport(TransportError::ConnectionClosed))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_client_calls_server_tool() {
+        struct Add;
+
+        #[async_trait]
+        impl crate::ToolHandler for Add {
+            async fn handle(
+                &self,
+                args: serde_json::Value,
+                _extra: crate::RequestHandlerExtra,
+            ) -> Result<serde_json::Value> {
+                let sum = args["a"].as_i64().unwrap_or(0) + args["b"].as_i64().unwrap_or(0);
+                Ok(serde_json::json!({"sum": sum}))
+            }
+        }
+
+        let server = crate::Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .capabilities(crate::ServerCapabilities::tools_only())
+            .tool("add", Add)
+            .build()
+            .unwrap();
+        let (client_transport, server_transport) = duplex();
+        let running = tokio::spawn(server.run(server_transport));
+
+        let mut client = crate::Client::new(client_transport);
+        client
+            .initialize(crate::ClientCapabilities::default())
+            .await
+            .unwrap();
+        let tools = client.list_tools(None).await.unwrap();
+        assert_eq!(tools.tools.len(), 1);
+        assert_eq!(tools.tools[0].name, "add");
+
+        let result = client
+            .call_tool("add".to_string(), serde_json::json!({"a": 2, "b": 3}))
+            .await
+            .unwrap();
+        assert!(!result.is_error);
+        let crate::types::Content::Text { text } = &result.content[0] else {
+            panic!("Expected text content");
+        };
+        assert!(text.contains('5'), "{}", text);
+        running.abort();
+    }
+}
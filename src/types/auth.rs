@@ -24,10 +24,11 @@ pub struct AuthInfo {
 }
 
 /// Authentication scheme types.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum AuthScheme {
     /// No authentication
+    #[default]
     None,
     /// Bearer token authentication
     Bearer,
@@ -156,12 +157,6 @@ pub enum PkceMethod {
     S256,
 }
 
-impl Default for AuthScheme {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 impl AuthInfo {
     /// Create auth info with no authentication.
     ///
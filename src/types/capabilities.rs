@@ -6,6 +6,9 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Experimental capability a server advertises when it accepts JSON-RPC batches.
+pub const BATCH_CAPABILITY: &str = "batch";
+
 /// Client capabilities advertised during initialization.
 ///
 /// # Examples
@@ -153,6 +156,28 @@ pub struct CompletionCapabilities {
 }
 
 impl ClientCapabilities {
+    /// Start building client capabilities fluently.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::ClientCapabilities;
+    ///
+    /// let capabilities = ClientCapabilities::builder()
+    ///     .tools()
+    ///     .resources_with_subscribe()
+    ///     .sampling()
+    ///     .roots(true)
+    ///     .build();
+    ///
+    /// assert!(capabilities.supports_tools());
+    /// assert_eq!(capabilities.resources.as_ref().unwrap().subscribe, Some(true));
+    /// assert!(capabilities.roots.as_ref().unwrap().list_changed);
+    /// ```
+    pub fn builder() -> ClientCapabilitiesBuilder {
+        ClientCapabilitiesBuilder::default()
+    }
+
     /// Create a minimal set of client capabilities.
     ///
     /// # Examples
@@ -372,6 +397,27 @@ impl ClientCapabilities {
 }
 
 impl ServerCapabilities {
+    /// Start building server capabilities fluently.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::ServerCapabilities;
+    ///
+    /// let capabilities = ServerCapabilities::builder()
+    ///     .tools_with_list_changed()
+    ///     .resources_with_subscribe()
+    ///     .logging()
+    ///     .build();
+    ///
+    /// assert!(capabilities.provides_resources());
+    /// assert!(capabilities.logging.is_some());
+    /// assert_eq!(capabilities.tools.unwrap().list_changed, Some(true));
+    /// ```
+    pub fn builder() -> ServerCapabilitiesBuilder {
+        ServerCapabilitiesBuilder::default()
+    }
+
     /// Create a minimal set of server capabilities.
     ///
     /// # Examples
@@ -467,7 +513,7 @@ impl ServerCapabilities {
     /// #             description: Some("Greeting prompt".to_string()),
     /// #             messages: vec![PromptMessage {
     /// #                 role: Role::System,
-    /// #                 content: Content::Text { text: "Hello!".to_string() },
+    /// #                 content: vec![Content::Text { text: "Hello!".to_string() }],
     /// #             }],
     /// #         })
     /// #     }
@@ -519,6 +565,8 @@ impl ServerCapabilities {
     /// #     async fn read(&self, uri: &str, _extra: pmcp::RequestHandlerExtra) -> Result<ReadResourceResult, pmcp::Error> {
     /// #         Ok(ReadResourceResult {
     /// #             contents: vec![Content::Text { text: "File contents".to_string() }],
+    /// #             content_hash: None,
+    /// #             not_modified: false,
     /// #         })
     /// #     }
     /// #     async fn list(&self, _path: Option<String>, _extra: pmcp::RequestHandlerExtra) -> Result<ListResourcesResult, pmcp::Error> {
@@ -653,6 +701,251 @@ impl ServerCapabilities {
     pub fn provides_resources(&self) -> bool {
         self.resources.is_some()
     }
+
+    /// Check if the server accepts JSON-RPC batches.
+    ///
+    /// Advertised as the [`BATCH_CAPABILITY`] experimental capability.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::ServerCapabilities;
+    ///
+    /// assert!(!ServerCapabilities::default().supports_batch());
+    /// assert!(ServerCapabilities::builder().batch().build().supports_batch());
+    /// ```
+    pub fn supports_batch(&self) -> bool {
+        self.experimental
+            .as_ref()
+            .and_then(|experimental| experimental.get(BATCH_CAPABILITY))
+            .is_some_and(|value| value.as_bool() != Some(false))
+    }
+}
+
+/// Fluent builder for [`ClientCapabilities`].
+///
+/// Each method enables a capability; the `_with_` variants also set one of its
+/// sub-flags and can be combined with each other.
+#[derive(Debug, Clone, Default)]
+pub struct ClientCapabilitiesBuilder {
+    capabilities: ClientCapabilities,
+}
+
+impl ClientCapabilitiesBuilder {
+    /// Advertise tool support.
+    pub fn tools(mut self) -> Self {
+        self.capabilities.tools.get_or_insert_with(Default::default);
+        self
+    }
+
+    /// Advertise tool support with list change notifications.
+    pub fn tools_with_list_changed(mut self) -> Self {
+        self.capabilities
+            .tools
+            .get_or_insert_with(Default::default)
+            .list_changed = Some(true);
+        self
+    }
+
+    /// Advertise prompt support.
+    pub fn prompts(mut self) -> Self {
+        self.capabilities
+            .prompts
+            .get_or_insert_with(Default::default);
+        self
+    }
+
+    /// Advertise prompt support with list change notifications.
+    pub fn prompts_with_list_changed(mut self) -> Self {
+        self.capabilities
+            .prompts
+            .get_or_insert_with(Default::default)
+            .list_changed = Some(true);
+        self
+    }
+
+    /// Advertise resource support.
+    pub fn resources(mut self) -> Self {
+        self.capabilities
+            .resources
+            .get_or_insert_with(Default::default);
+        self
+    }
+
+    /// Advertise resource support with subscriptions.
+    pub fn resources_with_subscribe(mut self) -> Self {
+        self.capabilities
+            .resources
+            .get_or_insert_with(Default::default)
+            .subscribe = Some(true);
+        self
+    }
+
+    /// Advertise resource support with list change notifications.
+    pub fn resources_with_list_changed(mut self) -> Self {
+        self.capabilities
+            .resources
+            .get_or_insert_with(Default::default)
+            .list_changed = Some(true);
+        self
+    }
+
+    /// Advertise logging support.
+    pub fn logging(mut self) -> Self {
+        self.capabilities
+            .logging
+            .get_or_insert_with(Default::default);
+        self
+    }
+
+    /// Advertise sampling support.
+    pub fn sampling(mut self) -> Self {
+        self.capabilities
+            .sampling
+            .get_or_insert_with(Default::default);
+        self
+    }
+
+    /// Advertise sampling support limited to the given model families.
+    pub fn sampling_with_models(mut self, models: Vec<String>) -> Self {
+        self.capabilities
+            .sampling
+            .get_or_insert_with(Default::default)
+            .models = Some(models);
+        self
+    }
+
+    /// Advertise roots support.
+    pub fn roots(mut self, list_changed: bool) -> Self {
+        self.capabilities.roots = Some(RootsCapabilities { list_changed });
+        self
+    }
+
+    /// Advertise an experimental capability.
+    pub fn experimental(mut self, name: impl Into<String>, value: serde_json::Value) -> Self {
+        self.capabilities
+            .experimental
+            .get_or_insert_with(Default::default)
+            .insert(name.into(), value);
+        self
+    }
+
+    /// Finish building the capabilities.
+    pub fn build(self) -> ClientCapabilities {
+        self.capabilities
+    }
+}
+
+/// Fluent builder for [`ServerCapabilities`].
+///
+/// Each method enables a capability; the `_with_` variants also set one of its
+/// sub-flags and can be combined with each other.
+#[derive(Debug, Clone, Default)]
+pub struct ServerCapabilitiesBuilder {
+    capabilities: ServerCapabilities,
+}
+
+impl ServerCapabilitiesBuilder {
+    /// Provide tools.
+    pub fn tools(mut self) -> Self {
+        self.capabilities.tools.get_or_insert_with(Default::default);
+        self
+    }
+
+    /// Provide tools and send list change notifications.
+    pub fn tools_with_list_changed(mut self) -> Self {
+        self.capabilities
+            .tools
+            .get_or_insert_with(Default::default)
+            .list_changed = Some(true);
+        self
+    }
+
+    /// Provide prompts.
+    pub fn prompts(mut self) -> Self {
+        self.capabilities
+            .prompts
+            .get_or_insert_with(Default::default);
+        self
+    }
+
+    /// Provide prompts and send list change notifications.
+    pub fn prompts_with_list_changed(mut self) -> Self {
+        self.capabilities
+            .prompts
+            .get_or_insert_with(Default::default)
+            .list_changed = Some(true);
+        self
+    }
+
+    /// Provide resources.
+    pub fn resources(mut self) -> Self {
+        self.capabilities
+            .resources
+            .get_or_insert_with(Default::default);
+        self
+    }
+
+    /// Provide resources that clients can subscribe to.
+    pub fn resources_with_subscribe(mut self) -> Self {
+        self.capabilities
+            .resources
+            .get_or_insert_with(Default::default)
+            .subscribe = Some(true);
+        self
+    }
+
+    /// Provide resources and send list change notifications.
+    pub fn resources_with_list_changed(mut self) -> Self {
+        self.capabilities
+            .resources
+            .get_or_insert_with(Default::default)
+            .list_changed = Some(true);
+        self
+    }
+
+    /// Provide log messages.
+    pub fn logging(mut self) -> Self {
+        self.capabilities
+            .logging
+            .get_or_insert_with(Default::default);
+        self
+    }
+
+    /// Provide argument completions.
+    pub fn completions(mut self) -> Self {
+        self.capabilities
+            .completions
+            .get_or_insert_with(Default::default);
+        self
+    }
+
+    /// Provide sampling.
+    pub fn sampling(mut self) -> Self {
+        self.capabilities
+            .sampling
+            .get_or_insert_with(Default::default);
+        self
+    }
+
+    /// Advertise an experimental capability.
+    pub fn experimental(mut self, name: impl Into<String>, value: serde_json::Value) -> Self {
+        self.capabilities
+            .experimental
+            .get_or_insert_with(Default::default)
+            .insert(name.into(), value);
+        self
+    }
+
+    /// Accept JSON-RPC batches.
+    pub fn batch(self) -> Self {
+        self.experimental(BATCH_CAPABILITY, serde_json::json!({}))
+    }
+
+    /// Finish building the capabilities.
+    pub fn build(self) -> ServerCapabilities {
+        self.capabilities
+    }
 }
 
 #[cfg(test)]
@@ -687,6 +980,52 @@ mod tests {
         assert!(!prompts_only.provides_resources());
     }
 
+    #[test]
+    fn client_capabilities_builder_sets_sub_flags() {
+        let caps = ClientCapabilities::builder()
+            .tools_with_list_changed()
+            .prompts()
+            .resources_with_subscribe()
+            .resources_with_list_changed()
+            .sampling_with_models(vec!["claude".to_string()])
+            .roots(true)
+            .experimental("beta", serde_json::json!(true))
+            .build();
+
+        assert_eq!(caps.tools.unwrap().list_changed, Some(true));
+        assert_eq!(caps.prompts.unwrap().list_changed, None);
+        let resources = caps.resources.unwrap();
+        assert_eq!(resources.subscribe, Some(true));
+        assert_eq!(resources.list_changed, Some(true));
+        assert_eq!(
+            caps.sampling.unwrap().models,
+            Some(vec!["claude".to_string()])
+        );
+        assert!(caps.roots.unwrap().list_changed);
+        assert!(caps.logging.is_none());
+        assert_eq!(caps.experimental.unwrap()["beta"], true);
+    }
+
+    #[test]
+    fn server_capabilities_builder_sets_sub_flags() {
+        let caps = ServerCapabilities::builder()
+            .tools()
+            .prompts_with_list_changed()
+            .resources_with_subscribe()
+            .logging()
+            .completions()
+            .build();
+
+        assert_eq!(caps.tools.unwrap().list_changed, None);
+        assert_eq!(caps.prompts.unwrap().list_changed, Some(true));
+        let resources = caps.resources.unwrap();
+        assert_eq!(resources.subscribe, Some(true));
+        assert_eq!(resources.list_changed, None);
+        assert!(caps.logging.is_some());
+        assert!(caps.completions.is_some());
+        assert!(caps.sampling.is_none());
+    }
+
     #[test]
     fn capabilities_serialization() {
         let caps = ClientCapabilities {
@@ -9,7 +9,9 @@ use std::collections::HashMap;
 /// # Examples
 ///
 /// ```rust
-/// use pmcp::types::elicitation::{ElicitInputRequest, InputType, InputValidation, SelectOption};
+/// use pmcp::types::elicitation::{
+///     ElicitInputRequest, InputType, InputValidation, SelectOption, UiHint,
+/// };
 /// use serde_json::json;
 /// use std::collections::HashMap;
 ///
@@ -28,6 +30,7 @@ use std::collections::HashMap;
 ///         options: None,
 ///         message: None,
 ///     }),
+///     ui_hint: UiHint::Text,
 ///     metadata: HashMap::new(),
 /// };
 ///
@@ -65,6 +68,7 @@ use std::collections::HashMap;
 ///         ]),
 ///         message: None,
 ///     }),
+///     ui_hint: UiHint::Select,
 ///     metadata: {
 ///         let mut meta = HashMap::new();
 ///         meta.insert("category".to_string(), json!("ui"));
@@ -87,6 +91,7 @@ use std::collections::HashMap;
 ///         options: None,
 ///         message: None,
 ///     }),
+///     ui_hint: UiHint::default(),
 ///     metadata: HashMap::new(),
 /// };
 /// ```
@@ -114,6 +119,10 @@ pub struct ElicitInputRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub validation: Option<InputValidation>,
 
+    /// Widget the client should render for the input.
+    #[serde(default, skip_serializing_if = "UiHint::is_text")]
+    pub ui_hint: UiHint,
+
     /// Additional metadata.
     #[serde(flatten)]
     pub metadata: HashMap<String, Value>,
@@ -155,6 +164,30 @@ pub enum InputType {
     Email,
 }
 
+/// Widget a client should use to ask for an input.
+///
+/// Only a hint: clients that cannot render the widget fall back to a text field.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UiHint {
+    /// Free text field.
+    #[default]
+    Text,
+    /// Text field that hides what is typed.
+    Password,
+    /// List to pick from, filled from the validation options.
+    Select,
+    /// Yes/no confirmation dialog.
+    Confirm,
+}
+
+impl UiHint {
+    /// Whether this is the default free text hint.
+    pub fn is_text(&self) -> bool {
+        *self == Self::Text
+    }
+}
+
 /// Input validation rules.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -232,6 +265,7 @@ pub struct ElicitInputBuilder {
     description: Option<String>,
     default: Option<Value>,
     validation: Option<InputValidation>,
+    ui_hint: UiHint,
     metadata: HashMap<String, Value>,
 }
 
@@ -245,6 +279,7 @@ impl ElicitInputBuilder {
             description: None,
             default: None,
             validation: None,
+            ui_hint: UiHint::Text,
             metadata: HashMap::new(),
         }
     }
@@ -352,6 +387,12 @@ impl ElicitInputBuilder {
         self
     }
 
+    /// Set the widget the client should render.
+    pub fn ui_hint(mut self, hint: UiHint) -> Self {
+        self.ui_hint = hint;
+        self
+    }
+
     /// Add metadata.
     pub fn metadata(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
         self.metadata.insert(key.into(), value.into());
@@ -367,6 +408,7 @@ impl ElicitInputBuilder {
             description: self.description,
             default: self.default,
             validation: self.validation,
+            ui_hint: self.ui_hint,
             metadata: self.metadata,
         }
     }
@@ -485,4 +527,28 @@ mod tests {
         let deserialized: ElicitInputRequest = serde_json::from_value(json).unwrap();
         assert_eq!(deserialized.prompt, request.prompt);
     }
+
+    #[test]
+    fn test_ui_hint_round_trips() {
+        for (hint, wire) in [
+            (UiHint::Password, "password"),
+            (UiHint::Select, "select"),
+            (UiHint::Confirm, "confirm"),
+        ] {
+            let request = elicit_text("Continue?").ui_hint(hint).build();
+            let json = serde_json::to_value(&request).unwrap();
+            assert_eq!(json["uiHint"], wire);
+
+            let deserialized: ElicitInputRequest = serde_json::from_value(json).unwrap();
+            assert_eq!(deserialized.ui_hint, hint);
+            assert!(!deserialized.metadata.contains_key("uiHint"));
+        }
+
+        // Free text is the default and is left off the wire
+        let request = elicit_text("Name?").build();
+        let json = serde_json::to_value(&request).unwrap();
+        assert!(json.get("uiHint").is_none());
+        let deserialized: ElicitInputRequest = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized.ui_hint, UiHint::Text);
+    }
 }
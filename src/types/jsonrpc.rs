@@ -335,7 +335,7 @@ impl From<crate::Error> for JSONRPCError {
                 message: message.clone(),
                 data: data.clone(),
             },
-            _ => Self::new(-32603, err.to_string()),
+            _ => Self::new(err.json_rpc_code().as_i32(), err.to_string()),
         }
     }
 }
@@ -411,6 +411,23 @@ mod tests {
         assert_eq!(RequestId::from(42u64), RequestId::Number(42));
     }
 
+    #[test]
+    fn request_id_type_survives_round_trip() {
+        for raw in [json!(7), json!("7"), json!(-1), json!("req-1")] {
+            let id: RequestId = serde_json::from_value(raw.clone()).unwrap();
+            assert_eq!(serde_json::to_value(&id).unwrap(), raw);
+
+            let request: JSONRPCRequest = serde_json::from_value(json!({
+                "jsonrpc": "2.0",
+                "id": raw,
+                "method": "ping"
+            }))
+            .unwrap();
+            let response = JSONRPCResponse::<serde_json::Value>::success(request.id, json!({}));
+            assert_eq!(serde_json::to_value(&response).unwrap()["id"], raw);
+        }
+    }
+
     #[test]
     fn request_serialization() {
         let request = JSONRPCRequest::new(1i64, "test/method", Some(json!({"key": "value"})));
@@ -507,7 +524,7 @@ mod tests {
         // Test from MCP error
         let mcp_err = crate::error::Error::validation("Bad input");
         let jsonrpc_err = JSONRPCError::from(mcp_err);
-        assert_eq!(jsonrpc_err.code, -32603); // Internal error (default)
+        assert_eq!(jsonrpc_err.code, -32602); // Invalid params
         assert!(jsonrpc_err.message.contains("Bad input"));
     }
 
@@ -19,22 +19,23 @@ pub use crate::{DEFAULT_PROTOCOL_VERSION, LATEST_PROTOCOL_VERSION, SUPPORTED_PRO
 // Re-export commonly used types
 pub use auth::{AuthInfo, AuthScheme};
 pub use capabilities::{
-    ClientCapabilities, CompletionCapabilities, LoggingCapabilities, PromptCapabilities,
-    ResourceCapabilities, RootsCapabilities, SamplingCapabilities, ServerCapabilities,
-    ToolCapabilities,
+    ClientCapabilities, ClientCapabilitiesBuilder, CompletionCapabilities, LoggingCapabilities,
+    PromptCapabilities, ResourceCapabilities, RootsCapabilities, SamplingCapabilities,
+    ServerCapabilities, ServerCapabilitiesBuilder, ToolCapabilities, BATCH_CAPABILITY,
 };
 pub use jsonrpc::{JSONRPCError, JSONRPCNotification, JSONRPCRequest, JSONRPCResponse, RequestId};
 pub use protocol::{
-    CallToolParams, CallToolRequest, CallToolResult, CancelledNotification, CancelledParams,
-    ClientNotification, ClientRequest, CompleteRequest, CompleteResult, CompletionArgument,
-    CompletionReference, CompletionResult, Content, CreateMessageParams, CreateMessageRequest,
-    CreateMessageResult, GetPromptParams, GetPromptRequest, GetPromptResult, Implementation,
-    IncludeContext, InitializeParams, InitializeRequest, InitializeResult, ListPromptsParams,
-    ListPromptsRequest, ListPromptsResult, ListResourceTemplatesRequest,
-    ListResourceTemplatesResult, ListResourcesParams, ListResourcesRequest, ListResourcesResult,
-    ListToolsParams, ListToolsRequest, ListToolsResult, LoggingLevel, MessageContent, ModelHint,
-    ModelPreferences, Notification, Progress, ProgressNotification, ProgressToken, PromptArgument,
-    PromptInfo, PromptMessage, ProtocolVersion, ReadResourceParams, ReadResourceRequest,
-    ReadResourceResult, Request, ResourceInfo, ResourceTemplate, Role, SamplingMessage,
-    ServerNotification, ServerRequest, SubscribeRequest, TokenUsage, ToolInfo, UnsubscribeRequest,
+    content_hash, ArgumentChunk, ArgumentStream, CallToolParams, CallToolRequest, CallToolResult,
+    CancelledNotification, CancelledParams, ChangeKind, ClientNotification, ClientRequest,
+    CompleteRequest, CompleteResult, CompletionArgument, CompletionReference, CompletionResult,
+    Content, CreateMessageParams, CreateMessageRequest, CreateMessageResult, GetPromptParams,
+    GetPromptRequest, GetPromptResult, Implementation, IncludeContext, InitializeParams,
+    InitializeRequest, InitializeResult, ListPromptsParams, ListPromptsRequest, ListPromptsResult,
+    ListResourceTemplatesRequest, ListResourceTemplatesResult, ListResourcesParams,
+    ListResourcesRequest, ListResourcesResult, ListToolsParams, ListToolsRequest, ListToolsResult,
+    LoggingLevel, MessageContent, ModelHint, ModelPreferences, Notification, Progress,
+    ProgressNotification, ProgressToken, PromptArgument, PromptInfo, PromptMessage,
+    ProtocolVersion, ReadResourceParams, ReadResourceRequest, ReadResourceResult, Request,
+    RequestMeta, ResourceInfo, ResourceTemplate, Role, SamplingMessage, ServerNotification,
+    ServerRequest, SubscribeRequest, TokenUsage, ToolInfo, UnsubscribeRequest,
 };
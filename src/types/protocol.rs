@@ -98,6 +98,15 @@ pub struct ToolInfo {
     pub description: Option<String>,
     /// JSON Schema for tool parameters
     pub input_schema: Value,
+    /// Category used to group related tools in client menus
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Free-form tags for filtering and grouping tools
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// JSON Schema describing the tool's result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
 }
 
 /// List tools response.
@@ -120,6 +129,70 @@ pub struct CallToolRequest {
     /// Tool arguments (must match input schema)
     #[serde(default)]
     pub arguments: Value,
+    /// Request metadata
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<RequestMeta>,
+}
+
+impl CallToolRequest {
+    /// Create a tool call request without metadata.
+    pub fn new(name: impl Into<String>, arguments: Value) -> Self {
+        Self {
+            name: name.into(),
+            arguments,
+            meta: None,
+        }
+    }
+}
+
+/// Request metadata carried in `_meta`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestMeta {
+    /// Token to send progress notifications about this request with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress_token: Option<ProgressToken>,
+    /// Time after which the client no longer waits for the result
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<chrono::DateTime<chrono::Utc>>,
+    /// Arguments sent ahead of the request as [`ArgumentChunk`]s
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub argument_stream: Option<ArgumentStream>,
+    /// Content hash of the client's copy of the resource being read
+    ///
+    /// A server that supports conditional reads answers `resources/read` with
+    /// [`ReadResourceResult::unchanged`] if the resource still has this hash. Servers
+    /// that do not ignore it and return the contents.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub if_none_match: Option<String>,
+}
+
+/// Reference to tool call arguments streamed ahead of the request.
+///
+/// A client sends large arguments as JSON text split across
+/// `notifications/tools/argumentChunk` notifications, then sends the `tools/call`
+/// request with an empty `arguments` and this in `_meta.argumentStream`. The server
+/// joins the chunks in sequence order and parses them as the call's arguments.
+/// Cancelling the request ID equal to `stream_id` discards the chunks received so far.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArgumentStream {
+    /// Stream the chunks were sent on
+    pub stream_id: String,
+    /// Number of chunks sent
+    pub chunks: u64,
+}
+
+/// One piece of streamed tool call arguments.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArgumentChunk {
+    /// Stream the chunk belongs to
+    pub stream_id: String,
+    /// Position of the chunk in the stream, starting at 0
+    pub seq: u64,
+    /// Next piece of the arguments' JSON text
+    pub data: String,
 }
 
 /// Tool call parameters (legacy name).
@@ -135,6 +208,100 @@ pub struct CallToolResult {
     /// Whether the tool call represents an error
     #[serde(default)]
     pub is_error: bool,
+    /// Side information about the call, such as timing or the model used
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Value>,
+}
+
+impl CallToolResult {
+    /// Text of all text items, joined by newlines.
+    ///
+    /// Other content is skipped. Returns `None` if there is no text item.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::{CallToolResult, Content};
+    ///
+    /// let result = CallToolResult {
+    ///     content: vec![
+    ///         Content::Text { text: "first".to_string() },
+    ///         Content::Image { data: "AA==".to_string(), mime_type: "image/png".to_string() },
+    ///         Content::Text { text: "second".to_string() },
+    ///     ],
+    ///     is_error: false,
+    ///     meta: None,
+    /// };
+    /// assert_eq!(result.text().as_deref(), Some("first\nsecond"));
+    /// ```
+    pub fn text(&self) -> Option<String> {
+        let texts: Vec<&str> = self
+            .content
+            .iter()
+            .filter_map(|content| match content {
+                Content::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        (!texts.is_empty()).then(|| texts.join("\n"))
+    }
+
+    /// First text item parsed as JSON.
+    ///
+    /// Returns `None` if there is no text item or the first one is not valid JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::{CallToolResult, Content};
+    /// use serde_json::json;
+    ///
+    /// let result = CallToolResult {
+    ///     content: vec![Content::Text { text: r#"{"sum": 5}"#.to_string() }],
+    ///     is_error: false,
+    ///     meta: None,
+    /// };
+    /// assert_eq!(result.json(), Some(json!({"sum": 5})));
+    /// ```
+    pub fn json(&self) -> Option<Value> {
+        self.content.iter().find_map(|content| match content {
+            Content::Text { text } => Some(serde_json::from_str(text).ok()),
+            _ => None,
+        })?
+    }
+
+    /// Decoded data and MIME type of every image item.
+    ///
+    /// Images whose data is not valid base64 are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::{CallToolResult, Content};
+    ///
+    /// let result = CallToolResult {
+    ///     content: vec![Content::Image {
+    ///         data: "cG5n".to_string(),
+    ///         mime_type: "image/png".to_string(),
+    ///     }],
+    ///     is_error: false,
+    ///     meta: None,
+    /// };
+    /// assert_eq!(result.images(), vec![(b"png".to_vec(), "image/png".to_string())]);
+    /// ```
+    pub fn images(&self) -> Vec<(Vec<u8>, String)> {
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+        self.content
+            .iter()
+            .filter_map(|content| match content {
+                Content::Image { data, mime_type } => {
+                    Some((STANDARD.decode(data).ok()?, mime_type.clone()))
+                },
+                _ => None,
+            })
+            .collect()
+    }
 }
 
 /// Message content type alias.
@@ -158,6 +325,14 @@ pub enum Content {
         /// MIME type (e.g., "image/png")
         mime_type: String,
     },
+    /// Binary data, e.g. the contents of a binary resource
+    #[serde(rename_all = "camelCase")]
+    Blob {
+        /// Base64-encoded data
+        data: String,
+        /// MIME type (e.g., "application/octet-stream")
+        mime_type: String,
+    },
     /// Resource reference
     #[serde(rename_all = "camelCase")]
     Resource {
@@ -172,6 +347,41 @@ pub enum Content {
     },
 }
 
+impl Content {
+    /// Create blob content, base64-encoding `data`.
+    ///
+    /// Encodes with SIMD when the `simd` feature is enabled and the CPU supports it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::Content;
+    ///
+    /// let content = Content::blob([0xFF, 0x00], "application/octet-stream");
+    /// assert!(matches!(content, Content::Blob { ref data, .. } if data == "/wA="));
+    /// ```
+    pub fn blob(data: impl AsRef<[u8]>, mime_type: impl Into<String>) -> Self {
+        Self::Blob {
+            data: encode_base64(data.as_ref()),
+            mime_type: mime_type.into(),
+        }
+    }
+}
+
+/// Standard base64 encoding of `data`.
+#[cfg(feature = "simd")]
+fn encode_base64(data: &[u8]) -> String {
+    crate::simd::base64_encode(data)
+}
+
+/// Standard base64 encoding of `data`.
+#[cfg(not(feature = "simd"))]
+fn encode_base64(data: &[u8]) -> String {
+    use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+    STANDARD.encode(data)
+}
+
 /// List prompts request.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -235,6 +445,9 @@ pub struct GetPromptRequest {
     /// Prompt arguments
     #[serde(default)]
     pub arguments: HashMap<String, String>,
+    /// Request metadata
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<RequestMeta>,
 }
 
 /// Get prompt params (legacy name).
@@ -252,13 +465,81 @@ pub struct GetPromptResult {
 }
 
 /// Message in a prompt.
+///
+/// A message may carry several content blocks, e.g. instructions alongside an image.
+/// A single block is sent as a `content` object, as the spec requires; several blocks
+/// are sent as an array. Both forms are accepted when reading.
+///
+/// # Examples
+///
+/// ```rust
+/// use pmcp::types::{Content, PromptMessage, Role};
+///
+/// let message = PromptMessage::multi_part(
+///     Role::User,
+///     vec![
+///         Content::Text { text: "What is in this diagram?".to_string() },
+///         Content::Image { data: "iVBORw0KGgo=".to_string(), mime_type: "image/png".to_string() },
+///     ],
+/// );
+/// let json = serde_json::to_value(&message).unwrap();
+/// assert_eq!(json["content"][1]["type"], "image");
+/// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PromptMessage {
     /// Message role
     pub role: Role,
-    /// Message content
-    pub content: MessageContent,
+    /// Message content blocks
+    #[serde(with = "one_or_many")]
+    pub content: Vec<MessageContent>,
+}
+
+impl PromptMessage {
+    /// Create a message with a single content block.
+    pub fn new(role: Role, content: MessageContent) -> Self {
+        Self {
+            role,
+            content: vec![content],
+        }
+    }
+
+    /// Create a message with several content blocks.
+    pub fn multi_part(role: Role, content: Vec<MessageContent>) -> Self {
+        Self { role, content }
+    }
+}
+
+/// Serde for content that is a single block or an array of blocks.
+mod one_or_many {
+    use super::Content;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(
+        content: &[Content],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match content {
+            [single] => single.serialize(serializer),
+            _ => content.serialize(serializer),
+        }
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Content>, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum OneOrMany {
+            One(Content),
+            Many(Vec<Content>),
+        }
+
+        Ok(match OneOrMany::deserialize(deserializer)? {
+            OneOrMany::One(content) => vec![content],
+            OneOrMany::Many(content) => content,
+        })
+    }
 }
 
 /// Message role.
@@ -290,6 +571,9 @@ pub struct ListResourcesRequest {
     /// Pagination cursor
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cursor: Cursor,
+    /// Request metadata
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<RequestMeta>,
 }
 
 /// List resources params (legacy name).
@@ -328,6 +612,19 @@ pub struct ListResourcesResult {
 pub struct ReadResourceRequest {
     /// Resource URI
     pub uri: String,
+    /// Request metadata
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<RequestMeta>,
+}
+
+impl ReadResourceRequest {
+    /// Create a read request without metadata.
+    pub fn new(uri: impl Into<String>) -> Self {
+        Self {
+            uri: uri.into(),
+            meta: None,
+        }
+    }
 }
 
 /// Read resource params (legacy name).
@@ -340,6 +637,9 @@ pub struct ListResourceTemplatesRequest {
     /// Pagination cursor
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cursor: Cursor,
+    /// Request metadata
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<RequestMeta>,
 }
 
 /// Resource template.
@@ -393,6 +693,9 @@ pub struct CompleteRequest {
     pub r#ref: CompletionReference,
     /// The argument to complete
     pub argument: CompletionArgument,
+    /// Request metadata
+    #[serde(rename = "_meta", default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<RequestMeta>,
 }
 
 /// Completion reference.
@@ -445,6 +748,43 @@ pub struct CompletionResult {
     pub has_more: bool,
 }
 
+impl CompletionResult {
+    /// Most values a server sends in one result, as recommended by MCP.
+    pub const MAX_VALUES: usize = 100;
+
+    /// Create a result holding every available value.
+    pub fn new(values: Vec<String>) -> Self {
+        Self {
+            total: Some(values.len()),
+            values,
+            has_more: false,
+        }
+    }
+
+    /// Keep at most `max` values, flagging dropped ones with
+    /// [`has_more`](Self::has_more) and counting them in [`total`](Self::total).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::types::CompletionResult;
+    ///
+    /// let values = (0..150).map(|i| format!("item-{}", i)).collect();
+    /// let result = CompletionResult::new(values).truncated(CompletionResult::MAX_VALUES);
+    /// assert_eq!(result.values.len(), 100);
+    /// assert_eq!(result.total, Some(150));
+    /// assert!(result.has_more);
+    /// ```
+    pub fn truncated(mut self, max: usize) -> Self {
+        if self.values.len() > max {
+            self.total = Some(self.total.unwrap_or(0).max(self.values.len()));
+            self.values.truncate(max);
+            self.has_more = true;
+        }
+        self
+    }
+}
+
 /// Logging level.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -467,6 +807,121 @@ pub enum LoggingLevel {
 pub struct ReadResourceResult {
     /// Resource contents
     pub contents: Vec<Content>,
+    /// Hash of the contents (see [`content_hash`]), if the server provides one.
+    ///
+    /// Stable for unchanged contents, so clients can use it to validate cached copies
+    /// without hashing the contents themselves.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    /// Whether the resource still has the hash the client sent in
+    /// `_meta.ifNoneMatch`, in which case `contents` is empty.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub not_modified: bool,
+}
+
+impl ReadResourceResult {
+    /// Create a result without a content hash.
+    pub fn new(contents: Vec<Content>) -> Self {
+        Self {
+            contents,
+            content_hash: None,
+            not_modified: false,
+        }
+    }
+
+    /// Answer a conditional read whose resource still has `content_hash`.
+    pub fn unchanged(content_hash: impl Into<String>) -> Self {
+        Self {
+            contents: vec![],
+            content_hash: Some(content_hash.into()),
+            not_modified: true,
+        }
+    }
+
+    /// Set [`content_hash`](Self::content_hash) from the contents.
+    ///
+    /// The hash covers the text or data of each content in order. A single text
+    /// content hashes the same as its bytes do with [`content_hash`], e.g. the file it
+    /// was read from.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::types::{content_hash, Content, ReadResourceResult};
+    ///
+    /// let result = ReadResourceResult::new(vec![Content::Text {
+    ///     text: "hello".to_string(),
+    /// }])
+    /// .with_content_hash();
+    /// assert_eq!(result.content_hash, Some(content_hash(b"hello")));
+    /// ```
+    pub fn with_content_hash(mut self) -> Self {
+        use sha2::{Digest, Sha256};
+
+        let mut hasher = Sha256::new();
+        for content in &self.contents {
+            match content {
+                Content::Text { text } => hasher.update(text.as_bytes()),
+                Content::Image { data, .. } | Content::Blob { data, .. } => {
+                    hasher.update(data.as_bytes());
+                },
+                Content::Resource { text, .. } => {
+                    hasher.update(text.as_deref().unwrap_or_default().as_bytes());
+                },
+            }
+        }
+        self.content_hash = Some(format_sha256(&hasher.finalize()));
+        self
+    }
+
+    /// Decoded data of the first blob content, or `None` if there is none.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::types::{Content, ReadResourceResult};
+    ///
+    /// let bytes = vec![0x89, b'P', b'N', b'G', 0xFF];
+    /// let result = ReadResourceResult::new(vec![Content::blob(&bytes, "image/png")]);
+    /// assert_eq!(result.blob()?, Some(bytes));
+    /// # Ok::<(), pmcp::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns a parse error if the data is not valid base64.
+    pub fn blob(&self) -> crate::Result<Option<Vec<u8>>> {
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+        let Some(data) = self.contents.iter().find_map(|content| match content {
+            Content::Blob { data, .. } => Some(data),
+            _ => None,
+        }) else {
+            return Ok(None);
+        };
+        STANDARD
+            .decode(data)
+            .map(Some)
+            .map_err(|e| crate::Error::parse(format!("Invalid base64 blob: {}", e)))
+    }
+}
+
+/// Hash of resource content, as `sha256:` followed by the lowercase hex digest.
+pub fn content_hash(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+
+    format_sha256(&Sha256::digest(bytes))
+}
+
+fn format_sha256(digest: &[u8]) -> String {
+    use std::fmt::Write;
+
+    digest
+        .iter()
+        .fold(String::from("sha256:"), |mut hash, byte| {
+            let _ = write!(hash, "{:02x}", byte);
+            hash
+        })
 }
 
 /// Model preferences for sampling.
@@ -576,6 +1031,29 @@ pub enum ClientRequest {
     ElicitInputResponse(crate::types::elicitation::ElicitInputResponse),
 }
 
+impl ClientRequest {
+    /// JSON-RPC method name of this request.
+    pub fn method(&self) -> &'static str {
+        match self {
+            Self::Initialize(_) => "initialize",
+            Self::ListTools(_) => "tools/list",
+            Self::CallTool(_) => "tools/call",
+            Self::ListPrompts(_) => "prompts/list",
+            Self::GetPrompt(_) => "prompts/get",
+            Self::ListResources(_) => "resources/list",
+            Self::ListResourceTemplates(_) => "resources/templates/list",
+            Self::ReadResource(_) => "resources/read",
+            Self::Subscribe(_) => "resources/subscribe",
+            Self::Unsubscribe(_) => "resources/unsubscribe",
+            Self::Complete(_) => "completion/complete",
+            Self::SetLoggingLevel { .. } => "logging/setLevel",
+            Self::Ping => "ping",
+            Self::CreateMessage(_) => "sampling/createMessage",
+            Self::ElicitInputResponse(_) => "elicitation/response",
+        }
+    }
+}
+
 /// Server request types.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "method", content = "params", rename_all = "camelCase")]
@@ -586,9 +1064,12 @@ pub enum ServerRequest {
     /// List roots request
     #[serde(rename = "roots/list")]
     ListRoots,
-    /// Elicit input from user
-    #[serde(rename = "elicitation/elicitInput")]
+    /// Elicit input from user, sent as `elicitation/input`
+    #[serde(rename = "elicitation/elicitInput", alias = "elicitation/input")]
     ElicitInput(Box<crate::types::elicitation::ElicitInputRequest>),
+    /// Ping request
+    #[serde(rename = "ping")]
+    Ping,
 }
 
 /// Create message parameters (for server requests).
@@ -662,23 +1143,18 @@ pub struct SamplingMessage {
 }
 
 /// Context to include in sampling.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum IncludeContext {
     /// Include all context
     All,
     /// Include no context
+    #[default]
     None,
     /// Include specific context types
     ThisServerOnly,
 }
 
-impl Default for IncludeContext {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 /// Client notification types.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "method", content = "params", rename_all = "camelCase")]
@@ -695,6 +1171,9 @@ pub enum ClientNotification {
     /// Progress update
     #[serde(rename = "notifications/progress")]
     Progress(Progress),
+    /// Piece of tool call arguments streamed ahead of the request
+    #[serde(rename = "notifications/tools/argumentChunk")]
+    ArgumentChunk(ArgumentChunk),
 }
 
 /// Cancelled notification.
@@ -736,6 +1215,13 @@ pub enum ServerNotification {
     /// Log message
     #[serde(rename = "notifications/message")]
     LogMessage(LogMessageParams),
+    /// The server's capabilities changed, e.g. because a plugin was loaded
+    ///
+    /// A PMCP extension to MCP: the params are the complete new capabilities, which
+    /// replace those from `initialize` without reconnecting. Clients that do not know
+    /// the method ignore it.
+    #[serde(rename = "notifications/capabilities/changed")]
+    CapabilitiesChanged(ServerCapabilities),
 }
 
 /// Resource updated notification.
@@ -744,6 +1230,75 @@ pub enum ServerNotification {
 pub struct ResourceUpdatedParams {
     /// Resource URI that was updated
     pub uri: String,
+    /// What changed (omitted for a full update)
+    #[serde(default, skip_serializing_if = "ChangeKind::is_full")]
+    pub change: ChangeKind,
+}
+
+impl ResourceUpdatedParams {
+    /// Create params for a full update of `uri`.
+    pub fn new(uri: impl Into<String>) -> Self {
+        Self {
+            uri: uri.into(),
+            change: ChangeKind::Full,
+        }
+    }
+}
+
+/// How a subscribed resource changed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "patch", rename_all = "camelCase")]
+pub enum ChangeKind {
+    /// The resource changed and should be re-read
+    #[default]
+    Full,
+    /// The resource was deleted
+    Deleted,
+    /// The resource changed by the given JSON Patch (RFC 6902)
+    Patch(Vec<crate::utils::json_patch::PatchOperation>),
+}
+
+impl ChangeKind {
+    /// Whether this is a full update.
+    pub fn is_full(&self) -> bool {
+        matches!(self, Self::Full)
+    }
+
+    /// Apply this change to a cached copy of the resource.
+    ///
+    /// Returns `Ok(true)` if `document` is now up to date, or `Ok(false)` for a
+    /// [`ChangeKind::Full`] update, where the resource must be read again.
+    /// A deleted resource becomes `Value::Null`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pmcp::types::protocol::ChangeKind;
+    /// use serde_json::json;
+    ///
+    /// let change: ChangeKind = serde_json::from_value(json!({
+    ///     "kind": "patch",
+    ///     "patch": [{"op": "replace", "path": "/status", "value": "done"}]
+    /// }))?;
+    ///
+    /// let mut cached = json!({"status": "running"});
+    /// assert!(change.apply(&mut cached)?);
+    /// assert_eq!(cached, json!({"status": "done"}));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn apply(&self, document: &mut Value) -> crate::Result<bool> {
+        match self {
+            Self::Full => Ok(false),
+            Self::Deleted => {
+                *document = Value::Null;
+                Ok(true)
+            },
+            Self::Patch(patch) => {
+                crate::utils::json_patch::apply_patch(document, patch)?;
+                Ok(true)
+            },
+        }
+    }
 }
 
 /// Log message notification.
@@ -826,6 +1381,127 @@ mod tests {
         assert_eq!(json["text"], "Hello");
     }
 
+    #[test]
+    fn completion_result_truncation() {
+        let values = |n: usize| (0..n).map(|i| i.to_string()).collect::<Vec<_>>();
+
+        let result = CompletionResult::new(values(3)).truncated(3);
+        assert_eq!(result.values, values(3));
+        assert_eq!(result.total, Some(3));
+        assert!(!result.has_more);
+
+        let result = CompletionResult::new(values(5)).truncated(2);
+        assert_eq!(result.values, values(2));
+        assert_eq!(result.total, Some(5));
+        assert!(result.has_more);
+
+        // A handler's own larger total is kept
+        let result = CompletionResult {
+            values: values(5),
+            total: Some(40),
+            has_more: true,
+        }
+        .truncated(2);
+        assert_eq!(result.total, Some(40));
+        assert!(result.has_more);
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(
+            json,
+            json!({"values": ["0", "1"], "total": 40, "hasMore": true})
+        );
+    }
+
+    fn tool_result(content: Vec<Content>) -> CallToolResult {
+        CallToolResult {
+            content,
+            is_error: false,
+            meta: None,
+        }
+    }
+
+    #[test]
+    fn call_tool_result_text_skips_other_content() {
+        let result = tool_result(vec![
+            Content::Text {
+                text: "one".to_string(),
+            },
+            Content::Resource {
+                uri: "file:///a".to_string(),
+                text: Some("not included".to_string()),
+                mime_type: None,
+            },
+            Content::Text {
+                text: "two".to_string(),
+            },
+        ]);
+        assert_eq!(result.text().as_deref(), Some("one\ntwo"));
+
+        let images_only = tool_result(vec![Content::Image {
+            data: "cG5n".to_string(),
+            mime_type: "image/png".to_string(),
+        }]);
+        assert_eq!(images_only.text(), None);
+        assert_eq!(tool_result(vec![]).text(), None);
+    }
+
+    #[test]
+    fn call_tool_result_json_parses_first_text() {
+        let result = tool_result(vec![
+            Content::Image {
+                data: "cG5n".to_string(),
+                mime_type: "image/png".to_string(),
+            },
+            Content::Text {
+                text: r#"{"sum": 5}"#.to_string(),
+            },
+            Content::Text {
+                text: "[1, 2]".to_string(),
+            },
+        ]);
+        assert_eq!(result.json(), Some(json!({"sum": 5})));
+
+        let not_json = tool_result(vec![
+            Content::Text {
+                text: "sum is 5".to_string(),
+            },
+            Content::Text {
+                text: "5".to_string(),
+            },
+        ]);
+        assert_eq!(not_json.json(), None);
+        assert_eq!(tool_result(vec![]).json(), None);
+    }
+
+    #[test]
+    fn call_tool_result_images_decodes_base64() {
+        let result = tool_result(vec![
+            Content::Image {
+                data: "cG5n".to_string(),
+                mime_type: "image/png".to_string(),
+            },
+            Content::Text {
+                text: "caption".to_string(),
+            },
+            Content::Image {
+                data: "not base64!".to_string(),
+                mime_type: "image/jpeg".to_string(),
+            },
+            Content::Image {
+                data: "Z2lm".to_string(),
+                mime_type: "image/gif".to_string(),
+            },
+        ]);
+        assert_eq!(
+            result.images(),
+            vec![
+                (b"png".to_vec(), "image/png".to_string()),
+                (b"gif".to_vec(), "image/gif".to_string()),
+            ]
+        );
+        assert!(tool_result(vec![]).images().is_empty());
+    }
+
     #[test]
     fn tool_info_serialization() {
         let tool = ToolInfo {
@@ -837,6 +1513,9 @@ mod tests {
                     "param": {"type": "string"}
                 }
             }),
+            category: None,
+            tags: Vec::new(),
+            output_schema: None,
         };
 
         let json = serde_json::to_value(&tool).unwrap();
@@ -845,6 +1524,93 @@ mod tests {
         assert_eq!(json["inputSchema"]["type"], "object");
     }
 
+    #[test]
+    fn tool_info_category_and_tags_round_trip() {
+        let tool = ToolInfo {
+            name: "grep".to_string(),
+            description: None,
+            input_schema: json!({}),
+            category: Some("search".to_string()),
+            tags: vec!["files".to_string(), "text".to_string()],
+            output_schema: None,
+        };
+
+        let json = serde_json::to_value(&tool).unwrap();
+        assert_eq!(json["category"], "search");
+        assert_eq!(json["tags"], json!(["files", "text"]));
+
+        let parsed: ToolInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.category.as_deref(), Some("search"));
+        assert_eq!(parsed.tags, vec!["files", "text"]);
+
+        // Both fields are optional on the wire
+        let plain: ToolInfo =
+            serde_json::from_value(json!({"name": "plain", "inputSchema": {}})).unwrap();
+        assert!(plain.category.is_none());
+        assert!(plain.tags.is_empty());
+        let json = serde_json::to_value(&plain).unwrap();
+        assert!(json.get("category").is_none());
+        assert!(json.get("tags").is_none());
+    }
+
+    #[test]
+    fn call_tool_result_meta_round_trip() {
+        let result = CallToolResult {
+            content: vec![Content::Text {
+                text: "done".to_string(),
+            }],
+            is_error: false,
+            meta: Some(json!({"model": "small", "elapsedMs": 42})),
+        };
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["_meta"]["model"], "small");
+        let parsed: CallToolResult = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.meta, result.meta);
+
+        let plain: CallToolResult = serde_json::from_value(json!({"content": []})).unwrap();
+        assert!(plain.meta.is_none());
+        assert!(serde_json::to_value(&plain).unwrap().get("_meta").is_none());
+    }
+
+    #[test]
+    fn call_tool_request_deadline_round_trip() {
+        let json = json!({
+            "name": "slow",
+            "arguments": {},
+            "_meta": {"deadline": "2025-01-01T00:00:00Z"}
+        });
+        let request: CallToolRequest = serde_json::from_value(json.clone()).unwrap();
+        let deadline = request
+            .meta
+            .as_ref()
+            .and_then(|meta| meta.deadline)
+            .unwrap();
+        assert_eq!(deadline.to_rfc3339(), "2025-01-01T00:00:00+00:00");
+        assert_eq!(
+            serde_json::to_value(&request).unwrap()["_meta"]["deadline"],
+            "2025-01-01T00:00:00Z"
+        );
+
+        let plain = CallToolRequest::new("slow", json!({}));
+        assert!(serde_json::to_value(&plain).unwrap().get("_meta").is_none());
+    }
+
+    #[test]
+    fn client_request_method_matches_wire_name() {
+        let requests = vec![
+            ClientRequest::Ping,
+            ClientRequest::ListTools(ListToolsRequest { cursor: None }),
+            ClientRequest::SetLoggingLevel {
+                level: LoggingLevel::Info,
+            },
+        ];
+        for request in requests {
+            let json = serde_json::to_value(&request).unwrap();
+            assert_eq!(json["method"], request.method());
+        }
+    }
+
     #[test]
     fn test_all_notification_types() {
         let progress = ServerNotification::Progress(ProgressNotification {
@@ -871,11 +1637,11 @@ mod tests {
         let json = serde_json::to_value(&roots_changed).unwrap();
         assert_eq!(json["method"], "notifications/roots/list_changed");
 
-        let resource_updated = ServerNotification::ResourceUpdated(ResourceUpdatedParams {
-            uri: "file://test.txt".to_string(),
-        });
+        let resource_updated =
+            ServerNotification::ResourceUpdated(ResourceUpdatedParams::new("file://test.txt"));
         let json = serde_json::to_value(&resource_updated).unwrap();
         assert_eq!(json["method"], "notifications/resources/updated");
+        assert!(json["params"].get("change").is_none());
 
         let log_msg = ServerNotification::LogMessage(LogMessageParams {
             level: LogLevel::Info,
@@ -903,6 +1669,58 @@ mod tests {
         assert_eq!(json["mimeType"], "text/plain");
     }
 
+    #[test]
+    fn test_read_resource_content_hash() {
+        let text = |text: &str| Content::Text {
+            text: text.to_string(),
+        };
+        let result = ReadResourceResult::new(vec![text("hello")]);
+        let json = serde_json::to_value(&result).unwrap();
+        assert!(json.get("contentHash").is_none());
+
+        let result = result.with_content_hash();
+        let hash = result.content_hash.clone().unwrap();
+        assert_eq!(
+            hash,
+            "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_ne!(
+            ReadResourceResult::new(vec![text("hello!")])
+                .with_content_hash()
+                .content_hash,
+            Some(hash.clone())
+        );
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["contentHash"], hash);
+        let parsed: ReadResourceResult = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.content_hash, Some(hash));
+    }
+
+    #[test]
+    fn test_blob_content_round_trips() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let result =
+            ReadResourceResult::new(vec![Content::blob(&bytes, "application/octet-stream")]);
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["contents"][0]["type"], "blob");
+        assert_eq!(json["contents"][0]["mimeType"], "application/octet-stream");
+        let parsed: ReadResourceResult = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.blob().unwrap(), Some(bytes));
+
+        let text = ReadResourceResult::new(vec![Content::Text {
+            text: "hello".to_string(),
+        }]);
+        assert_eq!(text.blob().unwrap(), None);
+
+        let invalid = ReadResourceResult::new(vec![Content::Blob {
+            data: "not base64!".to_string(),
+            mime_type: "application/octet-stream".to_string(),
+        }]);
+        assert!(invalid.blob().is_err());
+    }
+
     #[test]
     fn test_prompt_types() {
         let prompt = PromptInfo {
@@ -922,6 +1740,43 @@ mod tests {
         assert_eq!(json["arguments"][0]["required"], true);
     }
 
+    #[test]
+    fn test_mixed_content_prompt_message_round_trips() {
+        let message = PromptMessage::multi_part(
+            Role::User,
+            vec![
+                Content::Text {
+                    text: "Describe this image".to_string(),
+                },
+                Content::Image {
+                    data: "iVBORw0KGgo=".to_string(),
+                    mime_type: "image/png".to_string(),
+                },
+            ],
+        );
+
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(json["content"][0]["type"], "text");
+        assert_eq!(json["content"][1]["mimeType"], "image/png");
+        let parsed: PromptMessage = serde_json::from_value(json).unwrap();
+        assert!(matches!(
+            parsed.content.as_slice(),
+            [Content::Text { .. }, Content::Image { mime_type, .. }] if mime_type == "image/png"
+        ));
+
+        // A single block keeps the spec's object form, in both directions
+        let single = PromptMessage::new(
+            Role::Assistant,
+            Content::Text {
+                text: "Hi".to_string(),
+            },
+        );
+        let json = serde_json::to_value(&single).unwrap();
+        assert_eq!(json["content"], json!({"type": "text", "text": "Hi"}));
+        let parsed: PromptMessage = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.content.len(), 1);
+    }
+
     #[test]
     fn test_log_levels() {
         assert_eq!(serde_json::to_value(LogLevel::Debug).unwrap(), "debug");
@@ -0,0 +1,236 @@
+//! Minimal JSON Patch (RFC 6902) support.
+//!
+//! Used by `notifications/resources/updated` to describe incremental resource
+//! changes, so clients can update a cached copy instead of re-reading it.
+
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A single JSON Patch operation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    /// Add a value at `path`
+    Add {
+        /// JSON Pointer to the target location
+        path: String,
+        /// Value to add
+        value: Value,
+    },
+    /// Remove the value at `path`
+    Remove {
+        /// JSON Pointer to the target location
+        path: String,
+    },
+    /// Replace the value at `path`
+    Replace {
+        /// JSON Pointer to the target location
+        path: String,
+        /// Replacement value
+        value: Value,
+    },
+    /// Move the value at `from` to `path`
+    Move {
+        /// JSON Pointer to the source location
+        from: String,
+        /// JSON Pointer to the target location
+        path: String,
+    },
+    /// Copy the value at `from` to `path`
+    Copy {
+        /// JSON Pointer to the source location
+        from: String,
+        /// JSON Pointer to the target location
+        path: String,
+    },
+    /// Check that the value at `path` equals `value`
+    Test {
+        /// JSON Pointer to the target location
+        path: String,
+        /// Expected value
+        value: Value,
+    },
+}
+
+/// Apply a JSON Patch to a document.
+///
+/// Operations are applied in order. If any operation fails the document is left
+/// unchanged and an error is returned.
+///
+/// # Examples
+///
+/// ```rust
+/// use pmcp::utils::json_patch::{apply_patch, PatchOperation};
+/// use serde_json::json;
+///
+/// let mut doc = json!({"count": 1});
+/// apply_patch(&mut doc, &[PatchOperation::Replace {
+///     path: "/count".to_string(),
+///     value: json!(2),
+/// }])?;
+/// assert_eq!(doc, json!({"count": 2}));
+/// # Ok::<(), pmcp::Error>(())
+/// ```
+pub fn apply_patch(document: &mut Value, patch: &[PatchOperation]) -> Result<()> {
+    let mut patched = document.clone();
+    for operation in patch {
+        apply_operation(&mut patched, operation)?;
+    }
+    *document = patched;
+    Ok(())
+}
+
+fn apply_operation(document: &mut Value, operation: &PatchOperation) -> Result<()> {
+    match operation {
+        PatchOperation::Add { path, value } => add(document, path, value.clone()),
+        PatchOperation::Remove { path } => remove(document, path).map(drop),
+        PatchOperation::Replace { path, value } => {
+            let target = document
+                .pointer_mut(path)
+                .ok_or_else(|| path_not_found(path))?;
+            *target = value.clone();
+            Ok(())
+        },
+        PatchOperation::Move { from, path } => {
+            if path.starts_with(&format!("{}/", from)) {
+                return Err(Error::invalid_params(format!(
+                    "Cannot move '{}' into its own child '{}'",
+                    from, path
+                )));
+            }
+            let value = remove(document, from)?;
+            add(document, path, value)
+        },
+        PatchOperation::Copy { from, path } => {
+            let value = document
+                .pointer(from)
+                .cloned()
+                .ok_or_else(|| path_not_found(from))?;
+            add(document, path, value)
+        },
+        PatchOperation::Test { path, value } => match document.pointer(path) {
+            Some(actual) if actual == value => Ok(()),
+            Some(_) => Err(Error::invalid_params(format!(
+                "Patch test failed at '{}'",
+                path
+            ))),
+            None => Err(path_not_found(path)),
+        },
+    }
+}
+
+fn add(document: &mut Value, path: &str, value: Value) -> Result<()> {
+    if path.is_empty() {
+        *document = value;
+        return Ok(());
+    }
+    let (parent, key) = split_pointer(path)?;
+    match document.pointer_mut(parent) {
+        Some(Value::Object(map)) => {
+            map.insert(key, value);
+            Ok(())
+        },
+        Some(Value::Array(items)) => {
+            let index = if key == "-" {
+                items.len()
+            } else {
+                parse_index(&key, items.len() + 1, path)?
+            };
+            items.insert(index, value);
+            Ok(())
+        },
+        _ => Err(path_not_found(path)),
+    }
+}
+
+fn remove(document: &mut Value, path: &str) -> Result<Value> {
+    let (parent, key) = split_pointer(path)?;
+    match document.pointer_mut(parent) {
+        Some(Value::Object(map)) => map.remove(&key).ok_or_else(|| path_not_found(path)),
+        Some(Value::Array(items)) => {
+            let index = parse_index(&key, items.len(), path)?;
+            Ok(items.remove(index))
+        },
+        _ => Err(path_not_found(path)),
+    }
+}
+
+/// Split a JSON Pointer into its parent pointer and unescaped last token.
+fn split_pointer(path: &str) -> Result<(&str, String)> {
+    let slash = path
+        .rfind('/')
+        .ok_or_else(|| Error::invalid_params(format!("Invalid JSON Pointer '{}'", path)))?;
+    let key = path[slash + 1..].replace("~1", "/").replace("~0", "~");
+    Ok((&path[..slash], key))
+}
+
+fn parse_index(key: &str, len: usize, path: &str) -> Result<usize> {
+    match key.parse::<usize>() {
+        Ok(index) if index < len && (key == "0" || !key.starts_with('0')) => Ok(index),
+        _ => Err(path_not_found(path)),
+    }
+}
+
+fn path_not_found(path: &str) -> Error {
+    Error::invalid_params(format!("Patch path '{}' does not exist", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn patch(operations: Value) -> Vec<PatchOperation> {
+        serde_json::from_value(operations).unwrap()
+    }
+
+    #[test]
+    fn test_apply_rfc6902_operations() {
+        let mut doc = json!({"a": {"b": 1}, "list": [1, 2, 3], "x/y": true});
+        let operations = patch(json!([
+            {"op": "add", "path": "/a/c", "value": 2},
+            {"op": "remove", "path": "/list/0"},
+            {"op": "add", "path": "/list/-", "value": 4},
+            {"op": "replace", "path": "/x~1y", "value": false},
+            {"op": "copy", "from": "/a/b", "path": "/copied"},
+            {"op": "move", "from": "/a/c", "path": "/moved"},
+            {"op": "test", "path": "/moved", "value": 2}
+        ]));
+
+        apply_patch(&mut doc, &operations).unwrap();
+        assert_eq!(
+            doc,
+            json!({"a": {"b": 1}, "list": [2, 3, 4], "x/y": false, "copied": 1, "moved": 2})
+        );
+    }
+
+    #[test]
+    fn test_failed_patch_leaves_document_unchanged() {
+        let original = json!({"a": 1});
+        let mut doc = original.clone();
+        let operations = patch(json!([
+            {"op": "replace", "path": "/a", "value": 2},
+            {"op": "test", "path": "/a", "value": 3}
+        ]));
+
+        assert!(apply_patch(&mut doc, &operations).is_err());
+        assert_eq!(doc, original);
+
+        let missing = patch(json!([{"op": "remove", "path": "/missing"}]));
+        assert!(apply_patch(&mut doc, &missing).is_err());
+        let out_of_bounds = patch(json!([{"op": "add", "path": "/a/0", "value": 1}]));
+        assert!(apply_patch(&mut doc, &out_of_bounds).is_err());
+    }
+
+    #[test]
+    fn test_replace_whole_document() {
+        let mut doc = json!({"a": 1});
+        apply_patch(
+            &mut doc,
+            &patch(json!([{"op": "add", "path": "", "value": [1]}])),
+        )
+        .unwrap();
+        assert_eq!(doc, json!([1]));
+    }
+}
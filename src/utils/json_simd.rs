@@ -1,28 +1,24 @@
 // High-performance JSON parsing with SIMD acceleration
 // Falls back to standard serde_json when SIMD is not available
 
-#![allow(unsafe_code)]
-
 use serde::{Deserialize, Serialize};
 use serde_json::{Error as JsonError, Value};
 
 /// Parse JSON with SIMD acceleration when available
-#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+#[cfg(feature = "simd")]
 pub fn parse_json_fast<T: for<'de> Deserialize<'de>>(input: &[u8]) -> Result<T, JsonError> {
     // Runtime feature detection for AVX2
-    if is_x86_feature_detected!("avx2") {
+    if crate::simd::avx2_available() {
         // First validate UTF-8 using SIMD
-        unsafe {
-            if !crate::simd::json::validate_utf8_simd(input) {
-                return Err(serde_json::Error::io(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "Invalid UTF-8",
-                )));
-            }
+        if !crate::simd::validate_utf8(input) {
+            return Err(serde_json::Error::io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Invalid UTF-8",
+            )));
         }
 
         // Use optimized whitespace skipping
-        let ws_positions = unsafe { crate::simd::json::find_whitespace_simd(input) };
+        let ws_positions = crate::simd::find_whitespace(input);
 
         // If there's minimal whitespace, parse directly
         if ws_positions.len() < input.len() / 10 {
@@ -69,26 +65,24 @@ pub fn parse_json_fast<T: for<'de> Deserialize<'de>>(input: &[u8]) -> Result<T,
 }
 
 /// Parse JSON - fallback for non-SIMD platforms
-#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+#[cfg(not(feature = "simd"))]
 pub fn parse_json_fast<T: for<'de> Deserialize<'de>>(input: &[u8]) -> Result<T, JsonError> {
     serde_json::from_slice(input)
 }
 
 /// Serialize JSON with SIMD acceleration when available
-#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+#[cfg(feature = "simd")]
 pub fn serialize_json_fast<T: Serialize>(value: &T) -> Result<Vec<u8>, JsonError> {
     let json = serde_json::to_vec(value)?;
 
     // Runtime feature detection
-    if is_x86_feature_detected!("avx2") {
+    if crate::simd::avx2_available() {
         // Use SIMD to validate output
-        unsafe {
-            if !crate::simd::json::validate_utf8_simd(&json) {
-                return Err(serde_json::Error::io(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "Generated invalid UTF-8",
-                )));
-            }
+        if !crate::simd::validate_utf8(&json) {
+            return Err(serde_json::Error::io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Generated invalid UTF-8",
+            )));
         }
     }
 
@@ -96,7 +90,7 @@ pub fn serialize_json_fast<T: Serialize>(value: &T) -> Result<Vec<u8>, JsonError
 }
 
 /// Serialize JSON - fallback for non-SIMD platforms
-#[cfg(not(all(feature = "simd", target_arch = "x86_64")))]
+#[cfg(not(feature = "simd"))]
 pub fn serialize_json_fast<T: Serialize>(value: &T) -> Result<Vec<u8>, JsonError> {
     serde_json::to_vec(value)
 }
@@ -111,13 +105,13 @@ pub fn parse_json_batch<T: for<'de> Deserialize<'de>>(
 
 /// Fast JSON pretty printing
 pub fn pretty_print_fast(value: &Value) -> Result<String, JsonError> {
-    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    #[cfg(feature = "simd")]
     {
-        if is_x86_feature_detected!("avx2") {
+        if crate::simd::avx2_available() {
             let compact = serde_json::to_vec(value)?;
 
             // Find all structure points using SIMD
-            let escapes = unsafe { crate::simd::json::find_escapes_simd(&compact) };
+            let escapes = crate::simd::find_escapes(&compact);
 
             // Allocate with estimated size
             let mut result = String::with_capacity(compact.len() * 2);
@@ -1,6 +1,7 @@
 //! Utility modules for the MCP SDK.
 
 pub mod batching;
+pub mod json_patch;
 pub mod parallel_batch;
 pub mod validation;
 
@@ -9,6 +10,7 @@ pub mod validation;
 pub mod json_simd;
 
 pub use batching::{BatchingConfig, DebouncingConfig, MessageBatcher, MessageDebouncer};
+pub use json_patch::{apply_patch, PatchOperation};
 pub use parallel_batch::{
     process_batch_parallel, process_batch_parallel_stateful, BatchProcessor, ParallelBatchConfig,
 };
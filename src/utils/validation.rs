@@ -1,3 +1,491 @@
 //! Validation utilities for MCP protocol.
 //!
-//! This module is reserved for future validation functionality.
+//! Provides a lightweight JSON Schema checker covering the subset of keywords that
+//! tool schemas use in practice:
+//!
+//! - `type`, `enum` and `const`
+//! - `properties`, `required` and `additionalProperties` for objects
+//! - `items`, `minItems` and `maxItems` for arrays
+//! - `minLength` and `maxLength` for strings, `minimum` and `maximum` for numbers
+//! - the `allOf`, `anyOf` and `oneOf` combinators
+//! - local `$ref`s into `$defs` or `definitions`
+//!
+//! The annotations `$schema`, `$id`, `$comment`, `title`, `description`, `default`,
+//! `examples`, `deprecated`, `readOnly`, `writeOnly` and `format` are allowed and
+//! have no effect. A schema using any other keyword is rejected rather than only
+//! partly enforced.
+
+use crate::error::{Error, Result};
+use serde_json::Value;
+
+/// Validate `value` against a JSON Schema.
+///
+/// All violations are collected and reported together in a single
+/// [`Error::Validation`], each prefixed with the JSON Pointer of the offending value.
+/// A schema using keywords this checker does not enforce (see the
+/// [module documentation](self)) fails validation for any value.
+///
+/// # Examples
+///
+/// ```rust
+/// use pmcp::utils::validation::validate_json_schema;
+/// use serde_json::json;
+///
+/// let schema = json!({
+///     "type": "object",
+///     "properties": { "count": { "type": "integer", "minimum": 0 } },
+///     "required": ["count"]
+/// });
+///
+/// assert!(validate_json_schema(&schema, &json!({"count": 3})).is_ok());
+/// assert!(validate_json_schema(&schema, &json!({"count": -1})).is_err());
+/// assert!(validate_json_schema(&schema, &json!({})).is_err());
+///
+/// let unsupported = json!({ "type": "string", "pattern": "^[a-z]+$" });
+/// assert!(validate_json_schema(&unsupported, &json!("abc")).is_err());
+/// ```
+pub fn validate_json_schema(schema: &Value, value: &Value) -> Result<()> {
+    let errors = schema_errors(schema, value);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::validation(errors.join("; ")))
+    }
+}
+
+/// Collect every violation of `schema` by `value`, without failing fast.
+///
+/// If `schema` uses unsupported keywords, those are reported instead.
+pub fn schema_errors(schema: &Value, value: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    check_keywords(schema, "", &mut errors);
+    if errors.is_empty() {
+        check(schema, schema, value, "", &mut errors);
+    }
+    errors
+}
+
+/// Keywords that constrain values, all enforced by [`check`]
+const ENFORCED_KEYWORDS: &[&str] = &[
+    "type",
+    "enum",
+    "const",
+    "properties",
+    "required",
+    "additionalProperties",
+    "items",
+    "minItems",
+    "maxItems",
+    "minLength",
+    "maxLength",
+    "minimum",
+    "maximum",
+    "allOf",
+    "anyOf",
+    "oneOf",
+    "$ref",
+    "$defs",
+    "definitions",
+];
+
+/// Keywords that only annotate a schema
+const ANNOTATION_KEYWORDS: &[&str] = &[
+    "$schema",
+    "$id",
+    "$comment",
+    "title",
+    "description",
+    "default",
+    "examples",
+    "deprecated",
+    "readOnly",
+    "writeOnly",
+    "format",
+];
+
+/// Report the keywords of `schema` and its subschemas that are not supported, with
+/// the JSON Pointer of the schema using them.
+fn check_keywords(schema: &Value, path: &str, errors: &mut Vec<String>) {
+    let Value::Object(schema) = schema else {
+        return;
+    };
+    for (keyword, value) in schema {
+        if !ENFORCED_KEYWORDS.contains(&keyword.as_str())
+            && !ANNOTATION_KEYWORDS.contains(&keyword.as_str())
+        {
+            errors.push(format!(
+                "schema {}: unsupported keyword '{}'",
+                display(path),
+                keyword
+            ));
+            continue;
+        }
+        let path = format!("{}/{}", path, keyword);
+        match keyword.as_str() {
+            "properties" | "$defs" | "definitions" => {
+                for (name, sub) in value.as_object().into_iter().flatten() {
+                    check_keywords(sub, &format!("{}/{}", path, name), errors);
+                }
+            },
+            "allOf" | "anyOf" | "oneOf" => {
+                for (index, sub) in value.as_array().into_iter().flatten().enumerate() {
+                    check_keywords(sub, &format!("{}/{}", path, index), errors);
+                }
+            },
+            "items" | "additionalProperties" => check_keywords(value, &path, errors),
+            _ => {},
+        }
+    }
+}
+
+fn check(root: &Value, schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let schema = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => {
+            errors.push(format!("{}: no value is allowed here", display(path)));
+            return;
+        },
+        Value::Object(schema) => schema,
+        _ => return,
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        match resolve_ref(root, reference) {
+            Some(target) => check(root, target, value, path, errors),
+            None => errors.push(format!(
+                "{}: unresolvable reference {}",
+                display(path),
+                reference
+            )),
+        }
+    }
+
+    if let Some(expected) = schema.get("type") {
+        let matches = match expected {
+            Value::String(name) => has_type(value, name),
+            Value::Array(names) => names
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|name| has_type(value, name)),
+            _ => true,
+        };
+        if !matches {
+            errors.push(format!(
+                "{}: expected {}, got {}",
+                display(path),
+                type_list(expected),
+                type_name(value)
+            ));
+            // Further keywords would only produce noise for a value of the wrong type
+            return;
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            errors.push(format!(
+                "{}: {} is not one of {:?}",
+                display(path),
+                value,
+                allowed
+            ));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            errors.push(format!("{}: expected {}", display(path), expected));
+        }
+    }
+
+    check_combinators(root, schema, value, path, errors);
+
+    match value {
+        Value::Object(object) => check_object(root, schema, object, path, errors),
+        Value::Array(items) => check_array(root, schema, items, path, errors),
+        Value::String(text) => {
+            let length = text.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                if length < min {
+                    errors.push(format!(
+                        "{}: shorter than {} characters",
+                        display(path),
+                        min
+                    ));
+                }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                if length > max {
+                    errors.push(format!("{}: longer than {} characters", display(path), max));
+                }
+            }
+        },
+        Value::Number(number) => {
+            let Some(number) = number.as_f64() else {
+                return;
+            };
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+                if number < min {
+                    errors.push(format!(
+                        "{}: {} is less than {}",
+                        display(path),
+                        number,
+                        min
+                    ));
+                }
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+                if number > max {
+                    errors.push(format!(
+                        "{}: {} is greater than {}",
+                        display(path),
+                        number,
+                        max
+                    ));
+                }
+            }
+        },
+        _ => {},
+    }
+}
+
+fn check_combinators(
+    root: &Value,
+    schema: &serde_json::Map<String, Value>,
+    value: &Value,
+    path: &str,
+    errors: &mut Vec<String>,
+) {
+    if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+        for sub in all {
+            check(root, sub, value, path, errors);
+        }
+    }
+    if let Some(any) = schema.get("anyOf").and_then(Value::as_array) {
+        let matched = any.iter().any(|sub| {
+            let mut sub_errors = Vec::new();
+            check(root, sub, value, path, &mut sub_errors);
+            sub_errors.is_empty()
+        });
+        if !matched {
+            errors.push(format!(
+                "{}: does not match any allowed schema",
+                display(path)
+            ));
+        }
+    }
+    if let Some(one) = schema.get("oneOf").and_then(Value::as_array) {
+        let matched = one
+            .iter()
+            .filter(|sub| {
+                let mut sub_errors = Vec::new();
+                check(root, sub, value, path, &mut sub_errors);
+                sub_errors.is_empty()
+            })
+            .count();
+        if matched != 1 {
+            errors.push(format!(
+                "{}: matches {} schemas, expected exactly one",
+                display(path),
+                matched
+            ));
+        }
+    }
+}
+
+fn check_object(
+    root: &Value,
+    schema: &serde_json::Map<String, Value>,
+    object: &serde_json::Map<String, Value>,
+    path: &str,
+    errors: &mut Vec<String>,
+) {
+    if let Some(required) = schema.get("required").and_then(Value::as_array) {
+        for name in required.iter().filter_map(Value::as_str) {
+            if !object.contains_key(name) {
+                errors.push(format!(
+                    "{}: missing required property '{}'",
+                    display(path),
+                    name
+                ));
+            }
+        }
+    }
+
+    let properties = schema.get("properties").and_then(Value::as_object);
+    for (name, property) in object {
+        let child = format!("{}/{}", path, name);
+        match properties.and_then(|properties| properties.get(name)) {
+            Some(property_schema) => check(root, property_schema, property, &child, errors),
+            None => match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    errors.push(format!("{}: unexpected property '{}'", display(path), name));
+                },
+                Some(additional) => check(root, additional, property, &child, errors),
+                None => {},
+            },
+        }
+    }
+}
+
+fn check_array(
+    root: &Value,
+    schema: &serde_json::Map<String, Value>,
+    items: &[Value],
+    path: &str,
+    errors: &mut Vec<String>,
+) {
+    if let Some(item_schema) = schema.get("items") {
+        for (index, item) in items.iter().enumerate() {
+            check(
+                root,
+                item_schema,
+                item,
+                &format!("{}/{}", path, index),
+                errors,
+            );
+        }
+    }
+    if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+        if (items.len() as u64) < min {
+            errors.push(format!("{}: fewer than {} items", display(path), min));
+        }
+    }
+    if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+        if items.len() as u64 > max {
+            errors.push(format!("{}: more than {} items", display(path), max));
+        }
+    }
+}
+
+fn resolve_ref<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    let pointer = reference.strip_prefix('#')?;
+    root.pointer(pointer)
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        },
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn type_list(expected: &Value) -> String {
+    match expected {
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" or "),
+        other => other.as_str().unwrap_or_default().to_string(),
+    }
+}
+
+fn display(path: &str) -> &str {
+    if path.is_empty() {
+        "/"
+    } else {
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reports_every_violation_with_its_path() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string", "minLength": 1 },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["name", "id"],
+            "additionalProperties": false
+        });
+
+        let errors = schema_errors(&schema, &json!({"name": "", "tags": ["a", 2], "x": 1}));
+        assert_eq!(errors.len(), 4, "{:?}", errors);
+        assert!(errors.iter().any(|e| e.contains("'id'")));
+        assert!(errors.iter().any(|e| e.starts_with("/name:")));
+        assert!(errors.iter().any(|e| e.starts_with("/tags/1:")));
+        assert!(errors.iter().any(|e| e.contains("'x'")));
+    }
+
+    #[test]
+    fn follows_local_references_and_combinators() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "point": { "$ref": "#/$defs/Point" },
+                "label": { "anyOf": [{ "type": "string" }, { "type": "null" }] }
+            },
+            "$defs": {
+                "Point": {
+                    "type": "object",
+                    "properties": { "x": { "type": "integer" } },
+                    "required": ["x"]
+                }
+            }
+        });
+
+        assert!(validate_json_schema(&schema, &json!({"point": {"x": 1}, "label": null})).is_ok());
+        assert!(validate_json_schema(&schema, &json!({"point": {"x": 1.5}})).is_err());
+        assert!(validate_json_schema(&schema, &json!({"label": 3})).is_err());
+    }
+
+    #[test]
+    fn rejects_schemas_with_unsupported_keywords() {
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "Search",
+            "type": "object",
+            "properties": {
+                // A property may be named like a keyword
+                "pattern": { "type": "string", "format": "regex" },
+                "query": { "type": "string", "pattern": "^[a-z]+$" },
+                "limit": { "type": "integer", "exclusiveMinimum": 0 }
+            }
+        });
+
+        let mut errors = schema_errors(&schema, &json!({"query": "abc", "limit": 1}));
+        errors.sort();
+        assert_eq!(
+            errors,
+            [
+                "schema /properties/limit: unsupported keyword 'exclusiveMinimum'",
+                "schema /properties/query: unsupported keyword 'pattern'"
+            ]
+        );
+        assert!(validate_json_schema(&schema, &json!({})).is_err());
+    }
+
+    #[test]
+    fn enum_and_nullable_types() {
+        let schema = json!({ "type": ["string", "null"], "enum": ["a", "b", null] });
+        assert!(validate_json_schema(&schema, &json!("a")).is_ok());
+        assert!(validate_json_schema(&schema, &Value::Null).is_ok());
+        assert!(validate_json_schema(&schema, &json!("c")).is_err());
+        assert!(validate_json_schema(&schema, &json!(1)).is_err());
+    }
+}
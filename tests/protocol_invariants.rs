@@ -96,13 +96,16 @@ prop_compose! {
             name,
             description: if has_desc { Some(description) } else { None },
             input_schema: schema,
+            category: None,
+            tags: Vec::new(),
+            output_schema: None,
         }
     }
 }
 
 prop_compose! {
     fn arb_content()(
-        choice in 0..3,
+        choice in 0..4,
         text in ".*",
         data in prop::collection::vec(0u8..255, 0..100),
         mime_type in prop::sample::select(vec![
@@ -119,6 +122,10 @@ prop_compose! {
                 data: String::from_utf8(data).unwrap_or_else(|_| "invalid_data".to_string()),
                 mime_type: mime_type.to_string(),
             },
+            3 => Content::Blob {
+                data: base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &data),
+                mime_type: mime_type.to_string(),
+            },
             _ => Content::Resource {
                 uri,
                 text: if text.is_empty() { None } else { Some(text) },
@@ -177,6 +184,7 @@ proptest! {
         match (&content, &parsed) {
             (Content::Text { .. }, Content::Text { .. }) => {},
             (Content::Image { .. }, Content::Image { .. }) => {},
+            (Content::Blob { .. }, Content::Blob { .. }) => {},
             (Content::Resource { .. }, Content::Resource { .. }) => {},
             _ => prop_assert!(false, "Content type mismatch after roundtrip"),
         }
@@ -204,7 +212,7 @@ proptest! {
         let back = error_code.as_i32();
 
         // Known error codes should roundtrip exactly
-        if matches!(code, -32700 | -32600 | -32601 | -32602 | -32603 | -32001 | -32002 | -32003 | -32004) {
+        if matches!(code, -32700 | -32600 | -32601 | -32602 | -32603 | -32001 | -32002 | -32003 | -32004 | -32005 | -32011) {
             prop_assert_eq!(code, back);
         } else if (-32099..=-32000).contains(&code) {
             // Server error codes map to InternalError (-32603)
@@ -230,14 +238,16 @@ proptest! {
             2 => ClientRequest::CallTool(CallToolParams {
                 name: tool_name,
                 arguments: args,
+                meta: None,
             }),
             3 => ClientRequest::ListPrompts(ListPromptsParams { cursor: cursor.clone() }),
             4 => ClientRequest::GetPrompt(GetPromptParams {
                 name: prompt_name,
                 arguments: Default::default(),
+                meta: None,
             }),
-            5 => ClientRequest::ListResources(ListResourcesParams { cursor }),
-            _ => ClientRequest::ReadResource(ReadResourceParams { uri: resource_uri }),
+            5 => ClientRequest::ListResources(ListResourcesParams { cursor, meta: None }),
+            _ => ClientRequest::ReadResource(ReadResourceParams::new(resource_uri)),
         };
 
         let json = serde_json::to_value(&request).unwrap();
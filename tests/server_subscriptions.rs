@@ -46,21 +46,19 @@ impl ResourceHandler for TestResourceHandler {
     ) -> pmcp::Result<ReadResourceResult> {
         let resources = self.resources.read().await;
 
-        resources.get(uri).map_or_else(
-            || {
-                Err(pmcp::Error::not_found(format!(
-                    "Resource {} not found",
-                    uri
-                )))
-            },
-            |content| {
-                Ok(ReadResourceResult {
-                    contents: vec![Content::Text {
-                        text: content.clone(),
-                    }],
-                })
-            },
-        )
+        match resources.get(uri) {
+            Some(content) => Ok(ReadResourceResult {
+                contents: vec![Content::Text {
+                    text: content.clone(),
+                }],
+                content_hash: None,
+                not_modified: false,
+            }),
+            None => Err(pmcp::Error::not_found(format!(
+                "Resource {} not found",
+                uri
+            ))),
+        }
     }
 
     async fn list(
@@ -11,17 +11,16 @@ mod session_validation_tests {
     type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
     use std::net::{Ipv4Addr, SocketAddr};
     use std::sync::Arc;
-    use tokio::sync::Mutex;
     use url::Url;
 
     async fn create_test_server() -> Result<(SocketAddr, tokio::task::JoinHandle<()>)> {
-        let server = Arc::new(Mutex::new(
+        let server = Arc::new(
             Server::builder()
                 .name("test-server")
                 .version("1.0.0")
                 .build()
                 .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?,
-        ));
+        );
         let addr = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0);
         let http_server = StreamableHttpServer::new(addr, server);
         http_server
@@ -44,6 +43,8 @@ mod session_validation_tests {
             auth_provider: None,
             session_id: None,
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client1 = StreamableHttpTransport::new(client_config);
@@ -83,6 +84,8 @@ mod session_validation_tests {
             auth_provider: None,
             session_id: Some(session_id),
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client2 = StreamableHttpTransport::new(client_config2);
@@ -109,6 +112,8 @@ mod session_validation_tests {
             auth_provider: None,
             session_id: Some("invalid-session-id".to_string()),
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client = StreamableHttpTransport::new(client_config);
@@ -140,6 +145,8 @@ mod session_validation_tests {
             auth_provider: None,
             session_id: None,
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client = StreamableHttpTransport::new(client_config);
@@ -171,6 +178,8 @@ mod session_validation_tests {
             auth_provider: None,
             session_id: None,
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client = StreamableHttpTransport::new(client_config);
@@ -278,6 +287,8 @@ mod session_validation_tests {
             auth_provider: None,
             session_id: None,
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client = StreamableHttpTransport::new(client_config);
@@ -357,6 +368,8 @@ mod session_validation_tests {
             auth_provider: None,
             session_id: None,
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client = StreamableHttpTransport::new(client_config);
@@ -466,6 +479,8 @@ mod session_validation_tests {
             auth_provider: None,
             session_id: None,
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut init_client = StreamableHttpTransport::new(init_config);
@@ -501,6 +516,8 @@ mod session_validation_tests {
             auth_provider: None,
             session_id: None, // No session ID
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client = StreamableHttpTransport::new(client_config);
@@ -524,6 +541,8 @@ mod session_validation_tests {
             auth_provider: None,
             session_id: Some("non-existent-session".to_string()),
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client2 = StreamableHttpTransport::new(client_config2);
@@ -554,6 +573,8 @@ mod session_validation_tests {
             auth_provider: None,
             session_id: None,
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client = StreamableHttpTransport::new(client_config);
@@ -622,6 +643,8 @@ mod session_validation_tests {
             auth_provider: None,
             session_id: Some(session_id),
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client2 = StreamableHttpTransport::new(client_config2);
@@ -19,7 +19,7 @@ use tokio::sync::Mutex;
 use url::Url;
 
 /// Create a test server with minimal capabilities
-async fn create_test_server() -> Arc<Mutex<Server>> {
+async fn create_test_server() -> Arc<Server> {
     let server = Server::builder()
         .name("test-server")
         .version("1.0.0")
@@ -27,7 +27,7 @@ async fn create_test_server() -> Arc<Mutex<Server>> {
         .build()
         .unwrap();
 
-    Arc::new(Mutex::new(server))
+    Arc::new(server)
 }
 
 #[tokio::test]
@@ -54,6 +54,8 @@ async fn test_streamable_http_stateless_mode() {
         auth_provider: None,
         session_id: None,
         enable_json_response: true,
+        accept: None,
+        sse_accept: None,
         on_resumption_token: None,
     };
 
@@ -130,6 +132,8 @@ async fn test_streamable_http_stateful_mode() {
         auth_provider: None,
         session_id: None,
         enable_json_response: false,
+        accept: None,
+        sse_accept: None,
         on_resumption_token: None,
     };
 
@@ -226,6 +230,8 @@ async fn test_transport_send_receive_multiple() {
         auth_provider: None,
         session_id: None,
         enable_json_response: true,
+        accept: None,
+        sse_accept: None,
         on_resumption_token: None,
     };
 
@@ -307,6 +313,8 @@ async fn test_transport_with_headers() {
         auth_provider: None,
         session_id: None,
         enable_json_response: true,
+        accept: None,
+        sse_accept: None,
         on_resumption_token: None,
     };
 
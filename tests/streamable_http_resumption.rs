@@ -0,0 +1,303 @@
+//! Session resumption of the streamable HTTP client transport against a mock server.
+
+#![cfg(feature = "streamable-http")]
+
+use pmcp::shared::streamable_http::{StreamableHttpTransport, StreamableHttpTransportConfig};
+use pmcp::shared::{Transport, TransportMessage};
+use pmcp::types::{ClientRequest, Notification, Request, RequestId};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use url::Url;
+
+/// A request read by the mock server.
+struct MockRequest {
+    method: String,
+    /// Headers by lowercase name
+    headers: HashMap<String, String>,
+}
+
+/// Accept a connection and read one HTTP request from it, body included.
+async fn next_request(listener: &TcpListener) -> (MockRequest, BufReader<TcpStream>) {
+    let (socket, _) = listener.accept().await.unwrap();
+    let mut reader = BufReader::new(socket);
+    let mut line = String::new();
+    reader.read_line(&mut line).await.unwrap();
+    let method = line.split_whitespace().next().unwrap().to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        line.clear();
+        reader.read_line(&mut line).await.unwrap();
+        let Some((name, value)) = line.trim_end().split_once(':') else {
+            break;
+        };
+        headers.insert(name.to_ascii_lowercase(), value.trim().to_string());
+    }
+    let length = headers
+        .get("content-length")
+        .map_or(0, |length| length.parse().unwrap());
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await.unwrap();
+
+    (MockRequest { method, headers }, reader)
+}
+
+/// Start an SSE response sent in chunks.
+async fn start_events(socket: &mut BufReader<TcpStream>) {
+    socket
+        .write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\
+              Transfer-Encoding: chunked\r\nConnection: close\r\n\r\n",
+        )
+        .await
+        .unwrap();
+}
+
+/// Send a progress notification as SSE event `id`.
+async fn send_event(socket: &mut BufReader<TcpStream>, id: u32) {
+    let event = format!(
+        "id: {id}\nretry: 10\ndata: {{\"jsonrpc\":\"2.0\",\"method\":\"notifications/progress\",\
+         \"params\":{{\"progressToken\":\"job\",\"progress\":{id}}}}}\n\n"
+    );
+    let chunk = format!("{:x}\r\n{}\r\n", event.len(), event);
+    socket.write_all(chunk.as_bytes()).await.unwrap();
+    socket.flush().await.unwrap();
+}
+
+/// Receive the next message and return its progress.
+async fn next_progress(transport: &mut StreamableHttpTransport) -> f64 {
+    let message = tokio::time::timeout(Duration::from_secs(5), transport.receive())
+        .await
+        .expect("no event received")
+        .unwrap();
+    match message {
+        TransportMessage::Notification(Notification::Progress(progress)) => progress.progress,
+        other => panic!("Expected a progress notification, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_interrupted_stream_is_resumed_with_session_and_last_event_id() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+        // The first request gets a session assigned
+        let (request, mut socket) = next_request(&listener).await;
+        assert_eq!(request.method, "POST");
+        assert!(!request.headers.contains_key("mcp-session-id"));
+        let body = r#"{"jsonrpc":"2.0","id":1,"result":{}}"#;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nMcp-Session-Id: session-1\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+        drop(socket);
+
+        // The event stream breaks after the first event
+        let (request, mut socket) = next_request(&listener).await;
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.headers["mcp-session-id"], "session-1");
+        assert!(!request.headers.contains_key("last-event-id"));
+        start_events(&mut socket).await;
+        send_event(&mut socket, 1).await;
+        drop(socket);
+
+        // The resumed stream replays what was sent meanwhile, then ends
+        let (request, mut socket) = next_request(&listener).await;
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.headers["mcp-session-id"], "session-1");
+        assert_eq!(request.headers["last-event-id"], "1");
+        start_events(&mut socket).await;
+        send_event(&mut socket, 2).await;
+        send_event(&mut socket, 3).await;
+        socket.write_all(b"0\r\n\r\n").await.unwrap();
+    });
+
+    let mut transport = StreamableHttpTransport::new(StreamableHttpTransportConfig {
+        url: Url::parse(&format!("http://{}", addr)).unwrap(),
+        extra_headers: vec![],
+        auth_provider: None,
+        session_id: None,
+        enable_json_response: false,
+        accept: None,
+        sse_accept: None,
+        on_resumption_token: None,
+    });
+    transport
+        .send(TransportMessage::Request {
+            id: RequestId::from(1i64),
+            request: Request::Client(Box::new(ClientRequest::Ping)),
+        })
+        .await
+        .unwrap();
+    assert!(matches!(
+        transport.receive().await.unwrap(),
+        TransportMessage::Response(_)
+    ));
+    assert_eq!(transport.session_id().as_deref(), Some("session-1"));
+
+    transport.start_sse(None).await.unwrap();
+    assert_eq!(next_progress(&mut transport).await, 1.0);
+    assert_eq!(next_progress(&mut transport).await, 2.0);
+    assert_eq!(next_progress(&mut transport).await, 3.0);
+    assert_eq!(transport.last_event_id().as_deref(), Some("3"));
+
+    server.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_interrupted_stream_without_session_is_not_resumed() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+        let (_, mut socket) = next_request(&listener).await;
+        start_events(&mut socket).await;
+        send_event(&mut socket, 1).await;
+        drop(socket);
+
+        // No session to resume, so no request follows
+        tokio::time::timeout(Duration::from_millis(200), listener.accept())
+            .await
+            .expect_err("stream resumed without a session");
+    });
+
+    let mut transport = StreamableHttpTransport::new(StreamableHttpTransportConfig {
+        url: Url::parse(&format!("http://{}", addr)).unwrap(),
+        extra_headers: vec![],
+        auth_provider: None,
+        session_id: None,
+        enable_json_response: false,
+        accept: None,
+        sse_accept: None,
+        on_resumption_token: None,
+    });
+    transport.start_sse(None).await.unwrap();
+    assert_eq!(next_progress(&mut transport).await, 1.0);
+
+    server.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_each_stream_resumes_after_its_own_last_event() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+        let (_, mut socket) = next_request(&listener).await;
+        let body = r#"{"jsonrpc":"2.0","id":1,"result":{}}"#;
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nMcp-Session-Id: session-1\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+        drop(socket);
+
+        // The GET stream stays open after its first event
+        let (request, mut get_stream) = next_request(&listener).await;
+        assert_eq!(request.method, "GET");
+        start_events(&mut get_stream).await;
+        send_event(&mut get_stream, 1).await;
+
+        // The stream answering a POST breaks after a later event
+        let (request, mut socket) = next_request(&listener).await;
+        assert_eq!(request.method, "POST");
+        start_events(&mut socket).await;
+        send_event(&mut socket, 2).await;
+        drop(socket);
+
+        let (request, mut socket) = next_request(&listener).await;
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.headers["last-event-id"], "2");
+        start_events(&mut socket).await;
+        send_event(&mut socket, 3).await;
+        socket.write_all(b"0\r\n\r\n").await.unwrap();
+
+        // The GET stream resumes after its own event, not the POST stream's
+        drop(get_stream);
+        let (request, mut socket) = next_request(&listener).await;
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.headers["last-event-id"], "1");
+        start_events(&mut socket).await;
+        send_event(&mut socket, 4).await;
+        socket.write_all(b"0\r\n\r\n").await.unwrap();
+    });
+
+    let mut transport = StreamableHttpTransport::new(StreamableHttpTransportConfig {
+        url: Url::parse(&format!("http://{}", addr)).unwrap(),
+        extra_headers: vec![],
+        auth_provider: None,
+        session_id: None,
+        enable_json_response: false,
+        accept: None,
+        sse_accept: None,
+        on_resumption_token: None,
+    });
+    transport
+        .send(TransportMessage::Request {
+            id: RequestId::from(1i64),
+            request: Request::Client(Box::new(ClientRequest::Ping)),
+        })
+        .await
+        .unwrap();
+    transport.receive().await.unwrap();
+
+    transport.start_sse(None).await.unwrap();
+    assert_eq!(next_progress(&mut transport).await, 1.0);
+    transport
+        .send(TransportMessage::Request {
+            id: RequestId::from(2i64),
+            request: Request::Client(Box::new(ClientRequest::Ping)),
+        })
+        .await
+        .unwrap();
+    assert_eq!(next_progress(&mut transport).await, 2.0);
+    assert_eq!(next_progress(&mut transport).await, 3.0);
+    assert_eq!(next_progress(&mut transport).await, 4.0);
+
+    server.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_close_ends_streams_answering_posts() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+        let (_, mut socket) = next_request(&listener).await;
+        start_events(&mut socket).await;
+        send_event(&mut socket, 1).await;
+
+        // The client hangs up once closed
+        let mut rest = Vec::new();
+        tokio::time::timeout(Duration::from_secs(5), socket.read_to_end(&mut rest))
+            .await
+            .expect("stream still open after close")
+            .unwrap();
+    });
+
+    let mut transport = StreamableHttpTransport::new(StreamableHttpTransportConfig {
+        url: Url::parse(&format!("http://{}", addr)).unwrap(),
+        extra_headers: vec![],
+        auth_provider: None,
+        session_id: None,
+        enable_json_response: false,
+        accept: None,
+        sse_accept: None,
+        on_resumption_token: None,
+    });
+    transport
+        .send(TransportMessage::Request {
+            id: RequestId::from(1i64),
+            request: Request::Client(Box::new(ClientRequest::Ping)),
+        })
+        .await
+        .unwrap();
+    assert_eq!(next_progress(&mut transport).await, 1.0);
+    transport.close().await.unwrap();
+
+    server.await.unwrap();
+}
@@ -11,19 +11,18 @@ mod streamable_http_server_tests {
     type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
     use std::net::{Ipv4Addr, SocketAddr};
     use std::sync::Arc;
-    use tokio::sync::Mutex;
     use url::Url;
 
     #[tokio::test]
     async fn test_initialization_generates_session_id() -> Result<()> {
         // Setup server with stateful mode (default)
-        let server = Arc::new(Mutex::new(
+        let server = Arc::new(
             Server::builder()
                 .name("test-server")
                 .version("1.0.0")
                 .build()
                 .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?,
-        ));
+        );
         let addr = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0);
         let http_server = StreamableHttpServer::new(addr, server);
         let (server_addr, server_task) = http_server
@@ -41,6 +40,8 @@ mod streamable_http_server_tests {
             auth_provider: None,
             session_id: None,
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client = StreamableHttpTransport::new(client_config);
@@ -80,13 +81,13 @@ mod streamable_http_server_tests {
     #[tokio::test]
     async fn test_non_init_requires_session_in_stateful_mode() -> Result<()> {
         // Setup server with stateful mode
-        let server = Arc::new(Mutex::new(
+        let server = Arc::new(
             Server::builder()
                 .name("test-server")
                 .version("1.0.0")
                 .build()
                 .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?,
-        ));
+        );
         let addr = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0);
         let http_server = StreamableHttpServer::new(addr, server);
         let (server_addr, server_task) = http_server
@@ -104,6 +105,8 @@ mod streamable_http_server_tests {
             auth_provider: None,
             session_id: None, // No session ID
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client = StreamableHttpTransport::new(client_config);
@@ -127,13 +130,13 @@ mod streamable_http_server_tests {
     #[tokio::test]
     async fn test_stateless_mode_no_session_required() -> Result<()> {
         // Setup server in stateless mode
-        let server = Arc::new(Mutex::new(
+        let server = Arc::new(
             Server::builder()
                 .name("test-server")
                 .version("1.0.0")
                 .build()
                 .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?,
-        ));
+        );
         let addr = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0);
 
         let config = StreamableHttpServerConfig {
@@ -160,6 +163,8 @@ mod streamable_http_server_tests {
             auth_provider: None,
             session_id: None,
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client = StreamableHttpTransport::new(client_config);
@@ -214,13 +219,13 @@ mod streamable_http_server_tests {
     #[tokio::test]
     async fn test_protocol_version_header_included() -> Result<()> {
         // Setup server
-        let server = Arc::new(Mutex::new(
+        let server = Arc::new(
             Server::builder()
                 .name("test-server")
                 .version("1.0.0")
                 .build()
                 .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?,
-        ));
+        );
         let addr = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0);
         let http_server = StreamableHttpServer::new(addr, server);
         let (server_addr, server_task) = http_server
@@ -238,6 +243,8 @@ mod streamable_http_server_tests {
             auth_provider: None,
             session_id: None,
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client = StreamableHttpTransport::new(client_config);
@@ -273,4 +280,185 @@ mod streamable_http_server_tests {
         server_task.abort();
         Ok(())
     }
+
+    /// State of a server built with `ServerBuilder::state`
+    struct Inventory {
+        items: Vec<String>,
+    }
+
+    #[tokio::test]
+    async fn test_server_with_state_is_served() -> Result<()> {
+        let server = Server::builder()
+            .name("inventory-server")
+            .version("1.0.0")
+            .state(Inventory {
+                items: vec!["widget".to_string()],
+            })
+            .build()
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+        assert_eq!(server.state().items, ["widget"]);
+        let addr = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0);
+        let http_server = StreamableHttpServer::new(addr, Arc::new(server));
+        let (server_addr, server_task) = http_server
+            .start()
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+
+        let mut client = StreamableHttpTransport::new(StreamableHttpTransportConfig {
+            url: Url::parse(&format!("http://{}", server_addr)).map_err(|e| {
+                Box::new(pmcp::Error::Internal(e.to_string()))
+                    as Box<dyn std::error::Error + Send + Sync>
+            })?,
+            extra_headers: vec![],
+            auth_provider: None,
+            session_id: None,
+            enable_json_response: false,
+            accept: None,
+            sse_accept: None,
+            on_resumption_token: None,
+        });
+        client
+            .send(TransportMessage::Request {
+                id: 1i64.into(),
+                request: Request::Client(Box::new(ClientRequest::Initialize(InitializeParams {
+                    protocol_version: pmcp::LATEST_PROTOCOL_VERSION.to_string(),
+                    capabilities: ClientCapabilities::default(),
+                    client_info: Implementation {
+                        name: "test-client".to_string(),
+                        version: "1.0.0".to_string(),
+                    },
+                }))),
+            })
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+        let response = client
+            .receive()
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+        assert!(
+            matches!(response, TransportMessage::Response(ref r) if r.is_success()),
+            "{:?}",
+            response
+        );
+
+        server_task.abort();
+        Ok(())
+    }
+
+    /// Tool that waits until it is cancelled and reports the reason it was given
+    struct CancelAwareTool {
+        started: Arc<tokio::sync::Notify>,
+    }
+
+    #[async_trait::async_trait]
+    impl pmcp::ToolHandler for CancelAwareTool {
+        async fn handle(
+            &self,
+            _args: serde_json::Value,
+            extra: pmcp::RequestHandlerExtra,
+        ) -> pmcp::Result<serde_json::Value> {
+            self.started.notify_one();
+            extra.cancelled().await;
+            Ok(serde_json::json!({ "reason": extra.cancellation_reason() }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_notification_reaches_running_tool() -> Result<()> {
+        let started = Arc::new(tokio::sync::Notify::new());
+        let server = Server::builder()
+            .name("test-server")
+            .version("1.0.0")
+            .tool(
+                "wait",
+                CancelAwareTool {
+                    started: started.clone(),
+                },
+            )
+            .build()
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+        let addr = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0);
+        let http_server = StreamableHttpServer::new(addr, Arc::new(server));
+        let (server_addr, server_task) = http_server
+            .start()
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+        let url = Url::parse(&format!("http://{}", server_addr)).map_err(|e| {
+            Box::new(pmcp::Error::Internal(e.to_string()))
+                as Box<dyn std::error::Error + Send + Sync>
+        })?;
+        let config = |session_id: Option<String>| StreamableHttpTransportConfig {
+            url: url.clone(),
+            extra_headers: vec![],
+            auth_provider: None,
+            session_id,
+            enable_json_response: false,
+            accept: None,
+            sse_accept: None,
+            on_resumption_token: None,
+        };
+
+        let mut client = StreamableHttpTransport::new(config(None));
+        client
+            .send(TransportMessage::Request {
+                id: 1i64.into(),
+                request: Request::Client(Box::new(ClientRequest::Initialize(InitializeParams {
+                    protocol_version: pmcp::LATEST_PROTOCOL_VERSION.to_string(),
+                    capabilities: ClientCapabilities::default(),
+                    client_info: Implementation {
+                        name: "test-client".to_string(),
+                        version: "1.0.0".to_string(),
+                    },
+                }))),
+            })
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+        client
+            .receive()
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+        let session_id = client.session_id();
+
+        // The call is answered once the second connection of the session cancels it
+        let call = tokio::spawn(async move {
+            client
+                .send(TransportMessage::Request {
+                    id: 2i64.into(),
+                    request: Request::Client(Box::new(ClientRequest::CallTool(
+                        pmcp::types::CallToolParams::new("wait", serde_json::json!({})),
+                    ))),
+                })
+                .await?;
+            client.receive().await
+        });
+        started.notified().await;
+        let mut canceller = StreamableHttpTransport::new(config(session_id));
+        canceller
+            .send(TransportMessage::Notification(
+                pmcp::types::Notification::Client(pmcp::types::ClientNotification::Cancelled(
+                    pmcp::types::CancelledParams {
+                        request_id: 2i64.into(),
+                        reason: Some("user pressed stop".to_string()),
+                    },
+                )),
+            ))
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+
+        let response = tokio::time::timeout(std::time::Duration::from_secs(5), call)
+            .await
+            .expect("cancelled call should be answered")?
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+        let TransportMessage::Response(response) = response else {
+            panic!("Expected a response, got {:?}", response);
+        };
+        assert!(
+            serde_json::to_string(&response)?.contains("user pressed stop"),
+            "{:?}",
+            response
+        );
+
+        server_task.abort();
+        Ok(())
+    }
 }
@@ -10,7 +10,6 @@ mod spec_compliance_tests {
     };
     use std::net::{Ipv4Addr, SocketAddr};
     use std::sync::Arc;
-    use tokio::sync::Mutex;
     use url::Url;
 
     // Use boxed error for tests to satisfy clippy's large_enum_variant warning
@@ -26,13 +25,13 @@ mod spec_compliance_tests {
     #[tokio::test]
     async fn test_baseline_accept_header_validation() -> Result<()> {
         // Test in stateful mode (but applies to both)
-        let server = Arc::new(Mutex::new(
+        let server = Arc::new(
             Server::builder()
                 .name("test-server")
                 .version("1.0.0")
                 .build()
                 .map_err(box_err)?,
-        ));
+        );
         let addr = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0);
         let http_server = StreamableHttpServer::new(addr, server);
         let (server_addr, server_task) = http_server.start().await.map_err(box_err)?;
@@ -83,13 +82,13 @@ mod spec_compliance_tests {
 
     #[tokio::test]
     async fn test_baseline_content_type_validation() -> Result<()> {
-        let server = Arc::new(Mutex::new(
+        let server = Arc::new(
             Server::builder()
                 .name("test-server")
                 .version("1.0.0")
                 .build()
                 .map_err(box_err)?,
-        ));
+        );
         let addr = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0);
         let http_server = StreamableHttpServer::new(addr, server);
         let (server_addr, server_task) = http_server.start().await.map_err(box_err)?;
@@ -141,13 +140,13 @@ mod spec_compliance_tests {
     #[tokio::test]
     async fn test_baseline_protocol_version_required_non_init() -> Result<()> {
         // Test that non-init requests MUST include protocol version header
-        let server = Arc::new(Mutex::new(
+        let server = Arc::new(
             Server::builder()
                 .name("test-server")
                 .version("1.0.0")
                 .build()
                 .map_err(box_err)?,
-        ));
+        );
         let addr = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0);
         let http_server = StreamableHttpServer::new(addr, server);
         let (server_addr, server_task) = http_server.start().await.map_err(box_err)?;
@@ -161,6 +160,8 @@ mod spec_compliance_tests {
             auth_provider: None,
             session_id: None,
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client = StreamableHttpTransport::new(client_config);
@@ -221,13 +222,13 @@ mod spec_compliance_tests {
 
     #[tokio::test]
     async fn test_baseline_protocol_version_requirement() -> Result<()> {
-        let server = Arc::new(Mutex::new(
+        let server = Arc::new(
             Server::builder()
                 .name("test-server")
                 .version("1.0.0")
                 .build()
                 .map_err(box_err)?,
-        ));
+        );
         let addr = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0);
         let http_server = StreamableHttpServer::new(addr, server);
         let (server_addr, server_task) = http_server.start().await.map_err(box_err)?;
@@ -241,6 +242,8 @@ mod spec_compliance_tests {
             auth_provider: None,
             session_id: None,
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client = StreamableHttpTransport::new(client_config);
@@ -298,13 +301,13 @@ mod spec_compliance_tests {
 
     #[tokio::test]
     async fn test_baseline_notifications_only_returns_202() -> Result<()> {
-        let server = Arc::new(Mutex::new(
+        let server = Arc::new(
             Server::builder()
                 .name("test-server")
                 .version("1.0.0")
                 .build()
                 .map_err(box_err)?,
-        ));
+        );
         let addr = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0);
         let http_server = StreamableHttpServer::new(addr, server);
         let (server_addr, server_task) = http_server.start().await.map_err(box_err)?;
@@ -318,6 +321,8 @@ mod spec_compliance_tests {
             auth_provider: None,
             session_id: None,
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client = StreamableHttpTransport::new(client_config);
@@ -390,13 +395,13 @@ mod spec_compliance_tests {
 
     #[tokio::test]
     async fn test_stateful_initialize_creates_session() -> Result<()> {
-        let server = Arc::new(Mutex::new(
+        let server = Arc::new(
             Server::builder()
                 .name("test-server")
                 .version("1.0.0")
                 .build()
                 .map_err(box_err)?,
-        ));
+        );
         let addr = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0);
         // Explicitly use stateful mode (default)
         let http_server = StreamableHttpServer::new(addr, server);
@@ -410,6 +415,8 @@ mod spec_compliance_tests {
             auth_provider: None,
             session_id: None, // No session ID initially
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client = StreamableHttpTransport::new(client_config);
@@ -441,13 +448,13 @@ mod spec_compliance_tests {
 
     #[tokio::test]
     async fn test_stateful_concurrent_sse_conflict() -> Result<()> {
-        let server = Arc::new(Mutex::new(
+        let server = Arc::new(
             Server::builder()
                 .name("test-server")
                 .version("1.0.0")
                 .build()
                 .map_err(box_err)?,
-        ));
+        );
         let addr = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0);
         let http_server = StreamableHttpServer::new(addr, server);
         let (server_addr, server_task) = http_server.start().await.map_err(box_err)?;
@@ -461,6 +468,8 @@ mod spec_compliance_tests {
             auth_provider: None,
             session_id: None,
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client = StreamableHttpTransport::new(client_config);
@@ -517,13 +526,13 @@ mod spec_compliance_tests {
     // ==================== STATELESS MODE TESTS ====================
 
     async fn create_stateless_server() -> Result<(SocketAddr, tokio::task::JoinHandle<()>)> {
-        let server = Arc::new(Mutex::new(
+        let server = Arc::new(
             Server::builder()
                 .name("test-server")
                 .version("1.0.0")
                 .build()
                 .map_err(box_err)?,
-        ));
+        );
         let addr = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0);
         let config = StreamableHttpServerConfig {
             session_id_generator: None, // Stateless mode
@@ -548,6 +557,8 @@ mod spec_compliance_tests {
             auth_provider: None,
             session_id: None,
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client = StreamableHttpTransport::new(client_config);
@@ -589,6 +600,8 @@ mod spec_compliance_tests {
             auth_provider: None,
             session_id: None,
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client = StreamableHttpTransport::new(client_config);
@@ -643,6 +656,8 @@ mod spec_compliance_tests {
             auth_provider: None,
             session_id: None, // No session ID
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client = StreamableHttpTransport::new(client_config);
@@ -674,6 +689,8 @@ mod spec_compliance_tests {
             auth_provider: None,
             session_id: Some("arbitrary-session-id".to_string()), // Arbitrary session ID
             enable_json_response: false,
+            accept: None,
+            sse_accept: None,
             on_resumption_token: None,
         };
         let mut client = StreamableHttpTransport::new(client_config);
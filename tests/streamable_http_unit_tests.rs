@@ -50,6 +50,8 @@ fn test_streamable_http_config_creation() {
         })),
         session_id: Some("session-123".to_string()),
         enable_json_response: true,
+        accept: None,
+        sse_accept: None,
         on_resumption_token: Some(Arc::new(|token| {
             println!("Resumption token: {}", token);
         })),
@@ -71,6 +73,8 @@ fn test_streamable_http_transport_creation() {
         auth_provider: None,
         session_id: None,
         enable_json_response: false,
+        accept: None,
+        sse_accept: None,
         on_resumption_token: None,
     };
 
@@ -89,6 +93,8 @@ fn test_streamable_http_transport_session_management() {
         auth_provider: None,
         session_id: Some("initial-session".to_string()),
         enable_json_response: false,
+        accept: None,
+        sse_accept: None,
         on_resumption_token: None,
     };
 
@@ -112,6 +118,8 @@ fn test_streamable_http_transport_protocol_version() {
         auth_provider: None,
         session_id: None,
         enable_json_response: false,
+        accept: None,
+        sse_accept: None,
         on_resumption_token: None,
     };
 
@@ -131,6 +139,8 @@ fn test_streamable_http_config_debug() {
         auth_provider: None,
         session_id: None,
         enable_json_response: false,
+        accept: None,
+        sse_accept: None,
         on_resumption_token: None,
     };
 
@@ -148,6 +158,8 @@ fn test_streamable_http_transport_debug() {
         auth_provider: None,
         session_id: None,
         enable_json_response: false,
+        accept: None,
+        sse_accept: None,
         on_resumption_token: None,
     };
 
@@ -165,6 +177,8 @@ fn test_streamable_http_config_clone() {
         auth_provider: None,
         session_id: Some("session-123".to_string()),
         enable_json_response: true,
+        accept: None,
+        sse_accept: None,
         on_resumption_token: None,
     };
 
@@ -183,6 +197,8 @@ fn test_streamable_http_transport_clone() {
         auth_provider: None,
         session_id: Some("session-123".to_string()),
         enable_json_response: false,
+        accept: None,
+        sse_accept: None,
         on_resumption_token: None,
     };
 
@@ -199,6 +215,8 @@ async fn test_streamable_http_transport_close() {
         auth_provider: None,
         session_id: None,
         enable_json_response: false,
+        accept: None,
+        sse_accept: None,
         on_resumption_token: None,
     };
 
@@ -219,6 +237,8 @@ async fn test_streamable_http_send_with_auth_provider() {
         })),
         session_id: None,
         enable_json_response: false,
+        accept: None,
+        sse_accept: None,
         on_resumption_token: None,
     };
 
@@ -36,6 +36,8 @@ impl ResourceHandler for TestResourceHandler {
             contents: vec![Content::Text {
                 text: format!("Content of {}", uri),
             }],
+            content_hash: None,
+            not_modified: false,
         })
     }
 
@@ -192,6 +194,26 @@ async fn test_batch_error_handling() {
             .unwrap(),
     );
 
+    // Initialize the server first
+    let init_request = JSONRPCRequest {
+        jsonrpc: "2.0".to_string(),
+        method: "initialize".to_string(),
+        params: Some(json!({
+            "protocolVersion": "2024-11-05",
+            "capabilities": {},
+            "clientInfo": {
+                "name": "test-client",
+                "version": "1.0.0"
+            }
+        })),
+        id: RequestId::from("init"),
+    };
+
+    let _init_response = server
+        .handle_batch_request(BatchRequest::Single(init_request))
+        .await
+        .unwrap();
+
     let batch = BatchRequest::Batch(vec![
         // Valid request
         JSONRPCRequest {
@@ -246,7 +268,7 @@ async fn test_batch_error_handling() {
     assert_eq!(responses[2].id, RequestId::from(3i64));
     match &responses[2].payload {
         pmcp::types::jsonrpc::ResponsePayload::Error(error) => {
-            assert_eq!(error.code, -32603); // Internal error (not found)
+            assert_eq!(error.code, -32005); // Not found
         },
         pmcp::types::jsonrpc::ResponsePayload::Result(_) => {
             panic!("Expected error for non-existent tool")
@@ -0,0 +1,71 @@
+//! Tests for binary resources read through a client.
+
+#![cfg(feature = "testing")]
+
+use async_trait::async_trait;
+use pmcp::testing::duplex;
+use pmcp::types::{Content, ListResourcesResult, ReadResourceResult, ServerCapabilities};
+use pmcp::{Client, ClientCapabilities, RequestHandlerExtra, ResourceHandler, Server};
+
+/// Bytes that are not valid UTF-8, and every byte value besides.
+fn binary_data() -> Vec<u8> {
+    let mut bytes = vec![0xFF, 0xFE, 0x00, 0xC3, 0x28];
+    bytes.extend(0..=255u8);
+    bytes
+}
+
+struct BinaryResources;
+
+#[async_trait]
+impl ResourceHandler for BinaryResources {
+    async fn read(
+        &self,
+        _uri: &str,
+        _extra: RequestHandlerExtra,
+    ) -> pmcp::Result<ReadResourceResult> {
+        Ok(ReadResourceResult::new(vec![Content::blob(
+            binary_data(),
+            "application/octet-stream",
+        )]))
+    }
+
+    async fn list(
+        &self,
+        _cursor: Option<String>,
+        _extra: RequestHandlerExtra,
+    ) -> pmcp::Result<ListResourcesResult> {
+        Ok(ListResourcesResult {
+            resources: vec![],
+            next_cursor: None,
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_blob_resource_round_trips() {
+    let server = Server::builder()
+        .name("blob-server")
+        .version("1.0.0")
+        .capabilities(ServerCapabilities::resources_only())
+        .resources(BinaryResources)
+        .build()
+        .unwrap();
+    let (client_transport, server_transport) = duplex();
+    tokio::spawn(server.run(server_transport));
+
+    let mut client = Client::new(client_transport);
+    client
+        .initialize(ClientCapabilities::default())
+        .await
+        .unwrap();
+
+    let result = client
+        .read_resource("file:///data.bin".to_string())
+        .await
+        .unwrap();
+    assert!(matches!(
+        &result.contents[..],
+        [Content::Blob { mime_type, .. }] if mime_type == "application/octet-stream"
+    ));
+    assert_eq!(result.blob().unwrap(), Some(binary_data()));
+}
@@ -0,0 +1,169 @@
+//! End-to-end tests for elicitation between a server and a client.
+
+#![cfg(feature = "testing")]
+
+use pmcp::server::elicitation::ElicitationManager;
+use pmcp::shared::{Transport, TransportMessage};
+use pmcp::testing::{duplex, ServerTransport};
+use pmcp::types::elicitation::{elicit_text, ElicitInputResponse};
+use pmcp::types::jsonrpc::ResponsePayload;
+use pmcp::types::{ClientRequest, JSONRPCResponse, Request, RequestId};
+use pmcp::{Client, ClientCapabilities};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Answer the next request read from `transport` with `result`.
+async fn answer(transport: &mut ServerTransport, result: Value) -> Request {
+    let TransportMessage::Request { id, request } = transport.receive().await.unwrap() else {
+        panic!("expected a request");
+    };
+    transport
+        .send(TransportMessage::Response(JSONRPCResponse {
+            jsonrpc: "2.0".to_string(),
+            id,
+            payload: ResponsePayload::Result(result),
+        }))
+        .await
+        .unwrap();
+    request
+}
+
+async fn connect() -> (Client<pmcp::testing::ClientTransport>, ServerTransport) {
+    let (client_transport, mut server_transport) = duplex();
+    let mut client = Client::new(client_transport);
+    let server = tokio::spawn(async move {
+        answer(
+            &mut server_transport,
+            json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": {},
+                "serverInfo": {"name": "elicit-server", "version": "1.0.0"}
+            }),
+        )
+        .await;
+        server_transport
+    });
+    client
+        .initialize(ClientCapabilities::default())
+        .await
+        .unwrap();
+    let mut server_transport = server.await.unwrap();
+    assert!(matches!(
+        server_transport.receive().await.unwrap(),
+        TransportMessage::Notification(_)
+    ));
+    (client, server_transport)
+}
+
+#[tokio::test]
+async fn test_client_answers_server_elicitation() {
+    let (client, mut server_transport) = connect().await;
+    let mut elicitations = client.elicitation_requests();
+
+    let (request_tx, mut request_rx) = mpsc::channel(1);
+    let mut manager = ElicitationManager::new();
+    manager.set_request_channel(request_tx);
+    let manager = Arc::new(manager);
+    let elicited = tokio::spawn({
+        let manager = manager.clone();
+        async move {
+            manager
+                .elicit_input(elicit_text("Your name?").id("name").build())
+                .await
+        }
+    });
+
+    // The server forwards the elicitation to the client
+    let request = request_rx.recv().await.unwrap();
+    server_transport
+        .send(TransportMessage::Request {
+            id: RequestId::from(100i64),
+            request: Request::Server(Box::new(request)),
+        })
+        .await
+        .unwrap();
+
+    let request = elicitations.recv().await.unwrap();
+    assert_eq!(request.prompt, "Your name?");
+    let responder = tokio::spawn(async move {
+        client
+            .respond_elicitation(
+                &request.elicitation_id,
+                ElicitInputResponse {
+                    elicitation_id: String::new(),
+                    value: Some(json!("alice")),
+                    cancelled: false,
+                    error: None,
+                },
+            )
+            .await
+    });
+
+    // The server hands the client's answer to the manager
+    let Request::Client(request) = answer(&mut server_transport, json!({})).await else {
+        panic!("expected a client request");
+    };
+    let ClientRequest::ElicitInputResponse(response) = *request else {
+        panic!("expected an elicitation response");
+    };
+    manager.handle_response(response).await.unwrap();
+
+    responder.await.unwrap().unwrap();
+    let response = elicited.await.unwrap().unwrap();
+    assert_eq!(response.elicitation_id, "name");
+    assert_eq!(response.value, Some(json!("alice")));
+    assert!(!response.cancelled);
+}
+
+#[tokio::test]
+async fn test_cancelled_elicitation_cannot_be_answered() {
+    let (client, mut server_transport) = connect().await;
+    let mut elicitations = client.elicitation_requests();
+
+    server_transport
+        .send(TransportMessage::Request {
+            id: RequestId::from(100i64),
+            request: Request::Server(Box::new(pmcp::types::ServerRequest::ElicitInput(Box::new(
+                elicit_text("Your name?").id("name").build(),
+            )))),
+        })
+        .await
+        .unwrap();
+    assert_eq!(elicitations.recv().await.unwrap().elicitation_id, "name");
+
+    // The server gives up on the elicitation
+    let cancelled = pmcp::shared::protocol_helpers::parse_notification(json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/cancelled",
+        "params": {"requestId": 100, "reason": "timed out"}
+    }))
+    .unwrap();
+    server_transport
+        .send(TransportMessage::Notification(cancelled))
+        .await
+        .unwrap();
+    // A ping behind the notification shows it was read
+    let ping = tokio::spawn({
+        let client = client.clone();
+        async move { client.ping().await }
+    });
+    answer(&mut server_transport, json!({})).await;
+    ping.await.unwrap().unwrap();
+
+    let response = ElicitInputResponse {
+        elicitation_id: "name".to_string(),
+        value: Some(json!("alice")),
+        cancelled: false,
+        error: None,
+    };
+    let err = client
+        .respond_elicitation("name", response)
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("No pending elicitation"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,40 @@
+//! Tests for server instructions received by a client.
+
+#![cfg(feature = "testing")]
+
+use pmcp::testing::{duplex, ClientTransport};
+use pmcp::{Client, ClientCapabilities, Server, ServerBuilder};
+
+/// Run the server of `builder` and initialize a client connected to it.
+async fn initialized_client(builder: ServerBuilder) -> Client<ClientTransport> {
+    let server = builder.name("guide").version("1.0.0").build().unwrap();
+    let (client_transport, server_transport) = duplex();
+    tokio::spawn(server.run(server_transport));
+
+    let mut client = Client::new(client_transport);
+    client
+        .initialize(ClientCapabilities::default())
+        .await
+        .unwrap();
+    client
+}
+
+#[tokio::test]
+async fn test_client_receives_server_instructions() {
+    let client = initialized_client(
+        Server::builder().instructions("Call `search` before `fetch`, never the other way."),
+    )
+    .await;
+
+    assert_eq!(
+        client.get_instructions(),
+        Some("Call `search` before `fetch`, never the other way.")
+    );
+}
+
+#[tokio::test]
+async fn test_server_without_instructions_sends_none() {
+    let client = initialized_client(Server::builder()).await;
+
+    assert_eq!(client.get_instructions(), None);
+}
@@ -0,0 +1,87 @@
+//! Tests for typed tool calls between a real client and server.
+
+#![cfg(feature = "testing")]
+
+use async_trait::async_trait;
+use pmcp::testing::duplex;
+use pmcp::types::ServerCapabilities;
+use pmcp::{Client, ClientCapabilities, RequestHandlerExtra, Server, ToolHandler};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Serialize)]
+struct GreetArgs {
+    name: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Greeting {
+    greeting: String,
+    length: usize,
+}
+
+/// Greets by name, as a plain string or as an object
+struct GreetTool {
+    structured: bool,
+}
+
+#[async_trait]
+impl ToolHandler for GreetTool {
+    async fn handle(&self, args: Value, _extra: RequestHandlerExtra) -> pmcp::Result<Value> {
+        let greeting = format!("Hello, {}!", args["name"].as_str().unwrap_or_default());
+        if self.structured {
+            Ok(json!({"greeting": greeting, "length": greeting.len()}))
+        } else {
+            Ok(Value::String(greeting))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_typed_tool_results_round_trip() {
+    let server = Server::builder()
+        .name("typed-server")
+        .version("1.0.0")
+        .capabilities(ServerCapabilities::tools_only())
+        .tool("greet", GreetTool { structured: false })
+        .tool("greet_structured", GreetTool { structured: true })
+        .build()
+        .unwrap();
+    let (client_transport, server_transport) = duplex();
+    tokio::spawn(server.run(server_transport));
+
+    let mut client = Client::new(client_transport);
+    client
+        .initialize(ClientCapabilities::default())
+        .await
+        .unwrap();
+
+    // Strings are encoded like every other result, so they decode as strings
+    let greeting: String = client
+        .call_tool_typed(
+            "greet",
+            GreetArgs {
+                name: "\"quoted\" world".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(greeting, "Hello, \"quoted\" world!");
+
+    let greeting: Greeting = client
+        .call_tool_typed(
+            "greet_structured",
+            GreetArgs {
+                name: "world".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        greeting,
+        Greeting {
+            greeting: "Hello, world!".to_string(),
+            length: 13,
+        }
+    );
+}
@@ -10,20 +10,19 @@ type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>
 use std::net::{Ipv4Addr, SocketAddr};
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::Mutex;
 use tokio::time::timeout;
 use url::Url;
 
 #[tokio::test]
 async fn test_streamable_http_transport_send_receive() -> Result<()> {
     // Setup the server
-    let server = Arc::new(Mutex::new(
+    let server = Arc::new(
         Server::builder()
             .name("test-server")
             .version("1.0.0")
             .build()
             .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?,
-    ));
+    );
     let addr = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), 0);
     let http_server = StreamableHttpServer::new(addr, server);
     let (server_addr, server_task) = http_server
@@ -41,6 +40,8 @@ async fn test_streamable_http_transport_send_receive() -> Result<()> {
         auth_provider: None,
         session_id: None,
         enable_json_response: false,
+        accept: None,
+        sse_accept: None,
         on_resumption_token: None,
     };
     let mut client_transport = StreamableHttpTransport::new(client_config);
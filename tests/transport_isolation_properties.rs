@@ -1,6 +1,9 @@
 //! Property tests for transport isolation functionality.
 
-use pmcp::shared::protocol::{Protocol, ProtocolOptions, TransportId};
+use pmcp::shared::protocol::{
+    FieldCase, Protocol, ProtocolOptions, TransportId, Utf8Mode, DEFAULT_MAX_JSON_DEPTH,
+    DEFAULT_MAX_PENDING,
+};
 use pmcp::types::{JSONRPCResponse, RequestId};
 use proptest::prelude::*;
 use std::collections::HashSet;
@@ -217,6 +220,12 @@ proptest! {
         let options = ProtocolOptions {
             enforce_strict_capabilities: enforce_strict,
             debounced_notification_methods: methods.clone(),
+            arbitrary_precision: false,
+            field_case: FieldCase::Strict,
+            utf8: Utf8Mode::Strict,
+            max_pending: DEFAULT_MAX_PENDING,
+            max_json_depth: DEFAULT_MAX_JSON_DEPTH,
+            pending_timeout: None,
         };
 
         let protocol = Protocol::new(options.clone());
@@ -52,6 +52,8 @@ impl ResourceHandler for TestResourceHandler {
                 contents: vec![pmcp::types::Content::Text {
                     text: "Hello from Rust server!".to_string(),
                 }],
+                content_hash: None,
+                not_modified: false,
             })
         } else {
             Err(Error::resource_not_found(uri))
@@ -92,9 +94,9 @@ impl PromptHandler for TestPromptHandler {
             description: Some(format!("Greeting for {}", name)),
             messages: vec![pmcp::types::PromptMessage {
                 role: pmcp::types::Role::User,
-                content: pmcp::types::Content::Text {
+                content: vec![pmcp::types::Content::Text {
                     text: format!("Please greet {}", name),
-                },
+                }],
             }],
         })
     }